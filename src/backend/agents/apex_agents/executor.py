@@ -65,7 +65,9 @@ class QueuedTask:
     retry_count: int = 0
     trace_id: str | None = None
     span_id: str | None = None
+    traceparent: str | None = None
     agent_config: AgentConfig | None = None
+    context_truncation: dict[str, Any] | None = None
 
     @classmethod
     def from_json(cls, data: dict[str, Any]) -> "QueuedTask":
@@ -74,18 +76,28 @@ class QueuedTask:
         if "agent_config" in data and data["agent_config"]:
             agent_config = AgentConfig(**data["agent_config"])
 
+        # The orchestrator sends the task span's context under trace_context
+        trace_context = data.get("trace_context") or {}
+
+        # A truncated string context arrives as a plain string
+        context = data.get("context") or {}
+        if not isinstance(context, dict):
+            context = {"text": context}
+
         return cls(
             id=data["id"],
             name=data["name"],
             instruction=data.get("instruction", ""),
-            context=data.get("context", {}),
+            context=context,
             parameters=data.get("parameters", {}),
             priority=data.get("priority", 0),
             max_retries=data.get("max_retries", 3),
             retry_count=data.get("retry_count", 0),
-            trace_id=data.get("trace_id"),
-            span_id=data.get("span_id"),
+            trace_id=trace_context.get("trace_id", data.get("trace_id")),
+            span_id=trace_context.get("span_id", data.get("span_id")),
+            traceparent=trace_context.get("traceparent", data.get("traceparent")),
             agent_config=agent_config,
+            context_truncation=data.get("context_truncation"),
         )
 
 
@@ -371,6 +383,8 @@ class TaskQueue:
             "retry_count": task.retry_count,
             "trace_id": task.trace_id,
             "span_id": task.span_id,
+            "traceparent": task.traceparent,
+            "context_truncation": task.context_truncation,
         }
 
         try:
@@ -587,6 +601,13 @@ class AgentExecutor:
             agent=agent.config.name,
         )
 
+        if task.context_truncation is not None:
+            self._logger.warning(
+                "Task context was truncated by the orchestrator",
+                task_id=task.id,
+                **task.context_truncation,
+            )
+
         # Report task started
         if self._backend_client:
             await self._backend_client.report_task_started(task.id, str(agent.id))
@@ -598,6 +619,7 @@ class AgentExecutor:
             agent_name=agent.config.name,
             trace_id=task.trace_id,
             span_id=task.span_id,
+            traceparent=task.traceparent,
         ) as span_ctx:
             try:
                 # Build task input
@@ -624,8 +646,8 @@ class AgentExecutor:
                     duration_ms=duration_ms,
                 )
 
-                # Get trace context for result
-                trace_ctx = span_ctx.get_trace_context()
+                # Link the result back to the worker's span
+                trace_id, span_id = span_ctx.span_ids()
 
                 result = TaskResult(
                     task_id=task.id,
@@ -635,9 +657,8 @@ class AgentExecutor:
                     tokens_used=agent.metrics.tokens_used,
                     cost_dollars=agent.metrics.cost_dollars,
                     duration_ms=duration_ms,
-                    trace_id=trace_ctx.get("traceparent", "").split("-")[1]
-                    if trace_ctx.get("traceparent")
-                    else None,
+                    trace_id=trace_id,
+                    span_id=span_id,
                 )
 
                 self._logger.info(
@@ -660,6 +681,7 @@ class AgentExecutor:
                     task,
                     f"Task timed out after {self.settings.worker.max_task_duration_seconds} seconds",
                     start_time,
+                    span_ctx,
                 )
 
             except Exception as e:
@@ -668,13 +690,14 @@ class AgentExecutor:
                     task_id=task.id,
                     error=str(e),
                 )
-                return await self._handle_task_failure(task, str(e), start_time)
+                return await self._handle_task_failure(task, str(e), start_time, span_ctx)
 
     async def _handle_task_failure(
         self,
         task: QueuedTask,
         error: str,
         start_time: datetime,
+        span_ctx: TaskSpanContext | None = None,
     ) -> TaskResult:
         """
         Handle task failure with retry logic.
@@ -683,6 +706,7 @@ class AgentExecutor:
             task: The failed task.
             error: Error message.
             start_time: When execution started.
+            span_ctx: The task's span, to link the result back to.
 
         Returns:
             TaskResult with failure status.
@@ -701,11 +725,14 @@ class AgentExecutor:
             if self._task_queue:
                 await self._task_queue.requeue_task(task)
 
+        trace_id, span_id = span_ctx.span_ids() if span_ctx else (None, None)
         return TaskResult(
             task_id=task.id,
             status=TaskStatus.FAILED,
             error=error,
             duration_ms=duration_ms,
+            trace_id=trace_id,
+            span_id=span_id,
         )
 
     def _get_agent_for_task(self, task: QueuedTask) -> Agent:
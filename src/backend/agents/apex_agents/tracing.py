@@ -31,6 +31,8 @@ from opentelemetry.trace import (
     Status,
     StatusCode,
     Tracer,
+    format_span_id,
+    format_trace_id,
     get_current_span,
     set_span_in_context,
 )
@@ -456,6 +458,7 @@ class TaskSpanContext:
         agent_name: str,
         trace_id: str | None = None,
         span_id: str | None = None,
+        traceparent: str | None = None,
     ):
         """
         Initialize task span context.
@@ -466,19 +469,24 @@ class TaskSpanContext:
             agent_name: Name of the executing agent.
             trace_id: Optional parent trace ID.
             span_id: Optional parent span ID.
+            traceparent: Optional W3C traceparent of the parent span; takes
+                precedence over trace_id and span_id.
         """
         self.task_id = task_id
         self.task_name = task_name
         self.agent_name = agent_name
         self.trace_id = trace_id
         self.span_id = span_id
+        self.traceparent = traceparent
         self._span: Span | None = None
         self._token: object | None = None
 
     def __enter__(self) -> "TaskSpanContext":
         """Start the task span."""
         # Set parent context if available
-        if self.trace_id:
+        if self.traceparent:
+            self._token = attach(extract_context({"traceparent": self.traceparent}))
+        elif self.trace_id:
             parent_context = set_trace_context(self.trace_id, self.span_id)
             if parent_context:
                 self._token = attach(parent_context)
@@ -541,3 +549,12 @@ class TaskSpanContext:
     def get_trace_context(self) -> dict[str, str]:
         """Get the current trace context for propagation."""
         return inject_context({})
+
+    def span_ids(self) -> tuple[str | None, str | None]:
+        """Get the trace and span IDs of the task span, as hex strings."""
+        if not self._span:
+            return None, None
+        span_context = self._span.get_span_context()
+        if not span_context.is_valid:
+            return None, None
+        return format_trace_id(span_context.trace_id), format_span_id(span_context.span_id)
@@ -19,6 +19,12 @@ from apex_agents.executor import (
 )
 
 
+# A W3C traceparent from the orchestrator and the IDs of the worker's span
+PARENT_TRACEPARENT = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01"
+WORKER_TRACE_ID = "0af7651916cd43dd8448eb211c80319c"
+WORKER_SPAN_ID = "00f067aa0ba902b7"
+
+
 @pytest.fixture
 def mock_settings():
     """Create mock settings."""
@@ -117,6 +123,46 @@ class TestQueuedTask:
         assert task.agent_config is not None
         assert task.agent_config.name == "custom-agent"
 
+    def test_from_json_trace_context(self):
+        """Test reading the orchestrator's trace context."""
+        data = {
+            "id": "task-3",
+            "name": "traced-task",
+            "trace_context": {
+                "trace_id": WORKER_TRACE_ID,
+                "span_id": "b7ad6b7169203331",
+                "traceparent": PARENT_TRACEPARENT,
+            },
+        }
+
+        task = QueuedTask.from_json(data)
+
+        assert task.trace_id == WORKER_TRACE_ID
+        assert task.span_id == "b7ad6b7169203331"
+        assert task.traceparent == PARENT_TRACEPARENT
+
+    def test_from_json_truncated_context(self):
+        """Test reading a context the orchestrator truncated."""
+        truncation = {"strategy": "head", "original_bytes": 5000, "truncated_bytes": 100}
+        data = {
+            "id": "task-4",
+            "name": "truncated-task",
+            "context": {"_truncated": True, "text": '{"notes":"xxx'},
+            "context_truncation": truncation,
+        }
+
+        task = QueuedTask.from_json(data)
+
+        assert task.context["_truncated"] is True
+        assert task.context_truncation == truncation
+
+        # A truncated string context is still handed to agents as an object
+        data["context"] = "partial notes"
+        task = QueuedTask.from_json(data)
+
+        assert task.context == {"text": "partial notes"}
+        assert task.context_truncation == truncation
+
 
 class TestTaskResult:
     """Tests for TaskResult."""
@@ -400,12 +446,19 @@ class TestAgentExecutor:
         executor._backend_client = AsyncMock()
         executor._llm_client = MagicMock()
 
-        with patch("apex_agents.executor.TaskSpanContext"):
+        queued_task.traceparent = PARENT_TRACEPARENT
+        with patch("apex_agents.executor.TaskSpanContext") as span_cls:
+            span = span_cls.return_value.__enter__.return_value
+            span.span_ids.return_value = (WORKER_TRACE_ID, WORKER_SPAN_ID)
             result = await executor.execute_task(queued_task)
 
         assert result.status == TaskStatus.COMPLETED
         assert result.result == "Task completed"
         assert result.tokens_used == 100
+        # The worker span is parented on the orchestrator's, and the result links back to it
+        assert span_cls.call_args.kwargs["traceparent"] == PARENT_TRACEPARENT
+        assert (result.trace_id, result.span_id) == (WORKER_TRACE_ID, WORKER_SPAN_ID)
+        assert result.duration_ms >= 0
 
     @pytest.mark.asyncio
     async def test_execute_task_timeout(self, executor, queued_task):
@@ -429,11 +482,14 @@ class TestAgentExecutor:
         executor._task_queue = AsyncMock()
         executor.settings.worker.max_task_duration_seconds = 1
 
-        with patch("apex_agents.executor.TaskSpanContext"):
+        with patch("apex_agents.executor.TaskSpanContext") as span_cls:
+            span = span_cls.return_value.__enter__.return_value
+            span.span_ids.return_value = (WORKER_TRACE_ID, WORKER_SPAN_ID)
             result = await executor.execute_task(queued_task)
 
         assert result.status == TaskStatus.FAILED
         assert "timed out" in result.error.lower()
+        assert result.span_id == WORKER_SPAN_ID
 
     @pytest.mark.asyncio
     async def test_execute_task_failure_with_retry(self, executor, queued_task):
@@ -451,7 +507,9 @@ class TestAgentExecutor:
         executor._llm_client = MagicMock()
         executor._task_queue = AsyncMock()
 
-        with patch("apex_agents.executor.TaskSpanContext"):
+        with patch("apex_agents.executor.TaskSpanContext") as span_cls:
+            span = span_cls.return_value.__enter__.return_value
+            span.span_ids.return_value = (WORKER_TRACE_ID, WORKER_SPAN_ID)
             result = await executor.execute_task(queued_task)
 
         assert result.status == TaskStatus.FAILED
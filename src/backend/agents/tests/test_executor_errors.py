@@ -100,7 +100,8 @@ class TestAgentExecutorErrorPaths:
             mock_agent.run.return_value = mock_output
             mock_agent_cls.return_value = mock_agent
 
-            with patch("apex_agents.executor.TaskSpanContext"):
+            with patch("apex_agents.executor.TaskSpanContext") as span_cls:
+                span_cls.return_value.__enter__.return_value.span_ids.return_value = (None, None)
                 result = await executor.execute_task(task)
 
         assert result.status == TaskStatus.COMPLETED
@@ -281,6 +281,18 @@ class TestTaskSpanContext:
         ) as ctx:
             assert ctx.trace_id is not None
 
+    def test_context_manager_with_traceparent(self):
+        """Test the task span continues the trace of a W3C traceparent."""
+        with TaskSpanContext(
+            task_id="task-parented",
+            task_name="parented-task",
+            agent_name="test-agent",
+            traceparent="00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+        ) as ctx:
+            trace_id, span_id = ctx.span_ids()
+            assert trace_id == "0af7651916cd43dd8448eb211c80319c"
+            assert span_id is not None
+
     def test_add_attribute(self):
         """Test adding attribute to span context."""
         with TaskSpanContext(
@@ -0,0 +1,27 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - HTTP Audit Log
+-- Migration: 20240101000005_audit_log.sql
+-- Description: Durable sink for request-level audit entries written by AuditLayer
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE TABLE audit_log (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    created_at   TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    request_id   TEXT,
+    actor_id     TEXT,                          -- authenticated user id, NULL if anonymous
+    api_key_id   TEXT,                          -- managed API key used, if any
+    auth_method  TEXT,
+    action       TEXT        NOT NULL,          -- HTTP method
+    resource     TEXT        NOT NULL,          -- request path
+    status       INTEGER     NOT NULL,
+    client_ip    TEXT,
+    user_agent   TEXT,
+    duration_ms  BIGINT      NOT NULL,
+    audit_level  TEXT        NOT NULL
+);
+
+COMMENT ON TABLE audit_log IS 'Append-only request audit trail for compliance reviews';
+
+CREATE INDEX idx_audit_log_created_at ON audit_log (created_at DESC);
+CREATE INDEX idx_audit_log_actor      ON audit_log (actor_id, created_at DESC) WHERE actor_id IS NOT NULL;
+CREATE INDEX idx_audit_log_resource   ON audit_log (resource text_pattern_ops);
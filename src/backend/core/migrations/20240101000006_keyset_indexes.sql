@@ -0,0 +1,8 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Keyset Pagination Indexes
+-- Migration: 20240101000006_keyset_indexes.sql
+-- Description: Support (created_at, id) cursor paging on the agents and contracts lists
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE INDEX idx_agents_created_keyset ON agents(created_at DESC, id DESC);
+CREATE INDEX idx_contracts_created_keyset ON agent_contracts(created_at DESC, id DESC);
@@ -0,0 +1,24 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Agent Performance History
+-- Migration: 20240101000007_agent_stats_history.sql
+-- Description: Periodic per-agent performance snapshots written by AggregateMetricsJob
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE TABLE agent_stats_history (
+    id               UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    agent_id         UUID             NOT NULL REFERENCES agents(id) ON DELETE CASCADE,
+    captured_at      TIMESTAMPTZ      NOT NULL DEFAULT NOW(),
+    window_minutes   INTEGER          NOT NULL,          -- lookback covered by this snapshot
+    tasks_completed  BIGINT           NOT NULL DEFAULT 0,
+    tasks_failed     BIGINT           NOT NULL DEFAULT 0,
+    success_rate     DOUBLE PRECISION,                   -- NULL when no tasks finished in the window
+    avg_duration_ms  DOUBLE PRECISION,
+    tokens_used      BIGINT           NOT NULL DEFAULT 0,
+    cost_dollars     DOUBLE PRECISION NOT NULL DEFAULT 0,
+    reputation_score DOUBLE PRECISION NOT NULL
+);
+
+COMMENT ON TABLE agent_stats_history IS 'Time series of agent success rate, latency, usage and reputation';
+
+CREATE INDEX idx_agent_stats_history_agent ON agent_stats_history (agent_id, captured_at DESC);
+CREATE INDEX idx_agent_stats_history_captured_at ON agent_stats_history (captured_at);
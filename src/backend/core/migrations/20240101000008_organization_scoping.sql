@@ -0,0 +1,64 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Tenant Ownership of Core Resources
+-- Migration: 20240101000008_organization_scoping.sql
+-- Description: Adds organization_id to tasks, DAGs, agents and contracts so list/get
+--              queries can be scoped to the caller's organization
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- COLUMNS
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+-- Nullable so the columns can be added without a table rewrite; rows left
+-- NULL are visible to admins only.
+ALTER TABLE dags            ADD COLUMN organization_id UUID REFERENCES organizations(id) ON DELETE RESTRICT;
+ALTER TABLE tasks           ADD COLUMN organization_id UUID REFERENCES organizations(id) ON DELETE RESTRICT;
+ALTER TABLE agents          ADD COLUMN organization_id UUID REFERENCES organizations(id) ON DELETE RESTRICT;
+ALTER TABLE agent_contracts ADD COLUMN organization_id UUID REFERENCES organizations(id) ON DELETE RESTRICT;
+
+COMMENT ON COLUMN dags.organization_id IS 'Owning tenant; NULL rows are visible to admins only';
+COMMENT ON COLUMN tasks.organization_id IS 'Owning tenant, same as the parent DAG';
+COMMENT ON COLUMN agents.organization_id IS 'Owning tenant; NULL rows are visible to admins only';
+COMMENT ON COLUMN agent_contracts.organization_id IS 'Owning tenant, same as the task';
+
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- BACKFILL
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+-- Everything created before tenancy belonged to the single implicit tenant.
+-- Hand it to a 'default' organization; operators of multi-tenant deployments
+-- can then move rows to their real owners with an UPDATE per organization.
+INSERT INTO organizations (name, slug, owner_id)
+VALUES ('Default', 'default', '00000000-0000-0000-0000-000000000000')
+ON CONFLICT (slug) DO NOTHING;
+
+UPDATE dags SET organization_id = (SELECT id FROM organizations WHERE slug = 'default')
+WHERE organization_id IS NULL;
+
+-- Tasks and contracts follow their parents so a DAG's pieces never straddle tenants
+UPDATE tasks t SET organization_id = d.organization_id
+FROM dags d
+WHERE t.dag_id = d.id AND t.organization_id IS NULL;
+
+UPDATE tasks SET organization_id = (SELECT id FROM organizations WHERE slug = 'default')
+WHERE organization_id IS NULL;
+
+UPDATE agents SET organization_id = (SELECT id FROM organizations WHERE slug = 'default')
+WHERE organization_id IS NULL;
+
+UPDATE agent_contracts c SET organization_id = t.organization_id
+FROM tasks t
+WHERE c.task_id = t.id AND c.organization_id IS NULL;
+
+UPDATE agent_contracts SET organization_id = (SELECT id FROM organizations WHERE slug = 'default')
+WHERE organization_id IS NULL;
+
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- INDEXES
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+-- Scoped versions of the list orderings
+CREATE INDEX idx_dags_organization ON dags(organization_id);
+CREATE INDEX idx_tasks_organization_created ON tasks(organization_id, created_at DESC);
+CREATE INDEX idx_agents_organization_keyset ON agents(organization_id, created_at DESC, id DESC);
+CREATE INDEX idx_contracts_organization_keyset ON agent_contracts(organization_id, created_at DESC, id DESC);
@@ -0,0 +1,58 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Event Stream Optimistic Concurrency
+-- Migration: 20240101000009_event_version_constraint.sql
+-- Description: Guarantees one event per (aggregate, version) so concurrent appends
+--              to the same stream conflict instead of interleaving
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- CONSTRAINTS
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+-- Streams appended to concurrently under the legacy schema can hold two events
+-- at one version, which would fail the constraint below. Keep every event:
+-- renumber each such stream (and any with a version below 1) from 1 in
+-- (version, id) order, so later appends see a consistent stream.
+WITH broken_streams AS (
+    SELECT aggregate_type, aggregate_id
+    FROM events
+    GROUP BY aggregate_type, aggregate_id
+    HAVING COUNT(*) > COUNT(DISTINCT version) OR MIN(version) < 1
+), renumbered AS (
+    SELECT e.id,
+           ROW_NUMBER() OVER (PARTITION BY e.aggregate_type, e.aggregate_id ORDER BY e.version, e.id) AS version
+    FROM events e
+    JOIN broken_streams b USING (aggregate_type, aggregate_id)
+)
+UPDATE events e
+SET version = r.version
+FROM renumbered r
+WHERE e.id = r.id AND e.version <> r.version;
+
+-- Databases created from the initial schema already have these; ones that grew
+-- from the legacy schema do not. EventStore::append matches on the constraint
+-- name to report a ConcurrencyConflict, so it must be exactly this.
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'events_unique_version'
+          AND conrelid = 'events'::regclass
+    ) THEN
+        ALTER TABLE events
+            ADD CONSTRAINT events_unique_version UNIQUE (aggregate_type, aggregate_id, version);
+    END IF;
+
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint
+        WHERE conname = 'events_version_positive'
+          AND conrelid = 'events'::regclass
+    ) THEN
+        ALTER TABLE events
+            ADD CONSTRAINT events_version_positive CHECK (version > 0);
+    END IF;
+END
+$$;
+
+COMMENT ON CONSTRAINT events_unique_version ON events IS
+    'Optimistic concurrency: a second append at the same stream version is rejected';
@@ -0,0 +1,22 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Usage Reports
+-- Migration: 20240101000010_usage_reports.sql
+-- Description: Per-period usage reports generated by SendUsageReportsJob
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE TABLE usage_reports (
+    id            UUID PRIMARY KEY,
+    period        VARCHAR(16) NOT NULL,              -- daily | weekly | monthly
+    period_start  TIMESTAMPTZ NOT NULL,
+    period_end    TIMESTAMPTZ NOT NULL,
+    generated_at  TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    report        JSONB       NOT NULL,              -- the report as served by the API
+    csv           TEXT        NOT NULL,              -- one line per organization
+
+    CONSTRAINT usage_reports_unique_period UNIQUE (period, period_start),
+    CONSTRAINT usage_reports_period_order CHECK (period_start < period_end)
+);
+
+COMMENT ON TABLE usage_reports IS 'Generated per-organization usage reports, one per period';
+
+CREATE INDEX idx_usage_reports_generated_at ON usage_reports (generated_at DESC);
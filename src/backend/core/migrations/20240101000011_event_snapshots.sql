@@ -0,0 +1,21 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Event Snapshots
+-- Migration: 20240101000011_event_snapshots.sql
+-- Description: Latest aggregate state per stream, so events it covers can be pruned
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE TABLE event_snapshots (
+    aggregate_type VARCHAR(50) NOT NULL,
+    aggregate_id   UUID        NOT NULL,
+    version        INTEGER     NOT NULL,             -- stream version the state includes
+    state          JSONB       NOT NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (aggregate_type, aggregate_id),
+    CONSTRAINT event_snapshots_version_positive CHECK (version > 0)
+);
+
+COMMENT ON TABLE event_snapshots IS 'Latest snapshot of each aggregate; events below its version may be pruned';
+
+-- Retention scans old events per aggregate type
+CREATE INDEX IF NOT EXISTS idx_events_type_created_at ON events (aggregate_type, created_at);
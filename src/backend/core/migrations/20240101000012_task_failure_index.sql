@@ -0,0 +1,12 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Task Failure Breakdown
+-- Migration: 20240101000012_task_failure_index.sql
+-- Description: Failed tasks by error code, for the failure breakdown dashboard
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+-- error_code and error_details (a serialized TaskError) already exist on tasks;
+-- the breakdown groups recent failures by code
+CREATE INDEX idx_tasks_failed_error_code ON tasks(error_code, completed_at DESC)
+WHERE status = 'failed';
+
+COMMENT ON COLUMN tasks.error_details IS 'Structured failure: code, message, internal detail, retry_count, agent_id, model';
@@ -0,0 +1,22 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - DAG Templates
+-- Migration: 20240101000013_dag_templates.sql
+-- Description: Stored workflow specs with {{placeholders}}, instantiated into
+--              DAGs through POST /api/v1/templates/:id/instantiate
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE TABLE dag_templates (
+    id              UUID PRIMARY KEY,
+    organization_id UUID REFERENCES organizations(id) ON DELETE CASCADE,
+    name            VARCHAR(255) NOT NULL,
+    description     TEXT,
+    spec            JSONB        NOT NULL,              -- DagSpec with placeholders
+    defaults        JSONB        NOT NULL DEFAULT '{}', -- placeholder -> default value
+    created_at      TIMESTAMPTZ  NOT NULL DEFAULT NOW(),
+    updated_at      TIMESTAMPTZ  NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE dag_templates IS 'Reusable parameterized workflows';
+COMMENT ON COLUMN dag_templates.organization_id IS 'Owning tenant; NULL rows are visible to admins only';
+
+CREATE INDEX idx_dag_templates_org_name ON dag_templates (organization_id, name);
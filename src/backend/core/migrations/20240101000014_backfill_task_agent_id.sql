@@ -0,0 +1,46 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Backfill Task Agent Attribution
+-- Migration: 20240101000014_backfill_task_agent_id.sql
+-- Description: Fills tasks.agent_id on finished tasks that were completed before
+--              the orchestrator recorded it, inferring the agent where possible
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+-- Sources, most reliable first. Each step only touches rows still NULL, and
+-- only assigns agents that still exist (agent_id references agents).
+
+-- 1. The agent named in the task's last TaskAssigned event
+UPDATE tasks t
+SET agent_id = a.id
+FROM (
+    SELECT DISTINCT ON (aggregate_id) aggregate_id, event_data->>'agent_id' AS agent_id
+    FROM events
+    WHERE aggregate_type = 'task'
+      AND event_type = 'TaskAssigned'
+      AND event_data->>'agent_id' ~* '^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$'
+    ORDER BY aggregate_id, id DESC
+) e
+JOIN agents a ON a.id = e.agent_id::uuid
+WHERE t.id = e.aggregate_id
+  AND t.agent_id IS NULL
+  AND t.status IN ('completed', 'failed');
+
+-- 2. The agent that held the task's most recent contract
+UPDATE tasks t
+SET agent_id = c.agent_id
+FROM (
+    SELECT DISTINCT ON (task_id) task_id, agent_id
+    FROM agent_contracts
+    ORDER BY task_id, created_at DESC
+) c
+WHERE t.id = c.task_id
+  AND t.agent_id IS NULL
+  AND t.status IN ('completed', 'failed');
+
+-- 3. Failures recorded with structured details carry the agent
+UPDATE tasks t
+SET agent_id = a.id
+FROM agents a
+WHERE t.agent_id IS NULL
+  AND t.status = 'failed'
+  AND t.error_details->>'agent_id' ~* '^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$'
+  AND a.id = (t.error_details->>'agent_id')::uuid;
@@ -0,0 +1,21 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Backups
+-- Migration: 20240101000015_backups.sql
+-- Description: Backup runs recorded by the backup job; the database_backup
+--              health check reads the newest completed one
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE TABLE backups (
+    id           UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    kind         VARCHAR(50)  NOT NULL DEFAULT 'full',     -- full, incremental, ...
+    status       VARCHAR(50)  NOT NULL DEFAULT 'running',  -- running, completed, failed
+    location     TEXT,                                     -- where the backup was written
+    size_bytes   BIGINT,
+    started_at   TIMESTAMPTZ  NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    error        TEXT
+);
+
+COMMENT ON TABLE backups IS 'Database backup runs, newest completed_at drives backup freshness health';
+
+CREATE INDEX idx_backups_completed ON backups (completed_at DESC) WHERE status = 'completed';
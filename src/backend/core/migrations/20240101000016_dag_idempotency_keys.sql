@@ -0,0 +1,23 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - DAG Idempotency Keys
+-- Migration: 20240101000016_dag_idempotency_keys.sql
+-- Description: Client-supplied Idempotency-Key of each DAG submission, so a
+--              retried submission returns the DAG created the first time
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE TABLE dag_idempotency_keys (
+    id              UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    organization_id UUID REFERENCES organizations(id) ON DELETE CASCADE,
+    idempotency_key VARCHAR(255) NOT NULL,
+    dag_id          UUID         NOT NULL,
+    created_at      TIMESTAMPTZ  NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE dag_idempotency_keys IS 'DAG submitted under each client idempotency key';
+COMMENT ON COLUMN dag_idempotency_keys.organization_id IS 'Keys are unique per tenant; NULL for unscoped callers';
+
+-- One DAG per key and tenant; NULL tenants share one namespace
+CREATE UNIQUE INDEX idx_dag_idempotency_keys_org_key ON dag_idempotency_keys (
+    COALESCE(organization_id, '00000000-0000-0000-0000-000000000000'::uuid),
+    idempotency_key
+);
@@ -0,0 +1,62 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - DAG Pause
+-- Migration: 20240101000017_dag_pause.sql
+-- Description: Keep a paused DAG paused while its in-flight tasks finish;
+--              only reaching a terminal state overrides the pause
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE OR REPLACE FUNCTION update_dag_status()
+RETURNS TRIGGER AS $$
+DECLARE
+    v_total INTEGER;
+    v_completed INTEGER;
+    v_failed INTEGER;
+    v_running INTEGER;
+    v_new_status dag_status;
+BEGIN
+    -- Count tasks by status
+    SELECT
+        COUNT(*),
+        COUNT(*) FILTER (WHERE status = 'completed'),
+        COUNT(*) FILTER (WHERE status = 'failed'),
+        COUNT(*) FILTER (WHERE status IN ('assigned', 'running'))
+    INTO v_total, v_completed, v_failed, v_running
+    FROM tasks
+    WHERE dag_id = NEW.dag_id;
+
+    -- Determine new DAG status
+    IF v_failed > 0 THEN
+        v_new_status = 'failed';
+    ELSIF v_completed = v_total AND v_total > 0 THEN
+        v_new_status = 'completed';
+    ELSIF v_running > 0 OR v_completed > 0 THEN
+        v_new_status = 'running';
+    ELSE
+        v_new_status = 'pending';
+    END IF;
+
+    -- Update DAG
+    UPDATE dags
+    SET
+        status = CASE
+            WHEN status = 'paused' AND v_new_status IN ('pending', 'running') THEN status
+            ELSE v_new_status
+        END,
+        completed_tasks = v_completed,
+        failed_tasks = v_failed,
+        started_at = CASE
+            WHEN started_at IS NULL AND v_new_status = 'running' THEN NOW()
+            ELSE started_at
+        END,
+        completed_at = CASE
+            WHEN v_new_status IN ('completed', 'failed') AND completed_at IS NULL THEN NOW()
+            ELSE completed_at
+        END,
+        updated_at = NOW()
+    WHERE id = NEW.dag_id;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION update_dag_status() IS 'Updates DAG status and timestamps based on task state changes, leaving paused DAGs paused';
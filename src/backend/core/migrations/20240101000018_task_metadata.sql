@@ -0,0 +1,10 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Task Metadata
+-- Migration: 20240101000018_task_metadata.sql
+-- Description: Free-form notes recorded while a task runs, such as how its
+--              agent won the task when bidding (Contract Net) is enabled
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+ALTER TABLE tasks ADD COLUMN IF NOT EXISTS metadata JSONB NOT NULL DEFAULT '{}';
+
+COMMENT ON COLUMN tasks.metadata IS 'Notes recorded while running the task, keyed by name (e.g. cnp_award)';
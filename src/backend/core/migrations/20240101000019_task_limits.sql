@@ -0,0 +1,10 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Task Limits
+-- Migration: 20240101000019_task_limits.sql
+-- Description: Resource limits a task asked for in place of the orchestrator's
+--              default, kept so a reloaded DAG runs under the same limits
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+ALTER TABLE tasks ADD COLUMN IF NOT EXISTS limits JSONB;
+
+COMMENT ON COLUMN tasks.limits IS 'Per-task resource limits (token_limit, cost_limit, api_call_limit, time_limit_seconds); NULL uses the default';
@@ -0,0 +1,22 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - DAG Idempotency Key Namespaces
+-- Migration: 20240101000020_dag_idempotency_namespace.sql
+-- Description: Scope idempotency keys by the caller's tenant id as given,
+--              so tenants whose id isn't an organization row (and so store
+--              a NULL organization_id) no longer share one key space
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+ALTER TABLE dag_idempotency_keys ADD COLUMN IF NOT EXISTS namespace VARCHAR(255) NOT NULL DEFAULT '';
+
+UPDATE dag_idempotency_keys
+SET namespace = COALESCE(organization_id::text, '')
+WHERE namespace = '';
+
+COMMENT ON COLUMN dag_idempotency_keys.namespace IS 'Tenant the key belongs to; empty for unscoped callers';
+COMMENT ON COLUMN dag_idempotency_keys.organization_id IS 'Owning organization; NULL for unscoped callers and tenants without an organization row';
+
+DROP INDEX IF EXISTS idx_dag_idempotency_keys_org_key;
+
+-- One DAG per key and tenant
+CREATE UNIQUE INDEX IF NOT EXISTS idx_dag_idempotency_keys_namespace_key
+    ON dag_idempotency_keys (namespace, idempotency_key);
@@ -0,0 +1,19 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - Organization Quota Periods
+-- Migration: 20240101000021_organization_quota_periods.sql
+-- Description: Tasks accepted per organization and month, counted atomically
+--              so concurrent requests can't overrun a task quota, and the
+--              soft-limit warnings already sent for the month
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE TABLE organization_quota_periods (
+    organization_id UUID        NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    period_start    TIMESTAMPTZ NOT NULL,
+    tasks           BIGINT      NOT NULL DEFAULT 0,
+    warned          TEXT[]      NOT NULL DEFAULT '{}',
+    PRIMARY KEY (organization_id, period_start)
+);
+
+COMMENT ON TABLE organization_quota_periods IS 'Per-month quota accounting for each organization';
+COMMENT ON COLUMN organization_quota_periods.tasks IS 'Tasks accepted this period, never below the tasks table count';
+COMMENT ON COLUMN organization_quota_periods.warned IS 'Resources whose soft-limit warning was sent this period';
@@ -0,0 +1,68 @@
+-- ═══════════════════════════════════════════════════════════════════════════════
+-- Project Apex - DAG Terminal Status
+-- Migration: 20240101000022_dag_terminal_status.sql
+-- Description: Keep a cancelled, completed or failed DAG in that state when
+--              its tasks change afterwards, as a paused DAG stays paused.
+--              Cancelling a DAG also cancels its tasks, and the task
+--              updates would otherwise replace 'cancelled' with the status
+--              worked out from the tasks
+-- ═══════════════════════════════════════════════════════════════════════════════
+
+CREATE OR REPLACE FUNCTION update_dag_status()
+RETURNS TRIGGER AS $$
+DECLARE
+    v_total INTEGER;
+    v_completed INTEGER;
+    v_failed INTEGER;
+    v_running INTEGER;
+    v_new_status dag_status;
+BEGIN
+    -- Count tasks by status
+    SELECT
+        COUNT(*),
+        COUNT(*) FILTER (WHERE status = 'completed'),
+        COUNT(*) FILTER (WHERE status = 'failed'),
+        COUNT(*) FILTER (WHERE status IN ('assigned', 'running'))
+    INTO v_total, v_completed, v_failed, v_running
+    FROM tasks
+    WHERE dag_id = NEW.dag_id;
+
+    -- Determine new DAG status
+    IF v_failed > 0 THEN
+        v_new_status = 'failed';
+    ELSIF v_completed = v_total AND v_total > 0 THEN
+        v_new_status = 'completed';
+    ELSIF v_running > 0 OR v_completed > 0 THEN
+        v_new_status = 'running';
+    ELSE
+        v_new_status = 'pending';
+    END IF;
+
+    -- Update DAG. A completed DAG only runs again when tasks are added to
+    -- it, as when a map task that finished last expands into instances.
+    UPDATE dags
+    SET
+        status = CASE
+            WHEN status = 'paused' AND v_new_status IN ('pending', 'running') THEN status
+            WHEN status IN ('cancelled', 'failed') THEN status
+            WHEN status = 'completed' AND TG_OP <> 'INSERT' THEN status
+            ELSE v_new_status
+        END,
+        completed_tasks = v_completed,
+        failed_tasks = v_failed,
+        started_at = CASE
+            WHEN started_at IS NULL AND v_new_status = 'running' THEN NOW()
+            ELSE started_at
+        END,
+        completed_at = CASE
+            WHEN v_new_status IN ('completed', 'failed') AND completed_at IS NULL THEN NOW()
+            ELSE completed_at
+        END,
+        updated_at = NOW()
+    WHERE id = NEW.dag_id;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+COMMENT ON FUNCTION update_dag_status() IS 'Updates DAG status and timestamps based on task state changes, leaving paused DAGs paused and finished DAGs finished';
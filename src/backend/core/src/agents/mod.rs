@@ -4,6 +4,12 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+use crate::error::{ApexError, Result};
+use crate::observability::metrics::record_agent_wait;
 
 /// Unique identifier for an agent.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -153,6 +159,15 @@ impl Agent {
         self.current_load.fetch_sub(1, Ordering::SeqCst);
     }
 
+    /// Selection score: `reputation * (1 - load / max_load)`.
+    pub fn selection_score(&self) -> f64 {
+        if self.max_load == 0 {
+            return 0.0;
+        }
+        let utilisation = self.current_load() as f64 / self.max_load as f64;
+        self.reputation_score() * (1.0 - utilisation).max(0.0)
+    }
+
     /// Record a successful execution.
     pub fn record_success(&self, tokens: u64, cost: f64) {
         self.success_count.fetch_add(1, Ordering::Relaxed);
@@ -266,6 +281,108 @@ pub struct AgentStats {
     pub reputation_score: f64,
 }
 
+/// A load slot held on an agent, released when dropped.
+pub struct AgentSlot {
+    agent: Arc<Agent>,
+    /// Woken when the slot is released, for tasks waiting on a free agent
+    released: Option<Arc<Notify>>,
+}
+
+impl AgentSlot {
+    /// Claim a slot on `agent`, if it has one free.
+    pub fn acquire(agent: &Arc<Agent>) -> Option<Self> {
+        agent.acquire_slot().then(|| Self { agent: agent.clone(), released: None })
+    }
+
+    pub fn agent(&self) -> &Arc<Agent> {
+        &self.agent
+    }
+}
+
+impl std::ops::Deref for AgentSlot {
+    type Target = Agent;
+
+    fn deref(&self) -> &Agent {
+        &self.agent
+    }
+}
+
+impl Drop for AgentSlot {
+    fn drop(&mut self) {
+        self.agent.release_slot();
+        if let Some(released) = &self.released {
+            released.notify_waiters();
+        }
+    }
+}
+
+/// Claim a slot on the best available agent.
+///
+/// Agents are ranked by [`Agent::selection_score`], ties going to the lowest
+/// agent id so the choice doesn't depend on map iteration order. If a slot
+/// was taken concurrently the next-ranked agent is tried.
+pub fn claim_least_loaded<'a>(agents: impl IntoIterator<Item = &'a Arc<Agent>>) -> Option<AgentSlot> {
+    let mut ranked: Vec<(f64, &Arc<Agent>)> = agents.into_iter()
+        .filter(|a| a.is_available())
+        .map(|a| (a.selection_score(), a))
+        .collect();
+    ranked.sort_by(|(sa, a), (sb, b)| sb.total_cmp(sa).then_with(|| a.id.0.cmp(&b.id.0)));
+
+    ranked.into_iter().find_map(|(_, a)| AgentSlot::acquire(a))
+}
+
+/// Claim a slot like [`claim_least_loaded`], waiting up to `timeout` for one
+/// to free up when every agent is at capacity.
+///
+/// Fails fast with `NoAgentsRegistered` when `agents` is empty, and with
+/// `AllAgentsBusy` when the wait runs out. `released` is notified whenever a
+/// slot claimed here is dropped, so share one `Notify` across all callers
+/// drawing from the same agents.
+pub async fn claim_agent(
+    agents: &[Arc<Agent>],
+    released: &Arc<Notify>,
+    timeout: Duration,
+) -> Result<AgentSlot> {
+    let (slot, ()) = claim_agent_with(agents, released, timeout, |agents| {
+        claim_least_loaded(agents).map(|slot| (slot, ()))
+    })
+    .await?;
+    Ok(slot)
+}
+
+/// Claim a slot like [`claim_agent`], choosing the agent with `pick`, which
+/// returns the slot it claimed along with whatever it learned choosing it.
+/// `pick` is called again each time a slot is released.
+pub async fn claim_agent_with<T>(
+    agents: &[Arc<Agent>],
+    released: &Arc<Notify>,
+    timeout: Duration,
+    mut pick: impl FnMut(&[Arc<Agent>]) -> Option<(AgentSlot, T)>,
+) -> Result<(AgentSlot, T)> {
+    if agents.is_empty() {
+        return Err(ApexError::no_agents_registered());
+    }
+
+    let started = Instant::now();
+    let deadline = tokio::time::Instant::from_std(started + timeout);
+    loop {
+        // Register for the wakeup before checking, so a release between the
+        // check and the wait isn't missed
+        let notified = released.notified();
+
+        if let Some((mut slot, choice)) = pick(agents) {
+            slot.released = Some(released.clone());
+            record_agent_wait(started.elapsed().as_secs_f64(), "acquired");
+            return Ok((slot, choice));
+        }
+
+        if tokio::time::timeout_at(deadline, notified).await.is_err() {
+            record_agent_wait(started.elapsed().as_secs_f64(), "timeout");
+            return Err(ApexError::all_agents_busy(agents.len(), timeout.as_secs()));
+        }
+    }
+}
+
 /// Builder for creating agents with specific configurations.
 pub struct AgentBuilder {
     name: String,
@@ -386,6 +503,73 @@ mod tests {
         assert!(agent.acquire_slot()); // Slot available again
     }
 
+    #[test]
+    fn test_least_loaded_distributes_evenly() {
+        let agents: Vec<Arc<Agent>> = (0..3)
+            .map(|i| Arc::new(Agent::new(format!("agent-{}", i), "gpt-4")))
+            .collect();
+
+        let slots: Vec<AgentSlot> = (0..10)
+            .map(|_| claim_least_loaded(&agents).unwrap())
+            .collect();
+
+        for agent in &agents {
+            let load = agent.current_load();
+            assert!((3..=4).contains(&load), "{} got {}", agent.name, load);
+        }
+
+        drop(slots);
+        assert!(agents.iter().all(|a| a.current_load() == 0));
+    }
+
+    #[test]
+    fn test_least_loaded_prefers_reputation_then_id() {
+        let a = Arc::new(Agent::new("a", "gpt-4"));
+        let b = Arc::new(Agent::new("b", "gpt-4"));
+        let lower = if a.id.0 < b.id.0 { a.id } else { b.id };
+
+        let slot = claim_least_loaded([&a, &b]).unwrap();
+        assert_eq!(slot.id, lower);
+        drop(slot);
+
+        b.record_failure();
+        assert_eq!(claim_least_loaded([&a, &b]).unwrap().id, a.id);
+    }
+
+    #[test]
+    fn test_least_loaded_skips_full_agents() {
+        let agent = Arc::new(Agent::new("solo", "gpt-4").with_max_load(1));
+        let _held = claim_least_loaded([&agent]).unwrap();
+        assert!(claim_least_loaded([&agent]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_claim_agent_no_agents_fails_fast() {
+        let released = Arc::new(Notify::new());
+        let err = claim_agent(&[], &released, Duration::from_secs(60)).await.err().unwrap();
+        assert_eq!(err.code(), crate::error::ErrorCode::NoAgentsRegistered);
+    }
+
+    #[tokio::test]
+    async fn test_claim_agent_waits_for_release() {
+        let agents = vec![Arc::new(Agent::new("solo", "gpt-4").with_max_load(1))];
+        let released = Arc::new(Notify::new());
+
+        let held = claim_agent(&agents, &released, Duration::from_secs(1)).await.unwrap();
+        let err = claim_agent(&agents, &released, Duration::from_millis(20)).await.err().unwrap();
+        assert_eq!(err.code(), crate::error::ErrorCode::AllAgentsBusy);
+
+        let waiter = {
+            let (agents, released) = (agents.clone(), released.clone());
+            tokio::spawn(async move { claim_agent(&agents, &released, Duration::from_secs(5)).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(held);
+
+        let slot = waiter.await.unwrap().unwrap();
+        assert_eq!(slot.id, agents[0].id);
+    }
+
     #[test]
     fn test_reputation_updates() {
         let agent = Agent::new("TestAgent", "gpt-4");
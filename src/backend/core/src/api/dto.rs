@@ -0,0 +1,255 @@
+//! Request and response bodies of the REST API.
+//!
+//! Handlers build these types rather than ad-hoc JSON, so each endpoint's
+//! wire format is defined once. The server only needs one direction of each
+//! (deserialize requests, serialize responses); the `client` feature derives
+//! the other direction too, which is what `apex-client` uses to talk to the
+//! server with the exact same structs.
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::dag::{DagSpec, DagTemplate, TemplateParams};
+use crate::db::ApprovalDecision;
+use crate::pagination::CursorInfo;
+use crate::routing::ModelTier;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Tasks
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Body of `POST /api/v1/tasks`.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+pub struct CreateTaskRequest {
+    pub name: String,
+    pub instruction: String,
+    pub context: Option<serde_json::Value>,
+    pub priority: Option<i32>,
+    pub limits: Option<ResourceLimitsDto>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ResourceLimitsDto {
+    pub token_limit: Option<u64>,
+    pub cost_limit: Option<f64>,
+    pub api_call_limit: Option<u64>,
+    pub time_limit_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct TaskResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub status: String,
+    pub tokens_used: u64,
+    pub cost_dollars: f64,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Structured cause of a failure: code, message, retry count, agent and model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_details: Option<serde_json::Value>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// DAGs
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Body of `POST /api/v1/dags`: a workflow spec, as in a `workflow.yaml` file.
+pub type CreateDagRequest = DagSpec;
+
+/// Request header making `POST /api/v1/dags` (and template instantiation)
+/// safe to retry: a second submission with the same key returns the first
+/// DAG instead of creating another.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct DagResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub task_count: usize,
+    pub status: String,
+    /// The idempotency key matched an earlier submission and `id` is that
+    /// DAG; nothing new was created
+    #[serde(default)]
+    pub existing: bool,
+}
+
+/// A stored DAG with its nodes, edges and tasks, as returned by `GET /api/v1/dags/:id`.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct DagDetail {
+    pub id: Uuid,
+    pub name: String,
+    pub status: String,
+    pub metadata: Option<serde_json::Value>,
+    pub created_at: String,
+    pub started_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub nodes: Vec<DagNodeSummary>,
+    pub edges: Vec<DagEdge>,
+    pub tasks: Vec<DagTaskSummary>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct DagNodeSummary {
+    pub id: Uuid,
+    pub task_template: serde_json::Value,
+    pub depends_on: Option<Vec<String>>,
+    pub is_entry: bool,
+    pub is_exit: bool,
+}
+
+/// `from` must complete before `to` can start.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct DagEdge {
+    pub from: String,
+    pub to: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct DagTaskSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub status: String,
+    pub tokens_used: i64,
+    pub cost_dollars: f64,
+}
+
+/// Outcome of `POST /api/v1/dags/:id/execute`.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct DagExecutionResponse {
+    pub dag_id: Uuid,
+    pub status: String,
+    pub tasks_completed: usize,
+    pub tasks_failed: usize,
+    pub total_tokens: u64,
+    pub total_cost: f64,
+    pub duration_ms: u64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// DAG Templates
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Body of `POST /api/v1/templates` and `PUT /api/v1/templates/:id`.
+pub type CreateTemplateRequest = DagTemplate;
+
+/// A stored template, as returned by the template endpoints.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct TemplateResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub spec: DagSpec,
+    pub defaults: TemplateParams,
+    /// Placeholders without a default, which instantiation must supply
+    pub required_parameters: Vec<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Body of `POST /api/v1/templates/:id/instantiate`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+pub struct InstantiateTemplateRequest {
+    /// Placeholder values; omitted ones fall back to the template's defaults
+    #[serde(default)]
+    pub params: TemplateParams,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Agents
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// One entry of `GET /api/v1/agents`.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct AgentSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub model: String,
+    pub status: String,
+    pub current_load: i32,
+    pub max_load: i32,
+    pub success_rate: f64,
+    pub reputation_score: f64,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Cost Estimates
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Body of `POST /api/v1/estimate`.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+pub struct CostEstimateRequest {
+    pub instruction: String,
+    pub context: Option<serde_json::Value>,
+    /// Model to price; defaults to the one the router would pick
+    pub model: Option<String>,
+}
+
+/// Cost of one likely response size.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct OutputCostEstimate {
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+    pub cost: f64,
+}
+
+/// Response of `POST /api/v1/estimate`.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct CostEstimateResponse {
+    pub model: String,
+    pub tier: ModelTier,
+    pub input_tokens: u64,
+    /// Cheapest and dearest of `outputs`
+    pub cost_min: f64,
+    pub cost_max: f64,
+    pub outputs: Vec<OutputCostEstimate>,
+    /// Whether the prompt plus the largest output fits the model's context
+    pub fits_context: bool,
+    /// Model and tier the router would pick for this instruction
+    pub routed_model: String,
+    pub routed_tier: ModelTier,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Approvals
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Body of `POST /api/v1/approvals/batch`.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(feature = "client", derive(Serialize))]
+pub struct BatchApprovalRequest {
+    pub ids: Vec<Uuid>,
+    pub decision: ApprovalDecision,
+    /// Note recorded with an approval
+    pub comment: Option<String>,
+    /// Why the actions were denied; required when denying
+    pub reason: Option<String>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Pagination
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A keyset-paginated list. Pass `page_info.end_cursor` as `?after=` to get
+/// the next page.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "client", derive(Deserialize))]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page_info: CursorInfo,
+}
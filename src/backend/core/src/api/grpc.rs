@@ -232,12 +232,13 @@ fn to_grpc_status(err: ApexError) -> Status {
         ErrorCode::TaskNotFound | ErrorCode::AgentNotFound | ErrorCode::RecordNotFound | ErrorCode::ToolNotFound | ErrorCode::ContractNotFound => tonic::Code::NotFound,
         ErrorCode::TaskAlreadyExists | ErrorCode::DuplicateRecord => tonic::Code::AlreadyExists,
         ErrorCode::InvalidStateTransition | ErrorCode::DependencyNotMet => tonic::Code::FailedPrecondition,
-        ErrorCode::TokenLimitExceeded | ErrorCode::CostLimitExceeded | ErrorCode::TimeLimitExceeded | ErrorCode::ApiCallLimitExceeded | ErrorCode::ContractViolation | ErrorCode::ContractExpired | ErrorCode::LlmRateLimited | ErrorCode::AgentOverloaded => tonic::Code::ResourceExhausted,
+        ErrorCode::ConcurrencyConflict => tonic::Code::Aborted,
+        ErrorCode::TokenLimitExceeded | ErrorCode::CostLimitExceeded | ErrorCode::TimeLimitExceeded | ErrorCode::ApiCallLimitExceeded | ErrorCode::ContractViolation | ErrorCode::ContractExpired | ErrorCode::QuotaExceeded | ErrorCode::RateLimited | ErrorCode::LlmRateLimited | ErrorCode::AgentOverloaded | ErrorCode::AllAgentsBusy | ErrorCode::TooManyActiveDags => tonic::Code::ResourceExhausted,
         ErrorCode::DagCycleDetected | ErrorCode::DagValidationFailed | ErrorCode::ValidationError | ErrorCode::InvalidInput | ErrorCode::MissingRequiredField | ErrorCode::InvalidFormat | ErrorCode::ConfigurationError | ErrorCode::InvalidConfiguration => tonic::Code::InvalidArgument,
         ErrorCode::Unauthorized | ErrorCode::InvalidToken | ErrorCode::TokenExpired => tonic::Code::Unauthenticated,
         ErrorCode::Forbidden => tonic::Code::PermissionDenied,
         ErrorCode::LlmTimeout | ErrorCode::AgentTimeout | ErrorCode::ToolTimeout => tonic::Code::DeadlineExceeded,
-        ErrorCode::LlmUnavailable | ErrorCode::AgentUnavailable | ErrorCode::DatabaseConnectionFailed | ErrorCode::CacheConnectionFailed | ErrorCode::ExternalServiceError => tonic::Code::Unavailable,
+        ErrorCode::LlmUnavailable | ErrorCode::AgentUnavailable | ErrorCode::NoAgentsRegistered | ErrorCode::DatabaseConnectionFailed | ErrorCode::CacheConnectionFailed | ErrorCode::ExternalServiceError => tonic::Code::Unavailable,
         ErrorCode::NotImplemented => tonic::Code::Unimplemented,
         _ => tonic::Code::Internal,
     };
@@ -577,7 +578,8 @@ impl ApexOrchestrator for ApexGrpcService {
         }
 
         // Submit to orchestrator
-        self.orchestrator.submit_dag(dag.clone()).await.map_err(to_grpc_status)?;
+        let budget = req.budget.as_ref().map(from_proto_resource_limits);
+        self.orchestrator.submit_dag(dag.clone(), None, budget, None).await.map_err(to_grpc_status)?;
 
         tracing::info!(
             dag_id = %dag_id,
@@ -710,6 +712,7 @@ impl ApexOrchestrator for ApexGrpcService {
         } else {
             DagStatus::Pending
         };
+        let budget = self.orchestrator.dag_budget(dag_id).await;
 
         Ok(Response::new(GetDagStatusResponse {
             dag_id: dag_id.to_string(),
@@ -727,6 +730,8 @@ impl ApexOrchestrator for ApexGrpcService {
             total_tokens: 0, // Would need to aggregate
             total_cost_microdollars: 0,
             duration_ms: 0,
+            budget: budget.as_ref().map(|(limits, _)| to_proto_resource_limits(limits)),
+            budget_remaining: budget.as_ref().map(|(_, remaining)| to_proto_resource_limits(remaining)),
         }))
     }
 
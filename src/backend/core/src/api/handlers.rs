@@ -1,17 +1,39 @@
 //! API request handlers with input validation and sanitization.
 
 use axum::{
-    extract::{Path, State},
-    response::IntoResponse,
+    extract::{OriginalUri, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     Json,
 };
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 use uuid::Uuid;
 
 use super::{AppState, ApiResponse};
+use super::v2::{BatchResponse, BatchResult, BatchSummary};
+pub use super::dto::{
+    AgentSummary, BatchApprovalRequest, CostEstimateRequest, CostEstimateResponse, CreateDagRequest,
+    CreateTaskRequest, CreateTemplateRequest, DagDetail, DagEdge, DagExecutionResponse, DagNodeSummary,
+    DagResponse, DagTaskSummary, InstantiateTemplateRequest, IDEMPOTENCY_KEY_HEADER, OutputCostEstimate, Page, TaskResponse,
+    TemplateResponse,
+};
 use super::middleware::{sanitize_string, ValidationErrors};
-use crate::dag::{TaskDAG, Task, TaskId, TaskInput, TaskStatus};
+use crate::dag::{DagProgress, DagStats, TaskDAG, Task, TaskId, TaskInput, TaskStatus};
 use crate::agents::{Agent, AgentId};
+use crate::cache::{Cache, CacheKey, CacheLookup, KeyType};
+use crate::contracts::quota::{month_start, next_month_start};
+use crate::contracts::{OrganizationQuota, OrganizationUsage, QuotaResource, QuotaStatus, ResourceLimits};
+use crate::db::{ApprovalDecision, ApprovalOutcome, AuditLogFilter, Database, DagTemplateRow, TaskRow};
+use crate::error::ApexError;
+use crate::jobs::JobId;
+use crate::orchestrator::{AgentCircuitMetrics, CircuitState, DagSubmission, IdempotencyKey};
+use crate::middleware::auth::{AuthContext, AuthMethod, RequireAuth};
+use crate::middleware::{AuditEntry, AuditLevel};
+use crate::pagination::{Cursor, CursorInfo, PageLinks, PaginationQuery};
+use crate::rbac::{OrganizationId, Permission, PolicyEngine, TenantScope, UserId};
+use crate::routing::{ModelRouter, ModelTier};
+use crate::validation::{Validate, ValidatedQuery, ValidationErrorKind, ValidationResult};
+use crate::websocket::QuotaWarning;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Health Check
@@ -25,26 +47,74 @@ pub async fn health_check() -> impl IntoResponse {
     }))
 }
 
+/// 404 with the usual error envelope.
+///
+/// Also the answer for a resource owned by another organization, so callers
+/// can't probe for the existence of other tenants' IDs.
+fn not_found(message: &str) -> Response {
+    (StatusCode::NOT_FOUND, Json(ApiResponse::<()>::error(message))).into_response()
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Task Handlers
 // ═══════════════════════════════════════════════════════════════════════════════
 
-#[derive(Deserialize)]
-pub struct CreateTaskRequest {
-    pub name: String,
-    pub instruction: String,
-    pub context: Option<serde_json::Value>,
-    pub priority: Option<i32>,
-    pub limits: Option<ResourceLimitsDto>,
+/// Cache key of the tombstone for a task id that doesn't exist under `scope`.
+///
+/// Every tenant's tombstone for the id carries the same tag, so
+/// [`forget_missing_tasks`] can clear them all once the task exists.
+fn missing_task_key(scope: &TenantScope, id: Uuid) -> CacheKey {
+    let tenant = scope
+        .organization_filter()
+        .map_or_else(|| "all".to_string(), |org| org.to_string());
+    CacheKey::new(KeyType::Task)
+        .with_id(id.to_string())
+        .with_segment("missing")
+        .with_segment(tenant)
+        .with_tag(missing_task_tag(id))
+}
+
+fn missing_task_tag(id: Uuid) -> String {
+    format!("missing-task:{}", id)
+}
+
+/// Drop the tombstones of tasks that now exist, so lookups made before they
+/// were created don't keep answering 404 until the tombstones expire.
+async fn forget_missing_tasks(cache: &Cache, ids: impl IntoIterator<Item = TaskId>) {
+    for id in ids {
+        if let Err(e) = cache.invalidate_by_tag(&missing_task_tag(id.0)).await {
+            tracing::debug!(error = %e, task_id = %id.0, "Failed to clear missing task tombstone");
+        }
+    }
+}
+
+/// Fetch a task, remembering misses so clients polling an unknown id don't
+/// reach the database every time.
+///
+/// Found tasks aren't cached since their status changes as they run. Cache
+/// errors fall back to the database.
+async fn find_task(state: &AppState, scope: &TenantScope, id: Uuid) -> crate::error::Result<Option<TaskRow>> {
+    let key = missing_task_key(scope, id);
+    if let Ok(CacheLookup::Negative) = state.cache.lookup::<serde::de::IgnoredAny>(&key).await {
+        return Ok(None);
+    }
+
+    let task = state.db.get_task(TaskId(id), scope).await?;
+    if task.is_none() {
+        if let Err(e) = state.cache.cache_none(&key).await {
+            tracing::debug!(error = %e, "Failed to cache missing task");
+        }
+    }
+    Ok(task)
 }
 
 impl CreateTaskRequest {
-    fn sanitize(&mut self) {
+    pub(super) fn sanitize(&mut self) {
         self.name = sanitize_string(&self.name);
         self.instruction = sanitize_string(&self.instruction);
     }
 
-    fn validate(&self) -> ValidationErrors {
+    pub(super) fn validate(&self) -> ValidationErrors {
         let mut errors = ValidationErrors::new();
         if self.name.is_empty() {
             errors.add("name", "must not be empty");
@@ -77,35 +147,18 @@ impl CreateTaskRequest {
     }
 }
 
-#[derive(Deserialize, Serialize)]
-pub struct ResourceLimitsDto {
-    pub token_limit: Option<u64>,
-    pub cost_limit: Option<f64>,
-    pub api_call_limit: Option<u64>,
-    pub time_limit_seconds: Option<u64>,
-}
-
-#[derive(Serialize)]
-pub struct TaskResponse {
-    pub id: Uuid,
-    pub name: String,
-    pub status: String,
-    pub tokens_used: u64,
-    pub cost_dollars: f64,
-    pub created_at: String,
-}
-
 pub async fn create_task(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    scope: TenantScope,
     Json(mut req): Json<CreateTaskRequest>,
-) -> impl IntoResponse {
+) -> Response {
     req.sanitize();
     let errors = req.validate();
     if !errors.is_empty() {
-        return Json(ApiResponse::error_with_code(
-            serde_json::to_string(&errors).unwrap_or_else(|_| "Validation failed".to_string()),
-            "VALIDATION_ERROR",
-        ));
+        return errors.into_response();
+    }
+    if let Err(e) = enforce_quota(&state, &scope, 1).await {
+        return Json(ApiResponse::<()>::from_apex_error(&e)).into_response();
     }
 
     let input = TaskInput {
@@ -127,16 +180,19 @@ pub async fn create_task(
         tokens_used: task.tokens_used,
         cost_dollars: task.cost_dollars,
         created_at: task.created_at.to_rfc3339(),
+        error: None,
+        error_details: None,
     };
 
-    Json(ApiResponse::success(response))
+    Json(ApiResponse::success(response)).into_response()
 }
 
 pub async fn get_task(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match state.db.get_task(TaskId(id)).await {
+) -> Response {
+    match find_task(&state, &scope, id).await {
         Ok(Some(task)) => {
             let response = TaskResponse {
                 id: task.id,
@@ -145,56 +201,178 @@ pub async fn get_task(
                 tokens_used: task.tokens_used as u64,
                 cost_dollars: task.cost_dollars,
                 created_at: task.created_at.to_rfc3339(),
+                error: task.error,
+                error_details: task.error_details,
             };
-            Json(ApiResponse::success(response))
+            Json(ApiResponse::success(response)).into_response()
         }
-        Ok(None) => Json(ApiResponse::error("Task not found")),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        Ok(None) => not_found("Task not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
     }
 }
 
 pub async fn get_task_status(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match state.db.get_task(TaskId(id)).await {
+) -> Response {
+    match find_task(&state, &scope, id).await {
         Ok(Some(task)) => {
             Json(ApiResponse::success(serde_json::json!({
                 "id": task.id,
                 "status": task.status,
                 "tokens_used": task.tokens_used,
                 "cost_dollars": task.cost_dollars,
-            })))
+            }))).into_response()
         }
-        Ok(None) => Json(ApiResponse::error("Task not found")),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        Ok(None) => not_found("Task not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
     }
 }
 
 pub async fn cancel_task(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match state.db.update_task_status(TaskId(id), TaskStatus::Cancelled).await {
-        Ok(_) => Json(ApiResponse::success(serde_json::json!({
+) -> Response {
+    match state.db.update_task_status(TaskId(id), TaskStatus::Cancelled, None, &scope).await {
+        Ok(true) => Json(ApiResponse::success(serde_json::json!({
             "id": id,
             "status": "cancelled"
-        }))),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        }))).into_response(),
+        Ok(false) => not_found("Task not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RequeueTaskQuery {
+    /// Seconds the task must have been running; defaults to
+    /// `orchestrator.stuck_task_secs`
+    pub stuck_after_secs: Option<u64>,
+}
+
+/// Put a task whose worker died back on the worker queue. The task must be
+/// running past the staleness threshold with no result waiting and retries
+/// left; 409 otherwise. Admin only; audited.
+pub async fn requeue_task(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RequeueTaskQuery>,
+) -> Response {
+    if !auth.has_role("admin") {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Admin role required to requeue tasks", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    let stuck_after = query.stuck_after_secs.unwrap_or(state.config.orchestrator.stuck_task_secs);
+    let started = std::time::Instant::now();
+    let (status, response) = match state
+        .orchestrator
+        .requeue_task(TaskId(id), std::time::Duration::from_secs(stuck_after))
+        .await
+    {
+        Ok(task) => (StatusCode::OK, Json(ApiResponse::success(task)).into_response()),
+        Err(e) => (e.http_status(), (e.http_status(), Json(ApiResponse::<()>::from_apex_error(&e))).into_response()),
+    };
+
+    let path = format!("/api/v1/tasks/{}/requeue", id);
+    if let Err(e) = state.db.insert_audit_entry(&audit_entry(&auth, path, status, started)).await {
+        tracing::error!(task_id = %id, error = %e, "Failed to audit task requeue");
     }
+    response
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
-// DAG Handlers
+// Cost Estimates
 // ═══════════════════════════════════════════════════════════════════════════════
 
-#[derive(Deserialize)]
-pub struct CreateDagRequest {
-    pub name: String,
-    pub tasks: Vec<DagTaskRequest>,
-    pub dependencies: Vec<DependencyRequest>,
+/// Response sizes an estimate is priced at: a short answer, the size the
+/// orchestrator reserves when checking budgets, and a long report.
+const ESTIMATE_OUTPUT_TOKENS: [u32; 3] = [256, 1024, 4096];
+
+impl CostEstimateRequest {
+    fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        if self.instruction.trim().is_empty() {
+            errors.add("instruction", "must not be empty");
+        } else if self.instruction.len() > 50_000 {
+            errors.add("instruction", "must be at most 50,000 characters");
+        }
+        errors
+    }
+}
+
+/// Price `req` on its model (or the routed one) at each of
+/// [`ESTIMATE_OUTPUT_TOKENS`].
+fn estimate_cost(router: &ModelRouter, req: &CostEstimateRequest) -> Result<CostEstimateResponse, ApexError> {
+    let routed_model = router.select_model(&req.instruction);
+    let model = req.model.clone().unwrap_or_else(|| routed_model.clone());
+    let Some(config) = router.get_model(&model) else {
+        let known: Vec<&str> = router.models().iter().map(|m| m.name.as_str()).collect();
+        return Err(ApexError::validation(format!(
+            "Unknown model '{}'; expected one of {}",
+            model,
+            known.join(", ")
+        )));
+    };
+
+    let input = TaskInput {
+        instruction: req.instruction.clone(),
+        context: req.context.clone().unwrap_or(serde_json::Value::Null),
+        ..Default::default()
+    };
+    let prompt = input.prompt();
+    let estimates: Vec<_> = ESTIMATE_OUTPUT_TOKENS
+        .iter()
+        .filter_map(|&output_tokens| router.estimate_dispatch(&model, &prompt, output_tokens))
+        .collect();
+    let outputs: Vec<OutputCostEstimate> = estimates
+        .iter()
+        .map(|e| OutputCostEstimate {
+            output_tokens: e.output_tokens,
+            total_tokens: e.total_tokens(),
+            cost: e.cost,
+        })
+        .collect();
+
+    Ok(CostEstimateResponse {
+        tier: config.tier.clone(),
+        input_tokens: estimates.first().map_or(0, |e| e.input_tokens),
+        cost_min: outputs.iter().map(|o| o.cost).fold(f64::INFINITY, f64::min),
+        cost_max: outputs.iter().map(|o| o.cost).fold(0.0, f64::max),
+        fits_context: estimates.last().is_some_and(|e| e.fits_context),
+        routed_tier: router.get_model(&routed_model).map_or(ModelTier::Standard, |m| m.tier.clone()),
+        routed_model,
+        model,
+        outputs,
+    })
+}
+
+/// Estimate tokens and cost of an instruction before submitting it.
+pub async fn estimate_task_cost(
+    State(state): State<AppState>,
+    Json(mut req): Json<CostEstimateRequest>,
+) -> Response {
+    req.instruction = sanitize_string(&req.instruction);
+    let errors = req.validate();
+    if !errors.is_empty() {
+        return errors.into_response();
+    }
+
+    match estimate_cost(state.orchestrator.model_router(), &req) {
+        Ok(estimate) => Json(ApiResponse::success(estimate)).into_response(),
+        Err(e) => (e.http_status(), Json(ApiResponse::<()>::from_apex_error(&e))).into_response(),
+    }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// DAG Handlers
+// ═══════════════════════════════════════════════════════════════════════════════
+
 impl CreateDagRequest {
     fn sanitize(&mut self) {
         self.name = sanitize_string(&self.name);
@@ -202,6 +380,9 @@ impl CreateDagRequest {
             task.id = sanitize_string(&task.id);
             task.name = sanitize_string(&task.name);
             task.instruction = sanitize_string(&task.instruction);
+            for dep in &mut task.depends_on {
+                *dep = sanitize_string(dep);
+            }
         }
         for dep in &mut self.dependencies {
             dep.from = sanitize_string(&dep.from);
@@ -209,7 +390,8 @@ impl CreateDagRequest {
         }
     }
 
-    fn validate(&self) -> ValidationErrors {
+    /// Field-level checks; graph structure is checked by `TaskDAG::from_spec`.
+    fn validate_fields(&self) -> ValidationErrors {
         let mut errors = ValidationErrors::new();
         if self.name.is_empty() {
             errors.add("name", "must not be empty");
@@ -229,8 +411,18 @@ impl CreateDagRequest {
             if task.instruction.is_empty() {
                 errors.add(format!("tasks[{}].instruction", i), "must not be empty");
             }
+            if let Some(limits) = &task.limits {
+                validate_limits(&mut errors, &format!("tasks[{}].limits", i), limits);
+            }
         }
         let task_ids: std::collections::HashSet<&str> = self.tasks.iter().map(|t| t.id.as_str()).collect();
+        for (i, task) in self.tasks.iter().enumerate() {
+            for (j, dep) in task.depends_on.iter().enumerate() {
+                if !task_ids.contains(dep.as_str()) {
+                    errors.add(format!("tasks[{}].depends_on[{}]", i, j), format!("references unknown task '{}'", dep));
+                }
+            }
+        }
         for (i, dep) in self.dependencies.iter().enumerate() {
             if !task_ids.contains(dep.from.as_str()) {
                 errors.add(format!("dependencies[{}].from", i), format!("references unknown task '{}'", dep.from));
@@ -242,197 +434,515 @@ impl CreateDagRequest {
                 errors.add(format!("dependencies[{}]", i), "task cannot depend on itself");
             }
         }
+        if let Some(budget) = &self.budget {
+            validate_limits(&mut errors, "budget", budget);
+        }
         errors
     }
 }
 
-#[derive(Deserialize)]
-pub struct DagTaskRequest {
-    pub id: String,
-    pub name: String,
-    pub instruction: String,
+/// Every limit must be positive: a zero limit would fail the task before it starts.
+fn validate_limits(errors: &mut ValidationErrors, field: &str, limits: &ResourceLimits) {
+    if limits.token_limit == 0 {
+        errors.add(format!("{}.token_limit", field), "must be greater than 0");
+    }
+    if !(limits.cost_limit.is_finite() && limits.cost_limit > 0.0) {
+        errors.add(format!("{}.cost_limit", field), "must be greater than 0");
+    }
+    if limits.api_call_limit == 0 {
+        errors.add(format!("{}.api_call_limit", field), "must be greater than 0");
+    }
+    if limits.time_limit_seconds == 0 {
+        errors.add(format!("{}.time_limit_seconds", field), "must be greater than 0");
+    }
 }
 
-#[derive(Deserialize)]
-pub struct DependencyRequest {
-    pub from: String,
-    pub to: String,
+/// The `Idempotency-Key` of a DAG submission, scoped to the caller's organization.
+fn idempotency_key(headers: &HeaderMap, scope: &TenantScope) -> crate::error::Result<Option<IdempotencyKey>> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| ApexError::validation("Idempotency key must be printable ASCII"))?;
+    IdempotencyKey::new(key, scope).map(Some)
 }
 
-#[derive(Serialize)]
-pub struct DagResponse {
-    pub id: Uuid,
-    pub name: String,
-    pub task_count: usize,
-    pub status: String,
+/// `response` for the outcome of submitting its DAG. An existing DAG is
+/// described as stored, not as the retried request.
+async fn submitted(
+    state: &AppState,
+    scope: &TenantScope,
+    mut response: DagResponse,
+    submission: DagSubmission,
+) -> crate::error::Result<DagResponse> {
+    response.id = submission.dag_id();
+    response.existing = submission.is_existing();
+    if response.existing {
+        let dag = state
+            .db
+            .get_dag(response.id, scope)
+            .await?
+            .ok_or_else(|| ApexError::not_found("DAG", response.id.to_string()))?;
+        response.name = dag.name;
+        response.task_count = dag.total_tasks as usize;
+        response.status = dag.status;
+    }
+    Ok(response)
 }
 
+/// Submit a DAG. Retries carrying the same `Idempotency-Key` header get the
+/// first submission's DAG back, marked `existing`.
 pub async fn create_dag(
     State(state): State<AppState>,
+    scope: TenantScope,
+    headers: HeaderMap,
     Json(mut req): Json<CreateDagRequest>,
-) -> impl IntoResponse {
+) -> Response {
     req.sanitize();
-    let errors = req.validate();
+    let errors = req.validate_fields();
     if !errors.is_empty() {
-        return Json(ApiResponse::error_with_code(
-            serde_json::to_string(&errors).unwrap_or_else(|_| "Validation failed".to_string()),
-            "VALIDATION_ERROR",
-        ));
+        return errors.into_response();
     }
-
-    let mut dag = TaskDAG::new(&req.name);
-    let mut task_map = std::collections::HashMap::new();
-
-    for task_req in &req.tasks {
-        let input = TaskInput {
-            instruction: task_req.instruction.clone(),
-            context: serde_json::Value::Null,
-            parameters: serde_json::Value::Null,
-            artifacts: vec![],
-        };
-        let task = Task::new(&task_req.name, input);
-        let task_id = task.id;
-
-        if let Err(e) = dag.add_task(task) {
-            return Json(ApiResponse::from_apex_error(&e));
-        }
-
-        task_map.insert(task_req.id.clone(), task_id);
+    let key = match idempotency_key(&headers, &scope) {
+        Ok(key) => key,
+        Err(e) => return Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    };
+    if let Err(e) = enforce_quota(&state, &scope, req.tasks.len() as u64).await {
+        return Json(ApiResponse::<()>::from_apex_error(&e)).into_response();
     }
 
-    for dep in &req.dependencies {
-        let from_id = match task_map.get(&dep.from) {
-            Some(id) => *id,
-            None => return Json(ApiResponse::error(format!("Task not found: {}", dep.from))),
-        };
-        let to_id = match task_map.get(&dep.to) {
-            Some(id) => *id,
-            None => return Json(ApiResponse::error(format!("Task not found: {}", dep.to))),
-        };
-
-        if let Err(e) = dag.add_dependency(from_id, to_id) {
-            return Json(ApiResponse::from_apex_error(&e));
-        }
-    }
+    let dag = match TaskDAG::from_spec(&req) {
+        Ok(dag) => dag,
+        Err(e) => return Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    };
 
     let response = DagResponse {
         id: dag.id(),
         name: dag.name().to_string(),
         task_count: req.tasks.len(),
         status: "created".to_string(),
+        existing: false,
     };
 
-    match state.orchestrator.submit_dag(dag).await {
-        Ok(_) => Json(ApiResponse::success(response)),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+    let task_ids = dag.topological_order().unwrap_or_default();
+    match state.orchestrator.submit_dag(dag, scope.owner(), req.budget.clone(), key.as_ref()).await {
+        Ok(submission) => {
+            forget_missing_tasks(&state.cache, task_ids).await;
+            match submitted(&state, &scope, response, submission).await {
+                Ok(response) => Json(ApiResponse::success(response)).into_response(),
+                Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+            }
+        }
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
     }
 }
 
 pub async fn get_dag(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match state.db.get_dag(id).await {
+) -> Response {
+    match state.db.get_dag(id, &scope).await {
         Ok(Some(dag)) => {
             let nodes = state.db.get_dag_nodes(id).await.unwrap_or_default();
             let tasks = state.db.get_dag_tasks(id).await.unwrap_or_default();
 
-            let edges: Vec<serde_json::Value> = nodes
+            let edges = nodes
                 .iter()
-                .filter_map(|node| {
-                    node.depends_on.as_ref().map(|deps| {
-                        deps.iter()
-                            .map(|dep| {
-                                serde_json::json!({
-                                    "from": dep,
-                                    "to": node.id,
-                                })
-                            })
-                            .collect::<Vec<_>>()
-                    })
+                .flat_map(|node| {
+                    node.depends_on
+                        .iter()
+                        .flatten()
+                        .map(move |dep| DagEdge { from: dep.clone(), to: node.id })
                 })
-                .flatten()
                 .collect();
 
-            Json(ApiResponse::success(serde_json::json!({
-                "id": dag.id,
-                "name": dag.name,
-                "status": dag.status,
-                "metadata": dag.metadata,
-                "created_at": dag.created_at.to_rfc3339(),
-                "started_at": dag.started_at.map(|t| t.to_rfc3339()),
-                "completed_at": dag.completed_at.map(|t| t.to_rfc3339()),
-                "nodes": nodes.iter().map(|n| serde_json::json!({
-                    "id": n.id,
-                    "task_template": n.task_template,
-                    "depends_on": n.depends_on,
-                    "is_entry": n.is_entry,
-                    "is_exit": n.is_exit,
-                })).collect::<Vec<_>>(),
-                "edges": edges,
-                "tasks": tasks.iter().map(|t| serde_json::json!({
-                    "id": t.id,
-                    "name": t.name,
-                    "status": t.status,
-                    "tokens_used": t.tokens_used,
-                    "cost_dollars": t.cost_dollars,
-                })).collect::<Vec<_>>(),
-            })))
+            Json(ApiResponse::success(DagDetail {
+                id: dag.id,
+                name: dag.name,
+                status: dag.status,
+                metadata: dag.metadata,
+                created_at: dag.created_at.to_rfc3339(),
+                started_at: dag.started_at.map(|t| t.to_rfc3339()),
+                completed_at: dag.completed_at.map(|t| t.to_rfc3339()),
+                nodes: nodes.iter().map(|n| DagNodeSummary {
+                    id: n.id,
+                    task_template: n.task_template.clone(),
+                    depends_on: n.depends_on.clone(),
+                    is_entry: n.is_entry,
+                    is_exit: n.is_exit,
+                }).collect(),
+                edges,
+                tasks: tasks.into_iter().map(|t| DagTaskSummary {
+                    id: t.id,
+                    name: t.name,
+                    status: t.status,
+                    tokens_used: t.tokens_used,
+                    cost_dollars: t.cost_dollars,
+                }).collect(),
+            })).into_response()
         }
-        Ok(None) => Json(ApiResponse::error("DAG not found")),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        Ok(None) => not_found("DAG not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+/// Refuse to act on DAG `id` unless it is stored under `scope`.
+///
+/// Unscoped callers may act on any DAG, including ones never persisted.
+async fn check_dag_owner(state: &AppState, scope: &TenantScope, id: Uuid) -> std::result::Result<(), Response> {
+    if scope.is_all() {
+        return Ok(());
+    }
+    match state.db.get_dag(id, scope).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(not_found("DAG not found")),
+        Err(e) => Err(Json(ApiResponse::<()>::from_apex_error(&e)).into_response()),
     }
 }
 
 pub async fn execute_dag(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
+) -> Response {
+    if let Err(response) = check_dag_owner(&state, &scope, id).await {
+        return response;
+    }
     match state.orchestrator.execute_dag(id).await {
-        Ok(result) => Json(ApiResponse::success(serde_json::json!({
-            "dag_id": result.dag_id,
-            "status": format!("{:?}", result.status),
-            "tasks_completed": result.tasks_completed,
-            "tasks_failed": result.tasks_failed,
-            "total_tokens": result.total_tokens,
-            "total_cost": result.total_cost,
-            "duration_ms": result.duration_ms,
-        }))),
+        Ok(result) => Json(ApiResponse::success(DagExecutionResponse {
+            dag_id: result.dag_id,
+            status: format!("{:?}", result.status),
+            tasks_completed: result.tasks_completed,
+            tasks_failed: result.tasks_failed,
+            total_tokens: result.total_tokens,
+            total_cost: result.total_cost,
+            duration_ms: result.duration_ms,
+        })).into_response(),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+/// Stop dispatching new tasks of a running DAG; in-flight tasks finish.
+pub async fn pause_dag(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if let Err(response) = check_dag_owner(&state, &scope, id).await {
+        return response;
+    }
+    match state.orchestrator.pause_dag(id) {
+        Ok(()) => Json(ApiResponse::success(serde_json::json!({ "id": id, "status": "paused" }))).into_response(),
+        Err(e) => (e.http_status(), Json(ApiResponse::<()>::from_apex_error(&e))).into_response(),
+    }
+}
+
+/// Resume a paused DAG where it left off.
+pub async fn resume_dag(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if let Err(response) = check_dag_owner(&state, &scope, id).await {
+        return response;
+    }
+    match state.orchestrator.resume_dag(id) {
+        Ok(()) => Json(ApiResponse::success(serde_json::json!({ "id": id, "status": "running" }))).into_response(),
+        Err(e) => (e.http_status(), Json(ApiResponse::<()>::from_apex_error(&e))).into_response(),
+    }
+}
+
+/// Cancel a DAG: tasks not yet run are cancelled right away, and the
+/// request is accepted while its in-flight tasks drain in the background.
+pub async fn cancel_dag(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if let Err(response) = check_dag_owner(&state, &scope, id).await {
+        return response;
+    }
+    match state.orchestrator.begin_cancel_dag(id).await {
+        Ok(cancelled) => {
+            let orchestrator = state.orchestrator.clone();
+            tokio::spawn(async move { orchestrator.finish_cancel_dag(id).await });
+            (
+                StatusCode::ACCEPTED,
+                Json(ApiResponse::success(serde_json::json!({
+                    "id": id,
+                    "status": "cancelling",
+                    "tasks_cancelled": cancelled
+                }))),
+            ).into_response()
+        }
+        Err(e) => (e.http_status(), Json(ApiResponse::<()>::from_apex_error(&e))).into_response(),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// DAG Template Handlers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+impl CreateTemplateRequest {
+    fn sanitize(&mut self) {
+        self.name = sanitize_string(&self.name);
+        self.description = self.description.as_deref().map(sanitize_string);
+        self.spec.sanitize();
+    }
+
+    /// Field-level checks on the template and its spec.
+    fn validate_fields(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        if self.name.is_empty() {
+            errors.add("name", "must not be empty");
+        } else if self.name.len() > 255 {
+            errors.add("name", "must be at most 255 characters");
+        }
+        for error in self.spec.validate_fields().errors {
+            errors.add(format!("spec.{}", error.field), error.message);
+        }
+        errors
+    }
+}
+
+fn template_response(row: DagTemplateRow) -> crate::error::Result<TemplateResponse> {
+    let template = row.template()?;
+    Ok(TemplateResponse {
+        id: row.id,
+        required_parameters: template.required_parameters(),
+        name: template.name,
+        description: template.description,
+        spec: template.spec,
+        defaults: template.defaults,
+        created_at: row.created_at.to_rfc3339(),
+        updated_at: row.updated_at.to_rfc3339(),
+    })
+}
+
+/// Sanitize and check a template body, returning the response for a rejected one.
+fn check_template(req: &mut CreateTemplateRequest) -> std::result::Result<(), Response> {
+    req.sanitize();
+    let errors = req.validate_fields();
+    if !errors.is_empty() {
+        return Err(errors.into_response());
+    }
+    req.validate()
+        .map_err(|e| Json(ApiResponse::<()>::from_apex_error(&e)).into_response())
+}
+
+pub async fn create_template(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Json(mut req): Json<CreateTemplateRequest>,
+) -> Response {
+    if let Err(response) = check_template(&mut req) {
+        return response;
+    }
+
+    match state.db.insert_dag_template(Uuid::new_v4(), &req, &scope).await.and_then(template_response) {
+        Ok(template) => (StatusCode::CREATED, Json(ApiResponse::success(template))).into_response(),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+pub async fn list_templates(
+    State(state): State<AppState>,
+    scope: TenantScope,
+) -> impl IntoResponse {
+    let templates = state.db.list_dag_templates(&scope).await.and_then(|rows| {
+        rows.into_iter().map(template_response).collect::<crate::error::Result<Vec<_>>>()
+    });
+    match templates {
+        Ok(templates) => Json(ApiResponse::success(templates)),
         Err(e) => Json(ApiResponse::from_apex_error(&e)),
     }
 }
 
+pub async fn get_template(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<Uuid>,
+) -> Response {
+    match state.db.get_dag_template(id, &scope).await {
+        Ok(Some(row)) => match template_response(row) {
+            Ok(template) => Json(ApiResponse::success(template)).into_response(),
+            Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+        },
+        Ok(None) => not_found("Template not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+pub async fn update_template(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<Uuid>,
+    Json(mut req): Json<CreateTemplateRequest>,
+) -> Response {
+    if let Err(response) = check_template(&mut req) {
+        return response;
+    }
+
+    match state.db.update_dag_template(id, &req, &scope).await {
+        Ok(Some(row)) => match template_response(row) {
+            Ok(template) => Json(ApiResponse::success(template)).into_response(),
+            Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+        },
+        Ok(None) => not_found("Template not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+pub async fn delete_template(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<Uuid>,
+) -> Response {
+    match state.db.delete_dag_template(id, &scope).await {
+        Ok(true) => Json(ApiResponse::success(serde_json::json!({"id": id, "status": "deleted"}))).into_response(),
+        Ok(false) => not_found("Template not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+/// Substitute the parameters into a stored template and submit the resulting DAG.
+///
+/// Missing required parameters and unknown ones are rejected before anything
+/// is submitted, as is a rendered spec that fails the checks of [`create_dag`].
+/// An `Idempotency-Key` header works as for [`create_dag`].
+pub async fn instantiate_template(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    headers: HeaderMap,
+    Path(template_id): Path<Uuid>,
+    Json(req): Json<InstantiateTemplateRequest>,
+) -> Response {
+    let key = match idempotency_key(&headers, &scope) {
+        Ok(key) => key,
+        Err(e) => return Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    };
+    let template = match state.db.get_dag_template(template_id, &scope).await {
+        Ok(Some(row)) => row.template(),
+        Ok(None) => return not_found("Template not found"),
+        Err(e) => Err(e),
+    };
+    let mut spec = match template.and_then(|t| t.render(&req.params)) {
+        Ok(spec) => spec,
+        Err(e) => return Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    };
+    // Parameters are caller input, so the rendered spec is checked the same
+    // way as one submitted directly
+    spec.sanitize();
+    let errors = spec.validate_fields();
+    if !errors.is_empty() {
+        return errors.into_response();
+    }
+    let budget = spec.budget.clone();
+    let dag = match TaskDAG::from_spec(&spec) {
+        Ok(dag) => dag,
+        Err(e) => return Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    };
+
+    let task_count = dag.stats().total;
+    if let Err(e) = enforce_quota(&state, &scope, task_count as u64).await {
+        return Json(ApiResponse::<()>::from_apex_error(&e)).into_response();
+    }
+
+    let response = DagResponse {
+        id: dag.id(),
+        name: dag.name().to_string(),
+        task_count,
+        status: "created".to_string(),
+        existing: false,
+    };
+
+    let task_ids = dag.topological_order().unwrap_or_default();
+    match state.orchestrator.submit_dag(dag, scope.owner(), budget, key.as_ref()).await {
+        Ok(submission) => {
+            forget_missing_tasks(&state.cache, task_ids).await;
+            match submitted(&state, &scope, response, submission).await {
+                Ok(response) => Json(ApiResponse::success(response)).into_response(),
+                Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+            }
+        }
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+/// DAG status with task counts, percent complete, spend so far, remaining budget and running tasks.
+///
+/// Live DAGs are read from the orchestrator; finished ones are rebuilt from the database.
+/// In-memory DAGs carry no owner, so scoped callers only see DAGs that are persisted
+/// under their organization.
 pub async fn get_dag_status(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match state.db.get_dag(id).await {
-        Ok(Some(dag)) => {
-            let tasks = state.db.get_dag_tasks(id).await.unwrap_or_default();
-            let total = tasks.len();
-            let completed = tasks.iter().filter(|t| t.status == "completed").count();
-            let failed = tasks.iter().filter(|t| t.status == "failed").count();
-            let running = tasks.iter().filter(|t| t.status == "running").count();
-            let pending = tasks.iter().filter(|t| t.status == "pending" || t.status == "ready").count();
+) -> Response {
+    let dag = match state.db.get_dag(id, &scope).await {
+        Ok(dag) => dag,
+        Err(e) => return Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    };
+    if dag.is_none() && !scope.is_all() {
+        return not_found("DAG not found");
+    }
 
-            Json(ApiResponse::success(serde_json::json!({
-                "id": dag.id,
-                "name": dag.name,
-                "status": dag.status,
-                "started_at": dag.started_at.map(|t| t.to_rfc3339()),
-                "completed_at": dag.completed_at.map(|t| t.to_rfc3339()),
-                "tasks": {
-                    "total": total,
-                    "completed": completed,
-                    "failed": failed,
-                    "running": running,
-                    "pending": pending,
+    let live = state.orchestrator.active_dag_progress(id).await;
+    let budget = state.orchestrator.dag_budget(id).await;
+
+    let (name, progress) = match (&dag, live) {
+        (_, Some((name, progress))) => (name, progress),
+        (Some(dag), None) => {
+            let tasks = state.db.get_dag_tasks(id).await.unwrap_or_default();
+            let mut stats = DagStats::default();
+            let mut running = Vec::new();
+            for task in &tasks {
+                if let Ok(status) = task.status.parse::<TaskStatus>() {
+                    if status == TaskStatus::Running {
+                        running.push(TaskId(task.id));
+                    }
+                    stats.record(&status);
                 }
-            })))
+            }
+            let tokens = tasks.iter().map(|t| t.tokens_used.max(0) as u64).sum();
+            let cost = tasks.iter().map(|t| t.cost_dollars).sum();
+            (dag.name.clone(), DagProgress::new(stats, tokens, cost, running))
         }
-        Ok(None) => Json(ApiResponse::error("DAG not found")),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
-    }
+        (None, None) => return not_found("DAG not found"),
+    };
+
+    let status = match &dag {
+        _ if state.orchestrator.is_dag_paused(id) => "paused".to_string(),
+        Some(dag) => dag.status.clone(),
+        None if progress.stats.pending + progress.stats.ready == progress.stats.total => "pending".to_string(),
+        None => "running".to_string(),
+    };
+
+    Json(ApiResponse::success(serde_json::json!({
+        "id": id,
+        "name": name,
+        "status": status,
+        "started_at": dag.as_ref().and_then(|d| d.started_at).map(|t| t.to_rfc3339()),
+        "completed_at": dag.as_ref().and_then(|d| d.completed_at).map(|t| t.to_rfc3339()),
+        "tasks": {
+            "total": progress.stats.total,
+            "completed": progress.stats.completed,
+            "failed": progress.stats.failed,
+            "running": progress.stats.running,
+            "pending": progress.stats.pending + progress.stats.ready,
+        },
+        "stats": progress.stats,
+        "percent_complete": progress.percent_complete,
+        "tokens_used": progress.tokens_used,
+        "cost_dollars": progress.cost_dollars,
+        "running_task_ids": progress.running_task_ids,
+        "budget": budget.map(|(limits, remaining)| serde_json::json!({
+            "limits": limits,
+            "remaining": remaining,
+        })),
+    }))).into_response()
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -484,44 +994,109 @@ impl RegisterAgentRequest {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Keyset Pagination
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Query parameters of a keyset-paged list, newest first.
+///
+/// Only `created_at` ordering is supported and `after` must be a cursor
+/// handed out by a previous page.
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct KeysetQuery(pub PaginationQuery);
+
+impl Validate for KeysetQuery {
+    fn validate(&self) -> ValidationResult<()> {
+        let mut errors = match self.0.validate_fields(Some(&["created_at"])) {
+            Ok(()) => crate::validation::ValidationErrors::new(),
+            Err(errors) => errors,
+        };
+        // Decodable, but not a keyset cursor (e.g. an offset cursor)
+        let decodes = self.0.after.as_deref().is_some_and(|c| Cursor::decode(c).is_ok());
+        if decodes && keyset_after(&self.0).is_none() {
+            errors.add_with_message(
+                "after",
+                ValidationErrorKind::Custom { code: "invalid_cursor".to_string() },
+                "Cursor is not a valid list position",
+            );
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+/// Decode the `after` cursor of a validated list request into a `(created_at, id)` position.
+fn keyset_after(query: &PaginationQuery) -> Option<(chrono::DateTime<chrono::Utc>, Uuid)> {
+    Cursor::decode(query.after.as_deref()?).ok()?.keyset_position()
+}
+
+/// Drop the probe row fetched past `limit` and describe the page.
+fn keyset_page<T>(
+    mut rows: Vec<T>,
+    limit: usize,
+    has_previous: bool,
+    position: impl Fn(&T) -> (chrono::DateTime<chrono::Utc>, Uuid),
+) -> (Vec<T>, CursorInfo) {
+    let has_next = rows.len() > limit;
+    rows.truncate(limit);
+
+    let encode = |row: &T| {
+        let (created_at, id) = position(row);
+        Cursor::keyset(created_at, id).encode().ok()
+    };
+    let info = CursorInfo::new(
+        rows.first().and_then(encode),
+        rows.last().and_then(encode),
+        has_previous,
+        has_next,
+    );
+    (rows, info)
+}
+
+/// List agents newest first, paged with `?after=<end_cursor>&limit=N`.
+///
+/// The `Link` header points at the first and next pages.
 pub async fn list_agents(
     State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    scope: TenantScope,
+    ValidatedQuery(KeysetQuery(query)): ValidatedQuery<KeysetQuery>,
 ) -> impl IntoResponse {
-    match state.db.get_agents().await {
-        Ok(agents) => {
-            let agents: Vec<serde_json::Value> = agents.iter().map(|a| {
-                serde_json::json!({
-                    "id": a.id,
-                    "name": a.name,
-                    "model": a.model,
-                    "status": a.status,
-                    "current_load": a.current_load,
-                    "max_load": a.max_load,
-                    "success_rate": if a.success_count + a.failure_count > 0 {
-                        a.success_count as f64 / (a.success_count + a.failure_count) as f64
-                    } else {
-                        1.0
-                    },
-                    "reputation_score": a.reputation_score,
-                })
+    let after = keyset_after(&query);
+    let limit = query.effective_limit() as usize;
+
+    match state.db.get_agents_page(after, limit as i64 + 1, &scope).await {
+        Ok(rows) => {
+            let (agents, page_info) = keyset_page(rows, limit, after.is_some(), |a| (a.created_at, a.id));
+            let links = PageLinks::for_cursors(&uri, &page_info, "after");
+            let items = agents.into_iter().map(|a| AgentSummary {
+                success_rate: if a.success_count + a.failure_count > 0 {
+                    a.success_count as f64 / (a.success_count + a.failure_count) as f64
+                } else {
+                    1.0
+                },
+                id: a.id,
+                name: a.name,
+                model: a.model,
+                status: a.status,
+                current_load: a.current_load,
+                max_load: a.max_load,
+                reputation_score: a.reputation_score,
             }).collect();
-            Json(ApiResponse::success(agents))
+            (links, Json(ApiResponse::success(Page { items, page_info })))
         }
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        Err(e) => (PageLinks::new(), Json(ApiResponse::from_apex_error(&e))),
     }
 }
 
 pub async fn register_agent(
     State(state): State<AppState>,
     Json(mut req): Json<RegisterAgentRequest>,
-) -> impl IntoResponse {
+) -> Response {
     req.sanitize();
     let errors = req.validate();
     if !errors.is_empty() {
-        return Json(ApiResponse::error_with_code(
-            serde_json::to_string(&errors).unwrap_or_else(|_| "Validation failed".to_string()),
-            "VALIDATION_ERROR",
-        ));
+        return errors.into_response();
     }
 
     let mut agent = Agent::new(&req.name, &req.model);
@@ -541,14 +1116,15 @@ pub async fn register_agent(
         "name": stats.name,
         "model": stats.model,
         "status": "registered"
-    })))
+    }))).into_response()
 }
 
 pub async fn get_agent(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match state.db.get_agent(id).await {
+) -> Response {
+    match state.db.get_agent(id, &scope).await {
         Ok(Some(agent)) => {
             let success_rate = if agent.success_count + agent.failure_count > 0 {
                 agent.success_count as f64 / (agent.success_count + agent.failure_count) as f64
@@ -572,30 +1148,40 @@ pub async fn get_agent(
                 "reputation_score": agent.reputation_score,
                 "created_at": agent.created_at.to_rfc3339(),
                 "last_active_at": agent.last_active_at.map(|t| t.to_rfc3339()),
-            })))
+            }))).into_response()
         }
-        Ok(None) => Json(ApiResponse::error("Agent not found")),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        Ok(None) => not_found("Agent not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
     }
 }
 
 pub async fn remove_agent(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    state.orchestrator.deregister_agent(AgentId(id));
-    match state.db.delete_agent(id).await {
-        Ok(true) => Json(ApiResponse::success(serde_json::json!({"id": id, "status": "removed"}))),
-        Ok(false) => Json(ApiResponse::error("Agent not found")),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+) -> Response {
+    match state.db.delete_agent(id, &scope).await {
+        Ok(true) => {
+            state.orchestrator.deregister_agent(AgentId(id));
+            Json(ApiResponse::success(serde_json::json!({"id": id, "status": "removed"}))).into_response()
+        }
+        Ok(false) => {
+            // Agents that were never persisted have no owner; only an unscoped caller may drop them
+            if scope.is_all() {
+                state.orchestrator.deregister_agent(AgentId(id));
+            }
+            not_found("Agent not found")
+        }
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
     }
 }
 
 pub async fn get_agent_stats(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match state.db.get_agent(id).await {
+) -> Response {
+    match state.db.get_agent(id, &scope).await {
         Ok(Some(agent)) => {
             let tasks_completed = agent.success_count + agent.failure_count;
             let success_rate = if tasks_completed > 0 {
@@ -622,10 +1208,67 @@ pub async fn get_agent_stats(
                 "reputation_score": agent.reputation_score,
                 "current_load": agent.current_load,
                 "max_load": agent.max_load,
-            })))
+            }))).into_response()
         }
-        Ok(None) => Json(ApiResponse::error("Agent not found")),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        Ok(None) => not_found("Agent not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AgentHistoryQuery {
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Performance snapshots for an agent. Defaults to the last 7 days.
+pub async fn get_agent_history(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<Uuid>,
+    Query(query): Query<AgentHistoryQuery>,
+) -> Response {
+    let to = query.to.unwrap_or_else(chrono::Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(7));
+    if from >= to {
+        return Json(ApiResponse::<()>::error_with_code("'from' must be before 'to'", "VALIDATION_ERROR")).into_response();
+    }
+
+    match state.db.get_agent(id, &scope).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return not_found("Agent not found"),
+        Err(e) => return Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+
+    match state.db.get_agent_history(id, from, to).await {
+        Ok(snapshots) => {
+            let tasks_completed: i64 = snapshots.iter().map(|s| s.tasks_completed).sum();
+            let tasks_failed: i64 = snapshots.iter().map(|s| s.tasks_failed).sum();
+            let finished = tasks_completed + tasks_failed;
+            let success_rate = (finished > 0).then(|| tasks_completed as f64 / finished as f64);
+            // Weight each window's average by the number of tasks it covered.
+            let (weighted_ms, weight) = snapshots.iter()
+                .filter_map(|s| s.avg_duration_ms.map(|ms| (ms, (s.tasks_completed + s.tasks_failed) as f64)))
+                .fold((0.0, 0.0), |(acc, n), (ms, w)| (acc + ms * w, n + w));
+            let avg_duration_ms = (weight > 0.0).then(|| weighted_ms / weight);
+
+            Json(ApiResponse::success(serde_json::json!({
+                "agent_id": id,
+                "from": from.to_rfc3339(),
+                "to": to.to_rfc3339(),
+                "snapshots": snapshots,
+                "summary": {
+                    "tasks_completed": tasks_completed,
+                    "tasks_failed": tasks_failed,
+                    "success_rate": success_rate,
+                    "avg_duration_ms": avg_duration_ms,
+                    "tokens_used": snapshots.iter().map(|s| s.tokens_used).sum::<i64>(),
+                    "cost_dollars": snapshots.iter().map(|s| s.cost_dollars).sum::<f64>(),
+                    "reputation_score": snapshots.last().map(|s| s.reputation_score),
+                },
+            }))).into_response()
+        }
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
     }
 }
 
@@ -633,11 +1276,22 @@ pub async fn get_agent_stats(
 // Contract Handlers
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// List contracts newest first, paged with `?after=<end_cursor>&limit=N`.
+///
+/// The `Link` header points at the first and next pages.
 pub async fn list_contracts(
     State(state): State<AppState>,
+    OriginalUri(uri): OriginalUri,
+    scope: TenantScope,
+    ValidatedQuery(KeysetQuery(query)): ValidatedQuery<KeysetQuery>,
 ) -> impl IntoResponse {
-    match state.db.get_contracts(100, 0).await {
-        Ok(contracts) => {
+    let after = keyset_after(&query);
+    let limit = query.effective_limit() as usize;
+
+    match state.db.get_contracts_page(after, limit as i64 + 1, &scope).await {
+        Ok(rows) => {
+            let (contracts, page_info) = keyset_page(rows, limit, after.is_some(), |c| (c.created_at, c.id));
+            let links = PageLinks::for_cursors(&uri, &page_info, "after");
             let contracts: Vec<serde_json::Value> = contracts.iter().map(|c| {
                 serde_json::json!({
                     "id": c.id,
@@ -655,17 +1309,21 @@ pub async fn list_contracts(
                     "expires_at": c.expires_at.map(|t| t.to_rfc3339()),
                 })
             }).collect();
-            Json(ApiResponse::success(contracts))
+            (links, Json(ApiResponse::success(serde_json::json!({
+                "items": contracts,
+                "page_info": page_info,
+            }))))
         }
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        Err(e) => (PageLinks::new(), Json(ApiResponse::from_apex_error(&e))),
     }
 }
 
 pub async fn get_contract(
     State(state): State<AppState>,
+    scope: TenantScope,
     Path(id): Path<Uuid>,
-) -> impl IntoResponse {
-    match state.db.get_contract(id).await {
+) -> Response {
+    match state.db.get_contract(id, &scope).await {
         Ok(Some(contract)) => {
             Json(ApiResponse::success(serde_json::json!({
                 "id": contract.id,
@@ -682,11 +1340,622 @@ pub async fn get_contract(
                 "status": contract.status,
                 "created_at": contract.created_at.to_rfc3339(),
                 "expires_at": contract.expires_at.map(|t| t.to_rfc3339()),
-            })))
+            }))).into_response()
         }
-        Ok(None) => Json(ApiResponse::error("Contract not found")),
-        Err(e) => Json(ApiResponse::from_apex_error(&e)),
+        Ok(None) => not_found("Contract not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Organization Quotas
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// What the organization in `scope` has run since `since`.
+async fn organization_usage(
+    db: &Database,
+    scope: &TenantScope,
+    since: chrono::DateTime<chrono::Utc>,
+) -> crate::error::Result<OrganizationUsage> {
+    let stats = db.get_system_stats(scope, Some(since)).await?;
+    Ok(OrganizationUsage {
+        tasks: stats.total_tasks,
+        tokens: stats.total_tokens,
+        cost: stats.total_cost,
+    })
+}
+
+/// Refuse `new_tasks` more tasks once the caller's organization has used up a
+/// monthly quota, and warn the organization's room about quotas past the soft
+/// limit, once per resource and month.
+///
+/// Unscoped callers (admins, or a server without auth) and tenants without an
+/// organization row aren't subject to quotas.
+pub(crate) async fn enforce_quota(
+    state: &AppState,
+    scope: &TenantScope,
+    new_tasks: u64,
+) -> crate::error::Result<()> {
+    let Some(org_id) = scope.owner() else {
+        return Ok(());
+    };
+    let quota = match state.db.get_organization_quota(org_id).await? {
+        Some(quota) if quota != OrganizationQuota::default() => quota,
+        _ => return Ok(()),
+    };
+
+    let now = chrono::Utc::now();
+    let period_start = month_start(now);
+    let mut usage = organization_usage(&state.db, scope, period_start).await?;
+    quota.check(&org_id.to_string(), &usage, new_tasks)?;
+
+    // Tasks are counted and compared in one locked step, so concurrent
+    // requests can't each pass the check above and together overrun the cap
+    usage.tasks += new_tasks;
+    if let Some(limit) = quota.monthly_task_limit {
+        let (tasks, accepted) = state
+            .db
+            .reserve_quota_tasks(org_id, period_start, usage.tasks - new_tasks, new_tasks, limit)
+            .await?;
+        if !accepted {
+            return Err(ApexError::quota_exceeded(
+                org_id.to_string(),
+                QuotaResource::Tasks.as_str(),
+                tasks as f64,
+                limit as f64,
+            ));
+        }
+        usage.tasks = tasks;
+    }
+
+    for status in quota.statuses(&usage).into_iter().filter(QuotaStatus::is_near_limit) {
+        if !state.db.mark_quota_warned(org_id, period_start, status.resource.as_str()).await? {
+            continue;
+        }
+        state.ws.send_quota_warning(QuotaWarning {
+            organization_id: org_id.to_string(),
+            resource: status.resource,
+            used: status.used,
+            limit: status.limit,
+            utilization: status.utilization(),
+            period_end: next_month_start(now),
+            timestamp: now,
+        }).await;
+    }
+    Ok(())
+}
+
+/// Month-to-date usage for an organization, against its quota.
+pub async fn get_organization_usage(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if scope.organization_filter().is_some_and(|own| own != id) {
+        return not_found("Organization not found");
+    }
+
+    let quota = match state.db.get_organization_quota(id).await {
+        Ok(Some(quota)) => quota,
+        Ok(None) => return not_found("Organization not found"),
+        Err(e) => return Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    };
+
+    let now = chrono::Utc::now();
+    let period_start = month_start(now);
+    let org_scope = TenantScope::Organization(OrganizationId::new(id.to_string()));
+    match organization_usage(&state.db, &org_scope, period_start).await {
+        Ok(usage) => {
+            let limits: Vec<serde_json::Value> = quota.statuses(&usage).iter().map(|s| {
+                serde_json::json!({
+                    "resource": s.resource,
+                    "used": s.used,
+                    "limit": s.limit,
+                    "utilization": s.utilization(),
+                    "near_limit": s.is_near_limit(),
+                })
+            }).collect();
+
+            Json(ApiResponse::success(serde_json::json!({
+                "organization_id": id,
+                "period_start": period_start.to_rfc3339(),
+                "period_end": next_month_start(now).to_rfc3339(),
+                "usage": usage,
+                "quota": quota,
+                "limits": limits,
+            }))).into_response()
+        }
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Approval Handlers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Most approvals one batch request may decide.
+const MAX_APPROVAL_BATCH: usize = 100;
+
+/// Whether the policy engine grants the caller `permission`, through their
+/// token's roles or their bindings in their organization, and their
+/// credential's scopes allow it.
+fn is_permitted(engine: &PolicyEngine, auth: &AuthContext, permission: &Permission) -> bool {
+    let org = OrganizationId::new(auth.org_id.as_deref().unwrap_or_default());
+    auth.has_scope(permission)
+        && engine
+            .check_with_roles(&UserId::new(&auth.user_id), &auth.roles, permission, &org)
+            .is_allowed()
+}
+
+impl BatchApprovalRequest {
+    fn sanitize(&mut self) {
+        self.comment = self.comment.as_deref().map(sanitize_string).filter(|c| !c.is_empty());
+        self.reason = self.reason.as_deref().map(sanitize_string).filter(|r| !r.is_empty());
+    }
+
+    fn validate(&self) -> ValidationErrors {
+        let mut errors = ValidationErrors::new();
+        if self.ids.is_empty() {
+            errors.add("ids", "must not be empty");
+        } else if self.ids.len() > MAX_APPROVAL_BATCH {
+            errors.add("ids", format!("must contain at most {} ids", MAX_APPROVAL_BATCH));
+        }
+        if self.decision == ApprovalDecision::Deny && self.reason.is_none() {
+            errors.add("reason", "is required when denying");
+        }
+        errors
+    }
+}
+
+/// An audit log entry for a `POST` to `path` by `auth`, made by the handler
+/// itself rather than the audit middleware.
+fn audit_entry(auth: &AuthContext, path: String, status: StatusCode, started: std::time::Instant) -> AuditEntry {
+    AuditEntry {
+        timestamp: chrono::Utc::now(),
+        request_id: Some(auth.request_id.clone()),
+        method: "POST".to_string(),
+        path,
+        status: status.as_u16(),
+        user_id: Some(auth.user_id.clone()),
+        api_key_id: auth.api_key_id.clone(),
+        client_ip: None,
+        auth_method: Some(match auth.auth_method {
+            AuthMethod::Jwt => "bearer",
+            AuthMethod::ApiKey => "api_key",
+            AuthMethod::Anonymous => "anonymous",
+        }.to_string()),
+        duration_ms: started.elapsed().as_millis() as u64,
+        audit_level: AuditLevel::Standard,
+        user_agent: None,
+    }
+}
+
+/// Approve or deny many approvals in one request.
+///
+/// Each id is decided independently: one that is missing, expired or already
+/// decided is reported in its result without affecting the rest. Every
+/// decision is written to the audit log as if it had been made on its own.
+pub async fn batch_decide_approvals(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Json(mut req): Json<BatchApprovalRequest>,
+) -> Response {
+    if !is_permitted(&state.policy, &auth, &Permission::new("approval", "approve")) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Permission approval:approve required", "FORBIDDEN")),
+        ).into_response();
     }
+
+    req.sanitize();
+    let errors = req.validate();
+    if !errors.is_empty() {
+        return errors.into_response();
+    }
+
+    let note = match req.decision {
+        ApprovalDecision::Approve => req.comment.as_deref(),
+        ApprovalDecision::Deny => req.reason.as_deref(),
+    };
+    let action = match req.decision {
+        ApprovalDecision::Approve => "approve",
+        ApprovalDecision::Deny => "deny",
+    };
+
+    let mut results: Vec<BatchResult<serde_json::Value>> = Vec::with_capacity(req.ids.len());
+    let mut succeeded = 0usize;
+
+    for (i, approval_id) in req.ids.iter().enumerate() {
+        let started = std::time::Instant::now();
+        let outcome = state
+            .db
+            .decide_approval(*approval_id, req.decision, &auth.user_id, note, &scope)
+            .await;
+
+        let (status, error) = match &outcome {
+            Ok(ApprovalOutcome::Decided) => (StatusCode::OK, None),
+            Ok(ApprovalOutcome::NotFound) => (StatusCode::NOT_FOUND, Some("Approval not found".to_string())),
+            Ok(ApprovalOutcome::AlreadyDecided(current)) => {
+                (StatusCode::CONFLICT, Some(format!("Approval already {}", current)))
+            }
+            Ok(ApprovalOutcome::Expired) => (StatusCode::GONE, Some("Approval has expired".to_string())),
+            Err(e) => (e.http_status(), Some(e.user_message().to_string())),
+        };
+
+        let path = format!("/api/v1/approvals/{}/{}", approval_id, action);
+        let entry = audit_entry(&auth, path, status, started);
+        if let Err(e) = state.db.insert_audit_entry(&entry).await {
+            tracing::error!(approval_id = %approval_id, error = %e, "Failed to audit approval decision");
+        }
+
+        if error.is_none() {
+            // Wake the task blocked on this approval and tell the approvals room
+            state.orchestrator.approvals().resolve(*approval_id, req.decision.into());
+            state
+                .ws
+                .send_approval_decided(
+                    approval_id.to_string(),
+                    req.decision == ApprovalDecision::Approve,
+                    auth.user_id.clone(),
+                    note.map(str::to_string),
+                )
+                .await;
+        }
+
+        match error {
+            None => {
+                succeeded += 1;
+                results.push(BatchResult {
+                    index: i,
+                    success: true,
+                    data: Some(serde_json::json!({
+                        "id": approval_id,
+                        "status": req.decision.status(),
+                    })),
+                    error: None,
+                });
+            }
+            Some(error) => results.push(BatchResult {
+                index: i,
+                success: false,
+                data: None,
+                error: Some(error),
+            }),
+        }
+    }
+
+    let failed = req.ids.len() - succeeded;
+    Json(BatchResponse {
+        success: failed == 0,
+        results,
+        summary: BatchSummary {
+            total: req.ids.len(),
+            succeeded,
+            failed,
+        },
+    }).into_response()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Usage Report Handlers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Deserialize)]
+pub struct UsageReportListQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageReportQuery {
+    /// `json` (default) or `csv`
+    pub format: Option<String>,
+}
+
+fn can_read_reports(auth: &AuthContext) -> bool {
+    auth.has_role("admin") && auth.has_scope(&Permission::new("reports", "read"))
+}
+
+fn reports_forbidden() -> Response {
+    (
+        StatusCode::FORBIDDEN,
+        Json(ApiResponse::<()>::error_with_code("Admin role required to read usage reports", "FORBIDDEN")),
+    ).into_response()
+}
+
+/// List generated usage reports, newest period first. Admin only.
+pub async fn list_usage_reports(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    Query(query): Query<UsageReportListQuery>,
+) -> Response {
+    if !can_read_reports(&auth) {
+        return reports_forbidden();
+    }
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 500);
+    let offset = query.offset.unwrap_or(0).max(0);
+    match state.db.list_usage_reports(limit, offset).await {
+        Ok(reports) => Json(ApiResponse::success(reports)).into_response(),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+/// A generated usage report as JSON, or as CSV with `?format=csv`. Admin only.
+pub async fn get_usage_report(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<UsageReportQuery>,
+) -> Response {
+    if !can_read_reports(&auth) {
+        return reports_forbidden();
+    }
+
+    let csv = match query.format.as_deref().map(str::to_ascii_lowercase).as_deref() {
+        None | Some("json") => false,
+        Some("csv") => true,
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(ApiResponse::<()>::error_with_code(
+                    format!("Unknown format '{}', expected 'json' or 'csv'", other),
+                    "VALIDATION_ERROR",
+                )),
+            ).into_response();
+        }
+    };
+
+    match state.db.get_usage_report(id).await {
+        Ok(Some(row)) if csv => {
+            let disposition = format!(
+                "attachment; filename=\"usage-{}-{}.csv\"",
+                row.period,
+                row.period_start.format("%Y-%m-%d"),
+            );
+            (
+                [
+                    (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+                    (header::CONTENT_DISPOSITION, disposition),
+                ],
+                row.csv,
+            ).into_response()
+        }
+        Ok(Some(row)) => Json(ApiResponse::success(row.report)).into_response(),
+        Ok(None) => not_found("Usage report not found"),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Audit Handlers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::Utc>>,
+    pub to: Option<chrono::DateTime<chrono::Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+/// List audit log entries. Needs `audit:read`, which admins and any custom
+/// role granting it have.
+pub async fn list_audit_log(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    Query(query): Query<AuditQuery>,
+) -> Response {
+    if !is_permitted(&state.policy, &auth, &Permission::new("audit", "read")) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Permission audit:read required to read the audit log", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    let filter = AuditLogFilter {
+        actor: query.actor,
+        action: query.action.map(|a| a.to_uppercase()),
+        resource: query.resource,
+        from: query.from,
+        to: query.to,
+    };
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    let offset = query.offset.unwrap_or(0).max(0);
+
+    match state.db.query_audit_log(&filter, limit, offset).await {
+        Ok(rows) => {
+            let entries: Vec<serde_json::Value> = rows.iter().map(|r| {
+                serde_json::json!({
+                    "id": r.id,
+                    "timestamp": r.created_at.to_rfc3339(),
+                    "request_id": r.request_id,
+                    "actor": r.actor_id,
+                    "api_key_id": r.api_key_id,
+                    "auth_method": r.auth_method,
+                    "action": r.action,
+                    "resource": r.resource,
+                    "status": r.status,
+                    "client_ip": r.client_ip,
+                    "user_agent": r.user_agent,
+                    "duration_ms": r.duration_ms,
+                    "audit_level": r.audit_level,
+                })
+            }).collect();
+            Json(ApiResponse::success(entries)).into_response()
+        }
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Runtime Configuration
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Effective configuration the server loaded, with secrets redacted. Admin only.
+pub async fn get_config(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+) -> Response {
+    if !auth.has_role("admin") {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Admin role required to read the configuration", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    Json(ApiResponse::success(state.config.redacted())).into_response()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Circuit Breakers
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Key of the global circuit breaker; every other key is a model name or an
+/// agent id.
+pub const GLOBAL_CIRCUIT_BREAKER: &str = "global";
+
+/// State of the global circuit breaker and of every model's and agent's.
+/// Admin only.
+pub async fn list_circuit_breakers(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+) -> Response {
+    if !auth.has_role("admin") {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Admin role required to read circuit breakers", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    let breakers = state.orchestrator.circuit_breakers();
+    Json(ApiResponse::success(serde_json::json!({
+        "global": breakers.global_metrics(),
+        "models": model_circuits(&breakers.all_metrics()),
+        "agents": model_circuits(&state.orchestrator.agent_circuit_breakers().all_metrics()),
+    }))).into_response()
+}
+
+/// Force a circuit breaker closed without waiting out its recovery timeout,
+/// e.g. once a provider is known to have recovered. Admin only; every reset
+/// is written to the audit log.
+pub async fn reset_circuit_breaker(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    Path(key): Path<String>,
+) -> Response {
+    if !auth.has_role("admin") {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Admin role required to reset circuit breakers", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    let started = std::time::Instant::now();
+    let breakers = state.orchestrator.circuit_breakers();
+    let found = if key == GLOBAL_CIRCUIT_BREAKER {
+        breakers.reset_global();
+        true
+    } else {
+        breakers.reset_agent(&key) || state.orchestrator.agent_circuit_breakers().reset_agent(&key)
+    };
+    if !found {
+        return not_found("Circuit breaker not found");
+    }
+
+    tracing::warn!(breaker = %key, user_id = %auth.user_id, "Circuit breaker manually reset");
+    let path = format!("/api/v1/circuit-breakers/{}/reset", key);
+    if let Err(e) = state.db.insert_audit_entry(&audit_entry(&auth, path, StatusCode::OK, started)).await {
+        tracing::error!(breaker = %key, error = %e, "Failed to audit circuit breaker reset");
+    }
+
+    Json(ApiResponse::success(serde_json::json!({
+        "key": key,
+        "state": CircuitState::Closed,
+    }))).into_response()
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Dead-Lettered Jobs
+// ═══════════════════════════════════════════════════════════════════════════════
+
+#[derive(Debug, Deserialize)]
+pub struct DeadLetterQuery {
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PurgeDeadLettersQuery {
+    /// Drop jobs dead for longer than this many seconds
+    pub older_than_secs: i64,
+}
+
+/// Jobs that failed all their attempts, most recent first. Admin only.
+pub async fn list_dead_letters(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    Query(query): Query<DeadLetterQuery>,
+) -> Response {
+    if !auth.has_role("admin") {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Admin role required to read dead-lettered jobs", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    let limit = query.limit.unwrap_or(100).clamp(1, 1000);
+    Json(ApiResponse::success(state.jobs.dead_letters(limit).await)).into_response()
+}
+
+/// Put a dead-lettered job back on the queue with its attempts reset, e.g.
+/// after fixing the deploy that failed it. Admin only; audited.
+pub async fn replay_dead_letter(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Response {
+    if !auth.has_role("admin") {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Admin role required to replay jobs", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    let started = std::time::Instant::now();
+    let response = match state.jobs.replay(JobId(id)).await {
+        Ok(job) => Json(ApiResponse::success(job)).into_response(),
+        Err(e) => (e.http_status(), Json(ApiResponse::<()>::from_apex_error(&e))).into_response(),
+    };
+
+    let path = format!("/api/v1/jobs/dead-letters/{}/replay", id);
+    let status = response.status();
+    if let Err(e) = state.db.insert_audit_entry(&audit_entry(&auth, path, status, started)).await {
+        tracing::error!(job_id = %id, error = %e, "Failed to audit job replay");
+    }
+    response
+}
+
+/// Drop dead-lettered jobs older than `older_than_secs`. Admin only.
+pub async fn purge_dead_letters(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+    Query(query): Query<PurgeDeadLettersQuery>,
+) -> Response {
+    if !auth.has_role("admin") {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Admin role required to purge jobs", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    let older_than = chrono::Duration::seconds(query.older_than_secs.max(0));
+    let purged = state.jobs.purge_dead_letters(older_than).await;
+    tracing::info!(purged, older_than_secs = query.older_than_secs, user_id = %auth.user_id, "Purged dead-lettered jobs");
+    Json(ApiResponse::success(serde_json::json!({ "purged": purged }))).into_response()
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -695,18 +1964,21 @@ pub async fn get_contract(
 
 pub async fn get_system_stats(
     State(state): State<AppState>,
+    scope: TenantScope,
 ) -> impl IntoResponse {
     let orchestrator_stats = state.orchestrator.stats();
 
-    match state.db.get_system_stats().await {
+    match state.db.get_system_stats(&scope, None).await {
         Ok(db_stats) => {
             Json(ApiResponse::success(serde_json::json!({
                 "orchestrator": {
                     "active_dags": orchestrator_stats.active_dags,
+                    "max_dags": orchestrator_stats.max_dags,
                     "registered_agents": orchestrator_stats.registered_agents,
                     "active_contracts": orchestrator_stats.active_contracts,
                     "available_workers": orchestrator_stats.available_workers,
                     "max_workers": orchestrator_stats.max_workers,
+                    "circuits": model_circuits(&orchestrator_stats.circuits),
                 },
                 "database": {
                     "total_tasks": db_stats.total_tasks,
@@ -723,6 +1995,77 @@ pub async fn get_system_stats(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct FailureBreakdownQuery {
+    /// Only failures at or after this time; default the last 7 days
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Failed tasks grouped by error code, for the failures dashboard.
+pub async fn get_failure_breakdown(
+    State(state): State<AppState>,
+    scope: TenantScope,
+    Query(query): Query<FailureBreakdownQuery>,
+) -> Response {
+    let since = query.since.unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::days(7));
+    match state.db.get_failure_breakdown(&scope, Some(since)).await {
+        Ok(rows) => Json(ApiResponse::success(serde_json::json!({
+            "since": since.to_rfc3339(),
+            "total_failures": rows.iter().map(|r| r.failures).sum::<i64>(),
+            "by_error_code": rows,
+        }))).into_response(),
+        Err(e) => Json(ApiResponse::<()>::from_apex_error(&e)).into_response(),
+    }
+}
+
+/// Live orchestrator counters, without the database round trip of `/stats`.
+/// They span every tenant, so need `orchestrator:read`, which only admins
+/// are granted.
+pub async fn get_orchestrator_stats(
+    RequireAuth(auth): RequireAuth,
+    State(state): State<AppState>,
+) -> Response {
+    if !is_permitted(&state.policy, &auth, &Permission::new("orchestrator", "read")) {
+        return (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::<()>::error_with_code("Admin role required to read orchestrator stats", "FORBIDDEN")),
+        ).into_response();
+    }
+
+    let stats = state.orchestrator.stats();
+    Json(ApiResponse::success(serde_json::json!({
+        "active_dags": stats.active_dags,
+        "max_dags": stats.max_dags,
+        "registered_agents": stats.registered_agents,
+        "busy_agents": stats.busy_agents,
+        "active_contracts": stats.active_contracts,
+        "available_workers": stats.available_workers,
+        "max_workers": stats.max_workers,
+        "worker_utilization": stats.worker_utilization(),
+        "circuits": model_circuits(&stats.circuits),
+    }))).into_response()
+}
+
+/// Per-model (or per-agent) circuit breaker state, keyed by model name (or
+/// agent id).
+fn model_circuits(circuits: &[AgentCircuitMetrics]) -> serde_json::Value {
+    circuits
+        .iter()
+        .map(|circuit| {
+            let value = serde_json::json!({
+                "state": circuit.state,
+                "failure_count": circuit.failure_count,
+                "backoff_multiplier": circuit.backoff_multiplier,
+                "total_successes": circuit.total_successes,
+                "total_failures": circuit.total_failures,
+                "open_reason": circuit.open_reason,
+            });
+            (circuit.agent_id.clone(), value)
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
 pub async fn prometheus_metrics() -> impl IntoResponse {
     let registry = crate::telemetry::metrics::MetricsRegistry::global();
     let body = registry.render();
@@ -732,3 +2075,490 @@ pub async fn prometheus_metrics() -> impl IntoResponse {
         body,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    fn auth(roles: &[&str], scopes: Option<Vec<Permission>>) -> AuthContext {
+        let mut auth = AuthContext::anonymous("req-1".to_string());
+        auth.user_id = "alice".to_string();
+        auth.auth_method = AuthMethod::Jwt;
+        auth.roles = roles.iter().map(|r| r.to_string()).collect();
+        auth.scopes = scopes;
+        auth
+    }
+
+    #[test]
+    fn test_circuit_breaker_views() {
+        let registry = crate::orchestrator::AgentCircuitBreakerRegistry::new(10, 1);
+        registry.record_failure("gpt-4o");
+        registry.record_success("claude-3.5-haiku");
+
+        let circuits = model_circuits(&registry.all_metrics());
+        assert_eq!(circuits["gpt-4o"]["state"], "open");
+        assert_eq!(circuits["gpt-4o"]["open_reason"], "consecutive_failures");
+        assert_eq!(circuits["claude-3.5-haiku"]["state"], "closed");
+        assert_eq!(circuits["claude-3.5-haiku"]["total_successes"], 1);
+
+        let path = "/api/v1/circuit-breakers/gpt-4o/reset".to_string();
+        let entry = audit_entry(&auth(&["admin"], None), path, StatusCode::OK, std::time::Instant::now());
+        assert_eq!(entry.user_id.as_deref(), Some("alice"));
+        assert_eq!(entry.auth_method.as_deref(), Some("bearer"));
+        assert_eq!((entry.method.as_str(), entry.status), ("POST", 200));
+    }
+
+    #[tokio::test]
+    async fn test_created_task_clears_missing_tombstones() {
+        let cache = Cache::in_memory(100);
+        let id = TaskId::new();
+        let scopes = [TenantScope::All, TenantScope::Organization(OrganizationId::new("org-1"))];
+        for scope in &scopes {
+            cache.cache_none(&missing_task_key(scope, id.0)).await.unwrap();
+            let lookup = cache.lookup::<serde::de::IgnoredAny>(&missing_task_key(scope, id.0)).await.unwrap();
+            assert!(matches!(lookup, CacheLookup::Negative));
+        }
+
+        forget_missing_tasks(&cache, [id]).await;
+        for scope in &scopes {
+            let lookup = cache.lookup::<serde::de::IgnoredAny>(&missing_task_key(scope, id.0)).await.unwrap();
+            assert!(matches!(lookup, CacheLookup::Miss));
+        }
+    }
+
+    #[test]
+    fn test_template_validation() {
+        let mut req: CreateTemplateRequest = serde_json::from_value(serde_json::json!({
+            "name": "  <b>research</b> ",
+            "spec": {
+                "name": "research-{{topic}}",
+                "tasks": [{ "id": "a", "name": "A", "instruction": "", "depends_on": ["b"] }]
+            }
+        })).unwrap();
+        req.sanitize();
+        assert_eq!(req.name, "research");
+
+        let fields: Vec<String> = req.validate_fields().errors.into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["spec.tasks[0].instruction", "spec.tasks[0].depends_on[0]"]);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_list_requires_admin() {
+        use crate::api::tests::{bearer, call, test_state};
+        use axum::http::Method;
+
+        let state = test_state().await;
+        let uri = "/api/v1/circuit-breakers";
+        assert_eq!(call(&state, Method::GET, uri, None).await, StatusCode::UNAUTHORIZED);
+        let operator = bearer(&state, &["operator"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&operator)).await, StatusCode::FORBIDDEN);
+        let admin = bearer(&state, &["admin"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&admin)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_reset_requires_admin() {
+        use crate::api::tests::{bearer, call, test_state};
+        use axum::http::Method;
+
+        let state = test_state().await;
+        let uri = "/api/v1/circuit-breakers/global/reset";
+        assert_eq!(call(&state, Method::POST, uri, Some("Bearer not-a-token")).await, StatusCode::UNAUTHORIZED);
+        let operator = bearer(&state, &["operator"], "org-1");
+        assert_eq!(call(&state, Method::POST, uri, Some(&operator)).await, StatusCode::FORBIDDEN);
+        let admin = bearer(&state, &["admin"], "org-1");
+        assert_eq!(call(&state, Method::POST, uri, Some(&admin)).await, StatusCode::OK);
+        let missing = "/api/v1/circuit-breakers/no-such-model/reset";
+        assert_eq!(call(&state, Method::POST, missing, Some(&admin)).await, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated Postgres at DATABASE_URL"]
+    async fn test_batch_approve_wakes_waiting_task() {
+        use crate::api::tests::{bearer, call_json, test_state};
+        use crate::db::tests::{insert_organization, insert_pending_approval, live_database};
+        use crate::orchestrator::ApprovalResolution;
+        use axum::http::Method;
+
+        let db = live_database().await;
+        let org = insert_organization(&db).await;
+        let (approval, _) = insert_pending_approval(&db, org, chrono::Duration::hours(1)).await;
+        let state = test_state().await;
+        let waiter = tokio::spawn({
+            let orchestrator = state.orchestrator.clone();
+            async move { orchestrator.approvals().wait(approval, std::time::Duration::from_secs(5)).await }
+        });
+        while state.orchestrator.approvals().waiting() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let uri = "/api/v1/approvals/batch";
+        let body = serde_json::json!({ "ids": [approval], "decision": "approve" });
+        let developer = bearer(&state, &["developer"], &org.to_string());
+        let (status, _) = call_json(&state, Method::POST, uri, Some(&developer), body.clone()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let operator = bearer(&state, &["operator"], &org.to_string());
+        let (status, response) = call_json(&state, Method::POST, uri, Some(&operator), body).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(response["summary"]["succeeded"], 1);
+        assert_eq!(waiter.await.unwrap(), Some(ApprovalResolution::Approved));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated Postgres at DATABASE_URL"]
+    async fn test_instantiate_checks_rendered_spec() {
+        use crate::api::tests::{bearer, call_json, test_state};
+        use crate::db::tests::{insert_organization, live_database};
+        use axum::http::Method;
+
+        let db = live_database().await;
+        let org = insert_organization(&db).await;
+        let state = test_state().await;
+        let template = serde_json::json!({
+            "name": "one-step",
+            "spec": { "name": "one-step", "tasks": [{ "id": "a", "name": "A", "instruction": "{{step}}" }] }
+        });
+
+        let caller = bearer(&state, &["operator"], &org.to_string());
+        let (status, created) = call_json(&state, Method::POST, "/api/v1/templates", Some(&caller), template.clone()).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", created);
+        let id: Uuid = serde_json::from_value(created["data"]["id"].clone()).unwrap();
+        let owner: Option<Uuid> = sqlx::query_scalar("SELECT organization_id FROM dag_templates WHERE id = $1")
+            .bind(id)
+            .fetch_one(db.pool())
+            .await
+            .unwrap();
+        assert_eq!(owner, Some(org));
+
+        // A parameter that sanitizes to nothing leaves an empty instruction
+        let uri = format!("/api/v1/templates/{}/instantiate", id);
+        let params = serde_json::json!({ "params": { "step": "<b></b>" } });
+        let (status, response) = call_json(&state, Method::POST, &uri, Some(&caller), params).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{}", response);
+
+        // A tenant without an organization row stores the template unowned
+        // rather than under the nil organization
+        let unowned = bearer(&state, &["operator"], "acme");
+        let (status, created) = call_json(&state, Method::POST, "/api/v1/templates", Some(&unowned), template).await;
+        assert_eq!(status, StatusCode::CREATED, "{}", created);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated Postgres at DATABASE_URL"]
+    async fn test_existing_submission_reports_the_stored_dag() {
+        use crate::api::tests::test_state;
+        use crate::dag::{Task, TaskDAG, TaskInput};
+        use crate::db::tests::{insert_organization, live_database};
+
+        let db = live_database().await;
+        let org = insert_organization(&db).await;
+        let state = test_state().await;
+        let scope = TenantScope::Organization(OrganizationId::new(org.to_string()));
+        let key = IdempotencyKey::new(format!("order-{}", Uuid::new_v4()), &scope).unwrap();
+
+        let mut first = TaskDAG::new("first");
+        first.add_task(Task::new("a", TaskInput::default())).unwrap();
+        first.add_task(Task::new("b", TaskInput::default())).unwrap();
+        let first_id = state.orchestrator.submit_dag(first, scope.owner(), None, Some(&key)).await.unwrap().dag_id();
+
+        // A retry with a different body still describes the first DAG
+        let mut retry = TaskDAG::new("retry");
+        retry.add_task(Task::new("c", TaskInput::default())).unwrap();
+        let response = DagResponse {
+            id: retry.id(),
+            name: "retry".into(),
+            task_count: 1,
+            status: "created".into(),
+            existing: false,
+        };
+        let submission = state.orchestrator.submit_dag(retry, scope.owner(), None, Some(&key)).await.unwrap();
+        let response = submitted(&state, &scope, response, submission).await.unwrap();
+        assert_eq!((response.id, response.existing), (first_id, true));
+        assert_eq!((response.name.as_str(), response.task_count), ("first", 2));
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_requires_admin() {
+        use crate::api::tests::{bearer, call, test_state};
+        use axum::http::Method;
+
+        let state = test_state().await;
+        let uri = "/api/v1/audit";
+        assert_eq!(call(&state, Method::GET, uri, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(call(&state, Method::GET, uri, Some("Bearer not-a-token")).await, StatusCode::UNAUTHORIZED);
+        let operator = bearer(&state, &["operator"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&operator)).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated Postgres at DATABASE_URL"]
+    async fn test_admins_read_the_audit_log() {
+        use crate::api::tests::{bearer, call, test_state};
+        use axum::http::Method;
+
+        crate::db::tests::live_database().await;
+        let state = test_state().await;
+        let admin = bearer(&state, &["admin"], "org-1");
+        assert_eq!(call(&state, Method::GET, "/api/v1/audit", Some(&admin)).await, StatusCode::OK);
+
+        // So do custom roles granted audit:read
+        let permissions = [Permission::new("audit", "read")].into_iter().collect();
+        state.policy.add_role(crate::rbac::Role::new("auditor", "Auditor", "Reads the audit log", permissions));
+        let auditor = bearer(&state, &["auditor"], "org-1");
+        assert_eq!(call(&state, Method::GET, "/api/v1/audit", Some(&auditor)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_config_requires_admin_and_hides_secrets() {
+        use crate::api::tests::{bearer, call, call_json, test_state};
+        use axum::http::Method;
+
+        let state = test_state().await;
+        let uri = "/api/v1/config";
+        assert_eq!(call(&state, Method::GET, uri, None).await, StatusCode::UNAUTHORIZED);
+        let operator = bearer(&state, &["operator"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&operator)).await, StatusCode::FORBIDDEN);
+
+        let admin = bearer(&state, &["admin"], "org-1");
+        let (status, body) = call_json(&state, Method::GET, uri, Some(&admin), serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["data"]["database"]["url"].is_string());
+        assert!(!body.to_string().contains("apex_secret"));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated Postgres at DATABASE_URL"]
+    async fn test_dag_controls_are_scoped_to_the_owner() {
+        use crate::api::tests::{bearer, call, test_state};
+        use crate::dag::{Task, TaskDAG, TaskInput};
+        use crate::db::tests::{insert_organization, live_database};
+        use axum::http::Method;
+
+        let db = live_database().await;
+        let (owner, other) = (insert_organization(&db).await, insert_organization(&db).await);
+        let state = test_state().await;
+        let mut dag = TaskDAG::new("scoped");
+        dag.add_task(Task::new("a", TaskInput::default())).unwrap();
+        let dag_id = state.orchestrator.submit_dag(dag, Some(owner), None, None).await.unwrap().dag_id();
+
+        let stranger = bearer(&state, &["operator"], &other.to_string());
+        for action in ["pause", "resume", "cancel"] {
+            let uri = format!("/api/v1/dags/{}/{}", dag_id, action);
+            assert_eq!(call(&state, Method::POST, &uri, Some(&stranger)).await, StatusCode::NOT_FOUND, "{}", action);
+        }
+
+        let operator = bearer(&state, &["operator"], &owner.to_string());
+        let uri = |action: &str| format!("/api/v1/dags/{}/{}", dag_id, action);
+        assert_eq!(call(&state, Method::POST, &uri("pause"), Some(&operator)).await, StatusCode::OK);
+        assert_eq!(call(&state, Method::POST, &uri("resume"), Some(&operator)).await, StatusCode::OK);
+        assert_eq!(call(&state, Method::POST, &uri("cancel"), Some(&operator)).await, StatusCode::ACCEPTED);
+        for _ in 0..100 {
+            if state.orchestrator.stats().active_dags == 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(state.orchestrator.stats().active_dags, 0);
+    }
+
+    #[tokio::test]
+    async fn test_usage_reports_require_admin() {
+        use crate::api::tests::{bearer, call, test_state};
+        use axum::http::Method;
+
+        let state = test_state().await;
+        let uri = "/api/v1/reports/usage";
+        assert_eq!(call(&state, Method::GET, uri, None).await, StatusCode::UNAUTHORIZED);
+        let operator = bearer(&state, &["operator"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&operator)).await, StatusCode::FORBIDDEN);
+        let admin = bearer(&state, &["admin"], "org-1");
+        let csv = format!("{}/{}?format=xml", uri, Uuid::new_v4());
+        assert_eq!(call(&state, Method::GET, &csv, Some(&admin)).await, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_requeue_task_requires_admin() {
+        use crate::api::tests::{bearer, call, test_state};
+        use axum::http::Method;
+
+        let state = test_state().await;
+        let uri = format!("/api/v1/tasks/{}/requeue", Uuid::new_v4());
+        let operator = bearer(&state, &["operator"], "org-1");
+        assert_eq!(call(&state, Method::POST, &uri, Some(&operator)).await, StatusCode::FORBIDDEN);
+        let admin = bearer(&state, &["admin"], "org-1");
+        assert_eq!(call(&state, Method::POST, &uri, Some(&admin)).await, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_orchestrator_stats_require_admin() {
+        use crate::api::tests::{bearer, call, test_state};
+        use axum::http::Method;
+
+        let state = test_state().await;
+        let uri = "/api/v1/orchestrator/stats";
+        assert_eq!(call(&state, Method::GET, uri, None).await, StatusCode::UNAUTHORIZED);
+        let operator = bearer(&state, &["operator"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&operator)).await, StatusCode::FORBIDDEN);
+        let admin = bearer(&state, &["admin"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&admin)).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_dead_letters_require_admin() {
+        use crate::api::tests::{bearer, call, test_state};
+        use axum::http::Method;
+
+        let state = test_state().await;
+        let uri = "/api/v1/jobs/dead-letters";
+        assert_eq!(call(&state, Method::GET, uri, None).await, StatusCode::UNAUTHORIZED);
+        let operator = bearer(&state, &["operator"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&operator)).await, StatusCode::FORBIDDEN);
+        let admin = bearer(&state, &["admin"], "org-1");
+        assert_eq!(call(&state, Method::GET, uri, Some(&admin)).await, StatusCode::OK);
+        let replay = format!("{}/{}/replay", uri, Uuid::new_v4());
+        assert_eq!(call(&state, Method::POST, &replay, Some(&admin)).await, StatusCode::NOT_FOUND);
+        let purge = format!("{}?older_than_secs=0", uri);
+        assert_eq!(call(&state, Method::DELETE, &purge, Some(&admin)).await, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_task_limits_must_be_positive() {
+        let req: CreateDagRequest = serde_json::from_value(serde_json::json!({
+            "name": "limited",
+            "tasks": [{
+                "id": "a", "name": "A", "instruction": "go",
+                "limits": { "token_limit": 1000, "cost_limit": 0.1, "api_call_limit": 5, "time_limit_seconds": 0 }
+            }]
+        })).unwrap();
+        let fields: Vec<String> = req.validate_fields().errors.into_iter().map(|e| e.field).collect();
+        assert_eq!(fields, vec!["tasks[0].limits.time_limit_seconds"]);
+    }
+
+    #[tokio::test]
+    async fn test_validation_failure_response() {
+        let mut req: CreateDagRequest = serde_json::from_value(serde_json::json!({
+            "name": "",
+            "tasks": [
+                { "id": "a", "name": "", "instruction": "" },
+                { "id": "b", "name": "B", "instruction": "go", "depends_on": ["a", "zzz"] }
+            ],
+            "dependencies": [{ "from": "b", "to": "b" }]
+        })).unwrap();
+        req.sanitize();
+
+        let response = req.validate_fields().into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error_code"], "VALIDATION_ERROR");
+        let errors = body["errors"].as_object().unwrap();
+        assert_eq!(errors.len(), 5);
+        assert_eq!(body["errors"]["name"], serde_json::json!(["must not be empty"]));
+        assert_eq!(body["errors"]["tasks[0].name"], serde_json::json!(["must not be empty"]));
+        assert_eq!(body["errors"]["tasks[0].instruction"], serde_json::json!(["must not be empty"]));
+        assert_eq!(body["errors"]["tasks[1].depends_on[1]"], serde_json::json!(["references unknown task 'zzz'"]));
+        assert_eq!(body["errors"]["dependencies[0]"], serde_json::json!(["task cannot depend on itself"]));
+
+        // Errors converted from the validation framework keep their fields
+        let mut framework = crate::validation::ValidationErrors::new();
+        framework.add_required("spec.tasks[0].id");
+        let apex = ApexError::from(framework);
+        let response = ApiResponse::<()>::from_apex_error(&apex);
+        assert_eq!(response.errors.unwrap()["spec.tasks[0].id"].len(), 1);
+        assert_eq!(apex.http_status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn test_keyset_query_validation() {
+        let query = |sort_by: Option<&str>, after: Option<String>| {
+            KeysetQuery(PaginationQuery { sort_by: sort_by.map(String::from), after, ..Default::default() })
+        };
+        let position = Cursor::keyset(chrono::Utc::now(), Uuid::new_v4()).encode().unwrap();
+
+        assert!(query(Some("created_at"), Some(position.clone())).validate().is_ok());
+        assert!(query(Some("name"), None).validate().unwrap_err().has_errors("sort_by"));
+        assert!(query(None, Some("bogus".into())).validate().unwrap_err().has_errors("after"));
+
+        // A well-formed cursor that isn't a list position
+        let other = Cursor::with_value("id", 7i64).encode().unwrap();
+        assert!(query(None, Some(other)).validate().unwrap_err().has_errors("after"));
+        assert!(keyset_after(&query(None, Some(position)).0).is_some());
+    }
+
+    #[test]
+    fn test_approval_permission() {
+        let engine = PolicyEngine::with_predefined_roles();
+        let approve = Permission::new("approval", "approve");
+        assert!(is_permitted(&engine, &auth(&["operator"], None), &approve));
+        assert!(is_permitted(&engine, &auth(&["admin"], None), &approve));
+        assert!(!is_permitted(&engine, &auth(&["developer", "viewer"], None), &approve));
+
+        // A key scoped to reading can't approve, whatever its owner's role
+        let read_only = Some(vec![Permission::new("approval", "read")]);
+        assert!(!is_permitted(&engine, &auth(&["operator"], read_only), &approve));
+
+        // A role bound in the caller's organization counts as well as the token's
+        let mut bound = auth(&[], None);
+        bound.org_id = Some("org-1".to_string());
+        assert!(!is_permitted(&engine, &bound, &approve));
+        engine.bind_role(crate::rbac::RoleBinding::new(
+            UserId::new("alice"),
+            crate::rbac::RoleId::new("operator"),
+            OrganizationId::new("org-1"),
+        ));
+        assert!(is_permitted(&engine, &bound, &approve));
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        let router = ModelRouter::new();
+        let req = CostEstimateRequest {
+            instruction: "Summarize this paragraph.".to_string(),
+            context: None,
+            model: Some("gpt-4o".to_string()),
+        };
+        let estimate = estimate_cost(&router, &req).unwrap();
+
+        assert_eq!(estimate.model, "gpt-4o");
+        assert_eq!(estimate.tier, ModelTier::Standard);
+        assert!(estimate.input_tokens > 0);
+        assert_eq!(estimate.outputs.len(), ESTIMATE_OUTPUT_TOKENS.len());
+        assert_eq!(estimate.cost_min, estimate.outputs[0].cost);
+        assert_eq!(estimate.cost_max, estimate.outputs[2].cost);
+        assert!(estimate.cost_min < estimate.cost_max);
+        assert_eq!(estimate.routed_model, router.select_model(&req.instruction));
+
+        // Without a model, the routed one is priced
+        let routed = estimate_cost(&router, &CostEstimateRequest { model: None, ..req.clone() }).unwrap();
+        assert_eq!(routed.model, routed.routed_model);
+
+        let unknown = CostEstimateRequest { model: Some("gpt-9".to_string()), ..req };
+        assert_eq!(estimate_cost(&router, &unknown).unwrap_err().code(), ErrorCode::ValidationError);
+    }
+
+    #[test]
+    fn test_batch_approval_validation() {
+        let mut req = BatchApprovalRequest {
+            ids: vec![Uuid::new_v4()],
+            decision: ApprovalDecision::Deny,
+            comment: None,
+            reason: Some("   ".to_string()),
+        };
+        req.sanitize();
+        assert!(!req.validate().is_empty(), "denying needs a reason");
+
+        req.reason = Some("Too expensive".to_string());
+        assert!(req.validate().is_empty());
+
+        req.ids = vec![Uuid::new_v4(); MAX_APPROVAL_BATCH + 1];
+        assert!(!req.validate().is_empty());
+
+        let body = r#"{"ids": [], "decision": "approve", "comment": "ok"}"#;
+        let req: BatchApprovalRequest = serde_json::from_str(body).unwrap();
+        assert_eq!(req.decision, ApprovalDecision::Approve);
+        assert!(!req.validate().is_empty());
+    }
+}
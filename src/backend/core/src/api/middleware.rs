@@ -150,6 +150,24 @@ impl ValidationErrors {
         self.errors.push(FieldError { field: field.into(), message: message.into() });
     }
     pub fn is_empty(&self) -> bool { self.errors.is_empty() }
+
+    /// The messages for each field path, in the order they were added.
+    pub fn to_message_map(&self) -> std::collections::HashMap<String, Vec<String>> {
+        let mut map: std::collections::HashMap<String, Vec<String>> = std::collections::HashMap::new();
+        for error in &self.errors {
+            map.entry(error.field.clone()).or_default().push(error.message.clone());
+        }
+        map
+    }
+}
+
+/// `422 Unprocessable Entity` shaped like [`crate::validation::ValidationErrors`]'
+/// response: `{"errors": {"tasks[0].name": ["must not be empty"]}, ...}`.
+impl IntoResponse for ValidationErrors {
+    fn into_response(self) -> Response {
+        let body = super::ApiResponse::<()>::validation_failed(self.to_message_map());
+        (StatusCode::UNPROCESSABLE_ENTITY, Json(body)).into_response()
+    }
 }
 
 impl Default for ValidationErrors {
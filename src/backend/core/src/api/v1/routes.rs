@@ -3,12 +3,15 @@
 //! This module defines all V1 API routes and their handlers.
 //! V1 is the current stable API version.
 
+use std::sync::Arc;
+
 use axum::{
-    routing::{delete, get, post},
+    routing::{delete, get, post, put},
     Router,
 };
 
 use crate::api::{handlers, AppState};
+use crate::rbac::{PolicyEngine, RequirePermissionLayer};
 use super::plugins;
 
 /// V1 API prefix.
@@ -16,7 +19,9 @@ pub const V1_PREFIX: &str = "/api/v1";
 
 /// Build the V1 API router.
 ///
-/// All routes are mounted under `/api/v1/`.
+/// All routes are mounted under `/api/v1/`. Each one requires an RBAC
+/// permission, checked against `policy` for the caller's roles and, for API
+/// keys, against the key's scopes; callers without it get 403.
 ///
 /// # Endpoints
 ///
@@ -25,24 +30,50 @@ pub const V1_PREFIX: &str = "/api/v1";
 /// - `GET /api/v1/tasks/:id` - Get task by ID
 /// - `GET /api/v1/tasks/:id/status` - Get task status
 /// - `POST /api/v1/tasks/:id/cancel` - Cancel a task
+/// - `POST /api/v1/tasks/:id/requeue` - Requeue a task stuck on a dead worker (admin)
+///
+/// ## Estimates
+/// - `POST /api/v1/estimate` - Token and cost estimate for an instruction on a model, before submitting it
 ///
 /// ## DAGs
 /// - `POST /api/v1/dags` - Create a new DAG
 /// - `GET /api/v1/dags/:id` - Get DAG by ID
 /// - `POST /api/v1/dags/:id/execute` - Execute a DAG
-/// - `GET /api/v1/dags/:id/status` - Get DAG execution status
+/// - `GET /api/v1/dags/:id/status` - Get DAG status, task counts and progress
+/// - `POST /api/v1/dags/:id/pause` - Stop dispatching new tasks; in-flight ones finish
+/// - `POST /api/v1/dags/:id/resume` - Resume a paused DAG
+/// - `POST /api/v1/dags/:id/cancel` - Cancel a DAG; in-flight tasks drain in the background
+///
+/// ## DAG Templates
+/// - `GET /api/v1/templates` - List templates
+/// - `POST /api/v1/templates` - Store a template (a DAG spec with `{{placeholders}}`)
+/// - `GET /api/v1/templates/:id` - Get template by ID, with its required parameters
+/// - `PUT /api/v1/templates/:id` - Replace a template
+/// - `DELETE /api/v1/templates/:id` - Delete a template
+/// - `POST /api/v1/templates/:id/instantiate` - Substitute parameters and submit the DAG
 ///
 /// ## Agents
-/// - `GET /api/v1/agents` - List all agents
+/// - `GET /api/v1/agents` - List agents (keyset paged: `?after=<cursor>&limit=N`)
 /// - `POST /api/v1/agents` - Register a new agent
 /// - `GET /api/v1/agents/:id` - Get agent by ID
 /// - `DELETE /api/v1/agents/:id` - Remove an agent
 /// - `GET /api/v1/agents/:id/stats` - Get agent statistics
+/// - `GET /api/v1/agents/:id/history` - Performance snapshots (`?from=&to=`, default last 7 days)
 ///
 /// ## Contracts
-/// - `GET /api/v1/contracts` - List all contracts
+/// - `GET /api/v1/contracts` - List contracts (keyset paged: `?after=<cursor>&limit=N`)
 /// - `GET /api/v1/contracts/:id` - Get contract by ID
 ///
+/// ## Organizations
+/// - `GET /api/v1/organizations/:id/usage` - Month-to-date usage against the organization's quota
+///
+/// ## Approvals
+/// - `POST /api/v1/approvals/batch` - Approve or deny many approvals, with per-item results
+///
+/// ## Reports
+/// - `GET /api/v1/reports/usage` - List generated usage reports (admin only)
+/// - `GET /api/v1/reports/usage/:id` - A usage report as JSON, or CSV with `?format=csv` (admin only)
+///
 /// ## Plugins
 /// - `GET /api/v1/plugins` - List all plugins
 /// - `GET /api/v1/plugins/:name` - Get plugin details
@@ -52,39 +83,91 @@ pub const V1_PREFIX: &str = "/api/v1";
 /// - `POST /api/v1/plugins/:name/disable` - Disable a plugin
 /// - `POST /api/v1/plugins/:name/uninstall` - Uninstall a plugin
 ///
+/// ## Jobs
+/// - `GET /api/v1/jobs/dead-letters` - Jobs that failed all their attempts, newest first (`?limit=`, admin only)
+/// - `POST /api/v1/jobs/dead-letters/:id/replay` - Requeue a dead job with its attempts reset (admin only, audited)
+/// - `DELETE /api/v1/jobs/dead-letters?older_than_secs=N` - Drop old dead jobs (admin only)
+///
+/// ## Circuit Breakers
+/// - `GET /api/v1/circuit-breakers` - Global and per-model circuit breaker state (admin only)
+/// - `POST /api/v1/circuit-breakers/:key/reset` - Force a breaker closed; `key` is a model or `global` (admin only, audited)
+///
+/// ## Security
+/// - `GET /api/v1/csrf-token` - Mint a CSRF token (served by `CsrfLayer`)
+///
+/// ## Audit
+/// - `GET /api/v1/audit` - Query the audit log (`audit:read`, admins by default)
+///
 /// ## System
 /// - `GET /api/v1/stats` - Get system statistics
-pub fn v1_router() -> Router<AppState> {
+/// - `GET /api/v1/stats/failures` - Failed tasks grouped by error code (`?since=`, default last 7 days)
+/// - `GET /api/v1/orchestrator/stats` - Live orchestrator counters and worker utilization (admin only)
+/// - `GET /api/v1/config` - Effective runtime configuration, secrets redacted (admin only)
+pub fn v1_router(policy: Arc<PolicyEngine>) -> Router<AppState> {
+    let require = |permission: &str| RequirePermissionLayer::new(policy.clone(), permission);
     Router::new()
         // Task endpoints
-        .route("/tasks", post(handlers::create_task))
-        .route("/tasks/:id", get(handlers::get_task))
-        .route("/tasks/:id/status", get(handlers::get_task_status))
-        .route("/tasks/:id/cancel", post(handlers::cancel_task))
+        .route("/tasks", post(handlers::create_task).route_layer(require("task:submit")))
+        .route("/tasks/:id", get(handlers::get_task).route_layer(require("task:read")))
+        .route("/tasks/:id/status", get(handlers::get_task_status).route_layer(require("task:read")))
+        .route("/tasks/:id/cancel", post(handlers::cancel_task).route_layer(require("task:submit")))
+        .route("/tasks/:id/requeue", post(handlers::requeue_task).route_layer(require("task:submit")))
+        // Estimates
+        .route("/estimate", post(handlers::estimate_task_cost).route_layer(require("task:read")))
         // DAG endpoints
-        .route("/dags", post(handlers::create_dag))
-        .route("/dags/:id", get(handlers::get_dag))
-        .route("/dags/:id/execute", post(handlers::execute_dag))
-        .route("/dags/:id/status", get(handlers::get_dag_status))
-        // Agent endpoints
-        .route("/agents", get(handlers::list_agents))
-        .route("/agents", post(handlers::register_agent))
-        .route("/agents/:id", get(handlers::get_agent))
-        .route("/agents/:id", delete(handlers::remove_agent))
-        .route("/agents/:id/stats", get(handlers::get_agent_stats))
+        .route("/dags", post(handlers::create_dag).route_layer(require("swarm:create")))
+        .route("/dags/:id", get(handlers::get_dag).route_layer(require("swarm:read")))
+        .route("/dags/:id/execute", post(handlers::execute_dag).route_layer(require("swarm:create")))
+        .route("/dags/:id/status", get(handlers::get_dag_status).route_layer(require("swarm:read")))
+        .route("/dags/:id/pause", post(handlers::pause_dag).route_layer(require("swarm:create")))
+        .route("/dags/:id/resume", post(handlers::resume_dag).route_layer(require("swarm:create")))
+        .route("/dags/:id/cancel", post(handlers::cancel_dag).route_layer(require("swarm:create")))
+        // DAG template endpoints
+        .route("/templates", get(handlers::list_templates).route_layer(require("swarm:read")))
+        .route("/templates", post(handlers::create_template).route_layer(require("swarm:create")))
+        .route("/templates/:id", get(handlers::get_template).route_layer(require("swarm:read")))
+        .route("/templates/:id", put(handlers::update_template).route_layer(require("swarm:create")))
+        .route("/templates/:id", delete(handlers::delete_template).route_layer(require("swarm:delete")))
+        .route("/templates/:id/instantiate", post(handlers::instantiate_template).route_layer(require("swarm:create")))
+        // Agent endpoints; reading them is open to any agent permission
+        .route("/agents", get(handlers::list_agents).route_layer(require("agent:*")))
+        .route("/agents", post(handlers::register_agent).route_layer(require("agent:manage")))
+        .route("/agents/:id", get(handlers::get_agent).route_layer(require("agent:*")))
+        .route("/agents/:id", delete(handlers::remove_agent).route_layer(require("agent:manage")))
+        .route("/agents/:id/stats", get(handlers::get_agent_stats).route_layer(require("agent:*")))
+        .route("/agents/:id/history", get(handlers::get_agent_history).route_layer(require("agent:*")))
         // Contract endpoints
-        .route("/contracts", get(handlers::list_contracts))
-        .route("/contracts/:id", get(handlers::get_contract))
+        .route("/contracts", get(handlers::list_contracts).route_layer(require("task:read")))
+        .route("/contracts/:id", get(handlers::get_contract).route_layer(require("task:read")))
+        // Organization endpoints
+        .route("/organizations/:id/usage", get(handlers::get_organization_usage).route_layer(require("task:read")))
+        // Approval endpoints
+        .route("/approvals/batch", post(handlers::batch_decide_approvals).route_layer(require("approval:approve")))
+        // Report endpoints
+        .route("/reports/usage", get(handlers::list_usage_reports).route_layer(require("reports:read")))
+        .route("/reports/usage/:id", get(handlers::get_usage_report).route_layer(require("reports:read")))
         // Plugin endpoints
-        .route("/plugins", get(plugins::list_plugins))
-        .route("/plugins/discover", post(plugins::discover_plugins))
-        .route("/plugins/:name", get(plugins::get_plugin))
-        .route("/plugins/:name/install", post(plugins::install_plugin))
-        .route("/plugins/:name/enable", post(plugins::enable_plugin))
-        .route("/plugins/:name/disable", post(plugins::disable_plugin))
-        .route("/plugins/:name/uninstall", post(plugins::uninstall_plugin))
+        .route("/plugins", get(plugins::list_plugins).route_layer(require("plugin:read")))
+        .route("/plugins/discover", post(plugins::discover_plugins).route_layer(require("plugin:manage")))
+        .route("/plugins/:name", get(plugins::get_plugin).route_layer(require("plugin:read")))
+        .route("/plugins/:name/install", post(plugins::install_plugin).route_layer(require("plugin:manage")))
+        .route("/plugins/:name/enable", post(plugins::enable_plugin).route_layer(require("plugin:manage")))
+        .route("/plugins/:name/disable", post(plugins::disable_plugin).route_layer(require("plugin:manage")))
+        .route("/plugins/:name/uninstall", post(plugins::uninstall_plugin).route_layer(require("plugin:manage")))
+        // Dead-lettered jobs
+        .route("/jobs/dead-letters", get(handlers::list_dead_letters).route_layer(require("job:read")))
+        .route("/jobs/dead-letters", delete(handlers::purge_dead_letters).route_layer(require("job:manage")))
+        .route("/jobs/dead-letters/:id/replay", post(handlers::replay_dead_letter).route_layer(require("job:manage")))
+        // Circuit breakers
+        .route("/circuit-breakers", get(handlers::list_circuit_breakers).route_layer(require("circuit_breaker:read")))
+        .route("/circuit-breakers/:key/reset", post(handlers::reset_circuit_breaker).route_layer(require("circuit_breaker:manage")))
+        // Audit
+        .route("/audit", get(handlers::list_audit_log).route_layer(require("audit:read")))
         // Stats
-        .route("/stats", get(handlers::get_system_stats))
+        .route("/stats", get(handlers::get_system_stats).route_layer(require("swarm:read")))
+        .route("/stats/failures", get(handlers::get_failure_breakdown).route_layer(require("task:read")))
+        .route("/orchestrator/stats", get(handlers::get_orchestrator_stats).route_layer(require("orchestrator:read")))
+        .route("/config", get(handlers::get_config).route_layer(require("settings:read")))
 }
 
 /// V1 API route constants for use in clients and documentation.
@@ -94,22 +177,45 @@ pub mod paths {
     pub const TASK: &str = "/api/v1/tasks/:id";
     pub const TASK_STATUS: &str = "/api/v1/tasks/:id/status";
     pub const TASK_CANCEL: &str = "/api/v1/tasks/:id/cancel";
+    pub const TASK_REQUEUE: &str = "/api/v1/tasks/:id/requeue";
+
+    // Estimate routes
+    pub const ESTIMATE: &str = "/api/v1/estimate";
 
     // DAG routes
     pub const DAGS: &str = "/api/v1/dags";
     pub const DAG: &str = "/api/v1/dags/:id";
     pub const DAG_EXECUTE: &str = "/api/v1/dags/:id/execute";
     pub const DAG_STATUS: &str = "/api/v1/dags/:id/status";
+    pub const DAG_PAUSE: &str = "/api/v1/dags/:id/pause";
+    pub const DAG_RESUME: &str = "/api/v1/dags/:id/resume";
+    pub const DAG_CANCEL: &str = "/api/v1/dags/:id/cancel";
+
+    // DAG template routes
+    pub const TEMPLATES: &str = "/api/v1/templates";
+    pub const TEMPLATE: &str = "/api/v1/templates/:id";
+    pub const TEMPLATE_INSTANTIATE: &str = "/api/v1/templates/:id/instantiate";
 
     // Agent routes
     pub const AGENTS: &str = "/api/v1/agents";
     pub const AGENT: &str = "/api/v1/agents/:id";
     pub const AGENT_STATS: &str = "/api/v1/agents/:id/stats";
+    pub const AGENT_HISTORY: &str = "/api/v1/agents/:id/history";
 
     // Contract routes
     pub const CONTRACTS: &str = "/api/v1/contracts";
     pub const CONTRACT: &str = "/api/v1/contracts/:id";
 
+    // Organization routes
+    pub const ORGANIZATION_USAGE: &str = "/api/v1/organizations/:id/usage";
+
+    // Approval routes
+    pub const APPROVALS_BATCH: &str = "/api/v1/approvals/batch";
+
+    // Report routes
+    pub const USAGE_REPORTS: &str = "/api/v1/reports/usage";
+    pub const USAGE_REPORT: &str = "/api/v1/reports/usage/:id";
+
     // Plugin routes
     pub const PLUGINS: &str = "/api/v1/plugins";
     pub const PLUGIN: &str = "/api/v1/plugins/:name";
@@ -119,8 +225,25 @@ pub mod paths {
     pub const PLUGIN_DISABLE: &str = "/api/v1/plugins/:name/disable";
     pub const PLUGIN_UNINSTALL: &str = "/api/v1/plugins/:name/uninstall";
 
+    // Job routes
+    pub const DEAD_LETTERS: &str = "/api/v1/jobs/dead-letters";
+    pub const DEAD_LETTER_REPLAY: &str = "/api/v1/jobs/dead-letters/:id/replay";
+
+    // Circuit breaker routes
+    pub const CIRCUIT_BREAKERS: &str = "/api/v1/circuit-breakers";
+    pub const CIRCUIT_BREAKER_RESET: &str = "/api/v1/circuit-breakers/:key/reset";
+
+    // Security routes
+    pub const CSRF_TOKEN: &str = "/api/v1/csrf-token";
+
+    // Audit routes
+    pub const AUDIT: &str = "/api/v1/audit";
+
     // System routes
     pub const STATS: &str = "/api/v1/stats";
+    pub const FAILURE_BREAKDOWN: &str = "/api/v1/stats/failures";
+    pub const CONFIG: &str = "/api/v1/config";
+    pub const ORCHESTRATOR_STATS: &str = "/api/v1/orchestrator/stats";
 }
 
 #[cfg(test)]
@@ -14,16 +14,23 @@
 //! Do not use in production until it becomes stable.
 
 use axum::{
-    extract::{Query, State},
+    extract::{OriginalUri, State},
     response::IntoResponse,
     routing::{delete, get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::Duration;
 use uuid::Uuid;
 
 use crate::api::{handlers, AppState};
+use crate::cache::{Cache, CacheKey};
 use crate::dag::{Task, TaskInput, TaskStatus, TaskId};
+use crate::db::Database;
+use crate::pagination::{page_url, PageLinks};
+use crate::rbac::{PolicyEngine, RequirePermissionLayer, TenantScope};
+use crate::validation::{Validate, ValidatedQuery, ValidationErrorKind, ValidationErrors, ValidationResult};
 
 /// V2 API prefix.
 pub const V2_PREFIX: &str = "/api/v2";
@@ -31,34 +38,38 @@ pub const V2_PREFIX: &str = "/api/v2";
 /// Build the V2 API router.
 ///
 /// V2 includes all V1 endpoints plus new features.
-/// Some endpoints have enhanced functionality.
-pub fn v2_router() -> Router<AppState> {
+/// Some endpoints have enhanced functionality. Routes require the same
+/// RBAC permissions as their V1 counterparts.
+pub fn v2_router(policy: Arc<PolicyEngine>) -> Router<AppState> {
+    let require = |permission: &str| RequirePermissionLayer::new(policy.clone(), permission);
     Router::new()
         // Task endpoints (same as V1 for now)
-        .route("/tasks", post(handlers::create_task))
-        .route("/tasks", get(list_tasks_v2))
-        .route("/tasks/:id", get(handlers::get_task))
-        .route("/tasks/:id/status", get(handlers::get_task_status))
-        .route("/tasks/:id/cancel", post(handlers::cancel_task))
+        .route("/tasks", post(handlers::create_task).route_layer(require("task:submit")))
+        .route("/tasks", get(list_tasks_v2).route_layer(require("task:read")))
+        .route("/tasks/:id", get(handlers::get_task).route_layer(require("task:read")))
+        .route("/tasks/:id/status", get(handlers::get_task_status).route_layer(require("task:read")))
+        .route("/tasks/:id/cancel", post(handlers::cancel_task).route_layer(require("task:submit")))
         // Batch operations (V2 only)
-        .route("/tasks/batch", post(batch_create_tasks))
-        .route("/tasks/batch/cancel", post(batch_cancel_tasks))
+        .route("/tasks/batch", post(batch_create_tasks).route_layer(require("task:submit")))
+        .route("/tasks/batch/cancel", post(batch_cancel_tasks).route_layer(require("task:submit")))
         // DAG endpoints (same as V1 for now)
-        .route("/dags", post(handlers::create_dag))
-        .route("/dags/:id", get(handlers::get_dag))
-        .route("/dags/:id/execute", post(handlers::execute_dag))
-        .route("/dags/:id/status", get(handlers::get_dag_status))
+        .route("/dags", post(handlers::create_dag).route_layer(require("swarm:create")))
+        .route("/dags/:id", get(handlers::get_dag).route_layer(require("swarm:read")))
+        .route("/dags/:id/execute", post(handlers::execute_dag).route_layer(require("swarm:create")))
+        .route("/dags/:id/status", get(handlers::get_dag_status).route_layer(require("swarm:read")))
         // Agent endpoints (same as V1 for now)
-        .route("/agents", get(handlers::list_agents))
-        .route("/agents", post(handlers::register_agent))
-        .route("/agents/:id", get(handlers::get_agent))
-        .route("/agents/:id", delete(handlers::remove_agent))
-        .route("/agents/:id/stats", get(handlers::get_agent_stats))
+        .route("/agents", get(handlers::list_agents).route_layer(require("agent:*")))
+        .route("/agents", post(handlers::register_agent).route_layer(require("agent:manage")))
+        .route("/agents/:id", get(handlers::get_agent).route_layer(require("agent:*")))
+        .route("/agents/:id", delete(handlers::remove_agent).route_layer(require("agent:manage")))
+        .route("/agents/:id/stats", get(handlers::get_agent_stats).route_layer(require("agent:*")))
         // Contract endpoints (same as V1 for now)
-        .route("/contracts", get(handlers::list_contracts))
-        .route("/contracts/:id", get(handlers::get_contract))
+        .route("/contracts", get(handlers::list_contracts).route_layer(require("task:read")))
+        .route("/contracts/:id", get(handlers::get_contract).route_layer(require("task:read")))
+        // Organization endpoints (same as V1 for now)
+        .route("/organizations/:id/usage", get(handlers::get_organization_usage).route_layer(require("task:read")))
         // Stats
-        .route("/stats", get(handlers::get_system_stats))
+        .route("/stats", get(handlers::get_system_stats).route_layer(require("swarm:read")))
         // V2 specific: Version info
         .route("/version", get(version_info))
 }
@@ -80,12 +91,50 @@ pub struct PaginationParams {
     /// Sort direction.
     #[serde(default)]
     pub sort_order: SortOrder,
+    /// Count the total exactly instead of using a recently cached count.
+    #[serde(default)]
+    pub refresh_count: bool,
 }
 
 fn default_limit() -> u32 {
     20
 }
 
+/// Fields `list_tasks_v2` can order by.
+const SORTABLE_FIELDS: &[&str] = &["created_at"];
+
+impl Validate for PaginationParams {
+    fn validate(&self) -> ValidationResult<()> {
+        let mut errors = ValidationErrors::new();
+        if self.limit == 0 || self.limit > 100 {
+            errors.add_error(
+                "limit",
+                ValidationErrorKind::Range {
+                    min: "1".to_string(),
+                    max: "100".to_string(),
+                    actual: self.limit.to_string(),
+                },
+            );
+        }
+        if self.cursor.as_deref().is_some_and(|c| base64_decode_offset(c).is_err()) {
+            errors.add_with_message(
+                "cursor",
+                ValidationErrorKind::Custom { code: "invalid_cursor".to_string() },
+                "Must be a cursor returned by a previous page",
+            );
+        }
+        if self.sort_by.as_deref().is_some_and(|f| !SORTABLE_FIELDS.contains(&f)) {
+            errors.add_error(
+                "sort_by",
+                ValidationErrorKind::NotInSet {
+                    allowed: SORTABLE_FIELDS.iter().map(|s| s.to_string()).collect(),
+                },
+            );
+        }
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SortOrder {
@@ -105,6 +154,8 @@ pub struct PaginatedResponse<T> {
 #[derive(Debug, Serialize)]
 pub struct PaginationInfo {
     pub total: u64,
+    /// `total` came from the count cache and may be up to a few seconds stale.
+    pub is_estimate: bool,
     pub limit: u32,
     pub has_more: bool,
     pub next_cursor: Option<String>,
@@ -147,37 +198,35 @@ pub struct BatchSummary {
 // ═══════════════════════════════════════════════════════════════════════════════
 
 /// List tasks with V2 cursor-based pagination.
+///
+/// The `Link` header mirrors the body's cursors and adds the first and last
+/// pages.
 pub async fn list_tasks_v2(
     State(state): State<AppState>,
-    Query(params): Query<PaginationParams>,
+    OriginalUri(uri): OriginalUri,
+    scope: TenantScope,
+    ValidatedQuery(params): ValidatedQuery<PaginationParams>,
 ) -> impl IntoResponse {
-    let limit = params.limit.min(100) as i64;
-    let offset = if let Some(ref cursor_str) = params.cursor {
-        // Decode cursor to get the offset
-        match base64_decode_offset(cursor_str) {
-            Ok(off) => off,
-            Err(_) => 0i64,
-        }
-    } else {
-        0i64
-    };
+    let limit = params.limit as i64;
+    let offset = params.cursor.as_deref().and_then(|c| base64_decode_offset(c).ok()).unwrap_or(0);
 
-    let total = match state.db.get_task_count().await {
-        Ok(count) => count as u64,
-        Err(_) => return Json(PaginatedResponse::<serde_json::Value> {
+    let (total, is_estimate) = match task_total(&state.cache, &state.db, &scope, params.refresh_count).await {
+        Ok(total) => total,
+        Err(_) => return (PageLinks::new(), Json(PaginatedResponse::<serde_json::Value> {
             success: false,
             data: vec![],
             pagination: PaginationInfo {
                 total: 0,
+                is_estimate: false,
                 limit: params.limit,
                 has_more: false,
                 next_cursor: None,
                 prev_cursor: None,
             },
-        }),
+        })),
     };
 
-    match state.db.get_tasks_paginated(limit + 1, offset).await {
+    match state.db.get_tasks_paginated(limit + 1, offset, &scope).await {
         Ok(tasks) => {
             let has_more = tasks.len() as i64 > limit;
             let tasks: Vec<serde_json::Value> = tasks.iter().take(limit as usize).map(|t| {
@@ -205,30 +254,87 @@ pub async fn list_tasks_v2(
                 None
             };
 
-            Json(PaginatedResponse::<serde_json::Value> {
+            let mut links = PageLinks::new().with_link("first", page_url(&uri, &[("cursor", None)]));
+            if let Some(cursor) = &prev_cursor {
+                links = links.with_link("prev", page_url(&uri, &[("cursor", Some(cursor))]));
+            }
+            if let Some(cursor) = &next_cursor {
+                links = links.with_link("next", page_url(&uri, &[("cursor", Some(cursor))]));
+            }
+            if total > 0 {
+                let last = base64_encode_offset((total as i64 - 1) / limit * limit);
+                links = links.with_link("last", page_url(&uri, &[("cursor", Some(&last))]));
+            }
+
+            (links, Json(PaginatedResponse::<serde_json::Value> {
                 success: true,
                 data: tasks,
                 pagination: PaginationInfo {
                     total,
+                    is_estimate,
                     limit: params.limit,
                     has_more,
                     next_cursor,
                     prev_cursor,
                 },
-            })
+            }))
         }
-        Err(_) => Json(PaginatedResponse::<serde_json::Value> {
+        Err(_) => (PageLinks::new(), Json(PaginatedResponse::<serde_json::Value> {
             success: false,
             data: vec![],
             pagination: PaginationInfo {
                 total: 0,
+                is_estimate: false,
                 limit: params.limit,
                 has_more: false,
                 next_cursor: None,
                 prev_cursor: None,
             },
-        }),
+        })),
+    }
+}
+
+/// How long a listing's total count is reused before counting again.
+const TASK_COUNT_TTL: Duration = Duration::from_secs(10);
+
+/// Cache key for the total of a task listing.
+///
+/// Segments name the tenant and then the listing's filter; `list_tasks_v2`
+/// is unfiltered, so every page shares the `all` count. Unscoped callers
+/// count under the `all` tenant.
+fn task_count_key(scope: &TenantScope, filter: &str) -> CacheKey {
+    let tenant = scope
+        .organization_filter()
+        .map_or_else(|| "all".to_string(), |org| org.to_string());
+    CacheKey::metrics("task_count")
+        .with_segment(tenant)
+        .with_segment(filter)
+        .with_ttl(TASK_COUNT_TTL)
+}
+
+/// Total task count, and whether it came from the cache.
+///
+/// Paging through a large table would otherwise run `COUNT(*)` per page.
+/// `refresh` skips the cached value (and re-primes it). Cache errors fall
+/// back to an exact count.
+async fn task_total(
+    cache: &Cache,
+    db: &Database,
+    scope: &TenantScope,
+    refresh: bool,
+) -> crate::error::Result<(u64, bool)> {
+    let key = task_count_key(scope, "all");
+    if !refresh {
+        if let Ok(Some(total)) = cache.get::<u64>(&key).await {
+            return Ok((total, true));
+        }
+    }
+
+    let total = db.get_task_count(scope).await? as u64;
+    if let Err(e) = cache.set(&key, &total).await {
+        tracing::debug!(error = %e, "Failed to cache task count");
     }
+    Ok((total, false))
 }
 
 /// Encode an offset into a base64 cursor string.
@@ -247,59 +353,107 @@ fn base64_decode_offset(cursor: &str) -> Result<i64, ()> {
 }
 
 /// Batch create tasks.
+///
+/// Items are validated independently: an invalid one fails in its result
+/// without affecting the rest, and the response is still `200` with
+/// `success: false`. The quota is checked against the valid items only.
 pub async fn batch_create_tasks(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
+    scope: TenantScope,
     Json(req): Json<BatchRequest<handlers::CreateTaskRequest>>,
 ) -> impl IntoResponse {
-    let mut results: Vec<BatchResult<serde_json::Value>> = Vec::with_capacity(req.items.len());
+    let prepared: Vec<_> = req.items.into_iter().map(prepare_batch_task).collect();
+    let valid = prepared.iter().filter(|task| task.is_ok()).count() as u64;
+
+    // The valid items are accepted or refused against the quota together
+    let quota = match valid {
+        0 => Ok(()),
+        _ => handlers::enforce_quota(&state, &scope, valid)
+            .await
+            .map_err(|e| e.user_message().to_string()),
+    };
+
+    Json(task_batch_response(prepared, quota))
+}
+
+/// Sanitize and validate one item of a batch, building its task.
+fn prepare_batch_task(mut req: handlers::CreateTaskRequest) -> Result<Task, String> {
+    req.sanitize();
+    let errors = req.validate();
+    if !errors.is_empty() {
+        return Err(serde_json::to_string(&errors).unwrap_or_else(|_| "Validation failed".to_string()));
+    }
+
+    let input = TaskInput {
+        instruction: req.instruction,
+        context: req.context.unwrap_or(serde_json::Value::Null),
+        parameters: serde_json::Value::Null,
+        artifacts: vec![],
+    };
+    let mut task = Task::new(req.name, input);
+    if let Some(priority) = req.priority {
+        task.priority = priority;
+    }
+    Ok(task)
+}
+
+/// Per-item results of a task batch; valid items fail with `quota`'s error
+/// if it refused them.
+fn task_batch_response(
+    prepared: Vec<Result<Task, String>>,
+    quota: Result<(), String>,
+) -> BatchResponse<serde_json::Value> {
+    let total = prepared.len();
+    let mut results: Vec<BatchResult<serde_json::Value>> = Vec::with_capacity(total);
     let mut succeeded = 0usize;
-    let failed = 0usize;
-
-    for (i, task_req) in req.items.iter().enumerate() {
-        let input = TaskInput {
-            instruction: task_req.instruction.clone(),
-            context: task_req.context.clone().unwrap_or(serde_json::Value::Null),
-            parameters: serde_json::Value::Null,
-            artifacts: vec![],
-        };
+    let mut failed = 0usize;
 
-        let mut task = Task::new(&task_req.name, input);
-        if let Some(priority) = task_req.priority {
-            task.priority = priority;
+    for (i, task) in prepared.into_iter().enumerate() {
+        let task = task.and_then(|task| quota.clone().map(|()| task));
+        match task {
+            Ok(task) => {
+                results.push(BatchResult {
+                    index: i,
+                    success: true,
+                    data: Some(serde_json::json!({
+                        "id": task.id.0,
+                        "name": task.name,
+                        "status": task.status.as_str(),
+                        "tokens_used": task.tokens_used,
+                        "cost_dollars": task.cost_dollars,
+                        "created_at": task.created_at.to_rfc3339(),
+                    })),
+                    error: None,
+                });
+                succeeded += 1;
+            }
+            Err(error) => {
+                results.push(BatchResult {
+                    index: i,
+                    success: false,
+                    data: None,
+                    error: Some(error),
+                });
+                failed += 1;
+            }
         }
-
-        let response = serde_json::json!({
-            "id": task.id.0,
-            "name": task.name,
-            "status": task.status.as_str(),
-            "tokens_used": task.tokens_used,
-            "cost_dollars": task.cost_dollars,
-            "created_at": task.created_at.to_rfc3339(),
-        });
-
-        results.push(BatchResult {
-            index: i,
-            success: true,
-            data: Some(response),
-            error: None,
-        });
-        succeeded += 1;
     }
 
-    Json(BatchResponse {
+    BatchResponse {
         success: failed == 0,
         results,
         summary: BatchSummary {
-            total: req.items.len(),
+            total,
             succeeded,
             failed,
         },
-    })
+    }
 }
 
 /// Batch cancel tasks.
 pub async fn batch_cancel_tasks(
     State(state): State<AppState>,
+    scope: TenantScope,
     Json(req): Json<BatchRequest<Uuid>>,
 ) -> impl IntoResponse {
     let mut results: Vec<BatchResult<serde_json::Value>> = Vec::with_capacity(req.items.len());
@@ -307,8 +461,8 @@ pub async fn batch_cancel_tasks(
     let mut failed = 0usize;
 
     for (i, task_id) in req.items.iter().enumerate() {
-        match state.db.update_task_status(TaskId(*task_id), TaskStatus::Cancelled).await {
-            Ok(_) => {
+        match state.db.update_task_status(TaskId(*task_id), TaskStatus::Cancelled, None, &scope).await {
+            Ok(true) => {
                 results.push(BatchResult {
                     index: i,
                     success: true,
@@ -320,6 +474,15 @@ pub async fn batch_cancel_tasks(
                 });
                 succeeded += 1;
             }
+            Ok(false) => {
+                results.push(BatchResult {
+                    index: i,
+                    success: false,
+                    data: None,
+                    error: Some("Task not found".to_string()),
+                });
+                failed += 1;
+            }
             Err(e) => {
                 results.push(BatchResult {
                     index: i,
@@ -405,5 +568,74 @@ mod tests {
     fn test_pagination_defaults() {
         let params: PaginationParams = serde_json::from_str("{}").unwrap();
         assert_eq!(params.limit, 20);
+        assert!(!params.refresh_count);
+    }
+
+    #[test]
+    fn test_pagination_params_validation() {
+        let params: PaginationParams = serde_json::from_str("{}").unwrap();
+        assert!(params.validate().is_ok());
+
+        let params: PaginationParams = serde_json::from_str(
+            r#"{"limit": 500, "cursor": "garbage!", "sort_by": "password"}"#,
+        ).unwrap();
+        let errors = params.validate().unwrap_err();
+        assert!(errors.has_errors("limit"));
+        assert!(errors.has_errors("cursor"));
+        assert!(errors.has_errors("sort_by"));
+
+        let params = PaginationParams {
+            cursor: Some(base64_encode_offset(40)),
+            ..serde_json::from_str(r#"{"sort_by": "created_at"}"#).unwrap()
+        };
+        assert!(params.validate().is_ok());
+    }
+
+    #[test]
+    fn test_task_batch_counts_invalid_items_as_failed() {
+        let items: Vec<handlers::CreateTaskRequest> = serde_json::from_value(serde_json::json!([
+            { "name": "ok", "instruction": "Summarize the report" },
+            { "name": "", "instruction": "No name" },
+            { "name": "bad priority", "instruction": "x", "priority": 500 },
+            { "name": "also ok", "instruction": "Translate it" },
+        ])).unwrap();
+        let prepared: Vec<_> = items.into_iter().map(prepare_batch_task).collect();
+
+        let response = task_batch_response(prepared, Ok(()));
+        assert!(!response.success);
+        assert_eq!((response.summary.total, response.summary.succeeded, response.summary.failed), (4, 2, 2));
+        let outcomes: Vec<_> = response.results.iter().map(|r| (r.index, r.success)).collect();
+        assert_eq!(outcomes, [(0, true), (1, false), (2, false), (3, true)]);
+        assert!(response.results[1].error.as_deref().unwrap().contains("name"));
+        assert!(response.results[2].error.as_deref().unwrap().contains("priority"));
+
+        // A refused quota fails the valid items too
+        let valid = vec![prepare_batch_task(serde_json::from_value(serde_json::json!(
+            { "name": "ok", "instruction": "Summarize" }
+        )).unwrap())];
+        let response = task_batch_response(valid, Err("Quota exceeded".to_string()));
+        assert!(!response.success);
+        assert_eq!(response.summary.failed, 1);
+        assert_eq!(response.results[0].error.as_deref(), Some("Quota exceeded"));
+    }
+
+    #[tokio::test]
+    async fn test_task_total_uses_cached_count() {
+        let cache = Cache::in_memory(100);
+        // Never reachable: any exact count fails
+        let db = Database::connect_lazy("postgres://apex@127.0.0.1:1/apex").unwrap();
+
+        let scope = TenantScope::All;
+        assert!(task_total(&cache, &db, &scope, false).await.is_err());
+
+        cache.set(&task_count_key(&scope, "all"), &1234u64).await.unwrap();
+        assert_eq!(task_total(&cache, &db, &scope, false).await.unwrap(), (1234, true));
+
+        // An exact count is requested, so the cache is bypassed
+        assert!(task_total(&cache, &db, &scope, true).await.is_err());
+
+        // Another tenant never sees that count
+        let tenant = TenantScope::Organization("7c9e6679-7425-40de-944b-e07fc1f90ae7".into());
+        assert!(task_total(&cache, &db, &tenant, false).await.is_err());
     }
 }
@@ -11,16 +11,21 @@
 //! 2. Server validates JWT and registers the connection
 //! 3. Server sends `Connected` with connection ID and session ID
 //! 4. If `session_id` was provided, session is restored (subs, auth, missed msgs)
-//! 5. Client sends `Subscribe`/`Unsubscribe` for room-based filtering
-//! 6. Server sends periodic heartbeats
+//! 5. Client sends `Subscribe`/`Unsubscribe` for room-based filtering; task, agent
+//!    and DAG rooms are only joined if the connection's organization owns them
+//! 6. Server sends periodic heartbeats and pings; clients silent past the timeout are evicted
 //! 7. On disconnect, session is persisted for future reconnection
 //!
 //! ## Reconnection with Exponential Backoff (Client-Side)
 //!
+//! The server sends the backoff parameters in `connected` and enforces them;
+//! see the reconnection contract in [`crate::websocket`].
+//!
 //! ```text
-//! base_delay = 1000ms, max_delay = 30000ms, max_attempts = 5
-//! for attempt in 0..max_attempts:
-//!     delay = min(base_delay * 2^attempt, max_delay) + random_jitter
+//! base_delay = connected.reconnect_backoff_ms, max_attempts = connected.max_reconnect_attempts
+//! for attempt in 0..:
+//!     delay = base_delay * 2^min(attempt, max_attempts) + random_jitter
+//!     delay = max(delay, retry_after_ms from the last closing/close frame)
 //!     connect to /ws?session_id=<prev>&token=<jwt>
 //! ```
 //!
@@ -30,27 +35,31 @@
 
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
-        Query, State,
+        ws::{close_code, Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Query, State,
     },
-    response::IntoResponse,
+    http::HeaderMap,
+    response::Response,
 };
 use chrono::Utc;
 use futures::{SinkExt, StreamExt};
 use serde::Deserialize;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc};
-use tokio::time::{interval, Duration, Instant};
+use tokio::time::{interval, Duration};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
 use super::AppState;
 
+use crate::orchestrator::OrchestratorStats;
 use crate::websocket::{
-    WebSocketConfig, WebSocketState,
-    handler::{ConnectionId, ConnectionState, WebSocketConnection},
+    AgentMetrics, MetricsSnapshot, ResourceMetrics, SystemMetrics, TaskMetrics,
+    WebSocketState,
+    handler::{self, ConnectionId, ConnectionState, WebSocketConnection},
     message::{
-        ClientMessage, ServerMessage,
+        ClientMessage, ServerMessage, PresenceChange, SubscriptionTarget,
         ErrorNotification, ErrorSeverity, ErrorSource,
     },
     room::RoomId,
@@ -68,21 +77,40 @@ const CHANNEL_CAPACITY: usize = 100;
 const BACKPRESSURE_WARN_THRESHOLD: usize = 80;
 
 /// Handle WebSocket upgrade with authentication and session recovery.
+///
+/// Upgrades from an IP that already holds `max_connections_per_ip`
+/// connections get a 429. The IP is the peer address, or the
+/// `X-Forwarded-For` client when the peer is a trusted proxy.
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     Query(params): Query<WsQueryParams>,
     State(state): State<AppState>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, params, state))
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+) -> Response {
+    let ip = state.ws.config.trusted_proxies.client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
+    if let Some(rejection) = handler::check_ip_limit(&state.ws.handler, ip).await {
+        return rejection;
+    }
+
+    let ws = handler::limit_message_size(ws, state.ws.config.max_message_size);
+    ws.on_upgrade(move |socket| handle_socket(socket, params, state, ip))
 }
 
 /// Full lifecycle WebSocket connection handler.
-async fn handle_socket(socket: WebSocket, params: WsQueryParams, _app_state: AppState) {
-    let (mut ws_sender, mut ws_receiver) = socket.split();
-    let (tx, mut rx) = mpsc::channel::<ServerMessage>(CHANNEL_CAPACITY);
+async fn handle_socket(mut socket: WebSocket, params: WsQueryParams, app_state: AppState, ip: Option<IpAddr>) {
+    let ws_state = app_state.ws.clone();
+    let ws_config = ws_state.config.clone();
 
-    let ws_config = WebSocketConfig::default();
-    let ws_state = Arc::new(WebSocketState::new(ws_config.clone()));
+    // Refuse clients that reconnect faster than their backoff allows
+    if let Err(retry_after) = ws_state.handler.check_reconnect(params.session_id.as_deref(), ip) {
+        warn!(session_id = ?params.session_id, ip = ?ip, retry_after_ms = retry_after.as_millis() as u64, "Reconnect refused: too soon");
+        handler::reject_with_retry(&mut socket, "Reconnecting too fast", retry_after).await;
+        return;
+    }
+
+    let (mut ws_sender, mut ws_receiver) = socket.split();
+    let (tx, rx) = mpsc::channel::<ServerMessage>(CHANNEL_CAPACITY);
 
     let mut connection = WebSocketConnection::new(tx.clone());
     let conn_id = connection.id;
@@ -102,9 +130,6 @@ async fn handle_socket(socket: WebSocket, params: WsQueryParams, _app_state: App
                             connection.claims = Some(claims);
                         }
                     }
-                    for rid in session::strings_to_room_ids(&sd.subscribed_rooms) {
-                        connection.add_subscription(rid);
-                    }
                     recovered_session = Some(sd);
                 }
                 Ok(None) => warn!(session_id = %old_session_id, "No stored session found"),
@@ -129,8 +154,13 @@ async fn handle_socket(socket: WebSocket, params: WsQueryParams, _app_state: App
         }
     }
 
-    if let Err(e) = ws_state.handler.register_connection(connection, None).await {
-        error!(error = %e, "Failed to register connection");
+    // Registration re-checks the per-IP limit; concurrent upgrades can race past the pre-check
+    if let Err(e) = ws_state.handler.register_connection(connection, ip).await {
+        warn!(error = %e, "Failed to register connection");
+        let _ = ws_sender.send(Message::Close(Some(axum::extract::ws::CloseFrame {
+            code: close_code::AGAIN,
+            reason: e.into(),
+        }))).await;
         return;
     }
 
@@ -138,6 +168,8 @@ async fn handle_socket(socket: WebSocket, params: WsQueryParams, _app_state: App
         connection_id: conn_id.to_string(),
         server_time: Utc::now(),
         session_id: session_id.clone(),
+        reconnect_backoff_ms: ws_config.reconnection_backoff_ms,
+        max_reconnect_attempts: ws_config.max_reconnection_attempts,
     };
     if let Ok(json) = serde_json::to_string(&connected_msg) {
         if ws_sender.send(Message::Text(json)).await.is_err() {
@@ -148,7 +180,7 @@ async fn handle_socket(socket: WebSocket, params: WsQueryParams, _app_state: App
 
     // Restore session subscriptions and replay missed messages
     if let Some(ref sd) = recovered_session {
-        let room_ids = session::strings_to_room_ids(&sd.subscribed_rooms);
+        let room_ids = ws_state.authorized_rooms(conn_id, session::strings_to_room_ids(&sd.subscribed_rooms)).await;
         {
             let mut rm = ws_state.room_manager.write().await;
             for r in &room_ids { rm.join_room(conn_id, r.clone()); }
@@ -176,42 +208,52 @@ async fn handle_socket(socket: WebSocket, params: WsQueryParams, _app_state: App
     }
 
     let mut heartbeat_timer = interval(Duration::from_secs(ws_config.heartbeat_interval_secs));
-    let forward_handle = {
-        let mut ws_sender = ws_sender;
-        tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                if let Ok(json) = serde_json::to_string(&msg) {
-                    if ws_sender.send(Message::Text(json)).await.is_err() {
-                        break;
-                    }
-                }
-            }
-        })
-    };
-    let mut last_activity = Instant::now();
+    let mut forward_handle = handler::spawn_outgoing(
+        ws_sender, rx, ws_state.handler.clone(), conn_id,
+        Duration::from_secs(ws_config.heartbeat_interval_secs),
+    );
+    let mut global_rx = ws_state.broadcaster.subscribe_global();
+    // Set once a close notice is queued, so the outgoing task gets to flush it
+    let mut closing = false;
 
     loop {
         tokio::select! {
+            received = global_rx.recv() => {
+                if let Some(notice) = handler::shutdown_notice(received) {
+                    info!(connection_id = %conn_id, "Server shutting down, closing connection");
+                    let _ = tx.send(notice).await;
+                    closing = true;
+                    break;
+                }
+            }
+
             msg = ws_receiver.next() => {
                 match msg {
                     Some(Ok(Message::Text(text))) => {
-                        last_activity = Instant::now();
                         ws_state.handler.record_message_received();
                         handle_client_message(&text, conn_id, &ws_state, &tx).await;
                     }
                     Some(Ok(Message::Ping(_))) => {
-                        last_activity = Instant::now();
                         let _ = tx.send(ServerMessage::Pong {
                             client_timestamp: None,
                             server_timestamp: Utc::now().timestamp_millis(),
                         }).await;
                     }
+                    Some(Ok(Message::Pong(_))) => {
+                        ws_state.handler.record_pong(conn_id).await;
+                    }
                     Some(Ok(Message::Close(_))) => {
                         info!(connection_id = %conn_id, "Client requested close");
                         break;
                     }
                     Some(Err(e)) => {
-                        error!(connection_id = %conn_id, error = %e, "WebSocket error");
+                        if let Some(notice) = handler::oversized_message_close(&e, ws_config.max_message_size) {
+                            warn!(connection_id = %conn_id, error = %e, "Incoming message over the size limit, closing");
+                            let _ = tx.try_send(notice);
+                            closing = true;
+                        } else {
+                            error!(connection_id = %conn_id, error = %e, "WebSocket error");
+                        }
                         break;
                     }
                     None => {
@@ -231,14 +273,22 @@ async fn handle_socket(socket: WebSocket, params: WsQueryParams, _app_state: App
                     let _ = tx.try_send(ServerMessage::Closing {
                         reason: "Client too slow".to_string(),
                         code: 1008,
+                        retry_after_ms: Some(ws_config.reconnection_backoff_ms),
                     });
+                    closing = true;
                     break;
                 }
                 if tx.send(ServerMessage::Heartbeat { timestamp: Utc::now().timestamp_millis() }).await.is_err() {
                     break;
                 }
-                if last_activity.elapsed() > Duration::from_secs(ws_config.connection_timeout_secs) {
-                    warn!(connection_id = %conn_id, "Connection timed out");
+                if ws_state.handler.is_heartbeat_expired(conn_id).await {
+                    warn!(connection_id = %conn_id, "No pong within timeout, disconnecting");
+                    let _ = tx.try_send(ServerMessage::Closing {
+                        reason: "Heartbeat timeout".to_string(),
+                        code: close_code::AWAY,
+                        retry_after_ms: None,
+                    });
+                    closing = true;
                     break;
                 }
             }
@@ -261,9 +311,12 @@ async fn handle_socket(socket: WebSocket, params: WsQueryParams, _app_state: App
         }
     }
 
+    if closing {
+        let _ = tokio::time::timeout(handler::SHUTDOWN_FLUSH_TIMEOUT, &mut forward_handle).await;
+    }
     forward_handle.abort();
     ws_state.handler.unregister_connection(conn_id).await;
-    ws_state.room_manager.write().await.remove_connection_from_all(conn_id);
+    ws_state.leave_all_rooms(conn_id).await;
     info!(connection_id = %conn_id, "WebSocket connection closed and cleaned up");
 }
 
@@ -320,19 +373,39 @@ async fn handle_client_message(
 
         ClientMessage::Subscribe { target } => {
             let room_id: RoomId = (&target).into();
-            { state.room_manager.write().await.join_room(conn_id, room_id.clone()); }
+            if let Err(reason) = state.authorize_subscription(conn_id, &room_id).await {
+                debug!(connection_id = %conn_id, room = %room_id.as_str(), %reason, "Subscription denied");
+                let _ = tx.send(ServerMessage::SubscribeDenied { target, reason }).await;
+                return;
+            }
+            let joined = state.room_manager.write().await.join_room(conn_id, room_id.clone());
             let _ = state.handler.add_subscription(conn_id, room_id.clone()).await;
+            if joined { state.notify_presence_change(&room_id, conn_id, PresenceChange::Joined).await; }
             let _ = tx.send(ServerMessage::Subscribed { target, current_state: None }).await;
         }
 
         ClientMessage::Unsubscribe { target } => {
             let room_id: RoomId = (&target).into();
-            { state.room_manager.write().await.leave_room(conn_id, &room_id); }
+            let left = state.room_manager.write().await.leave_room(conn_id, &room_id);
             let _ = state.handler.remove_subscription(conn_id, &room_id).await;
+            if left { state.notify_presence_change(&room_id, conn_id, PresenceChange::Left).await; }
             let _ = tx.send(ServerMessage::Unsubscribed { target }).await;
         }
 
+        ClientMessage::QueryPresence { room } => {
+            // Only connections that may join a room may see who is in it
+            if let Err(reason) = state.authorize_subscription(conn_id, &room).await {
+                debug!(connection_id = %conn_id, room = %room.as_str(), %reason, "Presence query denied");
+                if let Some(target) = SubscriptionTarget::for_room(&room) {
+                    let _ = tx.send(ServerMessage::SubscribeDenied { target, reason }).await;
+                }
+                return;
+            }
+            let _ = tx.send(state.presence(&room, conn_id).await).await;
+        }
+
         ClientMessage::Ping { timestamp } => {
+            state.handler.record_pong(conn_id).await;
             let _ = tx.send(ServerMessage::Pong {
                 client_timestamp: timestamp,
                 server_timestamp: Utc::now().timestamp_millis(),
@@ -370,7 +443,7 @@ async fn handle_client_message(
                     Ok(Some(sd)) => {
                         let last_id = last_message_id.map(|id| id as i64)
                             .or(sd.last_seen_event_id).unwrap_or(0);
-                        let room_ids = session::strings_to_room_ids(&sd.subscribed_rooms);
+                        let room_ids = state.authorized_rooms(conn_id, session::strings_to_room_ids(&sd.subscribed_rooms)).await;
                         let mut all_missed = Vec::new();
                         for r in &room_ids {
                             { state.room_manager.write().await.join_room(conn_id, r.clone()); }
@@ -413,7 +486,7 @@ async fn handle_client_message(
                                 let _ = state.handler.authenticate_connection(conn_id, claims).await;
                             }
                         }
-                        let room_ids = session::strings_to_room_ids(&sd.subscribed_rooms);
+                        let room_ids = state.authorized_rooms(conn_id, session::strings_to_room_ids(&sd.subscribed_rooms)).await;
                         let mut total_missed: usize = 0;
                         for r in &room_ids {
                             { state.room_manager.write().await.join_room(conn_id, r.clone()); }
@@ -451,6 +524,66 @@ async fn handle_client_message(
     }
 }
 
+/// Push a [`MetricsSnapshot`] built from the orchestrator stats to the metrics
+/// room every `metrics_interval_secs`.
+pub fn spawn_metrics_broadcast(state: AppState) -> tokio::task::JoinHandle<()> {
+    let started = std::time::Instant::now();
+    let period = Duration::from_secs(state.ws.config.metrics_interval_secs.max(1));
+
+    tokio::spawn(async move {
+        let mut ticker = interval(period);
+        loop {
+            ticker.tick().await;
+            let active_connections = state.ws.handler.get_stats().await.active_connections;
+            let snapshot = metrics_snapshot(
+                &state.orchestrator.stats(),
+                active_connections as u64,
+                started.elapsed().as_secs(),
+            );
+            state.ws.broadcast_metrics(snapshot).await;
+        }
+    })
+}
+
+/// Map orchestrator stats onto the dashboard snapshot. Fields the orchestrator
+/// doesn't track are left at zero.
+fn metrics_snapshot(stats: &OrchestratorStats, active_connections: u64, uptime_seconds: u64) -> MetricsSnapshot {
+    MetricsSnapshot {
+        timestamp: Utc::now(),
+        agents: AgentMetrics {
+            total: stats.registered_agents as u64,
+            active: stats.busy_agents as u64,
+            idle: stats.registered_agents.saturating_sub(stats.busy_agents) as u64,
+            errored: 0,
+            avg_success_rate: 0.0,
+            total_tasks_completed: 0,
+        },
+        tasks: TaskMetrics {
+            queued: 0,
+            running: stats.max_workers.saturating_sub(stats.available_workers) as u64,
+            completed_last_hour: 0,
+            failed_last_hour: 0,
+            avg_duration_ms: 0.0,
+            p99_duration_ms: 0.0,
+        },
+        resources: ResourceMetrics {
+            total_tokens_used: 0,
+            total_cost_dollars: 0.0,
+            tokens_last_hour: 0,
+            cost_last_hour: 0.0,
+            budget_remaining: None,
+        },
+        system: SystemMetrics {
+            cpu_usage_percent: 0.0,
+            memory_usage_percent: 0.0,
+            active_connections,
+            db_pool_available: 0,
+            cache_hit_rate: 0.0,
+            uptime_seconds,
+        },
+    }
+}
+
 /// Legacy broadcast compatibility shim. Use `WebSocketState::broadcast_*` for new code.
 #[allow(dead_code)]
 pub async fn broadcast_update(update: ServerMessage, tx: &broadcast::Sender<String>) {
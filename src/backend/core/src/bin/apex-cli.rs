@@ -3,13 +3,15 @@
 //! This CLI provides commands for managing tasks, agents, DAGs, approvals,
 //! database migrations, system health, and configuration.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::io::{self, Write};
+use std::path::PathBuf;
 use std::time::Duration;
 use tabled::{
     settings::{Style, Modify, object::Columns, Alignment},
@@ -17,6 +19,10 @@ use tabled::{
 };
 use uuid::Uuid;
 
+use apex_core::config::{Config, LlmConfig};
+use apex_core::dag::DagSpec;
+use apex_core::health::{HealthChecker, LlmProvider, LlmProviderHealthChecker};
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // CLI Structure
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -35,10 +41,14 @@ struct Cli {
     #[arg(short, long, global = true, default_value = "text")]
     format: OutputFormat,
 
-    /// Configuration file path
-    #[arg(short, long, global = true)]
+    /// Configuration file path [default: apex.toml, or $APEX_CONFIG]
+    #[arg(short, long, global = true, env = "APEX_CONFIG")]
     config: Option<String>,
 
+    /// API server URL
+    #[arg(long, global = true, env = "APEX_API_URL", default_value = "http://localhost:8080")]
+    api_url: String,
+
     /// Verbose output
     #[arg(short, long, global = true)]
     verbose: bool,
@@ -84,7 +94,7 @@ enum Commands {
     /// Seed the database with sample data
     Seed {
         /// Number of sample records to create
-        #[arg(short, long, default_value = "10")]
+        #[arg(long, default_value = "10")]
         count: u32,
 
         /// Seed specific entity type
@@ -117,6 +127,13 @@ enum Commands {
     /// View and edit configuration
     #[command(subcommand)]
     Config(ConfigCommands),
+
+    /// Print a shell completion script to stdout
+    #[command(hide = true)]
+    Completions {
+        /// Target shell
+        shell: Shell,
+    },
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -150,8 +167,16 @@ enum TaskCommands {
         task_id: Uuid,
 
         /// Show full output data
-        #[arg(short, long)]
+        #[arg(long)]
         full: bool,
+
+        /// Keep refreshing until the task finishes
+        #[arg(short, long)]
+        watch: bool,
+
+        /// Refresh interval in seconds (with --watch)
+        #[arg(long, default_value = "2")]
+        interval: u64,
     },
 
     /// Create a new task
@@ -183,7 +208,7 @@ enum TaskCommands {
         task_id: Uuid,
 
         /// Force cancel even if running
-        #[arg(short, long)]
+        #[arg(long)]
         force: bool,
     },
 
@@ -236,7 +261,7 @@ enum AgentCommands {
         agent_id: Uuid,
 
         /// Show performance history
-        #[arg(short, long)]
+        #[arg(long)]
         history: bool,
     },
 
@@ -276,6 +301,13 @@ enum AgentStatusFilter {
 
 #[derive(Subcommand)]
 enum DagCommands {
+    /// Create a DAG from a workflow file
+    Create {
+        /// Workflow definition (YAML or JSON)
+        #[arg(long)]
+        file: PathBuf,
+    },
+
     /// List all DAGs
     List {
         /// Filter by status
@@ -295,6 +327,14 @@ enum DagCommands {
         /// Show task tree
         #[arg(short, long)]
         tree: bool,
+
+        /// Keep refreshing until the DAG finishes
+        #[arg(short, long)]
+        watch: bool,
+
+        /// Refresh interval in seconds (with --watch)
+        #[arg(long, default_value = "2")]
+        interval: u64,
     },
 
     /// Start a DAG execution
@@ -317,7 +357,7 @@ enum DagCommands {
         dag_id: Uuid,
 
         /// Force stop (cancel running tasks)
-        #[arg(short, long)]
+        #[arg(long)]
         force: bool,
 
         /// Reason for stopping
@@ -379,7 +419,7 @@ enum ApprovalCommands {
         approval_id: Uuid,
 
         /// Approval comment
-        #[arg(short, long)]
+        #[arg(long)]
         comment: Option<String>,
     },
 
@@ -422,7 +462,7 @@ enum MigrateCommands {
     /// Revert the last migration
     Revert {
         /// Number of migrations to revert
-        #[arg(short, long, default_value = "1")]
+        #[arg(long, default_value = "1")]
         count: u32,
 
         /// Revert all migrations
@@ -430,7 +470,7 @@ enum MigrateCommands {
         all: bool,
 
         /// Force revert without confirmation
-        #[arg(short, long)]
+        #[arg(long)]
         force: bool,
     },
 
@@ -481,7 +521,7 @@ enum ConfigCommands {
     /// Validate configuration
     Validate {
         /// Configuration file to validate
-        #[arg(short, long)]
+        #[arg(long)]
         file: Option<String>,
     },
 
@@ -492,7 +532,7 @@ enum ConfigCommands {
         output: String,
 
         /// Overwrite existing file
-        #[arg(short, long)]
+        #[arg(long)]
         force: bool,
     },
 }
@@ -575,6 +615,22 @@ struct DagSummary {
     created_at: String,
 }
 
+/// `{ success, data, error }` wrapper returned by the API.
+#[derive(Debug, Deserialize)]
+struct ApiEnvelope<T> {
+    success: bool,
+    data: Option<T>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CreatedDag {
+    id: Uuid,
+    name: String,
+    task_count: usize,
+    status: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Tabled)]
 struct ApprovalSummary {
     #[tabled(rename = "ID")]
@@ -772,6 +828,86 @@ impl OutputHelper {
     }
 }
 
+/// Unwrap the API's `{ success, data, error }` envelope.
+async fn read_api_response<T: DeserializeOwned>(response: reqwest::Response, url: &str) -> Result<T> {
+    let body: ApiEnvelope<T> = response
+        .json()
+        .await
+        .with_context(|| format!("Failed to parse response from {}", url))?;
+    match body.data {
+        Some(data) if body.success => Ok(data),
+        _ => anyhow::bail!("API error: {}", body.error.unwrap_or_else(|| "Unknown error".into())),
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Watch Mode
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Poll `url` and redraw with `render` whenever the entity changes, until its
+/// `status` is terminal or the user presses Ctrl-C.
+///
+/// With `--format json` each change is written as one line of JSON instead.
+async fn watch_until_terminal(
+    url: &str,
+    interval: Duration,
+    output: &OutputHelper,
+    render: impl Fn(&serde_json::Value),
+) -> Result<()> {
+    let client = reqwest::Client::new();
+    let mut ticker = tokio::time::interval(interval.max(Duration::from_millis(100)));
+    let mut last = None;
+
+    loop {
+        let poll = async {
+            ticker.tick().await;
+            let response = client
+                .get(url)
+                .send()
+                .await
+                .with_context(|| format!("GET {} failed", url))?;
+            read_api_response::<serde_json::Value>(response, url).await
+        };
+
+        let snapshot = tokio::select! {
+            snapshot = poll => snapshot?,
+            _ = tokio::signal::ctrl_c() => {
+                if !matches!(output.format, OutputFormat::Json) {
+                    output.print_info("Stopped watching");
+                }
+                return Ok(());
+            }
+        };
+
+        if last.as_ref() != Some(&snapshot) {
+            match output.format {
+                OutputFormat::Json => println!("{}", serde_json::to_string(&snapshot)?),
+                _ => {
+                    // Clear the screen and move the cursor home before redrawing.
+                    print!("\x1b[2J\x1b[H");
+                    render(&snapshot);
+                }
+            }
+            io::stdout().flush()?;
+        }
+
+        let finished = matches!(json_str(&snapshot, "status"), "completed" | "failed" | "cancelled");
+        last = Some(snapshot);
+        if finished {
+            return Ok(());
+        }
+    }
+}
+
+fn json_str<'a>(value: &'a serde_json::Value, key: &str) -> &'a str {
+    value[key].as_str().unwrap_or("-")
+}
+
+fn progress_gauge(percent: f64, width: usize) -> String {
+    let filled = ((percent.clamp(0.0, 100.0) / 100.0) * width as f64).round() as usize;
+    format!("[{}{}]", "#".repeat(filled), ".".repeat(width - filled))
+}
+
 fn create_progress_bar(len: u64, message: &str) -> ProgressBar {
     let pb = ProgressBar::new(len);
     pb.set_style(
@@ -800,7 +936,7 @@ fn create_spinner(message: &str) -> ProgressBar {
 // Command Handlers
 // ═══════════════════════════════════════════════════════════════════════════════
 
-async fn handle_task_command(cmd: TaskCommands, output: &OutputHelper) -> Result<()> {
+async fn handle_task_command(cmd: TaskCommands, api_url: &str, output: &OutputHelper) -> Result<()> {
     match cmd {
         TaskCommands::List { status: _, dag_id: _, limit, offset } => {
             output.print_header("Tasks");
@@ -849,7 +985,20 @@ async fn handle_task_command(cmd: TaskCommands, output: &OutputHelper) -> Result
             ));
         }
 
-        TaskCommands::Get { task_id, full } => {
+        TaskCommands::Get { task_id, watch: true, interval, .. } => {
+            let url = format!("{}/api/v1/tasks/{}", api_url.trim_end_matches('/'), task_id);
+            watch_until_terminal(&url, Duration::from_secs(interval), output, |task| {
+                output.print_header(&format!("Task: {}", task_id));
+                output.print_key_value("Name", json_str(task, "name"));
+                output.print_key_value("Status", &format_status(json_str(task, "status")));
+                output.print_key_value("Tokens Used", &task["tokens_used"].to_string());
+                output.print_key_value("Cost", &format!("${:.3}", task["cost_dollars"].as_f64().unwrap_or(0.0)));
+                output.print_key_value("Created", json_str(task, "created_at"));
+            })
+            .await?;
+        }
+
+        TaskCommands::Get { task_id, full, .. } => {
             output.print_header(&format!("Task: {}", task_id));
             output.print_key_value("ID", &task_id.to_string());
             output.print_key_value("Name", "Research market trends");
@@ -998,8 +1147,41 @@ async fn handle_agent_command(cmd: AgentCommands, output: &OutputHelper) -> Resu
     Ok(())
 }
 
-async fn handle_dag_command(cmd: DagCommands, output: &OutputHelper) -> Result<()> {
+async fn handle_dag_command(cmd: DagCommands, api_url: &str, output: &OutputHelper) -> Result<()> {
     match cmd {
+        DagCommands::Create { file } => {
+            let content = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read workflow file {}", file.display()))?;
+            let spec: DagSpec = serde_yaml::from_str(&content)
+                .with_context(|| format!("Failed to parse workflow file {}", file.display()))?;
+
+            // Catch unknown references and cycles before anything is sent.
+            spec.validate()
+                .map_err(|e| anyhow::anyhow!("Invalid workflow {}: {}", file.display(), e.user_message()))?;
+
+            let spinner = create_spinner("Creating DAG...");
+            let url = format!("{}/api/v1/dags", api_url.trim_end_matches('/'));
+            let response = reqwest::Client::new()
+                .post(&url)
+                .json(&spec)
+                .send()
+                .await
+                .with_context(|| format!("POST {} failed", url));
+            spinner.finish_and_clear();
+
+            let dag: CreatedDag = read_api_response(response?, &url).await?;
+
+            match output.format {
+                OutputFormat::Json => output.print_json(&dag)?,
+                _ => {
+                    output.print_success(&format!("DAG {} created", dag.id));
+                    output.print_key_value("Name", &dag.name);
+                    output.print_key_value("Tasks", &dag.task_count.to_string());
+                    output.print_key_value("Status", &format_dag_status(&dag.status));
+                }
+            }
+        }
+
         DagCommands::List { status: _, limit: _ } => {
             output.print_header("DAGs");
 
@@ -1030,7 +1212,27 @@ async fn handle_dag_command(cmd: DagCommands, output: &OutputHelper) -> Result<(
             }
         }
 
-        DagCommands::Get { dag_id, tree } => {
+        DagCommands::Get { dag_id, watch: true, interval, .. } => {
+            let url = format!("{}/api/v1/dags/{}/status", api_url.trim_end_matches('/'), dag_id);
+            watch_until_terminal(&url, Duration::from_secs(interval), output, |dag| {
+                let tasks = &dag["tasks"];
+                let percent = dag["percent_complete"].as_f64().unwrap_or(0.0);
+                output.print_header(&format!("DAG: {}", dag_id));
+                output.print_key_value("Name", json_str(dag, "name"));
+                output.print_key_value("Status", &format_dag_status(json_str(dag, "status")));
+                output.print_key_value("Progress", &format!("{} {:.0}%", progress_gauge(percent, 30), percent));
+                output.print_key_value("Total Tasks", &tasks["total"].to_string());
+                output.print_key_value("Completed", &tasks["completed"].to_string());
+                output.print_key_value("Running", &tasks["running"].to_string());
+                output.print_key_value("Pending", &tasks["pending"].to_string());
+                output.print_key_value("Failed", &tasks["failed"].to_string());
+                output.print_key_value("Tokens Used", &dag["tokens_used"].to_string());
+                output.print_key_value("Cost", &format!("${:.3}", dag["cost_dollars"].as_f64().unwrap_or(0.0)));
+            })
+            .await?;
+        }
+
+        DagCommands::Get { dag_id, tree, .. } => {
             output.print_header(&format!("DAG: {}", dag_id));
             output.print_key_value("ID", &dag_id.to_string());
             output.print_key_value("Name", "Market Analysis Pipeline");
@@ -1357,14 +1559,19 @@ async fn handle_seed_command(count: u32, entity: Option<SeedEntity>, output: &Ou
     Ok(())
 }
 
-async fn handle_health_command(detailed: bool, _timeout: u64, output: &OutputHelper) -> Result<()> {
+async fn handle_health_command(
+    detailed: bool,
+    timeout: u64,
+    config_path: Option<&str>,
+    output: &OutputHelper,
+) -> Result<()> {
     output.print_header("System Health");
 
     let spinner = create_spinner("Checking system health...");
-    std::thread::sleep(Duration::from_millis(500));
+    let llm_rows = llm_provider_health(&load_llm_config(config_path), Duration::from_secs(timeout)).await;
     spinner.finish_and_clear();
 
-    let components = vec![
+    let mut components = vec![
         ComponentHealth {
             name: "PostgreSQL".to_string(),
             status: "Healthy".green().to_string(),
@@ -1389,19 +1596,8 @@ async fn handle_health_command(detailed: bool, _timeout: u64, output: &OutputHel
             latency_ms: 0,
             message: "Listening on :8080".to_string(),
         },
-        ComponentHealth {
-            name: "OpenAI API".to_string(),
-            status: "Healthy".green().to_string(),
-            latency_ms: 45,
-            message: "API key valid".to_string(),
-        },
-        ComponentHealth {
-            name: "Anthropic API".to_string(),
-            status: "Degraded".yellow().to_string(),
-            latency_ms: 120,
-            message: "High latency".to_string(),
-        },
     ];
+    components.extend(llm_rows);
 
     let health_status = HealthStatus {
         status: "Healthy".to_string(),
@@ -1429,6 +1625,51 @@ async fn handle_health_command(detailed: bool, _timeout: u64, output: &OutputHel
     Ok(())
 }
 
+/// LLM settings from the config file (or the environment when there is no
+/// file). Unreadable configuration counts as no providers configured.
+fn load_llm_config(config_path: Option<&str>) -> LlmConfig {
+    let path = config_file_path(config_path);
+    let config = if path.exists() {
+        Config::from_file(&path.to_string_lossy())
+    } else {
+        Config::load()
+    };
+    config.map(|c| c.llm).unwrap_or_default()
+}
+
+/// One row per LLM provider, checked against its API when it has a key.
+async fn llm_provider_health(llm: &LlmConfig, timeout: Duration) -> Vec<ComponentHealth> {
+    let checkers = LlmProviderHealthChecker::from_config(llm, timeout);
+    let rows = [(LlmProvider::OpenAi, "OpenAI API"), (LlmProvider::Anthropic, "Anthropic API")]
+        .into_iter()
+        .map(|(provider, label)| {
+            let checker = checkers.iter().find(|c| c.name() == provider.component_name());
+            async move {
+                let Some(checker) = checker else {
+                    return ComponentHealth {
+                        name: label.to_string(),
+                        status: "Not configured".dimmed().to_string(),
+                        latency_ms: 0,
+                        message: "No API key".to_string(),
+                    };
+                };
+                let health = checker.check().await;
+                let status = match health.status {
+                    apex_core::health::HealthStatus::Healthy => "Healthy".green(),
+                    apex_core::health::HealthStatus::Degraded => "Degraded".yellow(),
+                    apex_core::health::HealthStatus::Unhealthy => "Unhealthy".red(),
+                };
+                ComponentHealth {
+                    name: label.to_string(),
+                    status: status.to_string(),
+                    latency_ms: health.latency_ms.unwrap_or(0),
+                    message: health.error.or(health.message).unwrap_or_default(),
+                }
+            }
+        });
+    futures::future::join_all(rows).await
+}
+
 async fn handle_stats_command(period: String, live: bool, output: &OutputHelper) -> Result<()> {
     output.print_header(&format!("System Statistics ({})", period));
 
@@ -1507,7 +1748,9 @@ async fn handle_stats_command(period: String, live: bool, output: &OutputHelper)
     Ok(())
 }
 
-async fn handle_config_command(cmd: ConfigCommands, output: &OutputHelper) -> Result<()> {
+async fn handle_config_command(cmd: ConfigCommands, config_path: Option<&str>, output: &OutputHelper) -> Result<()> {
+    let path = config_file_path(config_path);
+
     match cmd {
         ConfigCommands::Show { section, show_secrets } => {
             output.print_header("Configuration");
@@ -1552,6 +1795,7 @@ async fn handle_config_command(cmd: ConfigCommands, output: &OutputHelper) -> Re
                     println!();
                     println!("{}", "[orchestrator]".bold());
                     output.print_key_value("max_concurrent_agents", "100");
+                    output.print_key_value("max_concurrent_dags", "100");
                     output.print_key_value("enable_model_routing", "true");
                     output.print_key_value("circuit_breaker_threshold", "5");
                     output.print_key_value("default_token_limit", "20000");
@@ -1582,44 +1826,45 @@ async fn handle_config_command(cmd: ConfigCommands, output: &OutputHelper) -> Re
         }
 
         ConfigCommands::Get { key } => {
-            let value = match key.as_str() {
-                "server.port" => "8080",
-                "server.host" => "0.0.0.0",
-                "server.grpc_port" => "50051",
-                "database.max_connections" => "20",
-                "orchestrator.max_concurrent_agents" => "100",
-                _ => {
-                    output.print_error(&format!("Unknown configuration key: {}", key));
-                    return Ok(());
-                }
-            };
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read configuration file {}", path.display()))?;
+            let config = parse_config(&text, &path)?;
+            let value = config_value(&config, &key)?;
 
             match output.format {
                 OutputFormat::Json => {
-                    println!(r#"{{"key": "{}", "value": "{}"}}"#, key, value);
-                }
-                _ => {
-                    println!("{}", value);
+                    output.print_json(&serde_json::json!({ "key": key, "value": value }))?;
                 }
+                _ => match value {
+                    serde_json::Value::String(s) => println!("{}", s),
+                    other => println!("{}", other),
+                },
             }
         }
 
         ConfigCommands::Set { key, value, persist } => {
-            output.print_success(&format!("Configuration updated: {} = {}", key, value));
+            let mut doc = load_config_document(&path)?;
+            set_config_value(&mut doc, &key, &value, &path)?;
+
             if persist {
-                output.print_info("Changes persisted to configuration file");
+                std::fs::write(&path, doc.to_string())
+                    .with_context(|| format!("Failed to write configuration file {}", path.display()))?;
+                output.print_success(&format!("Configuration updated: {} = {}", key, value));
+                output.print_info(&format!("Changes persisted to {}", path.display()));
             } else {
-                output.print_warning("Changes are temporary and will be lost on restart. Use --persist to save.");
+                output.print_success(&format!("Configuration value is valid: {} = {}", key, value));
+                output.print_warning("Nothing was written. Use --persist to save.");
             }
         }
 
         ConfigCommands::Validate { file } => {
-            let spinner = create_spinner("Validating configuration...");
-            std::thread::sleep(Duration::from_millis(300));
-            spinner.finish_and_clear();
+            let path = file.map(PathBuf::from).unwrap_or(path);
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read configuration file {}", path.display()))?;
+            parse_config(&text, &path)?;
 
             output.print_success("Configuration is valid");
-            output.print_key_value("File", file.as_deref().unwrap_or("(default)"));
+            output.print_key_value("File", &path.display().to_string());
         }
 
         ConfigCommands::Init { output: output_path, force } => {
@@ -1648,15 +1893,22 @@ pool_size = 10
 [observability]
 log_level = "info"
 json_logging = true
+# redact these fields from logged task payloads, besides keys/passwords/tokens
+# redacted_fields = ["email", "phone"]
 # otlp_endpoint = "http://localhost:4317"
 
 [orchestrator]
 max_concurrent_agents = 100
+max_concurrent_dags = 100
+# share workers between concurrent DAGs by their weight
+fair_scheduling = false
 enable_model_routing = true
 circuit_breaker_threshold = 5
 default_token_limit = 20000
 default_cost_limit = 0.25
 default_time_limit = 300
+# "redis" dispatches to Python workers; "inprocess" calls the LLM providers directly
+runner = "redis"
 
 [llm]
 # openai_api_key = "sk-..."
@@ -1672,6 +1924,103 @@ default_model = "gpt-4o-mini"
     Ok(())
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Config File
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Used when neither `--config` nor `APEX_CONFIG` is set.
+const DEFAULT_CONFIG_PATH: &str = "apex.toml";
+
+fn config_file_path(explicit: Option<&str>) -> PathBuf {
+    PathBuf::from(explicit.unwrap_or(DEFAULT_CONFIG_PATH))
+}
+
+/// Load the file for editing, keeping its comments and layout. A missing
+/// file yields an empty document.
+fn load_config_document(path: &std::path::Path) -> Result<toml_edit::DocumentMut> {
+    if !path.exists() {
+        return Ok(toml_edit::DocumentMut::new());
+    }
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read configuration file {}", path.display()))?;
+    text.parse()
+        .with_context(|| format!("Failed to parse configuration file {}", path.display()))
+}
+
+/// Deserialize into [`Config`]; errors carry the line, column and key.
+fn parse_config(text: &str, path: &std::path::Path) -> Result<Config> {
+    toml::from_str(text).map_err(|e| anyhow::anyhow!("Invalid configuration in {}: {}", path.display(), e))
+}
+
+/// Every known key with its default, as JSON. `database.url` has no default
+/// and is filled with an empty string.
+fn config_reference() -> serde_json::Value {
+    let config: Config = toml::from_str("[database]\nurl = \"\"").expect("defaults deserialize");
+    serde_json::to_value(config).expect("config serializes")
+}
+
+/// Look up a dotted key such as `server.port`.
+fn config_value(config: &Config, key: &str) -> Result<serde_json::Value> {
+    let json = serde_json::to_value(config)?;
+    key.split('.')
+        .try_fold(&json, |node, segment| node.get(segment))
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("Unknown configuration key: {}", key))
+}
+
+/// Set a dotted key in `doc`. The value is parsed as the type the key
+/// expects, and the edited document must still deserialize into [`Config`].
+fn set_config_value(doc: &mut toml_edit::DocumentMut, key: &str, raw: &str, path: &std::path::Path) -> Result<()> {
+    let reference = config_reference();
+    let expected = key
+        .split('.')
+        .try_fold(&reference, |node, segment| node.get(segment))
+        .ok_or_else(|| anyhow::anyhow!("Unknown configuration key: {}", key))?;
+    if expected.is_object() {
+        anyhow::bail!("{} is a section; set one of its keys instead", key);
+    }
+
+    let value = match expected {
+        serde_json::Value::String(_) => toml_edit::Value::from(raw),
+        _ => raw.parse::<toml_edit::Value>().unwrap_or_else(|_| toml_edit::Value::from(raw)),
+    };
+
+    let segments: Vec<&str> = key.split('.').collect();
+    let (last, parents) = segments.split_last().expect("key has at least one segment");
+    let mut table = doc.as_table_mut() as &mut dyn toml_edit::TableLike;
+    for segment in parents {
+        table = table
+            .entry(segment)
+            .or_insert_with(toml_edit::table)
+            .as_table_like_mut()
+            .ok_or_else(|| anyhow::anyhow!("{} is not a table in {}", segment, path.display()))?;
+    }
+    match table.get_mut(last).and_then(|item| item.as_value_mut()) {
+        // Keep the existing decor (inline comments, spacing) around the value.
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = value;
+            *existing.decor_mut() = decor;
+        }
+        None => {
+            table.insert(last, toml_edit::Item::Value(value));
+        }
+    }
+
+    // Required keys that are still missing don't block an edit; `config
+    // validate` reports them.
+    let mut check = doc.clone();
+    let database = check.entry("database").or_insert_with(toml_edit::table);
+    if let Some(database) = database.as_table_like_mut() {
+        if database.get("url").is_none() {
+            database.insert("url", toml_edit::value(""));
+        }
+    }
+    parse_config(&check.to_string(), path)
+        .with_context(|| format!("Cannot set {} = {}", key, raw))?;
+    Ok(())
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Formatting Helpers
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -1708,6 +2057,11 @@ fn format_dag_status(status: &str) -> String {
     }
 }
 
+/// Write the completion script for `shell` to `out`.
+fn write_completions(shell: Shell, out: &mut dyn Write) {
+    clap_complete::generate(shell, &mut Cli::command(), "apex", out);
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Main Entry Point
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -1722,15 +2076,19 @@ async fn main() -> Result<()> {
 
     // Handle commands
     let result = match cli.command {
-        Commands::Task(cmd) => handle_task_command(cmd, &output).await,
+        Commands::Task(cmd) => handle_task_command(cmd, &cli.api_url, &output).await,
         Commands::Agent(cmd) => handle_agent_command(cmd, &output).await,
-        Commands::Dag(cmd) => handle_dag_command(cmd, &output).await,
+        Commands::Dag(cmd) => handle_dag_command(cmd, &cli.api_url, &output).await,
         Commands::Approval(cmd) => handle_approval_command(cmd, &output).await,
         Commands::Migrate(cmd) => handle_migrate_command(cmd, &output).await,
         Commands::Seed { count, entity } => handle_seed_command(count, entity, &output).await,
-        Commands::Health { detailed, timeout } => handle_health_command(detailed, timeout, &output).await,
+        Commands::Health { detailed, timeout } => handle_health_command(detailed, timeout, cli.config.as_deref(), &output).await,
         Commands::Stats { period, live } => handle_stats_command(period, live, &output).await,
-        Commands::Config(cmd) => handle_config_command(cmd, &output).await,
+        Commands::Config(cmd) => handle_config_command(cmd, cli.config.as_deref(), &output).await,
+        Commands::Completions { shell } => {
+            write_completions(shell, &mut io::stdout());
+            Ok(())
+        }
     };
 
     if let Err(e) = result {
@@ -1740,3 +2098,65 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"# Apex Configuration File
+
+[server]
+port = 8080 # public port
+
+[database]
+url = "postgres://localhost/apex"
+"#;
+
+    #[test]
+    fn test_set_config_value_preserves_comments() {
+        let path = std::path::Path::new("apex.toml");
+        let mut doc: toml_edit::DocumentMut = SAMPLE.parse().unwrap();
+
+        set_config_value(&mut doc, "server.port", "9090", path).unwrap();
+        set_config_value(&mut doc, "orchestrator.max_concurrent_agents", "12", path).unwrap();
+        set_config_value(&mut doc, "llm.default_model", "gpt-4o", path).unwrap();
+
+        let text = doc.to_string();
+        assert!(text.starts_with("# Apex Configuration File"));
+        assert!(text.contains("port = 9090 # public port"), "{}", text);
+
+        let config = parse_config(&text, path).unwrap();
+        assert_eq!(config.server.port, 9090);
+        assert_eq!(config.orchestrator.max_concurrent_agents, 12);
+        assert_eq!(config_value(&config, "llm.default_model").unwrap(), "gpt-4o");
+    }
+
+    #[test]
+    fn test_set_config_value_in_new_file() {
+        let path = std::path::Path::new("apex.toml");
+        let mut doc = toml_edit::DocumentMut::new();
+        set_config_value(&mut doc, "server.port", "9090", path).unwrap();
+        assert_eq!(doc.to_string().trim(), "[server]\nport = 9090");
+    }
+
+    #[test]
+    fn test_set_config_value_rejects_bad_input() {
+        let path = std::path::Path::new("apex.toml");
+        let mut doc: toml_edit::DocumentMut = SAMPLE.parse().unwrap();
+
+        let err = set_config_value(&mut doc, "server.port", "not-a-port", path).unwrap_err();
+        assert!(format!("{:#}", err).contains("server.port"), "{:#}", err);
+        assert!(set_config_value(&mut doc, "server.prot", "1", path).is_err());
+        assert!(set_config_value(&mut doc, "server", "1", path).is_err());
+    }
+
+    #[test]
+    fn test_completions_for_each_shell() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            write_completions(shell, &mut script);
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("apex"), "{:?} completions missing command name", shell);
+        }
+    }
+}
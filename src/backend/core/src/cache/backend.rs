@@ -5,7 +5,10 @@
 //! - **RedisBackend**: Distributed cache using Redis
 //! - **MultiTierBackend**: L1 (memory) + L2 (Redis) multi-tier caching
 
+use super::format::SerializationFormat;
+use super::key::KeyType;
 use crate::error::{ApexError, ErrorCode, Result};
+use crate::telemetry::CacheMetrics;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
@@ -29,6 +32,15 @@ pub struct CacheEntry {
     /// Serialized data
     pub data: Vec<u8>,
 
+    /// Format `data` is encoded in; entries written before formats were
+    /// recorded are JSON
+    #[serde(default)]
+    pub format: SerializationFormat,
+
+    /// Tombstone recording that the value doesn't exist; `data` is empty
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub negative: bool,
+
     /// Time-to-live
     #[serde(with = "duration_serde")]
     pub ttl: Option<Duration>,
@@ -41,6 +53,18 @@ pub struct CacheEntry {
 }
 
 impl CacheEntry {
+    /// A tombstone for a value known not to exist.
+    pub fn tombstone(ttl: Duration, tags: Vec<String>) -> Self {
+        Self {
+            data: Vec::new(),
+            format: SerializationFormat::default(),
+            negative: true,
+            ttl: Some(ttl),
+            tags,
+            created_at: Utc::now(),
+        }
+    }
+
     /// Check if the entry has expired.
     pub fn is_expired(&self) -> bool {
         if let Some(ttl) = self.ttl {
@@ -252,9 +276,8 @@ impl InMemoryBackend {
                 let mut lru = self.lru_order.lock().await;
                 while evicted < to_evict && !lru.is_empty() {
                     if let Some(key) = lru.pop_front() {
-                        if let Some((_, entry)) = self.entries.remove(&key) {
-                            self.remove_from_tag_index(&key, &entry.entry.tags);
-                            self.size_bytes.fetch_sub(entry.entry.data.len() as u64, Ordering::Relaxed);
+                        if self.remove_entry(&key).is_some() {
+                            CacheMetrics::record_evictions(KeyType::label_for_key(&key), 1);
                             evicted += 1;
                         }
                     }
@@ -263,9 +286,8 @@ impl InMemoryBackend {
                 // Random eviction
                 let keys: Vec<_> = self.entries.iter().take(to_evict).map(|e| e.key().clone()).collect();
                 for key in keys {
-                    if let Some((_, entry)) = self.entries.remove(&key) {
-                        self.remove_from_tag_index(&key, &entry.entry.tags);
-                        self.size_bytes.fetch_sub(entry.entry.data.len() as u64, Ordering::Relaxed);
+                    if self.remove_entry(&key).is_some() {
+                        CacheMetrics::record_evictions(KeyType::label_for_key(&key), 1);
                         evicted += 1;
                     }
                 }
@@ -276,6 +298,15 @@ impl InMemoryBackend {
         }
     }
 
+    /// Remove an entry along with its tag index, size and metrics bookkeeping.
+    fn remove_entry(&self, key: &str) -> Option<InMemoryEntry> {
+        let (_, entry) = self.entries.remove(key)?;
+        self.remove_from_tag_index(key, &entry.entry.tags);
+        self.size_bytes.fetch_sub(entry.entry.data.len() as u64, Ordering::Relaxed);
+        CacheMetrics::adjust_entries(KeyType::label_for_key(key), -1.0);
+        Some(entry)
+    }
+
     /// Add key to tag index.
     fn add_to_tag_index(&self, key: &str, tags: &[String]) {
         for tag in tags {
@@ -321,9 +352,7 @@ impl InMemoryBackend {
         }
 
         for key in keys_to_remove {
-            if let Some((_, entry)) = self.entries.remove(&key) {
-                self.remove_from_tag_index(&key, &entry.entry.tags);
-                self.size_bytes.fetch_sub(entry.entry.data.len() as u64, Ordering::Relaxed);
+            if self.remove_entry(&key).is_some() {
                 expired += 1;
             }
         }
@@ -342,7 +371,7 @@ impl CacheBackend for InMemoryBackend {
         if let Some(mut entry) = self.entries.get_mut(key) {
             if entry.entry.is_expired() {
                 drop(entry);
-                self.entries.remove(key);
+                self.remove_entry(key);
                 self.misses.fetch_add(1, Ordering::Relaxed);
                 counter!("cache_misses_total", "backend" => "in_memory", "reason" => "expired").increment(1);
                 return Ok(None);
@@ -370,13 +399,9 @@ impl CacheBackend for InMemoryBackend {
         let size = entry.data.len();
         let tags = entry.tags.clone();
 
-        // Update size tracking
-        if let Some(existing) = self.entries.get(key) {
-            self.size_bytes.fetch_sub(existing.entry.data.len() as u64, Ordering::Relaxed);
-            self.remove_from_tag_index(key, &existing.entry.tags);
-        }
-
-        self.entries.insert(
+        // Update size tracking from whatever the insert replaced, so racing
+        // writers of one key count it once
+        let replaced = self.entries.insert(
             key.to_string(),
             InMemoryEntry {
                 entry,
@@ -384,6 +409,13 @@ impl CacheBackend for InMemoryBackend {
                 access_count: 0,
             },
         );
+        match replaced {
+            Some(existing) => {
+                self.size_bytes.fetch_sub(existing.entry.data.len() as u64, Ordering::Relaxed);
+                self.remove_from_tag_index(key, &existing.entry.tags);
+            }
+            None => CacheMetrics::adjust_entries(KeyType::label_for_key(key), 1.0),
+        }
 
         self.size_bytes.fetch_add(size as u64, Ordering::Relaxed);
         self.add_to_tag_index(key, &tags);
@@ -396,9 +428,7 @@ impl CacheBackend for InMemoryBackend {
     }
 
     async fn delete(&self, key: &str) -> Result<bool> {
-        if let Some((_, entry)) = self.entries.remove(key) {
-            self.remove_from_tag_index(key, &entry.entry.tags);
-            self.size_bytes.fetch_sub(entry.entry.data.len() as u64, Ordering::Relaxed);
+        if self.remove_entry(key).is_some() {
             counter!("cache_deletes_total", "backend" => "in_memory").increment(1);
             Ok(true)
         } else {
@@ -445,10 +475,14 @@ impl CacheBackend for InMemoryBackend {
     }
 
     async fn clear(&self) -> Result<()> {
-        self.entries.clear();
-        self.tag_index.clear();
+        // Remove entry by entry, so each one leaves the gauge exactly once
+        // even if a set races the clear
+        let keys: Vec<String> = self.entries.iter().map(|e| e.key().clone()).collect();
+        for key in keys {
+            self.remove_entry(&key);
+        }
+        self.tag_index.retain(|_, keys| !keys.is_empty());
         self.lru_order.lock().await.clear();
-        self.size_bytes.store(0, Ordering::Relaxed);
         counter!("cache_clears_total", "backend" => "in_memory").increment(1);
         Ok(())
     }
@@ -465,9 +499,7 @@ impl CacheBackend for InMemoryBackend {
         let keys: Vec<_> = self.entries.iter().filter(|e| regex.is_match(e.key())).map(|e| e.key().clone()).collect();
 
         for key in keys {
-            if let Some((_, entry)) = self.entries.remove(&key) {
-                self.remove_from_tag_index(&key, &entry.entry.tags);
-                self.size_bytes.fetch_sub(entry.entry.data.len() as u64, Ordering::Relaxed);
+            if self.remove_entry(&key).is_some() {
                 deleted += 1;
             }
         }
@@ -991,6 +1023,8 @@ mod tests {
     fn test_cache_entry_expiration() {
         let entry = CacheEntry {
             data: vec![1, 2, 3],
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_millis(100)),
             tags: vec!["test".to_string()],
             created_at: Utc::now() - chrono::Duration::milliseconds(200),
@@ -1003,6 +1037,8 @@ mod tests {
     fn test_cache_entry_not_expired() {
         let entry = CacheEntry {
             data: vec![1, 2, 3],
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(3600)),
             tags: vec!["test".to_string()],
             created_at: Utc::now(),
@@ -1015,6 +1051,8 @@ mod tests {
     fn test_cache_entry_no_ttl() {
         let entry = CacheEntry {
             data: vec![1, 2, 3],
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: None,
             tags: vec!["test".to_string()],
             created_at: Utc::now() - chrono::Duration::days(365),
@@ -1032,6 +1070,8 @@ mod tests {
 
         let entry = CacheEntry {
             data: b"test data".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["tag1".to_string()],
             created_at: Utc::now(),
@@ -1059,6 +1099,8 @@ mod tests {
 
         let entry = CacheEntry {
             data: b"data".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["project-123".to_string()],
             created_at: Utc::now(),
@@ -1085,6 +1127,8 @@ mod tests {
         for i in 0..10 {
             let entry = CacheEntry {
                 data: vec![i as u8],
+                format: SerializationFormat::Json,
+                negative: false,
                 ttl: Some(Duration::from_secs(60)),
                 tags: vec![],
                 created_at: Utc::now(),
@@ -1104,6 +1148,8 @@ mod tests {
 
         let entry = CacheEntry {
             data: b"test".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec![],
             created_at: Utc::now(),
@@ -1129,6 +1175,8 @@ mod tests {
 
         let entry = CacheEntry {
             data: b"expired data".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_millis(1)),
             tags: vec![],
             created_at: Utc::now() - chrono::Duration::seconds(10),
@@ -1146,6 +1194,8 @@ mod tests {
 
         let entry = CacheEntry {
             data: b"data".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_millis(1)),
             tags: vec![],
             created_at: Utc::now() - chrono::Duration::seconds(10),
@@ -1171,6 +1221,8 @@ mod tests {
 
         let entry = CacheEntry {
             data: b"data".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["tag".to_string()],
             created_at: Utc::now(),
@@ -1196,6 +1248,8 @@ mod tests {
 
         let entry1 = CacheEntry {
             data: b"original".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["v1".to_string()],
             created_at: Utc::now(),
@@ -1204,6 +1258,8 @@ mod tests {
 
         let entry2 = CacheEntry {
             data: b"updated".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["v2".to_string()],
             created_at: Utc::now(),
@@ -1220,6 +1276,8 @@ mod tests {
 
         let entry = CacheEntry {
             data: b"data".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec![],
             created_at: Utc::now(),
@@ -1243,6 +1301,8 @@ mod tests {
 
         let entry = CacheEntry {
             data: b"data".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["project-a".to_string(), "urgent".to_string()],
             created_at: Utc::now(),
@@ -1267,6 +1327,8 @@ mod tests {
     fn test_cache_entry_remaining_ttl() {
         let entry = CacheEntry {
             data: vec![1, 2, 3],
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(3600)),
             tags: vec![],
             created_at: Utc::now(),
@@ -1282,6 +1344,8 @@ mod tests {
     fn test_cache_entry_remaining_ttl_none_when_no_ttl() {
         let entry = CacheEntry {
             data: vec![1, 2, 3],
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: None,
             tags: vec![],
             created_at: Utc::now(),
@@ -1328,6 +1392,8 @@ mod tests {
 
         let fresh = CacheEntry {
             data: b"fresh".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(3600)),
             tags: vec![],
             created_at: Utc::now(),
@@ -1335,6 +1401,8 @@ mod tests {
 
         let expired = CacheEntry {
             data: b"expired".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_millis(1)),
             tags: vec![],
             created_at: Utc::now() - chrono::Duration::seconds(10),
@@ -1347,4 +1415,45 @@ mod tests {
         assert_eq!(cleaned, 1);
         assert!(backend.exists("fresh-key").await.unwrap());
     }
+
+    #[test]
+    fn test_entry_gauge_counts_each_key_once() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let gauge = || {
+            snapshotter
+                .snapshot()
+                .into_vec()
+                .into_iter()
+                .find(|(key, ..)| key.key().name() == "apex_cache_entries")
+                .map(|(.., value)| value)
+        };
+        let entry = || CacheEntry {
+            data: b"test".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
+            ttl: None,
+            tags: vec!["t".to_string()],
+            created_at: Utc::now(),
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+
+        metrics::with_local_recorder(&recorder, || {
+            runtime.block_on(async {
+                let backend = InMemoryBackend::new(InMemoryConfig::default());
+                futures::future::join_all((0..10).map(|_| backend.set("apex:task:1", entry()))).await;
+                backend.set("apex:task:2", entry()).await.unwrap();
+                assert_eq!(gauge(), Some(DebugValue::Gauge(2.0.into())));
+
+                backend.delete("apex:task:1").await.unwrap();
+                assert_eq!(gauge(), Some(DebugValue::Gauge(1.0.into())));
+                backend.set("apex:task:3", entry()).await.unwrap();
+                backend.clear().await.unwrap();
+                assert_eq!(gauge(), Some(DebugValue::Gauge(0.0.into())));
+                assert!(backend.get_by_tag("t").await.unwrap().is_empty());
+            })
+        });
+    }
 }
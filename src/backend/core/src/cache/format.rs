@@ -0,0 +1,160 @@
+//! Serialization formats for cached values.
+//!
+//! Every [`CacheEntry`](super::CacheEntry) records the format its data was
+//! written in, so a cache can switch formats while entries written in the
+//! old one are still live: reads always decode with the entry's own format.
+//!
+//! - **JSON**: human-readable and the most permissive; the default
+//! - **MessagePack**: the same data model as JSON in a compact binary form
+//! - **bincode**: the smallest and fastest, but not self-describing, so it
+//!   can't hold `serde_json::Value`, untagged enums or fields skipped with
+//!   `skip_serializing_if`
+
+use crate::error::{ApexError, ErrorCode, Result};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// How a cached value is encoded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SerializationFormat {
+    #[default]
+    Json,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    Bincode,
+}
+
+impl SerializationFormat {
+    /// Format name as used in configuration.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SerializationFormat::Json => "json",
+            SerializationFormat::MessagePack => "msgpack",
+            SerializationFormat::Bincode => "bincode",
+        }
+    }
+
+    /// Encode `value` in this format.
+    pub fn serialize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Vec<u8>> {
+        let encoded = match self {
+            SerializationFormat::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            // Named fields keep struct changes and skipped fields readable
+            SerializationFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            SerializationFormat::Bincode => bincode::serialize(value).map_err(|e| e.to_string()),
+        };
+        encoded.map_err(|e| {
+            ApexError::with_internal(
+                ErrorCode::SerializationError,
+                "Failed to serialize value for cache",
+                format!("{}: {}", self.as_str(), e),
+            )
+        })
+    }
+
+    /// Decode a value written in this format.
+    pub fn deserialize<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T> {
+        let decoded = match self {
+            SerializationFormat::Json => serde_json::from_slice(data).map_err(|e| e.to_string()),
+            SerializationFormat::MessagePack => rmp_serde::from_slice(data).map_err(|e| e.to_string()),
+            SerializationFormat::Bincode => bincode::deserialize(data).map_err(|e| e.to_string()),
+        };
+        decoded.map_err(|e| {
+            ApexError::with_internal(
+                ErrorCode::DeserializationError,
+                "Failed to deserialize cached value",
+                format!("{}: {}", self.as_str(), e),
+            )
+        })
+    }
+}
+
+impl std::fmt::Display for SerializationFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for SerializationFormat {
+    type Err = ApexError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(SerializationFormat::Json),
+            "msgpack" | "messagepack" => Ok(SerializationFormat::MessagePack),
+            "bincode" => Ok(SerializationFormat::Bincode),
+            other => Err(ApexError::validation(format!(
+                "Unknown cache serialization format '{}' (expected json, msgpack or bincode)",
+                other
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        id: uuid::Uuid,
+        name: String,
+        score: f64,
+        tags: Vec<String>,
+        parent: Option<u64>,
+        counts: HashMap<String, u32>,
+        status: Status,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    enum Status {
+        Running { attempt: u32 },
+        Done,
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            id: uuid::Uuid::new_v4(),
+            name: "summarize".to_string(),
+            score: 0.75,
+            tags: vec!["a".to_string(), "b".to_string()],
+            parent: None,
+            counts: HashMap::from([("tokens".to_string(), 42)]),
+            status: Status::Running { attempt: 2 },
+        }
+    }
+
+    #[test]
+    fn test_round_trip_each_format() {
+        let value = sample();
+        for format in [SerializationFormat::Json, SerializationFormat::MessagePack, SerializationFormat::Bincode] {
+            let data = format.serialize(&value).unwrap();
+            let decoded: Sample = format.deserialize(&data).unwrap();
+            assert_eq!(decoded, value, "{}", format);
+        }
+    }
+
+    #[test]
+    fn test_binary_formats_are_smaller() {
+        let value = sample();
+        let json = SerializationFormat::Json.serialize(&value).unwrap().len();
+        assert!(SerializationFormat::MessagePack.serialize(&value).unwrap().len() < json);
+        assert!(SerializationFormat::Bincode.serialize(&value).unwrap().len() < json);
+    }
+
+    #[test]
+    fn test_wrong_format_is_a_deserialization_error() {
+        let data = SerializationFormat::Bincode.serialize(&sample()).unwrap();
+        let err = SerializationFormat::Json.deserialize::<Sample>(&data).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DeserializationError);
+    }
+
+    #[test]
+    fn test_parse_and_serde_names() {
+        assert_eq!("MsgPack".parse::<SerializationFormat>().unwrap(), SerializationFormat::MessagePack);
+        assert_eq!("bincode".parse::<SerializationFormat>().unwrap(), SerializationFormat::Bincode);
+        assert!("xml".parse::<SerializationFormat>().is_err());
+        assert_eq!(serde_json::to_string(&SerializationFormat::MessagePack).unwrap(), "\"msgpack\"");
+        assert_eq!(SerializationFormat::default(), SerializationFormat::Json);
+    }
+}
@@ -621,6 +621,7 @@ impl Default for InvalidationBatch {
 mod tests {
     use super::*;
     use crate::cache::backend::{InMemoryBackend, InMemoryConfig, CacheEntry};
+    use crate::cache::format::SerializationFormat;
 
     async fn create_test_backend() -> Arc<InMemoryBackend> {
         Arc::new(InMemoryBackend::new(InMemoryConfig::default()))
@@ -634,6 +635,8 @@ mod tests {
         // Add entries with tags
         let entry = CacheEntry {
             data: b"test".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["project-123".to_string()],
             created_at: Utc::now(),
@@ -659,6 +662,8 @@ mod tests {
         // Add entries
         let entry = CacheEntry {
             data: b"test".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec![],
             created_at: Utc::now(),
@@ -693,6 +698,8 @@ mod tests {
         // Add entry
         let entry = CacheEntry {
             data: b"test".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["tag1".to_string()],
             created_at: Utc::now(),
@@ -717,6 +724,8 @@ mod tests {
         // Add entries
         let entry = CacheEntry {
             data: b"test".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["batch-tag".to_string()],
             created_at: Utc::now(),
@@ -809,6 +818,8 @@ mod tests {
         let engine = InvalidationEngine::new(backend.clone());
         let entry = CacheEntry {
             data: b"data".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec![],
             created_at: Utc::now(),
@@ -826,6 +837,8 @@ mod tests {
         let engine = InvalidationEngine::new(backend.clone());
         let entry = CacheEntry {
             data: b"x".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["t".into()],
             created_at: Utc::now(),
@@ -869,6 +882,8 @@ mod tests {
         let engine = InvalidationEngine::new(backend.clone());
         let entry = CacheEntry {
             data: b"d".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec![],
             created_at: Utc::now(),
@@ -886,6 +901,8 @@ mod tests {
         let engine = InvalidationEngine::new(backend.clone());
         let entry = CacheEntry {
             data: b"d".to_vec(),
+            format: SerializationFormat::Json,
+            negative: false,
             ttl: Some(Duration::from_secs(60)),
             tags: vec!["my-tag".into()],
             created_at: Utc::now(),
@@ -59,6 +59,27 @@ pub enum KeyType {
 }
 
 impl KeyType {
+    /// Every key type, in declaration order.
+    pub const ALL: [KeyType; 13] = [
+        Self::Task, Self::Agent, Self::Dag, Self::Contract, Self::User, Self::Session,
+        Self::ApiResponse, Self::Config, Self::Metrics, Self::Routing, Self::ToolResult,
+        Self::RateLimit, Self::Custom,
+    ];
+
+    /// Classify a built key string by the first segment matching a type prefix.
+    ///
+    /// Backends only see strings; this recovers the type for metrics labels
+    /// regardless of namespace prefixes ahead of it.
+    pub fn from_key(key: &str) -> Option<KeyType> {
+        key.split(':')
+            .find_map(|segment| Self::ALL.into_iter().find(|t| t.prefix() == segment))
+    }
+
+    /// Metrics label for a built key string (`unknown` if unclassifiable).
+    pub fn label_for_key(key: &str) -> &'static str {
+        Self::from_key(key).map_or("unknown", |t| t.prefix())
+    }
+
     /// Get the default TTL for this key type.
     pub fn default_ttl(&self) -> Duration {
         match self {
@@ -610,6 +631,14 @@ mod tests {
         assert_eq!(display, "agent:agent-1");
     }
 
+    #[test]
+    fn test_key_type_from_key() {
+        let key = CacheKey::new(KeyType::Contract).with_id("c-1").with_namespace("tenant-a");
+        assert_eq!(KeyType::from_key(&format!("apex:cache:{}", key)), Some(KeyType::Contract));
+        assert_eq!(KeyType::label_for_key("apex:cache:tool:abc"), "tool");
+        assert_eq!(KeyType::label_for_key("something:else"), "unknown");
+    }
+
     #[test]
     fn test_key_type_default_ttl() {
         assert_eq!(KeyType::Task.default_ttl(), Duration::from_secs(60));
@@ -7,6 +7,7 @@
 //! - Response caching for idempotent requests
 
 use crate::cache::{Cache, CacheKey, KeyType};
+use crate::middleware::route_policy::SkipCache;
 use axum::{
     body::Body,
     extract::Request,
@@ -376,7 +377,7 @@ impl CacheMiddlewareConfigBuilder {
 }
 
 /// Simple glob matching.
-fn glob_matches(pattern: &str, text: &str) -> bool {
+pub(crate) fn glob_matches(pattern: &str, text: &str) -> bool {
     let parts: Vec<&str> = pattern.split('*').collect();
     if parts.len() == 1 {
         return pattern == text;
@@ -612,6 +613,11 @@ where
             // Call inner service
             let response = inner.call(request).await?;
 
+            // Streams never end, so buffering them to hash an ETag would hang
+            if response.extensions().get::<SkipCache>().is_some() {
+                return Ok(response);
+            }
+
             // Check if response is cacheable
             let status = response.status();
             let is_cacheable_response = config_clone.cacheable_status_codes.contains(&status);
@@ -817,6 +823,10 @@ pub async fn cache_middleware(
         return response;
     }
 
+    if response.extensions().get::<SkipCache>().is_some() {
+        return response;
+    }
+
     if !response.status().is_success() {
         return response;
     }
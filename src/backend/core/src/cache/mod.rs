@@ -4,6 +4,8 @@
 //!
 //! - **Backend Abstraction**: Pluggable backends (in-memory, Redis, multi-tier)
 //! - **Type-safe Keys**: Strongly-typed cache keys with namespacing and TTL
+//! - **Serialization Formats**: JSON, MessagePack or bincode, recorded per entry
+//! - **Negative Caching**: Short-lived tombstones for lookups that found nothing
 //! - **Invalidation Strategies**: Tag-based, pattern-based, and event-driven invalidation
 //! - **HTTP Middleware**: ETag support, Cache-Control headers, conditional requests
 //!
@@ -66,6 +68,7 @@
 //! ```
 
 pub mod backend;
+pub mod format;
 pub mod key;
 pub mod invalidation;
 pub mod middleware;
@@ -76,6 +79,7 @@ pub use backend::{
     RedisBackend, RedisConfig,
     MultiTierBackend, MultiTierConfig,
 };
+pub use format::SerializationFormat;
 pub use key::{CacheKey, KeyType, KeyBuilder};
 pub use invalidation::{
     InvalidationEngine, InvalidationEvent, InvalidationStrategy,
@@ -87,6 +91,7 @@ pub use middleware::{
 };
 
 use crate::error::{ApexError, ErrorCode, Result};
+use crate::telemetry::CacheMetrics;
 use serde::{de::DeserializeOwned, Serialize};
 use std::sync::Arc;
 use std::time::Duration;
@@ -116,6 +121,14 @@ pub struct CacheConfig {
 
     /// Compression threshold in bytes
     pub compression_threshold: usize,
+
+    /// Format new values are written in; existing entries are read in
+    /// whatever format they were written with
+    pub serialization_format: SerializationFormat,
+
+    /// TTL of tombstones for lookups that found nothing (`None` disables
+    /// negative caching)
+    pub negative_ttl: Option<Duration>,
 }
 
 impl Default for CacheConfig {
@@ -127,6 +140,8 @@ impl Default for CacheConfig {
             namespace_prefix: "apex:cache:".to_string(),
             enable_compression: true,
             compression_threshold: 1024, // 1 KB
+            serialization_format: SerializationFormat::Json,
+            negative_ttl: Some(Duration::from_secs(30)),
         }
     }
 }
@@ -175,6 +190,16 @@ impl CacheConfigBuilder {
         self
     }
 
+    pub fn serialization_format(mut self, format: SerializationFormat) -> Self {
+        self.config.serialization_format = format;
+        self
+    }
+
+    pub fn negative_ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.config.negative_ttl = ttl;
+        self
+    }
+
     pub fn build(self) -> CacheConfig {
         self.config
     }
@@ -184,6 +209,17 @@ impl CacheConfigBuilder {
 // Main Cache Interface
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Outcome of [`Cache::lookup`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CacheLookup<T> {
+    /// A cached value
+    Hit(T),
+    /// A tombstone: the value was recently looked up and doesn't exist
+    Negative,
+    /// Nothing cached
+    Miss,
+}
+
 /// Main cache interface providing a unified API over different backends.
 pub struct Cache {
     backend: Arc<dyn CacheBackend>,
@@ -241,24 +277,41 @@ impl Cache {
         Ok(Self::new(backend, CacheConfig::default()))
     }
 
-    /// Get a value from the cache.
-    #[instrument(skip(self), fields(key = %key))]
+    /// Get a value from the cache. A tombstone reads as `None`, like a miss;
+    /// use [`Cache::lookup`] to tell them apart.
     pub async fn get<T: DeserializeOwned>(&self, key: &CacheKey) -> Result<Option<T>> {
+        match self.lookup(key).await? {
+            CacheLookup::Hit(value) => Ok(Some(value)),
+            CacheLookup::Negative | CacheLookup::Miss => Ok(None),
+        }
+    }
+
+    /// Look up a value, telling a cached absence apart from a miss.
+    #[instrument(skip(self), fields(key = %key))]
+    pub async fn lookup<T: DeserializeOwned>(&self, key: &CacheKey) -> Result<CacheLookup<T>> {
         let full_key = self.build_key(key);
-        match self.backend.get(&full_key).await? {
+        let entry = self.backend.get(&full_key).await?;
+        if self.config.enable_metrics {
+            let key_type = key.key_type().prefix();
+            match entry {
+                Some(_) => CacheMetrics::record_hit(key_type),
+                None => CacheMetrics::record_miss(key_type),
+            }
+        }
+
+        match entry {
+            Some(entry) if entry.negative => {
+                debug!("Cache negative hit for key: {}", full_key);
+                Ok(CacheLookup::Negative)
+            }
             Some(entry) => {
-                let value: T = serde_json::from_slice(&entry.data)
-                    .map_err(|e| ApexError::with_internal(
-                        ErrorCode::DeserializationError,
-                        "Failed to deserialize cached value",
-                        e.to_string(),
-                    ))?;
+                let value: T = entry.format.deserialize(&entry.data)?;
                 debug!("Cache hit for key: {}", full_key);
-                Ok(Some(value))
+                Ok(CacheLookup::Hit(value))
             }
             None => {
                 debug!("Cache miss for key: {}", full_key);
-                Ok(None)
+                Ok(CacheLookup::Miss)
             }
         }
     }
@@ -278,12 +331,8 @@ impl Cache {
         value: &T,
         ttl: Duration,
     ) -> Result<()> {
-        let data = serde_json::to_vec(value)
-            .map_err(|e| ApexError::with_internal(
-                ErrorCode::SerializationError,
-                "Failed to serialize value for cache",
-                e.to_string(),
-            ))?;
+        let format = self.config.serialization_format;
+        let data = format.serialize(value)?;
 
         if data.len() > self.config.max_entry_size {
             return Err(ApexError::new(
@@ -295,6 +344,8 @@ impl Cache {
         let full_key = self.build_key(key);
         let entry = CacheEntry {
             data,
+            format,
+            negative: false,
             ttl: Some(ttl),
             tags: key.tags().to_vec(),
             created_at: chrono::Utc::now(),
@@ -305,6 +356,24 @@ impl Cache {
         Ok(())
     }
 
+    /// Record that the value under `key` doesn't exist, for `negative_ttl`.
+    ///
+    /// Lookups then return [`CacheLookup::Negative`] without asking the
+    /// source again. Any later `set` on the key replaces the tombstone.
+    /// Does nothing when negative caching is disabled.
+    #[instrument(skip(self), fields(key = %key))]
+    pub async fn cache_none(&self, key: &CacheKey) -> Result<()> {
+        let Some(ttl) = self.config.negative_ttl else {
+            return Ok(());
+        };
+        let full_key = self.build_key(key);
+        self.backend
+            .set(&full_key, CacheEntry::tombstone(ttl, key.tags().to_vec()))
+            .await?;
+        debug!("Cache tombstone set for key: {} with TTL: {:?}", full_key, ttl);
+        Ok(())
+    }
+
     /// Delete a value from the cache.
     #[instrument(skip(self), fields(key = %key))]
     pub async fn delete(&self, key: &CacheKey) -> Result<bool> {
@@ -342,6 +411,40 @@ impl Cache {
         Ok(value)
     }
 
+    /// Get or set a value that may not exist.
+    ///
+    /// When `factory` finds nothing, a tombstone is cached (see
+    /// [`Cache::cache_none`]) so repeated lookups of a missing value don't
+    /// reach the source until it expires.
+    #[instrument(skip(self, factory), fields(key = %key))]
+    pub async fn get_or_set_optional<T, F, Fut>(
+        &self,
+        key: &CacheKey,
+        factory: F,
+    ) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned,
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<Option<T>>>,
+    {
+        match self.lookup(key).await? {
+            CacheLookup::Hit(value) => return Ok(Some(value)),
+            CacheLookup::Negative => return Ok(None),
+            CacheLookup::Miss => {}
+        }
+
+        match factory().await? {
+            Some(value) => {
+                self.set(key, &value).await?;
+                Ok(Some(value))
+            }
+            None => {
+                self.cache_none(key).await?;
+                Ok(None)
+            }
+        }
+    }
+
     /// Invalidate entries by tag.
     #[instrument(skip(self))]
     pub async fn invalidate_by_tag(&self, tag: &str) -> Result<u64> {
@@ -488,6 +591,7 @@ mod tests {
         assert_eq!(config.namespace_prefix, "apex:cache:");
         assert!(config.enable_compression);
         assert_eq!(config.compression_threshold, 1024);
+        assert_eq!(config.negative_ttl, Some(Duration::from_secs(30)));
     }
 
     #[test]
@@ -587,6 +691,76 @@ mod tests {
         assert!(!deleted);
     }
 
+    #[tokio::test]
+    async fn test_mixed_formats_coexist() {
+        let backend: Arc<dyn CacheBackend> = Arc::new(InMemoryBackend::new(InMemoryConfig::default()));
+        let data = TestData { id: "mixed".to_string(), value: 5 };
+
+        let json = Cache::new(backend.clone(), CacheConfig::default());
+        let json_key = CacheKey::new(KeyType::Task).with_id("old");
+        json.set(&json_key, &data).await.unwrap();
+
+        // Switching formats leaves existing entries readable
+        for format in [SerializationFormat::MessagePack, SerializationFormat::Bincode] {
+            let cache = Cache::new(
+                backend.clone(),
+                CacheConfig::builder().serialization_format(format).build(),
+            );
+            let key = CacheKey::new(KeyType::Task).with_id(format.as_str());
+            cache.set(&key, &data).await.unwrap();
+
+            let entry = backend.get(&cache.build_key(&key)).await.unwrap().unwrap();
+            assert_eq!(entry.format, format);
+            assert_eq!(cache.get::<TestData>(&key).await.unwrap(), Some(data.clone()));
+            assert_eq!(cache.get::<TestData>(&json_key).await.unwrap(), Some(data.clone()));
+            assert_eq!(json.get::<TestData>(&key).await.unwrap(), Some(data.clone()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_negative_caching_skips_repeated_misses() {
+        let cache = Cache::in_memory(1000);
+        let key = CacheKey::new(KeyType::Task).with_id("missing");
+        let db_calls = std::sync::atomic::AtomicU32::new(0);
+        let lookup = || async {
+            db_calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(None::<TestData>)
+        };
+
+        assert_eq!(cache.get_or_set_optional(&key, lookup).await.unwrap(), None);
+        assert_eq!(cache.get_or_set_optional(&key, lookup).await.unwrap(), None);
+        assert_eq!(db_calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!(cache.lookup::<TestData>(&key).await.unwrap(), CacheLookup::Negative);
+        assert_eq!(cache.get::<TestData>(&key).await.unwrap(), None);
+
+        // A write replaces the tombstone
+        let data = TestData { id: "missing".to_string(), value: 3 };
+        cache.set(&key, &data).await.unwrap();
+        assert_eq!(cache.lookup(&key).await.unwrap(), CacheLookup::Hit(data));
+    }
+
+    #[tokio::test]
+    async fn test_negative_caching_disabled() {
+        let cache = Cache::new(
+            Arc::new(InMemoryBackend::new(InMemoryConfig::default())),
+            CacheConfig::builder().negative_ttl(None).build(),
+        );
+        let key = CacheKey::new(KeyType::Task).with_id("missing");
+        let db_calls = std::sync::atomic::AtomicU32::new(0);
+        for _ in 0..2 {
+            let found = cache
+                .get_or_set_optional(&key, || async {
+                    db_calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Ok(None::<TestData>)
+                })
+                .await
+                .unwrap();
+            assert_eq!(found, None);
+        }
+        assert_eq!(db_calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+        assert_eq!(cache.lookup::<TestData>(&key).await.unwrap(), CacheLookup::Miss);
+    }
+
     #[test]
     fn test_cache_clone() {
         let cache = Cache::in_memory(1000);
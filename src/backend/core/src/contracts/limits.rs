@@ -1,5 +1,6 @@
 //! Resource limit definitions.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// Resource limits for an agent contract.
@@ -80,6 +81,35 @@ impl ResourceLimits {
         }
     }
 
+    /// The tighter of these limits and `other`, field by field.
+    pub fn capped_at(&self, other: &ResourceLimits) -> Self {
+        Self {
+            token_limit: self.token_limit.min(other.token_limit),
+            cost_limit: self.cost_limit.min(other.cost_limit),
+            api_call_limit: self.api_call_limit.min(other.api_call_limit),
+            time_limit_seconds: self.time_limit_seconds.min(other.time_limit_seconds),
+        }
+    }
+
+    /// These limits with the time limit cut down to what is left before
+    /// `deadline`, rounded up to whole seconds. Zero once it has passed.
+    pub fn with_time_budget_deadline(&self, deadline: DateTime<Utc>) -> Self {
+        let remaining_ms = (deadline - Utc::now()).num_milliseconds().max(0) as u64;
+        Self {
+            time_limit_seconds: self.time_limit_seconds.min(remaining_ms.div_ceil(1000)),
+            ..self.clone()
+        }
+    }
+
+    /// Whether every limit is above zero; a zero limit can't be spent against.
+    pub fn is_positive(&self) -> bool {
+        self.token_limit > 0
+            && self.cost_limit.is_finite()
+            && self.cost_limit > 0.0
+            && self.api_call_limit > 0
+            && self.time_limit_seconds > 0
+    }
+
     /// Check if these limits are within another set of limits.
     pub fn fits_within(&self, other: &ResourceLimits) -> bool {
         self.token_limit <= other.token_limit
@@ -143,4 +173,20 @@ mod tests {
 
         assert_eq!(overhead.token_limit + allocatable.token_limit, limits.token_limit);
     }
+
+    #[test]
+    fn test_time_budget_deadline() {
+        let limits = ResourceLimits::simple();
+
+        let later = Utc::now() + chrono::Duration::seconds(30);
+        assert_eq!(limits.with_time_budget_deadline(later).time_limit_seconds, 30);
+
+        let far = Utc::now() + chrono::Duration::hours(1);
+        assert_eq!(limits.with_time_budget_deadline(far).time_limit_seconds, 60);
+
+        let past = Utc::now() - chrono::Duration::seconds(5);
+        let expired = limits.with_time_budget_deadline(past);
+        assert_eq!(expired.time_limit_seconds, 0);
+        assert_eq!(expired.token_limit, limits.token_limit);
+    }
 }
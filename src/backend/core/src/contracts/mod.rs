@@ -6,10 +6,12 @@
 mod limits;
 mod enforcement;
 mod tracker;
+pub mod quota;
 
 pub use limits::ResourceLimits;
 pub use enforcement::ContractEnforcer;
 pub use tracker::UsageTracker;
+pub use quota::{OrganizationQuota, OrganizationUsage, QuotaResource, QuotaStatus};
 
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -49,6 +51,10 @@ pub struct AgentContract {
 
     /// Child contracts spawned from this one
     pub child_contracts: Vec<Uuid>,
+
+    /// Limits of live child contracts, held back until they are released
+    #[serde(default)]
+    pub reserved: ResourceUsage,
 }
 
 /// Current resource usage against a contract.
@@ -98,36 +104,44 @@ impl AgentContract {
             created_at: now,
             expires_at,
             child_contracts: Vec::new(),
+            reserved: ResourceUsage::default(),
         }
     }
 
     /// Create a sub-contract from this parent contract.
     ///
-    /// Enforces the conservation law: child limits must not exceed parent's remaining budget.
+    /// Enforces the conservation law: child limits must not exceed parent's
+    /// remaining budget, less what live children already hold. The child's
+    /// limits stay reserved until [`release_child`](Self::release_child).
     pub fn create_child(&mut self, agent_id: Uuid, task_id: Uuid, child_limits: ResourceLimits) -> Result<Self> {
         // Check conservation law
-        let remaining_tokens = self.limits.token_limit.saturating_sub(self.usage.tokens_used);
-        let remaining_cost = self.limits.cost_limit - self.usage.cost_used;
-        let remaining_time = self.limits.time_limit_seconds.saturating_sub(self.usage.time_elapsed_secs);
+        let available = self.remaining_limits();
 
-        if child_limits.token_limit > remaining_tokens {
+        if child_limits.token_limit > available.token_limit {
             return Err(ApexError::contract_violation(
-                remaining_tokens as f64,
+                available.token_limit as f64,
                 child_limits.token_limit as f64,
             ));
         }
 
-        if child_limits.cost_limit > remaining_cost {
+        if child_limits.cost_limit > available.cost_limit {
             return Err(ApexError::contract_violation(
-                remaining_cost,
+                available.cost_limit,
                 child_limits.cost_limit,
             ));
         }
 
-        if child_limits.time_limit_seconds > remaining_time {
+        if child_limits.api_call_limit > available.api_call_limit {
+            return Err(ApexError::contract_violation(
+                available.api_call_limit as f64,
+                child_limits.api_call_limit as f64,
+            ));
+        }
+
+        if child_limits.time_limit_seconds > available.time_limit_seconds {
             return Err(ApexError::time_limit_exceeded(
                 child_limits.time_limit_seconds,
-                remaining_time,
+                available.time_limit_seconds,
             ));
         }
 
@@ -135,11 +149,27 @@ impl AgentContract {
         child.parent_contract_id = Some(self.id);
         child.expires_at = self.expires_at.min(child.expires_at);
 
+        self.reserved.tokens_used += child.limits.token_limit;
+        self.reserved.cost_used += child.limits.cost_limit;
+        self.reserved.api_calls_used += child.limits.api_call_limit;
         self.child_contracts.push(child.id);
 
         Ok(child)
     }
 
+    /// Release a finished child's reservation and charge what it spent.
+    pub fn release_child(&mut self, child: &AgentContract) {
+        if !self.child_contracts.contains(&child.id) {
+            return;
+        }
+        self.child_contracts.retain(|id| *id != child.id);
+        self.reserved.tokens_used = self.reserved.tokens_used.saturating_sub(child.limits.token_limit);
+        self.reserved.cost_used = (self.reserved.cost_used - child.limits.cost_limit).max(0.0);
+        self.reserved.api_calls_used = self.reserved.api_calls_used.saturating_sub(child.limits.api_call_limit);
+        self.usage.api_calls_used += child.usage.api_calls_used;
+        self.charge(child.usage.tokens_used, child.usage.cost_used);
+    }
+
     /// Record token usage.
     pub fn record_tokens(&mut self, tokens: u64) -> Result<()> {
         let new_total = self.usage.tokens_used + tokens;
@@ -194,6 +224,38 @@ impl AgentContract {
         }
     }
 
+    /// Remaining budget not reserved by live children, as limits, e.g. to
+    /// bound a new child contract.
+    pub fn remaining_limits(&self) -> ResourceLimits {
+        let remaining = self.remaining();
+        ResourceLimits {
+            token_limit: remaining.tokens_used.saturating_sub(self.reserved.tokens_used),
+            cost_limit: (remaining.cost_used - self.reserved.cost_used).max(0.0),
+            api_call_limit: remaining.api_calls_used.saturating_sub(self.reserved.api_calls_used),
+            time_limit_seconds: remaining.time_elapsed_secs,
+        }
+    }
+
+    /// Charge a finished child's spend to this contract.
+    ///
+    /// Unlike the `record_*` methods the spend is always recorded, since it
+    /// has already happened; the contract is marked exceeded once it runs out.
+    pub fn charge(&mut self, tokens: u64, cost: f64) {
+        self.usage.tokens_used += tokens;
+        self.usage.cost_used += cost;
+        self.usage.time_elapsed_secs = (Utc::now() - self.created_at).num_seconds().max(0) as u64;
+        if self.is_exhausted() {
+            self.status = ContractStatus::Exceeded;
+        }
+    }
+
+    /// Whether the token or cost budget is used up or the deadline has passed.
+    pub fn is_exhausted(&self) -> bool {
+        self.usage.tokens_used >= self.limits.token_limit
+            || self.usage.cost_used >= self.limits.cost_limit
+            || self.is_expired()
+    }
+
     /// Calculate utilization percentage.
     pub fn utilization(&self) -> ContractUtilization {
         ContractUtilization {
@@ -285,4 +347,45 @@ mod tests {
         let child = parent.create_child(Uuid::new_v4(), Uuid::new_v4(), valid_limits);
         assert!(child.is_ok());
     }
+
+    #[test]
+    fn test_charge_exhausts_budget() {
+        let mut parent = AgentContract::new(Uuid::nil(), Uuid::new_v4(), test_limits());
+
+        parent.charge(4000, 0.4);
+        assert!(!parent.is_exhausted());
+        assert_eq!(parent.remaining_limits().token_limit, 6000);
+
+        // A child capped at what remains always satisfies the conservation law
+        let child_limits = ResourceLimits::complex().capped_at(&parent.remaining_limits());
+        let child = parent.create_child(Uuid::new_v4(), Uuid::new_v4(), child_limits).unwrap();
+        assert_eq!(child.limits.token_limit, 6000);
+        assert!((child.limits.cost_limit - 0.6).abs() < 1e-9);
+
+        // Spend that overshoots is still recorded
+        parent.charge(7000, 0.1);
+        assert!(parent.is_exhausted());
+        assert_eq!(parent.status, ContractStatus::Exceeded);
+        assert_eq!(parent.usage.tokens_used, 11000);
+        assert_eq!(parent.remaining_limits().token_limit, 0);
+    }
+
+    #[test]
+    fn test_children_reserve_until_released() {
+        let mut parent = AgentContract::new(Uuid::nil(), Uuid::new_v4(), test_limits());
+        let limits = ResourceLimits { token_limit: 6000, cost_limit: 0.5, ..test_limits() };
+
+        // A second child can't be promised what the first already holds
+        let mut first = parent.create_child(Uuid::new_v4(), Uuid::new_v4(), limits.clone()).unwrap();
+        assert_eq!(parent.remaining_limits().token_limit, 4000);
+        assert!(parent.create_child(Uuid::new_v4(), Uuid::new_v4(), limits.clone()).is_err());
+
+        // Releasing returns the unused part and keeps the spend
+        first.charge(1000, 0.1);
+        parent.release_child(&first);
+        parent.release_child(&first);
+        assert_eq!(parent.usage.tokens_used, 1000);
+        assert_eq!(parent.remaining_limits().token_limit, 9000);
+        assert!(parent.child_contracts.is_empty());
+    }
 }
@@ -0,0 +1,213 @@
+//! Organization-wide monthly quotas.
+//!
+//! Contracts bound a single task; an [`OrganizationQuota`] bounds everything
+//! an organization runs in a calendar month (UTC). It is checked whenever new
+//! tasks or DAGs are accepted, against the month's usage so far.
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ApexError, Result};
+
+/// Fraction of a quota at which callers are warned that they are close.
+pub const SOFT_LIMIT_RATIO: f64 = 0.8;
+
+/// Monthly caps for an organization. `None` leaves a resource uncapped.
+///
+/// Stored under the `quota` key of the organization's settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrganizationQuota {
+    /// Tokens consumed per month
+    #[serde(default)]
+    pub monthly_token_limit: Option<u64>,
+
+    /// Dollars spent per month
+    #[serde(default)]
+    pub monthly_cost_limit: Option<f64>,
+
+    /// Tasks created per month
+    #[serde(default)]
+    pub monthly_task_limit: Option<u64>,
+}
+
+/// What an organization has consumed in the current period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrganizationUsage {
+    pub tasks: u64,
+    pub tokens: u64,
+    pub cost: f64,
+}
+
+/// A resource an [`OrganizationQuota`] can cap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaResource {
+    Tokens,
+    Cost,
+    Tasks,
+}
+
+impl QuotaResource {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Tokens => "tokens",
+            Self::Cost => "cost",
+            Self::Tasks => "tasks",
+        }
+    }
+}
+
+/// Usage of one capped resource against its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuotaStatus {
+    pub resource: QuotaResource,
+    pub used: f64,
+    pub limit: f64,
+}
+
+impl QuotaStatus {
+    /// `used / limit`; a zero limit counts as fully used.
+    pub fn utilization(&self) -> f64 {
+        if self.limit > 0.0 {
+            self.used / self.limit
+        } else {
+            1.0
+        }
+    }
+
+    pub fn is_near_limit(&self) -> bool {
+        self.utilization() >= SOFT_LIMIT_RATIO
+    }
+}
+
+impl OrganizationQuota {
+    /// Every capped resource's usage against its limit.
+    pub fn statuses(&self, usage: &OrganizationUsage) -> Vec<QuotaStatus> {
+        let caps = [
+            (QuotaResource::Tokens, usage.tokens as f64, self.monthly_token_limit.map(|l| l as f64)),
+            (QuotaResource::Cost, usage.cost, self.monthly_cost_limit),
+            (QuotaResource::Tasks, usage.tasks as f64, self.monthly_task_limit.map(|l| l as f64)),
+        ];
+        caps.into_iter()
+            .filter_map(|(resource, used, limit)| limit.map(|limit| QuotaStatus { resource, used, limit }))
+            .collect()
+    }
+
+    /// Decide whether `new_tasks` more tasks may be accepted.
+    ///
+    /// Tokens and cost aren't known until the tasks run, so those refuse new
+    /// work once the month's usage has reached the cap; tasks refuse anything
+    /// that would go past it. On success, returns the resources at or above
+    /// [`SOFT_LIMIT_RATIO`] after accepting, for the caller to warn about.
+    pub fn check(
+        &self,
+        organization_id: &str,
+        usage: &OrganizationUsage,
+        new_tasks: u64,
+    ) -> Result<Vec<QuotaStatus>> {
+        let after = OrganizationUsage {
+            tasks: usage.tasks + new_tasks,
+            ..*usage
+        };
+        let statuses = self.statuses(&after);
+
+        for status in &statuses {
+            let exceeded = match status.resource {
+                QuotaResource::Tasks => status.used > status.limit,
+                QuotaResource::Tokens | QuotaResource::Cost => status.used >= status.limit,
+            };
+            if exceeded {
+                return Err(ApexError::quota_exceeded(
+                    organization_id,
+                    status.resource.as_str(),
+                    status.used,
+                    status.limit,
+                ));
+            }
+        }
+
+        Ok(statuses.into_iter().filter(QuotaStatus::is_near_limit).collect())
+    }
+}
+
+/// Start of the calendar month (UTC) containing `now`.
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+/// Start of the calendar month after the one containing `now`.
+pub fn next_month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    let (year, month) = if now.month() == 12 {
+        (now.year() + 1, 1)
+    } else {
+        (now.year(), now.month() + 1)
+    };
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    fn quota() -> OrganizationQuota {
+        OrganizationQuota {
+            monthly_token_limit: Some(1_000),
+            monthly_cost_limit: Some(10.0),
+            monthly_task_limit: Some(10),
+        }
+    }
+
+    #[test]
+    fn test_check_within_quota() {
+        let usage = OrganizationUsage { tasks: 2, tokens: 100, cost: 1.0 };
+        assert!(quota().check("org", &usage, 3).unwrap().is_empty());
+
+        // Uncapped organizations are never refused
+        let huge = OrganizationUsage { tasks: 1 << 40, tokens: 1 << 40, cost: 1e9 };
+        assert!(OrganizationQuota::default().check("org", &huge, 100).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_warns_past_soft_limit() {
+        let usage = OrganizationUsage { tasks: 7, tokens: 850, cost: 1.0 };
+        let warnings = quota().check("org", &usage, 1).unwrap();
+        let resources: Vec<_> = warnings.iter().map(|w| w.resource).collect();
+        assert_eq!(resources, vec![QuotaResource::Tokens, QuotaResource::Tasks]);
+        assert_eq!(warnings[1].utilization(), 0.8);
+    }
+
+    #[test]
+    fn test_check_refuses_over_quota() {
+        let usage = OrganizationUsage { tasks: 8, tokens: 100, cost: 1.0 };
+        assert!(quota().check("org", &usage, 2).is_ok());
+        let err = quota().check("org", &usage, 3).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::QuotaExceeded);
+
+        let spent = OrganizationUsage { tasks: 0, tokens: 0, cost: 10.0 };
+        let err = quota().check("org", &spent, 1).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::QuotaExceeded);
+        assert!(err.user_message().contains("cost"));
+    }
+
+    #[test]
+    fn test_month_bounds() {
+        let now = Utc.with_ymd_and_hms(2024, 12, 17, 9, 30, 0).unwrap();
+        assert_eq!(month_start(now), Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap());
+        assert_eq!(next_month_start(now), Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+    }
+
+    #[test]
+    fn test_quota_from_settings() {
+        let quota: OrganizationQuota = serde_json::from_value(serde_json::json!({
+            "monthly_task_limit": 500,
+        }))
+        .unwrap();
+        assert_eq!(quota.monthly_task_limit, Some(500));
+        assert_eq!(quota.monthly_token_limit, None);
+    }
+}
@@ -0,0 +1,230 @@
+//! Named input bindings between tasks.
+//!
+//! A task can pull a single field out of a predecessor's output instead of
+//! receiving everything upstream produced. Each binding names a placeholder,
+//! the predecessor task (by name) and where in its [`TaskOutput`] the value
+//! lives. When the task is dispatched, every `{{placeholder}}` in its
+//! instruction and context is replaced by that value:
+//!
+//! ```yaml
+//! tasks:
+//!   - id: research
+//!     name: research
+//!     instruction: Research the topic and return data.summary
+//!   - id: write
+//!     name: write
+//!     instruction: "Write a blog post from this summary: {{research.summary}}"
+//!     depends_on: [research]
+//!     inputs:
+//!       research.summary: { task: research, pointer: /data/summary }
+//! ```
+//!
+//! In a spec, `task` is the predecessor's spec id. Substitution follows
+//! templates: a context string that is exactly one placeholder takes the
+//! value itself, anywhere else it is spliced into the text.
+//!
+//! [`TaskOutput`]: super::TaskOutput
+
+use petgraph::graph::NodeIndex;
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use super::condition::json_pointer;
+use super::template::{substitute, substitute_json};
+use super::{Task, TaskDAG, TaskId, TaskInput, TaskStatus};
+use crate::error::{ApexError, ErrorCode, Result};
+
+/// Where the value of a bound placeholder comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputBinding {
+    /// Name of the predecessor task
+    pub task: String,
+
+    /// JSON pointer into the predecessor's output, e.g. `/data/summary` or
+    /// `/result`; dotted paths as in conditions (`$.data.summary`) work too
+    pub pointer: String,
+}
+
+impl InputBinding {
+    pub fn new(task: impl Into<String>, pointer: impl Into<String>) -> Self {
+        Self {
+            task: task.into(),
+            pointer: pointer.into(),
+        }
+    }
+
+    /// `pointer` in JSON pointer form.
+    fn json_pointer(&self) -> String {
+        if self.pointer.is_empty() || self.pointer.starts_with('/') {
+            self.pointer.clone()
+        } else {
+            json_pointer(&self.pointer)
+        }
+    }
+}
+
+impl TaskDAG {
+    /// The ancestor of `task_id` named `name`.
+    ///
+    /// Fails if no ancestor has that name, or more than one does.
+    pub(super) fn bound_predecessor(&self, task_id: TaskId, name: &str) -> Result<&Task> {
+        let start = *self
+            .task_index
+            .get(&task_id)
+            .ok_or_else(|| ApexError::task_not_found(task_id.0))?;
+
+        let mut seen: HashSet<NodeIndex> = HashSet::new();
+        let mut stack = vec![start];
+        let mut matches = Vec::new();
+        while let Some(node) = stack.pop() {
+            for parent in self.graph.neighbors_directed(node, Direction::Incoming) {
+                if seen.insert(parent) {
+                    if self.graph[parent].name == name {
+                        matches.push(parent);
+                    }
+                    stack.push(parent);
+                }
+            }
+        }
+
+        let task = &self.graph[start].name;
+        match matches.as_slice() {
+            [node] => Ok(&self.graph[*node]),
+            [] => Err(ApexError::new(
+                ErrorCode::DagValidationFailed,
+                format!("Task '{}' binds an input to '{}', which is not one of its predecessors", task, name),
+            )),
+            _ => Err(ApexError::new(
+                ErrorCode::DagValidationFailed,
+                format!("Task '{}' binds an input to '{}', but several predecessors have that name", task, name),
+            )),
+        }
+    }
+
+    /// Check that every input binding names exactly one predecessor.
+    pub(super) fn validate_input_bindings(&self) -> Result<()> {
+        for task in self.graph.node_weights() {
+            for binding in task.input_bindings.values() {
+                self.bound_predecessor(task.id, &binding.task)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The input to dispatch `task_id` with: its own, with every bound
+    /// placeholder replaced by the value from its predecessor's output.
+    ///
+    /// Fails with `DependencyNotMet` if a bound predecessor didn't complete
+    /// or its output has nothing at the binding's pointer.
+    pub fn resolve_input(&self, task_id: TaskId) -> Result<TaskInput> {
+        let task = self
+            .get_task(task_id)
+            .ok_or_else(|| ApexError::task_not_found(task_id.0))?;
+        if task.input_bindings.is_empty() {
+            return Ok(task.input.clone());
+        }
+
+        let mut values = HashMap::new();
+        for (placeholder, binding) in &task.input_bindings {
+            let source = self.bound_predecessor(task_id, &binding.task)?;
+            let output = match (&source.status, &source.output) {
+                (TaskStatus::Completed, Some(output)) => serde_json::to_value(output)?,
+                (status, _) => {
+                    return Err(ApexError::new(
+                        ErrorCode::DependencyNotMet,
+                        format!(
+                            "Input '{}' of task '{}' needs the output of '{}', which did not complete (status {:?})",
+                            placeholder, task.name, source.name, status
+                        ),
+                    ));
+                }
+            };
+            let value = output.pointer(&binding.json_pointer()).cloned().ok_or_else(|| {
+                ApexError::new(
+                    ErrorCode::DependencyNotMet,
+                    format!(
+                        "Input '{}' of task '{}': the output of '{}' has no value at '{}'",
+                        placeholder, task.name, source.name, binding.pointer
+                    ),
+                )
+            })?;
+            values.insert(placeholder.as_str(), value);
+        }
+
+        let lookup = |name: &str| values.get(name);
+        let mut input = task.input.clone();
+        input.instruction = substitute(&input.instruction, &lookup);
+        substitute_json(&mut input.context, &lookup);
+        Ok(input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::TaskOutput;
+    use serde_json::json;
+
+    fn task(name: &str, instruction: &str) -> Task {
+        Task::new(name, TaskInput { instruction: instruction.to_string(), ..Default::default() })
+    }
+
+    /// `research -> write`, with `write` bound to `research`'s summary.
+    fn pipeline() -> (TaskDAG, TaskId, TaskId) {
+        let mut dag = TaskDAG::new("pipeline");
+        let research = dag.add_task(task("research", "Research the topic")).unwrap();
+        let mut write = task("write", "Write about: {{research.summary}}")
+            .with_input_binding("research.summary", InputBinding::new("research", "/data/summary"));
+        write.input.context = json!({ "summary": "{{research.summary}}", "note": "from {{research.summary}}" });
+        let write = dag.add_task(write).unwrap();
+        dag.add_dependency(research, write).unwrap();
+        (dag, research, write)
+    }
+
+    fn complete(dag: &mut TaskDAG, id: TaskId, data: serde_json::Value) {
+        let output = TaskOutput { result: "done".to_string(), data, ..Default::default() };
+        dag.get_task_mut(id).unwrap().complete(output, 10, 0.0);
+    }
+
+    #[test]
+    fn test_resolve_substitutes_bound_values() {
+        let (mut dag, research, write) = pipeline();
+        complete(&mut dag, research, json!({ "summary": "Rust is fast", "words": 3 }));
+
+        let input = dag.resolve_input(write).unwrap();
+        assert_eq!(input.instruction, "Write about: Rust is fast");
+        assert_eq!(input.context, json!({ "summary": "Rust is fast", "note": "from Rust is fast" }));
+
+        // Whole-string context placeholders keep the value's type
+        let task = dag.get_task_mut(write).unwrap();
+        task.input_bindings.insert("words".to_string(), InputBinding::new("research", "$.data.words"));
+        task.input.context = json!({ "count": "{{words}}" });
+        assert_eq!(dag.resolve_input(write).unwrap().context, json!({ "count": 3 }));
+    }
+
+    #[test]
+    fn test_resolve_fails_for_incomplete_predecessor_or_missing_field() {
+        let (mut dag, research, write) = pipeline();
+        let err = dag.resolve_input(write).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DependencyNotMet);
+        assert!(err.to_string().contains("did not complete"), "{}", err);
+
+        complete(&mut dag, research, json!({ "title": "no summary" }));
+        let err = dag.resolve_input(write).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DependencyNotMet);
+        assert!(err.to_string().contains("no value at '/data/summary'"), "{}", err);
+    }
+
+    #[test]
+    fn test_binding_must_name_a_predecessor() {
+        let (mut dag, _, _) = pipeline();
+        assert!(dag.validate_input_bindings().is_ok());
+
+        let orphan = task("orphan", "{{x}}").with_input_binding("x", InputBinding::new("research", "/result"));
+        let orphan = dag.add_task(orphan).unwrap();
+        let err = dag.resolve_input(orphan).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DagValidationFailed);
+        assert!(dag.validate_input_bindings().is_err());
+    }
+}
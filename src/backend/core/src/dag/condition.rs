@@ -0,0 +1,92 @@
+//! Conditions on DAG edges.
+//!
+//! A conditional edge only lets its target run when a field of the
+//! predecessor's [`TaskOutput`] has the expected value:
+//!
+//! ```yaml
+//! dependencies:
+//!   - from: classify
+//!     to: escalate
+//!     condition:
+//!       path: $.data.urgent
+//!       equals: true
+//! ```
+//!
+//! Once every predecessor has completed, a task with an unmet condition is
+//! skipped: it moves to `Cancelled` along with everything downstream of it.
+
+use serde::{Deserialize, Serialize};
+
+use super::TaskOutput;
+
+/// A predicate over the predecessor's output.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeCondition {
+    /// JSONPath into the output, e.g. `$.data.flag` or `$.data.items.0.kind`.
+    /// The leading `$.` is optional.
+    pub path: String,
+
+    /// Value the selected field must equal
+    pub equals: serde_json::Value,
+}
+
+impl EdgeCondition {
+    pub fn new(path: impl Into<String>, equals: impl Into<serde_json::Value>) -> Self {
+        Self {
+            path: path.into(),
+            equals: equals.into(),
+        }
+    }
+
+    /// Whether `output` satisfies the condition. A missing output or field
+    /// never does.
+    pub fn evaluate(&self, output: Option<&TaskOutput>) -> bool {
+        let Some(output) = output else {
+            return false;
+        };
+        let Ok(output) = serde_json::to_value(output) else {
+            return false;
+        };
+        output.pointer(&json_pointer(&self.path)) == Some(&self.equals)
+    }
+}
+
+/// A dotted JSONPath as a JSON pointer (`$.data.flag` -> `/data/flag`).
+pub(super) fn json_pointer(path: &str) -> String {
+    let path = path.trim();
+    let path = path.strip_prefix('$').unwrap_or(path);
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn output(data: serde_json::Value) -> TaskOutput {
+        TaskOutput {
+            result: "done".to_string(),
+            data,
+            artifacts: vec![],
+            reasoning: None,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_paths() {
+        let out = output(json!({ "flag": true, "items": [{ "kind": "pdf" }] }));
+
+        assert!(EdgeCondition::new("$.data.flag", true).evaluate(Some(&out)));
+        assert!(EdgeCondition::new("data.flag", true).evaluate(Some(&out)));
+        assert!(!EdgeCondition::new("$.data.flag", false).evaluate(Some(&out)));
+        assert!(EdgeCondition::new("$.data.items.0.kind", "pdf").evaluate(Some(&out)));
+        assert!(EdgeCondition::new("$.result", "done").evaluate(Some(&out)));
+
+        // Missing fields and outputs never match
+        assert!(!EdgeCondition::new("$.data.missing", serde_json::Value::Null).evaluate(Some(&out)));
+        assert!(!EdgeCondition::new("$.data.flag", true).evaluate(None));
+    }
+}
@@ -0,0 +1,155 @@
+//! Collapsing duplicate tasks within a DAG.
+//!
+//! Planners that fan out often produce the same subtask twice. With dedup
+//! enabled ([`TaskDAG::with_dedup`], or `dedup: true` in a spec), adding a
+//! task whose instruction, context, parameters and input bindings equal
+//! those of a task already in the DAG returns the existing task's id
+//! instead of inserting a copy, so edges added for the duplicate attach to
+//! the original and the work runs once. A task added together with its
+//! dependencies ([`TaskDAG::add_task_after`], which specs use) only
+//! collapses into one that has the same dependencies too.
+//!
+//! Map expansions collapse identical items the same way: every occurrence
+//! of an item shares one instance (which sees the `item_index` of the
+//! first), and the join repeats its output at each position.
+//!
+//! Tasks that failed or were cancelled are never reused, and map nodes are
+//! neither reused nor matched.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use super::{EdgeCondition, Task, TaskDAG, TaskId, TaskStatus};
+
+/// Hash of what a task would be dispatched with and the dependencies it was
+/// added with. Equal content hashes equal; a hit is confirmed with
+/// [`same_content`] and by checking the edges.
+pub(super) fn content_hash(task: &Task, dependencies: &[(TaskId, Option<EdgeCondition>)]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    task.input.instruction.hash(&mut hasher);
+    for value in [&task.input.context, &task.input.parameters] {
+        // Object keys serialize sorted, so equal values hash alike
+        serde_json::to_string(value).unwrap_or_default().hash(&mut hasher);
+    }
+    for (placeholder, binding) in &task.input_bindings {
+        (placeholder, &binding.task, &binding.pointer).hash(&mut hasher);
+    }
+    let mut dependencies: Vec<(TaskId, String)> = dependencies
+        .iter()
+        .map(|(from, condition)| (*from, serde_json::to_string(condition).unwrap_or_default()))
+        .collect();
+    dependencies.sort_unstable_by(|a, b| (a.0 .0, &a.1).cmp(&(b.0 .0, &b.1)));
+    dependencies.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn same_content(a: &Task, b: &Task) -> bool {
+    a.input.instruction == b.input.instruction
+        && a.input.context == b.input.context
+        && a.input.parameters == b.input.parameters
+        && a.input_bindings == b.input_bindings
+}
+
+impl TaskDAG {
+    /// Collapse tasks with identical content as they are added (off by default).
+    pub fn with_dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    pub fn dedup(&self) -> bool {
+        self.dedup
+    }
+
+    /// An earlier task with the same content as `task`, added with the same
+    /// `dependencies`, that can stand in for it.
+    pub(super) fn find_duplicate(&self, task: &Task, dependencies: &[(TaskId, Option<EdgeCondition>)]) -> Option<TaskId> {
+        let existing = self.get_task(*self.content_index.get(&content_hash(task, dependencies))?)?;
+        let reusable = !matches!(existing.status, TaskStatus::Failed | TaskStatus::Cancelled);
+        (reusable && same_content(existing, task) && self.has_dependencies(existing.id, dependencies))
+            .then_some(existing.id)
+    }
+
+    /// Make `task_id`, added with `dependencies`, findable by [`Self::find_duplicate`].
+    pub(super) fn index_content(&mut self, task_id: TaskId, dependencies: &[(TaskId, Option<EdgeCondition>)]) {
+        if let Some(task) = self.get_task(task_id) {
+            let hash = content_hash(task, dependencies);
+            self.content_index.entry(hash).or_insert(task_id);
+        }
+    }
+
+    /// Whether every one of `dependencies` is an edge into `task_id`.
+    fn has_dependencies(&self, task_id: TaskId, dependencies: &[(TaskId, Option<EdgeCondition>)]) -> bool {
+        dependencies.iter().all(|(from, condition)| {
+            match (self.task_index.get(from), self.task_index.get(&task_id)) {
+                (Some(from), Some(to)) => self.graph.edges_connecting(*from, *to).any(|edge| edge.weight() == condition),
+                _ => false,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::{FailurePolicy, MapSpec, MapUpdate, TaskInput, TaskOutput};
+    use serde_json::json;
+
+    fn task(name: &str, instruction: &str, context: serde_json::Value) -> Task {
+        Task::new(name, TaskInput { instruction: instruction.to_string(), context, ..Default::default() })
+    }
+
+    #[test]
+    fn test_identical_tasks_are_added_once() {
+        let mut dag = TaskDAG::new("plan").with_dedup(true);
+        let plan = dag.add_task(task("plan", "Plan", json!(null))).unwrap();
+        let a = dag.add_task(task("search", "Search", json!({ "q": "rust", "n": 5 }))).unwrap();
+        let b = dag.add_task(task("search again", "Search", json!({ "n": 5, "q": "rust" }))).unwrap();
+        let c = dag.add_task(task("search", "Search", json!({ "q": "go", "n": 5 }))).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(dag.stats().total, 3);
+
+        // Edges of the duplicate land on the original, once
+        dag.add_dependency(plan, a).unwrap();
+        dag.add_dependency(plan, b).unwrap();
+        assert_eq!(dag.graph.edge_count(), 1);
+
+        // A failed task is run again rather than reused
+        dag.get_task_mut(c).unwrap().status = TaskStatus::Failed;
+        let retry = dag.add_task(task("search", "Search", json!({ "q": "go", "n": 5 }))).unwrap();
+        assert_ne!(retry, c);
+
+        // Off by default
+        let mut plain = TaskDAG::new("plain");
+        let a = plain.add_task(task("a", "Search", json!(null))).unwrap();
+        assert_ne!(plain.add_task(task("a", "Search", json!(null))).unwrap(), a);
+    }
+
+    #[test]
+    fn test_identical_map_items_run_once() {
+        let mut dag = TaskDAG::new("fan-out").with_dedup(true);
+        let list = dag.add_task(task("list", "List", json!(null))).unwrap();
+        let read = dag.add_map_task(task("read", "Read", json!(null)), list, MapSpec::new("$.data.urls")).unwrap();
+
+        let output = TaskOutput { data: json!({ "urls": ["a", "b", "a"] }), ..Default::default() };
+        dag.get_task_mut(list).unwrap().complete(output, 1, 0.0);
+        let updates = dag.advance_map_nodes(FailurePolicy::default());
+        let MapUpdate::Expanded { instances, .. } = &updates[0] else { panic!("{:?}", updates) };
+        assert_eq!(instances.len(), 3);
+        assert_eq!(instances[0], instances[2]);
+        assert_ne!(instances[0], instances[1]);
+
+        // Two executions, not three
+        let ready = dag.get_ready_tasks();
+        assert_eq!(ready.len(), 2);
+        for id in ready {
+            let item = dag.get_task(id).unwrap().input.context["item"].clone();
+            let output = TaskOutput { result: item.as_str().unwrap().to_string(), ..Default::default() };
+            dag.get_task_mut(id).unwrap().complete(output, 1, 0.0);
+        }
+        dag.advance_map_nodes(FailurePolicy::default());
+        let joined = dag.get_task(read).unwrap().output.as_ref().unwrap();
+        assert_eq!(joined.result, "a\nb\na");
+    }
+}
@@ -14,9 +14,10 @@ use dashmap::DashMap;
 use tokio::sync::{broadcast, mpsc, RwLock};
 use uuid::Uuid;
 
-use super::{DagStats, Task, TaskDAG, TaskId, TaskOutput, TaskStatus};
+use super::{DagStats, FailurePolicy, MapUpdate, Task, TaskDAG, TaskId, TaskOutput, TaskStatus};
 use crate::contracts::{AgentContract, ContractEnforcer, ResourceLimits, UsageTracker};
 use crate::error::Result;
+use crate::orchestrator::AgentCircuitOpenReason;
 
 /// Events emitted during DAG execution.
 #[derive(Debug, Clone)]
@@ -31,6 +32,10 @@ pub enum ExecutionEvent {
     },
     /// DAG execution failed
     DagFailed { dag_id: Uuid, error: String },
+    /// DAG paused: no new tasks are dispatched, in-flight ones finish
+    DagPaused { dag_id: Uuid },
+    /// Paused DAG resumed
+    DagResumed { dag_id: Uuid },
     /// Task started execution
     TaskStarted { dag_id: Uuid, task_id: TaskId },
     /// Task completed successfully
@@ -57,6 +62,24 @@ pub enum ExecutionEvent {
         resource: String,
         usage_percent: f64,
     },
+    /// The circuit of a model opened after repeated failures; tasks routed
+    /// to it fail fast until it recovers
+    CircuitOpened {
+        model: String,
+        reason: AgentCircuitOpenReason,
+    },
+}
+
+/// How the executor schedules ready tasks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Spawn ready tasks concurrently and poll for results
+    #[default]
+    Concurrent,
+    /// Run ready tasks one at a time in topological order, without spawning
+    /// or sleeping, so the same DAG and task runner always produce the same
+    /// events. Intended for tests.
+    Deterministic,
 }
 
 /// Configuration for the DAG executor.
@@ -72,6 +95,10 @@ pub struct ExecutorConfig {
     pub poll_interval_ms: u64,
     /// Event channel buffer size
     pub event_buffer_size: usize,
+    /// How ready tasks are scheduled
+    pub mode: ExecutionMode,
+    /// Abort the DAG once more tasks than this have failed (`None` = never)
+    pub max_failed_tasks: Option<usize>,
 }
 
 impl Default for ExecutorConfig {
@@ -82,6 +109,18 @@ impl Default for ExecutorConfig {
             cancel_dependents_on_failure: true,
             poll_interval_ms: 50,
             event_buffer_size: 1000,
+            mode: ExecutionMode::Concurrent,
+            max_failed_tasks: None,
+        }
+    }
+}
+
+impl ExecutorConfig {
+    /// Default configuration in [`ExecutionMode::Deterministic`].
+    pub fn deterministic() -> Self {
+        Self {
+            mode: ExecutionMode::Deterministic,
+            ..Self::default()
         }
     }
 }
@@ -178,14 +217,67 @@ impl DagExecutor {
         tracing::info!(
             dag_id = %dag_id,
             execution_id = %self.execution_id,
+            mode = ?self.config.mode,
             "Starting DAG execution"
         );
 
-        let mut total_tokens = 0u64;
-        let mut total_cost = 0.0f64;
-        let mut tasks_completed = 0usize;
-        let mut tasks_failed = 0usize;
+        let mut totals = RunTotals::default();
+        match self.config.mode {
+            ExecutionMode::Concurrent => {
+                self.run_concurrent(dag_id, task_executor, &mut totals).await?
+            }
+            ExecutionMode::Deterministic => {
+                self.run_deterministic(dag_id, task_executor, &mut totals).await?
+            }
+        }
+
+        let duration_ms = self
+            .start_time
+            .map(|t| t.elapsed().as_millis() as u64)
+            .unwrap_or(0);
+        let stats = self.dag.read().await.stats();
+
+        // Emit completion event
+        self.emit_event(ExecutionEvent::DagCompleted {
+            dag_id,
+            stats: stats.clone(),
+            duration_ms,
+        });
+
+        tracing::info!(
+            dag_id = %dag_id,
+            execution_id = %self.execution_id,
+            tasks_completed = totals.tasks_completed,
+            tasks_failed = totals.tasks_failed,
+            total_tokens = totals.total_tokens,
+            total_cost = totals.total_cost,
+            duration_ms = duration_ms,
+            "DAG execution completed"
+        );
+
+        Ok(DagExecutionSummary {
+            dag_id,
+            execution_id: self.execution_id,
+            stats,
+            total_tokens: totals.total_tokens,
+            total_cost: totals.total_cost,
+            duration_ms,
+            tasks_completed: totals.tasks_completed,
+            tasks_failed: totals.tasks_failed,
+        })
+    }
 
+    /// Spawn ready tasks up to `max_concurrent_tasks` and poll for results.
+    async fn run_concurrent<F, Fut>(
+        &self,
+        dag_id: Uuid,
+        task_executor: F,
+        totals: &mut RunTotals,
+    ) -> Result<()>
+    where
+        F: Fn(Task) -> Fut + Send + Sync + Clone + 'static,
+        Fut: std::future::Future<Output = Result<TaskResult>> + Send,
+    {
         // Create task result channel
         let (result_sender, mut result_receiver) =
             mpsc::channel::<TaskResult>(self.config.max_concurrent_tasks);
@@ -204,11 +296,17 @@ impl DagExecutor {
             }
 
             // Get ready tasks
+            self.advance_dag(dag_id, totals).await;
             let ready_tasks = {
                 let dag = self.dag.read().await;
                 dag.get_ready_tasks()
             };
 
+            if ready_tasks.is_empty() && running_tasks.is_empty() {
+                self.cancel_blocked(dag_id).await;
+                break;
+            }
+
             // Launch ready tasks (respecting concurrency limit)
             let available_slots = self
                 .config
@@ -217,32 +315,10 @@ impl DagExecutor {
             let tasks_to_launch: Vec<_> = ready_tasks.into_iter().take(available_slots).collect();
 
             for task_id in tasks_to_launch {
-                // Get task and mark as running
-                let task = {
-                    let mut dag = self.dag.write().await;
-                    dag.update_task_status(task_id, TaskStatus::Ready)?;
-                    dag.get_task(task_id).cloned()
-                };
-
-                let Some(task) = task else {
+                let Some(task) = self.prepare_task(dag_id, task_id).await? else {
                     continue;
                 };
 
-                // Create contract for task
-                let contract = AgentContract::new(
-                    Uuid::new_v4(), // Agent will be assigned later
-                    task_id.0,
-                    self.config.default_limits.clone(),
-                );
-
-                // Validate against parent contract
-                self.contract_enforcer.validate_child_contract(&contract)?;
-
-                self.task_contracts.insert(task_id, contract);
-
-                // Emit task started event
-                self.emit_event(ExecutionEvent::TaskStarted { dag_id, task_id });
-
                 // Spawn task execution
                 let executor = task_executor.clone();
                 let sender = result_sender.clone();
@@ -259,20 +335,7 @@ impl DagExecutor {
                     }
 
                     // Execute the task
-                    let result = executor(task).await;
-
-                    let task_result = match result {
-                        Ok(r) => r,
-                        Err(e) => TaskResult {
-                            task_id,
-                            output: None,
-                            error: Some(e.to_string()),
-                            tokens_used: 0,
-                            cost: 0.0,
-                            duration_ms: 0,
-                            should_retry: e.is_retryable(),
-                        },
-                    };
+                    let task_result = run_task(&executor, task).await;
 
                     // Send result
                     let _ = sender.send(task_result).await;
@@ -285,70 +348,9 @@ impl DagExecutor {
             tokio::select! {
                 Some(result) = result_receiver.recv() => {
                     running_tasks.remove(&result.task_id);
-
-                    // Update task state
-                    let mut dag = self.dag.write().await;
-
-                    if let Some(error) = &result.error {
-                        if let Some(task) = dag.get_task_mut(result.task_id) {
-                            if result.should_retry && task.should_retry() {
-                                // Retry the task
-                                task.prepare_retry();
-                                self.emit_event(ExecutionEvent::TaskFailed {
-                                    dag_id,
-                                    task_id: result.task_id,
-                                    error: error.clone(),
-                                    will_retry: true,
-                                });
-                            } else {
-                                // Mark as failed
-                                task.fail(error);
-                                tasks_failed += 1;
-
-                                self.emit_event(ExecutionEvent::TaskFailed {
-                                    dag_id,
-                                    task_id: result.task_id,
-                                    error: error.clone(),
-                                    will_retry: false,
-                                });
-
-                                // Cancel dependents if configured
-                                if self.config.cancel_dependents_on_failure {
-                                    if let Ok(cancelled) = dag.cancel_dependents(result.task_id) {
-                                        for cancelled_id in cancelled {
-                                            self.emit_event(ExecutionEvent::TaskCancelled {
-                                                dag_id,
-                                                task_id: cancelled_id,
-                                            });
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    } else if let Some(output) = result.output {
-                        if let Some(task) = dag.get_task_mut(result.task_id) {
-                            task.complete(output, result.tokens_used, result.cost);
-                            total_tokens += result.tokens_used;
-                            total_cost += result.cost;
-                            tasks_completed += 1;
-
-                            // Update usage tracker
-                            self.usage_tracker.record_tokens(result.tokens_used);
-                            self.usage_tracker.record_cost(result.cost);
-                            self.usage_tracker.record_api_call();
-
-                            self.emit_event(ExecutionEvent::TaskCompleted {
-                                dag_id,
-                                task_id: result.task_id,
-                                tokens: result.tokens_used,
-                                cost: result.cost,
-                                duration_ms: result.duration_ms,
-                            });
-                        }
+                    if self.apply_result(dag_id, result, totals).await {
+                        break;
                     }
-
-                    // Remove task contract
-                    self.task_contracts.remove(&result.task_id);
                 }
                 _ = tokio::time::sleep(tokio::time::Duration::from_millis(self.config.poll_interval_ms)) => {
                     // Continue polling for ready tasks
@@ -361,42 +363,268 @@ impl DagExecutor {
             let _ = entry.value().is_finished();
         }
 
-        let duration_ms = self
-            .start_time
-            .map(|t| t.elapsed().as_millis() as u64)
-            .unwrap_or(0);
-        let stats = self.dag.read().await.stats();
+        Ok(())
+    }
 
-        // Emit completion event
-        self.emit_event(ExecutionEvent::DagCompleted {
-            dag_id,
-            stats: stats.clone(),
-            duration_ms,
-        });
+    /// Run ready tasks one at a time, in topological order, on the calling task.
+    ///
+    /// Each pass runs every task that was ready at its start before looking
+    /// again, so retries run on the following pass. When nothing is ready but
+    /// the DAG is not complete, the remaining tasks are blocked behind
+    /// failures and are cancelled at once rather than polled for.
+    async fn run_deterministic<F, Fut>(
+        &self,
+        dag_id: Uuid,
+        task_executor: F,
+        totals: &mut RunTotals,
+    ) -> Result<()>
+    where
+        F: Fn(Task) -> Fut + Send + Sync + Clone + 'static,
+        Fut: std::future::Future<Output = Result<TaskResult>> + Send,
+    {
+        loop {
+            self.advance_dag(dag_id, totals).await;
+            let ready_tasks: Vec<TaskId> = {
+                let dag = self.dag.read().await;
+                if dag.is_complete() {
+                    return Ok(());
+                }
+                // Map nodes insert tasks as they expand, so the order is taken per pass
+                let order = dag.topological_order()?;
+                let ready = dag.get_ready_tasks();
+                order.iter().copied().filter(|id| ready.contains(id)).collect()
+            };
 
-        tracing::info!(
-            dag_id = %dag_id,
-            execution_id = %self.execution_id,
-            tasks_completed = tasks_completed,
-            tasks_failed = tasks_failed,
-            total_tokens = total_tokens,
-            total_cost = total_cost,
-            duration_ms = duration_ms,
-            "DAG execution completed"
+            if ready_tasks.is_empty() {
+                self.cancel_blocked(dag_id).await;
+                return Ok(());
+            }
+
+            for task_id in ready_tasks {
+                let Some(task) = self.prepare_task(dag_id, task_id).await? else {
+                    continue;
+                };
+
+                {
+                    let mut dag = self.dag.write().await;
+                    if let Some(t) = dag.get_task_mut(task_id) {
+                        t.status = TaskStatus::Running;
+                        t.started_at = Some(chrono::Utc::now());
+                    }
+                }
+
+                let result = run_task(&task_executor, task).await;
+                if self.apply_result(dag_id, result, totals).await {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Mark a task ready, attach its contract and emit `TaskStarted`.
+    async fn prepare_task(&self, dag_id: Uuid, task_id: TaskId) -> Result<Option<Task>> {
+        // Get task and mark as ready
+        let task = {
+            let mut dag = self.dag.write().await;
+            dag.update_task_status(task_id, TaskStatus::Ready)?;
+            dag.get_task(task_id).cloned()
+        };
+
+        let Some(task) = task else {
+            return Ok(None);
+        };
+
+        // Create contract for task
+        let contract = AgentContract::new(
+            Uuid::new_v4(), // Agent will be assigned later
+            task_id.0,
+            self.config.default_limits.clone(),
         );
 
-        Ok(DagExecutionSummary {
-            dag_id,
-            execution_id: self.execution_id,
-            stats,
-            total_tokens,
-            total_cost,
-            duration_ms,
-            tasks_completed,
-            tasks_failed,
+        // Validate against parent contract
+        self.contract_enforcer.validate_child_contract(&contract)?;
+
+        self.task_contracts.insert(task_id, contract);
+
+        // Emit task started event
+        self.emit_event(ExecutionEvent::TaskStarted { dag_id, task_id });
+
+        Ok(Some(task))
+    }
+
+    /// Record a finished task: retry it, fail it (cancelling dependents per
+    /// the failure policy) or complete it.
+    ///
+    /// Returns `true` when the failure threshold was exceeded and the rest of
+    /// the DAG has been cancelled.
+    async fn apply_result(&self, dag_id: Uuid, result: TaskResult, totals: &mut RunTotals) -> bool {
+        let mut dag = self.dag.write().await;
+        let mut aborted = false;
+
+        if let Some(error) = &result.error {
+            let retry = dag
+                .get_task(result.task_id)
+                .map(|task| result.should_retry && task.should_retry());
+
+            match retry {
+                Some(true) => {
+                    // Retry the task
+                    if let Some(task) = dag.get_task_mut(result.task_id) {
+                        task.prepare_retry();
+                    }
+                    self.emit_event(ExecutionEvent::TaskFailed {
+                        dag_id,
+                        task_id: result.task_id,
+                        error: error.clone(),
+                        will_retry: true,
+                    });
+                }
+                Some(false) => {
+                    // Mark as failed and cancel dependents if configured
+                    let policy = self.failure_policy(&dag);
+                    let cancelled = dag
+                        .fail_task(result.task_id, error, policy)
+                        .unwrap_or_default();
+                    totals.tasks_failed += 1;
+
+                    self.emit_event(ExecutionEvent::TaskFailed {
+                        dag_id,
+                        task_id: result.task_id,
+                        error: error.clone(),
+                        will_retry: false,
+                    });
+                    for cancelled_id in cancelled {
+                        self.emit_event(ExecutionEvent::TaskCancelled {
+                            dag_id,
+                            task_id: cancelled_id,
+                        });
+                    }
+
+                    if self
+                        .config
+                        .max_failed_tasks
+                        .map(|limit| totals.tasks_failed > limit)
+                        .unwrap_or(false)
+                    {
+                        tracing::warn!(
+                            dag_id = %dag_id,
+                            tasks_failed = totals.tasks_failed,
+                            max_failed_tasks = ?self.config.max_failed_tasks,
+                            "Failure threshold exceeded, aborting DAG"
+                        );
+                        for cancelled_id in dag.cancel_remaining() {
+                            self.emit_event(ExecutionEvent::TaskCancelled {
+                                dag_id,
+                                task_id: cancelled_id,
+                            });
+                        }
+                        aborted = true;
+                    }
+                }
+                None => {}
+            }
+        } else if let Some(output) = result.output {
+            if let Some(task) = dag.get_task_mut(result.task_id) {
+                task.complete(output, result.tokens_used, result.cost);
+                totals.total_tokens += result.tokens_used;
+                totals.total_cost += result.cost;
+                totals.tasks_completed += 1;
+
+                // Update usage tracker
+                self.usage_tracker.record_tokens(result.tokens_used);
+                self.usage_tracker.record_cost(result.cost);
+                self.usage_tracker.record_api_call();
+
+                self.emit_event(ExecutionEvent::TaskCompleted {
+                    dag_id,
+                    task_id: result.task_id,
+                    tokens: result.tokens_used,
+                    cost: result.cost,
+                    duration_ms: result.duration_ms,
+                });
+            }
+        }
+
+        // Remove task contract
+        self.task_contracts.remove(&result.task_id);
+        aborted
+    }
+
+    /// Cancel tasks that can never become ready because a dependency failed
+    /// or was cancelled. Only called when nothing is ready or running.
+    async fn cancel_blocked(&self, dag_id: Uuid) {
+        let cancelled = self.dag.write().await.cancel_remaining();
+        if !cancelled.is_empty() {
+            tracing::info!(
+                dag_id = %dag_id,
+                blocked = cancelled.len(),
+                "Cancelling tasks blocked behind failed dependencies"
+            );
+        }
+        for task_id in cancelled {
+            self.emit_event(ExecutionEvent::TaskCancelled { dag_id, task_id });
+        }
+    }
+
+    /// The DAG's failure policy, or the one implied by the executor config.
+    fn failure_policy(&self, dag: &TaskDAG) -> FailurePolicy {
+        dag.failure_policy().unwrap_or(if self.config.cancel_dependents_on_failure {
+            FailurePolicy::CancelDependents
+        } else {
+            FailurePolicy::ContinueIndependent
         })
     }
 
+    /// Expand and join map nodes and skip tasks whose conditional edges were
+    /// not met, until neither changes anything.
+    async fn advance_dag(&self, dag_id: Uuid, totals: &mut RunTotals) {
+        loop {
+            let (updates, skipped) = {
+                let mut dag = self.dag.write().await;
+                let policy = self.failure_policy(&dag);
+                (dag.advance_map_nodes(policy), dag.skip_unsatisfied())
+            };
+            if updates.is_empty() && skipped.is_empty() {
+                return;
+            }
+
+            for update in updates {
+                match update {
+                    MapUpdate::Expanded { map_id, instances } => {
+                        tracing::debug!(dag_id = %dag_id, task_id = %map_id, instances = instances.len(), "Expanded map task");
+                    }
+                    MapUpdate::Joined { map_id } => {
+                        self.emit_event(ExecutionEvent::TaskCompleted {
+                            dag_id,
+                            task_id: map_id,
+                            tokens: 0,
+                            cost: 0.0,
+                            duration_ms: 0,
+                        });
+                    }
+                    MapUpdate::Failed { map_id, error, cancelled } => {
+                        totals.tasks_failed += 1;
+                        self.emit_event(ExecutionEvent::TaskFailed {
+                            dag_id,
+                            task_id: map_id,
+                            error,
+                            will_retry: false,
+                        });
+                        for task_id in cancelled {
+                            self.emit_event(ExecutionEvent::TaskCancelled { dag_id, task_id });
+                        }
+                    }
+                }
+            }
+            if !skipped.is_empty() {
+                tracing::debug!(dag_id = %dag_id, skipped = skipped.len(), "Skipping tasks with unmet conditions");
+            }
+            for task_id in skipped {
+                self.emit_event(ExecutionEvent::TaskCancelled { dag_id, task_id });
+            }
+        }
+    }
+
     /// Get current execution statistics.
     pub async fn stats(&self) -> DagStats {
         self.dag.read().await.stats()
@@ -430,6 +658,36 @@ impl DagExecutor {
     }
 }
 
+/// Run `executor` on `task`, turning an error into a failed [`TaskResult`].
+async fn run_task<F, Fut>(executor: &F, task: Task) -> TaskResult
+where
+    F: Fn(Task) -> Fut,
+    Fut: std::future::Future<Output = Result<TaskResult>>,
+{
+    let task_id = task.id;
+    match executor(task).await {
+        Ok(r) => r,
+        Err(e) => TaskResult {
+            task_id,
+            output: None,
+            error: Some(e.to_string()),
+            tokens_used: 0,
+            cost: 0.0,
+            duration_ms: 0,
+            should_retry: e.is_retryable(),
+        },
+    }
+}
+
+/// Running totals for one execution.
+#[derive(Debug, Default)]
+struct RunTotals {
+    total_tokens: u64,
+    total_cost: f64,
+    tasks_completed: usize,
+    tasks_failed: usize,
+}
+
 /// Summary of a completed DAG execution.
 #[derive(Debug, Clone)]
 pub struct DagExecutionSummary {
@@ -471,7 +729,7 @@ impl DagExecutionSummary {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::dag::TaskInput;
+    use crate::dag::{EdgeCondition, MapSpec, TaskInput};
 
     fn create_test_dag() -> TaskDAG {
         let mut dag = TaskDAG::new("test-dag");
@@ -572,4 +830,241 @@ mod tests {
         assert!(result.output.is_some());
         assert!(!result.should_retry);
     }
+
+    fn succeed(task: &Task) -> TaskResult {
+        TaskResult {
+            task_id: task.id,
+            output: Some(TaskOutput {
+                result: format!("{} done", task.name),
+                ..TaskOutput::default()
+            }),
+            error: None,
+            tokens_used: 10,
+            cost: 0.01,
+            duration_ms: 0,
+            should_retry: false,
+        }
+    }
+
+    fn fail(task: &Task, should_retry: bool) -> TaskResult {
+        TaskResult {
+            task_id: task.id,
+            output: None,
+            error: Some(format!("{} failed", task.name)),
+            tokens_used: 0,
+            cost: 0.0,
+            duration_ms: 0,
+            should_retry,
+        }
+    }
+
+    fn drain(receiver: &mut broadcast::Receiver<ExecutionEvent>) -> Vec<ExecutionEvent> {
+        std::iter::from_fn(|| receiver.try_recv().ok()).collect()
+    }
+
+    async fn run_in_order(dag: TaskDAG) -> (Vec<String>, DagExecutionSummary) {
+        let ran = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut executor = DagExecutor::new(dag, ExecutorConfig::deterministic(), None);
+        let summary = executor
+            .execute({
+                let ran = ran.clone();
+                move |task: Task| {
+                    ran.lock().unwrap().push(task.name.clone());
+                    let result = succeed(&task);
+                    async move { Ok(result) }
+                }
+            })
+            .await
+            .unwrap();
+        let ran = ran.lock().unwrap().clone();
+        (ran, summary)
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_runs_in_topological_order() {
+        let build = || {
+            let mut dag = create_test_dag();
+            dag.add_task(Task::new("Task D", TaskInput::default())).unwrap();
+            dag
+        };
+
+        let (first, summary) = run_in_order(build()).await;
+        let (second, _) = run_in_order(build()).await;
+
+        assert_eq!(first, second);
+        let position = |name: &str| first.iter().position(|n| n == name).unwrap();
+        assert!(position("Task A") < position("Task B"));
+        assert!(position("Task B") < position("Task C"));
+        assert!(summary.is_success());
+        assert_eq!(summary.tasks_completed, 4);
+        assert_eq!(summary.total_tokens, 40);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_branch_is_skipped() {
+        let mut dag = TaskDAG::new("branching");
+        let [a, b, c, d] = ["Task A", "Task B", "Task C", "Task D"]
+            .map(|name| dag.add_task(Task::new(name, TaskInput::default())).unwrap());
+        dag.add_conditional_dependency(a, b, EdgeCondition::new("$.result", "Task A done")).unwrap();
+        dag.add_conditional_dependency(a, c, EdgeCondition::new("$.result", "nothing")).unwrap();
+        dag.add_dependency(c, d).unwrap();
+
+        let (ran, summary) = run_in_order(dag).await;
+
+        assert_eq!(ran, vec!["Task A", "Task B"]);
+        assert_eq!(summary.tasks_completed, 2);
+        assert_eq!(summary.stats.cancelled, 2);
+    }
+
+    #[tokio::test]
+    async fn test_map_task_fans_out_and_merges() {
+        let mut dag = TaskDAG::new("fan-out");
+        let list = dag.add_task(Task::new("list", TaskInput::default())).unwrap();
+        let square = dag.add_map_task(Task::new("square", TaskInput::default()), list, MapSpec::new("$.data.items")).unwrap();
+        let merge = dag.add_task(Task::new("merge", TaskInput::default())).unwrap();
+        dag.add_dependency(square, merge).unwrap();
+
+        let ran = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut executor = DagExecutor::new(dag, ExecutorConfig { poll_interval_ms: 5, ..Default::default() }, None);
+        let summary = executor
+            .execute({
+                let ran = ran.clone();
+                move |task: Task| {
+                    ran.lock().unwrap().push(task.name.clone());
+                    let mut result = succeed(&task);
+                    let output = result.output.as_mut().unwrap();
+                    if task.name == "list" {
+                        output.data = serde_json::json!({ "items": [1, 2, 3, 4, 5] });
+                    } else if let Some(n) = task.input.context["item"].as_i64() {
+                        output.data = serde_json::json!(n * n);
+                    }
+                    async move { Ok(result) }
+                }
+            })
+            .await
+            .unwrap();
+
+        let ran = ran.lock().unwrap().clone();
+        assert_eq!(ran.len(), 7, "{:?}", ran);
+        assert_eq!(ran.first().map(String::as_str), Some("list"));
+        assert_eq!(ran.last().map(String::as_str), Some("merge"));
+        assert!(ran.iter().filter(|name| name.starts_with("square[")).count() == 5);
+
+        assert!(summary.is_success());
+        assert_eq!(summary.tasks_completed, 7);
+        // The five instances plus the joined map node itself
+        assert_eq!(summary.stats.total, 8);
+        assert_eq!(summary.stats.completed, 8);
+
+        let squares: Vec<serde_json::Value> = executor.dag.read().await
+            .get_task(square).unwrap()
+            .output.as_ref().unwrap()
+            .data.as_array().unwrap()
+            .iter().map(|output| output["data"].clone()).collect();
+        assert_eq!(squares, [1, 4, 9, 16, 25].map(serde_json::Value::from));
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_retries_then_succeeds() {
+        let attempts = Arc::new(std::sync::Mutex::new(0));
+        let mut executor = DagExecutor::new(create_test_dag(), ExecutorConfig::deterministic(), None);
+        let mut events = executor.subscribe();
+
+        let summary = executor
+            .execute({
+                let attempts = attempts.clone();
+                move |task: Task| {
+                    let mut attempts = attempts.lock().unwrap();
+                    let result = if task.name == "Task B" && *attempts < 2 {
+                        *attempts += 1;
+                        fail(&task, true)
+                    } else {
+                        succeed(&task)
+                    };
+                    async move { Ok(result) }
+                }
+            })
+            .await
+            .unwrap();
+
+        assert!(summary.is_success());
+        let retries = drain(&mut events)
+            .into_iter()
+            .filter(|e| matches!(e, ExecutionEvent::TaskFailed { will_retry: true, .. }))
+            .count();
+        assert_eq!(retries, 2);
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_failure_cancels_dependents() {
+        let mut executor = DagExecutor::new(create_test_dag(), ExecutorConfig::deterministic(), None);
+        let mut events = executor.subscribe();
+
+        let summary = executor
+            .execute(|task: Task| {
+                let result = if task.name == "Task A" { fail(&task, false) } else { succeed(&task) };
+                async move { Ok(result) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(summary.tasks_failed, 1);
+        assert_eq!(summary.stats.cancelled, 2);
+        let cancelled = drain(&mut events)
+            .into_iter()
+            .filter(|e| matches!(e, ExecutionEvent::TaskCancelled { .. }))
+            .count();
+        assert_eq!(cancelled, 2);
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_cancels_blocked_tasks_without_polling() {
+        let mut dag = create_test_dag();
+        dag.add_task(Task::new("Task D", TaskInput::default())).unwrap();
+        let config = ExecutorConfig {
+            cancel_dependents_on_failure: false,
+            // Would stall the test if the executor ever waited on the poll interval
+            poll_interval_ms: 60_000,
+            ..ExecutorConfig::deterministic()
+        };
+
+        let mut executor = DagExecutor::new(dag, config, None);
+        let summary = executor
+            .execute(|task: Task| {
+                let result = if task.name == "Task A" { fail(&task, false) } else { succeed(&task) };
+                async move { Ok(result) }
+            })
+            .await
+            .unwrap();
+
+        // D is independent and still runs; B and C can never become ready
+        assert_eq!(summary.tasks_completed, 1);
+        assert_eq!(summary.tasks_failed, 1);
+        assert_eq!(summary.stats.cancelled, 2);
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_failure_threshold_aborts() {
+        let mut dag = TaskDAG::new("independent");
+        for name in ["Task A", "Task B", "Task C"] {
+            dag.add_task(Task::new(name, TaskInput::default())).unwrap();
+        }
+        let config = ExecutorConfig {
+            max_failed_tasks: Some(1),
+            cancel_dependents_on_failure: false,
+            ..ExecutorConfig::deterministic()
+        };
+
+        let mut executor = DagExecutor::new(dag, config, None);
+        let summary = executor
+            .execute(|task: Task| {
+                let result = fail(&task, false);
+                async move { Ok(result) }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(summary.tasks_failed, 2);
+        assert_eq!(summary.stats.cancelled, 1);
+    }
 }
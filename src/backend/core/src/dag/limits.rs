@@ -0,0 +1,179 @@
+//! Size limits on submitted DAGs.
+//!
+//! A DAG is held in memory and scheduled as a whole, so an oversized one
+//! (a buggy client generating a million tasks, say) is refused before it is
+//! admitted. Three dimensions are bounded, each `0` for unlimited:
+//!
+//! - the number of tasks,
+//! - the depth, i.e. the number of tasks on the longest dependency chain,
+//! - the fan-out of a task: its direct dependents, or for a map node the
+//!   most instances one expansion may create.
+
+use petgraph::Direction;
+use serde::{Deserialize, Serialize};
+
+use super::TaskDAG;
+use crate::error::{ApexError, Result};
+
+/// Largest DAG accepted for execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DagLimits {
+    /// Most tasks in one DAG (0 = unlimited)
+    pub max_tasks: usize,
+
+    /// Most tasks on one dependency chain (0 = unlimited)
+    pub max_depth: usize,
+
+    /// Most direct dependents or map instances of one task (0 = unlimited)
+    pub max_fan_out: usize,
+}
+
+impl Default for DagLimits {
+    fn default() -> Self {
+        Self {
+            max_tasks: 10_000,
+            max_depth: 1_000,
+            max_fan_out: 1_000,
+        }
+    }
+}
+
+impl DagLimits {
+    /// No limits.
+    pub fn unlimited() -> Self {
+        Self {
+            max_tasks: 0,
+            max_depth: 0,
+            max_fan_out: 0,
+        }
+    }
+}
+
+/// `actual` exceeds `max`, unless `max` is 0.
+fn exceeds(actual: usize, max: usize) -> bool {
+    max > 0 && actual > max
+}
+
+impl TaskDAG {
+    /// Number of tasks on the longest dependency chain (0 for an empty DAG).
+    pub fn depth(&self) -> Result<usize> {
+        let order = self.topological_order()?;
+        let mut depths = vec![0usize; self.graph.node_count()];
+        let mut deepest = 0;
+        for task_id in order {
+            let node = self.task_index[&task_id];
+            let depth = 1 + self
+                .graph
+                .neighbors_directed(node, Direction::Incoming)
+                .map(|parent| depths[parent.index()])
+                .max()
+                .unwrap_or(0);
+            depths[node.index()] = depth;
+            deepest = deepest.max(depth);
+        }
+        Ok(deepest)
+    }
+
+    /// Check the DAG against `limits`, failing with `DagValidationFailed`
+    /// on the first one exceeded.
+    pub fn check_limits(&self, limits: &DagLimits) -> Result<()> {
+        let tasks = self.graph.node_count();
+        if exceeds(tasks, limits.max_tasks) {
+            return Err(ApexError::dag_too_large("task count", tasks, limits.max_tasks));
+        }
+
+        if limits.max_fan_out > 0 {
+            for node in self.graph.node_indices() {
+                let task = &self.graph[node];
+                let dependents = self.graph.neighbors_directed(node, Direction::Outgoing).count();
+                let instances = self.map_nodes.get(&task.id).map_or(0, |map| map.max_fan_out());
+                let fan_out = dependents.max(instances);
+                if exceeds(fan_out, limits.max_fan_out) {
+                    return Err(ApexError::dag_too_large(
+                        &format!("fan-out of task '{}'", task.name),
+                        fan_out,
+                        limits.max_fan_out,
+                    ));
+                }
+            }
+        }
+
+        if limits.max_depth > 0 {
+            let depth = self.depth()?;
+            if exceeds(depth, limits.max_depth) {
+                return Err(ApexError::dag_too_large("depth", depth, limits.max_depth));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::{MapSpec, Task, TaskInput};
+    use crate::error::ErrorCode;
+
+    fn task(name: &str) -> Task {
+        Task::new(name, TaskInput::default())
+    }
+
+    /// `root` feeding a chain of `chain` tasks and `leaves` direct dependents.
+    fn dag(chain: usize, leaves: usize) -> TaskDAG {
+        let mut dag = TaskDAG::new("limits");
+        let root = dag.add_task(task("root")).unwrap();
+        let mut prev = root;
+        for i in 0..chain {
+            let next = dag.add_task(task(&format!("step-{}", i))).unwrap();
+            dag.add_dependency(prev, next).unwrap();
+            prev = next;
+        }
+        for i in 0..leaves {
+            let leaf = dag.add_task(task(&format!("leaf-{}", i))).unwrap();
+            dag.add_dependency(root, leaf).unwrap();
+        }
+        dag
+    }
+
+    fn limits(max_tasks: usize, max_depth: usize, max_fan_out: usize) -> DagLimits {
+        DagLimits { max_tasks, max_depth, max_fan_out }
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(TaskDAG::new("empty").depth().unwrap(), 0);
+        assert_eq!(dag(0, 3).depth().unwrap(), 2);
+        assert_eq!(dag(4, 3).depth().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_limits_reject_oversized_dags() {
+        let dag = dag(3, 4);
+        assert!(dag.check_limits(&limits(8, 4, 5)).is_ok());
+        assert!(dag.check_limits(&DagLimits::unlimited()).is_ok());
+
+        let err = dag.check_limits(&limits(7, 0, 0)).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DagValidationFailed);
+        assert!(err.to_string().contains("task count is 8"), "{}", err);
+
+        let err = dag.check_limits(&limits(0, 3, 0)).unwrap_err();
+        assert!(err.to_string().contains("depth"), "{}", err);
+
+        // root has the 4 leaves and the first step of the chain
+        let err = dag.check_limits(&limits(0, 0, 4)).unwrap_err();
+        assert!(err.to_string().contains("fan-out of task 'root'"), "{}", err);
+    }
+
+    #[test]
+    fn test_map_fan_out_counts_toward_limit() {
+        let mut dag = TaskDAG::new("map");
+        let list = dag.add_task(task("list")).unwrap();
+        dag.add_map_task(task("read"), list, MapSpec::new("$.data.urls").with_max_fan_out(50))
+            .unwrap();
+
+        assert!(dag.check_limits(&limits(0, 0, 50)).is_ok());
+        let err = dag.check_limits(&limits(0, 0, 49)).unwrap_err();
+        assert!(err.to_string().contains("'read'"), "{}", err);
+    }
+}
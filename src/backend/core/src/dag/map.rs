@@ -0,0 +1,330 @@
+//! Map (fan-out) nodes that expand at runtime.
+//!
+//! A map node runs its task once per item of an array in its source task's
+//! output. When the source completes, the map node is expanded: one instance
+//! per item is inserted between the source and the map node, each a copy of
+//! the map node's task with the item injected into its context as `item`
+//! (and its position as `item_index`). The map node itself then acts as the
+//! join: once every instance has completed it completes without running,
+//! with the instances' outputs collected in order under `data`.
+//!
+//! ```yaml
+//! tasks:
+//!   - id: list
+//!     name: List sources
+//!     instruction: Return the URLs to read as data.urls
+//!   - id: read
+//!     name: Read source
+//!     instruction: Summarize the page at the URL in the context
+//!     depends_on: [list]
+//!     map:
+//!       items: $.data.urls
+//!       max_fan_out: 20
+//! ```
+//!
+//! An item list that isn't an array, or that is longer than `max_fan_out`,
+//! fails the map node instead of expanding it.
+
+use serde::{Deserialize, Serialize};
+
+use super::condition::json_pointer;
+use super::{FailurePolicy, Task, TaskDAG, TaskId, TaskOutput, TaskStatus};
+use crate::error::{ApexError, Result};
+
+/// Fan-out limit when a map doesn't set one.
+pub const DEFAULT_MAX_FAN_OUT: usize = 100;
+
+fn default_max_fan_out() -> usize {
+    DEFAULT_MAX_FAN_OUT
+}
+
+/// How a map node finds its items.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MapSpec {
+    /// JSONPath to the array in the source's output, e.g. `$.data.items`
+    pub items: String,
+
+    /// Most instances one expansion may create
+    #[serde(default = "default_max_fan_out")]
+    pub max_fan_out: usize,
+}
+
+impl MapSpec {
+    pub fn new(items: impl Into<String>) -> Self {
+        Self {
+            items: items.into(),
+            max_fan_out: DEFAULT_MAX_FAN_OUT,
+        }
+    }
+
+    pub fn with_max_fan_out(mut self, max_fan_out: usize) -> Self {
+        self.max_fan_out = max_fan_out;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct MapNode {
+    source: TaskId,
+    spec: MapSpec,
+    /// Set once the node has been expanded
+    instances: Option<Vec<TaskId>>,
+}
+
+impl MapNode {
+    /// A not yet expanded node fed by `source`, which must already be an edge into it.
+    pub(super) fn new(source: TaskId, spec: MapSpec) -> Self {
+        Self { source, spec, instances: None }
+    }
+
+    /// Most instances one expansion of this node may create.
+    pub(super) fn max_fan_out(&self) -> usize {
+        self.spec.max_fan_out
+    }
+}
+
+/// What [`TaskDAG::advance_map_nodes`] did to a map node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MapUpdate {
+    /// Instances were inserted for each item, in item order; with dedup,
+    /// repeated items repeat the instance of their first occurrence
+    Expanded { map_id: TaskId, instances: Vec<TaskId> },
+    /// Every instance completed and the map node completed with their outputs
+    Joined { map_id: TaskId },
+    /// The items couldn't be expanded; the map node failed and `cancelled`
+    /// were cancelled under the failure policy
+    Failed { map_id: TaskId, error: String, cancelled: Vec<TaskId> },
+}
+
+impl TaskDAG {
+    /// Add a map node that runs `template` once per item found in
+    /// `source`'s output. `source` must already be in the DAG.
+    pub fn add_map_task(&mut self, template: Task, source: TaskId, spec: MapSpec) -> Result<TaskId> {
+        if !self.task_index.contains_key(&source) {
+            return Err(ApexError::task_not_found(source.0));
+        }
+        let map_id = self.insert_task(template)?;
+        self.add_dependency(source, map_id)?;
+        self.map_nodes.insert(map_id, MapNode::new(source, spec));
+        Ok(map_id)
+    }
+
+    /// Whether `task_id` is a map node (which never runs itself).
+    pub fn is_map_task(&self, task_id: TaskId) -> bool {
+        self.map_nodes.contains_key(&task_id)
+    }
+
+    /// Expand map nodes whose source has completed and join those whose
+    /// instances have all completed.
+    ///
+    /// Call before [`TaskDAG::get_ready_tasks`]; new instances are ready
+    /// right away. A node that can't be expanded fails under `policy`.
+    pub fn advance_map_nodes(&mut self, policy: FailurePolicy) -> Vec<MapUpdate> {
+        let mut due: Vec<TaskId> = self
+            .map_nodes
+            .keys()
+            .filter(|id| self.readiness(self.task_index[*id]) == Some(true))
+            .copied()
+            .collect();
+        due.sort_by_key(|id| self.task_index[id]);
+
+        let mut updates = Vec::new();
+        for map_id in due {
+            // Cancelled as a dependent of a map node that failed earlier in this pass
+            if self.get_task(map_id).map(|t| &t.status) != Some(&TaskStatus::Pending) {
+                continue;
+            }
+
+            let update = match self.map_nodes[&map_id].instances.clone() {
+                Some(instances) => {
+                    self.join_map(map_id, &instances);
+                    MapUpdate::Joined { map_id }
+                }
+                None => match self.expand_map(map_id) {
+                    Ok(instances) => MapUpdate::Expanded { map_id, instances },
+                    Err(error) => {
+                        let cancelled = self.fail_task(map_id, error.as_str(), policy).unwrap_or_default();
+                        MapUpdate::Failed { map_id, error, cancelled }
+                    }
+                },
+            };
+            updates.push(update);
+        }
+        updates
+    }
+
+    fn expand_map(&mut self, map_id: TaskId) -> std::result::Result<Vec<TaskId>, String> {
+        let MapNode { source, spec, .. } = self.map_nodes[&map_id].clone();
+
+        let items = self
+            .get_task(source)
+            .and_then(|task| task.output.as_ref())
+            .and_then(|output| serde_json::to_value(output).ok())
+            .and_then(|output| output.pointer(&json_pointer(&spec.items)).cloned());
+        let items = match items {
+            Some(serde_json::Value::Array(items)) => items,
+            Some(_) => return Err(format!("'{}' in the source output is not an array", spec.items)),
+            None => return Err(format!("'{}' not found in the source output", spec.items)),
+        };
+        if items.len() > spec.max_fan_out {
+            return Err(format!(
+                "{} items exceed the maximum fan-out of {}",
+                items.len(),
+                spec.max_fan_out
+            ));
+        }
+
+        let template = self.get_task(map_id).cloned().ok_or("map task missing")?;
+        let mut instances = Vec::with_capacity(items.len());
+        let mut expanded: Vec<(serde_json::Value, TaskId)> = Vec::new();
+        for (index, item) in items.into_iter().enumerate() {
+            // Repeated items share the instance of their first occurrence
+            if self.dedup {
+                if let Some((_, id)) = expanded.iter().find(|(seen, _)| *seen == item) {
+                    instances.push(*id);
+                    continue;
+                }
+            }
+            let mut input = template.input.clone();
+            let mut context = match input.context.take() {
+                serde_json::Value::Object(map) => map,
+                serde_json::Value::Null => serde_json::Map::new(),
+                other => serde_json::Map::from_iter([("context".to_string(), other)]),
+            };
+            let seen = self.dedup.then(|| item.clone());
+            context.insert("item".to_string(), item);
+            context.insert("item_index".to_string(), index.into());
+            input.context = serde_json::Value::Object(context);
+
+            let mut task = Task::new(format!("{}[{}]", template.name, index), input);
+            task.parent_id = Some(map_id);
+            task.priority = template.priority;
+            task.max_retries = template.max_retries;
+            task.input_bindings = template.input_bindings.clone();
+
+            let id = self.insert_task(task).map_err(|e| e.to_string())?;
+            self.add_dependency(source, id).map_err(|e| e.to_string())?;
+            self.add_dependency(id, map_id).map_err(|e| e.to_string())?;
+            instances.push(id);
+            if let Some(item) = seen {
+                expanded.push((item, id));
+            }
+        }
+
+        if let Some(node) = self.map_nodes.get_mut(&map_id) {
+            node.instances = Some(instances.clone());
+        }
+        Ok(instances)
+    }
+
+    fn join_map(&mut self, map_id: TaskId, instances: &[TaskId]) {
+        let outputs: Vec<TaskOutput> = instances
+            .iter()
+            .map(|id| self.get_task(*id).and_then(|t| t.output.clone()).unwrap_or_default())
+            .collect();
+        let output = TaskOutput {
+            result: outputs.iter().map(|o| o.result.as_str()).collect::<Vec<_>>().join("\n"),
+            data: serde_json::to_value(&outputs).unwrap_or_default(),
+            ..Default::default()
+        };
+        // Spend is counted on the instances
+        if let Some(task) = self.get_task_mut(map_id) {
+            task.complete(output, 0, 0.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::TaskInput;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    fn finish(dag: &mut TaskDAG, id: TaskId, data: serde_json::Value) {
+        let task = dag.get_task_mut(id).unwrap();
+        task.start(Uuid::new_v4());
+        let output = TaskOutput { result: task.name.clone(), data, ..Default::default() };
+        task.complete(output, 10, 0.01);
+    }
+
+    fn fan_out(max_fan_out: usize) -> (TaskDAG, [TaskId; 3]) {
+        let mut dag = TaskDAG::new("fan-out");
+        let list = dag.add_task(Task::new("list", TaskInput::default())).unwrap();
+        let template = Task::new("read", TaskInput { context: json!({ "lang": "en" }), ..Default::default() });
+        let read = dag
+            .add_map_task(template, list, MapSpec::new("$.data.urls").with_max_fan_out(max_fan_out))
+            .unwrap();
+        let merge = dag.add_task(Task::new("merge", TaskInput::default())).unwrap();
+        dag.add_dependency(read, merge).unwrap();
+        (dag, [list, read, merge])
+    }
+
+    #[test]
+    fn test_map_expands_and_joins() {
+        let (mut dag, [list, read, merge]) = fan_out(10);
+        assert!(dag.advance_map_nodes(FailurePolicy::CancelDependents).is_empty());
+        assert_eq!(dag.get_ready_tasks(), vec![list]);
+
+        finish(&mut dag, list, json!({ "urls": ["a", "b", "c"] }));
+        let updates = dag.advance_map_nodes(FailurePolicy::CancelDependents);
+        let [MapUpdate::Expanded { map_id, instances }] = updates.as_slice() else {
+            panic!("expected one expansion, got {:?}", updates);
+        };
+        assert_eq!(*map_id, read);
+        assert_eq!(instances.len(), 3);
+
+        // The instances run in parallel; the map node itself never does
+        let mut ready = dag.get_ready_tasks();
+        ready.sort_by_key(|id| id.0);
+        let mut expected = instances.clone();
+        expected.sort_by_key(|id| id.0);
+        assert_eq!(ready, expected);
+
+        let second = dag.get_task(instances[1]).unwrap();
+        assert_eq!(second.name, "read[1]");
+        assert_eq!(second.parent_id, Some(read));
+        assert_eq!(second.input.context, json!({ "lang": "en", "item": "b", "item_index": 1 }));
+
+        for (i, id) in instances.iter().enumerate() {
+            finish(&mut dag, *id, json!({ "n": i }));
+        }
+        assert_eq!(
+            dag.advance_map_nodes(FailurePolicy::CancelDependents),
+            vec![MapUpdate::Joined { map_id: read }]
+        );
+
+        let output = dag.get_task(read).unwrap().output.clone().unwrap();
+        assert_eq!(output.data[2]["data"], json!({ "n": 2 }));
+        assert_eq!(dag.get_ready_tasks(), vec![merge]);
+    }
+
+    #[test]
+    fn test_fan_out_limit_fails_map_node() {
+        let (mut dag, [list, read, merge]) = fan_out(2);
+        finish(&mut dag, list, json!({ "urls": ["a", "b", "c"] }));
+
+        let updates = dag.advance_map_nodes(FailurePolicy::CancelDependents);
+        let [MapUpdate::Failed { map_id, error, cancelled }] = updates.as_slice() else {
+            panic!("expected a failure, got {:?}", updates);
+        };
+        assert_eq!(*map_id, read);
+        assert!(error.contains("exceed the maximum fan-out of 2"), "{}", error);
+        assert_eq!(cancelled, &vec![merge]);
+        assert_eq!(dag.stats().total, 3);
+    }
+
+    #[test]
+    fn test_empty_list_joins_immediately() {
+        let (mut dag, [list, read, merge]) = fan_out(10);
+        finish(&mut dag, list, json!({ "urls": [] }));
+
+        let updates = dag.advance_map_nodes(FailurePolicy::CancelDependents);
+        assert_eq!(updates, vec![MapUpdate::Expanded { map_id: read, instances: vec![] }]);
+        assert_eq!(
+            dag.advance_map_nodes(FailurePolicy::CancelDependents),
+            vec![MapUpdate::Joined { map_id: read }]
+        );
+        assert_eq!(dag.get_ready_tasks(), vec![merge]);
+    }
+}
@@ -7,25 +7,54 @@
 //! - Failure handling and cascading cancellation
 
 mod task;
+mod binding;
+mod condition;
+mod dedup;
 mod executor;
+mod limits;
+mod map;
 mod scheduler;
-
-pub use task::{Task, TaskId, TaskStatus, TaskInput, TaskOutput, Artifact};
-pub use executor::DagExecutor;
+mod spec;
+mod template;
+
+pub use task::{Task, TaskError, TaskId, TaskStatus, TaskInput, TaskOutput, Artifact};
+pub use binding::InputBinding;
+pub use condition::EdgeCondition;
+pub use limits::DagLimits;
+pub use map::{MapSpec, MapUpdate, DEFAULT_MAX_FAN_OUT};
+pub use executor::{
+    DagExecutionSummary, DagExecutor, ExecutionEvent, ExecutionMode, ExecutorConfig, TaskResult,
+};
 pub use scheduler::TaskScheduler;
+pub use spec::{DagSpec, DependencySpec, TaskSpec};
+pub use template::{DagTemplate, TemplateParams};
 
 use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::visit::EdgeRef;
 use petgraph::algo::{toposort, is_cyclic_directed};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
 use crate::error::{ApexError, Result};
 
+/// What happens to downstream tasks when a task fails for good.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Cancel every pending task that transitively depends on the failed one.
+    #[default]
+    CancelDependents,
+    /// Leave dependents pending (they can never become ready) and keep running
+    /// unrelated branches. Nothing is cancelled, so the DAG can be resumed.
+    ContinueIndependent,
+}
+
 /// A Directed Acyclic Graph of tasks with dependencies.
 #[derive(Debug, Clone)]
 pub struct TaskDAG {
-    /// The underlying graph structure
-    graph: DiGraph<Task, ()>,
+    /// The underlying graph structure; edges may carry a condition
+    graph: DiGraph<Task, Option<EdgeCondition>>,
 
     /// Map from TaskId to graph node index for O(1) lookup
     task_index: HashMap<TaskId, NodeIndex>,
@@ -38,6 +67,21 @@ pub struct TaskDAG {
 
     /// Creation timestamp
     created_at: chrono::DateTime<chrono::Utc>,
+
+    /// Per-DAG override of the orchestrator's failure policy
+    failure_policy: Option<FailurePolicy>,
+
+    /// Share of the worker pool relative to other DAGs under fair scheduling
+    weight: u32,
+
+    /// Map nodes, which expand into instances at runtime instead of running
+    map_nodes: HashMap<TaskId, map::MapNode>,
+
+    /// Whether tasks with identical content are collapsed as they are added
+    dedup: bool,
+
+    /// Content hash of each task that a duplicate can be collapsed into
+    content_index: HashMap<u64, TaskId>,
 }
 
 impl TaskDAG {
@@ -49,11 +93,72 @@ impl TaskDAG {
             id: Uuid::new_v4(),
             name: name.into(),
             created_at: chrono::Utc::now(),
+            failure_policy: None,
+            weight: 1,
+            map_nodes: HashMap::new(),
+            dedup: false,
+            content_index: HashMap::new(),
         }
     }
 
+    /// Override the orchestrator's failure policy for this DAG.
+    pub fn with_failure_policy(mut self, policy: FailurePolicy) -> Self {
+        self.failure_policy = Some(policy);
+        self
+    }
+
+    /// Set this DAG's share of the worker pool under fair scheduling
+    /// (default 1; 0 is treated as 1).
+    pub fn with_weight(mut self, weight: u32) -> Self {
+        self.weight = weight.max(1);
+        self
+    }
+
     /// Add a task to the DAG.
+    ///
+    /// With dedup on, a task identical to one already in the DAG isn't
+    /// added; the existing task's id is returned instead.
     pub fn add_task(&mut self, task: Task) -> Result<TaskId> {
+        if !self.dedup {
+            return self.insert_task(task);
+        }
+        if self.task_index.contains_key(&task.id) {
+            return Err(ApexError::task_already_exists(task.id.0));
+        }
+        if let Some(existing) = self.find_duplicate(&task, &[]) {
+            return Ok(existing);
+        }
+        let task_id = self.insert_task(task)?;
+        self.index_content(task_id, &[]);
+        Ok(task_id)
+    }
+
+    /// Add a task along with the edges into it, each from a task already in
+    /// the DAG and with an optional condition.
+    ///
+    /// With dedup on, a task identical to one already added with the same
+    /// dependencies isn't added; the existing task's id is returned instead.
+    pub fn add_task_after(&mut self, task: Task, dependencies: &[(TaskId, Option<EdgeCondition>)]) -> Result<TaskId> {
+        if self.dedup {
+            if self.task_index.contains_key(&task.id) {
+                return Err(ApexError::task_already_exists(task.id.0));
+            }
+            if let Some(existing) = self.find_duplicate(&task, dependencies) {
+                return Ok(existing);
+            }
+        }
+        let task_id = self.insert_task(task)?;
+        for (from, condition) in dependencies {
+            self.insert_edge(*from, task_id, condition.clone())?;
+        }
+        if self.dedup {
+            self.index_content(task_id, dependencies);
+        }
+        Ok(task_id)
+    }
+
+    /// Add a task without looking for a duplicate of it.
+    fn insert_task(&mut self, task: Task) -> Result<TaskId> {
         let task_id = task.id;
 
         if self.task_index.contains_key(&task_id) {
@@ -68,12 +173,27 @@ impl TaskDAG {
 
     /// Add a dependency: `from` must complete before `to` can start.
     pub fn add_dependency(&mut self, from: TaskId, to: TaskId) -> Result<()> {
+        self.insert_edge(from, to, None)
+    }
+
+    /// Add a dependency that also requires `from`'s output to satisfy
+    /// `condition`; otherwise `to` is skipped (see [`Self::skip_unsatisfied`]).
+    pub fn add_conditional_dependency(&mut self, from: TaskId, to: TaskId, condition: EdgeCondition) -> Result<()> {
+        self.insert_edge(from, to, Some(condition))
+    }
+
+    fn insert_edge(&mut self, from: TaskId, to: TaskId, condition: Option<EdgeCondition>) -> Result<()> {
         let from_idx = self.task_index.get(&from)
             .ok_or_else(|| ApexError::task_not_found(from.0))?;
         let to_idx = self.task_index.get(&to)
             .ok_or_else(|| ApexError::task_not_found(to.0))?;
 
-        self.graph.add_edge(*from_idx, *to_idx, ());
+        // Already there, e.g. added again for a collapsed duplicate
+        if self.graph.edges_connecting(*from_idx, *to_idx).any(|edge| *edge.weight() == condition) {
+            return Ok(());
+        }
+
+        self.graph.add_edge(*from_idx, *to_idx, condition);
 
         // Check for cycles after adding edge
         if is_cyclic_directed(&self.graph) {
@@ -104,29 +224,93 @@ impl TaskDAG {
             })
     }
 
-    /// Get all tasks that are ready to execute (all dependencies completed).
+    /// Every task in topological order, with its place in the graph.
+    pub fn nodes(&self) -> Result<Vec<DagNode<'_>>> {
+        let order = toposort(&self.graph, None).map_err(|cycle| {
+            let task = &self.graph[cycle.node_id()];
+            ApexError::cycle_detected(format!("Cycle involving task: {:?}", task.id))
+        })?;
+
+        let mut depths: HashMap<NodeIndex, usize> = HashMap::with_capacity(order.len());
+        let nodes = order
+            .into_iter()
+            .map(|idx| {
+                let dependencies: Vec<NodeIndex> = self.graph
+                    .neighbors_directed(idx, petgraph::Direction::Incoming)
+                    .collect();
+                let depth = dependencies.iter().filter_map(|dep| depths.get(dep)).max().map_or(0, |d| d + 1);
+                depths.insert(idx, depth);
+                DagNode {
+                    task: &self.graph[idx],
+                    dependencies: dependencies.into_iter().map(|dep| self.graph[dep].id).collect(),
+                    dependents: self.graph
+                        .neighbors_directed(idx, petgraph::Direction::Outgoing)
+                        .map(|dep| self.graph[dep].id)
+                        .collect(),
+                    depth,
+                }
+            })
+            .collect();
+        Ok(nodes)
+    }
+
+    /// Get all tasks that are ready to execute (all dependencies completed
+    /// and every incoming condition met). Map nodes are never returned; see
+    /// [`Self::advance_map_nodes`].
     pub fn get_ready_tasks(&self) -> Vec<TaskId> {
         self.task_index
             .iter()
-            .filter(|(_, &node_idx)| {
-                let task = &self.graph[node_idx];
-
-                // Task must be pending
-                if task.status != TaskStatus::Pending {
-                    return false;
-                }
-
-                // All predecessors must be completed
-                self.graph
-                    .neighbors_directed(node_idx, petgraph::Direction::Incoming)
-                    .all(|pred_idx| {
-                        self.graph[pred_idx].status == TaskStatus::Completed
-                    })
-            })
+            .filter(|(task_id, _)| !self.map_nodes.contains_key(task_id))
+            .filter(|(_, &node_idx)| self.readiness(node_idx) == Some(true))
             .map(|(task_id, _)| *task_id)
             .collect()
     }
 
+    /// Skip tasks whose predecessors have all completed but whose incoming
+    /// conditions are not met: they and their pending dependents are cancelled.
+    ///
+    /// Returns the tasks that were cancelled.
+    pub fn skip_unsatisfied(&mut self) -> Vec<TaskId> {
+        let unsatisfied: Vec<TaskId> = self.task_index
+            .iter()
+            .filter(|(_, &node_idx)| self.readiness(node_idx) == Some(false))
+            .map(|(task_id, _)| *task_id)
+            .collect();
+
+        let mut skipped = Vec::new();
+        for task_id in unsatisfied {
+            let task = &mut self.graph[self.task_index[&task_id]];
+            // Already cancelled as a dependent of an earlier skip
+            if task.status != TaskStatus::Pending {
+                continue;
+            }
+            task.status = TaskStatus::Cancelled;
+            skipped.push(task_id);
+            skipped.extend(self.cancel_dependents(task_id).unwrap_or_default());
+        }
+        skipped
+    }
+
+    /// For a pending task whose predecessors have all completed, whether its
+    /// incoming conditions hold. `None` while it is not pending or still waiting.
+    fn readiness(&self, node_idx: NodeIndex) -> Option<bool> {
+        if self.graph[node_idx].status != TaskStatus::Pending {
+            return None;
+        }
+
+        let mut satisfied = true;
+        for edge in self.graph.edges_directed(node_idx, petgraph::Direction::Incoming) {
+            let pred = &self.graph[edge.source()];
+            if pred.status != TaskStatus::Completed {
+                return None;
+            }
+            if let Some(condition) = edge.weight() {
+                satisfied &= condition.evaluate(pred.output.as_ref());
+            }
+        }
+        Some(satisfied)
+    }
+
     /// Check if all tasks are completed.
     pub fn is_complete(&self) -> bool {
         self.graph.node_weights().all(|task| {
@@ -186,31 +370,81 @@ impl TaskDAG {
         Ok(cancelled)
     }
 
+    /// Cancel every task that has not started yet.
+    pub fn cancel_remaining(&mut self) -> Vec<TaskId> {
+        self.graph.node_weights_mut()
+            .filter(|task| matches!(task.status, TaskStatus::Pending | TaskStatus::Ready))
+            .map(|task| {
+                task.status = TaskStatus::Cancelled;
+                task.id
+            })
+            .collect()
+    }
+
+    /// Mark a task as failed and apply `policy` to its dependents.
+    ///
+    /// Returns the tasks that were cancelled as a result.
+    pub fn fail_task(&mut self, task_id: TaskId, error: impl Into<TaskError>, policy: FailurePolicy) -> Result<Vec<TaskId>> {
+        let task = self.get_task_mut(task_id)
+            .ok_or_else(|| ApexError::task_not_found(task_id.0))?;
+        if !task.status.is_terminal() {
+            task.fail(error);
+        }
+
+        match policy {
+            FailurePolicy::CancelDependents => self.cancel_dependents(task_id),
+            FailurePolicy::ContinueIndependent => Ok(Vec::new()),
+        }
+    }
+
     /// Get statistics about the DAG.
     pub fn stats(&self) -> DagStats {
         let mut stats = DagStats::default();
 
         for task in self.graph.node_weights() {
-            stats.total += 1;
-            match task.status {
-                TaskStatus::Pending => stats.pending += 1,
-                TaskStatus::Ready => stats.ready += 1,
-                TaskStatus::Running => stats.running += 1,
-                TaskStatus::Completed => stats.completed += 1,
-                TaskStatus::Failed => stats.failed += 1,
-                TaskStatus::Cancelled => stats.cancelled += 1,
-            }
+            stats.record(&task.status);
         }
 
         stats
     }
 
+    /// Snapshot of execution progress: counts, spend so far and running tasks.
+    pub fn progress(&self) -> DagProgress {
+        let mut tokens_used = 0;
+        let mut cost_dollars = 0.0;
+        let mut running_task_ids = Vec::new();
+
+        for task in self.graph.node_weights() {
+            tokens_used += task.tokens_used;
+            cost_dollars += task.cost_dollars;
+            if task.status == TaskStatus::Running {
+                running_task_ids.push(task.id);
+            }
+        }
+
+        DagProgress::new(self.stats(), tokens_used, cost_dollars, running_task_ids)
+    }
+
     pub fn id(&self) -> Uuid { self.id }
     pub fn name(&self) -> &str { &self.name }
     pub fn created_at(&self) -> chrono::DateTime<chrono::Utc> { self.created_at }
+    pub fn failure_policy(&self) -> Option<FailurePolicy> { self.failure_policy }
+    pub fn weight(&self) -> u32 { self.weight }
 }
 
-#[derive(Debug, Default, Clone)]
+/// A task of a DAG with its edges, from [`TaskDAG::nodes`].
+#[derive(Debug, Clone)]
+pub struct DagNode<'a> {
+    pub task: &'a Task,
+    /// Tasks this one waits for
+    pub dependencies: Vec<TaskId>,
+    /// Tasks waiting for this one
+    pub dependents: Vec<TaskId>,
+    /// Longest chain of dependencies leading here (0 for entry tasks)
+    pub depth: usize,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
 pub struct DagStats {
     pub total: usize,
     pub pending: usize,
@@ -221,6 +455,52 @@ pub struct DagStats {
     pub cancelled: usize,
 }
 
+impl DagStats {
+    /// Count one task in the given status.
+    pub fn record(&mut self, status: &TaskStatus) {
+        self.total += 1;
+        match status {
+            TaskStatus::Pending => self.pending += 1,
+            TaskStatus::Ready => self.ready += 1,
+            TaskStatus::Running => self.running += 1,
+            TaskStatus::Completed => self.completed += 1,
+            TaskStatus::Failed => self.failed += 1,
+            TaskStatus::Cancelled => self.cancelled += 1,
+        }
+    }
+
+    /// Percentage of tasks in a terminal state. An empty DAG is 100% complete.
+    pub fn percent_complete(&self) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        let finished = self.completed + self.failed + self.cancelled;
+        finished as f64 * 100.0 / self.total as f64
+    }
+}
+
+/// Progress of a DAG for status reporting.
+#[derive(Debug, Clone, Serialize)]
+pub struct DagProgress {
+    pub stats: DagStats,
+    pub percent_complete: f64,
+    pub tokens_used: u64,
+    pub cost_dollars: f64,
+    pub running_task_ids: Vec<TaskId>,
+}
+
+impl DagProgress {
+    pub fn new(stats: DagStats, tokens_used: u64, cost_dollars: f64, running_task_ids: Vec<TaskId>) -> Self {
+        Self {
+            percent_complete: stats.percent_complete(),
+            stats,
+            tokens_used,
+            cost_dollars,
+            running_task_ids,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -253,6 +533,136 @@ mod tests {
         assert_eq!(result.unwrap_err().code(), crate::error::ErrorCode::DagCycleDetected);
     }
 
+    #[test]
+    fn test_progress_reports_running_tasks_and_spend() {
+        let mut dag = TaskDAG::new("test-dag");
+
+        let id_a = dag.add_task(Task::new("Task A", TaskInput::default())).unwrap();
+        let id_b = dag.add_task(Task::new("Task B", TaskInput::default())).unwrap();
+        let id_c = dag.add_task(Task::new("Task C", TaskInput::default())).unwrap();
+        dag.add_task(Task::new("Task D", TaskInput::default())).unwrap();
+
+        dag.get_task_mut(id_a).unwrap().start(Uuid::new_v4());
+        dag.get_task_mut(id_a).unwrap().complete(TaskOutput::default(), 100, 0.25);
+        dag.get_task_mut(id_b).unwrap().start(Uuid::new_v4());
+        dag.get_task_mut(id_c).unwrap().status = TaskStatus::Cancelled;
+
+        let progress = dag.progress();
+        assert_eq!(progress.stats.total, 4);
+        assert_eq!(progress.stats.running, 1);
+        assert_eq!(progress.stats.pending, 1);
+        assert_eq!(progress.percent_complete, 50.0);
+        assert_eq!(progress.tokens_used, 100);
+        assert_eq!(progress.cost_dollars, 0.25);
+        assert_eq!(progress.running_task_ids, vec![id_b]);
+    }
+
+    /// A -> {B -> D, C} -> E, with E also depending on D.
+    fn diamond() -> (TaskDAG, [TaskId; 5]) {
+        let mut dag = TaskDAG::new("diamond");
+        let ids = ["A", "B", "C", "D", "E"]
+            .map(|name| dag.add_task(Task::new(name, TaskInput::default())).unwrap());
+        let [a, b, c, d, e] = ids;
+        dag.add_dependency(a, b).unwrap();
+        dag.add_dependency(a, c).unwrap();
+        dag.add_dependency(b, d).unwrap();
+        dag.add_dependency(d, e).unwrap();
+        dag.add_dependency(c, e).unwrap();
+        (dag, ids)
+    }
+
+    fn run(dag: &mut TaskDAG, id: TaskId) {
+        let task = dag.get_task_mut(id).unwrap();
+        task.start(Uuid::new_v4());
+        task.complete(TaskOutput::default(), 0, 0.0);
+    }
+
+    #[test]
+    fn test_failure_cancels_transitive_dependents_only() {
+        let (mut dag, [a, b, c, d, e]) = diamond();
+        run(&mut dag, a);
+        dag.get_task_mut(b).unwrap().start(Uuid::new_v4());
+
+        let mut cancelled = dag.fail_task(b, "boom", FailurePolicy::CancelDependents).unwrap();
+        cancelled.sort_by_key(|id| id.0);
+        let mut expected = vec![d, e];
+        expected.sort_by_key(|id| id.0);
+        assert_eq!(cancelled, expected);
+
+        // The independent branch still runs and the DAG finishes
+        assert_eq!(dag.get_ready_tasks(), vec![c]);
+        run(&mut dag, c);
+        assert!(dag.is_complete());
+
+        let stats = dag.stats();
+        assert_eq!((stats.completed, stats.failed, stats.cancelled), (2, 1, 2));
+    }
+
+    #[test]
+    fn test_cancel_remaining_skips_finished_tasks() {
+        let (mut dag, [a, b, ..]) = diamond();
+        run(&mut dag, a);
+        dag.fail_task(b, "boom", FailurePolicy::ContinueIndependent).unwrap();
+
+        assert_eq!(dag.cancel_remaining().len(), 3);
+        assert!(dag.is_complete());
+        assert_eq!(dag.get_task(a).unwrap().status, TaskStatus::Completed);
+        assert_eq!(dag.get_task(b).unwrap().status, TaskStatus::Failed);
+    }
+
+    #[test]
+    fn test_continue_independent_leaves_dependents_pending() {
+        let (mut dag, [a, b, c, d, _]) = diamond();
+        run(&mut dag, a);
+
+        assert!(dag.fail_task(b, "boom", FailurePolicy::ContinueIndependent).unwrap().is_empty());
+        assert_eq!(dag.get_task(d).unwrap().status, TaskStatus::Pending);
+
+        run(&mut dag, c);
+        assert!(dag.get_ready_tasks().is_empty());
+        assert!(!dag.is_complete());
+    }
+
+    #[test]
+    fn test_conditional_edges_skip_unmet_branch() {
+        let mut dag = TaskDAG::new("triage");
+        let [classify, escalate, notify, archive] = ["classify", "escalate", "notify", "archive"]
+            .map(|name| dag.add_task(Task::new(name, TaskInput::default())).unwrap());
+        dag.add_conditional_dependency(classify, escalate, EdgeCondition::new("$.data.urgent", true)).unwrap();
+        dag.add_dependency(escalate, notify).unwrap();
+        dag.add_conditional_dependency(classify, archive, EdgeCondition::new("$.data.urgent", false)).unwrap();
+
+        // Conditions are not evaluated until the predecessor completes
+        assert!(dag.skip_unsatisfied().is_empty());
+        assert_eq!(dag.get_ready_tasks(), vec![classify]);
+
+        let task = dag.get_task_mut(classify).unwrap();
+        task.start(Uuid::new_v4());
+        let output = TaskOutput { data: serde_json::json!({ "urgent": false }), ..Default::default() };
+        task.complete(output, 0, 0.0);
+
+        // Only the branch whose condition holds is ready
+        assert_eq!(dag.get_ready_tasks(), vec![archive]);
+
+        // The other branch and everything below it is skipped
+        let mut skipped = dag.skip_unsatisfied();
+        skipped.sort_by_key(|id| id.0);
+        let mut expected = vec![escalate, notify];
+        expected.sort_by_key(|id| id.0);
+        assert_eq!(skipped, expected);
+        assert_eq!(dag.get_task(escalate).unwrap().status, TaskStatus::Cancelled);
+
+        run(&mut dag, archive);
+        assert!(dag.is_complete());
+        let stats = dag.stats();
+        assert_eq!((stats.completed, stats.cancelled), (2, 2));
+    }
+
+    #[test]
+    fn test_empty_dag_is_fully_complete() {
+        assert_eq!(TaskDAG::new("empty").progress().percent_complete, 100.0);
+    }
+
     #[test]
     fn test_topological_order() {
         let mut dag = TaskDAG::new("test-dag");
@@ -278,4 +688,26 @@ mod tests {
         assert!(pos_a < pos_b);
         assert!(pos_b < pos_c);
     }
+
+    #[test]
+    fn test_nodes_carry_edges_and_depth() {
+        let mut dag = TaskDAG::new("test-dag");
+        let a = dag.add_task(Task::new("a", TaskInput::default())).unwrap();
+        let b = dag.add_task(Task::new("b", TaskInput::default())).unwrap();
+        let c = dag.add_task(Task::new("c", TaskInput::default())).unwrap();
+        dag.add_dependency(a, b).unwrap();
+        dag.add_dependency(a, c).unwrap();
+        dag.add_dependency(b, c).unwrap();
+
+        let nodes = dag.nodes().unwrap();
+        let ids: Vec<TaskId> = nodes.iter().map(|n| n.task.id).collect();
+        assert_eq!(ids, vec![a, b, c]);
+        assert_eq!((nodes[0].depth, nodes[1].depth, nodes[2].depth), (0, 1, 2));
+        assert!(nodes[0].dependencies.is_empty());
+        assert_eq!(nodes[0].dependents.len(), 2);
+        let mut deps = nodes[2].dependencies.clone();
+        deps.sort_by_key(|id| ids.iter().position(|i| i == id));
+        assert_eq!(deps, vec![a, b]);
+        assert!(nodes[2].dependents.is_empty());
+    }
 }
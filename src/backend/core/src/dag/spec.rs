@@ -0,0 +1,477 @@
+//! Declarative DAG definitions.
+//!
+//! A [`DagSpec`] is the serializable form of a workflow: tasks keyed by a
+//! caller-chosen id plus the edges between them. It is what `POST /api/v1/dags`
+//! accepts and what workflow files (`workflow.yaml`) deserialize into.
+//!
+//! ```yaml
+//! name: research-pipeline
+//! tasks:
+//!   - id: fetch
+//!     name: Fetch sources
+//!     instruction: Collect recent papers on the topic
+//!   - id: summarize
+//!     name: Summarize
+//!     instruction: Summarize the collected papers
+//!     depends_on: [fetch]
+//! ```
+//!
+//! Entries in `dependencies` may carry a `condition` on the predecessor's
+//! output (see [`EdgeCondition`]); the target is skipped when it isn't met.
+//! A task with a `map` section fans out over its predecessor's output at
+//! runtime (see [`MapSpec`]), and one with `inputs` gets fields of its
+//! predecessors' outputs substituted into its instruction (see
+//! [`InputBinding`]). A `budget` caps the combined spend of every task in
+//! the DAG.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+
+use super::map::MapNode;
+use super::{EdgeCondition, FailurePolicy, InputBinding, MapSpec, Task, TaskDAG, TaskInput};
+use crate::contracts::ResourceLimits;
+use crate::error::{ApexError, Result};
+
+/// A workflow definition that [`TaskDAG::from_spec`] turns into a DAG.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagSpec {
+    pub name: String,
+
+    /// Overrides the orchestrator's failure policy for this DAG
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_policy: Option<FailurePolicy>,
+
+    /// Share of the worker pool relative to other DAGs under fair scheduling
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u32>,
+
+    /// Run tasks with identical instruction, context, parameters, inputs
+    /// and dependencies once (see [`TaskDAG::with_dedup`])
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dedup: bool,
+
+    /// Ceiling on the DAG's total spend, passed to
+    /// [`SwarmOrchestrator::submit_dag`](crate::orchestrator::SwarmOrchestrator::submit_dag)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<ResourceLimits>,
+
+    pub tasks: Vec<TaskSpec>,
+
+    /// Edges in addition to each task's `depends_on`
+    #[serde(default)]
+    pub dependencies: Vec<DependencySpec>,
+}
+
+/// One task of a [`DagSpec`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSpec {
+    /// Identifier used to reference this task within the spec
+    pub id: String,
+    pub name: String,
+    pub instruction: String,
+    #[serde(default)]
+    pub context: serde_json::Value,
+    #[serde(default)]
+    pub parameters: serde_json::Value,
+    #[serde(default)]
+    pub priority: i32,
+    /// Ids of tasks that must complete before this one starts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Run once per item of an array in the output of the single task in
+    /// `depends_on`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub map: Option<MapSpec>,
+    /// Placeholders in the instruction and context filled from a
+    /// predecessor's output at dispatch time (see [`InputBinding`]); `task`
+    /// is the predecessor's spec id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub inputs: BTreeMap<String, InputBinding>,
+    /// Limits for this task in place of the orchestrator's default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ResourceLimits>,
+}
+
+/// `from` must complete before `to` can start.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DependencySpec {
+    pub from: String,
+    pub to: String,
+    /// Only run `to` if `from`'s output satisfies this; otherwise skip it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<EdgeCondition>,
+}
+
+impl DagSpec {
+    /// Every edge as `(from, to)`: the explicit `dependencies` followed by
+    /// each task's `depends_on`.
+    pub fn edges(&self) -> Vec<(&str, &str)> {
+        let explicit = self.dependencies.iter().map(|d| (d.from.as_str(), d.to.as_str()));
+        let implicit = self
+            .tasks
+            .iter()
+            .flat_map(|t| t.depends_on.iter().map(move |from| (from.as_str(), t.id.as_str())));
+        explicit.chain(implicit).collect()
+    }
+
+    /// Check task ids and edges without building the DAG.
+    ///
+    /// Errors name the offending task: duplicate ids and unknown references
+    /// are validation errors, and a cycle reports the path that closes it.
+    pub fn validate(&self) -> Result<()> {
+        let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+        for task in &self.tasks {
+            if adjacency.insert(task.id.as_str(), Vec::new()).is_some() {
+                return Err(ApexError::validation(format!("Duplicate task id '{}'", task.id)));
+            }
+        }
+
+        for task in self.tasks.iter().filter(|t| t.map.is_some()) {
+            let explicit = self.dependencies.iter().any(|d| d.to == task.id);
+            if task.depends_on.len() != 1 || explicit {
+                return Err(ApexError::validation(format!(
+                    "Map task '{}' must depend on exactly one task through depends_on",
+                    task.id
+                )));
+            }
+        }
+
+        for task in &self.tasks {
+            if let Some(binding) = task.inputs.values().find(|b| !adjacency.contains_key(b.task.as_str())) {
+                return Err(ApexError::validation(format!(
+                    "Task '{}' binds an input to unknown task '{}'",
+                    task.id, binding.task
+                )));
+            }
+        }
+
+        for (from, to) in self.edges() {
+            if !adjacency.contains_key(from) {
+                return Err(ApexError::validation(format!(
+                    "Task '{}' depends on unknown task '{}'",
+                    to, from
+                )));
+            }
+            if !adjacency.contains_key(to) {
+                return Err(ApexError::validation(format!(
+                    "Dependency from '{}' references unknown task '{}'",
+                    from, to
+                )));
+            }
+            adjacency.get_mut(from).unwrap().push(to);
+        }
+
+        // Depth-first search in spec order; a back edge closes a cycle.
+        let mut state: HashMap<&str, Visit> = HashMap::new();
+        for task in &self.tasks {
+            if let Some(cycle) = find_cycle(task.id.as_str(), &adjacency, &mut state) {
+                return Err(ApexError::cycle_detected(format!(
+                    "task '{}' depends on itself via {}",
+                    cycle[0],
+                    cycle.join(" -> ")
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Tasks ordered so each comes after everything it depends on, and
+    /// otherwise in spec order. Assumes the spec is valid.
+    fn dependency_order(&self) -> Vec<&TaskSpec> {
+        let position: HashMap<&str, usize> = self.tasks.iter().enumerate().map(|(i, t)| (t.id.as_str(), i)).collect();
+        let mut blocking = vec![0usize; self.tasks.len()];
+        let mut unblocks: Vec<Vec<usize>> = vec![Vec::new(); self.tasks.len()];
+        for (from, to) in self.edges() {
+            blocking[position[to]] += 1;
+            unblocks[position[from]].push(position[to]);
+        }
+
+        let mut ready: BinaryHeap<Reverse<usize>> =
+            (0..self.tasks.len()).filter(|&i| blocking[i] == 0).map(Reverse).collect();
+        let mut order = Vec::with_capacity(self.tasks.len());
+        while let Some(Reverse(next)) = ready.pop() {
+            order.push(&self.tasks[next]);
+            for &to in &unblocks[next] {
+                blocking[to] -= 1;
+                if blocking[to] == 0 {
+                    ready.push(Reverse(to));
+                }
+            }
+        }
+        order
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visit {
+    InProgress,
+    Done,
+}
+
+/// Depth-first search from `start`, with an explicit stack so a long chain
+/// of tasks can't overflow the thread's stack.
+fn find_cycle<'a>(
+    start: &'a str,
+    adjacency: &HashMap<&'a str, Vec<&'a str>>,
+    state: &mut HashMap<&'a str, Visit>,
+) -> Option<Vec<&'a str>> {
+    if state.contains_key(start) {
+        return None;
+    }
+    state.insert(start, Visit::InProgress);
+    // The current path, with the index of each node's next dependent
+    let mut path: Vec<(&'a str, usize)> = vec![(start, 0)];
+    while let Some(top) = path.last_mut() {
+        let node = top.0;
+        let next = adjacency[node].get(top.1).copied();
+        top.1 += 1;
+        let Some(next) = next else {
+            state.insert(node, Visit::Done);
+            path.pop();
+            continue;
+        };
+        match state.get(next) {
+            Some(Visit::Done) => {}
+            Some(Visit::InProgress) => {
+                let start = path.iter().position(|(n, _)| *n == next).unwrap_or(0);
+                let mut cycle: Vec<&str> = path[start..].iter().map(|(n, _)| *n).collect();
+                cycle.push(next);
+                return Some(cycle);
+            }
+            None => {
+                state.insert(next, Visit::InProgress);
+                path.push((next, 0));
+            }
+        }
+    }
+    None
+}
+
+impl TaskDAG {
+    /// Build a DAG from a validated [`DagSpec`].
+    pub fn from_spec(spec: &DagSpec) -> Result<Self> {
+        spec.validate()?;
+
+        let mut dag = TaskDAG::new(&spec.name);
+        if let Some(policy) = spec.failure_policy {
+            dag = dag.with_failure_policy(policy);
+        }
+        if let Some(weight) = spec.weight {
+            dag = dag.with_weight(weight);
+        }
+        dag = dag.with_dedup(spec.dedup);
+
+        // Tasks are added after their predecessors, with the edges into
+        // them, so dedup compares dependencies and bindings can name the
+        // task a predecessor collapsed into
+        let names: HashMap<&str, &str> = spec.tasks.iter().map(|t| (t.id.as_str(), t.name.as_str())).collect();
+        let mut ids = HashMap::new();
+        for task_spec in spec.dependency_order() {
+            let explicit = spec
+                .dependencies
+                .iter()
+                .filter(|dep| dep.to == task_spec.id)
+                .map(|dep| (ids[dep.from.as_str()], dep.condition.clone()));
+            let implicit = task_spec.depends_on.iter().map(|from| (ids[from.as_str()], None));
+            let dependencies: Vec<_> = explicit.chain(implicit).collect();
+
+            let input = TaskInput {
+                instruction: task_spec.instruction.clone(),
+                context: task_spec.context.clone(),
+                parameters: task_spec.parameters.clone(),
+                artifacts: vec![],
+            };
+            let mut task = Task::new(&task_spec.name, input);
+            task.priority = task_spec.priority;
+            task.limits = task_spec.limits.clone();
+            for (placeholder, binding) in &task_spec.inputs {
+                // Bindings name the predecessor by spec id; tasks only know names
+                let source = ids
+                    .get(binding.task.as_str())
+                    .and_then(|id| dag.get_task(*id))
+                    .map_or(names[binding.task.as_str()], |source| source.name.as_str());
+                task.input_bindings.insert(placeholder.clone(), InputBinding::new(source, &binding.pointer));
+            }
+
+            let id = match &task_spec.map {
+                // A map node is a template, never a duplicate of another task
+                Some(map) => {
+                    let id = dag.insert_task(task)?;
+                    let source = dependencies[0].0;
+                    dag.add_dependency(source, id)?;
+                    dag.map_nodes.insert(id, MapNode::new(source, map.clone()));
+                    id
+                }
+                None => dag.add_task_after(task, &dependencies)?,
+            };
+            ids.insert(task_spec.id.as_str(), id);
+        }
+        dag.validate_input_bindings()?;
+
+        Ok(dag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+
+    fn spec(json: &str) -> DagSpec {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_from_spec_builds_dag() {
+        let spec = spec(r#"{
+            "name": "pipeline",
+            "weight": 3,
+            "tasks": [
+                { "id": "a", "name": "A", "instruction": "first" },
+                { "id": "b", "name": "B", "instruction": "second", "depends_on": ["a"] },
+                { "id": "c", "name": "C", "instruction": "third", "priority": 5 }
+            ],
+            "dependencies": [{ "from": "b", "to": "c" }]
+        }"#);
+
+        let dag = TaskDAG::from_spec(&spec).unwrap();
+        assert_eq!(dag.name(), "pipeline");
+        assert_eq!(dag.weight(), 3);
+        let order: Vec<String> = dag
+            .topological_order()
+            .unwrap()
+            .into_iter()
+            .map(|id| dag.get_task(id).unwrap().name.clone())
+            .collect();
+        assert_eq!(order, vec!["A", "B", "C"]);
+    }
+
+    #[test]
+    fn test_cycle_points_at_offending_task() {
+        let spec = spec(r#"{
+            "name": "loop",
+            "tasks": [
+                { "id": "start", "name": "S", "instruction": "s" },
+                { "id": "a", "name": "A", "instruction": "a", "depends_on": ["start", "b"] },
+                { "id": "b", "name": "B", "instruction": "b", "depends_on": ["a"] }
+            ]
+        }"#);
+
+        let err = spec.validate().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DagCycleDetected);
+        assert!(err.to_string().contains("'a' depends on itself via a -> b -> a"), "{}", err);
+    }
+
+    #[test]
+    fn test_long_chain_validates_without_recursion() {
+        let tasks: Vec<serde_json::Value> = (0..100_000)
+            .map(|i| {
+                let depends_on: Vec<String> = if i == 0 { vec![] } else { vec![format!("t{}", i - 1)] };
+                serde_json::json!({ "id": format!("t{}", i), "name": "T", "instruction": "x", "depends_on": depends_on })
+            })
+            .collect();
+        let mut spec: DagSpec = serde_json::from_value(serde_json::json!({ "name": "chain", "tasks": tasks })).unwrap();
+        assert!(spec.validate().is_ok());
+
+        // Closing the chain into a loop is still caught
+        spec.tasks[0].depends_on.push("t99999".to_string());
+        assert_eq!(spec.validate().unwrap_err().code(), ErrorCode::DagCycleDetected);
+    }
+
+    #[test]
+    fn test_input_bindings_from_spec() {
+        let spec = spec(r#"{
+            "name": "pipeline",
+            "tasks": [
+                { "id": "research", "name": "Research", "instruction": "find" },
+                { "id": "write", "name": "Write", "instruction": "use {{summary}}", "depends_on": ["research"],
+                  "inputs": { "summary": { "task": "research", "pointer": "/data/summary" } } }
+            ]
+        }"#);
+        let dag = TaskDAG::from_spec(&spec).unwrap();
+        let write = dag.graph.node_weights().find(|t| t.name == "Write").unwrap();
+        // Spec ids become task names
+        assert_eq!(write.input_bindings["summary"], InputBinding::new("Research", "/data/summary"));
+
+        let mut unknown = spec.clone();
+        unknown.tasks[1].inputs.get_mut("summary").unwrap().task = "nope".to_string();
+        let err = unknown.validate().unwrap_err();
+        assert!(err.to_string().contains("'write' binds an input to unknown task 'nope'"), "{}", err);
+
+        // Research no longer precedes Write
+        let mut unrelated = spec;
+        unrelated.tasks[1].depends_on.clear();
+        let err = TaskDAG::from_spec(&unrelated).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::DagValidationFailed);
+    }
+
+    #[test]
+    fn test_dedup_from_spec_compares_dependencies() {
+        let spec = spec(r#"{
+            "name": "fan-out",
+            "dedup": true,
+            "tasks": [
+                { "id": "report", "name": "Report", "instruction": "report on {{a}}", "depends_on": ["summary-a2"],
+                  "inputs": { "a": { "task": "summary-a2", "pointer": "/result" } } },
+                { "id": "summary-a", "name": "Summary A", "instruction": "summarize", "depends_on": ["fetch-a"] },
+                { "id": "summary-a2", "name": "Summary A again", "instruction": "summarize", "depends_on": ["fetch-a"] },
+                { "id": "summary-b", "name": "Summary B", "instruction": "summarize", "depends_on": ["fetch-b"] },
+                { "id": "fetch-a", "name": "Fetch A", "instruction": "fetch", "context": { "source": "a" } },
+                { "id": "fetch-b", "name": "Fetch B", "instruction": "fetch", "context": { "source": "b" } }
+            ]
+        }"#);
+        let dag = TaskDAG::from_spec(&spec).unwrap();
+
+        // The summaries of A collapse; the one of B has another predecessor
+        let mut names: Vec<_> = dag.graph.node_weights().map(|t| t.name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Fetch A", "Fetch B", "Report", "Summary A", "Summary B"]);
+
+        // The binding follows the collapse to the task that runs
+        let report = dag.graph.node_weights().find(|t| t.name == "Report").unwrap();
+        assert_eq!(report.input_bindings["a"], InputBinding::new("Summary A", "/result"));
+    }
+
+    #[test]
+    fn test_unknown_and_duplicate_ids() {
+        let unknown = spec(r#"{
+            "name": "x",
+            "tasks": [{ "id": "a", "name": "A", "instruction": "a", "depends_on": ["missing"] }]
+        }"#);
+        let err = unknown.validate().unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ValidationError);
+        assert!(err.to_string().contains("'a' depends on unknown task 'missing'"));
+
+        let duplicate = spec(r#"{
+            "name": "x",
+            "tasks": [
+                { "id": "a", "name": "A", "instruction": "a" },
+                { "id": "a", "name": "A2", "instruction": "a" }
+            ]
+        }"#);
+        assert!(duplicate.validate().unwrap_err().to_string().contains("Duplicate task id 'a'"));
+    }
+
+    #[test]
+    fn test_map_task_needs_single_source() {
+        let mapped = spec(r#"{
+            "name": "x",
+            "tasks": [
+                { "id": "list", "name": "L", "instruction": "l" },
+                { "id": "each", "name": "E", "instruction": "e", "depends_on": ["list"],
+                  "map": { "items": "$.data.items" } }
+            ]
+        }"#);
+        let dag = TaskDAG::from_spec(&mapped).unwrap();
+        assert_eq!(dag.map_nodes.len(), 1);
+
+        let orphan = spec(r#"{
+            "name": "x",
+            "tasks": [{ "id": "each", "name": "E", "instruction": "e", "map": { "items": "$.data" } }]
+        }"#);
+        let err = orphan.validate().unwrap_err();
+        assert!(err.to_string().contains("Map task 'each' must depend on exactly one task"), "{}", err);
+    }
+}
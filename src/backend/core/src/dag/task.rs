@@ -1,9 +1,15 @@
 //! Task definitions and state management.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
+use super::InputBinding;
+use crate::contracts::ResourceLimits;
+
+use crate::error::{ApexError, ErrorCode};
+
 /// Unique identifier for a task.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct TaskId(pub Uuid);
@@ -65,6 +71,23 @@ impl TaskStatus {
     }
 }
 
+impl std::str::FromStr for TaskStatus {
+    type Err = String;
+
+    /// Parse the snake_case form stored in the database.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(TaskStatus::Pending),
+            "ready" => Ok(TaskStatus::Ready),
+            "running" => Ok(TaskStatus::Running),
+            "completed" => Ok(TaskStatus::Completed),
+            "failed" => Ok(TaskStatus::Failed),
+            "cancelled" => Ok(TaskStatus::Cancelled),
+            other => Err(format!("unknown task status: {}", other)),
+        }
+    }
+}
+
 /// Input data for a task.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TaskInput {
@@ -84,6 +107,120 @@ pub struct TaskInput {
     pub artifacts: Vec<Artifact>,
 }
 
+impl TaskInput {
+    /// Latency target hint, read from the `latency_slo_ms` parameter.
+    pub fn latency_slo(&self) -> Option<std::time::Duration> {
+        self.parameters.get("latency_slo_ms")?
+            .as_u64()
+            .map(std::time::Duration::from_millis)
+    }
+
+    /// The prompt sent to the model: the instruction, followed by the context
+    /// if there is any.
+    pub fn prompt(&self) -> String {
+        if self.context.is_null() {
+            self.instruction.clone()
+        } else {
+            format!("{}\n{}", self.instruction, self.context)
+        }
+    }
+}
+
+/// Why a task failed, as stored in `tasks.error_details`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskError {
+    /// `None` for failures only known as a message, e.g. from a worker
+    pub code: Option<ErrorCode>,
+    /// Message safe to show to users
+    pub message: String,
+    /// Detail for operators; not shown to users
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub internal: Option<String>,
+    /// Retries made before this failure
+    #[serde(default)]
+    pub retry_count: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub failed_at: DateTime<Utc>,
+}
+
+impl TaskError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code: Some(code),
+            ..Self::from(message.into())
+        }
+    }
+
+    pub fn with_agent(mut self, agent_id: Uuid) -> Self {
+        self.agent_id = Some(agent_id);
+        self
+    }
+
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// The code as stored in `tasks.error_code`, e.g. `AGENT_TIMEOUT`.
+    pub fn code_name(&self) -> Option<String> {
+        let code = serde_json::to_value(self.code?).ok()?;
+        code.as_str().map(str::to_string)
+    }
+}
+
+/// Same shape as [`ApexError`]'s display, so `Task::error` reads as before.
+impl std::fmt::Display for TaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(code) = self.code {
+            write!(f, "[{}] ", code)?;
+        }
+        write!(f, "{}", self.message)?;
+        if let Some(ref internal) = self.internal {
+            write!(f, " (internal: {})", internal)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<String> for TaskError {
+    fn from(message: String) -> Self {
+        Self {
+            code: None,
+            message,
+            internal: None,
+            retry_count: 0,
+            agent_id: None,
+            model: None,
+            failed_at: Utc::now(),
+        }
+    }
+}
+
+impl From<&str> for TaskError {
+    fn from(message: &str) -> Self {
+        Self::from(message.to_string())
+    }
+}
+
+impl From<&String> for TaskError {
+    fn from(message: &String) -> Self {
+        Self::from(message.clone())
+    }
+}
+
+impl From<&ApexError> for TaskError {
+    fn from(err: &ApexError) -> Self {
+        Self {
+            code: Some(err.code()),
+            internal: err.internal_message().map(str::to_string),
+            ..Self::from(err.user_message())
+        }
+    }
+}
+
 /// Output data from a completed task.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct TaskOutput {
@@ -134,12 +271,20 @@ pub struct Task {
     /// Input data
     pub input: TaskInput,
 
+    /// Placeholders in the input filled from predecessors' outputs at
+    /// dispatch time, keyed by placeholder name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub input_bindings: BTreeMap<String, InputBinding>,
+
     /// Output data (populated on completion)
     pub output: Option<TaskOutput>,
 
     /// Error message (populated on failure)
     pub error: Option<String>,
 
+    /// Structured cause of the failure, for grouping failures by code
+    pub error_details: Option<TaskError>,
+
     /// ID of the agent assigned to this task
     pub agent_id: Option<Uuid>,
 
@@ -170,6 +315,15 @@ pub struct Task {
     /// Distributed tracing context
     pub trace_id: Option<String>,
     pub span_id: Option<String>,
+
+    /// Resource limits for this task, overriding the orchestrator default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<ResourceLimits>,
+
+    /// Notes recorded while running the task, for later analysis (e.g.
+    /// `cnp_award`, how its agent won the task)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_json::Value>,
 }
 
 impl Task {
@@ -182,8 +336,10 @@ impl Task {
             status: TaskStatus::Pending,
             priority: 0,
             input,
+            input_bindings: BTreeMap::new(),
             output: None,
             error: None,
+            error_details: None,
             agent_id: None,
             contract_id: None,
             retry_count: 0,
@@ -195,9 +351,23 @@ impl Task {
             completed_at: None,
             trace_id: None,
             span_id: None,
+            limits: None,
+            metadata: BTreeMap::new(),
         }
     }
 
+    /// Fill `{{placeholder}}` from a predecessor's output at dispatch time.
+    pub fn with_input_binding(mut self, placeholder: impl Into<String>, binding: InputBinding) -> Self {
+        self.input_bindings.insert(placeholder.into(), binding);
+        self
+    }
+
+    /// Run this task under `limits` instead of the orchestrator's default.
+    pub fn with_limits(mut self, limits: ResourceLimits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
     /// Create a subtask of this task.
     pub fn create_subtask(&self, name: impl Into<String>, input: TaskInput) -> Self {
         let mut subtask = Self::new(name, input);
@@ -217,15 +387,26 @@ impl Task {
     pub fn complete(&mut self, output: TaskOutput, tokens: u64, cost: f64) {
         self.status = TaskStatus::Completed;
         self.output = Some(output);
+        self.error = None;
+        self.error_details = None;
         self.tokens_used = tokens;
         self.cost_dollars = cost;
         self.completed_at = Some(Utc::now());
     }
 
     /// Mark task as failed with error.
-    pub fn fail(&mut self, error: impl Into<String>) {
+    ///
+    /// Takes a message or a [`TaskError`] (e.g. from an [`ApexError`]); the
+    /// retry count and agent are filled in from the task. `error` keeps the
+    /// one-line message for older readers.
+    pub fn fail(&mut self, error: impl Into<TaskError>) {
+        let mut error = error.into();
+        error.retry_count = self.retry_count;
+        error.agent_id = error.agent_id.or(self.agent_id);
+
         self.status = TaskStatus::Failed;
-        self.error = Some(error.into());
+        self.error = Some(error.to_string());
+        self.error_details = Some(error);
         self.completed_at = Some(Utc::now());
     }
 
@@ -239,6 +420,7 @@ impl Task {
         self.retry_count += 1;
         self.status = TaskStatus::Pending;
         self.error = None;
+        self.error_details = None;
         self.started_at = None;
         self.completed_at = None;
     }
@@ -255,6 +437,40 @@ impl Task {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_fail_records_structured_error() {
+        let mut task = Task::new("Test Task", TaskInput::default());
+        let agent_id = Uuid::new_v4();
+        task.start(agent_id);
+        task.retry_count = 2;
+
+        let err = ApexError::new(ErrorCode::AgentTimeout, "Worker did not answer")
+            .with_internal_message("no result after 300s");
+        task.fail(TaskError::from(&err).with_model("gpt-4o"));
+
+        let details = task.error_details.clone().unwrap();
+        assert_eq!(details.code, Some(ErrorCode::AgentTimeout));
+        assert_eq!(details.code_name().as_deref(), Some("AGENT_TIMEOUT"));
+        assert_eq!(details.retry_count, 2);
+        assert_eq!(details.agent_id, Some(agent_id));
+        assert_eq!(details.model.as_deref(), Some("gpt-4o"));
+        // The plain message still reads like the error's display
+        assert_eq!(task.error.as_deref(), Some(err.to_string().as_str()));
+
+        task.prepare_retry();
+        assert!(task.error_details.is_none());
+    }
+
+    #[test]
+    fn test_fail_with_message() {
+        let mut task = Task::new("Test Task", TaskInput::default());
+        task.fail("boom");
+        assert_eq!(task.error.as_deref(), Some("boom"));
+        let details = task.error_details.unwrap();
+        assert_eq!(details.code, None);
+        assert_eq!(details.code_name(), None);
+    }
+
     #[test]
     fn test_task_lifecycle() {
         let mut task = Task::new("Test Task", TaskInput::default());
@@ -272,6 +488,22 @@ mod tests {
         assert_eq!(task.tokens_used, 100);
     }
 
+    #[test]
+    fn test_latency_slo_hint() {
+        let mut input = TaskInput::default();
+        assert_eq!(input.latency_slo(), None);
+
+        input.parameters = serde_json::json!({ "latency_slo_ms": 1500 });
+        assert_eq!(input.latency_slo(), Some(std::time::Duration::from_millis(1500)));
+    }
+
+    #[test]
+    fn test_status_from_str() {
+        assert_eq!("running".parse::<TaskStatus>(), Ok(TaskStatus::Running));
+        assert_eq!("cancelled".parse::<TaskStatus>(), Ok(TaskStatus::Cancelled));
+        assert!("done".parse::<TaskStatus>().is_err());
+    }
+
     #[test]
     fn test_retry_logic() {
         let mut task = Task::new("Test Task", TaskInput::default());
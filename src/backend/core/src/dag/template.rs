@@ -0,0 +1,291 @@
+//! Reusable, parameterized workflows.
+//!
+//! A [`DagTemplate`] is a [`DagSpec`] whose task names, instructions and
+//! context may contain `{{placeholders}}`. Instantiating it with a set of
+//! parameters substitutes every placeholder and builds a runnable
+//! [`TaskDAG`]:
+//!
+//! ```yaml
+//! name: research
+//! defaults:
+//!   depth: brief
+//! spec:
+//!   name: research-{{topic}}
+//!   tasks:
+//!     - id: fetch
+//!       name: Fetch sources
+//!       instruction: Collect recent papers on {{topic}}
+//!     - id: summarize
+//!       name: Summarize
+//!       instruction: Write a {{depth}} summary of the collected papers
+//!       depends_on: [fetch]
+//! ```
+//!
+//! Inside `context`, a string that is exactly one placeholder is replaced by
+//! the parameter value itself, so numbers and objects keep their type.
+//! Anywhere else the value is spliced into the surrounding text.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+use super::{DagSpec, TaskDAG};
+use crate::error::{ApexError, Result};
+
+/// Values for a template's placeholders, keyed by placeholder name.
+pub type TemplateParams = HashMap<String, serde_json::Value>;
+
+fn placeholder_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_.-]*)\s*\}\}").unwrap())
+}
+
+/// A workflow definition with `{{placeholders}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagTemplate {
+    pub name: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    pub spec: DagSpec,
+
+    /// Values used for placeholders the caller doesn't supply
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub defaults: TemplateParams,
+}
+
+impl DagTemplate {
+    /// Every placeholder used by the spec, sorted by name.
+    ///
+    /// A task's input bindings are filled at dispatch time, not from
+    /// parameters, so they don't count in its instruction and context.
+    pub fn placeholders(&self) -> BTreeSet<String> {
+        let mut names = BTreeSet::new();
+        let mut collect = |text: &str, bound: &dyn Fn(&str) -> bool| {
+            for capture in placeholder_pattern().captures_iter(text) {
+                if !bound(&capture[1]) {
+                    names.insert(capture[1].to_string());
+                }
+            }
+        };
+
+        collect(&self.spec.name, &|_| false);
+        for task in &self.spec.tasks {
+            let bound = |name: &str| task.inputs.contains_key(name);
+            collect(&task.name, &|_| false);
+            collect(&task.instruction, &bound);
+            visit_strings(&task.context, &mut |text| collect(text, &bound));
+        }
+        names
+    }
+
+    /// Placeholders without a default, which every instantiation must supply.
+    pub fn required_parameters(&self) -> Vec<String> {
+        self.placeholders()
+            .into_iter()
+            .filter(|name| !self.defaults.contains_key(name))
+            .collect()
+    }
+
+    /// Check the spec's structure and that every default names a placeholder.
+    ///
+    /// Placeholders are only substituted into names, instructions and
+    /// context, so task ids and edges can be checked before instantiation.
+    pub fn validate(&self) -> Result<()> {
+        self.spec.validate()?;
+
+        let placeholders = self.placeholders();
+        let mut unused: Vec<&str> = self
+            .defaults
+            .keys()
+            .filter(|name| !placeholders.contains(*name))
+            .map(String::as_str)
+            .collect();
+        if !unused.is_empty() {
+            unused.sort_unstable();
+            return Err(ApexError::validation(format!(
+                "Defaults given for unknown placeholders: {}",
+                unused.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Substitute `params` (falling back to `defaults`) into the spec.
+    ///
+    /// Fails if a required placeholder has no value or `params` names a
+    /// placeholder the template doesn't use.
+    pub fn render(&self, params: &TemplateParams) -> Result<DagSpec> {
+        let placeholders = self.placeholders();
+
+        let mut unknown: Vec<&str> = params
+            .keys()
+            .filter(|name| !placeholders.contains(*name))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            unknown.sort_unstable();
+            return Err(ApexError::validation(format!(
+                "Unknown template parameters: {}",
+                unknown.join(", ")
+            )));
+        }
+
+        let missing: Vec<&str> = placeholders
+            .iter()
+            .filter(|name| !params.contains_key(*name) && !self.defaults.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ApexError::validation(format!(
+                "Missing template parameters: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let lookup = |name: &str| params.get(name).or_else(|| self.defaults.get(name));
+
+        let mut spec = self.spec.clone();
+        spec.name = substitute(&spec.name, &lookup);
+        for task in &mut spec.tasks {
+            task.name = substitute(&task.name, &lookup);
+            let inputs = std::mem::take(&mut task.inputs);
+            let unbound = |name: &str| if inputs.contains_key(name) { None } else { lookup(name) };
+            task.instruction = substitute(&task.instruction, &unbound);
+            substitute_json(&mut task.context, &unbound);
+            task.inputs = inputs;
+        }
+        Ok(spec)
+    }
+
+    /// Render the template and build the DAG.
+    pub fn instantiate(&self, params: &TemplateParams) -> Result<TaskDAG> {
+        TaskDAG::from_spec(&self.render(params)?)
+    }
+}
+
+fn visit_strings(value: &serde_json::Value, f: &mut impl FnMut(&str)) {
+    match value {
+        serde_json::Value::String(s) => f(s),
+        serde_json::Value::Array(items) => items.iter().for_each(|v| visit_strings(v, f)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| visit_strings(v, f)),
+        _ => {}
+    }
+}
+
+/// Replace each placeholder in `text`; strings are inserted as-is and other
+/// values as JSON.
+pub(super) fn substitute<'a>(text: &str, lookup: &impl Fn(&str) -> Option<&'a serde_json::Value>) -> String {
+    placeholder_pattern()
+        .replace_all(text, |captures: &regex::Captures| match lookup(&captures[1]) {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+            None => captures[0].to_string(),
+        })
+        .into_owned()
+}
+
+pub(super) fn substitute_json<'a>(value: &mut serde_json::Value, lookup: &impl Fn(&str) -> Option<&'a serde_json::Value>) {
+    match value {
+        serde_json::Value::String(s) => {
+            let whole = placeholder_pattern()
+                .captures(s)
+                .filter(|captures| captures[0].len() == s.len())
+                .and_then(|captures| lookup(&captures[1]));
+            *value = match whole {
+                Some(replacement) => replacement.clone(),
+                None => serde_json::Value::String(substitute(s, lookup)),
+            };
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(|v| substitute_json(v, lookup)),
+        serde_json::Value::Object(map) => map.values_mut().for_each(|v| substitute_json(v, lookup)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+    use serde_json::json;
+
+    fn template() -> DagTemplate {
+        serde_json::from_value(json!({
+            "name": "research",
+            "defaults": { "depth": "brief" },
+            "spec": {
+                "name": "research-{{topic}}",
+                "tasks": [
+                    { "id": "fetch", "name": "Fetch", "instruction": "Collect papers on {{ topic }}",
+                      "context": { "max_results": "{{limit}}", "note": "limit {{limit}}" } },
+                    { "id": "summarize", "name": "Summarize", "instruction": "Write a {{depth}} summary",
+                      "depends_on": ["fetch"] }
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    fn params(value: serde_json::Value) -> TemplateParams {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_placeholders_and_required_parameters() {
+        let template = template();
+        assert_eq!(
+            template.placeholders().into_iter().collect::<Vec<_>>(),
+            vec!["depth", "limit", "topic"]
+        );
+        assert_eq!(template.required_parameters(), vec!["limit", "topic"]);
+        assert!(template.validate().is_ok());
+    }
+
+    #[test]
+    fn test_render_substitutes_values() {
+        let spec = template().render(&params(json!({ "topic": "rust", "limit": 5 }))).unwrap();
+
+        assert_eq!(spec.name, "research-rust");
+        assert_eq!(spec.tasks[0].instruction, "Collect papers on rust");
+        // A lone placeholder keeps the value's type
+        assert_eq!(spec.tasks[0].context, json!({ "max_results": 5, "note": "limit 5" }));
+        assert_eq!(spec.tasks[1].instruction, "Write a brief summary");
+
+        let dag = template()
+            .instantiate(&params(json!({ "topic": "rust", "limit": 5, "depth": "detailed" })))
+            .unwrap();
+        assert_eq!(dag.name(), "research-rust");
+        assert_eq!(dag.stats().total, 2);
+    }
+
+    #[test]
+    fn test_bound_placeholders_are_not_parameters() {
+        let mut template = template();
+        template.spec.tasks[1].instruction = "Write a {{depth}} summary of {{papers}}".to_string();
+        template.spec.tasks[1]
+            .inputs
+            .insert("papers".to_string(), crate::dag::InputBinding::new("fetch", "/result"));
+        assert!(!template.placeholders().contains("papers"));
+
+        let spec = template.render(&params(json!({ "topic": "rust", "limit": 5 }))).unwrap();
+        assert_eq!(spec.tasks[1].instruction, "Write a brief summary of {{papers}}");
+    }
+
+    #[test]
+    fn test_missing_and_unknown_parameters_are_rejected() {
+        let err = template().render(&params(json!({ "limit": 5 }))).unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ValidationError);
+        assert!(err.to_string().contains("Missing template parameters: topic"), "{}", err);
+
+        let err = template()
+            .render(&params(json!({ "topic": "rust", "limit": 5, "topc": "x" })))
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown template parameters: topc"), "{}", err);
+
+        let mut template = template();
+        template.defaults.insert("unused".into(), json!(1));
+        assert!(template.validate().unwrap_err().to_string().contains("unknown placeholders: unused"));
+    }
+}
@@ -58,6 +58,7 @@ pub enum ErrorCode {
     TaskAlreadyExists,
     InvalidStateTransition,
     DependencyNotMet,
+    TooManyActiveDags,
 
     // Contract Errors (1100-1199)
     TokenLimitExceeded,
@@ -67,6 +68,7 @@ pub enum ErrorCode {
     ContractViolation,
     ContractNotFound,
     ContractExpired,
+    QuotaExceeded,
 
     // Agent Errors (1200-1299)
     AgentNotFound,
@@ -75,6 +77,8 @@ pub enum ErrorCode {
     AgentTimeout,
     LoopDetected,
     AgentUnavailable,
+    NoAgentsRegistered,
+    AllAgentsBusy,
 
     // Tool Errors (1300-1399)
     ToolNotFound,
@@ -89,6 +93,7 @@ pub enum ErrorCode {
     DatabaseTransactionFailed,
     RecordNotFound,
     DuplicateRecord,
+    ConcurrencyConflict,
 
     // Cache Errors (2100-2199)
     CacheError,
@@ -120,6 +125,9 @@ pub enum ErrorCode {
     MissingRequiredField,
     InvalidFormat,
 
+    // Rate Limiting (4200-4299)
+    RateLimited,
+
     // Configuration Errors (5000-5099)
     ConfigurationError,
     MissingConfiguration,
@@ -142,6 +150,7 @@ impl ErrorCode {
             Self::TaskAlreadyExists => 1003,
             Self::InvalidStateTransition => 1004,
             Self::DependencyNotMet => 1005,
+            Self::TooManyActiveDags => 1006,
 
             // Contract Errors
             Self::TokenLimitExceeded => 1100,
@@ -151,6 +160,7 @@ impl ErrorCode {
             Self::ContractViolation => 1104,
             Self::ContractNotFound => 1105,
             Self::ContractExpired => 1106,
+            Self::QuotaExceeded => 1107,
 
             // Agent Errors
             Self::AgentNotFound => 1200,
@@ -159,6 +169,8 @@ impl ErrorCode {
             Self::AgentTimeout => 1203,
             Self::LoopDetected => 1204,
             Self::AgentUnavailable => 1205,
+            Self::NoAgentsRegistered => 1206,
+            Self::AllAgentsBusy => 1207,
 
             // Tool Errors
             Self::ToolNotFound => 1300,
@@ -173,6 +185,7 @@ impl ErrorCode {
             Self::DatabaseTransactionFailed => 2003,
             Self::RecordNotFound => 2004,
             Self::DuplicateRecord => 2005,
+            Self::ConcurrencyConflict => 2006,
 
             // Cache Errors
             Self::CacheError => 2100,
@@ -204,6 +217,9 @@ impl ErrorCode {
             Self::MissingRequiredField => 4102,
             Self::InvalidFormat => 4103,
 
+            // Rate Limiting
+            Self::RateLimited => 4200,
+
             // Configuration Errors
             Self::ConfigurationError => 5000,
             Self::MissingConfiguration => 5001,
@@ -230,6 +246,7 @@ impl ErrorCode {
             // Conflict (409)
             Self::TaskAlreadyExists
             | Self::DuplicateRecord
+            | Self::ConcurrencyConflict
             | Self::InvalidStateTransition => StatusCode::CONFLICT,
 
             // Unprocessable Entity (422)
@@ -247,10 +264,17 @@ impl ErrorCode {
             | Self::CostLimitExceeded
             | Self::ApiCallLimitExceeded
             | Self::ContractViolation
-            | Self::ContractExpired => StatusCode::PAYMENT_REQUIRED,
+            | Self::ContractExpired
+            | Self::QuotaExceeded => StatusCode::PAYMENT_REQUIRED,
 
             // Too Many Requests (429)
-            Self::LlmRateLimited | Self::AgentOverloaded => StatusCode::TOO_MANY_REQUESTS,
+            Self::RateLimited
+            | Self::LlmRateLimited
+            | Self::AgentOverloaded
+            | Self::AllAgentsBusy
+            | Self::TooManyActiveDags => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
 
             // Timeout (408/504)
             Self::TimeLimitExceeded
@@ -271,6 +295,7 @@ impl ErrorCode {
             | Self::CacheConnectionFailed
             | Self::LlmUnavailable
             | Self::AgentUnavailable
+            | Self::NoAgentsRegistered
             | Self::ExternalServiceError => StatusCode::SERVICE_UNAVAILABLE,
 
             // Bad Gateway (502)
@@ -308,10 +333,13 @@ impl ErrorCode {
                 | Self::DatabaseQueryFailed
                 | Self::CacheConnectionFailed
                 | Self::CacheError
+                | Self::RateLimited
                 | Self::LlmRateLimited
                 | Self::LlmTimeout
                 | Self::LlmUnavailable
                 | Self::AgentOverloaded
+                | Self::AllAgentsBusy
+                | Self::TooManyActiveDags
                 | Self::AgentTimeout
                 | Self::ToolTimeout
                 | Self::NetworkError
@@ -332,6 +360,7 @@ impl ErrorCode {
             3000..=3099 => "external_service",
             4000..=4099 => "authentication",
             4100..=4199 => "validation",
+            4200..=4299 => "rate_limit",
             5000..=5099 => "configuration",
             9000..=9099 => "internal",
             _ => "unknown",
@@ -382,6 +411,7 @@ impl ErrorSeverity {
             | ErrorCode::DependencyNotMet
             | ErrorCode::TaskAlreadyExists
             | ErrorCode::DuplicateRecord
+            | ErrorCode::ConcurrencyConflict
             | ErrorCode::InvalidStateTransition
             | ErrorCode::ToolValidationFailed => Self::Low,
 
@@ -392,8 +422,12 @@ impl ErrorSeverity {
             | ErrorCode::ApiCallLimitExceeded
             | ErrorCode::ContractViolation
             | ErrorCode::ContractExpired
+            | ErrorCode::QuotaExceeded
+            | ErrorCode::RateLimited
             | ErrorCode::LlmRateLimited
             | ErrorCode::AgentOverloaded
+            | ErrorCode::AllAgentsBusy
+            | ErrorCode::TooManyActiveDags
             | ErrorCode::AgentTimeout
             | ErrorCode::ToolTimeout
             | ErrorCode::LlmTimeout
@@ -422,6 +456,7 @@ impl ErrorSeverity {
             | ErrorCode::TokenExpired
             | ErrorCode::DagCycleDetected
             | ErrorCode::AgentUnavailable
+            | ErrorCode::NoAgentsRegistered
             | ErrorCode::LlmUnavailable => Self::High,
 
             // Critical severity
@@ -668,6 +703,12 @@ impl ApexError {
         &self.details
     }
 
+    /// Messages per field path, for errors converted from validation errors.
+    pub fn field_errors(&self) -> Option<HashMap<String, Vec<String>>> {
+        let errors = self.details.context.get("field_errors")?;
+        serde_json::from_value(errors.clone()).ok()
+    }
+
     /// Get the HTTP status code.
     pub fn http_status(&self) -> StatusCode {
         self.code.http_status()
@@ -695,6 +736,8 @@ impl ApexError {
             ErrorCode::TimeLimitExceeded => "TIME_LIMIT",
             ErrorCode::ApiCallLimitExceeded => "API_LIMIT",
             ErrorCode::ContractViolation => "CONTRACT_VIOLATION",
+            ErrorCode::QuotaExceeded => "QUOTA_EXCEEDED",
+            ErrorCode::ConcurrencyConflict => "CONCURRENCY_CONFLICT",
             ErrorCode::AgentNotFound => "AGENT_NOT_FOUND",
             ErrorCode::AgentOverloaded => "AGENT_OVERLOADED",
             ErrorCode::AgentExecutionFailed => "AGENT_FAILED",
@@ -706,7 +749,7 @@ impl ApexError {
             ErrorCode::CacheError | ErrorCode::CacheConnectionFailed => "REDIS_ERROR",
             ErrorCode::SerializationError | ErrorCode::DeserializationError | ErrorCode::InvalidJson => "SERIALIZATION_ERROR",
             ErrorCode::LlmApiError => "LLM_ERROR",
-            ErrorCode::LlmRateLimited => "RATE_LIMITED",
+            ErrorCode::LlmRateLimited | ErrorCode::RateLimited => "RATE_LIMITED",
             ErrorCode::ConfigurationError | ErrorCode::MissingConfiguration | ErrorCode::InvalidConfiguration => "CONFIG_ERROR",
             ErrorCode::InternalError => "INTERNAL_ERROR",
             _ => "UNKNOWN_ERROR",
@@ -796,6 +839,10 @@ pub struct ErrorResponse {
 
     /// Error information
     pub error: ErrorInfo,
+
+    /// Messages per field path (e.g. `tasks[0].name`) of a validation failure
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub errors: Option<HashMap<String, Vec<String>>>,
 }
 
 /// Detailed error information for API responses.
@@ -841,6 +888,7 @@ impl From<&ApexError> for ErrorResponse {
                 request_id: None, // Set by middleware
                 timestamp: chrono::Utc::now(),
             },
+            errors: error.field_errors(),
         }
     }
 }
@@ -1159,6 +1207,24 @@ impl ApexError {
         .with_context("to_state", format!("{:?}", to))
     }
 
+    /// Create an error for a task whose DAG was cancelled before it ran.
+    pub fn task_cancelled(task_id: uuid::Uuid) -> Self {
+        Self::new(
+            ErrorCode::InvalidStateTransition,
+            format!("Task {} was cancelled", task_id),
+        )
+        .with_details(ErrorDetails::new().with_entity("task", task_id.to_string()))
+    }
+
+    /// Create an error for a task that cannot be requeued.
+    pub fn task_not_requeueable(task_id: uuid::Uuid, reason: impl Into<String>) -> Self {
+        Self::new(
+            ErrorCode::InvalidStateTransition,
+            format!("Task {} cannot be requeued: {}", task_id, reason.into()),
+        )
+        .with_details(ErrorDetails::new().with_entity("task", task_id.to_string()))
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Contract Errors
     // ─────────────────────────────────────────────────────────────────────────
@@ -1187,6 +1253,24 @@ impl ApexError {
         .with_context("limit", limit)
     }
 
+    /// An organization's monthly quota for `resource` is used up.
+    pub fn quota_exceeded(organization_id: impl Into<String>, resource: &str, used: f64, limit: f64) -> Self {
+        let organization_id = organization_id.into();
+        Self::new(
+            ErrorCode::QuotaExceeded,
+            format!("Monthly {} quota exceeded: used {}, limit {}", resource, used, limit),
+        )
+        .with_context("organization_id", &organization_id)
+        .with_context("resource", resource)
+        .with_context("used", used)
+        .with_context("limit", limit)
+        .with_details(
+            ErrorDetails::new()
+                .with_entity("organization", organization_id)
+                .with_suggestion("Wait for the next billing month or ask an administrator to raise the quota"),
+        )
+    }
+
     /// Create a time limit exceeded error.
     pub fn time_limit_exceeded(elapsed_secs: u64, limit_secs: u64) -> Self {
         Self::new(
@@ -1247,6 +1331,47 @@ impl ApexError {
         .with_details(ErrorDetails::new().with_retry_after(5))
     }
 
+    /// No agents are registered, so a task can never be assigned.
+    pub fn no_agents_registered() -> Self {
+        Self::new(
+            ErrorCode::NoAgentsRegistered,
+            "No agents are registered; register one with POST /api/v1/agents before running tasks",
+        )
+    }
+
+    /// Every agent stayed at capacity for the whole wait.
+    pub fn all_agents_busy(agents: usize, waited_secs: u64) -> Self {
+        Self::new(
+            ErrorCode::AllAgentsBusy,
+            format!("All {} agents stayed busy for {}s", agents, waited_secs),
+        )
+        .with_context("agents", agents)
+        .with_context("waited_secs", waited_secs)
+        .with_details(ErrorDetails::new().with_retry_after(5))
+    }
+
+    /// A submitted DAG exceeds one of its size limits.
+    pub fn dag_too_large(what: &str, actual: usize, max: usize) -> Self {
+        Self::new(
+            ErrorCode::DagValidationFailed,
+            format!("DAG {} is {}, over the limit of {}", what, actual, max),
+        )
+        .with_context("limit", what)
+        .with_context("actual", actual)
+        .with_context("max", max)
+    }
+
+    /// The orchestrator is already running its maximum number of DAGs.
+    pub fn too_many_active_dags(active: usize, max: usize) -> Self {
+        Self::new(
+            ErrorCode::TooManyActiveDags,
+            format!("{} DAGs are already running (max {}); retry once one finishes", active, max),
+        )
+        .with_context("active_dags", active)
+        .with_context("max_concurrent_dags", max)
+        .with_details(ErrorDetails::new().with_retry_after(5))
+    }
+
     /// Create an agent execution failed error.
     pub fn agent_execution_failed(reason: impl Into<String>) -> Self {
         Self::new(
@@ -1328,6 +1453,34 @@ impl ApexError {
         .with_details(ErrorDetails::new().with_retry_after(retry_after_secs))
     }
 
+    // ─────────────────────────────────────────────────────────────────────────
+    // Database Errors
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Create an optimistic concurrency conflict: another writer appended to
+    /// the aggregate's event stream after `expected_version` was read.
+    pub fn concurrency_conflict(
+        aggregate_type: impl Into<String>,
+        aggregate_id: impl std::fmt::Display,
+        expected_version: u64,
+    ) -> Self {
+        let aggregate_type = aggregate_type.into();
+        let aggregate_id = aggregate_id.to_string();
+        Self::new(
+            ErrorCode::ConcurrencyConflict,
+            format!(
+                "Concurrent write to {} {}: stream is no longer at version {}",
+                aggregate_type, aggregate_id, expected_version
+            ),
+        )
+        .with_context("expected_version", expected_version)
+        .with_details(
+            ErrorDetails::new()
+                .with_entity(&aggregate_type, aggregate_id)
+                .with_suggestion("Reload the aggregate and retry the command"),
+        )
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Configuration Errors
     // ─────────────────────────────────────────────────────────────────────────
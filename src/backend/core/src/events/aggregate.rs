@@ -4,7 +4,7 @@
 //! Each aggregate implements `Default` (empty state) and `apply` (fold an event).
 
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::agents::AgentId;
@@ -20,8 +20,13 @@ use super::event::DomainEvent;
 ///
 /// An aggregate starts at its `Default` state and folds each event via `apply`.
 /// This provides full temporal reconstruction -- given the same event stream,
-/// the resulting state is deterministic.
-pub trait Aggregate: Default {
+/// the resulting state is deterministic. State is serializable so it can be
+/// snapshotted, letting old events be pruned.
+pub trait Aggregate: Default + Serialize + DeserializeOwned {
+    /// The `aggregate_type` its events are stored under; the prefix of its
+    /// [`StreamId`](super::event::StreamId).
+    const AGGREGATE_TYPE: &'static str;
+
     /// Apply a single domain event to mutate state.
     ///
     /// Implementations must be pure functions of `(self, event) -> self'`.
@@ -29,6 +34,36 @@ pub trait Aggregate: Default {
     fn apply(&mut self, event: &DomainEvent);
 }
 
+/// Fold a stream's `(version, event)` pairs, in version order, into state.
+///
+/// Returns the state with the version of the last event, or `None` for an
+/// empty stream (the aggregate doesn't exist).
+pub fn rebuild<A: Aggregate>(events: impl IntoIterator<Item = (u64, DomainEvent)>) -> Option<(A, u64)> {
+    rebuild_from(None, events)
+}
+
+/// Like [`rebuild`], but starting from a snapshot of the state at a version.
+///
+/// Events at or below the snapshot's version are already folded into it and
+/// are skipped.
+pub fn rebuild_from<A: Aggregate>(
+    snapshot: Option<(A, u64)>,
+    events: impl IntoIterator<Item = (u64, DomainEvent)>,
+) -> Option<(A, u64)> {
+    let (mut aggregate, mut version) = match snapshot {
+        Some((state, version)) => (state, Some(version)),
+        None => (A::default(), None),
+    };
+    for (event_version, event) in events {
+        if version.is_some_and(|v| event_version <= v) {
+            continue;
+        }
+        aggregate.apply(&event);
+        version = Some(event_version);
+    }
+    version.map(|version| (aggregate, version))
+}
+
 // =============================================================================
 // Task Aggregate
 // =============================================================================
@@ -58,6 +93,8 @@ pub struct TaskAggregate {
 }
 
 impl Aggregate for TaskAggregate {
+    const AGGREGATE_TYPE: &'static str = "task";
+
     fn apply(&mut self, event: &DomainEvent) {
         self.version += 1;
 
@@ -136,6 +173,8 @@ pub struct AgentAggregate {
 }
 
 impl Aggregate for AgentAggregate {
+    const AGGREGATE_TYPE: &'static str = "agent";
+
     fn apply(&mut self, event: &DomainEvent) {
         self.version += 1;
 
@@ -197,6 +236,8 @@ pub struct DagAggregate {
 }
 
 impl Aggregate for DagAggregate {
+    const AGGREGATE_TYPE: &'static str = "dag";
+
     fn apply(&mut self, event: &DomainEvent) {
         self.version += 1;
 
@@ -418,4 +459,67 @@ mod tests {
         assert_eq!(agg.success, Some(true));
         assert_eq!(agg.total_tokens, 3000);
     }
+
+    #[test]
+    fn test_rebuild_from_versioned_stream() {
+        assert!(rebuild::<AgentAggregate>(Vec::new()).is_none());
+
+        let agent_id = AgentId::new();
+        let events = vec![
+            (1, DomainEvent::AgentCreated(AgentCreated {
+                agent_id,
+                name: "researcher".to_string(),
+                model: "gpt-4o".to_string(),
+                max_load: 4,
+            })),
+            (2, DomainEvent::AgentStatusChanged(AgentStatusChanged {
+                agent_id,
+                from_status: "idle".to_string(),
+                to_status: "busy".to_string(),
+            })),
+        ];
+
+        let (agent, version) = rebuild::<AgentAggregate>(events).unwrap();
+        assert_eq!(agent.agent_id, Some(agent_id));
+        assert_eq!(agent.status, "busy");
+        assert_eq!(version, 2);
+
+        // Stream IDs are prefixed with the type the aggregate loads by
+        assert!(StreamId::agent(agent_id).0.starts_with(AgentAggregate::AGGREGATE_TYPE));
+        assert!(StreamId::task(TaskId::new()).0.starts_with(TaskAggregate::AGGREGATE_TYPE));
+        assert!(StreamId::dag(Uuid::new_v4()).0.starts_with(DagAggregate::AGGREGATE_TYPE));
+    }
+
+    #[test]
+    fn test_rebuild_from_snapshot() {
+        let agent_id = AgentId::new();
+        let status = |from: &str, to: &str| DomainEvent::AgentStatusChanged(AgentStatusChanged {
+            agent_id,
+            from_status: from.to_string(),
+            to_status: to.to_string(),
+        });
+        let created = DomainEvent::AgentCreated(AgentCreated {
+            agent_id,
+            name: "researcher".to_string(),
+            model: "gpt-4o".to_string(),
+            max_load: 4,
+        });
+
+        let (snapshot, version) = rebuild::<AgentAggregate>(vec![(1, created), (2, status("idle", "busy"))]).unwrap();
+        let snapshot: AgentAggregate = serde_json::from_value(serde_json::to_value(&snapshot).unwrap()).unwrap();
+
+        // Events up to the snapshot were pruned except the snapshot's own
+        let (agent, version) = rebuild_from(
+            Some((snapshot.clone(), version)),
+            vec![(2, status("idle", "busy")), (3, status("busy", "idle"))],
+        ).unwrap();
+        assert_eq!(agent.name, "researcher");
+        assert_eq!(agent.status, "idle");
+        assert_eq!(version, 3);
+
+        // A snapshot with no later events is the current state
+        let (agent, version) = rebuild_from(Some((snapshot, 2)), Vec::new()).unwrap();
+        assert_eq!(agent.status, "busy");
+        assert_eq!(version, 2);
+    }
 }
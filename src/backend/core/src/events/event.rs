@@ -1019,40 +1019,61 @@ impl EventStore {
         Self { pool }
     }
 
-    /// Append a domain event to the immutable log.
+    /// Append a domain event to its aggregate's stream.
     ///
-    /// The event is serialized to JSONB and stored alongside trace/entity metadata.
-    /// Returns the generated `EventId`.
+    /// `expected_version` is the stream version the caller's decision was based
+    /// on (0 for a new aggregate); the event is stored as `expected_version + 1`.
+    /// If another writer has appended since, nothing is written and an
+    /// [`ErrorCode::ConcurrencyConflict`](crate::error::ErrorCode::ConcurrencyConflict)
+    /// error is returned, so the caller can reload the aggregate and retry.
     #[instrument(skip(self, event), fields(event_type = %event.event_type()))]
-    pub async fn append(&self, event: DomainEvent) -> Result<EventId, ApexError> {
+    pub async fn append(&self, event: DomainEvent, expected_version: u64) -> Result<EventId, ApexError> {
         let event_id = EventId::new();
         let event_type = event.event_type().to_string();
         let stream_id = event.stream_id();
 
-        // Derive entity_type and entity_id from the stream_id format "type-uuid".
-        let (entity_type, entity_id) = parse_stream_id(&stream_id.0);
+        // Derive aggregate_type and aggregate_id from the stream_id format "type-uuid".
+        let (aggregate_type, aggregate_id) = parse_stream_id(&stream_id.0);
+        let expected = i64::try_from(expected_version).unwrap_or(i64::MAX);
 
         let payload = serde_json::to_value(&event)?;
         let metadata = serde_json::json!({
             "stream_id": stream_id.0,
         });
 
-        sqlx::query(
+        // The version check only filters writers that are already behind; two
+        // writers racing from the same version both pass it, and the loser
+        // trips `events_unique_version` instead.
+        let result = sqlx::query(
             r#"
-            INSERT INTO events (event_id, event_type, entity_type, entity_id, payload, metadata, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, NOW())
+            INSERT INTO events (event_id, aggregate_type, aggregate_id, event_type,
+                                event_data, metadata, version)
+            SELECT $1, $2, $3, $4, $5, $6, $7 + 1
+            WHERE (SELECT COALESCE(MAX(version), 0) FROM events
+                   WHERE aggregate_type = $2 AND aggregate_id = $3) = $7
             "#,
         )
         .bind(event_id.0)
+        .bind(&aggregate_type)
+        .bind(aggregate_id)
         .bind(&event_type)
-        .bind(&entity_type)
-        .bind(entity_id)
         .bind(&payload)
         .bind(&metadata)
+        .bind(expected)
         .execute(&self.pool)
-        .await?;
+        .await
+        .map_err(|e| map_append_error(e, &aggregate_type, aggregate_id, expected_version))?;
 
-        tracing::debug!(event_id = %event_id, event_type = %event_type, "Event appended");
+        if result.rows_affected() == 0 {
+            return Err(ApexError::concurrency_conflict(aggregate_type, aggregate_id, expected_version));
+        }
+
+        tracing::debug!(
+            event_id = %event_id,
+            event_type = %event_type,
+            version = expected_version + 1,
+            "Event appended"
+        );
 
         Ok(event_id)
     }
@@ -1066,11 +1087,11 @@ impl EventStore {
     ) -> Result<Vec<DomainEvent>, ApexError> {
         let rows = sqlx::query_as::<_, EventRow>(
             r#"
-            SELECT event_id, trace_id, span_id, event_type, entity_type, entity_id,
-                   payload, metadata, created_at
+            SELECT event_id, trace_id, span_id, event_type, aggregate_type, aggregate_id,
+                   event_data, version, metadata, created_at
             FROM events
-            WHERE entity_id = $1 AND entity_type = $2
-            ORDER BY created_at ASC
+            WHERE aggregate_id = $1 AND aggregate_type = $2
+            ORDER BY version ASC
             "#,
         )
         .bind(entity_id)
@@ -1079,7 +1100,7 @@ impl EventStore {
         .await?;
 
         rows.into_iter()
-            .map(|row| deserialize_domain_event(&row.payload))
+            .map(|row| deserialize_domain_event(&row.event_data))
             .collect()
     }
 
@@ -1094,8 +1115,8 @@ impl EventStore {
     ) -> Result<Vec<DomainEvent>, ApexError> {
         let rows = sqlx::query_as::<_, EventRow>(
             r#"
-            SELECT event_id, trace_id, span_id, event_type, entity_type, entity_id,
-                   payload, metadata, created_at
+            SELECT event_id, trace_id, span_id, event_type, aggregate_type, aggregate_id,
+                   event_data, version, metadata, created_at
             FROM events
             WHERE created_at >= $1 AND created_at <= $2
             ORDER BY created_at ASC
@@ -1107,7 +1128,7 @@ impl EventStore {
         .await?;
 
         rows.into_iter()
-            .map(|row| deserialize_domain_event(&row.payload))
+            .map(|row| deserialize_domain_event(&row.event_data))
             .collect()
     }
 
@@ -1116,8 +1137,8 @@ impl EventStore {
     pub async fn get_by_trace(&self, trace_id: &str) -> Result<Vec<DomainEvent>, ApexError> {
         let rows = sqlx::query_as::<_, EventRow>(
             r#"
-            SELECT event_id, trace_id, span_id, event_type, entity_type, entity_id,
-                   payload, metadata, created_at
+            SELECT event_id, trace_id, span_id, event_type, aggregate_type, aggregate_id,
+                   event_data, version, metadata, created_at
             FROM events
             WHERE trace_id = $1
             ORDER BY created_at ASC
@@ -1128,41 +1149,232 @@ impl EventStore {
         .await?;
 
         rows.into_iter()
-            .map(|row| deserialize_domain_event(&row.payload))
+            .map(|row| deserialize_domain_event(&row.event_data))
             .collect()
     }
 
+    /// Rebuild an aggregate from its latest snapshot and the events after it.
+    ///
+    /// Returns the state and the stream's current version, which is the
+    /// `expected_version` for the next [`append`](Self::append). An empty
+    /// stream is `Ok(None)`: there is no such aggregate.
+    ///
+    /// ```rust,ignore
+    /// let Some((task, version)) = store.load_aggregate::<TaskAggregate>(task_id.0).await? else {
+    ///     return Err(ApexError::task_not_found(task_id.0));
+    /// };
+    /// if task.status == Some(TaskStatus::Running) {
+    ///     store.append(cancelled, version).await?;
+    /// }
+    /// ```
+    #[instrument(skip(self), fields(aggregate_type = A::AGGREGATE_TYPE))]
+    pub async fn load_aggregate<A: super::aggregate::Aggregate>(
+        &self,
+        aggregate_id: Uuid,
+    ) -> Result<Option<(A, u64)>, ApexError> {
+        let snapshot = self.load_snapshot::<A>(aggregate_id, None).await?;
+        let after = snapshot.as_ref().map_or(0, |(_, version)| *version);
+
+        let rows = sqlx::query_as::<_, EventRow>(
+            r#"
+            SELECT event_id, trace_id, span_id, event_type, aggregate_type, aggregate_id,
+                   event_data, version, metadata, created_at
+            FROM events
+            WHERE aggregate_type = $1 AND aggregate_id = $2 AND version > $3
+            ORDER BY version ASC
+            "#,
+        )
+        .bind(A::AGGREGATE_TYPE)
+        .bind(aggregate_id)
+        .bind(i64::try_from(after).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(super::aggregate::rebuild_from(snapshot, versioned_events(&rows)?))
+    }
+
     /// Reconstruct an aggregate's state at a specific point in time.
     ///
-    /// Replays all events for the entity up to (and including) the given timestamp,
-    /// folding them through the `Aggregate::apply` method.
+    /// Folds all events for the entity up to (and including) the given
+    /// timestamp through `Aggregate::apply`, starting from the snapshot if it
+    /// was taken at or before that time. Once events have been pruned, times
+    /// before the snapshot can no longer be reconstructed completely.
     #[instrument(skip(self))]
     pub async fn reconstruct_state<S: super::aggregate::Aggregate>(
         &self,
         entity_id: Uuid,
         at: DateTime<Utc>,
     ) -> Result<S, ApexError> {
+        let snapshot = self.load_snapshot::<S>(entity_id, Some(at)).await?;
+        let after = snapshot.as_ref().map_or(0, |(_, version)| *version);
+
         let rows = sqlx::query_as::<_, EventRow>(
             r#"
-            SELECT event_id, trace_id, span_id, event_type, entity_type, entity_id,
-                   payload, metadata, created_at
+            SELECT event_id, trace_id, span_id, event_type, aggregate_type, aggregate_id,
+                   event_data, version, metadata, created_at
             FROM events
-            WHERE entity_id = $1 AND created_at <= $2
-            ORDER BY created_at ASC
+            WHERE aggregate_id = $1 AND aggregate_type = $2 AND created_at <= $3 AND version > $4
+            ORDER BY version ASC
             "#,
         )
         .bind(entity_id)
+        .bind(S::AGGREGATE_TYPE)
         .bind(at)
+        .bind(i64::try_from(after).unwrap_or(i64::MAX))
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(super::aggregate::rebuild_from(snapshot, versioned_events(&rows)?)
+            .map(|(state, _)| state)
+            .unwrap_or_default())
+    }
+
+    // -------------------------------------------------------------------------
+    // Snapshots and retention
+    // -------------------------------------------------------------------------
+
+    /// Store `state` as the snapshot of an aggregate at stream `version`.
+    ///
+    /// Only the newest snapshot per aggregate is kept; saving an older one
+    /// than is stored does nothing.
+    #[instrument(skip(self, state), fields(aggregate_type = A::AGGREGATE_TYPE))]
+    pub async fn save_snapshot<A: super::aggregate::Aggregate>(
+        &self,
+        aggregate_id: Uuid,
+        state: &A,
+        version: u64,
+    ) -> Result<(), ApexError> {
+        sqlx::query(
+            r#"
+            INSERT INTO event_snapshots (aggregate_type, aggregate_id, version, state)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (aggregate_type, aggregate_id) DO UPDATE
+            SET version = EXCLUDED.version, state = EXCLUDED.state, created_at = NOW()
+            WHERE event_snapshots.version < EXCLUDED.version
+            "#,
+        )
+        .bind(A::AGGREGATE_TYPE)
+        .bind(aggregate_id)
+        .bind(i32::try_from(version).unwrap_or(i32::MAX))
+        .bind(serde_json::to_value(state)?)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Snapshot an aggregate's current state. Returns the version captured,
+    /// or `None` if the stream is empty.
+    pub async fn snapshot<A: super::aggregate::Aggregate>(&self, aggregate_id: Uuid) -> Result<Option<u64>, ApexError> {
+        let Some((state, version)) = self.load_aggregate::<A>(aggregate_id).await? else {
+            return Ok(None);
+        };
+        self.save_snapshot(aggregate_id, &state, version).await?;
+        Ok(Some(version))
+    }
+
+    /// Up to `limit` aggregates of `aggregate_type` with events created before
+    /// `before` that no snapshot covers yet, so [`prune`](Self::prune) would
+    /// have to keep them.
+    pub async fn unsnapshotted_before(
+        &self,
+        aggregate_type: &str,
+        before: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<Uuid>, ApexError> {
+        let ids = sqlx::query_scalar(
+            r#"
+            SELECT DISTINCT e.aggregate_id
+            FROM events e
+            LEFT JOIN event_snapshots s
+                ON s.aggregate_type = e.aggregate_type AND s.aggregate_id = e.aggregate_id
+            WHERE e.aggregate_type = $1 AND e.created_at < $2
+              AND e.version > COALESCE(s.version, 0)
+            LIMIT $3
+            "#,
+        )
+        .bind(aggregate_type)
+        .bind(before)
+        .bind(limit)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut aggregate = S::default();
-        for row in &rows {
-            let event = deserialize_domain_event(&row.payload)?;
-            aggregate.apply(&event);
+        Ok(ids)
+    }
+
+    /// Delete events of `aggregate_type` created before `before`, at most
+    /// `batch_size` rows per statement so no lock is held for long. Returns
+    /// how many were deleted.
+    ///
+    /// Only events strictly below their aggregate's snapshot version go: a
+    /// stream without a snapshot is never touched, and the event the snapshot
+    /// was taken at stays, so the stream version [`append`](Self::append)
+    /// checks against is preserved.
+    #[instrument(skip(self))]
+    pub async fn prune(
+        &self,
+        aggregate_type: &str,
+        before: DateTime<Utc>,
+        batch_size: i64,
+    ) -> Result<u64, ApexError> {
+        let mut pruned = 0;
+        loop {
+            let result = sqlx::query(
+                r#"
+                DELETE FROM events
+                WHERE id IN (
+                    SELECT e.id
+                    FROM events e
+                    JOIN event_snapshots s
+                        ON s.aggregate_type = e.aggregate_type AND s.aggregate_id = e.aggregate_id
+                    WHERE e.aggregate_type = $1 AND e.created_at < $2 AND e.version < s.version
+                    LIMIT $3
+                )
+                "#,
+            )
+            .bind(aggregate_type)
+            .bind(before)
+            .bind(batch_size)
+            .execute(&self.pool)
+            .await?;
+
+            pruned += result.rows_affected();
+            if result.rows_affected() < batch_size as u64 {
+                return Ok(pruned);
+            }
         }
+    }
 
-        Ok(aggregate)
+    /// The aggregate's snapshot, if any; with `at`, only if the event it was
+    /// taken at happened by then.
+    async fn load_snapshot<A: super::aggregate::Aggregate>(
+        &self,
+        aggregate_id: Uuid,
+        at: Option<DateTime<Utc>>,
+    ) -> Result<Option<(A, u64)>, ApexError> {
+        let row: Option<(i32, serde_json::Value)> = sqlx::query_as(
+            r#"
+            SELECT s.version, s.state
+            FROM event_snapshots s
+            WHERE s.aggregate_type = $1 AND s.aggregate_id = $2
+              AND ($3::timestamptz IS NULL OR EXISTS (
+                  SELECT 1 FROM events e
+                  WHERE e.aggregate_type = s.aggregate_type AND e.aggregate_id = s.aggregate_id
+                    AND e.version = s.version AND e.created_at <= $3
+              ))
+            "#,
+        )
+        .bind(A::AGGREGATE_TYPE)
+        .bind(aggregate_id)
+        .bind(at)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|(version, state)| {
+            let state = serde_json::from_value(state)?;
+            Ok((state, u64::try_from(version).unwrap_or_default()))
+        })
+        .transpose()
     }
 }
 
@@ -1182,22 +1394,53 @@ struct EventRow {
     #[allow(dead_code)]
     event_type: String,
     #[allow(dead_code)]
-    entity_type: String,
+    aggregate_type: String,
     #[allow(dead_code)]
-    entity_id: Uuid,
-    payload: serde_json::Value,
+    aggregate_id: Uuid,
+    event_data: serde_json::Value,
+    version: i32,
     #[allow(dead_code)]
     metadata: Option<serde_json::Value>,
     #[allow(dead_code)]
     created_at: DateTime<Utc>,
 }
 
+/// Name of the unique constraint on `(aggregate_type, aggregate_id, version)`.
+pub const VERSION_CONSTRAINT: &str = "events_unique_version";
+
+/// Map an event insert failure, turning a clash on [`VERSION_CONSTRAINT`]
+/// into a concurrency conflict for the aggregate being written.
+pub(crate) fn map_append_error(
+    error: sqlx::Error,
+    aggregate_type: &str,
+    aggregate_id: Uuid,
+    expected_version: u64,
+) -> ApexError {
+    match &error {
+        sqlx::Error::Database(db_err) if db_err.constraint() == Some(VERSION_CONSTRAINT) => {
+            ApexError::concurrency_conflict(aggregate_type, aggregate_id, expected_version)
+                .with_source(error)
+        }
+        _ => ApexError::from(error),
+    }
+}
+
+/// Deserialize rows into `(version, event)` pairs.
+fn versioned_events(rows: &[EventRow]) -> Result<Vec<(u64, DomainEvent)>, ApexError> {
+    rows.iter()
+        .map(|row| {
+            let version = u64::try_from(row.version).unwrap_or_default();
+            deserialize_domain_event(&row.event_data).map(|event| (version, event))
+        })
+        .collect()
+}
+
 /// Deserialize a `DomainEvent` from a JSONB payload.
 fn deserialize_domain_event(payload: &serde_json::Value) -> Result<DomainEvent, ApexError> {
     serde_json::from_value(payload.clone()).map_err(ApexError::from)
 }
 
-/// Parse a stream ID string (e.g. "task-<uuid>") into (entity_type, entity_id).
+/// Parse a stream ID string (e.g. "task-<uuid>") into (aggregate_type, aggregate_id).
 /// Falls back to ("unknown", nil UUID) if parsing fails.
 fn parse_stream_id(stream_id: &str) -> (String, Uuid) {
     if let Some(idx) = stream_id.find('-') {
@@ -1314,4 +1557,116 @@ mod tests {
         let restored: DomainEvent = serde_json::from_str(&json).unwrap();
         assert_eq!(restored.event_type(), "TaskCreated");
     }
+
+    /// A database error as Postgres reports a unique violation.
+    #[derive(Debug)]
+    struct UniqueViolation(&'static str);
+
+    impl std::fmt::Display for UniqueViolation {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "duplicate key value violates unique constraint \"{}\"", self.0)
+        }
+    }
+
+    impl std::error::Error for UniqueViolation {}
+
+    impl sqlx::error::DatabaseError for UniqueViolation {
+        fn message(&self) -> &str {
+            "duplicate key value violates unique constraint"
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn constraint(&self) -> Option<&str> {
+            Some(self.0)
+        }
+
+        fn kind(&self) -> sqlx::error::ErrorKind {
+            sqlx::error::ErrorKind::UniqueViolation
+        }
+    }
+
+    #[test]
+    fn test_version_clash_is_concurrency_conflict() {
+        use crate::error::ErrorCode;
+
+        let aggregate_id = Uuid::new_v4();
+        let clash = sqlx::Error::Database(Box::new(UniqueViolation(VERSION_CONSTRAINT)));
+        let err = map_append_error(clash, "task", aggregate_id, 3);
+        assert_eq!(err.code(), ErrorCode::ConcurrencyConflict);
+        assert_eq!(err.code().http_status(), axum::http::StatusCode::CONFLICT);
+        assert!(!err.is_retryable());
+        assert!(err.user_message().contains(&aggregate_id.to_string()));
+        assert!(err.user_message().contains("version 3"));
+
+        // Other unique violations keep their generic meaning
+        let duplicate = sqlx::Error::Database(Box::new(UniqueViolation("events_pkey")));
+        let err = map_append_error(duplicate, "task", aggregate_id, 3);
+        assert_eq!(err.code(), ErrorCode::DuplicateRecord);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated Postgres at DATABASE_URL"]
+    async fn test_real_version_clash_is_concurrency_conflict() {
+        use crate::error::ErrorCode;
+
+        let db = crate::db::tests::live_database().await;
+        let insert = "INSERT INTO events (aggregate_type, aggregate_id, event_type, event_data, version) \
+                      VALUES ('task', $1, 'test', '{}', $2)";
+        let aggregate_id = Uuid::new_v4();
+        sqlx::query(insert).bind(aggregate_id).bind(1).execute(db.pool()).await.unwrap();
+        let clash = sqlx::query(insert).bind(aggregate_id).bind(1).execute(db.pool()).await.unwrap_err();
+        assert_eq!(map_append_error(clash, "task", aggregate_id, 0).code(), ErrorCode::ConcurrencyConflict);
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated Postgres at DATABASE_URL"]
+    async fn test_version_migration_renumbers_duplicates() {
+        let db = crate::db::tests::live_database().await;
+        let mut tx = db.pool().begin().await.unwrap();
+
+        // A legacy database: no constraints, and a stream with a clash
+        sqlx::query("ALTER TABLE events DROP CONSTRAINT events_unique_version, DROP CONSTRAINT events_version_positive")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let aggregate_id = Uuid::new_v4();
+        for (version, event_type) in [(1, "first"), (2, "second"), (2, "racer"), (3, "third")] {
+            sqlx::query(
+                "INSERT INTO events (aggregate_type, aggregate_id, event_type, event_data, version) \
+                 VALUES ('task', $1, $2, '{}', $3)",
+            )
+            .bind(aggregate_id)
+            .bind(event_type)
+            .bind(version)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        sqlx::Executor::execute(&mut *tx, include_str!("../../migrations/20240101000009_event_version_constraint.sql"))
+            .await
+            .unwrap();
+
+        let stream: Vec<(String, i32)> =
+            sqlx::query_as("SELECT event_type, version FROM events WHERE aggregate_id = $1 ORDER BY version")
+                .bind(aggregate_id)
+                .fetch_all(&mut *tx)
+                .await
+                .unwrap();
+        let expected = [("first", 1), ("second", 2), ("racer", 3), ("third", 4)];
+        assert_eq!(stream, expected.map(|(t, v)| (t.to_string(), v)));
+
+        tx.rollback().await.unwrap();
+    }
 }
@@ -4,7 +4,9 @@
 //!
 //! - **`event`**: Domain events, event envelope/metadata types, and the persistent `EventStore`.
 //! - **`aggregate`**: The `Aggregate` trait for state reconstruction, with implementations
-//!   for Task, Agent, and DAG aggregates.
+//!   for Task, Agent, and DAG aggregates. `EventStore::load_aggregate` rebuilds one from
+//!   its latest snapshot and later events, together with the version to append at.
+//!   Events below a snapshot can be pruned with `EventStore::prune`.
 //! - **`crdt`**: Conflict-free Replicated Data Types (LWWRegister, GSet, ORSet, GCounter,
 //!   MergeableState) for deterministic merging of parallel agent outputs.
 
@@ -5,6 +5,7 @@
 //! - **Redis**: Cache connection and memory health
 //! - **Workers**: Worker pool health and heartbeat
 //! - **External APIs**: External service availability
+//! - **LLM Providers**: OpenAI/Anthropic reachability and credentials
 //!
 //! # Example
 //!
@@ -25,6 +26,7 @@ use tokio::sync::RwLock;
 use tracing::{debug, error, warn};
 
 use super::check::{ComponentHealth, HealthStatus};
+use crate::config::LlmConfig;
 use crate::orchestrator::WorkerPoolStats;
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -648,6 +650,162 @@ impl HealthChecker for ExternalApiHealthChecker {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// LLM Provider Health Checker
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// An LLM provider the orchestrator calls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LlmProvider {
+    OpenAi,
+    Anthropic,
+}
+
+impl LlmProvider {
+    /// Component name in health reports.
+    pub fn component_name(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "openai_api",
+            LlmProvider::Anthropic => "anthropic_api",
+        }
+    }
+
+    fn default_base_url(&self) -> &'static str {
+        match self {
+            LlmProvider::OpenAi => "https://api.openai.com",
+            LlmProvider::Anthropic => "https://api.anthropic.com",
+        }
+    }
+}
+
+/// Health checker for an LLM provider API.
+///
+/// Lists the provider's models: cheap, spends no tokens, and exercises the
+/// API key. A rejected key or an unreachable API is unhealthy; slow
+/// responses, rate limiting and server errors are degraded.
+pub struct LlmProviderHealthChecker {
+    provider: LlmProvider,
+    api_key: String,
+    base_url: String,
+    client: reqwest::Client,
+    config: HealthCheckConfig,
+}
+
+impl LlmProviderHealthChecker {
+    /// Create a checker for `provider` using its public API.
+    pub fn new(provider: LlmProvider, api_key: impl Into<String>) -> Self {
+        let config = HealthCheckConfig {
+            latency_threshold_ms: 2000,
+            ..HealthCheckConfig::default()
+        };
+        Self {
+            provider,
+            api_key: api_key.into(),
+            base_url: provider.default_base_url().to_string(),
+            client: Self::client(config.timeout),
+            config,
+        }
+    }
+
+    /// Checkers for the providers that have an API key configured.
+    pub fn from_config(llm: &LlmConfig, timeout: Duration) -> Vec<Self> {
+        let providers = [
+            (LlmProvider::OpenAi, &llm.openai_api_key, &llm.openai_base_url),
+            (LlmProvider::Anthropic, &llm.anthropic_api_key, &llm.anthropic_base_url),
+        ];
+        providers
+            .into_iter()
+            .filter_map(|(provider, key, base_url)| {
+                let mut checker = Self::new(provider, key.as_deref()?).with_timeout(timeout);
+                if let Some(base_url) = base_url {
+                    checker = checker.with_base_url(base_url);
+                }
+                Some(checker)
+            })
+            .collect()
+    }
+
+    /// Use a different API base URL (e.g. a proxy or compatible server).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Give up on the request after `timeout`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self.client = Self::client(timeout);
+        self
+    }
+
+    /// Report degraded above this latency.
+    pub fn with_latency_threshold(mut self, threshold_ms: u64) -> Self {
+        self.config.latency_threshold_ms = threshold_ms;
+        self
+    }
+
+    fn client(timeout: Duration) -> reqwest::Client {
+        reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default()
+    }
+
+    fn request(&self) -> reqwest::RequestBuilder {
+        let url = format!("{}/v1/models", self.base_url);
+        match self.provider {
+            LlmProvider::OpenAi => self.client.get(url).bearer_auth(&self.api_key),
+            LlmProvider::Anthropic => self
+                .client
+                .get(url)
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01"),
+        }
+    }
+}
+
+#[async_trait]
+impl HealthChecker for LlmProviderHealthChecker {
+    fn name(&self) -> &str {
+        self.provider.component_name()
+    }
+
+    async fn check(&self) -> ComponentHealth {
+        let start = Instant::now();
+        let result = self.request().send().await;
+        let latency = start.elapsed();
+
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                warn!(provider = self.name(), error = %e, "LLM provider health check failed");
+                return ComponentHealth::unhealthy(self.name())
+                    .with_error(format!("Unreachable: {}", e))
+                    .with_latency(latency)
+                    .with_metadata("url", &self.base_url);
+            }
+        };
+
+        let status = response.status();
+        let health = if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            ComponentHealth::unhealthy(self.name()).with_error("API key rejected")
+        } else if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            ComponentHealth::degraded(self.name()).with_message("Rate limited")
+        } else if !status.is_success() {
+            ComponentHealth::degraded(self.name()).with_message(format!("Unexpected status {}", status.as_u16()))
+        } else if latency.as_millis() as u64 > self.config.latency_threshold_ms {
+            ComponentHealth::degraded(self.name()).with_message("High latency")
+        } else {
+            ComponentHealth::healthy(self.name()).with_message("API key valid")
+        };
+
+        health
+            .with_latency(latency)
+            .with_metadata("http_status", status.as_u16())
+            .with_metadata("url", &self.base_url)
+    }
+}
+
 // Helper for ComponentHealth
 impl ComponentHealth {
     fn new_with_status(name: impl Into<String>, status: HealthStatus) -> Self {
@@ -705,12 +863,12 @@ impl DiskSpaceHealthChecker {
         {
             use std::ffi::CString;
             let c_path = CString::new(self.path.as_str())
-                .map_err(|e| format\!("Invalid path: {}", e))?;
+                .map_err(|e| format!("Invalid path: {}", e))?;
 
             unsafe {
                 let mut stat: libc::statvfs = std::mem::zeroed();
-                if libc::statvfs(c_path.as_ptr(), &mut stat) \!= 0 {
-                    return Err(format\!(
+                if libc::statvfs(c_path.as_ptr(), &mut stat) != 0 {
+                    return Err(format!(
                         "statvfs failed for {}: {}",
                         self.path,
                         std::io::Error::last_os_error()
@@ -762,19 +920,14 @@ impl HealthChecker for DiskSpaceHealthChecker {
 
         match self.get_disk_usage() {
             Ok(info) => {
-                let status = if info.usage_pct >= self.critical_threshold_pct {
-                    HealthStatus::Unhealthy
-                } else if info.usage_pct >= self.warning_threshold_pct {
-                    HealthStatus::Degraded
-                } else {
-                    HealthStatus::Healthy
-                };
-
-                let message = format\!(
-                    "Disk usage: {:.1}% ({} / {} available)",
+                let status = threshold_status(info.usage_pct, self.warning_threshold_pct, self.critical_threshold_pct);
+                let message = format!(
+                    "Disk usage on {}: {:.1}% ({} used of {}, {} available)",
+                    self.path,
                     info.usage_pct,
                     format_bytes(info.used_bytes),
-                    format_bytes(info.total_bytes)
+                    format_bytes(info.total_bytes),
+                    format_bytes(info.available_bytes)
                 );
 
                 ComponentHealth::new_with_status(self.name(), status)
@@ -787,7 +940,7 @@ impl HealthChecker for DiskSpaceHealthChecker {
                     .with_metadata("usage_pct", info.usage_pct)
             }
             Err(e) => {
-                warn\!(error = %e, path = %self.path, "Disk space check failed");
+                warn!(error = %e, path = %self.path, "Disk space check failed");
                 ComponentHealth::unhealthy(self.name())
                     .with_error(e)
                     .with_latency(start.elapsed())
@@ -804,15 +957,15 @@ fn format_bytes(bytes: u64) -> String {
     const TB: u64 = GB * 1024;
 
     if bytes >= TB {
-        format\!("{:.1} TB", bytes as f64 / TB as f64)
+        format!("{:.1} TB", bytes as f64 / TB as f64)
     } else if bytes >= GB {
-        format\!("{:.1} GB", bytes as f64 / GB as f64)
+        format!("{:.1} GB", bytes as f64 / GB as f64)
     } else if bytes >= MB {
-        format\!("{:.1} MB", bytes as f64 / MB as f64)
+        format!("{:.1} MB", bytes as f64 / MB as f64)
     } else if bytes >= KB {
-        format\!("{:.1} KB", bytes as f64 / KB as f64)
+        format!("{:.1} KB", bytes as f64 / KB as f64)
     } else {
-        format\!("{} B", bytes)
+        format!("{} B", bytes)
     }
 }
 
@@ -820,7 +973,11 @@ fn format_bytes(bytes: u64) -> String {
 // Memory Health Checker
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Health checker for system memory.
+/// Health checker for memory.
+///
+/// Thresholds apply to system memory in use (total minus available), which
+/// is what decides whether the process can keep allocating. Where available
+/// memory can't be read, the process's share of total memory is used.
 pub struct MemoryHealthChecker {
     /// Warning threshold percentage (memory usage above this = degraded).
     warning_threshold_pct: f64,
@@ -849,25 +1006,35 @@ impl MemoryHealthChecker {
         self
     }
 
-    /// Get process memory usage.
+    /// Get the process's resident set size.
     fn get_process_memory(&self) -> std::result::Result<ProcessMemoryInfo, String> {
-        #[cfg(unix)]
+        // Current RSS from /proc; getrusage only reports the peak
+        #[cfg(target_os = "linux")]
+        {
+            let statm = std::fs::read_to_string("/proc/self/statm")
+                .map_err(|e| format!("Failed to read /proc/self/statm: {}", e))?;
+            let pages: u64 = statm
+                .split_whitespace()
+                .nth(1)
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| "Failed to parse /proc/self/statm".to_string())?;
+            let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(0) as u64;
+            Ok(ProcessMemoryInfo { rss_bytes: pages * page_size })
+        }
+
+        #[cfg(all(unix, not(target_os = "linux")))]
         {
             unsafe {
                 let mut usage: libc::rusage = std::mem::zeroed();
-                if libc::getrusage(libc::RUSAGE_SELF, &mut usage) \!= 0 {
-                    return Err(format\!(
+                if libc::getrusage(libc::RUSAGE_SELF, &mut usage) != 0 {
+                    return Err(format!(
                         "getrusage failed: {}",
                         std::io::Error::last_os_error()
                     ));
                 }
-                // ru_maxrss is in bytes on macOS, kilobytes on Linux
-                #[cfg(target_os = "macos")]
-                let rss_bytes = usage.ru_maxrss as u64;
-                #[cfg(not(target_os = "macos"))]
-                let rss_bytes = usage.ru_maxrss as u64 * 1024;
-
-                Ok(ProcessMemoryInfo { rss_bytes })
+                // ru_maxrss is in bytes on macOS and kilobytes on the BSDs
+                let scale = if cfg!(target_os = "macos") { 1 } else { 1024 };
+                Ok(ProcessMemoryInfo { rss_bytes: usage.ru_maxrss.max(0) as u64 * scale })
             }
         }
 
@@ -877,8 +1044,8 @@ impl MemoryHealthChecker {
         }
     }
 
-    /// Get total system memory.
-    fn get_total_memory(&self) -> std::result::Result<u64, String> {
+    /// Get total and, where the platform reports it, available system memory.
+    fn get_system_memory(&self) -> std::result::Result<SystemMemoryInfo, String> {
         #[cfg(target_os = "macos")]
         {
             unsafe {
@@ -892,31 +1059,19 @@ impl MemoryHealthChecker {
                     &mut len,
                     std::ptr::null_mut(),
                     0,
-                ) \!= 0
+                ) != 0
                 {
                     return Err("sysctl HW_MEMSIZE failed".to_string());
                 }
-                Ok(size)
+                Ok(SystemMemoryInfo { total_bytes: size, available_bytes: None })
             }
         }
 
         #[cfg(target_os = "linux")]
         {
-            use std::fs;
-            let meminfo = fs::read_to_string("/proc/meminfo")
-                .map_err(|e| format\!("Failed to read /proc/meminfo: {}", e))?;
-            for line in meminfo.lines() {
-                if line.starts_with("MemTotal:") {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if parts.len() >= 2 {
-                        let kb: u64 = parts[1]
-                            .parse()
-                            .map_err(|e| format\!("Failed to parse MemTotal: {}", e))?;
-                        return Ok(kb * 1024);
-                    }
-                }
-            }
-            Err("MemTotal not found in /proc/meminfo".to_string())
+            let meminfo = std::fs::read_to_string("/proc/meminfo")
+                .map_err(|e| format!("Failed to read /proc/meminfo: {}", e))?;
+            parse_meminfo(&meminfo)
         }
 
         #[cfg(not(any(target_os = "linux", target_os = "macos")))]
@@ -932,6 +1087,28 @@ struct ProcessMemoryInfo {
     rss_bytes: u64,
 }
 
+/// System memory information.
+#[derive(Debug, Clone, PartialEq)]
+struct SystemMemoryInfo {
+    total_bytes: u64,
+    available_bytes: Option<u64>,
+}
+
+/// Read `MemTotal` and `MemAvailable` from the contents of `/proc/meminfo`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_meminfo(meminfo: &str) -> std::result::Result<SystemMemoryInfo, String> {
+    let field = |name: &str| -> Option<u64> {
+        let line = meminfo.lines().find(|l| l.starts_with(name))?;
+        let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+        Some(kb * 1024)
+    };
+    let total_bytes = field("MemTotal:").ok_or_else(|| "MemTotal not found in /proc/meminfo".to_string())?;
+    Ok(SystemMemoryInfo {
+        total_bytes,
+        available_bytes: field("MemAvailable:"),
+    })
+}
+
 impl Default for MemoryHealthChecker {
     fn default() -> Self {
         Self::new()
@@ -961,47 +1138,62 @@ impl HealthChecker for MemoryHealthChecker {
             .with_metadata("process_rss_bytes", process_mem.rss_bytes)
             .with_metadata("process_rss_mb", process_mem.rss_bytes / (1024 * 1024));
 
-        // Try to get total memory and compute usage percentage
-        if let Ok(total_memory) = self.get_total_memory() {
-            let usage_pct = (process_mem.rss_bytes as f64 / total_memory as f64) * 100.0;
-            health = health
-                .with_metadata("total_memory_bytes", total_memory)
-                .with_metadata("memory_usage_pct", usage_pct);
-
-            if usage_pct >= self.critical_threshold_pct {
-                health = health
-                    .with_status(HealthStatus::Unhealthy)
-                    .with_message(format\!(
-                        "Process memory usage critical: {:.1}% ({} / {})",
-                        usage_pct,
-                        format_bytes(process_mem.rss_bytes),
-                        format_bytes(total_memory)
-                    ));
-            } else if usage_pct >= self.warning_threshold_pct {
-                health = health
-                    .with_status(HealthStatus::Degraded)
-                    .with_message(format\!(
-                        "Process memory usage high: {:.1}% ({} / {})",
-                        usage_pct,
-                        format_bytes(process_mem.rss_bytes),
-                        format_bytes(total_memory)
-                    ));
-            } else {
-                health = health.with_message(format\!(
-                    "Process memory: {} ({:.1}% of {})",
-                    format_bytes(process_mem.rss_bytes),
-                    usage_pct,
-                    format_bytes(total_memory)
+        let system = match self.get_system_memory() {
+            Ok(system) if system.total_bytes > 0 => system,
+            _ => {
+                return health.with_message(format!(
+                    "Process RSS: {}",
+                    format_bytes(process_mem.rss_bytes)
                 ));
             }
-        } else {
-            health = health.with_message(format\!(
-                "Process RSS: {}",
-                format_bytes(process_mem.rss_bytes)
-            ));
+        };
+
+        let total = system.total_bytes;
+        let (usage_pct, message) = match system.available_bytes {
+            Some(available) => {
+                let pct = (total.saturating_sub(available) as f64 / total as f64) * 100.0;
+                let message = format!(
+                    "Memory usage: {:.1}% ({} available of {}), process RSS {}",
+                    pct,
+                    format_bytes(available),
+                    format_bytes(total),
+                    format_bytes(process_mem.rss_bytes)
+                );
+                (pct, message)
+            }
+            None => {
+                let pct = (process_mem.rss_bytes as f64 / total as f64) * 100.0;
+                let message = format!(
+                    "Process memory: {:.1}% ({} of {})",
+                    pct,
+                    format_bytes(process_mem.rss_bytes),
+                    format_bytes(total)
+                );
+                (pct, message)
+            }
+        };
+
+        health = health
+            .with_metadata("total_memory_bytes", total)
+            .with_metadata("memory_usage_pct", usage_pct);
+        if let Some(available) = system.available_bytes {
+            health = health.with_metadata("available_memory_bytes", available);
         }
 
         health
+            .with_status(threshold_status(usage_pct, self.warning_threshold_pct, self.critical_threshold_pct))
+            .with_message(message)
+    }
+}
+
+/// Degraded at `warning_pct` and unhealthy at `critical_pct`.
+fn threshold_status(usage_pct: f64, warning_pct: f64, critical_pct: f64) -> HealthStatus {
+    if usage_pct >= critical_pct {
+        HealthStatus::Unhealthy
+    } else if usage_pct >= warning_pct {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Healthy
     }
 }
 
@@ -1009,15 +1201,135 @@ impl HealthChecker for MemoryHealthChecker {
 // Backup Health Checker
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Health checker for database backup infrastructure.
+/// Where [`BackupHealthChecker`] finds the time of the most recent backup.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackupSource {
+    /// Newest completed backup recorded in the `backups` table
+    Table,
+    /// Last WAL segment archived by PostgreSQL (`pg_stat_archiver`)
+    WalArchive,
+    /// Newest file in a directory, e.g. a backup volume or a mounted bucket
+    Directory(String),
+}
+
+impl BackupSource {
+    fn as_str(&self) -> &'static str {
+        match self {
+            BackupSource::Table => "table",
+            BackupSource::WalArchive => "wal_archive",
+            BackupSource::Directory(_) => "directory",
+        }
+    }
+}
+
+/// Health checker for database backup freshness.
+///
+/// Degraded once the newest backup is older than the warning age, so a
+/// failing backup job shows up before the freshness window runs out;
+/// unhealthy when there is no backup or the newest is past the window.
 pub struct BackupHealthChecker {
     pool: PgPool,
+    source: BackupSource,
+    /// Backups older than this are stale.
+    max_age: Duration,
+    /// Backups older than this are about to go stale.
+    warning_age: Duration,
 }
 
 impl BackupHealthChecker {
-    /// Create a new backup health checker.
+    /// Create a checker reading the `backups` table with a 24 hour window.
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            source: BackupSource::Table,
+            max_age: Duration::from_secs(24 * 3600),
+            warning_age: Duration::from_secs(20 * 3600),
+        }
+    }
+
+    /// Read the last backup time from `source`.
+    pub fn with_source(mut self, source: BackupSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Unhealthy once the newest backup is older than `max_age`.
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = max_age;
+        self
+    }
+
+    /// Degraded once the newest backup is older than `warning_age`.
+    pub fn with_warning_age(mut self, warning_age: Duration) -> Self {
+        self.warning_age = warning_age;
+        self
+    }
+
+    /// Time of the most recent backup, if there is one.
+    async fn last_backup_at(&self) -> std::result::Result<Option<chrono::DateTime<Utc>>, String> {
+        match &self.source {
+            BackupSource::Table => sqlx::query_scalar(
+                "SELECT MAX(completed_at) FROM backups WHERE status = 'completed'",
+            )
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| format!("Failed to read backups table: {}", e)),
+            BackupSource::WalArchive => {
+                let monitor = crate::db::health::DatabaseHealthMonitor::new(self.pool.clone(), 0, 0);
+                Ok(monitor.validate_backups().await.last_archived_at)
+            }
+            BackupSource::Directory(path) => newest_file_time(path).await,
+        }
+    }
+}
+
+/// Modification time of the newest file directly under `path`.
+async fn newest_file_time(path: &str) -> std::result::Result<Option<chrono::DateTime<Utc>>, String> {
+    let mut entries = tokio::fs::read_dir(path)
+        .await
+        .map_err(|e| format!("Failed to list {}: {}", path, e))?;
+    let mut newest = None;
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| format!("Failed to list {}: {}", path, e))?
+    {
+        let Ok(metadata) = entry.metadata().await else { continue };
+        if !metadata.is_file() {
+            continue;
+        }
+        if let Ok(modified) = metadata.modified() {
+            let modified = chrono::DateTime::<Utc>::from(modified);
+            newest = newest.max(Some(modified));
+        }
+    }
+    Ok(newest)
+}
+
+/// Status and message for a backup taken at `last_backup_at`.
+fn backup_freshness(
+    last_backup_at: Option<chrono::DateTime<Utc>>,
+    now: chrono::DateTime<Utc>,
+    warning_age: Duration,
+    max_age: Duration,
+) -> (HealthStatus, String) {
+    let Some(last) = last_backup_at else {
+        return (HealthStatus::Unhealthy, "No backups found".to_string());
+    };
+    let age = (now - last).to_std().unwrap_or_default();
+    let age_text = format!("{}h {}m", age.as_secs() / 3600, age.as_secs() % 3600 / 60);
+    if age >= max_age {
+        (
+            HealthStatus::Unhealthy,
+            format!("Last backup {} ago, older than the {}h window", age_text, max_age.as_secs() / 3600),
+        )
+    } else if age >= warning_age {
+        (
+            HealthStatus::Degraded,
+            format!("Last backup {} ago, approaching the {}h window", age_text, max_age.as_secs() / 3600),
+        )
+    } else {
+        (HealthStatus::Healthy, format!("Last backup {} ago", age_text))
     }
 }
 
@@ -1029,35 +1341,23 @@ impl HealthChecker for BackupHealthChecker {
 
     async fn check(&self) -> ComponentHealth {
         let start = Instant::now();
-        let monitor = crate::db::health::DatabaseHealthMonitor::new(
-            self.pool.clone(),
-            0,
-            0,
-        );
-        let backup_result = monitor.validate_backups().await;
-
-        let status = match &backup_result.status {
-            crate::db::health::BackupStatus::Healthy => HealthStatus::Healthy,
-            crate::db::health::BackupStatus::Warning => HealthStatus::Degraded,
-            crate::db::health::BackupStatus::NotConfigured => HealthStatus::Degraded,
-            crate::db::health::BackupStatus::Unknown => HealthStatus::Unhealthy,
-        };
-
-        let message = match &backup_result.status {
-            crate::db::health::BackupStatus::Healthy => "Backup infrastructure is healthy",
-            crate::db::health::BackupStatus::Warning => "Backup has warnings (recent failures detected)",
-            crate::db::health::BackupStatus::NotConfigured => "No backup infrastructure detected",
-            crate::db::health::BackupStatus::Unknown => "Backup status unknown",
+        let health = match self.last_backup_at().await {
+            Ok(last_backup_at) => {
+                let (status, message) = backup_freshness(last_backup_at, Utc::now(), self.warning_age, self.max_age);
+                ComponentHealth::new_with_status(self.name(), status)
+                    .with_message(message)
+                    .with_metadata("last_backup_at", last_backup_at)
+            }
+            Err(e) => {
+                warn!(error = %e, source = self.source.as_str(), "Backup freshness check failed");
+                ComponentHealth::unhealthy(self.name()).with_error(e)
+            }
         };
 
-        ComponentHealth::new_with_status(self.name(), status)
-            .with_message(message)
+        health
             .with_latency(start.elapsed())
-            .with_metadata("wal_archiving_enabled", backup_result.wal_archiving_enabled)
-            .with_metadata("has_replication_slots", backup_result.has_replication_slots)
-            .with_metadata("archived_count", backup_result.archived_count)
-            .with_metadata("failed_count", backup_result.failed_count)
-            .with_metadata("database_size_bytes", backup_result.database_size_bytes)
+            .with_metadata("source", self.source.as_str())
+            .with_metadata("max_age_secs", self.max_age.as_secs())
     }
 }
 
@@ -1401,6 +1701,90 @@ mod tests {
         assert_eq!(checker.critical_threshold_pct, 95.0);
     }
 
+    #[test]
+    fn test_threshold_status() {
+        assert_eq!(threshold_status(50.0, 80.0, 95.0), HealthStatus::Healthy);
+        assert_eq!(threshold_status(80.0, 80.0, 95.0), HealthStatus::Degraded);
+        assert_eq!(threshold_status(99.0, 80.0, 95.0), HealthStatus::Unhealthy);
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:       16384000 kB\nMemFree:         1024000 kB\nMemAvailable:    4096000 kB\n";
+        assert_eq!(
+            parse_meminfo(meminfo).unwrap(),
+            SystemMemoryInfo { total_bytes: 16384000 * 1024, available_bytes: Some(4096000 * 1024) }
+        );
+        assert_eq!(parse_meminfo("MemTotal: 1024 kB").unwrap().available_bytes, None);
+        assert!(parse_meminfo("MemFree: 1024 kB").is_err());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_system_checkers_report_readings() {
+        let disk = DiskSpaceHealthChecker::new("/").check().await;
+        assert!(disk.metadata.contains_key("usage_pct"));
+        assert!(disk.message.unwrap().starts_with("Disk usage on /:"));
+
+        let memory = MemoryHealthChecker::new().with_warning_threshold(0.0).check().await;
+        assert!(memory.metadata.contains_key("available_memory_bytes"));
+        assert!(memory.message.unwrap().contains("process RSS"));
+        assert_ne!(memory.status, HealthStatus::Healthy);
+    }
+
+    #[test]
+    fn test_backup_freshness() {
+        let now = Utc::now();
+        let (warning, max) = (Duration::from_secs(20 * 3600), Duration::from_secs(24 * 3600));
+        let at = |hours: i64| Some(now - chrono::Duration::hours(hours));
+
+        assert_eq!(backup_freshness(at(2), now, warning, max).0, HealthStatus::Healthy);
+        let (status, message) = backup_freshness(at(21), now, warning, max);
+        assert_eq!(status, HealthStatus::Degraded);
+        assert_eq!(message, "Last backup 21h 0m ago, approaching the 24h window");
+        assert_eq!(backup_freshness(at(30), now, warning, max).0, HealthStatus::Unhealthy);
+        assert_eq!(backup_freshness(None, now, warning, max).0, HealthStatus::Unhealthy);
+    }
+
+    #[tokio::test]
+    async fn test_backup_directory_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap().to_string();
+        assert_eq!(newest_file_time(&path).await.unwrap(), None);
+
+        std::fs::write(dir.path().join("apex-2024-01-01.dump"), b"backup").unwrap();
+        let newest = newest_file_time(&path).await.unwrap().unwrap();
+        assert!(Utc::now() - newest < chrono::Duration::minutes(1));
+
+        let pool = PgPool::connect_lazy("postgres://localhost/apex_test").unwrap();
+        let health = BackupHealthChecker::new(pool)
+            .with_source(BackupSource::Directory(path))
+            .check()
+            .await;
+        assert_eq!(health.status, HealthStatus::Healthy);
+    }
+
+    #[tokio::test]
+    async fn test_backup_check_is_opt_in() {
+        use crate::health::{HealthConfig, HealthService};
+
+        let pool = PgPool::connect_lazy("postgres://localhost/apex_test").unwrap();
+        let mut service = HealthService::new(HealthConfig::default());
+        service.register_backup_checker(pool.clone());
+        assert!(service.check_health().await.get_component("database_backup").is_none());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("apex.dump"), b"backup").unwrap();
+        let config = HealthConfig {
+            backup_source: Some(BackupSource::Directory(dir.path().to_str().unwrap().to_string())),
+            ..Default::default()
+        };
+        let mut service = HealthService::new(config);
+        service.register_backup_checker(pool);
+        let report = service.check_health().await;
+        assert_eq!(report.get_component("database_backup").unwrap().status, HealthStatus::Healthy);
+    }
+
     #[tokio::test]
     async fn test_memory_checker_name() {
         let checker = MemoryHealthChecker::new();
@@ -1430,4 +1814,69 @@ mod tests {
         assert_eq!(checker.health_url, "http://localhost/health");
         assert_eq!(checker.failure_threshold, 5);
     }
+
+    fn llm_config(openai: Option<&str>, anthropic: Option<&str>) -> LlmConfig {
+        LlmConfig {
+            openai_api_key: openai.map(String::from),
+            anthropic_api_key: anthropic.map(String::from),
+            ..LlmConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_llm_checkers_only_for_configured_providers() {
+        let checkers = LlmProviderHealthChecker::from_config(&llm_config(None, Some("sk-ant")), Duration::from_secs(2));
+        assert_eq!(checkers.len(), 1);
+        assert_eq!(checkers[0].name(), "anthropic_api");
+        assert_eq!(checkers[0].config.timeout, Duration::from_secs(2));
+
+        assert!(LlmProviderHealthChecker::from_config(&llm_config(None, None), Duration::from_secs(2)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_llm_checker_statuses() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("authorization", "Bearer good"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"data":[]}"#))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .and(header("x-api-key", "good"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(100)))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+
+        let openai = |key: &str| LlmProviderHealthChecker::new(LlmProvider::OpenAi, key).with_base_url(server.uri());
+
+        let health = openai("good").check().await;
+        assert_eq!(health.status, HealthStatus::Healthy);
+
+        let health = openai("bad").check().await;
+        assert_eq!(health.status, HealthStatus::Unhealthy);
+        assert_eq!(health.error.as_deref(), Some("API key rejected"));
+
+        let slow = LlmProviderHealthChecker::new(LlmProvider::Anthropic, "good")
+            .with_base_url(server.uri())
+            .with_latency_threshold(50)
+            .check()
+            .await;
+        assert_eq!(slow.status, HealthStatus::Degraded);
+
+        let unreachable = openai("good")
+            .with_base_url("http://127.0.0.1:1")
+            .check()
+            .await;
+        assert_eq!(unreachable.status, HealthStatus::Unhealthy);
+    }
 }
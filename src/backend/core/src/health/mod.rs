@@ -23,6 +23,24 @@ pub struct HealthConfig {
     pub include_details: bool,
     /// Components to check
     pub components: Vec<String>,
+    /// Path on the data volume whose filesystem the disk check reads
+    pub disk_path: String,
+    /// Disk usage percentage at which `disk_space` is degraded
+    pub disk_warning_pct: f64,
+    /// Disk usage percentage at which `disk_space` is unhealthy
+    pub disk_critical_pct: f64,
+    /// Memory usage percentage at which `memory` is degraded
+    pub memory_warning_pct: f64,
+    /// Memory usage percentage at which `memory` is unhealthy
+    pub memory_critical_pct: f64,
+    /// Where `database_backup` finds the most recent backup; `None` (the
+    /// default) leaves the check off, for deployments that record backups
+    /// nowhere it can see
+    pub backup_source: Option<BackupSource>,
+    /// Age at which the most recent backup is stale (unhealthy)
+    pub backup_max_age: std::time::Duration,
+    /// Age at which the most recent backup is nearly stale (degraded)
+    pub backup_warning_age: std::time::Duration,
 }
 
 impl Default for HealthConfig {
@@ -36,8 +54,15 @@ impl Default for HealthConfig {
                 "workers".into(),
                 "disk_space".into(),
                 "memory".into(),
-                "database_backup".into(),
             ],
+            disk_path: "/".into(),
+            disk_warning_pct: 80.0,
+            disk_critical_pct: 95.0,
+            memory_warning_pct: 85.0,
+            memory_critical_pct: 95.0,
+            backup_source: None,
+            backup_max_age: std::time::Duration::from_secs(24 * 3600),
+            backup_warning_age: std::time::Duration::from_secs(20 * 3600),
         }
     }
 }
@@ -62,6 +87,42 @@ impl HealthService {
         self.checkers.push(checker);
     }
 
+    /// Register the `disk_space` and `memory` checkers with the configured
+    /// path and thresholds.
+    pub fn register_system_checkers(&mut self) {
+        let disk = DiskSpaceHealthChecker::new(self.config.disk_path.clone())
+            .with_warning_threshold(self.config.disk_warning_pct)
+            .with_critical_threshold(self.config.disk_critical_pct);
+        let memory = MemoryHealthChecker::new()
+            .with_warning_threshold(self.config.memory_warning_pct)
+            .with_critical_threshold(self.config.memory_critical_pct);
+        self.checkers.push(Arc::new(disk));
+        self.checkers.push(Arc::new(memory));
+    }
+
+    /// Register the `database_backup` freshness checker with the configured
+    /// source and window. Does nothing unless a backup source is configured.
+    pub fn register_backup_checker(&mut self, pool: sqlx::PgPool) {
+        let Some(source) = self.config.backup_source.clone() else {
+            return;
+        };
+        let checker = BackupHealthChecker::new(pool)
+            .with_source(source)
+            .with_max_age(self.config.backup_max_age)
+            .with_warning_age(self.config.backup_warning_age);
+        self.config.components.push(checker.name().to_string());
+        self.checkers.push(Arc::new(checker));
+    }
+
+    /// Register a [`LlmProviderHealthChecker`] for each LLM provider with an
+    /// API key, so unconfigured providers never affect readiness.
+    pub fn register_llm_providers(&mut self, llm: &crate::config::LlmConfig) {
+        for checker in LlmProviderHealthChecker::from_config(llm, self.config.check_timeout) {
+            self.config.components.push(checker.name().to_string());
+            self.checkers.push(Arc::new(checker));
+        }
+    }
+
     /// Run all health checks concurrently with timeout per check.
     pub async fn check_health(&self) -> HealthReport {
         let futures: Vec<_> = self
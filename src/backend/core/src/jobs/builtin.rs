@@ -1,23 +1,101 @@
 //! Built-in background jobs.
 
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use super::{Job, JobContext, JobResult, JobPriority, RetryPolicy};
+use super::{Job, JobContext, JobError, JobResult, JobPriority, RetryPolicy};
+use super::reports::{ReportPeriod, ReportSink, UsageReport, UsageReportStore};
+use crate::config::RetentionConfig;
+use crate::db::{Database, ExpiredApproval};
+use crate::events::{Aggregate, AgentAggregate, DagAggregate, EventStore, TaskAggregate};
+use crate::orchestrator::{RequeuedTask, SwarmOrchestrator};
+use crate::websocket::WebSocketState;
+
+/// Where [`CleanupExpiredApprovalsJob`] finds and expires approvals.
+#[async_trait]
+pub trait ApprovalSweeper: Send + Sync {
+    /// Expire pending approvals past their deadline (or older than
+    /// `max_age_hours` when they have none), fail their blocked tasks, and
+    /// return what was expired.
+    async fn expire_approvals(&self, max_age_hours: u64) -> crate::error::Result<Vec<ExpiredApproval>>;
+}
+
+#[async_trait]
+impl ApprovalSweeper for Database {
+    async fn expire_approvals(&self, max_age_hours: u64) -> crate::error::Result<Vec<ExpiredApproval>> {
+        Database::expire_approvals(self, max_age_hours).await
+    }
+}
+
+#[async_trait]
+impl ApprovalSweeper for SwarmOrchestrator {
+    async fn expire_approvals(&self, max_age_hours: u64) -> crate::error::Result<Vec<ExpiredApproval>> {
+        SwarmOrchestrator::expire_approvals(self, max_age_hours).await
+    }
+}
 
-/// Job: Clean up expired approval requests.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Job: Expire approval requests nobody decided in time.
+///
+/// Expired approvals are denied, the tasks blocked on them fail and are
+/// woken, and each expiry is announced on the approvals WebSocket room.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CleanupExpiredApprovalsJob {
-    /// Maximum age of approvals to keep (hours)
+    /// Age after which approvals without an explicit deadline expire (hours)
     pub max_age_hours: u64,
+
+    #[serde(skip)]
+    sweeper: Option<Arc<dyn ApprovalSweeper>>,
+
+    #[serde(skip)]
+    ws: Option<Arc<WebSocketState>>,
 }
 
 impl CleanupExpiredApprovalsJob {
     pub fn new() -> Self {
         Self {
             max_age_hours: 24,
+            sweeper: None,
+            ws: None,
         }
     }
+
+    /// Expire approvals in this database. Nothing waiting on them in
+    /// process is woken; prefer [`Self::with_orchestrator`].
+    pub fn with_database(self, db: Arc<Database>) -> Self {
+        self.with_sweeper(db)
+    }
+
+    /// Expire approvals through the orchestrator, which also wakes the tasks
+    /// waiting on them.
+    pub fn with_orchestrator(self, orchestrator: Arc<SwarmOrchestrator>) -> Self {
+        self.with_sweeper(orchestrator)
+    }
+
+    /// Expire approvals through a custom sweeper.
+    pub fn with_sweeper(mut self, sweeper: Arc<dyn ApprovalSweeper>) -> Self {
+        self.sweeper = Some(sweeper);
+        self
+    }
+
+    /// Broadcast expiries to the approvals room.
+    pub fn with_websocket(mut self, ws: Arc<WebSocketState>) -> Self {
+        self.ws = Some(ws);
+        self
+    }
+}
+
+impl std::fmt::Debug for CleanupExpiredApprovalsJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CleanupExpiredApprovalsJob")
+            .field("max_age_hours", &self.max_age_hours)
+            .field("sweeper", &self.sweeper.is_some())
+            .field("ws", &self.ws.is_some())
+            .finish()
+    }
 }
 
 impl Default for CleanupExpiredApprovalsJob {
@@ -33,12 +111,26 @@ impl Job for CleanupExpiredApprovalsJob {
     }
 
     async fn execute(&self, ctx: &JobContext) -> JobResult {
-        ctx.log_info(&format!(
-            "Cleaning up approvals older than {} hours",
-            self.max_age_hours
-        ));
-        // In production: DELETE FROM approvals WHERE expires_at < NOW()
-        ctx.report_progress(100, Some("Cleanup complete".to_string())).await;
+        let sweeper = self.sweeper.as_ref()
+            .ok_or_else(|| JobError::fatal("cleanup_expired_approvals requires an orchestrator or database"))?;
+
+        let expired = sweeper.expire_approvals(self.max_age_hours)
+            .await
+            .map_err(|e| JobError::retryable(format!("Failed to expire approvals: {}", e)))?;
+
+        for approval in &expired {
+            tracing::info!(
+                approval_id = %approval.id,
+                task_id = %approval.task_id,
+                task_failed = approval.task_failed,
+                "Approval expired"
+            );
+            if let Some(ws) = &self.ws {
+                ws.send_approval_expired(approval.id.to_string()).await;
+            }
+        }
+
+        ctx.report_progress(100, Some(format!("Expired {} approvals", expired.len()))).await;
         Ok(())
     }
 
@@ -47,19 +139,120 @@ impl Job for CleanupExpiredApprovalsJob {
     }
 }
 
+/// Where [`RequeueStuckTasksJob`] finds and requeues stuck tasks.
+#[async_trait]
+pub trait StuckTaskSweeper: Send + Sync {
+    /// Requeue running tasks without a result for at least `stuck_after`
+    /// and return what was requeued.
+    async fn requeue_stuck_tasks(&self, stuck_after: std::time::Duration) -> Vec<RequeuedTask>;
+}
+
+#[async_trait]
+impl StuckTaskSweeper for SwarmOrchestrator {
+    async fn requeue_stuck_tasks(&self, stuck_after: std::time::Duration) -> Vec<RequeuedTask> {
+        SwarmOrchestrator::requeue_stuck_tasks(self, stuck_after).await
+    }
+}
+
+/// Job: Requeue tasks whose worker died mid-task.
+///
+/// A task stuck in `Running` would otherwise wedge its DAG until the result
+/// timeout; requeued tasks are picked up by another worker.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RequeueStuckTasksJob {
+    /// Seconds a task may run without a result before it is requeued
+    pub stuck_after_secs: u64,
+
+    #[serde(skip)]
+    sweeper: Option<Arc<dyn StuckTaskSweeper>>,
+}
+
+impl RequeueStuckTasksJob {
+    pub fn new(stuck_after_secs: u64) -> Self {
+        Self {
+            stuck_after_secs,
+            sweeper: None,
+        }
+    }
+
+    /// Requeue tasks of this orchestrator's DAGs.
+    pub fn with_orchestrator(self, orchestrator: Arc<SwarmOrchestrator>) -> Self {
+        self.with_sweeper(orchestrator)
+    }
+
+    /// Requeue tasks through a custom sweeper.
+    pub fn with_sweeper(mut self, sweeper: Arc<dyn StuckTaskSweeper>) -> Self {
+        self.sweeper = Some(sweeper);
+        self
+    }
+}
+
+impl std::fmt::Debug for RequeueStuckTasksJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RequeueStuckTasksJob")
+            .field("stuck_after_secs", &self.stuck_after_secs)
+            .field("sweeper", &self.sweeper.is_some())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Job for RequeueStuckTasksJob {
+    fn name(&self) -> &'static str {
+        "requeue_stuck_tasks"
+    }
+
+    async fn execute(&self, ctx: &JobContext) -> JobResult {
+        let sweeper = self.sweeper.as_ref()
+            .ok_or_else(|| JobError::fatal("requeue_stuck_tasks requires an orchestrator"))?;
+
+        let requeued = sweeper
+            .requeue_stuck_tasks(std::time::Duration::from_secs(self.stuck_after_secs))
+            .await;
+
+        ctx.report_progress(100, Some(format!("Requeued {} stuck tasks", requeued.len()))).await;
+        Ok(())
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy::no_retry()
+    }
+}
+
 /// Job: Aggregate metrics from recent task executions.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Writes one `agent_stats_history` snapshot per agent covering the window.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AggregateMetricsJob {
     /// Time window for aggregation (minutes)
     pub window_minutes: u64,
+
+    #[serde(skip)]
+    db: Option<Arc<Database>>,
 }
 
 impl AggregateMetricsJob {
     pub fn new() -> Self {
         Self {
             window_minutes: 60,
+            db: None,
         }
     }
+
+    /// Write snapshots to this database.
+    pub fn with_database(mut self, db: Arc<Database>) -> Self {
+        self.db = Some(db);
+        self
+    }
+}
+
+impl std::fmt::Debug for AggregateMetricsJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AggregateMetricsJob")
+            .field("window_minutes", &self.window_minutes)
+            .field("db", &self.db.is_some())
+            .finish()
+    }
 }
 
 impl Default for AggregateMetricsJob {
@@ -79,8 +272,14 @@ impl Job for AggregateMetricsJob {
             "Aggregating metrics for the last {} minutes",
             self.window_minutes
         ));
-        // In production: Run aggregation queries and update summary tables
-        ctx.report_progress(100, Some("Metrics aggregated".to_string())).await;
+        let db = self.db.as_ref()
+            .ok_or_else(|| JobError::fatal("aggregate_metrics requires a database"))?;
+
+        let snapshots = db.snapshot_agent_stats(self.window_minutes.min(i32::MAX as u64) as i32)
+            .await
+            .map_err(|e| JobError::retryable(format!("Failed to snapshot agent stats: {}", e)))?;
+
+        ctx.report_progress(100, Some(format!("Recorded {} agent snapshots", snapshots))).await;
         Ok(())
     }
 
@@ -89,19 +288,67 @@ impl Job for AggregateMetricsJob {
     }
 }
 
-/// Job: Send usage reports via email/webhook.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Job: Generate the usage report for the last complete period and deliver it.
+///
+/// The report is sent to every sink, then saved to the store, where the API
+/// serves it. A period whose report is already saved is skipped, so the job
+/// can run more often than its period; a failed delivery is retried as a
+/// whole, which may send the report twice to sinks that already got it.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SendUsageReportsJob {
-    /// Report period (e.g., "daily", "weekly")
+    /// Report period: "daily", "weekly" or "monthly"
     pub period: String,
+
+    #[serde(skip)]
+    store: Option<Arc<dyn UsageReportStore>>,
+
+    #[serde(skip)]
+    sinks: Vec<Arc<dyn ReportSink>>,
 }
 
 impl SendUsageReportsJob {
+    /// A job for `period`, a [`ReportPeriod`] or its name. An unknown name
+    /// fails the job when it runs.
     pub fn new(period: impl Into<String>) -> Self {
         Self {
             period: period.into(),
+            store: None,
+            sinks: Vec::new(),
         }
     }
+
+    /// Read usage from and save reports to this database.
+    pub fn with_database(self, db: Arc<Database>) -> Self {
+        self.with_store(db)
+    }
+
+    /// Read usage from and save reports to a custom store.
+    pub fn with_store(mut self, store: Arc<dyn UsageReportStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Also deliver each report to `sink`.
+    pub fn with_sink(mut self, sink: Arc<dyn ReportSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    /// Deliver each report to all of `sinks`.
+    pub fn with_sinks(mut self, sinks: impl IntoIterator<Item = Arc<dyn ReportSink>>) -> Self {
+        self.sinks.extend(sinks);
+        self
+    }
+}
+
+impl std::fmt::Debug for SendUsageReportsJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SendUsageReportsJob")
+            .field("period", &self.period)
+            .field("store", &self.store.is_some())
+            .field("sinks", &self.sinks.iter().map(|s| s.name()).collect::<Vec<_>>())
+            .finish()
+    }
 }
 
 #[async_trait]
@@ -111,9 +358,38 @@ impl Job for SendUsageReportsJob {
     }
 
     async fn execute(&self, ctx: &JobContext) -> JobResult {
-        ctx.log_info(&format!("Generating {} usage report", self.period));
-        // In production: Generate report, send via configured channels
-        ctx.report_progress(100, Some("Report sent".to_string())).await;
+        let store = self.store.as_ref()
+            .ok_or_else(|| JobError::fatal("send_usage_reports requires a database"))?;
+        let period: ReportPeriod = self.period.parse().map_err(JobError::fatal)?;
+
+        let (start, end) = period.previous(Utc::now());
+        let exists = store.report_exists(period, start)
+            .await
+            .map_err(|e| JobError::retryable(format!("Failed to look up report: {}", e)))?;
+        if exists {
+            ctx.report_progress(100, Some(format!("{} report for {} already sent", period, start.date_naive()))).await;
+            return Ok(());
+        }
+
+        ctx.log_info(&format!("Generating {} usage report for {} to {}", period, start, end));
+        let usage = store.usage_by_organization(start, end)
+            .await
+            .map_err(|e| JobError::retryable(format!("Failed to aggregate usage: {}", e)))?;
+        let report = UsageReport::new(period, start, end, usage);
+        ctx.report_progress(25, Some(format!("Aggregated {} organizations", report.organizations.len()))).await;
+
+        for sink in &self.sinks {
+            sink.deliver(&report).await.map_err(|e| {
+                ctx.log_error(&format!("Failed to deliver report to {}: {}", sink.name(), e));
+                e
+            })?;
+        }
+
+        store.save_report(&report)
+            .await
+            .map_err(|e| JobError::retryable(format!("Failed to save report: {}", e)))?;
+
+        ctx.report_progress(100, Some(format!("Report {} sent to {} recipients", report.id, self.sinks.len()))).await;
         Ok(())
     }
 
@@ -122,19 +398,142 @@ impl Job for SendUsageReportsJob {
     }
 }
 
-/// Job: Clean up old log entries.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Where [`CleanupOldLogsJob`] prunes old rows.
+#[async_trait]
+pub trait RetentionStore: Send + Sync {
+    /// Snapshot up to `limit` aggregates of `aggregate_type` that have events
+    /// created before `before` not covered by a snapshot. Returns how many
+    /// were snapshotted; aggregate types that can't be snapshotted give 0.
+    async fn snapshot_stale(&self, aggregate_type: &str, before: DateTime<Utc>, limit: i64) -> crate::error::Result<u64>;
+
+    /// Delete snapshot-covered events of `aggregate_type` created before `before`.
+    async fn prune_events(&self, aggregate_type: &str, before: DateTime<Utc>, batch_size: i64) -> crate::error::Result<u64>;
+
+    /// Delete audit log entries created before `before`.
+    async fn prune_audit_log(&self, before: DateTime<Utc>, batch_size: i64) -> crate::error::Result<u64>;
+}
+
+#[async_trait]
+impl RetentionStore for Database {
+    async fn snapshot_stale(&self, aggregate_type: &str, before: DateTime<Utc>, limit: i64) -> crate::error::Result<u64> {
+        let store = EventStore::new(self.pool().clone());
+        let ids = store.unsnapshotted_before(aggregate_type, before, limit).await?;
+        for &id in &ids {
+            match aggregate_type {
+                TaskAggregate::AGGREGATE_TYPE => store.snapshot::<TaskAggregate>(id).await?,
+                AgentAggregate::AGGREGATE_TYPE => store.snapshot::<AgentAggregate>(id).await?,
+                DagAggregate::AGGREGATE_TYPE => store.snapshot::<DagAggregate>(id).await?,
+                _ => return Ok(0),
+            };
+        }
+        Ok(ids.len() as u64)
+    }
+
+    async fn prune_events(&self, aggregate_type: &str, before: DateTime<Utc>, batch_size: i64) -> crate::error::Result<u64> {
+        EventStore::new(self.pool().clone()).prune(aggregate_type, before, batch_size).await
+    }
+
+    async fn prune_audit_log(&self, before: DateTime<Utc>, batch_size: i64) -> crate::error::Result<u64> {
+        Database::prune_audit_log(self, before, batch_size).await
+    }
+}
+
+/// Aggregate types whose events are pruned even without a retention override.
+const PRUNED_AGGREGATE_TYPES: [&str; 3] = [
+    TaskAggregate::AGGREGATE_TYPE,
+    AgentAggregate::AGGREGATE_TYPE,
+    DagAggregate::AGGREGATE_TYPE,
+];
+
+/// Job: Delete audit log entries and events past their retention window.
+///
+/// Aggregates with old events are snapshotted first, and only events below
+/// the snapshot are deleted, so every aggregate can still be loaded.
+/// Deletes run in batches to keep lock times short.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CleanupOldLogsJob {
-    /// Maximum age of logs to keep (days)
+    /// Maximum age of logs to keep (days); also applies to events of
+    /// aggregate types without their own retention
     pub retention_days: u64,
+
+    /// Event retention per aggregate type (days)
+    pub aggregate_retention_days: HashMap<String, u64>,
+
+    /// Rows deleted (or aggregates snapshotted) per statement
+    pub batch_size: u32,
+
+    #[serde(skip)]
+    store: Option<Arc<dyn RetentionStore>>,
 }
 
 impl CleanupOldLogsJob {
     pub fn new() -> Self {
         Self {
             retention_days: 30,
+            aggregate_retention_days: HashMap::new(),
+            batch_size: 1000,
+            store: None,
+        }
+    }
+
+    /// Settings from the `retention` config section.
+    pub fn from_config(config: &RetentionConfig) -> Self {
+        Self {
+            retention_days: config.retention_days,
+            aggregate_retention_days: config.aggregate_retention_days.clone(),
+            batch_size: config.batch_size,
+            store: None,
         }
     }
+
+    /// Keep events of `aggregate_type` for `days` instead of `retention_days`.
+    pub fn with_aggregate_retention(mut self, aggregate_type: impl Into<String>, days: u64) -> Self {
+        self.aggregate_retention_days.insert(aggregate_type.into(), days);
+        self
+    }
+
+    /// Prune this database.
+    pub fn with_database(self, db: Arc<Database>) -> Self {
+        self.with_store(db)
+    }
+
+    /// Prune through a custom store.
+    pub fn with_store(mut self, store: Arc<dyn RetentionStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Retention for events of `aggregate_type` (days).
+    pub fn retention_for(&self, aggregate_type: &str) -> u64 {
+        self.aggregate_retention_days
+            .get(aggregate_type)
+            .copied()
+            .unwrap_or(self.retention_days)
+    }
+
+    /// Every aggregate type to prune, in a stable order.
+    fn aggregate_types(&self) -> Vec<&str> {
+        let mut types: Vec<&str> = PRUNED_AGGREGATE_TYPES.to_vec();
+        let mut extra: Vec<&str> = self.aggregate_retention_days
+            .keys()
+            .map(String::as_str)
+            .filter(|t| !types.contains(t))
+            .collect();
+        extra.sort_unstable();
+        types.extend(extra);
+        types
+    }
+}
+
+impl std::fmt::Debug for CleanupOldLogsJob {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CleanupOldLogsJob")
+            .field("retention_days", &self.retention_days)
+            .field("aggregate_retention_days", &self.aggregate_retention_days)
+            .field("batch_size", &self.batch_size)
+            .field("store", &self.store.is_some())
+            .finish()
+    }
 }
 
 impl Default for CleanupOldLogsJob {
@@ -150,12 +549,43 @@ impl Job for CleanupOldLogsJob {
     }
 
     async fn execute(&self, ctx: &JobContext) -> JobResult {
-        ctx.log_info(&format!(
-            "Cleaning up logs older than {} days",
-            self.retention_days
-        ));
-        // In production: DELETE FROM logs WHERE created_at < NOW() - interval
-        ctx.report_progress(100, Some("Log cleanup complete".to_string())).await;
+        let store = self.store.as_ref()
+            .ok_or_else(|| JobError::fatal("cleanup_old_logs requires a database"))?;
+        let now = Utc::now();
+        let batch_size = i64::from(self.batch_size.max(1));
+        let cutoff = |days: u64| now - chrono::Duration::days(days.min(i64::MAX as u64 / 86_400) as i64);
+
+        let mut snapshotted = 0;
+        let mut pruned_events = Vec::new();
+        for aggregate_type in self.aggregate_types() {
+            let before = cutoff(self.retention_for(aggregate_type));
+            loop {
+                let count = store.snapshot_stale(aggregate_type, before, batch_size)
+                    .await
+                    .map_err(|e| JobError::retryable(format!("Failed to snapshot {} aggregates: {}", aggregate_type, e)))?;
+                snapshotted += count;
+                if count < batch_size as u64 {
+                    break;
+                }
+            }
+            let pruned = store.prune_events(aggregate_type, before, batch_size)
+                .await
+                .map_err(|e| JobError::retryable(format!("Failed to prune {} events: {}", aggregate_type, e)))?;
+            pruned_events.push(format!("{} {}", pruned, aggregate_type));
+        }
+
+        let pruned_audit = store.prune_audit_log(cutoff(self.retention_days), batch_size)
+            .await
+            .map_err(|e| JobError::retryable(format!("Failed to prune audit log: {}", e)))?;
+
+        let summary = format!(
+            "Pruned events ({}) and {} audit entries; snapshotted {} aggregates",
+            pruned_events.join(", "),
+            pruned_audit,
+            snapshotted,
+        );
+        ctx.log_info(&summary);
+        ctx.report_progress(100, Some(summary)).await;
         Ok(())
     }
 
@@ -171,6 +601,238 @@ impl Job for CleanupOldLogsJob {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::Duration;
+    use tokio::sync::Mutex;
+    use uuid::Uuid;
+
+    use crate::db::OrganizationUsageRow;
+    use crate::jobs::JobMetadata;
+    use crate::websocket::{RoomId, ServerMessage};
+
+    /// Pending approvals with deadlines, kept in memory.
+    #[derive(Default)]
+    struct MemorySweeper {
+        pending: Mutex<Vec<(Uuid, Uuid, DateTime<Utc>)>>,
+    }
+
+    #[async_trait]
+    impl ApprovalSweeper for MemorySweeper {
+        async fn expire_approvals(&self, _max_age_hours: u64) -> crate::error::Result<Vec<ExpiredApproval>> {
+            let now = Utc::now();
+            let mut pending = self.pending.lock().await;
+            let (expired, kept): (Vec<_>, Vec<_>) = pending.drain(..).partition(|(_, _, at)| *at <= now);
+            *pending = kept;
+            Ok(expired
+                .into_iter()
+                .map(|(id, task_id, _)| ExpiredApproval { id, task_id, task_failed: true })
+                .collect())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_short_expiry_approval_is_swept() {
+        let short = (Uuid::new_v4(), Uuid::new_v4(), Utc::now() + Duration::milliseconds(20));
+        let long = (Uuid::new_v4(), Uuid::new_v4(), Utc::now() + Duration::hours(1));
+        let sweeper = Arc::new(MemorySweeper::default());
+        sweeper.pending.lock().await.extend([short, long]);
+
+        let ws = Arc::new(WebSocketState::with_defaults());
+        let mut approvals = ws.broadcaster.subscribe_to_room(RoomId::Approvals).await;
+        let job = CleanupExpiredApprovalsJob::new()
+            .with_sweeper(sweeper.clone())
+            .with_websocket(ws);
+
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+        let ctx = JobContext::new(JobMetadata::new(job.name()), job.retry_policy(), cancellation);
+        job.execute(&ctx).await.unwrap();
+
+        let remaining = sweeper.pending.lock().await.clone();
+        assert_eq!(remaining, vec![long]);
+
+        let broadcast = approvals.receiver.try_recv().unwrap();
+        assert!(matches!(
+            broadcast.message,
+            ServerMessage::ApprovalResult { request_id, approved: false, .. } if request_id == short.0.to_string()
+        ));
+        assert!(approvals.receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a migrated Postgres at DATABASE_URL"]
+    async fn test_expiry_wakes_waiting_task() {
+        use crate::db::tests::{insert_organization, insert_pending_approval, live_database};
+        use crate::orchestrator::ApprovalResolution;
+
+        let db = live_database().await;
+        let org = insert_organization(&db).await;
+        let (approval, _) = insert_pending_approval(&db, org, Duration::seconds(-1)).await;
+        let state = crate::api::tests::test_state().await;
+        let waiter = tokio::spawn({
+            let orchestrator = state.orchestrator.clone();
+            async move { orchestrator.approvals().wait(approval, std::time::Duration::from_secs(5)).await }
+        });
+        while state.orchestrator.approvals().waiting() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let job = CleanupExpiredApprovalsJob::new().with_orchestrator(state.orchestrator.clone());
+        let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+        let ctx = JobContext::new(JobMetadata::new(job.name()), job.retry_policy(), cancellation);
+        job.execute(&ctx).await.unwrap();
+
+        assert_eq!(waiter.await.unwrap(), Some(ApprovalResolution::Expired));
+    }
+
+    /// Running tasks with their start times, kept in memory.
+    #[derive(Default)]
+    struct MemoryStuckTasks {
+        running: Mutex<Vec<(Uuid, DateTime<Utc>)>>,
+    }
+
+    #[async_trait]
+    impl StuckTaskSweeper for MemoryStuckTasks {
+        async fn requeue_stuck_tasks(&self, stuck_after: std::time::Duration) -> Vec<RequeuedTask> {
+            let cutoff = Utc::now() - Duration::from_std(stuck_after).unwrap();
+            let mut running = self.running.lock().await;
+            let (stuck, kept): (Vec<_>, Vec<_>) = running.drain(..).partition(|(_, at)| *at <= cutoff);
+            *running = kept;
+            stuck
+                .into_iter()
+                .map(|(task_id, at)| RequeuedTask {
+                    task_id,
+                    dag_id: Uuid::nil(),
+                    retry_count: 1,
+                    stuck_secs: (Utc::now() - at).num_seconds() as u64,
+                })
+                .collect()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_stuck_tasks_are_requeued() {
+        let stuck = (Uuid::new_v4(), Utc::now() - Duration::minutes(10));
+        let fresh = (Uuid::new_v4(), Utc::now() - Duration::seconds(5));
+        let sweeper = Arc::new(MemoryStuckTasks::default());
+        sweeper.running.lock().await.extend([stuck, fresh]);
+
+        let job = RequeueStuckTasksJob::new(180).with_sweeper(sweeper.clone());
+        let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+        let ctx = JobContext::new(JobMetadata::new(job.name()), job.retry_policy(), cancellation);
+        job.execute(&ctx).await.unwrap();
+
+        assert_eq!(*sweeper.running.lock().await, vec![fresh]);
+    }
+
+    /// Usage and saved reports, kept in memory.
+    #[derive(Default)]
+    struct MemoryReportStore {
+        usage: Vec<OrganizationUsageRow>,
+        saved: Mutex<Vec<UsageReport>>,
+    }
+
+    #[async_trait]
+    impl UsageReportStore for MemoryReportStore {
+        async fn usage_by_organization(
+            &self,
+            _from: DateTime<Utc>,
+            _to: DateTime<Utc>,
+        ) -> crate::error::Result<Vec<OrganizationUsageRow>> {
+            Ok(self.usage.clone())
+        }
+
+        async fn report_exists(&self, period: ReportPeriod, period_start: DateTime<Utc>) -> crate::error::Result<bool> {
+            let saved = self.saved.lock().await;
+            Ok(saved.iter().any(|r| r.period == period && r.period_start == period_start))
+        }
+
+        async fn save_report(&self, report: &UsageReport) -> crate::error::Result<()> {
+            self.saved.lock().await.push(report.clone());
+            Ok(())
+        }
+    }
+
+    /// Records delivered reports; fails while `fail` is set.
+    #[derive(Default)]
+    struct RecordingSink {
+        delivered: Mutex<Vec<Uuid>>,
+        fail: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait]
+    impl ReportSink for RecordingSink {
+        fn name(&self) -> String {
+            "recording".to_string()
+        }
+
+        async fn deliver(&self, report: &UsageReport) -> Result<(), JobError> {
+            if self.fail.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(JobError::retryable("sink down"));
+            }
+            self.delivered.lock().await.push(report.id);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_usage_report_delivered_once_per_period() {
+        let store = Arc::new(MemoryReportStore {
+            usage: vec![OrganizationUsageRow {
+                organization_id: Some(Uuid::new_v4()),
+                organization_name: Some("Acme".to_string()),
+                tasks: 3,
+                completed_tasks: 2,
+                failed_tasks: 1,
+                tokens: 900,
+                cost: 0.42,
+            }],
+            ..Default::default()
+        });
+        let sink = Arc::new(RecordingSink::default());
+        sink.fail.store(true, std::sync::atomic::Ordering::SeqCst);
+        let job = SendUsageReportsJob::new(ReportPeriod::Daily)
+            .with_store(store.clone())
+            .with_sink(sink.clone());
+        let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+        let ctx = JobContext::new(JobMetadata::new(job.name()), job.retry_policy(), cancellation);
+
+        // Not saved until every recipient has it, so the retry sends it again
+        assert!(job.execute(&ctx).await.is_err());
+        assert!(store.saved.lock().await.is_empty());
+
+        sink.fail.store(false, std::sync::atomic::Ordering::SeqCst);
+        job.execute(&ctx).await.unwrap();
+        job.execute(&ctx).await.unwrap();
+
+        let saved = store.saved.lock().await;
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].totals.tokens, 900);
+        assert_eq!(saved[0].period_start, ReportPeriod::Daily.previous(Utc::now()).0);
+        assert_eq!(*sink.delivered.lock().await, vec![saved[0].id]);
+    }
+
+    #[tokio::test]
+    async fn test_usage_report_period_by_name() {
+        let store = Arc::new(MemoryReportStore::default());
+        let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+        let ctx = |job: &SendUsageReportsJob| JobContext::new(JobMetadata::new(job.name()), job.retry_policy(), cancellation.clone());
+
+        let job = SendUsageReportsJob::new("Weekly").with_store(store.clone());
+        job.execute(&ctx(&job)).await.unwrap();
+        assert_eq!(store.saved.lock().await[0].period, ReportPeriod::Weekly);
+
+        let job = SendUsageReportsJob::new("fortnightly").with_store(store.clone());
+        let err = job.execute(&ctx(&job)).await.unwrap_err();
+        assert!(!err.retryable);
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_approvals_requires_database() {
+        let job = CleanupExpiredApprovalsJob::new();
+        let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+        let ctx = JobContext::new(JobMetadata::new(job.name()), job.retry_policy(), cancellation);
+        assert!(job.execute(&ctx).await.is_err());
+    }
 
     #[test]
     fn test_cleanup_approvals_default() {
@@ -192,4 +854,101 @@ mod tests {
         assert_eq!(job.retention_days, 30);
         assert_eq!(job.name(), "cleanup_old_logs");
     }
+
+    /// Records the cutoff (in days ago) each call was made with.
+    #[derive(Default)]
+    struct RecordingRetentionStore {
+        calls: Mutex<Vec<(String, String, i64)>>,
+        stale: std::sync::atomic::AtomicU64,
+    }
+
+    impl RecordingRetentionStore {
+        async fn record(&self, call: &str, aggregate_type: &str, before: DateTime<Utc>) {
+            let days = (Utc::now() - before).num_hours() / 24;
+            self.calls.lock().await.push((call.to_string(), aggregate_type.to_string(), days));
+        }
+    }
+
+    #[async_trait]
+    impl RetentionStore for RecordingRetentionStore {
+        async fn snapshot_stale(&self, aggregate_type: &str, before: DateTime<Utc>, limit: i64) -> crate::error::Result<u64> {
+            self.record("snapshot", aggregate_type, before).await;
+            // Hand out the stale aggregates a batch at a time
+            let stale = self.stale.load(std::sync::atomic::Ordering::SeqCst);
+            let batch = stale.min(limit as u64);
+            self.stale.store(stale - batch, std::sync::atomic::Ordering::SeqCst);
+            Ok(batch)
+        }
+
+        async fn prune_events(&self, aggregate_type: &str, before: DateTime<Utc>, _batch_size: i64) -> crate::error::Result<u64> {
+            self.record("prune", aggregate_type, before).await;
+            Ok(5)
+        }
+
+        async fn prune_audit_log(&self, before: DateTime<Utc>, _batch_size: i64) -> crate::error::Result<u64> {
+            self.record("audit", "", before).await;
+            Ok(7)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_logs_per_aggregate_retention() {
+        let store = Arc::new(RecordingRetentionStore::default());
+        store.stale.store(3, std::sync::atomic::Ordering::SeqCst);
+        let mut job = CleanupOldLogsJob::new()
+            .with_aggregate_retention("task", 7)
+            .with_aggregate_retention("workflow", 365)
+            .with_store(store.clone());
+        job.batch_size = 2;
+        assert_eq!(job.retention_for("task"), 7);
+        assert_eq!(job.retention_for("agent"), 30);
+
+        let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+        let ctx = JobContext::new(JobMetadata::new(job.name()), job.retry_policy(), cancellation);
+        job.execute(&ctx).await.unwrap();
+
+        let calls = store.calls.lock().await.clone();
+        let call = |c: &str, t: &str, d: i64| (c.to_string(), t.to_string(), d);
+        assert_eq!(calls, vec![
+            // Three stale tasks take two snapshot batches before pruning
+            call("snapshot", "task", 7),
+            call("snapshot", "task", 7),
+            call("prune", "task", 7),
+            call("snapshot", "agent", 30),
+            call("prune", "agent", 30),
+            call("snapshot", "dag", 30),
+            call("prune", "dag", 30),
+            call("snapshot", "workflow", 365),
+            call("prune", "workflow", 365),
+            call("audit", "", 30),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_queued_cleanup_gets_store_at_run_time() {
+        use crate::jobs::{JobQueue, JobRegistry, JobWorker, WorkerConfig};
+
+        // The store isn't serialized with the job; the registry attaches it
+        let store = Arc::new(RecordingRetentionStore::default());
+        let registry = JobRegistry::new().register("cleanup_old_logs", {
+            let store = store.clone();
+            move |job: CleanupOldLogsJob| job.with_store(store.clone())
+        });
+        let queue = Arc::new(JobQueue::in_memory());
+        let config = WorkerConfig { poll_interval_ms: 10, ..Default::default() };
+        let handle = JobWorker::new(config).with_registry(registry).start(queue.clone());
+
+        queue.submit(&CleanupOldLogsJob::new().with_aggregate_retention("task", 7)).await.unwrap();
+        for _ in 0..100 {
+            if handle.stats().succeeded() == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert_eq!(handle.stats().succeeded(), 1);
+        let calls = store.calls.lock().await.clone();
+        assert_eq!(calls.first(), Some(&("snapshot".to_string(), "task".to_string(), 7)));
+        assert_eq!(calls.last(), Some(&("audit".to_string(), String::new(), 30)));
+        handle.shutdown();
+    }
 }
@@ -760,7 +760,7 @@ pub trait Job: Send + Sync {
     /// Serialize the job to JSON for storage.
     fn serialize(&self) -> Result<serde_json::Value>
     where
-        Self: Serialize,
+        Self: Serialize + Sized,
     {
         serde_json::to_value(self).map_err(|e| {
             ApexError::with_internal(
@@ -67,6 +67,7 @@ pub mod job;
 pub mod scheduler;
 pub mod queue;
 pub mod worker;
+pub mod reports;
 
 pub use job::{
     Job, JobContext, JobResult, JobStatus, JobError, JobMetadata,
@@ -74,20 +75,29 @@ pub use job::{
 };
 pub use scheduler::{
     JobScheduler, ScheduleSpec, ScheduledJob, CronSchedule, IntervalSchedule,
+    spawn_periodic,
 };
 pub use queue::{
-    JobQueue, QueueConfig, QueuedJob, DeadLetterQueue,
-    QueueStats, QueueBackend, InMemoryQueueBackend, RedisQueueBackend,
+    JobQueue, QueueConfig, QueuedJob, DeadLetter, DeadLetterQueue,
+    QueueStats, QueueBackend, InMemoryQueueBackend, OverflowPolicy, RedisQueueBackend,
 };
 pub use worker::{
-    JobWorker, WorkerConfig, WorkerStats, WorkerHandle,
+    JobWorker, JobRegistry, WorkerConfig, WorkerStats, WorkerHandle,
+};
+pub use reports::{
+    ReportPeriod, UsageReport, UsageTotals, UsageReportStore,
+    ReportSink, WebhookReportSink, DirectoryReportSink,
 };
 
 // Built-in jobs
 mod builtin;
 pub use builtin::{
+    ApprovalSweeper,
     CleanupExpiredApprovalsJob,
     AggregateMetricsJob,
     SendUsageReportsJob,
     CleanupOldLogsJob,
+    RetentionStore,
+    RequeueStuckTasksJob,
+    StuckTaskSweeper,
 };
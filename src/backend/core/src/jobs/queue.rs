@@ -5,10 +5,11 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::{BinaryHeap, VecDeque};
 use std::cmp::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, Semaphore};
 
-use super::JobMetadata;
+use super::{Job, JobId, JobMetadata, JobStatus};
 
 /// Configuration for the job queue.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,12 +84,26 @@ pub struct QueueStats {
     pub dead_letter: usize,
     /// Average wait time in seconds
     pub avg_wait_secs: f64,
+    /// How long the oldest pending job has waited, in seconds
+    #[serde(default)]
+    pub oldest_age_secs: f64,
+    /// Jobs that overflowed a full in-memory queue into its spill backend (total)
+    #[serde(default)]
+    pub spilled: u64,
+}
+
+/// A job in the dead letter queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub job: QueuedJob,
+    /// When the job was dead-lettered
+    pub dead_at: DateTime<Utc>,
 }
 
 /// Dead letter queue for failed jobs.
 #[derive(Debug)]
 pub struct DeadLetterQueue {
-    jobs: VecDeque<QueuedJob>,
+    jobs: VecDeque<DeadLetter>,
     max_size: usize,
 }
 
@@ -104,7 +119,7 @@ impl DeadLetterQueue {
         if self.jobs.len() >= self.max_size {
             self.jobs.pop_front();
         }
-        self.jobs.push_back(job);
+        self.jobs.push_back(DeadLetter { job, dead_at: Utc::now() });
     }
 
     pub fn len(&self) -> usize {
@@ -116,7 +131,26 @@ impl DeadLetterQueue {
     }
 
     pub fn drain(&mut self) -> Vec<QueuedJob> {
-        self.jobs.drain(..).collect()
+        self.jobs.drain(..).map(|dead| dead.job).collect()
+    }
+
+    /// Up to `limit` dead jobs, most recently dead first.
+    pub fn list(&self, limit: usize) -> Vec<DeadLetter> {
+        self.jobs.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Remove and return the dead job with `job_id`.
+    pub fn take(&mut self, job_id: JobId) -> Option<DeadLetter> {
+        let index = self.jobs.iter().position(|dead| dead.job.metadata.id == job_id)?;
+        self.jobs.remove(index)
+    }
+
+    /// Drop jobs dead for longer than `older_than`, returning how many.
+    pub fn purge(&mut self, older_than: chrono::Duration) -> usize {
+        let cutoff = Utc::now() - older_than;
+        let before = self.jobs.len();
+        self.jobs.retain(|dead| dead.dead_at >= cutoff);
+        before - self.jobs.len()
     }
 }
 
@@ -141,10 +175,33 @@ pub trait QueueBackend: Send + Sync {
     }
 }
 
-/// In-memory queue backend for testing and development.
+/// What a bounded [`InMemoryQueueBackend`] does with jobs enqueued while
+/// it is full.
+#[derive(Clone, Default)]
+pub enum OverflowPolicy {
+    /// Make `enqueue` wait until a job is dequeued
+    #[default]
+    Backpressure,
+    /// Enqueue the job on another backend, typically a
+    /// [`RedisQueueBackend`]; spilled jobs move back into memory as room
+    /// frees up
+    Spill(Arc<dyn QueueBackend>),
+}
+
+/// In-memory queue backend.
+///
+/// Unbounded by default; with a capacity, a burst of jobs either waits or
+/// overflows into a spill backend per its [`OverflowPolicy`], instead of
+/// growing the heap without limit. Priority order holds among the jobs in
+/// memory; spilled jobs come back in the spill backend's order.
 pub struct InMemoryQueueBackend {
     queue: Arc<RwLock<BinaryHeap<QueuedJob>>>,
     stats: Arc<RwLock<QueueStats>>,
+    /// Free places in memory, if bounded
+    slots: Option<Arc<Semaphore>>,
+    overflow: OverflowPolicy,
+    /// Jobs in the spill backend not yet moved back
+    spilled: AtomicUsize,
 }
 
 impl InMemoryQueueBackend {
@@ -152,8 +209,50 @@ impl InMemoryQueueBackend {
         Self {
             queue: Arc::new(RwLock::new(BinaryHeap::new())),
             stats: Arc::new(RwLock::new(QueueStats::default())),
+            slots: None,
+            overflow: OverflowPolicy::Backpressure,
+            spilled: AtomicUsize::new(0),
         }
     }
+
+    /// Hold at most `capacity` jobs in memory (0 = unlimited).
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.slots = (capacity > 0).then(|| Arc::new(Semaphore::new(capacity)));
+        self
+    }
+
+    /// What to do with jobs enqueued while the queue is full.
+    pub fn with_overflow(mut self, overflow: OverflowPolicy) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    /// Push a job that already holds a slot.
+    async fn push(&self, job: QueuedJob) {
+        let mut queue = self.queue.write().await;
+        queue.push(job);
+        self.stats.write().await.pending = queue.len() + self.spilled.load(AtomicOrdering::SeqCst);
+    }
+
+    /// Take the slot freed by a dequeue: move the next spilled job into it,
+    /// or release it.
+    async fn refill(&self, slots: &Semaphore) {
+        if let OverflowPolicy::Spill(spill) = &self.overflow {
+            if self.spilled.load(AtomicOrdering::SeqCst) > 0 {
+                match spill.dequeue().await {
+                    Ok(Some(job)) => {
+                        self.spilled.fetch_sub(1, AtomicOrdering::SeqCst);
+                        self.push(job).await;
+                        return;
+                    }
+                    Ok(None) => {}
+                    // The job is still spilled; a later dequeue retries
+                    Err(e) => tracing::warn!(error = %e, "Failed to move spilled job back into memory"),
+                }
+            }
+        }
+        slots.add_permits(1);
+    }
 }
 
 impl Default for InMemoryQueueBackend {
@@ -165,18 +264,40 @@ impl Default for InMemoryQueueBackend {
 #[async_trait]
 impl QueueBackend for InMemoryQueueBackend {
     async fn enqueue(&self, job: QueuedJob) -> crate::error::Result<()> {
-        let mut queue = self.queue.write().await;
-        let mut stats = self.stats.write().await;
-        queue.push(job);
-        stats.pending = queue.len();
+        if let Some(slots) = &self.slots {
+            match &self.overflow {
+                OverflowPolicy::Backpressure => {
+                    slots.acquire().await.map_err(|e| crate::error::ApexError::internal(e.to_string()))?.forget();
+                }
+                OverflowPolicy::Spill(spill) => match slots.try_acquire() {
+                    Ok(permit) => permit.forget(),
+                    Err(_) => {
+                        tracing::debug!(job_id = %job.metadata.id, "In-memory queue full, spilling job");
+                        spill.enqueue(job).await?;
+                        self.spilled.fetch_add(1, AtomicOrdering::SeqCst);
+                        let mut stats = self.stats.write().await;
+                        stats.spilled += 1;
+                        stats.pending += 1;
+                        return Ok(());
+                    }
+                },
+            }
+        }
+        self.push(job).await;
         Ok(())
     }
 
     async fn dequeue(&self) -> crate::error::Result<Option<QueuedJob>> {
-        let mut queue = self.queue.write().await;
+        let job = self.queue.write().await.pop();
+        if job.is_some() {
+            if let Some(slots) = &self.slots {
+                self.refill(slots).await;
+            }
+        }
+
+        let queued = self.queue.read().await.len();
         let mut stats = self.stats.write().await;
-        let job = queue.pop();
-        stats.pending = queue.len();
+        stats.pending = queued + self.spilled.load(AtomicOrdering::SeqCst);
         if job.is_some() {
             stats.running += 1;
         }
@@ -184,11 +305,14 @@ impl QueueBackend for InMemoryQueueBackend {
     }
 
     async fn stats(&self) -> crate::error::Result<QueueStats> {
-        Ok(self.stats.read().await.clone())
+        let mut stats = self.stats.read().await.clone();
+        let oldest = self.queue.read().await.iter().map(|job| job.enqueued_at).min();
+        stats.oldest_age_secs = oldest.map_or(0.0, |at| (Utc::now() - at).num_milliseconds().max(0) as f64 / 1000.0);
+        Ok(stats)
     }
 
     async fn len(&self) -> crate::error::Result<usize> {
-        Ok(self.queue.read().await.len())
+        Ok(self.queue.read().await.len() + self.spilled.load(AtomicOrdering::SeqCst))
     }
 }
 
@@ -292,6 +416,21 @@ impl QueueBackend for RedisQueueBackend {
                 e.to_string(),
             ))?;
 
+        // The list is FIFO, so its head has waited longest
+        let head: Option<String> = redis::cmd("LINDEX")
+            .arg(&self.queue_key)
+            .arg(0_i64)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| crate::error::ApexError::with_internal(
+                crate::error::ErrorCode::CacheError,
+                "Failed to get Redis queue stats",
+                e.to_string(),
+            ))?;
+        let oldest_age_secs = head
+            .and_then(|value| serde_json::from_str::<QueuedJob>(&value).ok())
+            .map_or(0.0, |job| (Utc::now() - job.enqueued_at).num_milliseconds().max(0) as f64 / 1000.0);
+
         Ok(QueueStats {
             pending,
             running: 0,
@@ -299,6 +438,8 @@ impl QueueBackend for RedisQueueBackend {
             failed: 0,
             dead_letter: 0,
             avg_wait_secs: 0.0,
+            oldest_age_secs,
+            spilled: 0,
         })
     }
 
@@ -339,11 +480,13 @@ impl JobQueue {
         }
     }
 
-    /// Create a new in-memory job queue (for testing).
+    /// Create a new in-memory job queue, bounded by the default `max_size`
+    /// with backpressure.
     pub fn in_memory() -> Self {
+        let config = QueueConfig::default();
         Self::new(
-            Arc::new(InMemoryQueueBackend::new()),
-            QueueConfig::default(),
+            Arc::new(InMemoryQueueBackend::new().with_capacity(config.max_size)),
+            config,
         )
     }
 
@@ -352,6 +495,18 @@ impl JobQueue {
         self.backend.enqueue(job).await
     }
 
+    /// Serialize `job` and enqueue it for a worker, returning its id.
+    pub async fn submit<J: Job + Serialize>(&self, job: &J) -> crate::error::Result<JobId> {
+        let mut metadata = JobMetadata::new(job.name()).with_priority(job.priority());
+        metadata.max_attempts = job.retry_policy().max_attempts;
+        metadata.timeout_secs = job.timeout_secs();
+        let id = metadata.id;
+        self.backend
+            .enqueue(QueuedJob { metadata, data: Job::serialize(job)?, enqueued_at: Utc::now() })
+            .await?;
+        Ok(id)
+    }
+
     /// Dequeue the next job.
     pub async fn dequeue(&self) -> crate::error::Result<Option<QueuedJob>> {
         self.backend.dequeue().await
@@ -364,6 +519,42 @@ impl JobQueue {
         }
     }
 
+    /// Up to `limit` dead-lettered jobs, most recent first.
+    pub async fn dead_letters(&self, limit: usize) -> Vec<DeadLetter> {
+        self.dead_letter.read().await.list(limit)
+    }
+
+    /// Move a dead-lettered job back onto the queue, as if it had never run:
+    /// its attempts are reset, so it gets its full retry budget again.
+    pub async fn replay(&self, job_id: JobId) -> crate::error::Result<QueuedJob> {
+        let dead = self
+            .dead_letter
+            .write()
+            .await
+            .take(job_id)
+            .ok_or_else(|| crate::error::ApexError::not_found("Dead-lettered job", job_id.to_string()))?;
+
+        let mut job = dead.job.clone();
+        job.metadata.status = JobStatus::Pending;
+        job.metadata.attempts = 0;
+        job.metadata.started_at = None;
+        job.metadata.finished_at = None;
+        job.enqueued_at = Utc::now();
+
+        if let Err(e) = self.backend.enqueue(job.clone()).await {
+            // Keep it dead-lettered so the replay can be retried
+            self.dead_letter.write().await.jobs.push_back(dead);
+            return Err(e);
+        }
+        tracing::info!(job_id = %job_id, job_type = %job.metadata.job_type, "Replayed dead-lettered job");
+        Ok(job)
+    }
+
+    /// Drop dead-lettered jobs older than `older_than`, returning how many.
+    pub async fn purge_dead_letters(&self, older_than: chrono::Duration) -> usize {
+        self.dead_letter.write().await.purge(older_than)
+    }
+
     /// Get queue statistics.
     pub async fn stats(&self) -> crate::error::Result<QueueStats> {
         let mut stats = self.backend.stats().await?;
@@ -416,4 +607,88 @@ mod tests {
         let first = queue.dequeue().await.unwrap().unwrap();
         assert_eq!(first.metadata.job_type, "high");
     }
+
+    fn job(job_type: &str) -> QueuedJob {
+        QueuedJob {
+            metadata: JobMetadata::new(job_type),
+            data: serde_json::json!({}),
+            enqueued_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dead_letter_replay_and_purge() {
+        let queue = JobQueue::in_memory();
+        let mut failed = job("sync");
+        failed.metadata.status = JobStatus::Dead;
+        failed.metadata.attempts = 3;
+        failed.metadata.last_error = Some("upstream 500".to_string());
+        let id = failed.metadata.id;
+        queue.dead_letter(failed).await;
+        queue.dead_letter(job("other")).await;
+
+        let listed = queue.dead_letters(10).await;
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].job.metadata.job_type, "other");
+        assert_eq!(queue.dead_letters(1).await.len(), 1);
+
+        let replayed = queue.replay(id).await.unwrap();
+        assert_eq!((replayed.metadata.attempts, replayed.metadata.status), (0, JobStatus::Pending));
+        let dequeued = queue.dequeue().await.unwrap().unwrap();
+        assert_eq!(dequeued.metadata.id, id);
+        assert_eq!(dequeued.metadata.last_error.as_deref(), Some("upstream 500"));
+        assert_eq!(queue.stats().await.unwrap().dead_letter, 1);
+        assert!(queue.replay(id).await.is_err());
+
+        assert_eq!(queue.purge_dead_letters(chrono::Duration::hours(1)).await, 0);
+        assert_eq!(queue.purge_dead_letters(chrono::Duration::zero()).await, 1);
+        assert!(queue.dead_letters(10).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bounded_queue_applies_backpressure() {
+        let queue = Arc::new(InMemoryQueueBackend::new().with_capacity(2));
+        queue.enqueue(job("a")).await.unwrap();
+        queue.enqueue(job("b")).await.unwrap();
+
+        // Full: the third enqueue waits for a dequeue
+        let blocked = tokio::spawn({
+            let queue = queue.clone();
+            async move { queue.enqueue(job("c")).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(!blocked.is_finished());
+        assert_eq!(queue.len().await.unwrap(), 2);
+
+        queue.dequeue().await.unwrap().unwrap();
+        blocked.await.unwrap().unwrap();
+        assert_eq!(queue.len().await.unwrap(), 2);
+        assert!(queue.stats().await.unwrap().oldest_age_secs >= 0.02);
+    }
+
+    #[tokio::test]
+    async fn test_bounded_queue_spills_overflow() {
+        let spill = Arc::new(InMemoryQueueBackend::new());
+        let queue = InMemoryQueueBackend::new()
+            .with_capacity(2)
+            .with_overflow(OverflowPolicy::Spill(spill.clone()));
+        for job_type in ["a", "b", "c", "d"] {
+            queue.enqueue(job(job_type)).await.unwrap();
+        }
+
+        let stats = queue.stats().await.unwrap();
+        assert_eq!((stats.pending, stats.spilled), (4, 2));
+        assert_eq!(spill.len().await.unwrap(), 2);
+
+        // Each dequeue pulls a spilled job back into memory
+        let mut dequeued = vec![];
+        while let Some(job) = queue.dequeue().await.unwrap() {
+            dequeued.push(job.metadata.job_type);
+            assert!(queue.queue.read().await.len() <= 2);
+        }
+        dequeued.sort();
+        assert_eq!(dequeued, ["a", "b", "c", "d"]);
+        assert!(spill.is_empty().await.unwrap());
+        assert_eq!(queue.stats().await.unwrap().pending, 0);
+    }
 }
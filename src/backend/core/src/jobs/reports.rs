@@ -0,0 +1,439 @@
+//! Usage reports: per-organization tokens, cost and task counts for a
+//! billing period, rendered as JSON and CSV and delivered to [`ReportSink`]s.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::JobError;
+use crate::db::{Database, OrganizationUsageRow, UsageReportRow};
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Period
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// How much time one report covers. Periods are aligned to UTC midnight,
+/// Monday and the first of the month respectively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportPeriod {
+    Daily,
+    Weekly,
+    #[default]
+    Monthly,
+}
+
+impl ReportPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Weekly => "weekly",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    /// The period containing `at`, as `[start, end)`.
+    pub fn bounds(&self, at: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let midnight = Utc
+            .with_ymd_and_hms(at.year(), at.month(), at.day(), 0, 0, 0)
+            .single()
+            .expect("UTC midnight is unambiguous");
+        match self {
+            Self::Daily => (midnight, midnight + Duration::days(1)),
+            Self::Weekly => {
+                let start = midnight - Duration::days(at.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(7))
+            }
+            Self::Monthly => {
+                let start = first_of_month(at.year(), at.month());
+                let end = if at.month() == 12 {
+                    first_of_month(at.year() + 1, 1)
+                } else {
+                    first_of_month(at.year(), at.month() + 1)
+                };
+                (start, end)
+            }
+        }
+    }
+
+    /// The last period that had fully ended by `now`.
+    pub fn previous(&self, now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+        let (current_start, _) = self.bounds(now);
+        self.bounds(current_start - Duration::seconds(1))
+    }
+}
+
+fn first_of_month(year: i32, month: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+        .single()
+        .expect("UTC midnight is unambiguous")
+}
+
+impl fmt::Display for ReportPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<ReportPeriod> for String {
+    fn from(period: ReportPeriod) -> Self {
+        period.as_str().to_string()
+    }
+}
+
+impl FromStr for ReportPeriod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            "monthly" => Ok(Self::Monthly),
+            other => Err(format!("unknown report period '{}'", other)),
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Report
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Usage of every organization over one period.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub id: Uuid,
+    pub period: ReportPeriod,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub organizations: Vec<OrganizationUsageRow>,
+    pub totals: UsageTotals,
+}
+
+/// Sums across all organizations in a report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub tasks: i64,
+    pub completed_tasks: i64,
+    pub failed_tasks: i64,
+    pub tokens: i64,
+    pub cost: f64,
+}
+
+/// Header row of [`UsageReport::to_csv`].
+pub const CSV_HEADER: &str =
+    "organization_id,organization_name,tasks,completed_tasks,failed_tasks,tokens,cost";
+
+impl UsageReport {
+    pub fn new(
+        period: ReportPeriod,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        organizations: Vec<OrganizationUsageRow>,
+    ) -> Self {
+        let totals = organizations.iter().fold(UsageTotals::default(), |mut t, org| {
+            t.tasks += org.tasks;
+            t.completed_tasks += org.completed_tasks;
+            t.failed_tasks += org.failed_tasks;
+            t.tokens += org.tokens;
+            t.cost += org.cost;
+            t
+        });
+        Self {
+            id: Uuid::new_v4(),
+            period,
+            period_start,
+            period_end,
+            generated_at: Utc::now(),
+            organizations,
+            totals,
+        }
+    }
+
+    /// One line per organization under [`CSV_HEADER`]. Cost is in dollars
+    /// with six decimals, matching the precision it is stored at.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(CSV_HEADER);
+        csv.push('\n');
+        for org in &self.organizations {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{:.6}\n",
+                org.organization_id.map(|id| id.to_string()).unwrap_or_default(),
+                csv_field(org.organization_name.as_deref().unwrap_or_default()),
+                org.tasks,
+                org.completed_tasks,
+                org.failed_tasks,
+                org.tokens,
+                org.cost,
+            ));
+        }
+        csv
+    }
+
+    /// File name stem shared by the JSON and CSV renderings.
+    pub fn file_stem(&self) -> String {
+        format!("usage-{}-{}", self.period, self.period_start.format("%Y-%m-%d"))
+    }
+
+    /// The row stored for retrieval through the API.
+    pub fn to_row(&self) -> Result<UsageReportRow, serde_json::Error> {
+        Ok(UsageReportRow {
+            id: self.id,
+            period: self.period.as_str().to_string(),
+            period_start: self.period_start,
+            period_end: self.period_end,
+            generated_at: self.generated_at,
+            report: serde_json::to_value(self)?,
+            csv: self.to_csv(),
+        })
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Storage
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Where usage is read from and generated reports are kept.
+#[async_trait]
+pub trait UsageReportStore: Send + Sync {
+    /// Per-organization usage for tasks created in `[from, to)`.
+    async fn usage_by_organization(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> crate::error::Result<Vec<OrganizationUsageRow>>;
+
+    /// Whether the report for this period was already generated.
+    async fn report_exists(&self, period: ReportPeriod, period_start: DateTime<Utc>) -> crate::error::Result<bool>;
+
+    /// Keep a generated report for retrieval.
+    async fn save_report(&self, report: &UsageReport) -> crate::error::Result<()>;
+}
+
+#[async_trait]
+impl UsageReportStore for Database {
+    async fn usage_by_organization(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> crate::error::Result<Vec<OrganizationUsageRow>> {
+        Database::usage_by_organization(self, from, to).await
+    }
+
+    async fn report_exists(&self, period: ReportPeriod, period_start: DateTime<Utc>) -> crate::error::Result<bool> {
+        self.usage_report_exists(period.as_str(), period_start).await
+    }
+
+    async fn save_report(&self, report: &UsageReport) -> crate::error::Result<()> {
+        self.insert_usage_report(&report.to_row()?).await
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Delivery
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A recipient of generated reports.
+#[async_trait]
+pub trait ReportSink: Send + Sync {
+    /// Short description for logs, e.g. the target URL.
+    fn name(&self) -> String;
+
+    async fn deliver(&self, report: &UsageReport) -> Result<(), JobError>;
+}
+
+/// POSTs each report as JSON to a webhook.
+pub struct WebhookReportSink {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WebhookReportSink {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(30))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[async_trait]
+impl ReportSink for WebhookReportSink {
+    fn name(&self) -> String {
+        format!("webhook {}", crate::config::redact_webhook_url(&self.url))
+    }
+
+    async fn deliver(&self, report: &UsageReport) -> Result<(), JobError> {
+        let response = self.client
+            .post(&self.url)
+            .json(report)
+            .send()
+            .await
+            .map_err(|e| JobError::retryable(format!("Webhook request failed: {}", e)))?;
+
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else if status.is_client_error() && status != reqwest::StatusCode::TOO_MANY_REQUESTS {
+            Err(JobError::fatal(format!("Webhook rejected the report: {}", status)))
+        } else {
+            Err(JobError::retryable(format!("Webhook returned {}", status)))
+        }
+    }
+}
+
+/// Writes `<stem>.json` and `<stem>.csv` into a directory, such as a mounted
+/// object storage bucket.
+pub struct DirectoryReportSink {
+    dir: PathBuf,
+}
+
+impl DirectoryReportSink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl ReportSink for DirectoryReportSink {
+    fn name(&self) -> String {
+        format!("directory {}", self.dir.display())
+    }
+
+    async fn deliver(&self, report: &UsageReport) -> Result<(), JobError> {
+        let json = serde_json::to_vec_pretty(report)
+            .map_err(|e| JobError::fatal(format!("Failed to serialize report: {}", e)))?;
+        let stem = report.file_stem();
+
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .map_err(|e| JobError::retryable(format!("Failed to create {}: {}", self.dir.display(), e)))?;
+        for (path, contents) in [
+            (self.dir.join(format!("{}.json", stem)), json),
+            (self.dir.join(format!("{}.csv", stem)), report.to_csv().into_bytes()),
+        ] {
+            tokio::fs::write(&path, contents)
+                .await
+                .map_err(|e| JobError::retryable(format!("Failed to write {}: {}", path.display(), e)))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sinks for the configured recipients.
+pub fn sinks_from_config(config: &crate::config::ReportsConfig) -> Vec<Arc<dyn ReportSink>> {
+    let mut sinks: Vec<Arc<dyn ReportSink>> = config.webhook_urls
+        .iter()
+        .map(|url| Arc::new(WebhookReportSink::new(url.clone())) as Arc<dyn ReportSink>)
+        .collect();
+    if let Some(dir) = &config.output_dir {
+        sinks.push(Arc::new(DirectoryReportSink::new(dir.clone())));
+    }
+    sinks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        s.parse().unwrap()
+    }
+
+    fn org(name: &str, tasks: i64, cost: f64) -> OrganizationUsageRow {
+        OrganizationUsageRow {
+            organization_id: Some(Uuid::new_v4()),
+            organization_name: Some(name.to_string()),
+            tasks,
+            completed_tasks: tasks - 1,
+            failed_tasks: 1,
+            tokens: tasks * 100,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        let now = at("2024-03-14T15:09:26Z");
+        assert_eq!(
+            ReportPeriod::Daily.previous(now),
+            (at("2024-03-13T00:00:00Z"), at("2024-03-14T00:00:00Z"))
+        );
+        // 2024-03-14 is a Thursday
+        assert_eq!(
+            ReportPeriod::Weekly.previous(now),
+            (at("2024-03-04T00:00:00Z"), at("2024-03-11T00:00:00Z"))
+        );
+        assert_eq!(
+            ReportPeriod::Monthly.previous(now),
+            (at("2024-02-01T00:00:00Z"), at("2024-03-01T00:00:00Z"))
+        );
+        assert_eq!(
+            ReportPeriod::Monthly.previous(at("2024-01-01T00:00:00Z")),
+            (at("2023-12-01T00:00:00Z"), at("2024-01-01T00:00:00Z"))
+        );
+        assert_eq!("Weekly".parse::<ReportPeriod>(), Ok(ReportPeriod::Weekly));
+        assert!("hourly".parse::<ReportPeriod>().is_err());
+    }
+
+    #[test]
+    fn test_report_totals_and_csv() {
+        let report = UsageReport::new(
+            ReportPeriod::Monthly,
+            at("2024-02-01T00:00:00Z"),
+            at("2024-03-01T00:00:00Z"),
+            vec![org("Acme, Inc.", 10, 1.5), org("Globex", 4, 0.25)],
+        );
+        assert_eq!(report.totals.tasks, 14);
+        assert_eq!(report.totals.tokens, 1400);
+        assert!((report.totals.cost - 1.75).abs() < 1e-9);
+        assert_eq!(report.file_stem(), "usage-monthly-2024-02-01");
+
+        let csv = report.to_csv();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].ends_with(",\"Acme, Inc.\",10,9,1,1000,1.500000"));
+        assert_eq!(lines.len(), 3);
+
+        let row = report.to_row().unwrap();
+        assert_eq!(row.period, "monthly");
+        assert_eq!(row.report["totals"]["tasks"], 14);
+    }
+
+    #[tokio::test]
+    async fn test_directory_sink_writes_json_and_csv() {
+        let dir = std::env::temp_dir().join(format!("apex-reports-{}", Uuid::new_v4()));
+        let report = UsageReport::new(
+            ReportPeriod::Daily,
+            at("2024-03-13T00:00:00Z"),
+            at("2024-03-14T00:00:00Z"),
+            vec![org("Acme", 2, 0.1)],
+        );
+
+        DirectoryReportSink::new(&dir).deliver(&report).await.unwrap();
+
+        let json = std::fs::read_to_string(dir.join("usage-daily-2024-03-13.json")).unwrap();
+        assert_eq!(serde_json::from_str::<UsageReport>(&json).unwrap(), report);
+        let csv = std::fs::read_to_string(dir.join("usage-daily-2024-03-13.csv")).unwrap();
+        assert_eq!(csv, report.to_csv());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
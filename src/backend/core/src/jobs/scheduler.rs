@@ -6,7 +6,7 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 
-use super::{JobId, JobPriority};
+use super::{Job, JobContext, JobId, JobMetadata, JobPriority};
 
 /// Cron-based schedule specification.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -197,9 +197,58 @@ impl JobScheduler {
     }
 }
 
+/// Run `job` in-process every `interval` (first run after one interval).
+///
+/// Failures are logged and the next tick runs as normal; abort the returned
+/// handle to stop.
+pub fn spawn_periodic<J: Job + 'static>(job: J, interval: Duration) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        loop {
+            ticker.tick().await;
+
+            let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+            let mut metadata = JobMetadata::new(job.name()).with_priority(job.priority());
+            metadata.attempts = 1;
+            let ctx = JobContext::new(metadata, job.retry_policy(), cancellation);
+
+            if let Err(e) = job.execute(&ctx).await {
+                ctx.log_error(&format!("Periodic run failed: {}", e));
+            }
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    struct CountingJob(Arc<AtomicU32>);
+
+    #[async_trait::async_trait]
+    impl Job for CountingJob {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        async fn execute(&self, _ctx: &JobContext) -> super::super::JobResult {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Err(super::super::JobError::retryable("keeps going"))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_spawn_periodic_runs_repeatedly() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let handle = spawn_periodic(CountingJob(runs.clone()), Duration::from_millis(20));
+
+        tokio::time::sleep(Duration::from_millis(110)).await;
+        handle.abort();
+        assert!(runs.load(Ordering::SeqCst) >= 2);
+    }
 
     #[tokio::test]
     async fn test_schedule_interval() {
@@ -1,11 +1,14 @@
 //! Job worker for concurrent job execution.
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
 use tokio::sync::Semaphore;
 
-use super::JobQueue;
+use super::{Job, JobContext, JobError, JobQueue, QueuedJob, RetryPolicy};
 
 /// Configuration for the job worker.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -84,9 +87,64 @@ impl WorkerHandle {
     }
 }
 
+/// Rebuilds queued jobs from their serialized data.
+type JobBuilder = dyn Fn(serde_json::Value) -> Result<Box<dyn Job>, JobError> + Send + Sync;
+
+/// The job types a worker knows how to run.
+///
+/// Stores and handles are skipped when a job is serialized, so each entry
+/// attaches them again when the job is rebuilt for a run.
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    builders: HashMap<&'static str, Arc<JobBuilder>>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run queued jobs of type `name` as `J`, passing each through `prepare`
+    /// after it is deserialized.
+    pub fn register<J, F>(mut self, name: &'static str, prepare: F) -> Self
+    where
+        J: Job + DeserializeOwned + 'static,
+        F: Fn(J) -> J + Send + Sync + 'static,
+    {
+        self.builders.insert(
+            name,
+            Arc::new(move |data| {
+                let job: J = serde_json::from_value(data)
+                    .map_err(|e| JobError::fatal(format!("Invalid job data: {}", e)))?;
+                Ok(Box::new(prepare(job)) as Box<dyn Job>)
+            }),
+        );
+        self
+    }
+
+    /// Whether jobs of type `name` can be run.
+    pub fn contains(&self, name: &str) -> bool {
+        self.builders.contains_key(name)
+    }
+
+    fn build(&self, job: &QueuedJob) -> Result<Box<dyn Job>, JobError> {
+        let builder = self.builders.get(job.metadata.job_type.as_str()).ok_or_else(|| {
+            JobError::fatal(format!("No handler registered for job type {}", job.metadata.job_type))
+        })?;
+        builder(job.data.clone())
+    }
+}
+
+impl std::fmt::Debug for JobRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JobRegistry").field("job_types", &self.builders.keys().collect::<Vec<_>>()).finish()
+    }
+}
+
 /// Job worker that processes jobs from a queue.
 pub struct JobWorker {
     config: WorkerConfig,
+    registry: JobRegistry,
     stats: WorkerStats,
 }
 
@@ -95,18 +153,31 @@ impl JobWorker {
     pub fn new(config: WorkerConfig) -> Self {
         Self {
             config,
+            registry: JobRegistry::new(),
             stats: WorkerStats::new(),
         }
     }
 
+    /// Set the job types this worker runs. Jobs of any other type are
+    /// dead-lettered.
+    pub fn with_registry(mut self, registry: JobRegistry) -> Self {
+        self.registry = registry;
+        self
+    }
+
     /// Start the worker, returning a handle for control.
-    pub fn start(self, _queue: Arc<JobQueue>) -> WorkerHandle {
+    ///
+    /// Up to `concurrency` jobs run at once. A failed job is re-enqueued after
+    /// its backoff while its retry policy allows, and dead-lettered after that.
+    pub fn start(self, queue: Arc<JobQueue>) -> WorkerHandle {
         let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(false);
         let stats = self.stats.clone();
         let config = self.config.clone();
+        let registry = Arc::new(self.registry);
+        let worker_stats = stats.clone();
 
         tokio::spawn(async move {
-            let _semaphore = Arc::new(Semaphore::new(config.concurrency));
+            let semaphore = Arc::new(Semaphore::new(config.concurrency.max(1)));
             let poll_interval = tokio::time::Duration::from_millis(config.poll_interval_ms);
 
             tracing::info!(
@@ -116,17 +187,28 @@ impl JobWorker {
             );
 
             loop {
-                tokio::select! {
-                    _ = shutdown_rx.changed() => {
-                        if *shutdown_rx.borrow() {
-                            tracing::info!(worker = %config.name, "Worker shutting down");
-                            break;
-                        }
-                    }
-                    _ = tokio::time::sleep(poll_interval) => {
-                        // Poll queue for jobs
-                        // In production, this would dequeue and execute jobs
+                let permit = tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    permit = semaphore.clone().acquire_owned() => permit.expect("worker semaphore is never closed"),
+                };
+
+                match queue.dequeue().await {
+                    Ok(Some(job)) => {
+                        let (queue, registry, stats) = (queue.clone(), registry.clone(), worker_stats.clone());
+                        tokio::spawn(async move {
+                            run_job(&queue, &registry, &stats, job).await;
+                            drop(permit);
+                        });
+                        continue;
                     }
+                    Ok(None) => {}
+                    Err(e) => tracing::warn!(worker = %config.name, error = %e, "Failed to dequeue job"),
+                }
+                drop(permit);
+
+                tokio::select! {
+                    _ = shutdown_rx.changed() => break,
+                    _ = tokio::time::sleep(poll_interval) => {}
                 }
             }
 
@@ -140,6 +222,75 @@ impl JobWorker {
     }
 }
 
+/// Run one attempt of `queued`, then complete, retry or dead-letter it.
+async fn run_job(queue: &Arc<JobQueue>, registry: &JobRegistry, stats: &WorkerStats, mut queued: QueuedJob) {
+    stats.active.fetch_add(1, Ordering::Relaxed);
+    queued.metadata.mark_running();
+
+    let (result, policy) = match registry.build(&queued) {
+        Ok(job) => {
+            let policy = job.retry_policy();
+            let (_cancel, cancellation) = tokio::sync::watch::channel(false);
+            let ctx = JobContext::new(queued.metadata.clone(), policy.clone(), cancellation);
+            let result = match job.before_execute(&ctx).await {
+                Ok(()) => match queued.metadata.timeout_secs.or(job.timeout_secs()) {
+                    Some(secs) => tokio::time::timeout(Duration::from_secs(secs), job.execute(&ctx))
+                        .await
+                        .unwrap_or_else(|_| Err(JobError::retryable(format!("Timed out after {}s", secs)))),
+                    None => job.execute(&ctx).await,
+                },
+                Err(e) => Err(e),
+            };
+            job.after_execute(&ctx, &result).await;
+            (result, policy)
+        }
+        Err(e) => (Err(e), RetryPolicy::no_retry()),
+    };
+
+    stats.processed.fetch_add(1, Ordering::Relaxed);
+    stats.active.fetch_sub(1, Ordering::Relaxed);
+    let error = match result {
+        Ok(()) => {
+            stats.succeeded.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        Err(e) => e,
+    };
+    stats.failed.fetch_add(1, Ordering::Relaxed);
+
+    // The policy counts retries, not runs
+    let retries = queued.metadata.attempts - 1;
+    if policy.should_retry(retries, &error, queued.metadata.created_at) {
+        queued.metadata.mark_failed(&error.message);
+        let delay = policy.next_retry_delay(retries);
+        tracing::warn!(
+            job_id = %queued.metadata.id,
+            job_type = %queued.metadata.job_type,
+            attempt = queued.metadata.attempts,
+            error = %error,
+            "Job failed; retrying in {:?}", delay
+        );
+        let queue = queue.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(delay).await;
+            if let Err(e) = queue.enqueue(queued.clone()).await {
+                tracing::warn!(job_id = %queued.metadata.id, error = %e, "Failed to re-enqueue job; dead-lettering it");
+                queue.dead_letter(queued).await;
+            }
+        });
+    } else {
+        queued.metadata.mark_dead(&error.message);
+        tracing::error!(
+            job_id = %queued.metadata.id,
+            job_type = %queued.metadata.job_type,
+            attempts = queued.metadata.attempts,
+            error = %error,
+            "Job failed; moved to the dead letter queue"
+        );
+        queue.dead_letter(queued).await;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,4 +313,79 @@ mod tests {
         stats.processed.fetch_add(1, Ordering::Relaxed);
         assert_eq!(stats.processed(), 1);
     }
+
+    #[derive(Serialize, Deserialize)]
+    struct FlakyJob {
+        fail: bool,
+        #[serde(skip)]
+        runs: Option<Arc<AtomicU64>>,
+    }
+
+    #[async_trait::async_trait]
+    impl Job for FlakyJob {
+        fn name(&self) -> &'static str {
+            "flaky"
+        }
+
+        async fn execute(&self, _ctx: &JobContext) -> crate::jobs::JobResult {
+            self.runs.as_ref().unwrap().fetch_add(1, Ordering::Relaxed);
+            if self.fail {
+                Err(JobError::retryable("boom"))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn retry_policy(&self) -> RetryPolicy {
+            RetryPolicy { backoff: crate::jobs::BackoffStrategy::fixed(0), ..RetryPolicy::with_retries(2) }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_worker_runs_retries_and_dead_letters() {
+        let runs = Arc::new(AtomicU64::new(0));
+        let registry = JobRegistry::new().register("flaky", {
+            let runs = runs.clone();
+            move |job: FlakyJob| FlakyJob { runs: Some(runs.clone()), ..job }
+        });
+        let queue = Arc::new(JobQueue::in_memory());
+        let config = WorkerConfig { poll_interval_ms: 10, ..Default::default() };
+        let handle = JobWorker::new(config).with_registry(registry).start(queue.clone());
+
+        queue.submit(&FlakyJob { fail: false, runs: None }).await.unwrap();
+        let failing = queue.submit(&FlakyJob { fail: true, runs: None }).await.unwrap();
+        for _ in 0..100 {
+            if !queue.dead_letters(10).await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        // The failing job is tried once and retried twice, then dead-lettered
+        let dead = queue.dead_letters(10).await;
+        assert_eq!(dead.len(), 1);
+        assert_eq!(dead[0].job.metadata.id, failing);
+        assert_eq!(dead[0].job.metadata.attempts, 3);
+        assert_eq!(runs.load(Ordering::Relaxed), 4);
+        assert_eq!(handle.stats().succeeded(), 1);
+        handle.shutdown();
+    }
+
+    #[tokio::test]
+    async fn test_unknown_job_type_is_dead_lettered() {
+        let queue = Arc::new(JobQueue::in_memory());
+        let config = WorkerConfig { poll_interval_ms: 10, ..Default::default() };
+        let handle = JobWorker::new(config).start(queue.clone());
+        queue.submit(&FlakyJob { fail: false, runs: None }).await.unwrap();
+        for _ in 0..100 {
+            if !queue.dead_letters(10).await.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let dead = queue.dead_letters(10).await;
+        assert_eq!(dead.len(), 1);
+        assert!(dead[0].job.metadata.last_error.as_deref().unwrap().contains("No handler"));
+        handle.shutdown();
+    }
 }
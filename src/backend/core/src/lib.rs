@@ -62,14 +62,15 @@ pub mod prelude {
         PolicyEngine, PolicyDecision, PolicyError,
         Permission, Role, RoleId, RoleBinding, UserId, OrganizationId,
         Organization, OrganizationMember, OrganizationStatus, MemberRole,
-        ResourceScope, PredefinedRole,
+        ResourceScope, TenantScope, PredefinedRole,
         RequirePermissionLayer, RequirePermissionService, RbacContext,
     };
     pub use crate::middleware::{
         RateLimitLayer, RateLimitConfig, RateLimitError,
         AuthLayer, AuthConfig, Claims, AuthError, AuthContext, AuthMethod,
         TracingLayer, TracingConfig, RequestContext,
-        CompressionLayer, CompressionConfig, CompressionAlgorithm, CompressionLevel,
+        RequestIdLayer, RequestId,
+        CompressionNegotiationLayer, CompressionConfig, CompressionAlgorithm, CompressionLevel,
         SecurityHeadersLayer, SecurityHeadersConfig, FrameOptions, ReferrerPolicy,
         RequestSizeLayer, RequestSizeConfig,
         AuditLayer, AuditConfig, AuditEntry, AuditLevel, AuditLogger,
@@ -77,6 +78,8 @@ pub mod prelude {
         ApiKeyManager, ApiKeyConfig, ApiKeyEntry, GeneratedKey, KeyStatus,
         InputSanitizerLayer, SanitizeConfig, InjectionType,
     };
+    #[allow(deprecated)]
+    pub use crate::middleware::CompressionLayer;
     pub use crate::validation::{
         Validate, ValidateAsync, ValidateFull, ValidationRule,
         ValidationErrors, ValidationResult, ValidationErrorKind, FieldError,
@@ -84,7 +87,7 @@ pub mod prelude {
         Required, Email, Url, Uuid, MinLength, MaxLength, Min, Max, Range, Pattern,
     };
     pub use crate::cache::{
-        Cache, CacheConfig, CacheKey, KeyType, KeyBuilder,
+        Cache, CacheConfig, CacheKey, CacheLookup, KeyType, KeyBuilder, SerializationFormat,
         CacheBackend, CacheEntry, CacheStats,
         InMemoryBackend, InMemoryConfig,
         RedisBackend, RedisConfig,
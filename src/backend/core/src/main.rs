@@ -6,13 +6,22 @@ use std::sync::Arc;
 use std::net::SocketAddr;
 
 use apex_core::{
+    cache::Cache,
     config::Config,
     db::Database,
     db::health::DatabaseHealthMonitor,
-    orchestrator::{SwarmOrchestrator, OrchestratorConfig},
+    health::{DatabaseHealthChecker, HealthConfig, HealthService},
+    orchestrator::{InProcessTaskRunner, OrchestratorConfig, RunnerKind, SwarmOrchestrator},
     observability::{self, Tracer},
-    api::{self, AppState},
+    api::{self, spawn_metrics_broadcast, AppState},
     contracts::ResourceLimits,
+    jobs::{
+        reports, spawn_periodic, AggregateMetricsJob, CleanupExpiredApprovalsJob, CleanupOldLogsJob,
+        JobQueue, RequeueStuckTasksJob, SendUsageReportsJob,
+    },
+    middleware::{auth::{AuthConfig, Authenticator}, ApiKeyConfig, ApiKeyManager, TrustedProxies},
+    rbac::PolicyEngine,
+    websocket::{BroadcastTransportKind, RedisTransport, TenantRoomAuthorizer, WebSocketConfig, WebSocketState},
 };
 
 #[tokio::main]
@@ -35,9 +44,19 @@ async fn main() -> anyhow::Result<()> {
             observability: Default::default(),
             orchestrator: Default::default(),
             llm: Default::default(),
+            plugins: Default::default(),
+            reports: Default::default(),
+            retention: Default::default(),
+            auth: Default::default(),
         }
     });
 
+    // Redact secrets and configured fields from logged payloads
+    apex_core::telemetry::init_redaction(
+        &apex_core::telemetry::RedactionConfig::default()
+            .with_field_names(config.observability.redacted_fields.iter().cloned()),
+    );
+
     // Initialize observability
     observability::init(
         "apex-server",
@@ -68,34 +87,122 @@ async fn main() -> anyhow::Result<()> {
     // Create Redis client
     let redis_client = redis::Client::open(config.redis.url.as_str())
         .map_err(|e| anyhow::anyhow!("Failed to create Redis client: {}", e))?;
-    tracing::info!("Redis client created for {}", config.redis.url);
+    tracing::info!("Redis client created for {}", apex_core::config::redact_url(&config.redis.url));
 
     // Create orchestrator
     let orchestrator_config = OrchestratorConfig {
         max_concurrent_agents: config.orchestrator.max_concurrent_agents,
+        max_concurrent_dags: config.orchestrator.max_concurrent_dags,
         default_limits: ResourceLimits {
             token_limit: config.orchestrator.default_token_limit,
             cost_limit: config.orchestrator.default_cost_limit,
             api_call_limit: 100,
             time_limit_seconds: config.orchestrator.default_time_limit,
         },
+        max_task_limits: ResourceLimits {
+            token_limit: config.orchestrator.max_task_token_limit,
+            cost_limit: config.orchestrator.max_task_cost_limit,
+            api_call_limit: 200,
+            time_limit_seconds: config.orchestrator.max_task_time_limit,
+        },
         enable_model_routing: config.orchestrator.enable_model_routing,
         circuit_breaker_threshold: config.orchestrator.circuit_breaker_threshold,
         retry_delay_ms: 1000,
         task_result_timeout_secs: 300,
+        failure_policy: config.orchestrator.failure_policy,
+        max_failed_tasks: config.orchestrator.max_failed_tasks,
+        context_limit: config.orchestrator.context_limit.clone(),
+        dag_limits: config.orchestrator.dag_limits,
+        fair_scheduling: config.orchestrator.fair_scheduling,
+        stream_buffer: config.orchestrator.stream_buffer,
+        enable_cnp: config.orchestrator.enable_cnp,
     };
 
-    let orchestrator = Arc::new(
-        SwarmOrchestrator::new(orchestrator_config, db.clone(), redis_client, tracer).await?
-    );
+    let mut orchestrator =
+        SwarmOrchestrator::new(orchestrator_config, db.clone(), redis_client.clone(), tracer).await?;
+    if config.orchestrator.runner == RunnerKind::InProcess {
+        orchestrator = orchestrator.with_runner(Arc::new(InProcessTaskRunner::from_config(&config.llm)));
+        tracing::info!("Running tasks in-process against the LLM providers (no Redis workers)");
+    }
+    let orchestrator = Arc::new(orchestrator);
     tracing::info!("Orchestrator initialized");
 
+    // Task, agent and DAG rooms only admit connections from the owning organization
+    let ws_config = WebSocketConfig { trusted_proxies: TrustedProxies::from_env(), ..Default::default() };
+    let mut ws_state = WebSocketState::new(ws_config)
+        .with_room_authorizer(Arc::new(TenantRoomAuthorizer::new(db.clone())));
+    if config.server.broadcast == BroadcastTransportKind::Redis {
+        ws_state = ws_state.with_broadcast_transport(Arc::new(RedisTransport::new(redis_client)));
+        tracing::info!("Sharing WebSocket broadcasts with other replicas over Redis pub/sub");
+    }
+    let ws = Arc::new(ws_state);
+    let _broadcast_relay = ws.broadcaster.spawn_relay();
+
+    // Periodic agent performance snapshots
+    let aggregate_job = AggregateMetricsJob::new().with_database(db.clone());
+    let aggregate_interval = std::time::Duration::from_secs(aggregate_job.window_minutes * 60);
+    let _aggregate_metrics = spawn_periodic(aggregate_job, aggregate_interval);
+
+    // Expire approvals nobody decided in time
+    let approvals_job = CleanupExpiredApprovalsJob::new()
+        .with_orchestrator(orchestrator.clone())
+        .with_websocket(ws.clone());
+    let _expired_approvals = spawn_periodic(approvals_job, std::time::Duration::from_secs(60));
+
+    // Usage reports for the last complete period; runs hourly and skips
+    // periods already reported
+    let reports_job = SendUsageReportsJob::new(config.reports.period)
+        .with_database(db.clone())
+        .with_sinks(reports::sinks_from_config(&config.reports));
+    let _usage_reports = spawn_periodic(reports_job, std::time::Duration::from_secs(3600));
+
+    // Rescue tasks left running by a dead worker
+    if config.orchestrator.requeue_stuck_tasks {
+        let requeue_job = RequeueStuckTasksJob::new(config.orchestrator.stuck_task_secs)
+            .with_orchestrator(orchestrator.clone());
+        let _requeue_stuck = spawn_periodic(requeue_job, std::time::Duration::from_secs(60));
+    }
+
+    // Daily retention sweep of old audit entries and events
+    let retention_job = CleanupOldLogsJob::from_config(&config.retention).with_database(db.clone());
+    let _retention = spawn_periodic(retention_job, std::time::Duration::from_secs(24 * 3600));
+
+    // Every API route except health, metrics and /ws needs a JWT or API key.
+    // Keys issued by the key manager only reach routes their scopes grant
+    let auth = Authenticator::new(AuthConfig {
+        jwt_secret: config.auth.jwt_secret.clone().or_else(|| std::env::var("JWT_SECRET").ok()),
+        issuer: config.auth.issuer.clone(),
+        audience: config.auth.audience.clone(),
+        public_paths: api::PUBLIC_PATHS.iter().map(|p| p.to_string()).collect(),
+        ..Default::default()
+    })
+    .map_err(|e| anyhow::anyhow!("Invalid auth configuration: {}", e))?
+    .with_api_key_manager(ApiKeyManager::new(ApiKeyConfig::default()));
+
+    // Readiness: the database, the host's disk and memory, the latest backup
+    // if a backup source is configured, and every LLM provider with a key
+    let mut health = HealthService::new(HealthConfig::default());
+    health.register_checker(Arc::new(DatabaseHealthChecker::new(db.pool().clone())));
+    health.register_system_checkers();
+    health.register_backup_checker(db.pool().clone());
+    health.register_llm_providers(&config.llm);
+
     // Create app state
     let app_state = AppState {
         orchestrator,
         db,
+        config: Arc::new(config.clone()),
+        ws: ws.clone(),
+        cache: Arc::new(Cache::in_memory(10_000)),
+        jobs: Arc::new(JobQueue::in_memory()),
+        auth: Arc::new(auth),
+        policy: Arc::new(PolicyEngine::with_predefined_roles()),
+        health: Arc::new(tokio::sync::RwLock::new(health)),
     };
 
+    // Live metrics for dashboard clients subscribed to the metrics room
+    let _metrics_broadcast = spawn_metrics_broadcast(app_state.clone());
+
     // Build router
     let app = api::build_router(app_state);
 
@@ -105,8 +212,8 @@ async fn main() -> anyhow::Result<()> {
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown_signal(ws.clone()))
         .await?;
 
     // Cleanup
@@ -117,7 +224,10 @@ async fn main() -> anyhow::Result<()> {
 }
 
 /// Wait for shutdown signal.
-async fn shutdown_signal() {
+/// Wait for Ctrl+C or SIGTERM, then close WebSocket connections before the
+/// listener stops. Upgraded connections aren't tracked by the graceful
+/// shutdown, so they'd otherwise be dropped without a close frame.
+async fn shutdown_signal(ws: Arc<WebSocketState>) {
     let ctrl_c = async {
         tokio::signal::ctrl_c()
             .await
@@ -141,4 +251,9 @@ async fn shutdown_signal() {
     }
 
     tracing::info!("Shutdown signal received");
+
+    let remaining = ws.shutdown().await;
+    if remaining > 0 {
+        tracing::warn!(connections = remaining, "WebSocket connections still open after shutdown grace period");
+    }
 }
@@ -8,13 +8,22 @@ use std::sync::Arc;
 use thiserror::Error;
 use tracing::info;
 use uuid::Uuid;
+use crate::rbac::Permission;
 #[derive(Debug, Error)]
-pub enum ApiKeyError { #[error("Not found: {0}")] NotFound(String), #[error("Already revoked: {0}")] AlreadyRevoked(String), #[error("Max keys exceeded ({0})")] MaxKeysExceeded(usize), #[error("Expired: {0}")] Expired(String) }
+pub enum ApiKeyError { #[error("Not found: {0}")] NotFound(String), #[error("Already revoked: {0}")] AlreadyRevoked(String), #[error("Max keys exceeded ({0})")] MaxKeysExceeded(usize), #[error("Expired: {0}")] Expired(String), #[error("Already rotating: {0}")] AlreadyRotating(String) }
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum KeyStatus { Active, Rotating, Revoked, Expired }
 #[derive(Debug, Clone, Serialize)]
-pub struct ApiKeyEntry { pub id: String, pub key_hash: String, pub owner: String, pub label: String, pub status: KeyStatus, pub created_at: DateTime<Utc>, pub expires_at: Option<DateTime<Utc>>, pub last_used_at: Option<DateTime<Utc>>, pub previous_key_hash: Option<String>, pub rotation_grace_until: Option<DateTime<Utc>> }
+pub struct ApiKeyEntry { pub id: String, pub key_hash: String, pub owner: String, pub label: String, pub status: KeyStatus, pub created_at: DateTime<Utc>, pub expires_at: Option<DateTime<Utc>>, pub last_used_at: Option<DateTime<Utc>>, pub previous_key_hash: Option<String>, pub rotation_grace_until: Option<DateTime<Utc>>, pub org_id: String, pub scopes: Vec<Permission> }
+impl ApiKeyEntry {
+    /// Whether any of this key's scopes grants `permission`. An empty scope list grants nothing.
+    pub fn allows(&self, permission: &Permission) -> bool { self.scopes.iter().any(|s| s.matches(permission)) }
+    /// Time left before a `Rotating` key stops being accepted; `None` for keys that are not rotating.
+    pub fn grace_remaining(&self) -> Option<Duration> { if self.status != KeyStatus::Rotating { return None; } self.rotation_grace_until.map(|g| (g - Utc::now()).max(Duration::zero())) }
+}
 #[derive(Debug, Clone)] pub struct GeneratedKey { pub raw_key: String, pub key_id: String }
+/// Result of [`ApiKeyManager::rotate`]: the replacement key plus the window during which the old one is still accepted.
+#[derive(Debug, Clone)] pub struct RotatedKey { pub new_key: GeneratedKey, pub previous_key_id: String, pub grace_until: DateTime<Utc> }
 #[derive(Debug, Clone)]
 pub struct ApiKeyConfig { pub key_prefix: String, pub key_length: usize, pub rotation_grace_period: Duration, pub max_keys_per_user: usize, pub default_expiration: Option<Duration> }
 impl Default for ApiKeyConfig { fn default() -> Self { Self { key_prefix: "apex_".into(), key_length: 48, rotation_grace_period: Duration::hours(24), max_keys_per_user: 5, default_expiration: None } } }
@@ -22,18 +31,105 @@ impl Default for ApiKeyConfig { fn default() -> Self { Self { key_prefix: "apex_
 pub struct ApiKeyManager { config: ApiKeyConfig, keys_by_hash: Arc<DashMap<String, ApiKeyEntry>>, keys_by_id: Arc<DashMap<String, String>>, keys_by_owner: Arc<DashMap<String, Vec<String>>> }
 impl ApiKeyManager {
     pub fn new(config: ApiKeyConfig) -> Self { Self { config, keys_by_hash: Arc::new(DashMap::new()), keys_by_id: Arc::new(DashMap::new()), keys_by_owner: Arc::new(DashMap::new()) } }
-    pub fn generate_key(&self, owner: &str, label: &str) -> Result<GeneratedKey, ApiKeyError> {
+    /// Generate a key for `owner` in `org_id` with no scopes; it is refused everywhere until scopes are granted via [`Self::generate_scoped_key`].
+    pub fn generate_key(&self, owner: &str, org_id: &str, label: &str) -> Result<GeneratedKey, ApiKeyError> { self.generate_scoped_key(owner, org_id, label, Vec::new()) }
+    /// Generate a key in `org_id` that may only perform operations covered by `scopes` and by the owner's roles in that organization.
+    pub fn generate_scoped_key(&self, owner: &str, org_id: &str, label: &str, scopes: Vec<Permission>) -> Result<GeneratedKey, ApiKeyError> {
         let c = self.keys_by_owner.get(owner).map(|v| v.len()).unwrap_or(0);
         if c >= self.config.max_keys_per_user { return Err(ApiKeyError::MaxKeysExceeded(self.config.max_keys_per_user)); }
         let raw = self.gen_raw(); let hash = self.hash(&raw); let id = Uuid::new_v4().to_string();
         let exp = self.config.default_expiration.map(|d| Utc::now() + d);
-        let entry = ApiKeyEntry { id: id.clone(), key_hash: hash.clone(), owner: owner.into(), label: label.into(), status: KeyStatus::Active, created_at: Utc::now(), expires_at: exp, last_used_at: None, previous_key_hash: None, rotation_grace_until: None };
+        let entry = ApiKeyEntry { id: id.clone(), key_hash: hash.clone(), owner: owner.into(), label: label.into(), status: KeyStatus::Active, created_at: Utc::now(), expires_at: exp, last_used_at: None, previous_key_hash: None, rotation_grace_until: None, org_id: org_id.into(), scopes };
         self.keys_by_hash.insert(hash.clone(), entry); self.keys_by_id.insert(id.clone(), hash.clone()); self.keys_by_owner.entry(owner.into()).or_default().push(hash);
         info!(key_id=%id, owner=%owner, "API key generated"); Ok(GeneratedKey { raw_key: raw, key_id: id })
     }
+    /// Issue a replacement for `id`. The old key stays valid (flagged `Rotating`) until the grace period elapses or it is revoked.
+    pub fn rotate(&self, id: &str) -> Result<RotatedKey, ApiKeyError> {
+        let old_hash = self.keys_by_id.get(id).map(|v| v.clone()).ok_or_else(|| ApiKeyError::NotFound(id.into()))?;
+        let mut old = self.keys_by_hash.get_mut(&old_hash).ok_or_else(|| ApiKeyError::NotFound(id.into()))?;
+        match old.status { KeyStatus::Active => {}, KeyStatus::Rotating => return Err(ApiKeyError::AlreadyRotating(id.into())), KeyStatus::Revoked => return Err(ApiKeyError::AlreadyRevoked(id.into())), KeyStatus::Expired => return Err(ApiKeyError::Expired(id.into())) }
+        let grace_until = Utc::now() + self.config.rotation_grace_period;
+        old.status = KeyStatus::Rotating; old.rotation_grace_until = Some(grace_until);
+        let raw = self.gen_raw(); let hash = self.hash(&raw); let new_id = Uuid::new_v4().to_string();
+        let entry = ApiKeyEntry { id: new_id.clone(), key_hash: hash.clone(), owner: old.owner.clone(), label: old.label.clone(), status: KeyStatus::Active, created_at: Utc::now(), expires_at: self.config.default_expiration.map(|d| Utc::now() + d), last_used_at: None, previous_key_hash: Some(old_hash), rotation_grace_until: None, org_id: old.org_id.clone(), scopes: old.scopes.clone() };
+        let owner = old.owner.clone(); drop(old);
+        self.keys_by_hash.insert(hash.clone(), entry); self.keys_by_id.insert(new_id.clone(), hash.clone()); self.keys_by_owner.entry(owner.clone()).or_default().push(hash);
+        info!(key_id=%new_id, previous_key_id=%id, owner=%owner, grace_until=%grace_until, "API key rotated"); Ok(RotatedKey { new_key: GeneratedKey { raw_key: raw, key_id: new_id }, previous_key_id: id.into(), grace_until })
+    }
     pub fn revoke_key(&self, id: &str) -> Result<(), ApiKeyError> { let h = self.keys_by_id.get(id).map(|v| v.clone()).ok_or_else(|| ApiKeyError::NotFound(id.into()))?; self.keys_by_hash.get_mut(&h).ok_or_else(|| ApiKeyError::NotFound(id.into()))?.status = KeyStatus::Revoked; info!(key_id=%id, "API key revoked"); Ok(()) }
-    pub fn validate_key(&self, raw: &str) -> Option<ApiKeyEntry> { let h = self.hash(raw); if let Some(mut e) = self.keys_by_hash.get_mut(&h) { match &e.status { KeyStatus::Active | KeyStatus::Rotating => { if let Some(exp) = e.expires_at { if Utc::now() > exp { e.status = KeyStatus::Expired; return None; } } e.last_used_at = Some(Utc::now()); return Some(e.clone()); } _ => return None } } for e in self.keys_by_hash.iter() { if let Some(ref p) = e.previous_key_hash { if *p == h { if let Some(g) = e.rotation_grace_until { if Utc::now() < g { return Some(e.clone()); } } } } } None }
+    pub fn validate_key(&self, raw: &str) -> Option<ApiKeyEntry> {
+        let h = self.hash(raw);
+        if let Some(mut e) = self.keys_by_hash.get_mut(&h) {
+            match &e.status {
+                KeyStatus::Active | KeyStatus::Rotating => {
+                    if let Some(exp) = e.expires_at {
+                        if Utc::now() > exp { e.status = KeyStatus::Expired; return None; }
+                    }
+                    if e.status == KeyStatus::Rotating && !e.rotation_grace_until.is_some_and(|g| Utc::now() < g) {
+                        e.status = KeyStatus::Expired;
+                        info!(key_id=%e.id, "Rotation grace period elapsed");
+                        return None;
+                    }
+                    e.last_used_at = Some(Utc::now());
+                    return Some(e.clone());
+                }
+                _ => return None,
+            }
+        }
+        for e in self.keys_by_hash.iter() {
+            if let (Some(p), Some(g)) = (&e.previous_key_hash, e.rotation_grace_until) {
+                if *p == h && Utc::now() < g { return Some(e.clone()); }
+            }
+        }
+        None
+    }
     pub fn list_keys(&self, owner: &str) -> Vec<ApiKeyEntry> { self.keys_by_owner.get(owner).map(|hs| hs.iter().filter_map(|h| self.keys_by_hash.get(h).map(|e| e.clone())).collect()).unwrap_or_default() }
     fn gen_raw(&self) -> String { let mut r = rand::rng(); let c: Vec<char> = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789".chars().collect(); let s: String = (0..self.config.key_length).map(|_| c[r.random_range(0..c.len())]).collect(); format!("{}{}", self.config.key_prefix, s) }
     fn hash(&self, key: &str) -> String { let mut h = Sha256::new(); h.update(key.as_bytes()); hex::encode(h.finalize()) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(grace: Duration) -> ApiKeyManager { ApiKeyManager::new(ApiKeyConfig { rotation_grace_period: grace, ..Default::default() }) }
+
+    #[test]
+    fn test_rotated_key_accepted_during_window() {
+        let m = manager(Duration::hours(1));
+        let old = m.generate_key("alice", "org-1", "ci").unwrap();
+        let rotated = m.rotate(&old.key_id).unwrap();
+
+        let old_entry = m.validate_key(&old.raw_key).expect("old key accepted during grace window");
+        assert_eq!(old_entry.status, KeyStatus::Rotating);
+        assert!(old_entry.grace_remaining().unwrap() > Duration::minutes(59));
+
+        let new_entry = m.validate_key(&rotated.new_key.raw_key).unwrap();
+        assert_eq!(new_entry.status, KeyStatus::Active);
+        assert_eq!(new_entry.org_id, "org-1");
+        assert!(new_entry.scopes.is_empty());
+        assert!(!new_entry.allows(&Permission::new("task", "read")));
+        assert!(new_entry.grace_remaining().is_none());
+        assert!(matches!(m.rotate(&old.key_id), Err(ApiKeyError::AlreadyRotating(_))));
+    }
+
+    #[test]
+    fn test_rotated_key_rejected_after_window() {
+        let m = manager(Duration::zero());
+        let old = m.generate_key("alice", "org-1", "ci").unwrap();
+        let rotated = m.rotate(&old.key_id).unwrap();
+
+        assert!(m.validate_key(&old.raw_key).is_none());
+        assert!(m.validate_key(&rotated.new_key.raw_key).is_some());
+    }
+
+    #[test]
+    fn test_revoke_ends_rotation_window_early() {
+        let m = manager(Duration::hours(1));
+        let old = m.generate_key("alice", "org-1", "ci").unwrap();
+        m.rotate(&old.key_id).unwrap();
+        m.revoke_key(&old.key_id).unwrap();
+
+        assert!(m.validate_key(&old.raw_key).is_none());
+    }
+}
@@ -3,14 +3,18 @@ use axum::{extract::Request, http::{Method, StatusCode}, response::Response};
 use chrono::{DateTime, Utc};
 use futures::future::BoxFuture;
 use serde::Serialize;
-use std::{sync::Arc, task::{Context, Poll}, time::Instant};
+use std::{sync::{atomic::{AtomicU64, Ordering}, Arc}, task::{Context, Poll}, time::Instant};
+use metrics::counter;
 use tokio::sync::mpsc;
 use tower::{Layer, Service};
 use tracing::{error, info};
+use super::auth::AuthContext;
+use crate::db::Database;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum AuditLevel { Minimal, Standard, Full }
+impl AuditLevel { pub fn as_str(&self) -> &'static str { match self { Self::Minimal => "minimal", Self::Standard => "standard", Self::Full => "full" } } }
 #[derive(Debug, Clone, Serialize)]
-pub struct AuditEntry { pub timestamp: DateTime<Utc>, pub request_id: Option<String>, pub method: String, pub path: String, pub status: u16, pub user_id: Option<String>, pub client_ip: Option<String>, pub auth_method: Option<String>, pub duration_ms: u64, pub audit_level: AuditLevel, pub user_agent: Option<String> }
+pub struct AuditEntry { pub timestamp: DateTime<Utc>, pub request_id: Option<String>, pub method: String, pub path: String, pub status: u16, pub user_id: Option<String>, pub api_key_id: Option<String>, pub client_ip: Option<String>, pub auth_method: Option<String>, pub duration_ms: u64, pub audit_level: AuditLevel, pub user_agent: Option<String> }
 #[derive(Debug, Clone)] pub struct AuditRule { pub path_prefix: String, pub level: AuditLevel }
 #[derive(Debug, Clone)]
 pub struct AuditConfig { pub rules: Vec<AuditRule>, pub always_audit_methods: Vec<Method>, pub always_audit_statuses: Vec<StatusCode>, pub default_level: Option<AuditLevel>, pub channel_buffer_size: usize }
@@ -18,13 +22,19 @@ impl Default for AuditConfig { fn default() -> Self { Self { rules: vec![AuditRu
 const SENSITIVE_FIELDS: &[&str] = &["password","passwd","secret","token","api_key","apikey","credential","private_key","access_token","refresh_token","authorization"];
 pub fn is_sensitive_field(name: &str) -> bool { let l = name.to_lowercase(); SENSITIVE_FIELDS.iter().any(|p| l.contains(p)) }
 pub fn redact_if_sensitive(name: &str, value: &str) -> String { if is_sensitive_field(name) { "[REDACTED]".into() } else { value.into() } }
-#[derive(Debug, Clone)] pub struct AuditLogger { sender: mpsc::Sender<AuditEntry> }
+#[derive(Debug, Clone)] pub struct AuditLogger { sender: mpsc::Sender<AuditEntry>, dropped: Arc<AtomicU64> }
 impl AuditLogger {
-    pub fn new(buf: usize) -> Self { let (tx, mut rx) = mpsc::channel::<AuditEntry>(buf); tokio::spawn(async move { while let Some(e) = rx.recv().await { info!(target:"audit", method=%e.method, path=%e.path, status=e.status, "AUDIT"); } }); Self { sender: tx } }
-    pub async fn log(&self, entry: AuditEntry) { if let Err(e) = self.sender.send(entry).await { error!("Audit send failed: {}", e); } }
+    pub fn new(buf: usize) -> Self { Self::spawn(buf, None) }
+    /// Like [`AuditLogger::new`], but also persists every entry to the `audit_log` table.
+    pub fn with_database(buf: usize, db: Arc<Database>) -> Self { Self::spawn(buf, Some(db)) }
+    fn spawn(buf: usize, db: Option<Arc<Database>>) -> Self { let (tx, mut rx) = mpsc::channel::<AuditEntry>(buf); tokio::spawn(async move { while let Some(e) = rx.recv().await { info!(target:"audit", method=%e.method, path=%e.path, status=e.status, user_id=?e.user_id, api_key_id=?e.api_key_id, "AUDIT"); if let Some(db) = &db { if let Err(err) = db.insert_audit_entry(&e).await { error!(target:"audit", request_id=?e.request_id, path=%e.path, error=%err, "Audit persist failed"); } } } }); Self { sender: tx, dropped: Arc::default() } }
+    /// Queue an entry without waiting; a full or closed channel drops the entry, counts it and logs the failure.
+    pub async fn log(&self, entry: AuditEntry) { if let Err(e) = self.sender.try_send(entry) { let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1; counter!("apex_audit_entries_dropped_total").increment(1); error!(target:"audit", dropped, "Audit send failed, entry dropped: {}", e); } }
+    /// Entries dropped because the sink was full or gone.
+    pub fn dropped(&self) -> u64 { self.dropped.load(Ordering::Relaxed) }
 }
 #[derive(Clone)] pub struct AuditLayer { config: Arc<AuditConfig>, logger: AuditLogger }
-impl AuditLayer { pub fn new(config: AuditConfig) -> Self { let logger = AuditLogger::new(config.channel_buffer_size); Self { config: Arc::new(config), logger } } }
+impl AuditLayer { pub fn new(config: AuditConfig) -> Self { let logger = AuditLogger::new(config.channel_buffer_size); Self { config: Arc::new(config), logger } } pub fn with_database(config: AuditConfig, db: Arc<Database>) -> Self { let logger = AuditLogger::with_database(config.channel_buffer_size, db); Self { config: Arc::new(config), logger } } }
 impl<S> Layer<S> for AuditLayer { type Service = AuditService<S>; fn layer(&self, inner: S) -> Self::Service { AuditService { inner, config: self.config.clone(), logger: self.logger.clone() } } }
 #[derive(Clone)] pub struct AuditService<S> { inner: S, config: Arc<AuditConfig>, logger: AuditLogger }
 impl<S> Service<Request> for AuditService<S> where S: Service<Request, Response = Response> + Clone + Send + 'static, S::Future: Send + 'static, {
@@ -45,8 +55,34 @@ impl<S> Service<Request> for AuditService<S> where S: Service<Request, Response
         Box::pin(async move {
             let resp = inner.call(req).await?; let status = resp.status(); let dur = start.elapsed().as_millis() as u64;
             let mut al = lvl; if al.is_none() && config.always_audit_statuses.contains(&status) { al = Some(AuditLevel::Standard); }
-            if let Some(level) = al { logger.log(AuditEntry { timestamp: Utc::now(), request_id, method: method.to_string(), path, status: status.as_u16(), user_id: None, client_ip, auth_method, duration_ms: dur, audit_level: level, user_agent }).await; }
+            let caller = resp.extensions().get::<AuthContext>().filter(|c| c.is_authenticated()); let user_id = caller.map(|c| c.user_id.clone()); let api_key_id = caller.and_then(|c| c.api_key_id.clone());
+            if let Some(level) = al { logger.log(AuditEntry { timestamp: Utc::now(), request_id, method: method.to_string(), path, status: status.as_u16(), user_id, api_key_id, client_ip, auth_method, duration_ms: dur, audit_level: level, user_agent }).await; }
             Ok(resp)
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> AuditEntry { AuditEntry { timestamp: Utc::now(), request_id: None, method: "DELETE".into(), path: "/api/v1/agents/1".into(), status: 200, user_id: Some("alice".into()), api_key_id: None, client_ip: None, auth_method: None, duration_ms: 3, audit_level: AuditLevel::Minimal, user_agent: None } }
+
+    #[tokio::test]
+    async fn test_log_does_not_block_when_sink_is_full_or_gone() {
+        let (tx, rx) = mpsc::channel::<AuditEntry>(1);
+        let logger = AuditLogger { sender: tx, dropped: Arc::default() };
+        logger.log(entry()).await;
+        assert_eq!(logger.dropped(), 0);
+        logger.log(entry()).await; // channel full: dropped, not awaited
+        drop(rx);
+        logger.log(entry()).await; // receiver gone: dropped
+        assert_eq!(logger.dropped(), 2);
+    }
+
+    #[test]
+    fn test_audit_level_as_str() {
+        assert_eq!(AuditLevel::Full.as_str(), "full");
+        assert_eq!(AuditLevel::Minimal.as_str(), "minimal");
+    }
+}
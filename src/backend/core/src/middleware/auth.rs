@@ -24,7 +24,7 @@
 use axum::{
     body::Body,
     extract::{FromRequestParts, Request},
-    http::{request::Parts, HeaderMap, StatusCode},
+    http::{request::Parts, HeaderMap, HeaderValue, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -40,9 +40,12 @@ use std::{
 };
 use thiserror::Error;
 use tower::{Layer, Service};
-use tracing::{debug, error};
+use tracing::{debug, warn};
 use uuid::Uuid;
 
+use super::api_key_rotation::{ApiKeyEntry, ApiKeyManager, KeyStatus};
+use crate::rbac::Permission;
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Error Types
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -523,6 +526,18 @@ pub struct AuthContext {
 
     /// Request ID for correlation
     pub request_id: String,
+
+    /// ID of the managed API key used (for API key auth)
+    pub api_key_id: Option<String>,
+
+    /// Permissions the credential is restricted to.
+    ///
+    /// `None` means the credential is not scope-restricted (JWT users are
+    /// governed by their RBAC role bindings alone).
+    pub scopes: Option<Vec<Permission>>,
+
+    /// When a key that is being rotated out stops being accepted.
+    pub key_grace_until: Option<DateTime<Utc>>,
 }
 
 /// Authentication method.
@@ -547,6 +562,9 @@ impl AuthContext {
             token_id: Some(claims.jti),
             expires_at: Some(expires_at),
             request_id,
+            api_key_id: None,
+            scopes: None,
+            key_grace_until: None,
         }
     }
 
@@ -562,6 +580,27 @@ impl AuthContext {
             token_id: None,
             expires_at: None,
             request_id,
+            api_key_id: None,
+            scopes: None,
+            key_grace_until: None,
+        }
+    }
+
+    /// Create from a key issued by the [`ApiKeyManager`].
+    pub fn from_managed_key(entry: &ApiKeyEntry, request_id: String) -> Self {
+        Self {
+            user_id: entry.owner.clone(),
+            email: None,
+            name: Some(entry.label.clone()),
+            roles: Vec::new(),
+            org_id: Some(entry.org_id.clone()),
+            auth_method: AuthMethod::ApiKey,
+            token_id: None,
+            expires_at: entry.expires_at,
+            request_id,
+            api_key_id: Some(entry.id.clone()),
+            scopes: Some(entry.scopes.clone()),
+            key_grace_until: entry.rotation_grace_until.filter(|_| entry.status == KeyStatus::Rotating),
         }
     }
 
@@ -577,6 +616,9 @@ impl AuthContext {
             token_id: None,
             expires_at: None,
             request_id,
+            api_key_id: None,
+            scopes: None,
+            key_grace_until: None,
         }
     }
 
@@ -594,6 +636,16 @@ impl AuthContext {
     pub fn is_authenticated(&self) -> bool {
         self.auth_method != AuthMethod::Anonymous
     }
+
+    /// Check whether the credential's scopes cover a permission.
+    ///
+    /// Always true for credentials that are not scope-restricted.
+    pub fn has_scope(&self, permission: &Permission) -> bool {
+        match &self.scopes {
+            Some(scopes) => scopes.iter().any(|s| s.matches(permission)),
+            None => true,
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -607,6 +659,7 @@ pub struct Authenticator {
     decoding_key: Option<DecodingKey>,
     validation: Validation,
     revoked_tokens: Arc<DashMap<String, DateTime<Utc>>>,
+    api_key_manager: Option<ApiKeyManager>,
 }
 
 impl Authenticator {
@@ -673,9 +726,16 @@ impl Authenticator {
             decoding_key,
             validation,
             revoked_tokens: Arc::new(DashMap::new()),
+            api_key_manager: None,
         })
     }
 
+    /// Also accept keys issued by an [`ApiKeyManager`], carrying their scopes.
+    pub fn with_api_key_manager(mut self, manager: ApiKeyManager) -> Self {
+        self.api_key_manager = Some(manager);
+        self
+    }
+
     /// Check if a path is public (doesn't require auth).
     pub fn is_public_path(&self, path: &str) -> bool {
         self.config.public_paths.iter().any(|p| {
@@ -782,8 +842,31 @@ impl Authenticator {
 
     /// Validate an API key.
     fn validate_api_key(&self, key: &str, request_id: String) -> Result<AuthContext, AuthError> {
-        let info = self.config.api_keys.get(key)
-            .ok_or(AuthError::InvalidApiKey)?;
+        let info = match self.config.api_keys.get(key) {
+            Some(info) => info,
+            None => {
+                let entry = self.api_key_manager.as_ref()
+                    .and_then(|m| m.validate_key(key))
+                    .ok_or(AuthError::InvalidApiKey)?;
+
+                if let Some(remaining) = entry.grace_remaining() {
+                    warn!(
+                        key_id = %entry.id,
+                        owner = %entry.owner,
+                        grace_remaining_secs = remaining.num_seconds(),
+                        "Deprecated API key used during rotation window"
+                    );
+                }
+
+                counter!(
+                    "auth_success_total",
+                    "method" => "api_key"
+                )
+                .increment(1);
+
+                return Ok(AuthContext::from_managed_key(&entry, request_id));
+            }
+        };
 
         if !info.active {
             return Err(AuthError::AccountDisabled);
@@ -908,9 +991,18 @@ where
                         }
                     }
 
-                    // Inject auth context into request
-                    request.extensions_mut().insert(auth_context);
-                    inner.call(request).await
+                    // Inject auth context into request, and echo it on the
+                    // response so outer layers (audit) can see who called.
+                    request.extensions_mut().insert(auth_context.clone());
+                    let mut response = inner.call(request).await?;
+                    if let Some(until) = auth_context.key_grace_until {
+                        let remaining = (until - Utc::now()).num_seconds().max(0);
+                        let headers = response.headers_mut();
+                        headers.insert("Deprecation", HeaderValue::from_static("true"));
+                        headers.insert("X-API-Key-Grace-Remaining", HeaderValue::from(remaining));
+                    }
+                    response.extensions_mut().insert(auth_context);
+                    Ok(response)
                 }
                 Err(e) => Ok(e.into_response()),
             }
@@ -1094,4 +1186,57 @@ mod tests {
         assert_eq!(ctx.user_id, "user1");
         assert_eq!(ctx.auth_method, AuthMethod::ApiKey);
     }
+
+    #[tokio::test]
+    async fn test_managed_api_key_carries_scopes() {
+        use super::super::api_key_rotation::ApiKeyConfig;
+
+        let manager = ApiKeyManager::new(ApiKeyConfig::default());
+        let key = manager
+            .generate_scoped_key("svc-reporter", "org-1", "reporting", vec![Permission::new("task", "read")])
+            .unwrap();
+
+        let config = AuthConfig {
+            jwt_secret: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let auth = Authenticator::new(config).unwrap().with_api_key_manager(manager);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-API-Key", key.raw_key.parse().unwrap());
+        let ctx = auth.authenticate(&headers).await.unwrap();
+
+        assert_eq!(ctx.user_id, "svc-reporter");
+        assert_eq!(ctx.org_id.as_deref(), Some("org-1"));
+        assert_eq!(ctx.api_key_id.as_deref(), Some(key.key_id.as_str()));
+        assert!(ctx.has_scope(&Permission::new("task", "read")));
+        assert!(!ctx.has_scope(&Permission::new("task", "delete")));
+
+        headers.insert("X-API-Key", "apex_not-a-real-key".parse().unwrap());
+        assert!(matches!(auth.authenticate(&headers).await, Err(AuthError::InvalidApiKey)));
+    }
+
+    #[tokio::test]
+    async fn test_rotating_api_key_exposes_grace_window() {
+        use super::super::api_key_rotation::ApiKeyConfig;
+
+        let manager = ApiKeyManager::new(ApiKeyConfig::default());
+        let old = manager.generate_key("alice", "org-1", "ci").unwrap();
+        let rotated = manager.rotate(&old.key_id).unwrap();
+
+        let config = AuthConfig {
+            jwt_secret: Some("secret".to_string()),
+            ..Default::default()
+        };
+        let auth = Authenticator::new(config).unwrap().with_api_key_manager(manager);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("X-API-Key", old.raw_key.parse().unwrap());
+        let ctx = auth.authenticate(&headers).await.unwrap();
+        assert_eq!(ctx.key_grace_until, Some(rotated.grace_until));
+
+        headers.insert("X-API-Key", rotated.new_key.raw_key.parse().unwrap());
+        let ctx = auth.authenticate(&headers).await.unwrap();
+        assert!(ctx.key_grace_until.is_none());
+    }
 }
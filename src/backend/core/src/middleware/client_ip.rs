@@ -0,0 +1,76 @@
+//! Client address resolution behind reverse proxies.
+//!
+//! Forwarding headers (`X-Forwarded-For`, `X-Forwarded-Proto`) are only
+//! believed when the TCP peer is a configured proxy; from anyone else they
+//! are client-controlled and ignored. Proxies come from
+//! `APEX_TRUSTED_PROXIES`, a comma-separated list of IP addresses.
+
+use axum::{extract::ConnectInfo, http::HeaderMap};
+use std::net::{IpAddr, SocketAddr};
+
+/// Peers allowed to set forwarding headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedProxies(Vec<IpAddr>);
+
+impl TrustedProxies {
+    pub fn new(proxies: impl IntoIterator<Item = IpAddr>) -> Self {
+        Self(proxies.into_iter().collect())
+    }
+
+    /// Proxies from `APEX_TRUSTED_PROXIES`; unparseable entries are skipped
+    /// with a warning and an unset variable trusts nobody.
+    pub fn from_env() -> Self {
+        std::env::var("APEX_TRUSTED_PROXIES").map(|v| Self::parse(&v)).unwrap_or_default()
+    }
+
+    fn parse(value: &str) -> Self {
+        Self::new(value.split(',').map(str::trim).filter(|s| !s.is_empty()).filter_map(|s| {
+            s.parse().map_err(|_| tracing::warn!(entry = %s, "Ignoring invalid APEX_TRUSTED_PROXIES entry")).ok()
+        }))
+    }
+
+    /// Whether forwarding headers from `peer` can be believed.
+    pub fn trusts(&self, peer: Option<SocketAddr>) -> bool {
+        peer.is_some_and(|addr| self.0.contains(&addr.ip()))
+    }
+
+    /// The first `X-Forwarded-For` hop when `peer` is trusted, otherwise the peer itself.
+    pub fn client_ip(&self, headers: &HeaderMap, peer: Option<SocketAddr>) -> Option<IpAddr> {
+        self.forwarded(headers, peer, "x-forwarded-for")
+            .and_then(|v| v.trim().parse().ok())
+            .or_else(|| peer.map(|addr| addr.ip()))
+    }
+
+    /// The first value of a forwarding header, if `peer` is trusted to set it.
+    pub fn forwarded<'a>(&self, headers: &'a HeaderMap, peer: Option<SocketAddr>, name: &str) -> Option<&'a str> {
+        if !self.trusts(peer) {
+            return None;
+        }
+        headers.get(name).and_then(|v| v.to_str().ok()).and_then(|v| v.split(',').next())
+    }
+}
+
+/// The TCP peer recorded by `into_make_service_with_connect_info`.
+pub fn peer_addr<B>(request: &axum::http::Request<B>) -> Option<SocketAddr> {
+    request.extensions().get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(addr)| *addr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forwarded_headers_only_from_trusted_peers() {
+        let proxies = TrustedProxies::parse("10.0.0.1, bogus,");
+        assert_eq!(proxies, TrustedProxies::new(["10.0.0.1".parse().unwrap()]));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", "203.0.113.9, 10.0.0.1".parse().unwrap());
+        let proxy: SocketAddr = "10.0.0.1:4000".parse().unwrap();
+        let client: SocketAddr = "198.51.100.7:4000".parse().unwrap();
+
+        assert_eq!(proxies.client_ip(&headers, Some(proxy)), Some("203.0.113.9".parse().unwrap()));
+        assert_eq!(proxies.client_ip(&headers, Some(client)), Some(client.ip()));
+        assert_eq!(proxies.client_ip(&headers, None), None);
+    }
+}
@@ -1,16 +1,19 @@
-//! Response compression middleware.
+//! Response compression negotiation.
+//!
+//! The encoding itself is left to tower-http's `CompressionLayer`; this
+//! middleware decides whether and how a response may be compressed.
 //!
 //! Features:
 //! - Automatic content-type based compression decisions
 //! - Support for gzip, deflate, and brotli
 //! - Configurable minimum response size
-//! - Accept-Encoding negotiation
+//! - Accept-Encoding negotiation with quality values (`br;q=1.0, gzip;q=0.8, identity;q=0`)
 //! - Compression level configuration
 //!
 //! # Example
 //!
 //! ```rust,ignore
-//! use apex_core::middleware::compression::{CompressionLayer, CompressionConfig};
+//! use apex_core::middleware::compression::{CompressionNegotiationLayer, CompressionConfig};
 //!
 //! let config = CompressionConfig::builder()
 //!     .min_size(1024)
@@ -19,13 +22,14 @@
 //!
 //! let app = Router::new()
 //!     .route("/api/v1/tasks", get(list_tasks))
-//!     .layer(CompressionLayer::new(config));
+//!     .layer(CompressionNegotiationLayer::new(config))
+//!     .layer(tower_http::compression::CompressionLayer::new());
 //! ```
 
 use axum::{
     body::Body,
     extract::Request,
-    http::{header, HeaderMap, HeaderValue},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
     response::Response,
 };
 use futures::future::BoxFuture;
@@ -76,6 +80,43 @@ impl CompressionAlgorithm {
     }
 }
 
+/// Outcome of `Accept-Encoding` negotiation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodingNegotiation {
+    /// Compress with the given algorithm.
+    Compress {
+        algorithm: CompressionAlgorithm,
+        /// The client sent `identity;q=0` (or `*;q=0`): the response must be encoded.
+        required: bool,
+    },
+    /// Send the response uncompressed.
+    Identity,
+    /// Nothing acceptable to the client is supported.
+    NotAcceptable,
+}
+
+/// Parse an `Accept-Encoding` header into `(coding, quality)` pairs.
+///
+/// Codings are lowercased; a missing or malformed `q` parameter counts as `1.0`.
+pub fn parse_accept_encoding(value: &str) -> Vec<(String, f32)> {
+    value
+        .split(',')
+        .filter_map(|part| {
+            let mut params = part.split(';');
+            let coding = params.next()?.trim().to_lowercase();
+            if coding.is_empty() {
+                return None;
+            }
+            let quality = params
+                .filter_map(|p| p.split_once('='))
+                .find(|(k, _)| k.trim().eq_ignore_ascii_case("q"))
+                .and_then(|(_, v)| v.trim().parse::<f32>().ok())
+                .map_or(1.0, |q| q.clamp(0.0, 1.0));
+            Some((coding, quality))
+        })
+        .collect()
+}
+
 /// Compression level.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -149,6 +190,12 @@ impl Default for CompressionConfig {
         excluded_types.insert("audio/mpeg".to_string());
         excluded_types.insert("application/zip".to_string());
         excluded_types.insert("application/gzip".to_string());
+        excluded_types.insert("application/zstd".to_string());
+        excluded_types.insert("application/x-bzip2".to_string());
+        excluded_types.insert("application/x-7z-compressed".to_string());
+        excluded_types.insert("image/avif".to_string());
+        excluded_types.insert("video/webm".to_string());
+        excluded_types.insert("font/woff2".to_string());
 
         Self {
             enabled: true,
@@ -212,41 +259,44 @@ impl CompressionConfig {
         })
     }
 
-    /// Select the best compression algorithm based on Accept-Encoding header.
-    pub fn select_algorithm(&self, accept_encoding: &str) -> Option<CompressionAlgorithm> {
-        // Parse Accept-Encoding header
-        let accepted: Vec<(CompressionAlgorithm, f32)> = accept_encoding
-            .split(',')
-            .filter_map(|part| {
-                let mut parts = part.trim().splitn(2, ";q=");
-                let encoding = parts.next()?;
-                let quality: f32 = parts
-                    .next()
-                    .and_then(|q| q.trim().parse().ok())
-                    .unwrap_or(1.0);
-
-                if quality > 0.0 {
-                    CompressionAlgorithm::from_encoding(encoding).map(|alg| (alg, quality))
-                } else {
-                    None
-                }
-            })
-            .collect();
+    /// Negotiate an encoding from an `Accept-Encoding` header.
+    ///
+    /// Picks the supported algorithm with the highest client quality; ties go to
+    /// the order of `algorithms`. `*` covers codings not listed explicitly.
+    pub fn negotiate(&self, accept_encoding: &str) -> EncodingNegotiation {
+        let accepted = parse_accept_encoding(accept_encoding);
+        let quality_of = |coding: &str| {
+            accepted
+                .iter()
+                .find(|(c, _)| c == coding)
+                .or_else(|| accepted.iter().find(|(c, _)| c == "*"))
+                .map(|(_, q)| *q)
+        };
 
-        // Find the first preferred algorithm that's accepted
-        for preferred in &self.algorithms {
-            if accepted.iter().any(|(alg, _)| alg == preferred) {
-                return Some(*preferred);
+        let mut best: Option<(CompressionAlgorithm, f32)> = None;
+        for alg in self.algorithms.iter().filter(|a| **a != CompressionAlgorithm::Identity) {
+            let q = quality_of(alg.encoding_name()).unwrap_or(0.0);
+            if q > 0.0 && best.map_or(true, |(_, b)| q > b) {
+                best = Some((*alg, q));
             }
         }
 
-        // Check for wildcard
-        if accepted.iter().any(|(alg, _)| *alg == CompressionAlgorithm::Identity) {
-            // If identity is accepted, return the first preferred algorithm
-            return self.algorithms.first().copied();
+        // Identity is acceptable unless explicitly (or via `*`) given q=0.
+        let identity_allowed = quality_of("identity").map_or(true, |q| q > 0.0);
+
+        match best {
+            Some((algorithm, _)) => EncodingNegotiation::Compress { algorithm, required: !identity_allowed },
+            None if identity_allowed => EncodingNegotiation::Identity,
+            None => EncodingNegotiation::NotAcceptable,
         }
+    }
 
-        None
+    /// Select the best compression algorithm based on Accept-Encoding header.
+    pub fn select_algorithm(&self, accept_encoding: &str) -> Option<CompressionAlgorithm> {
+        match self.negotiate(accept_encoding) {
+            EncodingNegotiation::Compress { algorithm, .. } => Some(algorithm),
+            _ => None,
+        }
     }
 }
 
@@ -311,18 +361,18 @@ impl CompressionConfigBuilder {
 // Tower Layer and Service
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Compression layer for Tower.
+/// `Accept-Encoding` negotiation in front of tower-http's `CompressionLayer`.
 ///
-/// Note: This is a lightweight wrapper that sets up compression decisions.
-/// For actual compression, we recommend using tower-http's CompressionLayer
-/// which handles the actual encoding. This layer provides the configuration
-/// and decision logic.
+/// This layer doesn't encode anything itself: it answers `406` when the
+/// client accepts no supported coding, sets `Vary: Accept-Encoding` and
+/// records compression metrics. Install tower-http's `CompressionLayer`
+/// around it to do the encoding.
 #[derive(Clone)]
-pub struct CompressionLayer {
+pub struct CompressionNegotiationLayer {
     config: Arc<CompressionConfig>,
 }
 
-impl CompressionLayer {
+impl CompressionNegotiationLayer {
     /// Create a new compression layer.
     pub fn new(config: CompressionConfig) -> Self {
         Self {
@@ -331,34 +381,39 @@ impl CompressionLayer {
     }
 }
 
-impl Default for CompressionLayer {
+impl Default for CompressionNegotiationLayer {
     fn default() -> Self {
         Self::new(CompressionConfig::default())
     }
 }
 
-impl<S> Layer<S> for CompressionLayer {
-    type Service = CompressionService<S>;
+impl<S> Layer<S> for CompressionNegotiationLayer {
+    type Service = CompressionNegotiationService<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        CompressionService {
+        CompressionNegotiationService {
             inner,
             config: self.config.clone(),
         }
     }
 }
 
-/// Compression service.
-///
-/// This service adds compression-related headers and metrics.
-/// Actual compression should be handled by tower-http's compression middleware.
+/// Former name of [`CompressionNegotiationLayer`].
+#[deprecated(note = "renamed to `CompressionNegotiationLayer`")]
+pub type CompressionLayer = CompressionNegotiationLayer;
+
+/// Service produced by [`CompressionNegotiationLayer`].
 #[derive(Clone)]
-pub struct CompressionService<S> {
+pub struct CompressionNegotiationService<S> {
     inner: S,
     config: Arc<CompressionConfig>,
 }
 
-impl<S> Service<Request<Body>> for CompressionService<S>
+/// Former name of [`CompressionNegotiationService`].
+#[deprecated(note = "renamed to `CompressionNegotiationService`")]
+pub type CompressionService<S> = CompressionNegotiationService<S>;
+
+impl<S> Service<Request<Body>> for CompressionNegotiationService<S>
 where
     S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
     S::Future: Send,
@@ -397,21 +452,26 @@ where
                 .unwrap_or("");
 
             // Select compression algorithm
-            let algorithm = config.select_algorithm(accept_encoding);
+            let negotiation = config.negotiate(accept_encoding);
 
-            // Store compression decision for potential use
-            let should_check_compression = algorithm.is_some() && algorithm != Some(CompressionAlgorithm::Identity);
+            if negotiation == EncodingNegotiation::NotAcceptable {
+                counter!("compression_not_acceptable_total").increment(1);
+                let mut response = Response::new(Body::from("No acceptable content encoding"));
+                *response.status_mut() = StatusCode::NOT_ACCEPTABLE;
+                return Ok(response);
+            }
 
             // Call the inner service
             let mut response = inner.call(request).await?;
 
             // Record compression metrics
-            if should_check_compression {
+            if let EncodingNegotiation::Compress { algorithm: alg, required } = negotiation {
                 let content_type = response
                     .headers()
                     .get(header::CONTENT_TYPE)
                     .and_then(|v| v.to_str().ok())
-                    .unwrap_or("");
+                    .unwrap_or("")
+                    .to_string();
 
                 let content_length: Option<usize> = response
                     .headers()
@@ -419,39 +479,39 @@ where
                     .and_then(|v| v.to_str().ok())
                     .and_then(|s| s.parse().ok());
 
-                let will_compress = config.should_compress_content_type(content_type)
-                    && content_length.map_or(config.compress_streams, |len| {
-                        len >= config.min_size && (config.max_size == 0 || len <= config.max_size)
-                    });
+                // A client that refuses identity gets compression regardless of
+                // size, as long as the body isn't already encoded.
+                let will_compress = should_compress_response(response.headers(), &config)
+                    || (required && !response.headers().contains_key(header::CONTENT_ENCODING));
 
                 if will_compress {
-                    if let Some(alg) = algorithm {
-                        counter!(
-                            "compression_applied_total",
-                            "algorithm" => alg.encoding_name().to_string(),
-                            "content_type" => content_type.to_string()
-                        )
-                        .increment(1);
-
-                        debug!(
-                            algorithm = %alg.encoding_name(),
-                            content_type = %content_type,
-                            content_length = ?content_length,
-                            "Compression applied"
-                        );
+                    counter!(
+                        "compression_applied_total",
+                        "algorithm" => alg.encoding_name().to_string(),
+                        "content_type" => content_type.clone()
+                    )
+                    .increment(1);
 
-                        // Add Vary header to indicate content negotiation
-                        if !response.headers().contains_key(header::VARY) {
-                            response.headers_mut().insert(
-                                header::VARY,
-                                HeaderValue::from_static("Accept-Encoding"),
-                            );
-                        }
+                    debug!(
+                        algorithm = %alg.encoding_name(),
+                        content_type = %content_type,
+                        content_length = ?content_length,
+                        "Compression applied"
+                    );
+
+                    // Add Vary header to indicate content negotiation
+                    if !response.headers().contains_key(header::VARY) {
+                        response.headers_mut().insert(
+                            header::VARY,
+                            HeaderValue::from_static("Accept-Encoding"),
+                        );
                     }
                 } else {
                     counter!(
                         "compression_skipped_total",
-                        "reason" => if !config.should_compress_content_type(content_type) {
+                        "reason" => if response.headers().contains_key(header::CONTENT_ENCODING) {
+                            "already_encoded"
+                        } else if !config.should_compress_content_type(&content_type) {
                             "content_type"
                         } else if content_length.is_some_and(|len| len < config.min_size) {
                             "too_small"
@@ -593,6 +653,73 @@ mod tests {
         assert_eq!(alg, None);
     }
 
+    #[test]
+    fn test_parse_accept_encoding_quality_values() {
+        let parsed = parse_accept_encoding("gzip;q=0.8, br;q=1.0, identity; q=0, *");
+        assert_eq!(
+            parsed,
+            vec![
+                ("gzip".to_string(), 0.8),
+                ("br".to_string(), 1.0),
+                ("identity".to_string(), 0.0),
+                ("*".to_string(), 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_negotiate_br_preferred_client() {
+        let config = CompressionConfig::builder()
+            .algorithms(vec![CompressionAlgorithm::Gzip, CompressionAlgorithm::Brotli])
+            .build();
+
+        // Client quality beats server preference order
+        assert_eq!(
+            config.negotiate("gzip;q=0.8, br;q=1.0"),
+            EncodingNegotiation::Compress { algorithm: CompressionAlgorithm::Brotli, required: false }
+        );
+    }
+
+    #[test]
+    fn test_negotiate_gzip_only_client() {
+        let config = CompressionConfig::default();
+
+        assert_eq!(config.select_algorithm("gzip"), Some(CompressionAlgorithm::Gzip));
+        assert_eq!(config.select_algorithm("gzip;q=0.5, deflate;q=0.4"), Some(CompressionAlgorithm::Gzip));
+        // Wildcard picks up the server's preferred coding
+        assert_eq!(config.select_algorithm("*;q=0.1"), Some(CompressionAlgorithm::Brotli));
+        // ...but explicit refusals still win over the wildcard
+        assert_eq!(config.select_algorithm("br;q=0, *"), Some(CompressionAlgorithm::Gzip));
+    }
+
+    #[test]
+    fn test_negotiate_identity_forbidden() {
+        let config = CompressionConfig::default();
+
+        assert_eq!(
+            config.negotiate("gzip, identity;q=0"),
+            EncodingNegotiation::Compress { algorithm: CompressionAlgorithm::Gzip, required: true }
+        );
+        assert_eq!(config.negotiate("zstd, identity;q=0"), EncodingNegotiation::NotAcceptable);
+        assert_eq!(config.negotiate("zstd"), EncodingNegotiation::Identity);
+        assert_eq!(config.negotiate(""), EncodingNegotiation::Identity);
+    }
+
+    #[tokio::test]
+    async fn test_service_rejects_unsatisfiable_encoding() {
+        use tower::ServiceExt;
+
+        let svc = CompressionNegotiationLayer::default().layer(tower::service_fn(|_req: Request<Body>| async {
+            Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+        }));
+        let req = Request::builder()
+            .header(header::ACCEPT_ENCODING, "zstd, identity;q=0")
+            .body(Body::empty())
+            .unwrap();
+
+        assert_eq!(svc.oneshot(req).await.unwrap().status(), StatusCode::NOT_ACCEPTABLE);
+    }
+
     #[test]
     fn test_config_builder() {
         let config = CompressionConfig::builder()
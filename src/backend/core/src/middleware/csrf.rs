@@ -1,5 +1,14 @@
 //! CSRF protection middleware.
-use axum::{extract::Request, http::{Method, StatusCode}, response::{IntoResponse, Response}, Json};
+//!
+//! Uses the double-submit pattern for cookie-authenticated browser clients:
+//! 1. `GET /api/v1/csrf-token` (answered by this layer) mints a signed token, sets it in the
+//!    `csrf_token` cookie and returns it in the body as `{"data":{"token":..,"header":"x-csrf-token"}}`.
+//! 2. Every POST/PUT/PATCH/DELETE echoes the token in the `x-csrf-token` header. The header must be
+//!    a live token and, when the cookie is sent, must equal it.
+//!
+//! Requests carrying `Authorization: Bearer` or `X-API-Key` are not CSRF-susceptible (browsers never
+//! attach those automatically) and skip the check.
+use axum::{extract::Request, http::{header, HeaderValue, Method, StatusCode}, response::{IntoResponse, Response}, Json};
 use dashmap::DashMap;
 use futures::future::BoxFuture;
 use metrics::counter;
@@ -10,9 +19,10 @@ use tower::{Layer, Service};
 use tracing::{debug, warn};
 use uuid::Uuid;
 #[derive(Debug, Clone)]
-pub struct CsrfConfig { pub secret: String, pub token_ttl: Duration, pub exempt_paths: Vec<String>, pub protected_methods: Vec<Method>, pub token_header: String }
-impl Default for CsrfConfig { fn default() -> Self { Self { secret: Uuid::new_v4().to_string(), token_ttl: Duration::from_secs(3600), exempt_paths: vec!["/api/".into(),"/health".into(),"/ready".into(),"/metrics".into(),"/ws".into()], protected_methods: vec![Method::POST,Method::PUT,Method::PATCH,Method::DELETE], token_header: "x-csrf-token".into() } } }
+pub struct CsrfConfig { pub secret: String, pub token_ttl: Duration, pub exempt_paths: Vec<String>, pub protected_methods: Vec<Method>, pub token_header: String, pub cookie_name: String, pub token_endpoint: String }
+impl Default for CsrfConfig { fn default() -> Self { Self { secret: Uuid::new_v4().to_string(), token_ttl: Duration::from_secs(3600), exempt_paths: vec!["/health".into(),"/ready".into(),"/metrics".into(),"/ws".into()], protected_methods: vec![Method::POST,Method::PUT,Method::PATCH,Method::DELETE], token_header: "x-csrf-token".into(), cookie_name: "csrf_token".into(), token_endpoint: "/api/v1/csrf-token".into() } } }
 impl CsrfConfig { fn is_exempt(&self, p: &str) -> bool { self.exempt_paths.iter().any(|e| p.starts_with(e)) } fn is_protected(&self, m: &Method) -> bool { self.protected_methods.contains(m) } }
+fn cookie_value<'a>(req: &'a Request, name: &str) -> Option<&'a str> { req.headers().get_all(header::COOKIE).iter().filter_map(|v| v.to_str().ok()).flat_map(|v| v.split(';')).filter_map(|c| c.trim().split_once('=')).find(|(k, _)| *k == name).map(|(_, v)| v) }
 #[derive(Debug, Clone)] struct StoredToken { expires_at: Instant }
 #[derive(Debug, Clone)] struct TokenStore { tokens: Arc<DashMap<String, StoredToken>>, ttl: Duration }
 impl TokenStore {
@@ -32,12 +42,19 @@ impl<S> Service<Request> for CsrfService<S> where S: Service<Request, Response =
         let config = self.config.clone(); let store = self.store.clone(); let mut inner = self.inner.clone();
         Box::pin(async move {
             let method = req.method().clone(); let path = req.uri().path().to_string();
+            if method == Method::GET && path == config.token_endpoint {
+                let t = store.generate(&config.secret);
+                let cookie = format!("{}={}; Path=/; Max-Age={}; SameSite=Strict; Secure", config.cookie_name, t, config.token_ttl.as_secs());
+                let mut resp = Json(json!({"success":true,"data":{"token":t,"header":config.token_header,"expires_in":config.token_ttl.as_secs()}})).into_response();
+                if let Ok(v) = HeaderValue::from_str(&cookie) { resp.headers_mut().insert(header::SET_COOKIE, v); }
+                return Ok(resp);
+            }
             if config.is_exempt(&path) { return inner.call(req).await; }
             let has_bearer = req.headers().get("authorization").and_then(|v| v.to_str().ok()).map(|s| s.starts_with("Bearer ")).unwrap_or(false);
             if has_bearer || req.headers().contains_key("x-api-key") { return inner.call(req).await; }
             if config.is_protected(&method) {
                 match req.headers().get(config.token_header.as_str()).and_then(|v| v.to_str().ok()) {
-                    Some(t) if store.validate(t) => { debug!("CSRF ok"); }
+                    Some(t) if store.validate(t) && cookie_value(&req, &config.cookie_name).map_or(true, |c| c == t) => { debug!("CSRF ok"); }
                     Some(_) => { counter!("csrf_failed_total").increment(1); return Ok((StatusCode::FORBIDDEN, Json(json!({"success":false,"error":"CSRF validation failed","error_code":"CSRF_VALIDATION_FAILED"}))).into_response()); }
                     None => { counter!("csrf_missing_total").increment(1); return Ok((StatusCode::FORBIDDEN, Json(json!({"success":false,"error":"CSRF token required","error_code":"CSRF_TOKEN_MISSING"}))).into_response()); }
                 }
@@ -47,3 +64,52 @@ impl<S> Service<Request> for CsrfService<S> where S: Service<Request, Response =
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use tower::ServiceExt;
+
+    fn layer() -> CsrfLayer { CsrfLayer::new(CsrfConfig { exempt_paths: vec!["/health".into()], ..Default::default() }) }
+
+    async fn call(layer: &CsrfLayer, req: Request) -> Response {
+        layer.layer(tower::service_fn(|_req: Request| async { Ok::<_, std::convert::Infallible>(StatusCode::OK.into_response()) })).oneshot(req).await.unwrap()
+    }
+
+    fn post(token: Option<&str>, cookie: Option<&str>) -> Request {
+        let mut b = Request::builder().method(Method::POST).uri("/api/v1/tasks");
+        if let Some(t) = token { b = b.header("x-csrf-token", t); }
+        if let Some(c) = cookie { b = b.header(header::COOKIE, format!("theme=dark; csrf_token={}", c)); }
+        b.body(Body::empty()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_double_submit_flow() {
+        let csrf = layer();
+        let resp = call(&csrf, Request::builder().uri("/api/v1/csrf-token").body(Body::empty()).unwrap()).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let set_cookie = resp.headers().get(header::SET_COOKIE).unwrap().to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+        let token = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["data"]["token"].as_str().unwrap().to_string();
+        assert!(set_cookie.starts_with(&format!("csrf_token={};", token)));
+
+        assert_eq!(call(&csrf, post(None, Some(&token))).await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(call(&csrf, post(Some(&token), Some("forged"))).await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(call(&csrf, post(Some(&token), Some(&token))).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_bearer_requests_skip_enforcement() {
+        let req = Request::builder().method(Method::POST).uri("/api/v1/tasks").header("authorization", "Bearer abc").body(Body::empty()).unwrap();
+        assert_eq!(call(&layer(), req).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_default_config_protects_api_routes() {
+        let csrf = CsrfLayer::new(CsrfConfig::default());
+        assert_eq!(call(&csrf, post(None, None)).await.status(), StatusCode::FORBIDDEN);
+        let health = Request::builder().method(Method::POST).uri("/health").body(Body::empty()).unwrap();
+        assert_eq!(call(&csrf, health).await.status(), StatusCode::OK);
+    }
+}
@@ -1,5 +1,5 @@
 //! Input sanitization middleware for injection attack detection.
-use axum::{extract::Request, http::StatusCode, response::{IntoResponse, Response}, Json};
+use axum::{body::Body, extract::Request, http::{header, StatusCode}, response::{IntoResponse, Response}, Json};
 use futures::future::BoxFuture;
 use metrics::counter;
 use regex::Regex;
@@ -15,14 +15,51 @@ impl std::fmt::Display for InjectionType {
         match self { Self::SqlInjection => write!(f,"SQL Injection"), Self::Xss => write!(f,"XSS"), Self::CommandInjection => write!(f,"Command Injection"), Self::PathTraversal => write!(f,"Path Traversal"), Self::LdapInjection => write!(f,"LDAP Injection"), Self::LogInjection => write!(f,"Log Injection") }
     }
 }
+/// Where a detection happened, e.g. `query.q`, `header.x-trace`, `body.tasks[0].name`. Never contains the matched content:
+/// a query parameter whose name is itself suspicious is reported by position instead, e.g. `query[1]`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Detection { pub injection_type: InjectionType, pub field: String }
+/// `loose_fields` lists JSON body fields (by key, e.g. `"description"`, or by path, e.g. `"tasks[0].instruction"`) that may
+/// legitimately carry markup such as markdown: only script-ish XSS is checked there.
 #[derive(Debug, Clone)]
-pub struct SanitizeConfig { pub block_on_detection: bool, pub skip_headers: Vec<String>, pub exempt_paths: Vec<String>, pub max_url_length: usize, pub max_header_value_length: usize }
-impl Default for SanitizeConfig { fn default() -> Self { Self { block_on_detection: true, skip_headers: vec!["authorization".into(),"cookie".into(),"user-agent".into(),"accept".into(),"content-type".into(),"content-length".into(),"host".into(),"x-request-id".into(),"x-forwarded-for".into(),"x-csrf-token".into(),"x-api-key".into()], exempt_paths: vec![], max_url_length: 2048, max_header_value_length: 8192 } } }
+pub struct SanitizeConfig { pub block_on_detection: bool, pub skip_headers: Vec<String>, pub exempt_paths: Vec<String>, pub max_url_length: usize, pub max_header_value_length: usize, pub scan_json_body: bool, pub max_body_scan_bytes: usize, pub loose_fields: Vec<String> }
+impl Default for SanitizeConfig { fn default() -> Self { Self { block_on_detection: true, skip_headers: vec!["authorization".into(),"cookie".into(),"user-agent".into(),"accept".into(),"content-type".into(),"content-length".into(),"host".into(),"x-request-id".into(),"x-forwarded-for".into(),"x-csrf-token".into(),"x-api-key".into()], exempt_paths: vec![], max_url_length: 2048, max_header_value_length: 8192, scan_json_body: false, max_body_scan_bytes: 1024 * 1024, loose_fields: vec![] } } }
+impl SanitizeConfig { fn is_loose(&self, path: &str) -> bool { let key = path.rsplit('.').next().unwrap_or(path); let key = key.split('[').next().unwrap_or(key); self.loose_fields.iter().any(|f| f == path || f == key) } }
 struct Patterns { sql: Vec<Regex>, xss: Vec<Regex>, path: Vec<Regex>, log: Vec<Regex> }
 impl Patterns {
     fn new() -> Self { Self { sql: vec![Regex::new(r"(?i)(\bunion\b\s+\bselect\b)").unwrap(), Regex::new(r"(?i)(\bselect\b.+\bfrom\b)").unwrap(), Regex::new(r"(?i)(\bdrop\b\s+\btable\b)").unwrap(), Regex::new(r"(?i)('?\s*(or|and)\s+\d+\s*=\s*\d+)").unwrap(), Regex::new(r"(/\*|\*/|--)").unwrap(), Regex::new(r"(?i)(\bsleep\b\s*\()").unwrap()], xss: vec![Regex::new(r"(?i)(<\s*script)").unwrap(), Regex::new(r"(?i)(javascript\s*:)").unwrap(), Regex::new(r"(?i)(on(error|load|click|mouseover)\s*=)").unwrap(), Regex::new(r"(?i)(<\s*(iframe|object|embed|svg)\b)").unwrap()], path: vec![Regex::new(r"(\.\./|\.\.\\)").unwrap(), Regex::new(r"(%2e%2e%2f|%2e%2e/)").unwrap(), Regex::new(r"(%00|%0d%0a)").unwrap()], log: vec![Regex::new(r"[\r\n]").unwrap()] } }
     fn detect(&self, s: &str) -> Option<InjectionType> { for p in &self.path { if p.is_match(s) { return Some(InjectionType::PathTraversal); } } for p in &self.sql { if p.is_match(s) { return Some(InjectionType::SqlInjection); } } for p in &self.xss { if p.is_match(s) { return Some(InjectionType::Xss); } } for p in &self.log { if p.is_match(s) { return Some(InjectionType::LogInjection); } } None }
     fn detect_url(&self, s: &str) -> Option<InjectionType> { for p in &self.path { if p.is_match(s) { return Some(InjectionType::PathTraversal); } } for p in &self.xss { if p.is_match(s) { return Some(InjectionType::Xss); } } None }
+    /// Rules for allow-listed fields: only script execution vectors.
+    fn detect_loose(&self, s: &str) -> Option<InjectionType> { self.xss[..2].iter().any(|p| p.is_match(s)).then_some(InjectionType::Xss) }
+    /// Body strings are free text (prompts, descriptions), so newlines are not treated as log injection.
+    fn detect_body(&self, s: &str) -> Option<InjectionType> { for p in &self.path { if p.is_match(s) { return Some(InjectionType::PathTraversal); } } for p in &self.sql { if p.is_match(s) { return Some(InjectionType::SqlInjection); } } for p in &self.xss { if p.is_match(s) { return Some(InjectionType::Xss); } } None }
+    fn scan_json(&self, v: &serde_json::Value, path: &str, config: &SanitizeConfig) -> Option<Detection> {
+        match v {
+            serde_json::Value::String(s) => { let t = if config.is_loose(path) { self.detect_loose(s) } else { self.detect_body(s) }; t.map(|injection_type| Detection { injection_type, field: format!("body.{}", path) }) }
+            serde_json::Value::Array(a) => a.iter().enumerate().find_map(|(i, x)| self.scan_json(x, &format!("{}[{}]", path, i), config)),
+            serde_json::Value::Object(o) => o.iter().find_map(|(k, x)| self.scan_json(x, &if path.is_empty() { k.clone() } else { format!("{}.{}", path, k) }, config)),
+            _ => None,
+        }
+    }
+    fn scan_request(&self, req: &Request, path: &str, config: &SanitizeConfig) -> Option<Detection> {
+        if let Some(t) = self.detect_url(path) { return Some(Detection { injection_type: t, field: "path".into() }); }
+        if let Some(q) = req.uri().query() {
+            for (i, pair) in q.split('&').enumerate() {
+                let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+                if let Some(t) = self.detect(v).or_else(|| self.detect_url(k)) {
+                    let field = if self.detect(k).or_else(|| self.detect_url(k)).is_some() { format!("query[{}]", i) } else { format!("query.{}", k) };
+                    return Some(Detection { injection_type: t, field });
+                }
+            }
+        }
+        for (n, v) in req.headers() {
+            let h = n.as_str().to_lowercase();
+            if config.skip_headers.contains(&h) { continue; }
+            if let Some(t) = v.to_str().ok().and_then(|vs| self.detect(vs)) { return Some(Detection { injection_type: t, field: format!("header.{}", h) }); }
+        }
+        None
+    }
 }
 #[derive(Clone)] pub struct InputSanitizerLayer { config: Arc<SanitizeConfig>, patterns: Arc<Patterns> }
 impl InputSanitizerLayer { pub fn new(config: SanitizeConfig) -> Self { Self { config: Arc::new(config), patterns: Arc::new(Patterns::new()) } } }
@@ -31,9 +68,24 @@ impl<S> Layer<S> for InputSanitizerLayer { type Service = InputSanitizerService<
 impl<S> Service<Request> for InputSanitizerService<S> where S: Service<Request, Response = Response> + Clone + Send + 'static, S::Future: Send + 'static, {
     type Response = Response; type Error = S::Error; type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> { self.inner.poll_ready(cx) }
-    fn call(&mut self, req: Request) -> Self::Future { let config = self.config.clone(); let patterns = self.patterns.clone(); let mut inner = self.inner.clone(); Box::pin(async move { let path = req.uri().path().to_string(); if config.exempt_paths.iter().any(|p| path.starts_with(p)) { return inner.call(req).await; } if req.uri().to_string().len() > config.max_url_length { counter!("sanitizer_url_too_long").increment(1); if config.block_on_detection { return Ok(bad_req("URL too long")); } } if let Some(t) = patterns.detect_url(&path) { counter!("sanitizer_blocked").increment(1); warn!(path=%path, "Injection in URL"); if config.block_on_detection { return Ok(bad_req(&t.to_string())); } } if let Some(q) = req.uri().query() { if let Some(t) = patterns.detect(q) { counter!("sanitizer_blocked").increment(1); if config.block_on_detection { return Ok(bad_req(&t.to_string())); } } } for (n, v) in req.headers() { let h = n.as_str().to_lowercase(); if config.skip_headers.contains(&h) { continue; } if let Ok(vs) = v.to_str() { if vs.len() > config.max_header_value_length { if config.block_on_detection { return Ok(bad_req("Header too long")); } } if let Some(t) = patterns.detect(vs) { counter!("sanitizer_blocked").increment(1); if config.block_on_detection { return Ok(bad_req(&t.to_string())); } } } } inner.call(req).await }) }
+    fn call(&mut self, req: Request) -> Self::Future {
+        let config = self.config.clone(); let patterns = self.patterns.clone(); let mut inner = self.inner.clone();
+        Box::pin(async move {
+            let path = req.uri().path().to_string(); if config.exempt_paths.iter().any(|p| path.starts_with(p)) { return inner.call(req).await; }
+            if req.uri().to_string().len() > config.max_url_length { counter!("sanitizer_url_too_long").increment(1); if config.block_on_detection { return Ok(bad_req("URL too long", None)); } }
+            if config.block_on_detection && req.headers().iter().any(|(n, v)| !config.skip_headers.contains(&n.as_str().to_lowercase()) && v.len() > config.max_header_value_length) { return Ok(bad_req("Header too long", None)); }
+            if let Some(d) = patterns.scan_request(&req, &path, &config) { counter!("sanitizer_blocked").increment(1); warn!(field=?d.field, injection_type=%d.injection_type, "Injection detected"); if config.block_on_detection { return Ok(bad_req(&d.injection_type.to_string(), Some(&d))); } }
+            let is_json = req.headers().get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()).is_some_and(|c| c.starts_with("application/json"));
+            let len = req.headers().get(header::CONTENT_LENGTH).and_then(|v| v.to_str().ok()).and_then(|v| v.parse::<usize>().ok());
+            if !(config.scan_json_body && is_json && len.is_some_and(|l| l <= config.max_body_scan_bytes)) { return inner.call(req).await; }
+            let (parts, body) = req.into_parts();
+            let bytes = match axum::body::to_bytes(body, config.max_body_scan_bytes).await { Ok(b) => b, Err(_) => return Ok(bad_req("Unreadable body", None)) };
+            if let Ok(v) = serde_json::from_slice::<serde_json::Value>(&bytes) { if let Some(d) = patterns.scan_json(&v, "", &config) { counter!("sanitizer_blocked").increment(1); warn!(field=?d.field, injection_type=%d.injection_type, "Injection detected in body"); if config.block_on_detection { return Ok(bad_req(&d.injection_type.to_string(), Some(&d))); } } }
+            inner.call(Request::from_parts(parts, Body::from(bytes))).await
+        })
+    }
 }
-fn bad_req(detail: &str) -> Response { (StatusCode::BAD_REQUEST, Json(json!({"success":false,"error":"Malicious input detected","error_code":"MALICIOUS_INPUT","detail":detail}))).into_response() }
+fn bad_req(detail: &str, detection: Option<&Detection>) -> Response { let mut body = json!({"success":false,"error":"Malicious input detected","error_code":"MALICIOUS_INPUT","detail":detail}); if let Some(d) = detection { body["injection_type"] = json!(d.injection_type); body["field"] = json!(d.field); } (StatusCode::BAD_REQUEST, Json(body)).into_response() }
 
 #[cfg(test)]
 mod tests {
@@ -196,6 +248,64 @@ mod tests {
         assert!(config.exempt_paths.contains(&"/health".to_string()));
     }
 
+    // Field reporting
+    fn config_with_body() -> SanitizeConfig {
+        SanitizeConfig { scan_json_body: true, loose_fields: vec!["description".into()], ..Default::default() }
+    }
+
+    #[test]
+    fn test_scan_json_reports_sql_field_path() {
+        let body = json!({"name": "ok", "tasks": [{"instruction": "fine"}, {"instruction": "1' UNION SELECT password FROM users"}]});
+        let d = patterns().scan_json(&body, "", &config_with_body()).unwrap();
+        assert_eq!(d.injection_type, InjectionType::SqlInjection);
+        assert_eq!(d.field, "body.tasks[1].instruction");
+    }
+
+    #[test]
+    fn test_scan_json_reports_script_field_path() {
+        let body = json!({"agent": {"name": "<script>alert(1)</script>"}});
+        let d = patterns().scan_json(&body, "", &config_with_body()).unwrap();
+        assert_eq!(d.injection_type, InjectionType::Xss);
+        assert_eq!(d.field, "body.agent.name");
+    }
+
+    #[test]
+    fn test_loose_field_allows_markdown_but_not_scripts() {
+        let config = config_with_body();
+        let markdown = json!({"description": "# Title\n\n---\n<svg width=\"10\"></svg> see ../docs"});
+        assert!(patterns().scan_json(&markdown, "", &config).is_none());
+        let script = json!({"description": "<script>steal()</script>"});
+        assert_eq!(patterns().scan_json(&script, "", &config).unwrap().injection_type, InjectionType::Xss);
+    }
+
+    #[test]
+    fn test_scan_request_reports_query_param() {
+        let req = Request::builder().uri("/api/v1/tasks?page=1&q=DROP%20TABLE%20tasks;%20--").body(Body::empty()).unwrap();
+        let d = patterns().scan_request(&req, "/api/v1/tasks", &SanitizeConfig::default()).unwrap();
+        assert_eq!(d, Detection { injection_type: InjectionType::SqlInjection, field: "query.q".into() });
+    }
+
+    #[test]
+    fn test_scan_request_reports_malicious_query_key_by_position() {
+        let req = Request::builder().uri("/api/v1/tasks?page=1&javascript:alert(1)=x").body(Body::empty()).unwrap();
+        let d = patterns().scan_request(&req, "/api/v1/tasks", &SanitizeConfig::default()).unwrap();
+        assert_eq!(d, Detection { injection_type: InjectionType::Xss, field: "query[1]".into() });
+    }
+
+    #[tokio::test]
+    async fn test_rejection_names_type_and_field_without_content() {
+        use tower::ServiceExt;
+        let svc = InputSanitizerLayer::new(config_with_body()).layer(tower::service_fn(|_req: Request| async { Ok::<_, std::convert::Infallible>(StatusCode::OK.into_response()) }));
+        let payload = r#"{"name":"<script>alert(1)</script>"}"#;
+        let req = Request::builder().method("POST").uri("/api/v1/agents").header(header::CONTENT_TYPE, "application/json").header(header::CONTENT_LENGTH, payload.len()).body(Body::from(payload)).unwrap();
+        let resp = svc.oneshot(req).await.unwrap();
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = serde_json::from_slice(&axum::body::to_bytes(resp.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["injection_type"], "Xss");
+        assert_eq!(body["field"], "body.name");
+        assert!(!body.to_string().contains("alert"));
+    }
+
     // Priority behavior: path traversal should be detected before SQL
     #[test]
     fn test_detect_priority_path_over_sql() {
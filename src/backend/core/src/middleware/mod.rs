@@ -9,17 +9,25 @@ pub mod audit;
 pub mod csrf;
 pub mod api_key_rotation;
 pub mod input_sanitizer;
+pub mod route_policy;
+pub mod request_id;
+pub mod client_ip;
 
 pub use rate_limit::{RateLimitLayer, RateLimitConfig, RateLimitError};
 pub use auth::{AuthLayer, AuthConfig, Claims, AuthError, AuthContext, AuthMethod};
 pub use tracing::{TracingLayer, TracingConfig, RequestContext};
-pub use compression::{CompressionLayer, CompressionConfig, CompressionAlgorithm, CompressionLevel};
+#[allow(deprecated)]
+pub use compression::CompressionLayer;
+pub use compression::{CompressionNegotiationLayer, CompressionConfig, CompressionAlgorithm, CompressionLevel, EncodingNegotiation, parse_accept_encoding};
 pub use security_headers::{SecurityHeadersLayer, SecurityHeadersConfig, FrameOptions, ReferrerPolicy};
 pub use request_size::{RequestSizeLayer, RequestSizeConfig};
 pub use audit::{AuditLayer, AuditConfig, AuditEntry, AuditLevel, AuditLogger};
 pub use csrf::{CsrfLayer, CsrfConfig};
-pub use api_key_rotation::{ApiKeyManager, ApiKeyConfig, ApiKeyEntry, GeneratedKey, KeyStatus};
-pub use input_sanitizer::{InputSanitizerLayer, SanitizeConfig, InjectionType};
+pub use api_key_rotation::{ApiKeyManager, ApiKeyConfig, ApiKeyEntry, GeneratedKey, KeyStatus, RotatedKey};
+pub use input_sanitizer::{InputSanitizerLayer, SanitizeConfig, InjectionType, Detection};
+pub use request_id::{RequestIdLayer, RequestId, REQUEST_ID_HEADER};
+pub use client_ip::TrustedProxies;
+pub use route_policy::{RoutePolicyLayer, RoutePolicyConfig, RoutePolicy, RoutePolicyPredicate, CachePolicy, SkipCompression, SkipCache};
 
 #[derive(Debug, Clone, Default)]
 pub struct MiddlewareConfig {
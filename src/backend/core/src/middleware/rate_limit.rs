@@ -5,9 +5,9 @@
 //! - Sliding window counter for accurate burst control
 //! - Per-client (IP/API key) limits
 //! - Per-endpoint limits
-//! - Redis-backed distributed rate limiting
-//! - Graceful degradation when Redis is unavailable
-//! - Standard X-RateLimit headers
+//! - Redis-backed distributed rate limiting (atomic Lua sliding window shared by all instances)
+//! - Graceful degradation to a local token bucket when Redis is unreachable
+//! - Standard `RateLimit-*` / `Retry-After` headers (plus legacy `X-RateLimit-*`)
 //!
 //! # Example
 //!
@@ -44,13 +44,17 @@ use std::{
     net::{IpAddr, SocketAddr},
     sync::Arc,
     task::{Context, Poll},
-    time::{Duration, Instant},
+    time::Duration,
 };
 use thiserror::Error;
 use tokio::sync::Semaphore;
+use tokio::time::Instant;
 use tower::{Layer, Service};
 use tracing::{debug, error, info, warn};
 
+use crate::api::ApiResponse;
+use crate::error::{ApexError, ErrorCode};
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Error Types
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -83,34 +87,17 @@ impl IntoResponse for RateLimitError {
                 retry_after_secs,
             } => {
                 let mut headers = HeaderMap::new();
-                headers.insert(
-                    "X-RateLimit-Limit",
-                    HeaderValue::from_str(&limit.to_string()).unwrap(),
-                );
-                headers.insert(
-                    "X-RateLimit-Remaining",
-                    HeaderValue::from_str(&remaining.to_string()).unwrap(),
-                );
-                headers.insert(
-                    "X-RateLimit-Reset",
-                    HeaderValue::from_str(&reset_at.timestamp().to_string()).unwrap(),
-                );
+                insert_limit_headers(&mut headers, limit, remaining, reset_at);
                 headers.insert(
                     "Retry-After",
                     HeaderValue::from_str(&retry_after_secs.to_string()).unwrap(),
                 );
 
-                let body = serde_json::json!({
-                    "success": false,
-                    "error": {
-                        "code": "RATE_LIMIT_EXCEEDED",
-                        "message": "Too many requests. Please slow down.",
-                        "limit": limit,
-                        "remaining": remaining,
-                        "reset_at": reset_at.to_rfc3339(),
-                        "retry_after_secs": retry_after_secs,
-                    }
-                });
+                let error = ApexError::new(
+                    ErrorCode::RateLimited,
+                    format!("Too many requests. Retry in {}s.", retry_after_secs),
+                );
+                let body = ApiResponse::<()>::from_apex_error(&error);
 
                 (StatusCode::TOO_MANY_REQUESTS, headers, axum::Json(body)).into_response()
             }
@@ -132,6 +119,24 @@ impl IntoResponse for RateLimitError {
     }
 }
 
+/// Set `RateLimit-Limit`/`-Remaining`/`-Reset` (reset as delta seconds) and the legacy `X-RateLimit-*` equivalents.
+fn insert_limit_headers(headers: &mut HeaderMap, limit: u64, remaining: u64, reset_at: DateTime<Utc>) {
+    let reset_in = ceil_secs((reset_at - Utc::now()).to_std().unwrap_or_default());
+    let values = [
+        ("RateLimit-Limit", limit.to_string()),
+        ("RateLimit-Remaining", remaining.to_string()),
+        ("RateLimit-Reset", reset_in.to_string()),
+        ("X-RateLimit-Limit", limit.to_string()),
+        ("X-RateLimit-Remaining", remaining.to_string()),
+        ("X-RateLimit-Reset", reset_at.timestamp().to_string()),
+    ];
+    for (name, value) in values {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Configuration
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -339,6 +344,11 @@ impl TokenBucket {
         self.last_refill = now;
     }
 
+    /// Time until the bucket is back at capacity.
+    fn time_until_full(&self) -> Duration {
+        Duration::from_secs_f64((self.capacity - self.tokens).max(0.0) / self.refill_rate)
+    }
+
     /// Calculate time until tokens are available.
     fn time_until_available(&self, tokens: u64) -> Duration {
         let tokens_needed = (tokens as f64) - self.tokens;
@@ -350,6 +360,11 @@ impl TokenBucket {
     }
 }
 
+/// Whole seconds, rounded up, so clients never retry before the limit lifts.
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Sliding Window Counter
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -398,6 +413,11 @@ impl WindowEntry {
         (self.current_count as f64 + self.previous_count as f64 * previous_weight).ceil() as u64
     }
 
+    /// Time until the current window ends.
+    fn time_until_reset(&self) -> Duration {
+        self.window_duration.saturating_sub(self.window_start.elapsed())
+    }
+
     /// Maybe rotate to next window.
     fn maybe_rotate(&mut self) {
         let elapsed = self.window_start.elapsed();
@@ -478,6 +498,44 @@ impl InMemoryState {
     }
 }
 
+/// Sliding window counter evaluated atomically in Redis.
+///
+/// Uses the server clock so replicas with skewed clocks agree on the window. Only
+/// accepted requests are counted. Returns `{allowed, remaining, retry_after_ms, reset_ms}`.
+const SLIDING_WINDOW_SCRIPT: &str = r#"
+local limit = tonumber(ARGV[1])
+local window_ms = tonumber(ARGV[2])
+local t = redis.call('TIME')
+local now_ms = tonumber(t[1]) * 1000 + math.floor(tonumber(t[2]) / 1000)
+local idx = math.floor(now_ms / window_ms)
+local elapsed = now_ms - idx * window_ms
+local reset_ms = window_ms - elapsed
+local cur_key = KEYS[1] .. ':' .. idx
+local prev_key = KEYS[1] .. ':' .. (idx - 1)
+
+local current = tonumber(redis.call('GET', cur_key) or '0')
+local previous = tonumber(redis.call('GET', prev_key) or '0')
+local weight = reset_ms / window_ms
+
+if current + previous * weight + 1 > limit then
+    local room = limit - current - 1
+    local retry_ms = reset_ms
+    if room >= 0 and previous > 0 then
+        retry_ms = math.ceil((1 - room / previous) * window_ms - elapsed)
+    end
+    return {0, 0, retry_ms, reset_ms}
+end
+
+current = redis.call('INCR', cur_key)
+redis.call('PEXPIRE', cur_key, window_ms * 2)
+
+local remaining = math.floor(limit - current - previous * weight)
+if remaining < 0 then
+    remaining = 0
+end
+return {1, remaining, 0, reset_ms}
+"#;
+
 /// Rate limiter with Redis backend for distributed limiting.
 pub struct RateLimiter {
     config: RateLimitConfig,
@@ -485,6 +543,7 @@ pub struct RateLimiter {
     redis_client: Option<redis::Client>,
     redis_healthy: Arc<RwLock<bool>>,
     health_check_semaphore: Arc<Semaphore>,
+    window_script: redis::Script,
 }
 
 impl RateLimiter {
@@ -492,19 +551,7 @@ impl RateLimiter {
     pub async fn new(config: RateLimitConfig) -> Result<Self, RateLimitError> {
         let redis_client = if let Some(ref url) = config.redis_url {
             match redis::Client::open(url.as_str()) {
-                Ok(client) => {
-                    // Test connection
-                    match client.get_multiplexed_async_connection().await {
-                        Ok(_) => {
-                            info!("Rate limiter connected to Redis at {}", url);
-                            Some(client)
-                        }
-                        Err(e) => {
-                            warn!("Failed to connect to Redis for rate limiting: {}. Using in-memory fallback.", e);
-                            None
-                        }
-                    }
-                }
+                Ok(client) => Some(client),
                 Err(e) => {
                     warn!("Failed to create Redis client: {}. Using in-memory fallback.", e);
                     None
@@ -515,13 +562,29 @@ impl RateLimiter {
             None
         };
 
-        Ok(Self {
+        let limiter = Self {
             config,
             in_memory: Arc::new(InMemoryState::new()),
             redis_client,
             redis_healthy: Arc::new(RwLock::new(true)),
             health_check_semaphore: Arc::new(Semaphore::new(1)),
-        })
+            window_script: redis::Script::new(SLIDING_WINDOW_SCRIPT),
+        };
+
+        // Test connection; keep the client so the limiter can recover once Redis is back
+        if let Some(ref client) = limiter.redis_client {
+            match client.get_multiplexed_async_connection().await {
+                Ok(_) => info!("Rate limiter connected to Redis"),
+                Err(e) => limiter.mark_redis_unhealthy(&e.into()),
+            }
+        }
+
+        Ok(limiter)
+    }
+
+    /// Whether checks are currently shared across instances through Redis.
+    pub fn is_distributed(&self) -> bool {
+        self.redis_client.is_some() && *self.redis_healthy.read()
     }
 
     /// Check if client is whitelisted.
@@ -569,22 +632,36 @@ impl RateLimiter {
             )
         };
 
-        // Try Redis first, fall back to in-memory
-        if self.redis_client.is_some() && *self.redis_healthy.read() {
+        // Without Redis configured, limits are per-instance by design
+        if self.redis_client.is_none() {
+            return self.check_in_memory(client_id, endpoint, limit, window_secs);
+        }
+
+        if *self.redis_healthy.read() {
             match self.check_redis(client_id, endpoint, limit, window_secs).await {
                 Ok(result) => return Ok(result),
                 Err(e) => {
-                    warn!("Redis rate limit check failed: {}. Falling back to in-memory.", e);
-                    self.mark_redis_unhealthy();
+                    self.mark_redis_unhealthy(&e);
                     if !self.config.graceful_degradation {
                         return Err(e);
                     }
                 }
             }
+        } else if !self.config.graceful_degradation {
+            return Err(RateLimitError::Internal("Redis unavailable for rate limiting".to_string()));
         }
 
-        // In-memory fallback
-        self.check_in_memory(client_id, endpoint, limit, window_secs)
+        // Redis unreachable: degrade to the local token bucket
+        let result = self.check_token_bucket(client_id, 1);
+        counter!(
+            "rate_limit_checks_total",
+            "client_type" => format!("{:?}", std::mem::discriminant(client_id)),
+            "endpoint" => endpoint.to_string(),
+            "allowed" => result.allowed.to_string(),
+            "backend" => "local_fallback"
+        )
+        .increment(1);
+        Ok(result)
     }
 
     /// Check rate limit using Redis (distributed).
@@ -598,41 +675,23 @@ impl RateLimiter {
         let client = self.redis_client.as_ref().unwrap();
         let mut conn = client.get_multiplexed_async_connection().await?;
 
-        let key = format!("{}{}:{}", self.config.redis_key_prefix, client_id.to_key(""), endpoint);
-        let now = Utc::now();
-        let window_start = now.timestamp() / (window_secs as i64) * (window_secs as i64);
-        let window_key = format!("{}:{}", key, window_start);
-
-        // Lua script for atomic increment and check
-        let script = redis::Script::new(
-            r#"
-            local key = KEYS[1]
-            local limit = tonumber(ARGV[1])
-            local window_secs = tonumber(ARGV[2])
-
-            local current = redis.call('INCR', key)
-            if current == 1 then
-                redis.call('EXPIRE', key, window_secs + 1)
-            end
-
-            return current
-            "#,
-        );
+        // Hash tag keeps both window keys in one cluster slot
+        let key = format!("{{{}{}:{}}}", self.config.redis_key_prefix, client_id.to_key(""), endpoint);
 
-        let count: u64 = script
-            .key(&window_key)
+        let (allowed, remaining, retry_ms, reset_ms): (i64, i64, i64, i64) = self
+            .window_script
+            .key(&key)
             .arg(limit)
-            .arg(window_secs)
+            .arg(window_secs.max(1) * 1000)
             .invoke_async(&mut conn)
             .await?;
 
-        let reset_at = DateTime::from_timestamp(window_start + window_secs as i64, 0)
-            .unwrap_or(now);
-        let remaining = limit.saturating_sub(count);
-        let allowed = count <= limit;
+        let allowed = allowed == 1;
+        let remaining = remaining.max(0) as u64;
+        let reset_at = Utc::now() + chrono::Duration::milliseconds(reset_ms.max(0));
 
         let retry_after_secs = if !allowed {
-            Some((reset_at - now).num_seconds().max(1) as u64)
+            Some((retry_ms.max(0) as u64).div_ceil(1000).max(1))
         } else {
             None
         };
@@ -674,13 +733,13 @@ impl RateLimiter {
         let mut window = entry.write();
         let count = window.increment();
 
-        let now = Utc::now();
-        let reset_at = now + chrono::Duration::seconds(window_secs as i64);
+        let reset_in = window.time_until_reset();
+        let reset_at = Utc::now() + chrono::Duration::from_std(reset_in).unwrap_or_default();
         let remaining = limit.saturating_sub(count);
         let allowed = count <= limit;
 
         let retry_after_secs = if !allowed {
-            Some(window_secs - (window.window_start.elapsed().as_secs() % window_secs))
+            Some(ceil_secs(reset_in).max(1))
         } else {
             None
         };
@@ -731,23 +790,33 @@ impl RateLimiter {
         let limit = self.config.burst_size;
 
         let retry_after = if !allowed {
-            Some(bucket.time_until_available(tokens).as_secs().max(1))
+            Some(ceil_secs(bucket.time_until_available(tokens)).max(1))
         } else {
             None
         };
+        let reset_in = chrono::Duration::from_std(bucket.time_until_full()).unwrap_or_default();
 
         RateLimitResult {
             allowed,
             limit,
             remaining,
-            reset_at: Utc::now() + chrono::Duration::seconds(1),
+            reset_at: Utc::now() + reset_in,
             retry_after_secs: retry_after,
         }
     }
 
-    /// Mark Redis as unhealthy and schedule health check.
-    fn mark_redis_unhealthy(&self) {
-        *self.redis_healthy.write() = false;
+    /// Mark Redis as unhealthy and poll until it recovers.
+    fn mark_redis_unhealthy(&self, cause: &RateLimitError) {
+        let was_healthy = std::mem::replace(&mut *self.redis_healthy.write(), false);
+        if !was_healthy {
+            return;
+        }
+
+        warn!(
+            "Redis unreachable for rate limiting ({}). Degrading to per-instance token bucket; limits are no longer shared.",
+            cause
+        );
+        counter!("rate_limit_degraded_total").increment(1);
 
         let client = self.redis_client.clone();
         let healthy = self.redis_healthy.clone();
@@ -756,11 +825,16 @@ impl RateLimiter {
         tokio::spawn(async move {
             // Only one health check at a time
             let _permit = semaphore.acquire().await;
+            let Some(client) = client else { return };
+
+            loop {
+                tokio::time::sleep(Duration::from_secs(5)).await;
 
-            // Wait before retrying
-            tokio::time::sleep(Duration::from_secs(5)).await;
+                // Limiter dropped, nobody left to recover for
+                if Arc::strong_count(&healthy) == 1 {
+                    return;
+                }
 
-            if let Some(ref client) = client {
                 if let Ok(mut conn) = client.get_multiplexed_async_connection().await {
                     let ping_result: Result<String, _> = redis::cmd("PING")
                         .query_async(&mut conn)
@@ -769,6 +843,7 @@ impl RateLimiter {
                     if ping_result.is_ok() {
                         info!("Redis connection recovered for rate limiting");
                         *healthy.write() = true;
+                        return;
                     }
                 }
             }
@@ -922,19 +997,7 @@ where
                     let mut response = inner.call(request).await?;
 
                     // Add rate limit headers to response
-                    let headers = response.headers_mut();
-                    headers.insert(
-                        "X-RateLimit-Limit",
-                        HeaderValue::from_str(&result.limit.to_string()).unwrap(),
-                    );
-                    headers.insert(
-                        "X-RateLimit-Remaining",
-                        HeaderValue::from_str(&result.remaining.to_string()).unwrap(),
-                    );
-                    headers.insert(
-                        "X-RateLimit-Reset",
-                        HeaderValue::from_str(&result.reset_at.timestamp().to_string()).unwrap(),
-                    );
+                    insert_limit_headers(response.headers_mut(), result.limit, result.remaining, result.reset_at);
 
                     Ok(response)
                 }
@@ -1237,6 +1300,140 @@ mod tests {
         limiter.cleanup_expired();
     }
 
+    #[tokio::test]
+    async fn test_unreachable_redis_degrades_to_token_bucket() {
+        let config = RateLimitConfig {
+            redis_url: Some("redis://127.0.0.1:1".into()),
+            burst_size: 2,
+            requests_per_second: 1,
+            ..Default::default()
+        };
+        let limiter = RateLimiter::new(config).await.unwrap();
+        assert!(!limiter.is_distributed());
+
+        let client = ClientId::Ip("10.0.0.5".parse().unwrap());
+        assert!(limiter.check(&client, "/test").await.unwrap().allowed);
+        assert!(limiter.check(&client, "/test").await.unwrap().allowed);
+        let result = limiter.check(&client, "/test").await.unwrap();
+        assert!(!result.allowed);
+        assert_eq!(result.limit, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_redis_without_degradation_errors() {
+        let config = RateLimitConfig {
+            redis_url: Some("redis://127.0.0.1:1".into()),
+            graceful_degradation: false,
+            ..Default::default()
+        };
+        let limiter = RateLimiter::new(config).await.unwrap();
+        let client = ClientId::Ip("10.0.0.6".parse().unwrap());
+        assert!(limiter.check(&client, "/test").await.is_err());
+    }
+
+    /// Runs against `REDIS_URL` (default localhost).
+    #[tokio::test]
+    #[ignore = "needs Redis at REDIS_URL"]
+    async fn test_redis_limit_shared_across_instances() {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".into());
+        let config = RateLimitConfig {
+            redis_url: Some(url),
+            redis_key_prefix: format!("apex:test:{}:", uuid::Uuid::new_v4()),
+            requests_per_second: 1,
+            window_size_secs: 30,
+            ..Default::default()
+        };
+        let first = RateLimiter::new(config.clone()).await.unwrap();
+        let second = RateLimiter::new(config).await.unwrap();
+        assert!(first.is_distributed() && second.is_distributed(), "Redis not reachable");
+
+        let client = ClientId::Ip("10.0.0.7".parse().unwrap());
+        for i in 0..30 {
+            let limiter = if i % 2 == 0 { &first } else { &second };
+            let result = limiter.check(&client, "/shared").await.unwrap();
+            assert!(result.allowed);
+            assert_eq!(result.remaining, 29 - i);
+        }
+
+        let rejected = second.check(&client, "/shared").await.unwrap();
+        assert!(!rejected.allowed);
+        assert_eq!(rejected.remaining, 0);
+        assert!(rejected.retry_after_secs.unwrap() >= 1);
+        assert!(!first.check(&client, "/shared").await.unwrap().allowed);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_429_headers_and_body_after_exhausting_window() {
+        use tower::ServiceExt;
+
+        let config = RateLimitConfig {
+            enabled: true,
+            requests_per_second: 2,
+            window_size_secs: 2,
+            ..Default::default()
+        };
+        let layer = RateLimitLayer::from_config(config).await.unwrap();
+        let service = layer.layer(tower::service_fn(|_req: Request<Body>| async {
+            Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+        }));
+        let request = || Request::builder().uri("/api/v1/tasks").body(Body::empty()).unwrap();
+
+        for expected_remaining in (0..4).rev() {
+            let response = service.clone().oneshot(request()).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()["RateLimit-Remaining"], expected_remaining.to_string());
+        }
+
+        let response = service.clone().oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let header = |name: &str| -> u64 { response.headers()[name].to_str().unwrap().parse().unwrap() };
+        assert_eq!(header("RateLimit-Limit"), 4);
+        assert_eq!(header("RateLimit-Remaining"), 0);
+        assert_eq!(header("Retry-After"), 2);
+        assert_eq!(header("RateLimit-Reset"), 2);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error_code"], "RateLimited");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_bucket_reset_reflects_refill() {
+        let config = RateLimitConfig {
+            enabled: true,
+            requests_per_second: 1,
+            burst_size: 2,
+            ..Default::default()
+        };
+        let limiter = RateLimiter::new(config).await.unwrap();
+        let client = ClientId::Ip("10.0.0.1".parse().unwrap());
+
+        assert!(limiter.check_token_bucket(&client, 1).allowed);
+        assert!(limiter.check_token_bucket(&client, 1).allowed);
+        let before = Utc::now();
+        let result = limiter.check_token_bucket(&client, 1);
+        let after = Utc::now();
+        assert!(!result.allowed);
+        assert_eq!(result.remaining, 0);
+        assert_eq!(result.retry_after_secs, Some(1));
+        // Two tokens to refill at one per second; the paused clock means no
+        // refill happened in between
+        assert!(result.reset_at >= before + chrono::Duration::seconds(2));
+        assert!(result.reset_at <= after + chrono::Duration::seconds(2));
+    }
+
+    #[test]
+    fn test_limit_headers() {
+        let mut headers = HeaderMap::new();
+        insert_limit_headers(&mut headers, 10, 3, Utc::now() + chrono::Duration::seconds(30));
+        assert_eq!(headers["RateLimit-Limit"], "10");
+        assert_eq!(headers["RateLimit-Remaining"], "3");
+        let reset: i64 = headers["RateLimit-Reset"].to_str().unwrap().parse().unwrap();
+        assert!((29..=30).contains(&reset));
+        assert_eq!(headers["X-RateLimit-Limit"], "10");
+    }
+
     #[test]
     fn test_extract_client_id_anonymous() {
         let headers = HeaderMap::new();
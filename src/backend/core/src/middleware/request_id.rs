@@ -0,0 +1,182 @@
+//! Request ids for correlating logs, traces and client reports.
+//!
+//! [`RequestIdLayer`] gives every request an id: the caller's `X-Request-Id`
+//! when it sends a usable one, otherwise a fresh UUID. The id is
+//!
+//! - stored in the request extensions as [`RequestId`], where handlers can
+//!   extract it,
+//! - recorded on a `request` span wrapping the rest of the stack, so every
+//!   log line emitted while handling the request carries `request_id`,
+//! - echoed in the `X-Request-Id` response header.
+//!
+//! Mount it outside the other middleware so their logs are correlated too:
+//!
+//! ```rust,ignore
+//! let app = Router::new()
+//!     .route("/api/v1/tasks", post(create_task))
+//!     .layer(TraceLayer::new_for_http())
+//!     .layer(RequestIdLayer::new());
+//! ```
+
+use axum::{
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, HeaderName, HeaderValue},
+    response::Response,
+};
+use futures::future::BoxFuture;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tracing::Instrument;
+use uuid::Uuid;
+
+/// Header carrying the request id in both directions.
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest caller-supplied id that is kept.
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// The id of the current request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    /// A fresh random id.
+    pub fn generate() -> Self {
+        Self(Uuid::new_v4().to_string())
+    }
+
+    /// The caller's id if it is short and printable; anything else could
+    /// break log lines or headers and is replaced.
+    fn from_header(value: Option<&HeaderValue>) -> Option<Self> {
+        let value = value?.to_str().ok()?;
+        let valid = !value.is_empty()
+            && value.len() <= MAX_REQUEST_ID_LEN
+            && value.bytes().all(|b| b.is_ascii_graphic());
+        valid.then(|| Self(value.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for RequestId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Extract the id set by [`RequestIdLayer`], or a fresh one outside it.
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for RequestId
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts.extensions.get::<RequestId>().cloned().unwrap_or_else(RequestId::generate))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Layer
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Assigns, records and echoes a [`RequestId`] for every request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestIdLayer;
+
+impl RequestIdLayer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+impl<S> Service<Request> for RequestIdService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request) -> Self::Future {
+        let request_id = RequestId::from_header(request.headers().get(&REQUEST_ID_HEADER))
+            .unwrap_or_else(RequestId::generate);
+        request.extensions_mut().insert(request_id.clone());
+
+        let span = tracing::info_span!("request", request_id = %request_id);
+        let mut inner = self.inner.clone();
+
+        Box::pin(
+            async move {
+                let mut response = inner.call(request).await?;
+                if let Ok(value) = HeaderValue::from_str(request_id.as_str()) {
+                    response.headers_mut().insert(REQUEST_ID_HEADER, value);
+                }
+                Ok(response)
+            }
+            .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/echo", get(|id: RequestId| async move { id.0 }))
+            .layer(RequestIdLayer::new())
+    }
+
+    async fn call(request_id: Option<&str>) -> (String, String) {
+        let mut request = Request::builder().uri("/echo");
+        if let Some(id) = request_id {
+            request = request.header(REQUEST_ID_HEADER, id);
+        }
+        let response = app().oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        let header = response.headers()[REQUEST_ID_HEADER].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (header, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_request_id_round_trips() {
+        let (header, seen_by_handler) = call(Some("client-abc-123")).await;
+        assert_eq!(header, "client-abc-123");
+        assert_eq!(seen_by_handler, "client-abc-123");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_generated_when_missing_or_invalid() {
+        let (header, seen_by_handler) = call(None).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(header, seen_by_handler);
+
+        let (header, _) = call(Some(&"x".repeat(MAX_REQUEST_ID_LEN + 1))).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+    }
+}
@@ -1,21 +1,274 @@
 //! Request body size limiting middleware.
-use axum::{extract::Request, http::StatusCode, response::{IntoResponse, Response}, Json};
+//!
+//! Each request gets the limit of the first route rule matching its path
+//! (patterns use `*` wildcards), or the default limit. A body over the limit
+//! is answered with a `413 Payload Too Large` [`ApiResponse`] naming the
+//! limit:
+//!
+//! - when `Content-Length` already exceeds it, before the handler runs;
+//! - otherwise, for chunked or mislabelled bodies, as soon as the bytes read
+//!   pass the limit. The body is counted as it streams, never buffered here.
+//!
+//! Axum's own 2 MB extractor limit would cap routes allowed more than that,
+//! so routers using this layer should disable it with
+//! `DefaultBodyLimit::disable()`.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
 use futures::future::BoxFuture;
+use futures::StreamExt;
 use metrics::counter;
-use serde_json::json;
-use std::{collections::HashMap, task::{Context, Poll}};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
 use tower::{Layer, Service};
 use tracing::warn;
+
+use crate::api::ApiResponse;
+use crate::cache::middleware::glob_matches;
+
+const KB: usize = 1024;
+const MB: usize = 1024 * KB;
+
+/// Body size limits.
 #[derive(Debug, Clone)]
-pub struct RequestSizeConfig { pub default_limit: usize, pub endpoint_limits: HashMap<String, usize>, pub excluded_paths: Vec<String> }
-impl Default for RequestSizeConfig { fn default() -> Self { Self { default_limit: 1_048_576, endpoint_limits: HashMap::new(), excluded_paths: vec![] } } }
-impl RequestSizeConfig { fn limit_for_path(&self, path: &str) -> Option<usize> { for e in &self.excluded_paths { if path.starts_with(e) { return None; } } for (p, l) in &self.endpoint_limits { if path.starts_with(p) { return Some(*l); } } Some(self.default_limit) } }
-#[derive(Debug, Clone)] pub struct RequestSizeLayer { config: RequestSizeConfig }
-impl RequestSizeLayer { pub fn new(config: RequestSizeConfig) -> Self { Self { config } } }
-impl<S> Layer<S> for RequestSizeLayer { type Service = RequestSizeService<S>; fn layer(&self, inner: S) -> Self::Service { RequestSizeService { inner, config: self.config.clone() } } }
-#[derive(Debug, Clone)] pub struct RequestSizeService<S> { inner: S, config: RequestSizeConfig }
-impl<S> Service<Request> for RequestSizeService<S> where S: Service<Request, Response = Response> + Clone + Send + 'static, S::Future: Send + 'static, {
-    type Response = Response; type Error = S::Error; type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
-    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> { self.inner.poll_ready(cx) }
-    fn call(&mut self, req: Request) -> Self::Future { let config = self.config.clone(); let mut inner = self.inner.clone(); Box::pin(async move { let path = req.uri().path().to_string(); if let Some(max) = config.limit_for_path(&path) { if let Some(cl) = req.headers().get("content-length").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<usize>().ok()) { if cl > max { counter!("http_request_size_exceeded_total").increment(1); warn!(path=%path, "Body exceeds limit"); return Ok((StatusCode::PAYLOAD_TOO_LARGE, Json(json!({"success":false,"error":"Payload too large","error_code":"PAYLOAD_TOO_LARGE"}))).into_response()); } } } inner.call(req).await }) }
+pub struct RequestSizeConfig {
+    /// Limit for paths without a rule
+    pub default_limit: usize,
+    /// Path patterns and their limit; the first match wins
+    pub endpoint_limits: Vec<(String, usize)>,
+    /// Path prefixes that are never limited
+    pub excluded_paths: Vec<String>,
+}
+
+impl Default for RequestSizeConfig {
+    fn default() -> Self {
+        Self {
+            default_limit: MB,
+            endpoint_limits: vec![
+                // DAG specs and templates can carry many tasks
+                ("/api/*/dags*".to_string(), 8 * MB),
+                ("/api/*/templates*".to_string(), 8 * MB),
+                // Bodies that are at most a few fields
+                ("/api/*/tasks/*/cancel".to_string(), 64 * KB),
+                ("/api/*/agents*".to_string(), 64 * KB),
+                ("/api/*/plugins/*".to_string(), 64 * KB),
+            ],
+            excluded_paths: vec![],
+        }
+    }
+}
+
+impl RequestSizeConfig {
+    /// No rules and no exclusions, only `default_limit`.
+    pub fn with_default_limit(default_limit: usize) -> Self {
+        Self {
+            default_limit,
+            endpoint_limits: vec![],
+            excluded_paths: vec![],
+        }
+    }
+
+    /// Add a rule. Rules added later lose to earlier ones on overlap.
+    pub fn route(mut self, pattern: impl Into<String>, limit: usize) -> Self {
+        self.endpoint_limits.push((pattern.into(), limit));
+        self
+    }
+
+    /// Never limit paths starting with `prefix`.
+    pub fn exclude(mut self, prefix: impl Into<String>) -> Self {
+        self.excluded_paths.push(prefix.into());
+        self
+    }
+
+    /// The limit for `path`, or `None` if it is excluded.
+    pub fn limit_for_path(&self, path: &str) -> Option<usize> {
+        if self.excluded_paths.iter().any(|prefix| path.starts_with(prefix.as_str())) {
+            return None;
+        }
+        let limit = self
+            .endpoint_limits
+            .iter()
+            .find(|(pattern, _)| glob_matches(pattern, path))
+            .map_or(self.default_limit, |(_, limit)| *limit);
+        Some(limit)
+    }
+}
+
+/// The 413 response for a body over `limit` bytes.
+fn payload_too_large(path: &str, limit: usize) -> Response {
+    counter!("http_request_size_exceeded_total").increment(1);
+    warn!(path = %path, limit, "Request body exceeds limit");
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        Json(ApiResponse::<()>::error_with_code(
+            format!("Request body exceeds the {} byte limit", limit),
+            "PAYLOAD_TOO_LARGE",
+        )),
+    )
+        .into_response()
+}
+
+/// `body` with a running byte count that fails the stream, and sets
+/// `exceeded`, once more than `limit` bytes have been read.
+fn limit_body(body: Body, limit: usize, exceeded: Arc<AtomicBool>) -> Body {
+    let mut read = 0usize;
+    let stream = body.into_data_stream().map(move |chunk| {
+        let chunk = chunk?;
+        read += chunk.len();
+        if read > limit {
+            exceeded.store(true, Ordering::Relaxed);
+            return Err(axum::Error::new(format!("request body exceeds the {} byte limit", limit)));
+        }
+        Ok(chunk)
+    });
+    Body::from_stream(stream)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Layer
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Applies a [`RequestSizeConfig`] to every request body.
+#[derive(Debug, Clone)]
+pub struct RequestSizeLayer {
+    config: Arc<RequestSizeConfig>,
+}
+
+impl RequestSizeLayer {
+    pub fn new(config: RequestSizeConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl<S> Layer<S> for RequestSizeLayer {
+    type Service = RequestSizeService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestSizeService {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestSizeService<S> {
+    inner: S,
+    config: Arc<RequestSizeConfig>,
+}
+
+impl<S> Service<Request> for RequestSizeService<S>
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        let path = request.uri().path().to_string();
+        let limit = self.config.limit_for_path(&path);
+        let mut inner = self.inner.clone();
+
+        Box::pin(async move {
+            let Some(limit) = limit else {
+                return inner.call(request).await;
+            };
+
+            let declared = request
+                .headers()
+                .get(header::CONTENT_LENGTH)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|s| s.parse::<usize>().ok());
+            if declared.is_some_and(|length| length > limit) {
+                return Ok(payload_too_large(&path, limit));
+            }
+
+            // Whatever the handler answers when reading the body fails, the
+            // client gets the same structured 413
+            let exceeded = Arc::new(AtomicBool::new(false));
+            let request = request.map(|body| limit_body(body, limit, exceeded.clone()));
+            let response = inner.call(request).await?;
+            if exceeded.load(Ordering::Relaxed) {
+                return Ok(payload_too_large(&path, limit));
+            }
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        let config = RequestSizeConfig::with_default_limit(16).route("/api/*/dags", 64);
+        Router::new()
+            .route("/api/v1/tasks", post(|body: String| async move { body.len().to_string() }))
+            .route("/api/v1/dags", post(|body: String| async move { body.len().to_string() }))
+            .layer(RequestSizeLayer::new(config))
+    }
+
+    async fn send(path: &str, body: Body, content_length: Option<usize>) -> (StatusCode, serde_json::Value) {
+        let mut request = Request::builder().method("POST").uri(path);
+        if let Some(length) = content_length {
+            request = request.header(header::CONTENT_LENGTH, length);
+        }
+        let response = app().oneshot(request.body(body).unwrap()).await.unwrap();
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    #[test]
+    fn test_limit_for_path() {
+        let config = RequestSizeConfig::default().exclude("/api/v1/plugins/upload");
+        assert_eq!(config.limit_for_path("/api/v1/dags"), Some(8 * MB));
+        assert_eq!(config.limit_for_path("/api/v2/templates/abc/instantiate"), Some(8 * MB));
+        assert_eq!(config.limit_for_path("/api/v1/tasks/abc/cancel"), Some(64 * KB));
+        assert_eq!(config.limit_for_path("/api/v1/tasks"), Some(MB));
+        assert_eq!(config.limit_for_path("/api/v1/plugins/upload"), None);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_body_gets_structured_413() {
+        let (status, body) = send("/api/v1/tasks", Body::from("x".repeat(17)), Some(17)).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["success"], false);
+        assert_eq!(body["error_code"], "PAYLOAD_TOO_LARGE");
+        assert_eq!(body["error"], "Request body exceeds the 16 byte limit");
+
+        // The DAG route allows more
+        let (status, _) = send("/api/v1/dags", Body::from("x".repeat(17)), Some(17)).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_streamed_body_checked_without_content_length() {
+        let chunks = (0..5).map(|_| Ok::<_, std::io::Error>("xxxxx"));
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        let (status, body) = send("/api/v1/tasks", body, None).await;
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(body["error"], "Request body exceeds the 16 byte limit");
+
+        let chunks = (0..3).map(|_| Ok::<_, std::io::Error>("xxxxx"));
+        let body = Body::from_stream(futures::stream::iter(chunks));
+        let (status, _) = send("/api/v1/tasks", body, None).await;
+        assert_eq!(status, StatusCode::OK);
+    }
 }
@@ -0,0 +1,339 @@
+//! Per-route compression and caching policy.
+//!
+//! Compression and HTTP caching are mounted once for the whole router, but
+//! not every route can take them: compressing a Server-Sent Events stream
+//! makes the encoder buffer events until it has a block to flush, and caching
+//! middleware that reads the whole body to hash an ETag never returns on a
+//! stream at all. [`RoutePolicyLayer`] lets paths opt out of either, or set
+//! their own `Cache-Control`, and always opts out WebSocket upgrades and
+//! `text/event-stream` responses whatever their path.
+//!
+//! The decision is recorded on the response as [`SkipCompression`] and
+//! [`SkipCache`] extensions, so the layer must sit *inside* the compression
+//! and cache layers. tower-http's `CompressionLayer` honours it through
+//! [`RoutePolicyPredicate`]:
+//!
+//! ```rust,ignore
+//! let predicate = DefaultPredicate::new().and(RoutePolicyPredicate);
+//!
+//! let app = Router::new()
+//!     .route("/metrics", get(metrics))
+//!     .layer(RoutePolicyLayer::new(RoutePolicyConfig::default()))
+//!     .layer(CompressionLayer::new().compress_when(predicate));
+//! ```
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderValue, Response as HttpResponse, StatusCode},
+    response::Response,
+};
+use futures::future::BoxFuture;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower::{Layer, Service};
+use tower_http::compression::Predicate;
+
+use crate::cache::middleware::glob_matches;
+
+/// Response extension telling the compression layer to leave the body alone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkipCompression;
+
+/// Response extension telling caching middleware not to buffer, store or tag
+/// the response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkipCache;
+
+/// `Cache-Control` used for responses that must never be cached.
+pub const NO_STORE: &str = "no-store";
+
+/// How a route's responses may be cached.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum CachePolicy {
+    /// Whatever the cache middleware decides
+    #[default]
+    Default,
+    /// Never cached: `Cache-Control: no-store` and no cache middleware
+    NoStore,
+    /// This exact `Cache-Control` value, replacing any the handler set
+    Custom(String),
+}
+
+/// What a route opts in or out of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoutePolicy {
+    /// Allow response compression
+    pub compress: bool,
+    /// Caching behaviour
+    pub cache: CachePolicy,
+}
+
+impl Default for RoutePolicy {
+    fn default() -> Self {
+        Self {
+            compress: true,
+            cache: CachePolicy::Default,
+        }
+    }
+}
+
+impl RoutePolicy {
+    /// Neither compressed nor cached: for streamed and upgraded responses.
+    pub fn streaming() -> Self {
+        Self {
+            compress: false,
+            cache: CachePolicy::NoStore,
+        }
+    }
+
+    /// Compressed as usual, never cached: for live data such as metrics.
+    pub fn no_store() -> Self {
+        Self {
+            compress: true,
+            cache: CachePolicy::NoStore,
+        }
+    }
+
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    pub fn with_cache_control(mut self, value: impl Into<String>) -> Self {
+        self.cache = CachePolicy::Custom(value.into());
+        self
+    }
+}
+
+/// Path patterns and the policy for each.
+///
+/// Patterns use `*` wildcards; the first matching rule wins, and paths with
+/// no rule get [`RoutePolicy::default`].
+#[derive(Debug, Clone)]
+pub struct RoutePolicyConfig {
+    pub rules: Vec<(String, RoutePolicy)>,
+}
+
+impl Default for RoutePolicyConfig {
+    fn default() -> Self {
+        Self {
+            rules: vec![
+                ("/ws".to_string(), RoutePolicy::streaming()),
+                ("/api/*/stream".to_string(), RoutePolicy::streaming()),
+                ("/api/*/events".to_string(), RoutePolicy::streaming()),
+                ("/metrics".to_string(), RoutePolicy::no_store()),
+                ("/health".to_string(), RoutePolicy::no_store()),
+            ],
+        }
+    }
+}
+
+impl RoutePolicyConfig {
+    /// No rules; only the automatic WebSocket and SSE exclusions apply.
+    pub fn empty() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Add a rule. Rules added later lose to earlier ones on overlap.
+    pub fn route(mut self, pattern: impl Into<String>, policy: RoutePolicy) -> Self {
+        self.rules.push((pattern.into(), policy));
+        self
+    }
+
+    /// The policy configured for `path`.
+    pub fn policy_for(&self, path: &str) -> RoutePolicy {
+        self.rules
+            .iter()
+            .find(|(pattern, _)| glob_matches(pattern, path))
+            .map(|(_, policy)| policy.clone())
+            .unwrap_or_default()
+    }
+}
+
+/// Whether a response is a stream or protocol switch, which is never
+/// compressed or cached regardless of configuration.
+fn is_streaming(response: &Response) -> bool {
+    if response.status() == StatusCode::SWITCHING_PROTOCOLS {
+        return true;
+    }
+    response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.trim_start().starts_with("text/event-stream"))
+}
+
+/// Apply `policy` to `response`.
+fn apply_policy(mut policy: RoutePolicy, response: &mut Response) {
+    if is_streaming(response) {
+        policy = RoutePolicy::streaming();
+    }
+
+    if !policy.compress {
+        response.extensions_mut().insert(SkipCompression);
+    }
+
+    let cache_control = match &policy.cache {
+        CachePolicy::Default => None,
+        CachePolicy::NoStore => {
+            response.extensions_mut().insert(SkipCache);
+            response.headers_mut().remove(header::ETAG);
+            Some(HeaderValue::from_static(NO_STORE))
+        }
+        CachePolicy::Custom(value) => HeaderValue::from_str(value).ok(),
+    };
+    if let Some(value) = cache_control {
+        response.headers_mut().insert(header::CACHE_CONTROL, value);
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Layer
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Applies a [`RoutePolicyConfig`] to every response.
+#[derive(Debug, Clone)]
+pub struct RoutePolicyLayer {
+    config: Arc<RoutePolicyConfig>,
+}
+
+impl RoutePolicyLayer {
+    pub fn new(config: RoutePolicyConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl Default for RoutePolicyLayer {
+    fn default() -> Self {
+        Self::new(RoutePolicyConfig::default())
+    }
+}
+
+impl<S> Layer<S> for RoutePolicyLayer {
+    type Service = RoutePolicyService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RoutePolicyService {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RoutePolicyService<S> {
+    inner: S,
+    config: Arc<RoutePolicyConfig>,
+}
+
+impl<S> Service<Request<Body>> for RoutePolicyService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let policy = self.config.policy_for(request.uri().path());
+        let mut inner = self.inner.clone();
+
+        Box::pin(async move {
+            let mut response = inner.call(request).await?;
+            apply_policy(policy, &mut response);
+            Ok(response)
+        })
+    }
+}
+
+/// tower-http compression predicate that honours [`SkipCompression`].
+///
+/// Combine it with the default predicate so size and content-type checks
+/// still apply.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoutePolicyPredicate;
+
+impl Predicate for RoutePolicyPredicate {
+    fn should_compress<B>(&self, response: &HttpResponse<B>) -> bool
+    where
+        B: axum::body::HttpBody,
+    {
+        response.extensions().get::<SkipCompression>().is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tower::ServiceExt;
+    use tower_http::compression::{predicate::DefaultPredicate, CompressionLayer};
+
+    fn app() -> Router {
+        let body = "x".repeat(4096);
+        let sse_body = body.clone();
+        let predicate = DefaultPredicate::new().and(RoutePolicyPredicate);
+        Router::new()
+            .route("/api/v1/tasks", get(move || async move { body }))
+            .route("/metrics", get(|| async { "x".repeat(4096) }))
+            .route(
+                "/api/v1/feed",
+                get(move || async move {
+                    ([(header::CONTENT_TYPE, "text/event-stream")], sse_body)
+                }),
+            )
+            .layer(RoutePolicyLayer::new(RoutePolicyConfig::default().route(
+                "/api/v1/tasks",
+                RoutePolicy::default().with_cache_control("private, max-age=5"),
+            )))
+            .layer(CompressionLayer::new().compress_when(predicate))
+    }
+
+    async fn get_gzip(path: &str) -> Response<axum::body::Body> {
+        let request = Request::builder()
+            .uri(path)
+            .header(header::ACCEPT_ENCODING, "gzip")
+            .body(Body::empty())
+            .unwrap();
+        app().oneshot(request).await.unwrap().map(Body::new)
+    }
+
+    #[tokio::test]
+    async fn test_route_policies() {
+        let tasks = get_gzip("/api/v1/tasks").await;
+        assert_eq!(tasks.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(tasks.headers()[header::CACHE_CONTROL], "private, max-age=5");
+
+        let metrics = get_gzip("/metrics").await;
+        assert_eq!(metrics.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(metrics.headers()[header::CACHE_CONTROL], NO_STORE);
+        assert!(metrics.extensions().get::<SkipCache>().is_some());
+
+        // SSE is recognized by content type, whatever the path
+        let feed = get_gzip("/api/v1/feed").await;
+        assert!(feed.headers().get(header::CONTENT_ENCODING).is_none());
+        assert_eq!(feed.headers()[header::CACHE_CONTROL], NO_STORE);
+    }
+
+    #[test]
+    fn test_policy_for() {
+        let config = RoutePolicyConfig::default();
+        assert_eq!(config.policy_for("/ws"), RoutePolicy::streaming());
+        assert_eq!(config.policy_for("/api/v2/stream"), RoutePolicy::streaming());
+        assert_eq!(config.policy_for("/api/v1/tasks"), RoutePolicy::default());
+
+        let config = RoutePolicyConfig::empty()
+            .route("/api/v1/reports/*", RoutePolicy::no_store())
+            .route("/api/*", RoutePolicy::default().with_compression(false));
+        assert_eq!(config.policy_for("/api/v1/reports/42"), RoutePolicy::no_store());
+        assert!(!config.policy_for("/api/v1/tasks").compress);
+    }
+}
@@ -1,83 +1,374 @@
 //! Security headers middleware.
-use axum::{extract::Request, http::{HeaderName, HeaderValue}, response::Response};
+//!
+//! Defaults depend on the deployment environment (`APEX_ENVIRONMENT`, see
+//! [`SecurityHeadersConfig::from_env`]), which is production unless set:
+//!
+//! - **production** (and anything unrecognized): a strict same-origin CSP,
+//!   one year of HSTS and `X-Frame-Options: DENY`
+//! - **development**: a CSP that lets local dev servers, hot reload and
+//!   WebSockets through, no HSTS, and `SAMEORIGIN` framing
+//!
+//! Every header is configurable on top of those defaults, including a
+//! custom CSP string. HSTS is only sent on HTTPS requests, detected from the
+//! URI scheme or an `X-Forwarded-Proto` set by a trusted TLS-terminating
+//! proxy (see [`TrustedProxies`]); browsers ignore it over plain HTTP anyway.
+
+use axum::{
+    extract::Request,
+    http::{HeaderMap, HeaderName, HeaderValue},
+    response::Response,
+};
 use futures::future::BoxFuture;
 use std::task::{Context, Poll};
 use tower::{Layer, Service};
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum FrameOptions { Deny, SameOrigin }
-impl Default for FrameOptions { fn default() -> Self { Self::Deny } }
+use super::client_ip::{peer_addr, TrustedProxies};
+use super::RequestId;
+
+/// CSP for production: only same-origin resources.
+pub const STRICT_CSP: &str = "default-src 'self'; script-src 'self'; style-src 'self' 'unsafe-inline'; img-src 'self' data:; font-src 'self'; connect-src 'self'; frame-ancestors 'none'; base-uri 'self'; form-action 'self'";
+
+/// CSP for development: inline and eval'd scripts for dev tooling, and
+/// connections to local servers over HTTP and WebSockets.
+pub const DEVELOPMENT_CSP: &str = "default-src 'self'; script-src 'self' 'unsafe-inline' 'unsafe-eval'; style-src 'self' 'unsafe-inline'; img-src 'self' data: blob:; font-src 'self' data:; connect-src 'self' http://localhost:* ws://localhost:* http://127.0.0.1:* ws://127.0.0.1:*; frame-ancestors 'self'; base-uri 'self'";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameOptions {
+    #[default]
+    Deny,
+    SameOrigin,
+}
+
+impl FrameOptions {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Deny => "DENY",
+            Self::SameOrigin => "SAMEORIGIN",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferrerPolicy {
+    NoReferrer,
+    NoReferrerWhenDowngrade,
+    Origin,
+    OriginWhenCrossOrigin,
+    SameOrigin,
+    StrictOrigin,
+    #[default]
+    StrictOriginWhenCrossOrigin,
+    UnsafeUrl,
+}
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ReferrerPolicy { NoReferrer, NoReferrerWhenDowngrade, Origin, OriginWhenCrossOrigin, SameOrigin, StrictOrigin, StrictOriginWhenCrossOrigin, UnsafeUrl }
-impl Default for ReferrerPolicy { fn default() -> Self { Self::StrictOriginWhenCrossOrigin } }
 impl std::fmt::Display for ReferrerPolicy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::NoReferrer => write!(f, "no-referrer"), Self::NoReferrerWhenDowngrade => write!(f, "no-referrer-when-downgrade"),
-            Self::Origin => write!(f, "origin"), Self::OriginWhenCrossOrigin => write!(f, "origin-when-cross-origin"),
-            Self::SameOrigin => write!(f, "same-origin"), Self::StrictOrigin => write!(f, "strict-origin"),
-            Self::StrictOriginWhenCrossOrigin => write!(f, "strict-origin-when-cross-origin"), Self::UnsafeUrl => write!(f, "unsafe-url"),
+            Self::NoReferrer => write!(f, "no-referrer"),
+            Self::NoReferrerWhenDowngrade => write!(f, "no-referrer-when-downgrade"),
+            Self::Origin => write!(f, "origin"),
+            Self::OriginWhenCrossOrigin => write!(f, "origin-when-cross-origin"),
+            Self::SameOrigin => write!(f, "same-origin"),
+            Self::StrictOrigin => write!(f, "strict-origin"),
+            Self::StrictOriginWhenCrossOrigin => write!(f, "strict-origin-when-cross-origin"),
+            Self::UnsafeUrl => write!(f, "unsafe-url"),
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct SecurityHeadersConfig {
-    pub frame_options: FrameOptions, pub referrer_policy: ReferrerPolicy, pub hsts_max_age: u64,
-    pub hsts_include_subdomains: bool, pub hsts_preload: bool, pub content_security_policy: String,
-    pub permissions_policy: String, pub enable_request_id: bool, pub remove_server_header: bool, pub api_no_cache: bool,
+    pub frame_options: FrameOptions,
+    pub referrer_policy: ReferrerPolicy,
+    /// HSTS lifetime in seconds; 0 disables the header
+    pub hsts_max_age: u64,
+    pub hsts_include_subdomains: bool,
+    pub hsts_preload: bool,
+    /// Full CSP header value; empty disables the header
+    pub content_security_policy: String,
+    pub permissions_policy: String,
+    pub enable_request_id: bool,
+    pub remove_server_header: bool,
+    pub api_no_cache: bool,
+    /// Proxies whose `X-Forwarded-Proto` is believed
+    pub trusted_proxies: TrustedProxies,
 }
+
 impl Default for SecurityHeadersConfig {
     fn default() -> Self {
-        Self { frame_options: FrameOptions::Deny, referrer_policy: ReferrerPolicy::StrictOriginWhenCrossOrigin, hsts_max_age: 31_536_000,
-            hsts_include_subdomains: true, hsts_preload: false,
-            content_security_policy: "default-src 'self'; script-src 'self'; style-src 'self' 'unsafe-inline'; img-src 'self' data:; font-src 'self'; connect-src 'self'; frame-ancestors 'none'; base-uri 'self'; form-action 'self'".into(),
+        Self::production()
+    }
+}
+
+impl SecurityHeadersConfig {
+    /// Strict headers for internet-facing deployments.
+    pub fn production() -> Self {
+        Self {
+            frame_options: FrameOptions::Deny,
+            referrer_policy: ReferrerPolicy::StrictOriginWhenCrossOrigin,
+            hsts_max_age: 31_536_000,
+            hsts_include_subdomains: true,
+            hsts_preload: false,
+            content_security_policy: STRICT_CSP.into(),
             permissions_policy: "camera=(), microphone=(), geolocation=(), payment=()".into(),
-            enable_request_id: true, remove_server_header: true, api_no_cache: true,
+            enable_request_id: true,
+            remove_server_header: true,
+            api_no_cache: true,
+            trusted_proxies: TrustedProxies::default(),
+        }
+    }
+
+    /// Relaxed headers for local development.
+    pub fn development() -> Self {
+        Self {
+            frame_options: FrameOptions::SameOrigin,
+            hsts_max_age: 0,
+            hsts_include_subdomains: false,
+            content_security_policy: DEVELOPMENT_CSP.into(),
+            ..Self::production()
+        }
+    }
+
+    /// Defaults for a named environment. `development`, `dev` and `local`
+    /// are relaxed; everything else, including staging, is strict.
+    pub fn for_environment(environment: &str) -> Self {
+        match environment.to_ascii_lowercase().as_str() {
+            "development" | "dev" | "local" => Self::development(),
+            _ => Self::production(),
+        }
+    }
+
+    /// Defaults for `APEX_ENVIRONMENT`, which is `production` when unset,
+    /// trusting the proxies in `APEX_TRUSTED_PROXIES`.
+    pub fn from_env() -> Self {
+        let environment = std::env::var("APEX_ENVIRONMENT").unwrap_or_else(|_| "production".to_string());
+        Self::for_environment(&environment).with_trusted_proxies(TrustedProxies::from_env())
+    }
+
+    /// Replace the CSP with `policy`; an empty string disables the header.
+    pub fn with_content_security_policy(mut self, policy: impl Into<String>) -> Self {
+        self.content_security_policy = policy.into();
+        self
+    }
+
+    /// Set the HSTS lifetime; 0 disables the header.
+    pub fn with_hsts_max_age(mut self, max_age: u64) -> Self {
+        self.hsts_max_age = max_age;
+        self
+    }
+
+    pub fn with_frame_options(mut self, frame_options: FrameOptions) -> Self {
+        self.frame_options = frame_options;
+        self
+    }
+
+    pub fn with_referrer_policy(mut self, referrer_policy: ReferrerPolicy) -> Self {
+        self.referrer_policy = referrer_policy;
+        self
+    }
+
+    pub fn with_trusted_proxies(mut self, trusted_proxies: TrustedProxies) -> Self {
+        self.trusted_proxies = trusted_proxies;
+        self
+    }
+
+    fn hsts_value(&self) -> Option<String> {
+        if self.hsts_max_age == 0 {
+            return None;
+        }
+        let mut hsts = format!("max-age={}", self.hsts_max_age);
+        if self.hsts_include_subdomains {
+            hsts.push_str("; includeSubDomains");
         }
+        if self.hsts_preload {
+            hsts.push_str("; preload");
+        }
+        Some(hsts)
+    }
+}
+
+/// Whether the client reached us over HTTPS, directly or through a trusted proxy.
+fn is_https(request: &Request, trusted_proxies: &TrustedProxies) -> bool {
+    request.uri().scheme_str() == Some("https")
+        || trusted_proxies
+            .forwarded(request.headers(), peer_addr(request), "x-forwarded-proto")
+            .is_some_and(|proto| proto.trim().eq_ignore_ascii_case("https"))
+}
+
+fn insert(headers: &mut HeaderMap, name: &'static str, value: &str) {
+    if let Ok(value) = HeaderValue::from_str(value) {
+        headers.insert(HeaderName::from_static(name), value);
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct SecurityHeadersLayer { config: SecurityHeadersConfig }
-impl SecurityHeadersLayer { pub fn new(config: SecurityHeadersConfig) -> Self { Self { config } } }
+pub struct SecurityHeadersLayer {
+    config: SecurityHeadersConfig,
+}
+
+impl SecurityHeadersLayer {
+    pub fn new(config: SecurityHeadersConfig) -> Self {
+        Self { config }
+    }
+}
+
 impl<S> Layer<S> for SecurityHeadersLayer {
     type Service = SecurityHeadersService<S>;
-    fn layer(&self, inner: S) -> Self::Service { SecurityHeadersService { inner, config: self.config.clone() } }
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SecurityHeadersService {
+            inner,
+            config: self.config.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
-pub struct SecurityHeadersService<S> { inner: S, config: SecurityHeadersConfig }
+pub struct SecurityHeadersService<S> {
+    inner: S,
+    config: SecurityHeadersConfig,
+}
+
 impl<S> Service<Request> for SecurityHeadersService<S>
-where S: Service<Request, Response = Response> + Clone + Send + 'static, S::Future: Send + 'static, {
-    type Response = Response; type Error = S::Error; type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
-    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> { self.inner.poll_ready(cx) }
+where
+    S: Service<Request, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
     fn call(&mut self, mut req: Request) -> Self::Future {
-        let config = self.config.clone(); let mut inner = self.inner.clone();
-        let request_id = if config.enable_request_id {
-            let id = req.headers().get("x-request-id").and_then(|v| v.to_str().ok()).map(|s| s.to_string()).unwrap_or_else(|| Uuid::new_v4().to_string());
-            if let Ok(val) = HeaderValue::from_str(&id) { req.headers_mut().insert(HeaderName::from_static("x-request-id"), val); }
-            Some(id)
-        } else { None };
+        let config = self.config.clone();
+        let mut inner = self.inner.clone();
+
+        // Reuse the id from RequestIdLayer when it runs outside this layer
+        let request_id = config.enable_request_id.then(|| {
+            let id = req
+                .extensions()
+                .get::<RequestId>()
+                .map(|id| id.0.clone())
+                .or_else(|| req.headers().get("x-request-id").and_then(|v| v.to_str().ok()).map(|s| s.to_string()))
+                .unwrap_or_else(|| Uuid::new_v4().to_string());
+            if let Ok(val) = HeaderValue::from_str(&id) {
+                req.headers_mut().insert(HeaderName::from_static("x-request-id"), val);
+            }
+            id
+        });
         let is_api = req.uri().path().starts_with("/api/");
+        let hsts = config.hsts_value().filter(|_| is_https(&req, &config.trusted_proxies));
+
         Box::pin(async move {
-            let mut response = inner.call(req).await?; let headers = response.headers_mut();
-            headers.insert(HeaderName::from_static("x-content-type-options"), HeaderValue::from_static("nosniff"));
-            headers.insert(HeaderName::from_static("x-frame-options"), HeaderValue::from_static(match &config.frame_options { FrameOptions::Deny => "DENY", FrameOptions::SameOrigin => "SAMEORIGIN" }));
-            headers.insert(HeaderName::from_static("x-xss-protection"), HeaderValue::from_static("0"));
-            let mut hsts = format!("max-age={}", config.hsts_max_age);
-            if config.hsts_include_subdomains { hsts.push_str("; includeSubDomains"); }
-            if config.hsts_preload { hsts.push_str("; preload"); }
-            if let Ok(v) = HeaderValue::from_str(&hsts) { headers.insert(HeaderName::from_static("strict-transport-security"), v); }
-            if !config.content_security_policy.is_empty() { if let Ok(v) = HeaderValue::from_str(&config.content_security_policy) { headers.insert(HeaderName::from_static("content-security-policy"), v); } }
-            if let Ok(v) = HeaderValue::from_str(&config.referrer_policy.to_string()) { headers.insert(HeaderName::from_static("referrer-policy"), v); }
-            if !config.permissions_policy.is_empty() { if let Ok(v) = HeaderValue::from_str(&config.permissions_policy) { headers.insert(HeaderName::from_static("permissions-policy"), v); } }
-            if let Some(id) = request_id { if let Ok(v) = HeaderValue::from_str(&id) { headers.insert(HeaderName::from_static("x-request-id"), v); } }
-            if config.remove_server_header { headers.remove("server"); }
-            if config.api_no_cache && is_api { headers.insert(HeaderName::from_static("cache-control"), HeaderValue::from_static("no-store, no-cache, must-revalidate, private")); }
+            let mut response = inner.call(req).await?;
+            let headers = response.headers_mut();
+            insert(headers, "x-content-type-options", "nosniff");
+            insert(headers, "x-frame-options", config.frame_options.as_str());
+            insert(headers, "x-xss-protection", "0");
+            if let Some(hsts) = hsts {
+                insert(headers, "strict-transport-security", &hsts);
+            }
+            if !config.content_security_policy.is_empty() {
+                insert(headers, "content-security-policy", &config.content_security_policy);
+            }
+            insert(headers, "referrer-policy", &config.referrer_policy.to_string());
+            if !config.permissions_policy.is_empty() {
+                insert(headers, "permissions-policy", &config.permissions_policy);
+            }
+            if let Some(id) = request_id {
+                insert(headers, "x-request-id", &id);
+            }
+            if config.remove_server_header {
+                headers.remove("server");
+            }
+            if config.api_no_cache && is_api {
+                insert(headers, "cache-control", "no-store, no-cache, must-revalidate, private");
+            }
             Ok(response)
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::ConnectInfo, routing::get, Router};
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    const PROXY: &str = "10.0.0.1:443";
+
+    fn trusting_proxy(config: SecurityHeadersConfig) -> SecurityHeadersConfig {
+        let proxy: SocketAddr = PROXY.parse().unwrap();
+        config.with_trusted_proxies(TrustedProxies::new([proxy.ip()]))
+    }
+
+    async fn request_headers(config: SecurityHeadersConfig, peer: &str, https: bool) -> HeaderMap {
+        let app = Router::new()
+            .route("/api/v1/tasks", get(|| async { "ok" }))
+            .layer(SecurityHeadersLayer::new(config));
+        let mut request = Request::builder()
+            .uri("/api/v1/tasks")
+            .extension(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+        if https {
+            request = request.header("x-forwarded-proto", "https");
+        }
+        let response = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+        response.headers().clone()
+    }
+
+    /// Headers for a request relayed by a trusted proxy.
+    async fn headers(config: SecurityHeadersConfig, https: bool) -> HeaderMap {
+        request_headers(trusting_proxy(config), PROXY, https).await
+    }
+
+    #[tokio::test]
+    async fn test_development_and_production_headers_differ() {
+        let prod = headers(SecurityHeadersConfig::for_environment("production"), true).await;
+        let dev = headers(SecurityHeadersConfig::for_environment("development"), true).await;
+
+        assert_eq!(prod["content-security-policy"], STRICT_CSP);
+        assert_eq!(dev["content-security-policy"], DEVELOPMENT_CSP);
+        assert_eq!(prod["x-frame-options"], "DENY");
+        assert_eq!(dev["x-frame-options"], "SAMEORIGIN");
+        assert_eq!(prod["strict-transport-security"], "max-age=31536000; includeSubDomains");
+        assert!(!dev.contains_key("strict-transport-security"));
+
+        // Staging and unknown names get production headers
+        let staging = SecurityHeadersConfig::for_environment("staging");
+        assert_eq!(staging.content_security_policy, STRICT_CSP);
+    }
+
+    #[tokio::test]
+    async fn test_hsts_only_over_https() {
+        assert!(!headers(SecurityHeadersConfig::production(), false).await.contains_key("strict-transport-security"));
+        assert!(headers(SecurityHeadersConfig::production(), true).await.contains_key("strict-transport-security"));
+
+        // Anyone can send X-Forwarded-Proto; only a trusted proxy is believed
+        let direct = request_headers(SecurityHeadersConfig::production(), PROXY, true).await;
+        assert!(!direct.contains_key("strict-transport-security"));
+        let spoofed = request_headers(trusting_proxy(SecurityHeadersConfig::production()), "198.51.100.7:5000", true).await;
+        assert!(!spoofed.contains_key("strict-transport-security"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_headers() {
+        let config = SecurityHeadersConfig::production()
+            .with_content_security_policy("default-src 'none'")
+            .with_hsts_max_age(600)
+            .with_frame_options(FrameOptions::SameOrigin)
+            .with_referrer_policy(ReferrerPolicy::NoReferrer);
+        let custom = headers(config, true).await;
+        assert_eq!(custom["content-security-policy"], "default-src 'none'");
+        assert_eq!(custom["strict-transport-security"], "max-age=600; includeSubDomains");
+        assert_eq!(custom["x-frame-options"], "SAMEORIGIN");
+        assert_eq!(custom["referrer-policy"], "no-referrer");
+
+        let without_csp = SecurityHeadersConfig::production().with_content_security_policy("");
+        assert!(!headers(without_csp, false).await.contains_key("content-security-policy"));
+    }
+}
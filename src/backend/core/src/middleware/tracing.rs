@@ -281,11 +281,17 @@ impl RequestContext {
     ) -> Self {
         let headers = request.headers();
 
-        // Get or generate request ID
-        let request_id = headers
-            .get(&config.request_id_header)
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string())
+        // Reuse the id assigned by RequestIdLayer, else read or generate one
+        let request_id = request
+            .extensions()
+            .get::<super::RequestId>()
+            .map(|id| id.0.clone())
+            .or_else(|| {
+                headers
+                    .get(&config.request_id_header)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|s| s.to_string())
+            })
             .unwrap_or_else(|| {
                 if config.generate_request_id {
                     Uuid::new_v4().to_string()
@@ -325,6 +331,15 @@ impl RequestContext {
                 .and_then(|s| s.parse().ok()),
         }
     }
+
+    /// Route label for HTTP metrics. Requests that matched no route fall
+    /// back to their path, normalized and bounded so scanners probing
+    /// random URLs can't mint a series per URL.
+    fn metrics_route(&self) -> String {
+        self.route
+            .clone()
+            .unwrap_or_else(|| crate::telemetry::metrics::path_label(&self.path))
+    }
 }
 
 /// Extract client IP from headers and connection.
@@ -563,7 +578,7 @@ where
                     counter!(
                         "http_requests_total",
                         "method" => ctx.method.clone(),
-                        "route" => ctx.route.clone().unwrap_or_else(|| ctx.path.clone()),
+                        "route" => ctx.metrics_route(),
                         "status" => "error"
                     )
                     .increment(1);
@@ -582,7 +597,7 @@ fn record_metrics(
     duration: Duration,
     response_size: Option<u64>,
 ) {
-    let route = ctx.route.clone().unwrap_or_else(|| ctx.path.clone());
+    let route = ctx.metrics_route();
     let status_str = status.as_u16().to_string();
 
     counter!(
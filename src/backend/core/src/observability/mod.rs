@@ -5,6 +5,8 @@ use opentelemetry::Context;
 use opentelemetry_otlp::WithExportConfig;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
+use crate::telemetry::SensitiveFieldRedactor;
+
 /// Initialize the observability stack.
 pub fn init(service_name: &str, otlp_endpoint: Option<&str>) -> anyhow::Result<()> {
     // Set up OpenTelemetry tracing if endpoint is provided
@@ -146,6 +148,22 @@ pub mod metrics {
             "apex_tool_latency_seconds",
             "Tool execution latency in seconds"
         );
+        describe_histogram!(
+            "apex_dag_duration_seconds",
+            "End-to-end DAG execution duration in seconds"
+        );
+        describe_counter!(
+            "apex_dag_tasks_total",
+            "Total number of tasks executed as part of a DAG"
+        );
+        describe_histogram!(
+            "apex_dag_cost_dollars",
+            "Total cost of a DAG execution in dollars"
+        );
+        describe_histogram!(
+            "apex_agent_wait_seconds",
+            "Time a task waited for a free agent slot, by outcome"
+        );
     }
 
     /// Record a task completion.
@@ -173,10 +191,16 @@ pub mod metrics {
         gauge!("apex_queue_depth").set(depth as f64);
     }
 
+    /// Record how long a task waited for an agent slot (`acquired` or `timeout`).
+    pub fn record_agent_wait(wait_secs: f64, outcome: &'static str) {
+        histogram!("apex_agent_wait_seconds", "outcome" => outcome).record(wait_secs);
+    }
+
     /// Record tool call latency.
     pub fn record_tool_latency(tool: &str, latency_secs: f64) {
-        histogram!("apex_tool_latency_seconds", "tool" => tool.to_string()).record(latency_secs);
-        counter!("apex_tool_calls_total", "tool" => tool.to_string()).increment(1);
+        let tool = crate::telemetry::metrics::bounded_label("apex_tool", tool);
+        histogram!("apex_tool_latency_seconds", "tool" => tool.clone()).record(latency_secs);
+        counter!("apex_tool_calls_total", "tool" => tool).increment(1);
     }
 }
 
@@ -215,6 +239,8 @@ pub enum ApexEvent {
         agent_id: String,
         tool_name: String,
         latency_ms: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        resources: Option<crate::plugins::PluginResourceReport>,
     },
     ContractExceeded {
         contract_id: String,
@@ -233,14 +259,21 @@ pub enum ApexEvent {
 }
 
 impl ApexEvent {
+    /// Serialize this event with sensitive fields and values redacted.
+    pub fn to_json(&self) -> serde_json::Value {
+        let value = serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
+        SensitiveFieldRedactor::global().redact_json(&value)
+    }
+
     /// Log this event.
     pub fn log(&self) {
+        let redactor = SensitiveFieldRedactor::global();
         match self {
             ApexEvent::TaskCreated { task_id, dag_id, name } => {
                 tracing::info!(
                     task_id = %task_id,
                     dag_id = %dag_id,
-                    name = %name,
+                    name = %redactor.redact("name", name),
                     "Task created"
                 );
             }
@@ -264,7 +297,7 @@ impl ApexEvent {
             ApexEvent::TaskFailed { task_id, error, retry_count } => {
                 tracing::error!(
                     task_id = %task_id,
-                    error = %error,
+                    error = %redactor.redact("error", error),
                     retry_count = %retry_count,
                     "Task failed"
                 );
@@ -277,12 +310,15 @@ impl ApexEvent {
                     "Agent spawned"
                 );
             }
-            ApexEvent::ToolCalled { task_id, agent_id, tool_name, latency_ms } => {
+            ApexEvent::ToolCalled { task_id, agent_id, tool_name, latency_ms, resources } => {
                 tracing::debug!(
                     task_id = %task_id,
                     agent_id = %agent_id,
                     tool_name = %tool_name,
                     latency_ms = %latency_ms,
+                    memory_used_bytes = resources.as_ref().map(|r| r.memory_used_bytes),
+                    network_requests = resources.as_ref().map(|r| r.network_requests),
+                    network_bytes = resources.as_ref().map(|r| r.network_bytes),
                     "Tool called"
                 );
             }
@@ -312,3 +348,59 @@ impl ApexEvent {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    /// Collects formatted log output for inspection.
+    #[derive(Clone, Default)]
+    struct Captured(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Captured {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    const SECRET: &str = "sk-abcdefghijklmnopqrstuvwxyz012345";
+
+    fn task_failed() -> ApexEvent {
+        ApexEvent::TaskFailed {
+            task_id: "task-1".to_string(),
+            error: format!("Provider rejected key {SECRET}"),
+            retry_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_event_log_redacts_secrets() {
+        let captured = Captured::default();
+        let writer = captured.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || task_failed().log());
+
+        let line = String::from_utf8(captured.0.lock().unwrap().clone()).unwrap();
+        assert!(line.contains("Task failed"), "nothing logged: {line}");
+        assert!(line.contains("Provider rejected key [REDACTED]"));
+        assert!(!line.contains(SECRET));
+    }
+
+    #[test]
+    fn test_event_json_redacts_secrets() {
+        let json = task_failed().to_json();
+        assert_eq!(json["event_type"], "TaskFailed");
+        assert_eq!(json["error"], "Provider rejected key [REDACTED]");
+    }
+}
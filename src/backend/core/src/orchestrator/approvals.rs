@@ -0,0 +1,107 @@
+//! Waiting on approvals.
+//!
+//! A task blocked on a risky action waits here for its approval to be
+//! decided. Whoever decides or expires the approval resolves it, which wakes
+//! every waiter, in this process, with the outcome.
+
+use std::time::Duration;
+use dashmap::DashMap;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use crate::db::ApprovalDecision;
+
+/// How an approval ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalResolution {
+    Approved,
+    Denied,
+    Expired,
+}
+
+impl ApprovalResolution {
+    pub fn is_approved(&self) -> bool {
+        matches!(self, Self::Approved)
+    }
+}
+
+impl From<ApprovalDecision> for ApprovalResolution {
+    fn from(decision: ApprovalDecision) -> Self {
+        match decision {
+            ApprovalDecision::Approve => Self::Approved,
+            ApprovalDecision::Deny => Self::Denied,
+        }
+    }
+}
+
+/// Pending approvals with someone waiting on them, keyed by approval id.
+#[derive(Debug, Default)]
+pub struct ApprovalGate {
+    waiters: DashMap<Uuid, watch::Sender<Option<ApprovalResolution>>>,
+}
+
+impl ApprovalGate {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wait up to `timeout` for `approval_id` to be resolved. `None` if it
+    /// wasn't in time.
+    pub async fn wait(&self, approval_id: Uuid, timeout: Duration) -> Option<ApprovalResolution> {
+        let mut resolved = self
+            .waiters
+            .entry(approval_id)
+            .or_insert_with(|| watch::channel(None).0)
+            .subscribe();
+        let outcome = tokio::time::timeout(timeout, resolved.wait_for(Option::is_some))
+            .await
+            .ok()
+            .and_then(|r| r.ok().and_then(|r| *r));
+        // The last waiter to give up removes the entry
+        self.waiters
+            .remove_if(&approval_id, |_, sender| outcome.is_none() && sender.receiver_count() <= 1);
+        outcome
+    }
+
+    /// Wake everything waiting on `approval_id` with `resolution`. Returns
+    /// whether anything was waiting.
+    pub fn resolve(&self, approval_id: Uuid, resolution: ApprovalResolution) -> bool {
+        match self.waiters.remove(&approval_id) {
+            Some((_, sender)) => sender.send(Some(resolution)).is_ok(),
+            None => false,
+        }
+    }
+
+    /// How many approvals something is waiting on.
+    pub fn waiting(&self) -> usize {
+        self.waiters.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_resolve_wakes_waiters() {
+        let gate = Arc::new(ApprovalGate::new());
+        let approval_id = Uuid::new_v4();
+        let waiter = tokio::spawn({
+            let gate = gate.clone();
+            async move { gate.wait(approval_id, Duration::from_secs(5)).await }
+        });
+        while gate.waiting() == 0 {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(gate.resolve(approval_id, ApprovalDecision::Approve.into()));
+        assert_eq!(waiter.await.unwrap(), Some(ApprovalResolution::Approved));
+        assert_eq!(gate.waiting(), 0);
+
+        // Nothing left to wake, and a wait that times out cleans up after itself
+        assert!(!gate.resolve(approval_id, ApprovalResolution::Denied));
+        assert_eq!(gate.wait(approval_id, Duration::from_millis(10)).await, None);
+        assert_eq!(gate.waiting(), 0);
+    }
+}
@@ -0,0 +1,159 @@
+//! DAG-wide budgets.
+//!
+//! A DAG submitted with a budget runs every task under a child of one parent
+//! contract. Each child reserves its limits in the parent when it is created,
+//! so tasks running in parallel can never be promised more than the budget
+//! holds; the reservation is released, and what the task actually spent is
+//! charged, when its [`TaskContract`] is dropped.
+
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use std::time::Duration;
+use parking_lot::Mutex;
+use tokio::sync::Notify;
+use uuid::Uuid;
+
+use crate::contracts::{AgentContract, ResourceLimits};
+use crate::error::{ApexError, Result};
+
+/// The parent contract of a DAG's tasks.
+#[derive(Debug)]
+pub struct DagBudget {
+    contract: Mutex<AgentContract>,
+    /// Notified whenever a task releases its reservation
+    released: Notify,
+}
+
+impl DagBudget {
+    pub fn new(dag_id: Uuid, limits: ResourceLimits) -> Self {
+        Self {
+            contract: Mutex::new(AgentContract::new(Uuid::nil(), dag_id, limits)),
+            released: Notify::new(),
+        }
+    }
+
+    /// The budget and what is left of it, less reservations.
+    pub fn snapshot(&self) -> (ResourceLimits, ResourceLimits) {
+        let contract = self.contract.lock();
+        (contract.limits.clone(), contract.remaining_limits())
+    }
+
+    /// Whether the token or cost budget is spent or the deadline has passed.
+    pub fn is_exhausted(&self) -> bool {
+        self.contract.lock().is_exhausted()
+    }
+
+    /// `limits` cut down to the time left before the budget's deadline.
+    pub fn time_bounded(&self, limits: &ResourceLimits) -> ResourceLimits {
+        limits.with_time_budget_deadline(self.contract.lock().expires_at)
+    }
+
+    /// Reserve a child contract for a task, capped at what is unreserved.
+    ///
+    /// While running tasks hold the whole budget this waits for one of them
+    /// to release its reservation. Fails once the budget is spent or its
+    /// deadline passes.
+    pub async fn reserve(
+        self: &Arc<Self>,
+        agent_id: Uuid,
+        task_id: Uuid,
+        limits: &ResourceLimits,
+    ) -> Result<TaskContract> {
+        loop {
+            let released = self.released.notified();
+            let wait = {
+                let mut parent = self.contract.lock();
+                if parent.is_exhausted() {
+                    let remaining = parent.remaining();
+                    return Err(if parent.is_expired() {
+                        ApexError::time_limit_exceeded(parent.limits.time_limit_seconds, 0)
+                    } else if remaining.tokens_used == 0 {
+                        ApexError::token_limit_exceeded(parent.usage.tokens_used, parent.limits.token_limit)
+                    } else {
+                        ApexError::cost_limit_exceeded(parent.usage.cost_used, parent.limits.cost_limit)
+                    });
+                }
+                let available = parent.remaining_limits();
+                if available.token_limit > 0 && available.cost_limit > 0.0 && available.api_call_limit > 0 {
+                    let contract = parent.create_child(agent_id, task_id, limits.capped_at(&available))?;
+                    return Ok(TaskContract { budget: Some(self.clone()), contract });
+                }
+                (parent.expires_at - chrono::Utc::now()).to_std().unwrap_or(Duration::ZERO)
+            };
+            if tokio::time::timeout(wait, released).await.is_err() {
+                let parent = self.contract.lock();
+                return Err(ApexError::time_limit_exceeded(parent.limits.time_limit_seconds, 0));
+            }
+        }
+    }
+
+    fn release(&self, child: &AgentContract) {
+        self.contract.lock().release_child(child);
+        self.released.notify_waiters();
+    }
+}
+
+/// A task's contract. A child of a DAG budget settles its reservation when
+/// dropped.
+#[derive(Debug)]
+pub struct TaskContract {
+    budget: Option<Arc<DagBudget>>,
+    contract: AgentContract,
+}
+
+impl TaskContract {
+    /// A contract outside any DAG budget.
+    pub fn standalone(contract: AgentContract) -> Self {
+        Self { budget: None, contract }
+    }
+}
+
+impl Deref for TaskContract {
+    type Target = AgentContract;
+
+    fn deref(&self) -> &AgentContract {
+        &self.contract
+    }
+}
+
+impl DerefMut for TaskContract {
+    fn deref_mut(&mut self) -> &mut AgentContract {
+        &mut self.contract
+    }
+}
+
+impl Drop for TaskContract {
+    fn drop(&mut self) {
+        if let Some(budget) = &self.budget {
+            budget.release(&self.contract);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reservation_waits_for_release() {
+        let budget = Arc::new(DagBudget::new(Uuid::new_v4(), ResourceLimits { token_limit: 10_000, ..ResourceLimits::medium() }));
+        let mut first = budget.reserve(Uuid::new_v4(), Uuid::new_v4(), &ResourceLimits::medium()).await.unwrap();
+        assert_eq!(first.limits.token_limit, 10_000);
+        assert_eq!(budget.snapshot().1.token_limit, 0);
+
+        // The second task waits until the first settles, then gets the rest
+        let second = tokio::spawn({
+            let budget = budget.clone();
+            async move { budget.reserve(Uuid::new_v4(), Uuid::new_v4(), &ResourceLimits::medium()).await.map(|r| r.limits.token_limit) }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!second.is_finished());
+        first.charge(6_000, 0.01);
+        drop(first);
+        assert_eq!(second.await.unwrap().unwrap(), 4_000);
+
+        // Spend is kept once every reservation is released
+        let (limits, remaining) = budget.snapshot();
+        assert_eq!((limits.token_limit, remaining.token_limit), (10_000, 4_000));
+    }
+}
@@ -7,9 +7,11 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::time::{Duration, Instant};
 use parking_lot::RwLock;
+use serde::Serialize;
 
 /// Circuit breaker states.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum CircuitState {
     /// Normal operation - requests allowed
     Closed,
@@ -168,7 +170,7 @@ impl CircuitBreaker {
 }
 
 /// Metrics for the circuit breaker.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CircuitBreakerMetrics {
     pub state: CircuitState,
     pub failure_count: u32,
@@ -178,7 +180,8 @@ pub struct CircuitBreakerMetrics {
 }
 
 /// Reason why a per-agent circuit was opened.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum AgentCircuitOpenReason {
     /// Too many consecutive failures.
     ConsecutiveFailures,
@@ -217,10 +220,22 @@ impl AgentCircuitState {
             open_reason: None,
         }
     }
+
+    fn metrics(&self, agent_id: &str) -> AgentCircuitMetrics {
+        AgentCircuitMetrics {
+            agent_id: agent_id.to_string(),
+            state: self.state,
+            failure_count: self.failure_count,
+            backoff_multiplier: self.backoff_multiplier,
+            total_successes: self.total_successes,
+            total_failures: self.total_failures,
+            open_reason: self.open_reason.clone(),
+        }
+    }
 }
 
 /// Per-agent circuit breaker metrics.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct AgentCircuitMetrics {
     pub agent_id: String,
     pub state: CircuitState,
@@ -341,7 +356,10 @@ impl AgentCircuitBreakerRegistry {
     }
 
     /// Record a failed execution for an agent.
-    pub fn record_failure(&self, agent_id: &str) {
+    ///
+    /// Returns the reason if this failure opened the agent's circuit, or
+    /// re-opened it after a failed half-open probe.
+    pub fn record_failure(&self, agent_id: &str) -> Option<AgentCircuitOpenReason> {
         self.global.record_failure();
 
         let mut agents = self.agents.write();
@@ -366,6 +384,7 @@ impl AgentCircuitBreakerRegistry {
                     backoff_multiplier = agent_state.backoff_multiplier,
                     "Agent circuit breaker re-opened with increased backoff"
                 );
+                return agent_state.open_reason.clone();
             }
             CircuitState::Closed => {
                 agent_state.failure_count += 1;
@@ -379,10 +398,12 @@ impl AgentCircuitBreakerRegistry {
                         threshold = self.agent_failure_threshold,
                         "Agent circuit breaker opened due to consecutive failures"
                     );
+                    return agent_state.open_reason.clone();
                 }
             }
             CircuitState::Open => {}
         }
+        None
     }
 
     /// Open the circuit for a specific agent due to loop detection.
@@ -412,15 +433,19 @@ impl AgentCircuitBreakerRegistry {
     /// Get metrics for a specific agent.
     pub fn agent_metrics(&self, agent_id: &str) -> Option<AgentCircuitMetrics> {
         let agents = self.agents.read();
-        agents.get(agent_id).map(|state| AgentCircuitMetrics {
-            agent_id: agent_id.to_string(),
-            state: state.state,
-            failure_count: state.failure_count,
-            backoff_multiplier: state.backoff_multiplier,
-            total_successes: state.total_successes,
-            total_failures: state.total_failures,
-            open_reason: state.open_reason.clone(),
-        })
+        agents.get(agent_id).map(|state| state.metrics(agent_id))
+    }
+
+    /// Metrics for every agent seen so far, ordered by id.
+    pub fn all_metrics(&self) -> Vec<AgentCircuitMetrics> {
+        let mut metrics: Vec<_> = self
+            .agents
+            .read()
+            .iter()
+            .map(|(agent_id, state)| state.metrics(agent_id))
+            .collect();
+        metrics.sort_by(|a, b| a.agent_id.cmp(&b.agent_id));
+        metrics
     }
 
     /// Get global circuit breaker metrics.
@@ -439,16 +464,25 @@ impl AgentCircuitBreakerRegistry {
     }
 
     /// Force reset a specific agent's circuit.
-    pub fn reset_agent(&self, agent_id: &str) {
+    ///
+    /// Returns `false` if the agent has no circuit yet.
+    pub fn reset_agent(&self, agent_id: &str) -> bool {
         let mut agents = self.agents.write();
-        if let Some(state) = agents.get_mut(agent_id) {
-            state.state = CircuitState::Closed;
-            state.failure_count = 0;
-            state.opened_at = None;
-            state.backoff_multiplier = 1;
-            state.open_reason = None;
-            tracing::info!(agent_id = agent_id, "Agent circuit breaker manually reset");
-        }
+        let Some(state) = agents.get_mut(agent_id) else {
+            return false;
+        };
+        state.state = CircuitState::Closed;
+        state.failure_count = 0;
+        state.opened_at = None;
+        state.backoff_multiplier = 1;
+        state.open_reason = None;
+        tracing::info!(agent_id = agent_id, "Agent circuit breaker manually reset");
+        true
+    }
+
+    /// Force reset the global circuit breaker, leaving agent circuits alone.
+    pub fn reset_global(&self) {
+        self.global.reset();
     }
 
     /// Force reset the global circuit breaker and all agent circuits.
@@ -534,6 +568,27 @@ mod tests {
         assert_eq!(a_metrics.failure_count, 2);
     }
 
+    #[test]
+    fn test_agent_registry_reports_opening_failures() {
+        let registry = AgentCircuitBreakerRegistry::new(10, 2)
+            .with_base_recovery_timeout(Duration::from_millis(10));
+
+        assert_eq!(registry.record_failure("agent_a"), None);
+        assert_eq!(registry.record_failure("agent_a"), Some(AgentCircuitOpenReason::ConsecutiveFailures));
+        registry.record_success("agent_b");
+
+        // A failed half-open probe re-opens the circuit
+        std::thread::sleep(Duration::from_millis(15));
+        assert!(registry.can_execute("agent_a"));
+        assert_eq!(registry.record_failure("agent_a"), Some(AgentCircuitOpenReason::ConsecutiveFailures));
+
+        let metrics = registry.all_metrics();
+        let ids: Vec<_> = metrics.iter().map(|m| m.agent_id.as_str()).collect();
+        assert_eq!(ids, ["agent_a", "agent_b"]);
+        assert_eq!(metrics[0].state, CircuitState::Open);
+        assert_eq!(metrics[1].total_successes, 1);
+    }
+
     #[test]
     fn test_agent_registry_exponential_backoff() {
         let registry = AgentCircuitBreakerRegistry::new(10, 1)
@@ -615,8 +670,9 @@ mod tests {
         registry.record_failure("agent_r");
         assert!(!registry.can_execute("agent_r"));
 
-        registry.reset_agent("agent_r");
+        assert!(registry.reset_agent("agent_r"));
         assert!(registry.can_execute("agent_r"));
+        assert!(!registry.reset_agent("unknown"));
     }
 
     #[test]
@@ -7,10 +7,13 @@
 //! 4. **Award** — Winner is notified; runner-up is kept for failover.
 //! 5. **Monitor** — Heartbeat tracking with automatic failover.
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
 
+use crate::agents::{claim_agent_with, Agent, AgentSlot};
 use crate::error::{ApexError, ErrorCode, Result};
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -103,6 +106,22 @@ pub struct AgentBid {
     pub capabilities: Vec<String>,
 }
 
+impl AgentBid {
+    /// The bid a registered agent makes for `task_id`: `estimated_cost` is
+    /// the task's cost on the agent's model, the duration grows with how
+    /// loaded the agent is and the confidence is its reputation.
+    pub fn from_agent(agent: &Agent, task_id: &str, estimated_cost: f64) -> Self {
+        Self {
+            agent_id: agent.id.0.to_string(),
+            task_id: task_id.to_string(),
+            estimated_cost,
+            estimated_duration: agent.current_load() as f64 / agent.max_load.max(1) as f64,
+            confidence: agent.reputation_score(),
+            capabilities: agent.tools.iter().filter(|t| t.enabled).map(|t| t.name.clone()).collect(),
+        }
+    }
+}
+
 /// The result of evaluating a single bid.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BidScore {
@@ -401,23 +420,8 @@ impl CnpManager {
         task_id: &str,
         scored_bids: &[BidScore],
     ) -> Result<AwardDecision> {
-        if scored_bids.is_empty() {
-            return Err(ApexError::with_internal(
-                ErrorCode::AgentNotFound,
-                "No bids received for task",
-                format!("Task {} received zero bids", task_id),
-            ));
-        }
-
-        let winning_bid = scored_bids[0].clone();
-        let runner_up = scored_bids.get(1).cloned();
-
-        let decision = AwardDecision {
-            task_id: task_id.to_string(),
-            winning_bid: winning_bid.clone(),
-            runner_up,
-            total_bids: scored_bids.len(),
-        };
+        let decision = Self::decide_award(task_id, scored_bids)?;
+        let winning_bid = decision.winning_bid.clone();
 
         // Publish award to the winning agent's channel
         let payload = serde_json::to_string(&decision)?;
@@ -452,6 +456,69 @@ impl CnpManager {
         Ok(decision)
     }
 
+    /// Award to the best of `scored_bids` (sorted best first), keeping the
+    /// next for failover, without notifying anyone.
+    pub fn decide_award(task_id: &str, scored_bids: &[BidScore]) -> Result<AwardDecision> {
+        if scored_bids.is_empty() {
+            return Err(ApexError::with_internal(
+                ErrorCode::AgentNotFound,
+                "No bids received for task",
+                format!("Task {} received zero bids", task_id),
+            ));
+        }
+
+        Ok(AwardDecision {
+            task_id: task_id.to_string(),
+            winning_bid: scored_bids[0].clone(),
+            runner_up: scored_bids.get(1).cloned(),
+            total_bids: scored_bids.len(),
+        })
+    }
+
+    /// Run the protocol in-process among registered `agents` and claim a
+    /// slot on the winner.
+    ///
+    /// Every available agent bids on the announcement (see
+    /// [`AgentBid::from_agent`], with `cost_of` pricing the task on its
+    /// model); if the winner's slot is taken concurrently the award goes to
+    /// the next-best bid. Like [`claim_agent`](crate::agents::claim_agent),
+    /// waits up to `timeout` for an agent to free up when all are busy.
+    pub async fn claim_by_bidding(
+        &self,
+        announcement: &TaskAnnouncement,
+        agents: &[Arc<Agent>],
+        released: &Arc<Notify>,
+        timeout: Duration,
+        cost_of: impl Fn(&Agent) -> f64,
+    ) -> Result<(AgentSlot, AwardDecision)> {
+        let (slot, decision) = claim_agent_with(agents, released, timeout, |agents| {
+            let bidders: Vec<&Arc<Agent>> = agents.iter().filter(|a| a.is_available()).collect();
+            let bids: Vec<AgentBid> = bidders
+                .iter()
+                .map(|a| AgentBid::from_agent(a, &announcement.task_id, cost_of(a)))
+                .collect();
+            let scored = self.evaluate_bids(&bids, &announcement.requirements);
+
+            scored.iter().enumerate().find_map(|(i, bid)| {
+                let agent = bidders.iter().find(|a| a.id.0.to_string() == bid.bid.agent_id)?;
+                let slot = AgentSlot::acquire(agent)?;
+                let mut decision = Self::decide_award(&announcement.task_id, &scored[i..]).ok()?;
+                decision.total_bids = scored.len();
+                Some((slot, decision))
+            })
+        })
+        .await?;
+
+        tracing::debug!(
+            task_id = %announcement.task_id,
+            winner = %decision.winning_bid.bid.agent_id,
+            score = decision.winning_bid.score,
+            total_bids = decision.total_bids,
+            "Task awarded by bidding"
+        );
+        Ok((slot, decision))
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Step 5: Monitor Execution
     // ─────────────────────────────────────────────────────────────────────────
@@ -763,6 +830,48 @@ mod tests {
         }
     }
 
+    // ── In-process bidding ──────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn test_claim_by_bidding_awards_best_bid() {
+        let mgr = make_manager();
+        let cheap = Arc::new(Agent::new("cheap", "gpt-4o-mini"));
+        let pricey = Arc::new(Agent::new("pricey", "claude-opus-4"));
+        let agents = vec![pricey.clone(), cheap.clone()];
+        let announcement = TaskAnnouncement {
+            task_id: "task-1".to_string(),
+            description: "Summarize".to_string(),
+            requirements: vec![],
+            deadline_secs: 1,
+            min_bid_count: 1,
+            metadata: serde_json::Value::Null,
+        };
+        let cost_of = |agent: &Agent| if agent.model == "gpt-4o-mini" { 0.001 } else { 0.1 };
+        let released = Arc::new(Notify::new());
+
+        let (slot, decision) = mgr
+            .claim_by_bidding(&announcement, &agents, &released, Duration::from_secs(1), cost_of)
+            .await
+            .unwrap();
+        assert_eq!(slot.id, cheap.id);
+        assert_eq!(decision.winning_bid.bid.agent_id, cheap.id.0.to_string());
+        assert_eq!(decision.runner_up.unwrap().bid.agent_id, pricey.id.0.to_string());
+        assert_eq!(decision.total_bids, 2);
+        assert_eq!(decision.winning_bid.breakdown.cost_score, 1.0);
+        drop(slot);
+
+        // A busy winner loses the award to the next bid
+        let cheap = Arc::new(Agent::new("cheap", "gpt-4o-mini").with_max_load(1));
+        let agents = vec![pricey.clone(), cheap.clone()];
+        let _held = AgentSlot::acquire(&cheap).unwrap();
+        let (slot, decision) = mgr
+            .claim_by_bidding(&announcement, &agents, &released, Duration::from_secs(1), cost_of)
+            .await
+            .unwrap();
+        assert_eq!(slot.id, pricey.id);
+        assert_eq!((decision.total_bids, decision.runner_up.is_none()), (1, true));
+    }
+
     // ── Award Decision (unit-level, no Redis) ───────────────────────────
 
     #[test]
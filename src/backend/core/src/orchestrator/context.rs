@@ -0,0 +1,250 @@
+//! Task context size limits.
+//!
+//! Oversized `TaskInput::context` payloads are caught when the worker payload
+//! is built: either the task is rejected, or the context is truncated and the
+//! payload carries a [`ContextTruncation`] so the worker knows it is partial.
+//!
+//! A truncated string context stays a string. Any other context is cut down
+//! as serialized JSON and sent as an object, `{"_truncated": true, "text": ...}`,
+//! since workers expect an object.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ApexError, Result};
+use crate::routing::estimate_input_tokens;
+
+/// Marker inserted where middle-out truncation removed text.
+const ELISION: &str = "\n…\n";
+
+/// Key marking a context object that holds truncated JSON text.
+pub const TRUNCATED_KEY: &str = "_truncated";
+
+/// What to do with a context that exceeds the limit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowStrategy {
+    /// Fail the task with a validation error
+    #[default]
+    Reject,
+    /// Keep the beginning of the context
+    Head,
+    /// Keep the end of the context
+    Tail,
+    /// Keep both ends and drop the middle
+    MiddleOut,
+}
+
+/// Maximum context size per task. Unset bounds are not enforced.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContextLimit {
+    /// Maximum serialized context size in bytes
+    #[serde(default)]
+    pub max_bytes: Option<usize>,
+
+    /// Maximum context size in tokens, counted with the dispatch model's tokenizer
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+
+    /// Applied when either bound is exceeded
+    #[serde(default)]
+    pub on_overflow: OverflowStrategy,
+}
+
+/// Sent to the worker when its context was cut down.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContextTruncation {
+    pub strategy: OverflowStrategy,
+    pub original_bytes: usize,
+    pub truncated_bytes: usize,
+}
+
+impl ContextLimit {
+    fn is_unbounded(&self) -> bool {
+        self.max_bytes.is_none() && self.max_tokens.is_none()
+    }
+
+    fn fits(&self, text: &str, model: &str) -> bool {
+        self.max_bytes.map(|max| text.len() <= max).unwrap_or(true)
+            && self.max_tokens.map(|max| estimate_input_tokens(text, model) <= max).unwrap_or(true)
+    }
+
+    /// Enforce the limit on `context` for a task dispatched to `model`.
+    ///
+    /// Returns the context to send (unchanged when it fits) and, if it was
+    /// truncated, a description of what happened.
+    pub fn apply(
+        &self,
+        context: &serde_json::Value,
+        model: &str,
+    ) -> Result<(serde_json::Value, Option<ContextTruncation>)> {
+        if self.is_unbounded() || context.is_null() {
+            return Ok((context.clone(), None));
+        }
+
+        let text = match context {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        if self.fits(&text, model) {
+            return Ok((context.clone(), None));
+        }
+
+        if self.on_overflow == OverflowStrategy::Reject {
+            return Err(ApexError::validation(format!(
+                "Task context exceeds the configured limit ({} bytes{})",
+                text.len(),
+                self.max_tokens.map(|t| format!(", max {} tokens", t)).unwrap_or_default(),
+            )));
+        }
+
+        let render = |budget: usize| {
+            let cut = truncate(&text, budget, self.on_overflow);
+            if context.is_string() {
+                (serde_json::Value::String(cut.clone()), cut)
+            } else {
+                let value = serde_json::json!({ TRUNCATED_KEY: true, "text": cut });
+                let rendered = value.to_string();
+                (value, rendered)
+            }
+        };
+
+        // Shrink the byte budget until what is sent fits both bounds. Sizes
+        // and token counts are roughly linear in length, so this converges
+        // quickly.
+        let mut budget = self.max_bytes.unwrap_or(text.len()).min(text.len());
+        let (mut truncated, mut rendered) = render(budget);
+        while !self.fits(&rendered, model) && budget > 0 {
+            let by_bytes = self.max_bytes.map(|max| scale(budget, max as u128, rendered.len() as u128));
+            let by_tokens = self.max_tokens.map(|max| {
+                scale(budget, max as u128, estimate_input_tokens(&rendered, model) as u128)
+            });
+            budget = by_bytes.into_iter().chain(by_tokens).min().unwrap_or(0).min(budget - 1);
+            (truncated, rendered) = render(budget);
+        }
+
+        let truncation = ContextTruncation {
+            strategy: self.on_overflow,
+            original_bytes: text.len(),
+            truncated_bytes: rendered.len(),
+        };
+        Ok((truncated, Some(truncation)))
+    }
+}
+
+/// `budget` scaled by `target / actual`.
+fn scale(budget: usize, target: u128, actual: u128) -> usize {
+    (budget as u128 * target / actual.max(1)) as usize
+}
+
+/// Cut `text` to at most `max_bytes`, on char boundaries.
+fn truncate(text: &str, max_bytes: usize, strategy: OverflowStrategy) -> String {
+    if text.len() <= max_bytes {
+        return text.to_string();
+    }
+
+    match strategy {
+        OverflowStrategy::Reject | OverflowStrategy::Head => head(text, max_bytes).to_string(),
+        OverflowStrategy::Tail => tail(text, max_bytes).to_string(),
+        OverflowStrategy::MiddleOut => {
+            if max_bytes <= ELISION.len() {
+                return head(text, max_bytes).to_string();
+            }
+            let keep = max_bytes - ELISION.len();
+            let front = head(text, keep.div_ceil(2));
+            let back = tail(text, keep / 2);
+            format!("{}{}{}", front, ELISION, back)
+        }
+    }
+}
+
+fn head(text: &str, max_bytes: usize) -> &str {
+    let mut end = max_bytes.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+fn tail(text: &str, max_bytes: usize) -> &str {
+    let mut start = text.len() - max_bytes.min(text.len());
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn limit(max_bytes: usize, on_overflow: OverflowStrategy) -> ContextLimit {
+        ContextLimit { max_bytes: Some(max_bytes), max_tokens: None, on_overflow }
+    }
+
+    #[test]
+    fn test_within_limit_is_untouched() {
+        let context = json!({ "notes": "short" });
+        let (out, truncation) = limit(1024, OverflowStrategy::Reject).apply(&context, "gpt-4o").unwrap();
+        assert_eq!(out, context);
+        assert!(truncation.is_none());
+    }
+
+    #[test]
+    fn test_reject_over_limit() {
+        let context = json!("x".repeat(100));
+        let err = limit(10, OverflowStrategy::Reject).apply(&context, "gpt-4o").unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::ValidationError);
+    }
+
+    #[test]
+    fn test_truncation_strategies() {
+        let context = json!("abcdefghijklmnopqrstuvwxyz");
+
+        let (out, truncation) = limit(5, OverflowStrategy::Head).apply(&context, "gpt-4o").unwrap();
+        assert_eq!(out, json!("abcde"));
+        assert_eq!(truncation.unwrap().original_bytes, 26);
+
+        let (out, _) = limit(5, OverflowStrategy::Tail).apply(&context, "gpt-4o").unwrap();
+        assert_eq!(out, json!("vwxyz"));
+
+        let (out, truncation) = limit(12, OverflowStrategy::MiddleOut).apply(&context, "gpt-4o").unwrap();
+        let out = out.as_str().unwrap();
+        assert!(out.starts_with("abc") && out.ends_with("xyz") && out.contains('…'));
+        assert!(truncation.unwrap().truncated_bytes <= 12);
+    }
+
+    #[test]
+    fn test_truncation_respects_char_boundaries() {
+        let context = json!("héllo wörld");
+        let (out, _) = limit(2, OverflowStrategy::Head).apply(&context, "gpt-4o").unwrap();
+        assert_eq!(out, json!("h"));
+        let (out, _) = limit(2, OverflowStrategy::Tail).apply(&json!("niño"), "gpt-4o").unwrap();
+        assert_eq!(out, json!("o"));
+    }
+
+    #[test]
+    fn test_token_limit_truncates_to_fit() {
+        let limit = ContextLimit {
+            max_bytes: None,
+            max_tokens: Some(20),
+            on_overflow: OverflowStrategy::Tail,
+        };
+        let context = json!("The quick brown fox jumps over the lazy dog. ".repeat(20));
+        let (out, truncation) = limit.apply(&context, "gpt-4o").unwrap();
+        assert!(estimate_input_tokens(out.as_str().unwrap(), "gpt-4o") <= 20);
+        assert!(truncation.is_some());
+    }
+
+    #[test]
+    fn test_truncated_object_stays_an_object() {
+        let context = json!({ "notes": "x".repeat(500), "source": "crawl" });
+        let (out, truncation) = limit(100, OverflowStrategy::Head).apply(&context, "gpt-4o").unwrap();
+
+        assert_eq!(out[TRUNCATED_KEY], json!(true));
+        assert!(out["text"].as_str().unwrap().starts_with(r#"{"notes":"xxx"#));
+        let truncation = truncation.unwrap();
+        assert_eq!(truncation.truncated_bytes, out.to_string().len());
+        assert!(truncation.truncated_bytes <= 100);
+    }
+}
@@ -0,0 +1,236 @@
+//! Weighted-fair sharing of worker slots between DAGs.
+//!
+//! Every running DAG draws on the same worker pool. Left alone, a DAG with a
+//! wide layer of ready tasks takes every slot that frees up, and a small DAG
+//! submitted after it only gets a turn once that layer drains. A
+//! [`FairScheduler`] sits in front of the pool and hands each free slot to the
+//! waiting DAG with the lowest `running / weight`, so DAGs that are waiting
+//! share the pool in proportion to their weights and none of them waits behind
+//! another's backlog.
+//!
+//! Enabled with `orchestrator.fair_scheduling`; a DAG's weight comes from
+//! [`TaskDAG::weight`](crate::dag::TaskDAG::weight) (default 1).
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+/// Arbitrates a fixed number of worker slots between DAGs.
+pub struct FairScheduler {
+    capacity: usize,
+    state: Mutex<FairState>,
+}
+
+#[derive(Default)]
+struct FairState {
+    in_use: usize,
+    /// DAGs with a slot in use or a task waiting for one
+    dags: HashMap<Uuid, DagShare>,
+}
+
+struct DagShare {
+    weight: u32,
+    running: usize,
+    waiters: VecDeque<oneshot::Sender<FairPermit>>,
+}
+
+impl DagShare {
+    /// Slots in use per unit of weight; the lowest goes next.
+    fn load(&self) -> f64 {
+        self.running as f64 / self.weight as f64
+    }
+}
+
+/// A worker slot held by one of a DAG's tasks; released on drop.
+pub struct FairPermit {
+    scheduler: Arc<FairScheduler>,
+    dag_id: Uuid,
+    /// Cleared when the permit never reached its waiter, so drop is a no-op
+    armed: bool,
+}
+
+impl FairScheduler {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(FairState::default()),
+        }
+    }
+
+    /// Wait for a slot for one of `dag_id`'s tasks.
+    ///
+    /// `weight` is the DAG's share relative to other DAGs; 0 is treated as 1.
+    pub async fn acquire(self: &Arc<Self>, dag_id: Uuid, weight: u32) -> FairPermit {
+        let (tx, rx) = oneshot::channel();
+        {
+            let mut state = self.lock();
+            let share = state.dags.entry(dag_id).or_insert_with(|| DagShare {
+                weight: 1,
+                running: 0,
+                waiters: VecDeque::new(),
+            });
+            share.weight = weight.max(1);
+            share.waiters.push_back(tx);
+            self.dispatch(&mut state);
+        }
+        // Waiters stay queued until granted, and `self` keeps the scheduler alive
+        rx.await.expect("fair scheduler dropped a waiter")
+    }
+
+    /// Slots currently held by `dag_id`'s tasks.
+    pub fn running(&self, dag_id: Uuid) -> usize {
+        self.lock().dags.get(&dag_id).map_or(0, |share| share.running)
+    }
+
+    /// Slots currently held across all DAGs.
+    pub fn in_use(&self) -> usize {
+        self.lock().in_use
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FairState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Hand free slots to waiting DAGs, least-served first.
+    fn dispatch(self: &Arc<Self>, state: &mut FairState) {
+        while state.in_use < self.capacity {
+            let next = state
+                .dags
+                .iter()
+                .filter(|(_, share)| !share.waiters.is_empty())
+                .min_by(|(_, a), (_, b)| {
+                    a.load().total_cmp(&b.load()).then(a.running.cmp(&b.running))
+                })
+                .map(|(dag_id, _)| *dag_id);
+            let Some(dag_id) = next else { break };
+
+            let share = state.dags.get_mut(&dag_id).expect("picked from the map");
+            let waiter = share.waiters.pop_front().expect("picked with a waiter");
+            share.running += 1;
+            state.in_use += 1;
+
+            let permit = FairPermit {
+                scheduler: self.clone(),
+                dag_id,
+                armed: true,
+            };
+            if let Err(mut permit) = waiter.send(permit) {
+                // The waiting task was cancelled; give the slot to someone else
+                permit.armed = false;
+                share.running -= 1;
+                state.in_use -= 1;
+            }
+        }
+        state
+            .dags
+            .retain(|_, share| share.running > 0 || !share.waiters.is_empty());
+    }
+}
+
+impl Drop for FairPermit {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let mut state = self.scheduler.lock();
+        if let Some(share) = state.dags.get_mut(&self.dag_id) {
+            share.running = share.running.saturating_sub(1);
+        }
+        state.in_use = state.in_use.saturating_sub(1);
+        self.scheduler.dispatch(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::task::JoinHandle;
+
+    /// Queue `n` acquires for `dag_id` in the background.
+    fn queue(scheduler: &Arc<FairScheduler>, dag_id: Uuid, weight: u32, n: usize) -> Vec<JoinHandle<FairPermit>> {
+        (0..n)
+            .map(|_| {
+                let scheduler = scheduler.clone();
+                tokio::spawn(async move { scheduler.acquire(dag_id, weight).await })
+            })
+            .collect()
+    }
+
+    /// Let queued acquires run; the tests pause the clock, so this only
+    /// returns once every other task is idle.
+    async fn settle() {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    /// Permits from the acquires that have been granted so far.
+    async fn granted(handles: &mut Vec<JoinHandle<FairPermit>>) -> Vec<FairPermit> {
+        let (done, waiting) = std::mem::take(handles).into_iter().partition::<Vec<_>, _>(|h| h.is_finished());
+        *handles = waiting;
+        futures::future::join_all(done).await.into_iter().map(Result::unwrap).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_small_dag_not_starved() {
+        let scheduler = Arc::new(FairScheduler::new(2));
+        let (huge, tiny) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let mut huge_waiting = queue(&scheduler, huge, 1, 10);
+        settle().await;
+        let mut huge_running = granted(&mut huge_waiting).await;
+        assert_eq!(huge_running.len(), 2);
+
+        let mut tiny_waiting = queue(&scheduler, tiny, 1, 1);
+        settle().await;
+        assert!(granted(&mut tiny_waiting).await.is_empty());
+
+        // The next free slot goes to the tiny DAG, not the huge one's backlog
+        drop(huge_running.pop());
+        settle().await;
+        let tiny_running = granted(&mut tiny_waiting).await;
+        assert_eq!(tiny_running.len(), 1);
+        assert_eq!(scheduler.running(tiny), 1);
+        assert_eq!(scheduler.running(huge), 1);
+
+        drop(tiny_running);
+        assert_eq!(scheduler.running(tiny), 0);
+        assert_eq!(scheduler.running(huge), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slots_split_by_weight() {
+        let scheduler = Arc::new(FairScheduler::new(4));
+        let (light, heavy) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let mut blockers = queue(&scheduler, Uuid::new_v4(), 1, 4);
+        settle().await;
+        let blockers = granted(&mut blockers).await;
+        let _light = queue(&scheduler, light, 1, 4);
+        let _heavy = queue(&scheduler, heavy, 3, 4);
+        settle().await;
+
+        drop(blockers);
+        assert_eq!(scheduler.in_use(), 4);
+        assert_eq!(scheduler.running(light), 1);
+        assert_eq!(scheduler.running(heavy), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancelled_waiter_frees_its_slot() {
+        let scheduler = Arc::new(FairScheduler::new(1));
+        let dag_id = Uuid::new_v4();
+
+        let held = scheduler.acquire(dag_id, 1).await;
+        let cancelled = queue(&scheduler, dag_id, 1, 1).pop().unwrap();
+        settle().await;
+        cancelled.abort();
+        let _ = cancelled.await;
+
+        drop(held);
+        assert_eq!(scheduler.in_use(), 0);
+        let _next = scheduler.acquire(Uuid::new_v4(), 1).await;
+        assert_eq!(scheduler.in_use(), 1);
+    }
+}
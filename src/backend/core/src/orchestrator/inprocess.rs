@@ -0,0 +1,370 @@
+//! Embedded worker: calls the LLM provider directly from the orchestrator.
+//!
+//! With `runner = "inprocess"` no Redis queue or Python worker is involved:
+//! each task payload becomes one chat request to OpenAI or Anthropic, chosen
+//! by the dispatch model's provider. The contract travels with the payload and
+//! is enforced here: the prompt must fit the token limit, output is capped to
+//! what remains, the request is bounded by the time limit, and a response
+//! that overshoots the token or cost limit fails the task.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use super::runner::TaskRunner;
+use super::{RedisTaskPayload, RedisTaskResult};
+use crate::config::LlmConfig;
+use crate::dag::TaskInput;
+use crate::error::{ApexError, ErrorCode, Result};
+use crate::routing::{estimate_input_tokens, ModelRouter};
+
+const OPENAI_BASE_URL: &str = "https://api.openai.com";
+const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Upper bound on requested output tokens, regardless of the task budget.
+const MAX_OUTPUT_TOKENS: u64 = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Provider {
+    OpenAi,
+    Anthropic,
+}
+
+impl Provider {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Provider::OpenAi => "openai",
+            Provider::Anthropic => "anthropic",
+        }
+    }
+}
+
+struct Endpoint {
+    base_url: String,
+    api_key: String,
+}
+
+/// Text and token usage reported by the provider.
+struct Completion {
+    text: String,
+    input_tokens: u64,
+    output_tokens: u64,
+}
+
+/// [`TaskRunner`] that answers tasks with a direct provider call.
+pub struct InProcessTaskRunner {
+    http: reqwest::Client,
+    router: ModelRouter,
+    openai: Option<Endpoint>,
+    anthropic: Option<Endpoint>,
+    default_model: String,
+}
+
+impl InProcessTaskRunner {
+    /// Build from the `[llm]` section. A provider without an API key is
+    /// unavailable, and tasks routed to it fail.
+    pub fn from_config(config: &LlmConfig) -> Self {
+        let endpoint = |key: &Option<String>, url: &Option<String>, default: &str| {
+            key.as_ref().map(|api_key| Endpoint {
+                base_url: url.as_deref().unwrap_or(default).trim_end_matches('/').to_string(),
+                api_key: api_key.clone(),
+            })
+        };
+
+        Self {
+            http: reqwest::Client::new(),
+            router: ModelRouter::new(),
+            openai: endpoint(&config.openai_api_key, &config.openai_base_url, OPENAI_BASE_URL),
+            anthropic: endpoint(&config.anthropic_api_key, &config.anthropic_base_url, ANTHROPIC_BASE_URL),
+            default_model: config.default_model.clone(),
+        }
+    }
+
+    fn provider_for(&self, model: &str) -> Provider {
+        match self.router.get_model(model) {
+            Some(config) if config.provider == "anthropic" => Provider::Anthropic,
+            Some(_) => Provider::OpenAi,
+            None if model.starts_with("claude") => Provider::Anthropic,
+            None => Provider::OpenAi,
+        }
+    }
+
+    async fn complete(
+        &self,
+        provider: Provider,
+        model: &str,
+        prompt: &str,
+        max_tokens: u64,
+        timeout: Duration,
+    ) -> Result<Completion> {
+        let endpoint = match provider {
+            Provider::OpenAi => self.openai.as_ref(),
+            Provider::Anthropic => self.anthropic.as_ref(),
+        }
+        .ok_or_else(|| {
+            ApexError::new(
+                ErrorCode::LlmUnavailable,
+                format!("No API key configured for {}", provider.as_str()),
+            )
+        })?;
+
+        let request = match provider {
+            Provider::OpenAi => self
+                .http
+                .post(format!("{}/v1/chat/completions", endpoint.base_url))
+                .bearer_auth(&endpoint.api_key)
+                .json(&json!({
+                    "model": model,
+                    "max_tokens": max_tokens,
+                    "messages": [{ "role": "user", "content": prompt }],
+                })),
+            Provider::Anthropic => self
+                .http
+                .post(format!("{}/v1/messages", endpoint.base_url))
+                .header("x-api-key", &endpoint.api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&json!({
+                    "model": model,
+                    "max_tokens": max_tokens,
+                    "messages": [{ "role": "user", "content": prompt }],
+                })),
+        };
+
+        let response = request.timeout(timeout).send().await.map_err(|e| {
+            if e.is_timeout() {
+                ApexError::with_internal(
+                    ErrorCode::AgentTimeout,
+                    "Task execution timed out waiting for the model",
+                    e.to_string(),
+                )
+            } else {
+                ApexError::from(e)
+            }
+        })?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            return Err(ApexError::rate_limited(provider.as_str(), retry_after));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(ApexError::llm_api_error(provider.as_str(), format!("{}: {}", status, body)));
+        }
+
+        let body: serde_json::Value = response.json().await?;
+        let completion = match provider {
+            Provider::OpenAi => Completion {
+                text: body["choices"][0]["message"]["content"].as_str().unwrap_or_default().to_string(),
+                input_tokens: body["usage"]["prompt_tokens"].as_u64().unwrap_or(0),
+                output_tokens: body["usage"]["completion_tokens"].as_u64().unwrap_or(0),
+            },
+            Provider::Anthropic => Completion {
+                text: body["content"]
+                    .as_array()
+                    .map(|blocks| {
+                        blocks
+                            .iter()
+                            .filter_map(|b| b["text"].as_str())
+                            .collect::<Vec<_>>()
+                            .join("")
+                    })
+                    .unwrap_or_default(),
+                input_tokens: body["usage"]["input_tokens"].as_u64().unwrap_or(0),
+                output_tokens: body["usage"]["output_tokens"].as_u64().unwrap_or(0),
+            },
+        };
+        Ok(completion)
+    }
+}
+
+#[async_trait]
+impl TaskRunner for InProcessTaskRunner {
+    async fn run(&self, payload: RedisTaskPayload) -> Result<RedisTaskResult> {
+        let model = payload.model.clone().unwrap_or_else(|| self.default_model.clone());
+        let provider = self.provider_for(&model);
+        let contract = &payload.contract;
+
+        let input: TaskInput = serde_json::from_value(payload.input.clone())?;
+        let prompt = match &input.context {
+            serde_json::Value::Null => input.instruction.clone(),
+            serde_json::Value::String(context) => format!("{}\n{}", input.instruction, context),
+            context => format!("{}\n{}", input.instruction, context),
+        };
+
+        if contract.api_call_limit == 0 {
+            return Ok(RedisTaskResult::failed("Contract allows no API calls"));
+        }
+        let prompt_tokens = estimate_input_tokens(&prompt, &model);
+        if prompt_tokens >= contract.token_limit {
+            return Ok(RedisTaskResult::failed(format!(
+                "Prompt needs ~{} tokens, over the task's {} token limit",
+                prompt_tokens, contract.token_limit
+            )));
+        }
+        let max_tokens = (contract.token_limit - prompt_tokens).min(MAX_OUTPUT_TOKENS);
+
+        let completion = self
+            .complete(
+                provider,
+                &model,
+                &prompt,
+                max_tokens,
+                Duration::from_secs(contract.time_limit_seconds.max(1)),
+            )
+            .await?;
+
+        let tokens_used = completion.input_tokens + completion.output_tokens;
+        let cost_dollars = self.router.estimate_cost(
+            &model,
+            completion.input_tokens.min(u32::MAX as u64) as u32,
+            completion.output_tokens.min(u32::MAX as u64) as u32,
+        );
+
+        let overrun = if tokens_used > contract.token_limit {
+            Some(format!("used {} tokens, over its limit of {}", tokens_used, contract.token_limit))
+        } else if cost_dollars > contract.cost_limit {
+            Some(format!("cost ${:.4}, over its limit of ${:.4}", cost_dollars, contract.cost_limit))
+        } else {
+            None
+        };
+
+        let mut result = match overrun {
+            Some(reason) => RedisTaskResult::failed(format!("Task {}", reason)),
+            None => RedisTaskResult::completed(completion.text, tokens_used, cost_dollars),
+        };
+        result.tokens_used = tokens_used;
+        result.cost_dollars = cost_dollars;
+        result.data = Some(json!({
+            "model": model,
+            "provider": provider.as_str(),
+            "input_tokens": completion.input_tokens,
+            "output_tokens": completion.output_tokens,
+        }));
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::RedisContractPayload;
+    use axum::routing::post;
+    use axum::{Json, Router};
+
+    /// Fake OpenAI and Anthropic endpoints that echo the requested `max_tokens`.
+    async fn provider_server() -> String {
+        let router = Router::new()
+            .route(
+                "/v1/chat/completions",
+                post(|Json(body): Json<serde_json::Value>| async move {
+                    Json(json!({
+                        "choices": [{ "message": { "content": format!("openai max={}", body["max_tokens"]) } }],
+                        "usage": { "prompt_tokens": 1000, "completion_tokens": 500 },
+                    }))
+                }),
+            )
+            .route(
+                "/v1/messages",
+                post(|headers: axum::http::HeaderMap, Json(body): Json<serde_json::Value>| async move {
+                    assert_eq!(headers["x-api-key"], "sk-ant");
+                    Json(json!({
+                        "content": [{ "type": "text", "text": format!("anthropic {}", body["model"]) }],
+                        "usage": { "input_tokens": 200, "output_tokens": 100 },
+                    }))
+                }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn runner(base_url: &str) -> InProcessTaskRunner {
+        InProcessTaskRunner::from_config(&LlmConfig {
+            openai_api_key: Some("sk-openai".to_string()),
+            anthropic_api_key: Some("sk-ant".to_string()),
+            openai_base_url: Some(base_url.to_string()),
+            anthropic_base_url: Some(base_url.to_string()),
+            ..LlmConfig::default()
+        })
+    }
+
+    fn payload(model: &str, token_limit: u64, cost_limit: f64) -> RedisTaskPayload {
+        RedisTaskPayload {
+            task_id: "t-1".to_string(),
+            dag_id: "d-1".to_string(),
+            input: serde_json::to_value(TaskInput {
+                instruction: "Summarize the report".to_string(),
+                ..TaskInput::default()
+            })
+            .unwrap(),
+            contract: RedisContractPayload {
+                token_limit,
+                cost_limit,
+                api_call_limit: 10,
+                time_limit_seconds: 30,
+            },
+            trace_context: None,
+            context_truncation: None,
+            model: Some(model.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_provider_and_records_usage() {
+        let base_url = provider_server().await;
+        let runner = runner(&base_url);
+
+        let result = runner.run(payload("gpt-4o-mini", 20_000, 1.0)).await.unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(result.output, format!("openai max={}", MAX_OUTPUT_TOKENS));
+        assert_eq!(result.tokens_used, 1500);
+        let expected = 1000.0 / 1000.0 * 0.00015 + 500.0 / 1000.0 * 0.0006;
+        assert!((result.cost_dollars - expected).abs() < 1e-12);
+
+        let result = runner.run(payload("claude-3.5-haiku", 20_000, 1.0)).await.unwrap();
+        assert_eq!(result.output, "anthropic \"claude-3.5-haiku\"");
+        assert_eq!(result.tokens_used, 300);
+        assert_eq!(result.data.unwrap()["provider"], "anthropic");
+    }
+
+    #[tokio::test]
+    async fn test_enforces_contract_limits() {
+        let base_url = provider_server().await;
+        let runner = runner(&base_url);
+
+        // Output is capped to what the token limit leaves after the prompt
+        let result = runner.run(payload("gpt-4o-mini", 1_600, 1.0)).await.unwrap();
+        assert_eq!(result.status, "completed");
+        assert!(result.output.starts_with("openai max=159"), "{}", result.output);
+
+        // Reported usage over the limit fails the task but keeps the usage
+        let result = runner.run(payload("gpt-4o-mini", 1_200, 1.0)).await.unwrap();
+        assert_eq!(result.status, "failed");
+        assert_eq!(result.tokens_used, 1500);
+        assert!(result.error.unwrap().contains("over its limit of 1200"));
+
+        let result = runner.run(payload("gpt-4o-mini", 20_000, 0.0001)).await.unwrap();
+        assert_eq!(result.status, "failed");
+        assert!(result.error.unwrap().contains("cost"));
+
+        let result = runner.run(payload("gpt-4o-mini", 2, 1.0)).await.unwrap();
+        assert_eq!(result.status, "failed");
+        assert!(result.error.unwrap().contains("token limit"));
+    }
+
+    #[tokio::test]
+    async fn test_missing_api_key_is_an_error() {
+        let runner = InProcessTaskRunner::from_config(&LlmConfig::default());
+        let err = runner.run(payload("claude-3.5-haiku", 20_000, 1.0)).await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::LlmUnavailable);
+    }
+}
@@ -5,6 +5,14 @@
 pub mod worker_pool;
 pub mod circuit_breaker;
 pub mod cnp;
+pub mod context;
+pub mod runner;
+pub mod inprocess;
+pub mod scratchpad;
+pub mod fair;
+pub mod trace;
+pub mod budget;
+pub mod approvals;
 
 pub use worker_pool::{WorkerPool, WorkerPoolConfig, WorkerPoolStats, WorkerPermit, WorkerExecution};
 pub use circuit_breaker::{
@@ -15,31 +23,68 @@ pub use cnp::{
     CnpManager, CnpConfig, TaskAnnouncement, AgentBid, BidScore,
     ScoreBreakdown, AwardDecision,
 };
+pub use context::{ContextLimit, ContextTruncation, OverflowStrategy};
+pub use runner::{MockTaskRunner, RedisTaskRunner, RunnerKind, TaskRunner};
+pub use inprocess::InProcessTaskRunner;
+pub use scratchpad::{MemoryScratchpadStore, RedisScratchpadStore, Scratchpad, ScratchpadStore};
+pub use fair::{FairPermit, FairScheduler};
+pub use budget::{DagBudget, TaskContract};
+pub use approvals::{ApprovalGate, ApprovalResolution};
 
 use std::sync::Arc;
-use tokio::sync::{RwLock, Semaphore};
-use dashmap::DashMap;
+use std::time::Duration;
+use futures::Stream;
+use tokio::sync::{broadcast, mpsc, watch, Notify, RwLock, Semaphore};
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::Instrument;
+use dashmap::{DashMap, DashSet};
+use metrics::counter;
 use uuid::Uuid;
 
-use crate::dag::{TaskDAG, TaskId, TaskOutput};
-use crate::contracts::{AgentContract, ResourceLimits};
-use crate::agents::{Agent, AgentId};
+use crate::dag::{DagLimits, DagProgress, ExecutionEvent, FailurePolicy, MapUpdate, Task, TaskDAG, TaskError, TaskId, TaskOutput, TaskStatus};
+use crate::contracts::{AgentContract, ContractStatus, ResourceLimits};
+use crate::agents::{claim_agent, Agent, AgentId};
 use crate::routing::ModelRouter;
 use crate::error::{ApexError, Result};
 use crate::db::Database;
-use crate::observability::Tracer;
+use crate::rbac::TenantScope;
+use crate::observability::{ApexEvent, Tracer};
+use crate::telemetry::{DagMetrics, SensitiveFieldRedactor};
 
 use serde::{Deserialize, Serialize};
 
+/// Output tokens reserved when estimating a task's cost before dispatch.
+const ESTIMATED_OUTPUT_TOKENS: u32 = 1024;
+
+/// How long `cancel_dag` waits past the result timeout for in-flight tasks.
+const CANCEL_DRAIN_GRACE: Duration = Duration::from_secs(5);
+
+/// Times a task retries a Redis connection error, on top of `max_retries`.
+const MAX_CONNECTION_RETRIES: u32 = 3;
+
+/// Task metadata key under which a bidding round's [`AwardDecision`] is kept.
+pub const CNP_AWARD_KEY: &str = "cnp_award";
+
+/// Longest the DAG executor waits for a task to finish before re-checking.
+const READY_POLL_FALLBACK: Duration = Duration::from_secs(1);
+
 /// Configuration for the SwarmOrchestrator.
 #[derive(Debug, Clone)]
 pub struct OrchestratorConfig {
     /// Maximum concurrent agents
     pub max_concurrent_agents: usize,
 
+    /// Maximum DAGs held in memory at once (0 = unlimited); further
+    /// submissions are rejected until one finishes
+    pub max_concurrent_dags: usize,
+
     /// Default resource limits for tasks without explicit limits
     pub default_limits: ResourceLimits,
 
+    /// Most a task may ask for with its own limits, above the default or not
+    pub max_task_limits: ResourceLimits,
+
     /// Enable FrugalGPT model routing
     pub enable_model_routing: bool,
 
@@ -51,6 +96,121 @@ pub struct OrchestratorConfig {
 
     /// Timeout in seconds for waiting on task results from Redis
     pub task_result_timeout_secs: u64,
+
+    /// What to do with dependents of a failed task (DAGs may override)
+    pub failure_policy: FailurePolicy,
+
+    /// Abort the whole DAG once more tasks than this have failed (`None` = never)
+    pub max_failed_tasks: Option<FailureThreshold>,
+
+    /// Maximum task context size and what to do when it is exceeded
+    pub context_limit: ContextLimit,
+
+    /// Largest DAG accepted by `submit_dag`
+    pub dag_limits: DagLimits,
+
+    /// Share worker slots between concurrent DAGs by weight instead of
+    /// first come, first served
+    pub fair_scheduling: bool,
+
+    /// Items an `execute_dag_stream` consumer may fall behind by; task
+    /// updates past that are dropped rather than holding up execution
+    pub stream_buffer: usize,
+
+    /// Award each task to an agent by Contract Net bidding rather than to
+    /// the least-loaded, best-reputation agent
+    pub enable_cnp: bool,
+}
+
+/// How many task failures a DAG tolerates before it is aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FailureThreshold {
+    /// Absolute number of failed tasks
+    Count(usize),
+    /// Fraction (0.0-1.0) of the DAG's tasks
+    Fraction(f64),
+}
+
+impl FailureThreshold {
+    /// Resolve to the maximum number of failures allowed in a DAG of `total_tasks`.
+    pub fn limit(&self, total_tasks: usize) -> usize {
+        match *self {
+            FailureThreshold::Count(count) => count,
+            FailureThreshold::Fraction(fraction) => {
+                (total_tasks as f64 * fraction.clamp(0.0, 1.0)).floor() as usize
+            }
+        }
+    }
+}
+
+/// A client-chosen key that makes DAG submission safe to retry: submitting
+/// again under the same key returns the DAG created the first time.
+///
+/// Keys are unique per tenant: the organization id as the caller presented
+/// it, whether or not it names an `organizations` row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotencyKey {
+    key: String,
+    namespace: String,
+    organization_id: Option<Uuid>,
+}
+
+impl IdempotencyKey {
+    /// Longest key accepted.
+    pub const MAX_LEN: usize = 255;
+
+    /// Validate `key` for use by a caller in `scope`.
+    pub fn new(key: impl Into<String>, scope: &TenantScope) -> Result<Self> {
+        let key = key.into();
+        if key.is_empty() || key.len() > Self::MAX_LEN || !key.bytes().all(|b| b.is_ascii_graphic()) {
+            return Err(ApexError::validation(format!(
+                "Idempotency key must be 1-{} printable ASCII characters",
+                Self::MAX_LEN
+            )));
+        }
+        let namespace = match scope {
+            TenantScope::All => String::new(),
+            TenantScope::Organization(org) => org.as_str().to_string(),
+        };
+        Ok(Self { key, namespace, organization_id: scope.owner() })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.key
+    }
+
+    /// Tenant the key is unique within; empty for unscoped callers.
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn organization_id(&self) -> Option<Uuid> {
+        self.organization_id
+    }
+}
+
+/// Outcome of [`SwarmOrchestrator::submit_dag`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DagSubmission {
+    /// The DAG was accepted under this id
+    Created(Uuid),
+    /// An earlier submission with the same idempotency key created this DAG;
+    /// the new one was discarded
+    Existing(Uuid),
+}
+
+impl DagSubmission {
+    pub fn dag_id(&self) -> Uuid {
+        match *self {
+            DagSubmission::Created(id) | DagSubmission::Existing(id) => id,
+        }
+    }
+
+    /// Whether the submission matched an earlier one instead of creating a DAG.
+    pub fn is_existing(&self) -> bool {
+        matches!(self, DagSubmission::Existing(_))
+    }
 }
 
 /// Payload published to the Redis pending queue for agent workers.
@@ -61,6 +221,12 @@ pub struct RedisTaskPayload {
     pub input: serde_json::Value,
     pub contract: RedisContractPayload,
     pub trace_context: Option<RedisTraceContext>,
+    /// Set when `input.context` was cut down to fit the context limit
+    #[serde(default)]
+    pub context_truncation: Option<ContextTruncation>,
+    /// Model selected by the router; workers may fall back to their own default
+    #[serde(default)]
+    pub model: Option<String>,
 }
 
 /// Resource limits sent alongside a task to the worker.
@@ -77,6 +243,10 @@ pub struct RedisContractPayload {
 pub struct RedisTraceContext {
     pub trace_id: Option<String>,
     pub span_id: Option<String>,
+    /// W3C `traceparent` of the `execute_task` span; workers start their
+    /// task span as its child
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub traceparent: Option<String>,
 }
 
 /// Result payload returned by a Python agent worker via Redis.
@@ -92,18 +262,60 @@ pub struct RedisTaskResult {
     pub reasoning: Option<String>,
     #[serde(default)]
     pub error: Option<String>,
+    /// How long the worker spent on the task
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    /// Trace and span the worker ran the task in
+    #[serde(default)]
+    pub trace_id: Option<String>,
+    #[serde(default)]
+    pub span_id: Option<String>,
 }
 
 impl Default for OrchestratorConfig {
     fn default() -> Self {
         Self {
             max_concurrent_agents: 100,
+            max_concurrent_dags: 100,
             default_limits: ResourceLimits::medium(),
+            max_task_limits: ResourceLimits::complex(),
             enable_model_routing: true,
             circuit_breaker_threshold: 5,
             retry_delay_ms: 1000,
             task_result_timeout_secs: 300,
+            failure_policy: FailurePolicy::default(),
+            max_failed_tasks: None,
+            context_limit: ContextLimit::default(),
+            dag_limits: DagLimits::default(),
+            fair_scheduling: false,
+            stream_buffer: 256,
+            enable_cnp: false,
+        }
+    }
+}
+
+/// Where submitted DAGs are stored, so they survive a restart.
+#[async_trait::async_trait]
+pub trait DagStore: Send + Sync {
+    /// Store `dag` and its tasks under `organization_id`, all or nothing.
+    async fn insert_dag(&self, dag: &TaskDAG, organization_id: Option<Uuid>) -> Result<()>;
+
+    /// Store tasks added to a stored DAG while it runs, such as the
+    /// instances of an expanded map task.
+    async fn insert_tasks(&self, dag_id: Uuid, tasks: &[Task]) -> Result<()>;
+}
+
+#[async_trait::async_trait]
+impl DagStore for Database {
+    async fn insert_dag(&self, dag: &TaskDAG, organization_id: Option<Uuid>) -> Result<()> {
+        Database::insert_dag(self, dag, organization_id).await
+    }
+
+    async fn insert_tasks(&self, dag_id: Uuid, tasks: &[Task]) -> Result<()> {
+        for task in tasks {
+            Database::insert_task(self, task, dag_id).await?;
         }
+        Ok(())
     }
 }
 
@@ -116,29 +328,72 @@ pub struct SwarmOrchestrator {
     /// Database connection pool
     db: Arc<Database>,
 
-    /// Redis client for task queue communication
-    redis_client: redis::Client,
+    /// Dispatches tasks to workers and waits for their results
+    runner: Arc<dyn TaskRunner>,
+
+    /// Persists submitted DAGs
+    dag_store: Arc<dyn DagStore>,
 
     /// Worker pool semaphore for concurrency control
     worker_semaphore: Arc<Semaphore>,
 
+    /// Weighted-fair arbitration of worker slots between DAGs, if enabled
+    fair_scheduler: Option<Arc<FairScheduler>>,
+
+    /// Awards tasks to agents by bidding, if enabled
+    cnp: Option<Arc<CnpManager>>,
+
     /// Active DAGs being executed
     active_dags: DashMap<Uuid, Arc<RwLock<TaskDAG>>>,
 
+    /// Serializes the capacity check and insert in `submit_dag`
+    dag_admission: std::sync::Mutex<()>,
+
+    /// Pause flags of active DAGs; a paused DAG dispatches no new tasks
+    paused_dags: DashMap<Uuid, watch::Sender<bool>>,
+
+    /// DAGs being cancelled; their execution loop dispatches nothing more
+    cancelled_dags: DashSet<Uuid>,
+
+    /// Tasks whose payload is being published again by `requeue_task`
+    requeueing_tasks: DashSet<TaskId>,
+
+    /// Execution loops in progress; each receiver's sender closes when its
+    /// loop returns
+    executing_dags: DashMap<Uuid, watch::Receiver<()>>,
+
     /// Registered agents
-    agents: DashMap<AgentId, Arc<Agent>>,
+    agents: Arc<DashMap<AgentId, Arc<Agent>>>,
+
+    /// Notified when a task releases its agent slot
+    agent_released: Arc<Notify>,
 
-    /// Active contracts
-    contracts: DashMap<Uuid, Arc<RwLock<AgentContract>>>,
+    /// Budgets of active DAGs submitted with one, keyed by DAG id; each
+    /// task's contract is a child of its DAG's
+    dag_budgets: DashMap<Uuid, Arc<DagBudget>>,
+
+    /// Tasks waiting on an approval decision
+    approvals: Arc<ApprovalGate>,
 
     /// Model router for FrugalGPT
     model_router: Arc<ModelRouter>,
 
-    /// Circuit breaker for failure handling
-    circuit_breaker: Arc<CircuitBreaker>,
+    /// Circuit breakers keyed by model, so a failing model only blocks
+    /// the tasks routed to it
+    circuit_breakers: Arc<AgentCircuitBreakerRegistry>,
+
+    /// Circuit breakers keyed by agent id, so a flaky agent is passed over
+    /// while the rest of the swarm keeps working
+    agent_circuit_breakers: Arc<AgentCircuitBreakerRegistry>,
 
     /// Distributed tracing
     tracer: Arc<Tracer>,
+
+    /// Task lifecycle events for live subscribers
+    events: broadcast::Sender<ExecutionEvent>,
+
+    /// Shared CRDT scratchpads, one per DAG
+    scratchpads: Arc<dyn ScratchpadStore>,
 }
 
 impl SwarmOrchestrator {
@@ -150,22 +405,102 @@ impl SwarmOrchestrator {
         tracer: Arc<Tracer>,
     ) -> Result<Self> {
         let model_router = Arc::new(ModelRouter::new());
-        let circuit_breaker = Arc::new(CircuitBreaker::new(config.circuit_breaker_threshold));
+        // A failing model is cut off after `threshold` consecutive failures,
+        // so the global breaker only trips when several models fail together
+        let threshold = config.circuit_breaker_threshold;
+        let circuit_breakers = Arc::new(AgentCircuitBreakerRegistry::new(threshold.saturating_mul(4), threshold));
+        // Swarm-wide failures already trip the per-model global breaker
+        let agent_circuit_breakers = Arc::new(AgentCircuitBreakerRegistry::new(u32::MAX, threshold));
+        let (events, _) = broadcast::channel(1000);
 
         Ok(Self {
             worker_semaphore: Arc::new(Semaphore::new(config.max_concurrent_agents)),
+            fair_scheduler: config
+                .fair_scheduling
+                .then(|| Arc::new(FairScheduler::new(config.max_concurrent_agents))),
+            cnp: config
+                .enable_cnp
+                .then(|| Arc::new(CnpManager::with_defaults(redis_client.clone()))),
+            scratchpads: Arc::new(RedisScratchpadStore::new(redis_client.clone())),
+            runner: Arc::new(RedisTaskRunner::new(redis_client, config.task_result_timeout_secs)),
             config,
+            dag_store: db.clone(),
             db,
-            redis_client,
             active_dags: DashMap::new(),
-            agents: DashMap::new(),
-            contracts: DashMap::new(),
+            dag_admission: std::sync::Mutex::new(()),
+            paused_dags: DashMap::new(),
+            cancelled_dags: DashSet::new(),
+            requeueing_tasks: DashSet::new(),
+            executing_dags: DashMap::new(),
+            agents: Arc::new(DashMap::new()),
+            agent_released: Arc::new(Notify::new()),
+            dag_budgets: DashMap::new(),
+            approvals: Arc::new(ApprovalGate::new()),
             model_router,
-            circuit_breaker,
+            circuit_breakers,
+            agent_circuit_breakers,
             tracer,
+            events,
         })
     }
 
+    /// Replace the Redis task runner, e.g. with an in-process one.
+    pub fn with_runner(mut self, runner: Arc<dyn TaskRunner>) -> Self {
+        self.runner = runner;
+        self
+    }
+
+    /// Store submitted DAGs somewhere other than the database.
+    pub fn with_dag_store(mut self, store: Arc<dyn DagStore>) -> Self {
+        self.dag_store = store;
+        self
+    }
+
+    /// Replace the Redis scratchpad store, e.g. with an in-memory one.
+    pub fn with_scratchpad_store(mut self, store: Arc<dyn ScratchpadStore>) -> Self {
+        self.scratchpads = store;
+        self
+    }
+
+    /// A new handle on the shared scratchpad of `dag_id`'s agents.
+    ///
+    /// Writes through any handle merge with everyone else's instead of
+    /// overwriting them; see [`scratchpad`] for the semantics of each type.
+    pub fn scratchpad(&self, dag_id: Uuid) -> Scratchpad {
+        Scratchpad::new(dag_id, self.scratchpads.clone())
+    }
+
+    /// The model router tasks are dispatched through.
+    pub fn model_router(&self) -> &Arc<ModelRouter> {
+        &self.model_router
+    }
+
+    /// Approvals tasks are waiting on; resolve one when it is decided.
+    pub fn approvals(&self) -> &ApprovalGate {
+        &self.approvals
+    }
+
+    /// The per-model circuit breakers, for inspection and manual resets.
+    pub fn circuit_breakers(&self) -> &AgentCircuitBreakerRegistry {
+        &self.circuit_breakers
+    }
+
+    /// The per-agent circuit breakers, keyed by agent id.
+    pub fn agent_circuit_breakers(&self) -> &AgentCircuitBreakerRegistry {
+        &self.agent_circuit_breakers
+    }
+
+    /// Circuit state of one agent, including why it opened; `None` until
+    /// the agent is first considered for a task.
+    pub fn agent_circuit_metrics(&self, agent_id: AgentId) -> Option<AgentCircuitMetrics> {
+        self.agent_circuit_breakers.agent_metrics(&agent_id.0.to_string())
+    }
+
+    /// Subscribe to task lifecycle events.
+    pub fn subscribe(&self) -> broadcast::Receiver<ExecutionEvent> {
+        self.events.subscribe()
+    }
+
     /// Register an agent with the orchestrator.
     pub fn register_agent(&self, agent: Agent) -> AgentId {
         let id = agent.id;
@@ -179,310 +514,1226 @@ impl SwarmOrchestrator {
     }
 
     /// Submit a DAG for execution.
-    pub async fn submit_dag(&self, dag: TaskDAG) -> Result<Uuid> {
+    ///
+    /// `dag_budget` caps the DAG's total spend: every task runs under a child
+    /// contract bounded by what is left of it, and the DAG is aborted once it
+    /// is used up. Tasks already running at that point are allowed to finish.
+    ///
+    /// Fails with `TooManyActiveDags` (retryable, with a retry hint) once
+    /// `max_concurrent_dags` DAGs are active.
+    ///
+    /// With an `idempotency_key`, a DAG already submitted under the same key
+    /// is returned as [`DagSubmission::Existing`] and `dag` is dropped. The
+    /// key is claimed in the database, so this holds across restarts and
+    /// replicas; a rejected submission frees it again.
+    ///
+    /// The DAG and its tasks are stored as owned by `organization_id`; `None`
+    /// leaves them visible to admins only.
+    pub async fn submit_dag(
+        &self,
+        dag: TaskDAG,
+        organization_id: Option<Uuid>,
+        dag_budget: Option<ResourceLimits>,
+        idempotency_key: Option<&IdempotencyKey>,
+    ) -> Result<DagSubmission> {
         let dag_id = dag.id();
 
-        // Validate DAG
+        // Validate DAG, refusing oversized ones before anything is claimed
         let _ = dag.topological_order()?;
+        if dag_budget.as_ref().is_some_and(|budget| !budget.is_positive()) {
+            return Err(ApexError::validation("Every DAG budget limit must be greater than 0"));
+        }
+        if let Err(e) = dag.check_limits(&self.config.dag_limits) {
+            tracing::warn!(dag_id = %dag_id, error = %e, "DAG rejected, exceeds size limits");
+            return Err(e);
+        }
+
+        if let Some(key) = idempotency_key {
+            let holder = self
+                .db
+                .claim_dag_idempotency_key(key.as_str(), key.namespace(), key.organization_id(), dag_id)
+                .await?;
+            if holder != dag_id {
+                tracing::info!(dag_id = %holder, "DAG already submitted under this idempotency key");
+                return Ok(DagSubmission::Existing(holder));
+            }
+        }
+
+        // Store in active DAGs, if there is room
+        let dag_lock = Arc::new(RwLock::new(dag));
+        let rejected = {
+            let _admission = self.dag_admission.lock().unwrap_or_else(|e| e.into_inner());
+            let active = self.active_dags.len();
+            let max = self.config.max_concurrent_dags;
+            if max > 0 && active >= max && !self.active_dags.contains_key(&dag_id) {
+                tracing::warn!(dag_id = %dag_id, active, max, "DAG rejected, too many active DAGs");
+                Some(ApexError::too_many_active_dags(active, max))
+            } else {
+                self.active_dags.insert(dag_id, dag_lock.clone());
+                None
+            }
+        };
+        if let Some(err) = rejected {
+            self.release_idempotency_key(idempotency_key, dag_id).await;
+            return Err(err);
+        }
 
-        // Store in active DAGs
-        self.active_dags.insert(dag_id, Arc::new(RwLock::new(dag)));
+        // Persist before accepting, so an accepted DAG survives a restart
+        let persisted = self.dag_store.insert_dag(&*dag_lock.read().await, organization_id).await;
+        if let Err(e) = persisted {
+            tracing::error!(dag_id = %dag_id, error = %e, "Failed to persist DAG, rejecting it");
+            self.active_dags.remove(&dag_id);
+            self.release_idempotency_key(idempotency_key, dag_id).await;
+            return Err(e);
+        }
 
-        // Persist to database
-        // self.db.store_dag(&dag).await?;
+        match dag_budget {
+            Some(limits) => {
+                self.dag_budgets.insert(dag_id, Arc::new(DagBudget::new(dag_id, limits)));
+            }
+            None => {
+                self.dag_budgets.remove(&dag_id);
+            }
+        }
 
         tracing::info!(dag_id = %dag_id, "DAG submitted for execution");
 
-        Ok(dag_id)
+        Ok(DagSubmission::Created(dag_id))
     }
 
-    /// Execute a DAG to completion.
-    pub async fn execute_dag(&self, dag_id: Uuid) -> Result<DagExecutionResult> {
-        let dag_lock = self.active_dags.get(&dag_id)
-            .ok_or_else(|| ApexError::not_found("DAG", dag_id.to_string()))?
-            .clone();
+    /// Free `key` after `dag_id` was turned away, so a retry can submit.
+    async fn release_idempotency_key(&self, key: Option<&IdempotencyKey>, dag_id: Uuid) {
+        let Some(key) = key else {
+            return;
+        };
+        if let Err(e) = self
+            .db
+            .release_dag_idempotency_key(key.as_str(), key.namespace(), dag_id)
+            .await
+        {
+            tracing::warn!(dag_id = %dag_id, error = %e, "Failed to release idempotency key");
+        }
+    }
 
-        let span = tracing::info_span!("execute_dag", dag_id = %dag_id);
-        let _guard = span.enter();
+    /// Name and live progress of a DAG that is still held in memory.
+    pub async fn active_dag_progress(&self, dag_id: Uuid) -> Option<(String, DagProgress)> {
+        let dag_lock = self.active_dags.get(&dag_id)?.clone();
+        let dag = dag_lock.read().await;
+        Some((dag.name().to_string(), dag.progress()))
+    }
 
-        let start_time = std::time::Instant::now();
-        let mut total_tokens = 0u64;
-        let mut total_cost = 0.0f64;
-        let mut tasks_completed = 0usize;
-        let mut tasks_failed = 0usize;
+    /// Budget and what is left of it for an active DAG submitted with one.
+    pub async fn dag_budget(&self, dag_id: Uuid) -> Option<(ResourceLimits, ResourceLimits)> {
+        let budget = self.dag_budgets.get(&dag_id)?.clone();
+        Some(budget.snapshot())
+    }
 
-        loop {
-            // Get ready tasks
-            let ready_tasks = {
-                let dag = dag_lock.read().await;
-                if dag.is_complete() {
-                    break;
-                }
-                dag.get_ready_tasks()
-            };
+    /// Stop dispatching new tasks of an active DAG.
+    ///
+    /// Tasks already running finish and are recorded as usual; the rest wait,
+    /// without holding worker permits, until [`resume_dag`](Self::resume_dag).
+    /// Pausing a paused DAG does nothing.
+    pub fn pause_dag(&self, dag_id: Uuid) -> Result<()> {
+        self.set_paused(dag_id, true)
+    }
 
-            if ready_tasks.is_empty() {
-                // No tasks ready but DAG not complete - might be waiting for running tasks
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-                continue;
+    /// Resume dispatching the tasks of a paused DAG where it left off.
+    pub fn resume_dag(&self, dag_id: Uuid) -> Result<()> {
+        self.set_paused(dag_id, false)
+    }
+
+    /// Whether an active DAG is paused.
+    pub fn is_dag_paused(&self, dag_id: Uuid) -> bool {
+        self.paused_dags.get(&dag_id).is_some_and(|flag| *flag.borrow())
+    }
+
+    fn set_paused(&self, dag_id: Uuid, paused: bool) -> Result<()> {
+        if !self.active_dags.contains_key(&dag_id) {
+            return Err(ApexError::not_found("DAG", dag_id.to_string()));
+        }
+        let was_paused = self
+            .paused_dags
+            .entry(dag_id)
+            .or_insert_with(|| watch::channel(false).0)
+            .send_replace(paused);
+        if was_paused == paused {
+            return Ok(());
+        }
+
+        let event = if paused {
+            tracing::info!(dag_id = %dag_id, "DAG paused");
+            ExecutionEvent::DagPaused { dag_id }
+        } else {
+            tracing::info!(dag_id = %dag_id, "DAG resumed");
+            ExecutionEvent::DagResumed { dag_id }
+        };
+        let _ = self.events.send(event);
+
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            let persisted = if paused { db.pause_dag(dag_id).await } else { db.resume_dag(dag_id).await };
+            if let Err(e) = persisted {
+                tracing::warn!(dag_id = %dag_id, paused, error = %e, "Failed to persist DAG pause state");
             }
+        });
+        Ok(())
+    }
 
-            // Execute ready tasks in parallel
-            let mut handles = Vec::new();
+    /// A receiver of `dag_id`'s pause flag, created unpaused on first use.
+    fn pause_flag(&self, dag_id: Uuid) -> watch::Receiver<bool> {
+        self.paused_dags
+            .entry(dag_id)
+            .or_insert_with(|| watch::channel(false).0)
+            .subscribe()
+    }
 
-            for task_id in ready_tasks {
-                let permit = self.worker_semaphore.clone().acquire_owned().await?;
+    /// Cancel an active DAG, returning how many of its tasks were cancelled.
+    ///
+    /// Tasks not yet dispatched are cancelled, and dispatched ones still in
+    /// the worker queue are withdrawn from it. Tasks a worker already picked
+    /// up can't be recalled: this waits for them to finish (or their result
+    /// to time out) and for the execution loop to stop, so nothing of the DAG
+    /// is left running once it returns.
+    pub async fn cancel_dag(&self, dag_id: Uuid) -> Result<usize> {
+        let cancelled = self.begin_cancel_dag(dag_id).await?;
+        self.finish_cancel_dag(dag_id).await;
+        Ok(cancelled)
+    }
 
-                let dag_lock = dag_lock.clone();
-                let db = self.db.clone();
-                let redis_client = self.redis_client.clone();
-                let model_router = self.model_router.clone();
-                let agents = self.agents.clone();
-                let circuit_breaker = self.circuit_breaker.clone();
-                let default_limits = self.config.default_limits.clone();
-                let task_result_timeout_secs = self.config.task_result_timeout_secs;
+    /// The first half of [`Self::cancel_dag`]: stop dispatching, cancel and
+    /// withdraw what hasn't started, and return how many tasks that was,
+    /// without waiting on tasks already picked up. Follow it with
+    /// [`Self::finish_cancel_dag`].
+    pub async fn begin_cancel_dag(&self, dag_id: Uuid) -> Result<usize> {
+        let dag_lock = self
+            .active_dags
+            .get(&dag_id)
+            .ok_or_else(|| ApexError::not_found("DAG", dag_id.to_string()))?
+            .clone();
 
-                let handle = tokio::spawn(async move {
-                    let result = Self::execute_task(
-                        task_id,
-                        dag_id,
-                        dag_lock,
-                        db,
-                        redis_client,
-                        model_router,
-                        agents,
-                        circuit_breaker,
-                        default_limits,
-                        task_result_timeout_secs,
-                    ).await;
+        // Stop dispatching, waking the execution loop if it is paused
+        self.cancelled_dags.insert(dag_id);
+        if let Some(flag) = self.paused_dags.get(&dag_id) {
+            flag.send_replace(false);
+        }
 
-                    drop(permit); // Release semaphore permit
-                    result
-                });
+        let (not_started, in_flight) = {
+            let mut dag = dag_lock.write().await;
+            let cancelled = dag.cancel_remaining();
+            (cancelled, dag.progress().running_task_ids)
+        };
+        for &task_id in &not_started {
+            let _ = self.events.send(ExecutionEvent::TaskCancelled { dag_id, task_id });
+        }
 
-                handles.push(handle);
+        // The execution loop reports withdrawn tasks as cancelled once their
+        // dispatch returns
+        let mut withdrawn = 0;
+        for task_id in in_flight {
+            match self.runner.withdraw(&task_id.0.to_string()).await {
+                Ok(true) => withdrawn += 1,
+                Ok(false) => {}
+                Err(e) => tracing::warn!(task_id = %task_id, error = %e, "Failed to withdraw task of cancelled DAG"),
             }
+        }
 
-            // Wait for all parallel tasks
-            let results = futures::future::join_all(handles).await;
+        let cancelled = not_started.len() + withdrawn;
+        tracing::info!(dag_id = %dag_id, cancelled, "DAG cancelled");
+        Ok(cancelled)
+    }
 
-            for result in results {
-                match result {
-                    Ok(Ok(task_result)) => {
-                        total_tokens += task_result.tokens_used;
-                        total_cost += task_result.cost;
-                        tasks_completed += 1;
-                    }
-                    Ok(Err(e)) => {
-                        tracing::error!(error = %e, "Task execution failed");
-                        tasks_failed += 1;
-                    }
-                    Err(e) => {
-                        tracing::error!(error = %e, "Task join error");
-                        tasks_failed += 1;
-                    }
+    /// The second half of [`Self::cancel_dag`]: wait for the tasks of a DAG
+    /// being cancelled to finish (or their result to time out), then record
+    /// the cancellation. A DAG that was never executed is dropped from the
+    /// active DAGs here, since no execution loop will do it.
+    pub async fn finish_cancel_dag(&self, dag_id: Uuid) {
+        let executing = self.executing_dags.get(&dag_id).map(|entry| entry.value().clone());
+        match executing {
+            Some(mut finished) => {
+                let drain = Duration::from_secs(self.config.task_result_timeout_secs) + CANCEL_DRAIN_GRACE;
+                let drained = tokio::time::timeout(drain, async {
+                    while finished.changed().await.is_ok() {}
+                })
+                .await;
+                if drained.is_err() {
+                    tracing::warn!(dag_id = %dag_id, "Cancelled DAG still has tasks running after the result timeout");
                 }
             }
+            None => {
+                self.active_dags.remove(&dag_id);
+                self.paused_dags.remove(&dag_id);
+                self.cancelled_dags.remove(&dag_id);
+            }
         }
 
-        let elapsed = start_time.elapsed();
-
-        // Clean up
-        self.active_dags.remove(&dag_id);
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            if let Err(e) = db.cancel_dag(dag_id).await {
+                tracing::warn!(dag_id = %dag_id, error = %e, "Failed to persist DAG cancellation");
+            }
+        });
+    }
 
-        let result = DagExecutionResult {
-            dag_id,
-            status: if tasks_failed == 0 {
-                DagExecutionStatus::Completed
-            } else {
-                DagExecutionStatus::PartialFailure
-            },
-            tasks_completed,
-            tasks_failed,
-            total_tokens,
-            total_cost,
-            duration_ms: elapsed.as_millis() as u64,
+    /// Put a task whose worker died back on the worker queue.
+    ///
+    /// The task must be running for at least `stuck_after`, with retries
+    /// left and no result waiting. It is reset to `Ready` with its retry
+    /// count incremented, and its payload is published again; the dispatch
+    /// still waiting on the result picks up the new worker's answer. The DAG
+    /// is not locked while the payload is published.
+    pub async fn requeue_task(&self, task_id: TaskId, stuck_after: Duration) -> Result<RequeuedTask> {
+        let (dag_id, dag_lock) = self
+            .find_active_task(task_id)
+            .await
+            .ok_or_else(|| ApexError::task_not_found(task_id.0))?;
+
+        // Checked and marked requeueing under the DAG lock, so a concurrent
+        // requeue of the same task is refused. The lock is not held while the
+        // payload is published.
+        let (stuck_for, started_at) = {
+            let dag = dag_lock.read().await;
+            let task = dag.get_task(task_id).ok_or_else(|| ApexError::task_not_found(task_id.0))?;
+            let stuck_for = task
+                .started_at
+                .and_then(|started| (chrono::Utc::now() - started).to_std().ok())
+                .unwrap_or_default();
+            if task.status != TaskStatus::Running {
+                return Err(ApexError::task_not_requeueable(task_id.0, format!("it is {:?}, not Running", task.status)));
+            }
+            if stuck_for < stuck_after {
+                return Err(ApexError::task_not_requeueable(
+                    task_id.0,
+                    format!("it has been running for {}s, under the {}s threshold", stuck_for.as_secs(), stuck_after.as_secs()),
+                ));
+            }
+            if !task.should_retry() {
+                return Err(ApexError::task_not_requeueable(
+                    task_id.0,
+                    format!("it has used all {} retries", task.max_retries),
+                ));
+            }
+            if !self.requeueing_tasks.insert(task_id) {
+                return Err(ApexError::task_not_requeueable(task_id.0, "it is already being requeued"));
+            }
+            (stuck_for, task.started_at)
         };
 
-        tracing::info!(
+        let published = self.runner.requeue(&task_id.0.to_string()).await;
+
+        // Reset the task only if it is still the run that was published; a
+        // failed or refused publish leaves it as it was
+        let retry_count = {
+            let mut dag = dag_lock.write().await;
+            self.requeueing_tasks.remove(&task_id);
+            if !published? {
+                return Err(ApexError::task_not_requeueable(task_id.0, "its result has already arrived"));
+            }
+            let task = dag.get_task_mut(task_id).ok_or_else(|| ApexError::task_not_found(task_id.0))?;
+            if task.status != TaskStatus::Running || task.started_at != started_at {
+                return Err(ApexError::task_not_requeueable(task_id.0, "it finished while being requeued"));
+            }
+            task.retry_count += 1;
+            task.status = TaskStatus::Ready;
+            task.started_at = None;
+            task.retry_count
+        };
+        counter!("apex_tasks_requeued_total").increment(1);
+        tracing::warn!(
+            task_id = %task_id,
             dag_id = %dag_id,
-            tasks_completed = tasks_completed,
-            tasks_failed = tasks_failed,
-            total_tokens = total_tokens,
-            total_cost = total_cost,
-            duration_ms = result.duration_ms,
-            "DAG execution completed"
+            retry_count,
+            stuck_secs = stuck_for.as_secs(),
+            "Requeued stuck task"
         );
 
-        Ok(result)
+        let db = self.db.clone();
+        tokio::spawn(async move {
+            if let Err(e) = db.requeue_task(task_id).await {
+                tracing::warn!(task_id = %task_id, error = %e, "Failed to persist task requeue");
+            }
+        });
+
+        Ok(RequeuedTask {
+            task_id: task_id.0,
+            dag_id,
+            retry_count,
+            stuck_secs: stuck_for.as_secs(),
+        })
     }
 
-    /// Execute a single task by publishing it to Redis and waiting for the result.
-    async fn execute_task(
-        task_id: TaskId,
-        dag_id: Uuid,
-        dag_lock: Arc<RwLock<TaskDAG>>,
-        _db: Arc<Database>,
-        redis_client: redis::Client,
-        model_router: Arc<ModelRouter>,
-        agents: DashMap<AgentId, Arc<Agent>>,
-        circuit_breaker: Arc<CircuitBreaker>,
-        default_limits: ResourceLimits,
-        task_result_timeout_secs: u64,
-    ) -> Result<TaskExecutionResult> {
-        let span = tracing::info_span!("execute_task", task_id = %task_id);
-        let _guard = span.enter();
+    /// Expire pending approvals past their deadline (or older than
+    /// `max_age_hours` without one) and wake whatever is waiting on them, so
+    /// blocked tasks fail now rather than when their own wait times out.
+    pub async fn expire_approvals(&self, max_age_hours: u64) -> Result<Vec<crate::db::ExpiredApproval>> {
+        let expired = self.db.expire_approvals(max_age_hours).await?;
+        for approval in &expired {
+            self.approvals.resolve(approval.id, ApprovalResolution::Expired);
+        }
+        Ok(expired)
+    }
 
-        // Get task details
-        let task = {
+    /// Requeue every running task stuck for at least `stuck_after` with
+    /// retries left. Tasks that can't be requeued are logged and skipped.
+    pub async fn requeue_stuck_tasks(&self, stuck_after: Duration) -> Vec<RequeuedTask> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::from_std(stuck_after).unwrap_or_else(|_| chrono::Duration::zero());
+        let dags: Vec<_> = self.active_dags.iter().map(|entry| entry.value().clone()).collect();
+        let mut stuck = Vec::new();
+        for dag_lock in dags {
             let dag = dag_lock.read().await;
-            dag.get_task(task_id)
-                .ok_or_else(|| ApexError::task_not_found(task_id.0))?
-                .clone()
-        };
-
-        // Check circuit breaker
-        if !circuit_breaker.can_execute() {
-            return Err(ApexError::internal("Circuit breaker is open"));
+            stuck.extend(dag.progress().running_task_ids.into_iter().filter(|id| {
+                dag.get_task(*id)
+                    .is_some_and(|t| t.should_retry() && t.started_at.is_some_and(|started| started <= cutoff))
+            }));
         }
 
-        // Select agent (round-robin for now, CNP bidding later)
-        let agent = agents.iter()
-            .find(|entry| entry.value().is_available())
-            .map(|entry| entry.value().clone())
-            .ok_or_else(|| ApexError::internal("No available agents"))?;
-
-        // Select model via router
-        let model = if let Some(router) = Some(&model_router) {
-            router.select_model(&task.input.instruction)
-        } else {
-            "gpt-4o-mini".to_string()
-        };
-
-        // Mark task as running
-        {
-            let mut dag = dag_lock.write().await;
-            if let Some(t) = dag.get_task_mut(task_id) {
-                t.start(agent.id.0);
+        let mut requeued = Vec::with_capacity(stuck.len());
+        for task_id in stuck {
+            match self.requeue_task(task_id, stuck_after).await {
+                Ok(task) => requeued.push(task),
+                Err(e) => tracing::warn!(task_id = %task_id, error = %e, "Could not requeue stuck task"),
             }
         }
+        requeued
+    }
 
-        // Create contract for this task
-        let _contract = AgentContract::new(agent.id.0, task_id.0, default_limits.clone());
-
-        // Execute the task via Redis queue
-        let execution_start = std::time::Instant::now();
+    /// The active DAG `task_id` belongs to.
+    async fn find_active_task(&self, task_id: TaskId) -> Option<(Uuid, Arc<RwLock<TaskDAG>>)> {
+        let dags: Vec<_> = self
+            .active_dags
+            .iter()
+            .map(|entry| (*entry.key(), entry.value().clone()))
+            .collect();
+        for (dag_id, dag_lock) in dags {
+            if dag_lock.read().await.get_task(task_id).is_some() {
+                return Some((dag_id, dag_lock));
+            }
+        }
+        None
+    }
 
-        // Build the task payload for the pending queue
-        let payload = RedisTaskPayload {
-            task_id: task_id.0.to_string(),
-            dag_id: dag_id.to_string(),
-            input: serde_json::to_value(&task.input)?,
-            contract: RedisContractPayload {
-                token_limit: default_limits.token_limit,
-                cost_limit: default_limits.cost_limit,
-                api_call_limit: default_limits.api_call_limit,
-                time_limit_seconds: default_limits.time_limit_seconds,
-            },
-            trace_context: Some(RedisTraceContext {
-                trace_id: task.trace_id.clone(),
-                span_id: task.span_id.clone(),
-            }),
-        };
+    /// Execute a DAG to completion.
+    pub async fn execute_dag(&self, dag_id: Uuid) -> Result<DagExecutionResult> {
+        self.run_dag(dag_id, None).await
+    }
 
-        let payload_json = serde_json::to_string(&payload)?;
+    /// Execute a DAG in the background, streaming each task's outcome as it
+    /// finishes and then the DAG's result.
+    ///
+    /// Execution doesn't wait for the consumer: once it is
+    /// [`stream_buffer`](OrchestratorConfig::stream_buffer) items behind,
+    /// further task updates are dropped. The final
+    /// [`Finished`](DagStreamItem::Finished) item is always delivered, and
+    /// dropping the stream doesn't stop the DAG.
+    pub fn execute_dag_stream(self: &Arc<Self>, dag_id: Uuid) -> impl Stream<Item = DagStreamItem> {
+        let (tx, rx) = mpsc::channel(self.config.stream_buffer.max(1));
+        let orchestrator = self.clone();
+        tokio::spawn(async move {
+            let result = orchestrator.run_dag(dag_id, Some(&tx)).await;
+            let _ = tx.send(DagStreamItem::Finished(result)).await;
+        }.in_current_span());
+        ReceiverStream::new(rx)
+    }
 
-        // Publish task to the pending queue
-        {
-            let _redis_span = tracing::info_span!("redis_publish_task", task_id = %task_id);
-            let _redis_guard = _redis_span.enter();
-
-            let mut conn = redis_client.get_multiplexed_async_connection().await
-                .map_err(|e| ApexError::with_internal(
-                    crate::error::ErrorCode::CacheConnectionFailed,
-                    "Failed to connect to Redis for task publishing",
-                    e.to_string(),
-                ))?;
-
-            redis::cmd("RPUSH")
-                .arg("apex:tasks:pending")
-                .arg(&payload_json)
-                .query_async::<_, i64>(&mut conn)
-                .await
-                .map_err(|e| ApexError::with_internal(
-                    crate::error::ErrorCode::CacheError,
-                    "Failed to publish task to Redis queue",
-                    e.to_string(),
-                ))?;
-
-            tracing::debug!(task_id = %task_id, "Task published to apex:tasks:pending");
-        }
-
-        // Wait for the result on the per-task result queue
-        let result_key = format!("apex:tasks:result:{}", task_id.0);
-        let redis_result: RedisTaskResult = {
-            let _redis_span = tracing::info_span!("redis_await_result", task_id = %task_id, result_key = %result_key);
-            let _redis_guard = _redis_span.enter();
-
-            let mut conn = redis_client.get_multiplexed_async_connection().await
-                .map_err(|e| ApexError::with_internal(
-                    crate::error::ErrorCode::CacheConnectionFailed,
-                    "Failed to connect to Redis for result polling",
-                    e.to_string(),
-                ))?;
-
-            // BLPOP blocks until a result is available or the timeout expires
-            let blpop_result: Option<(String, String)> = redis::cmd("BLPOP")
-                .arg(&result_key)
-                .arg(task_result_timeout_secs)
-                .query_async(&mut conn)
-                .await
-                .map_err(|e| ApexError::with_internal(
-                    crate::error::ErrorCode::CacheError,
-                    "Failed to read task result from Redis",
-                    e.to_string(),
-                ))?;
-
-            match blpop_result {
-                Some((_key, value)) => {
-                    serde_json::from_str::<RedisTaskResult>(&value).map_err(|e| {
-                        ApexError::with_internal(
-                            crate::error::ErrorCode::DeserializationError,
-                            "Failed to deserialize task result from Redis",
-                            e.to_string(),
-                        )
-                    })?
-                }
-                None => {
-                    // Timeout: no result received within the configured window
-                    circuit_breaker.record_failure();
-                    return Err(ApexError::with_internal(
-                        crate::error::ErrorCode::AgentTimeout,
-                        "Task execution timed out waiting for agent result",
-                        format!(
-                            "No result on {} within {}s",
-                            result_key, task_result_timeout_secs
-                        ),
-                    ));
-                }
+    /// Push a task outcome to an `execute_dag_stream` consumer, unless it is
+    /// too far behind. Tasks cancelled with their DAG aren't reported.
+    async fn report_task(
+        progress: &mpsc::Sender<DagStreamItem>,
+        dag_lock: &RwLock<TaskDAG>,
+        task_id: TaskId,
+        result: &Result<TaskExecutionResult>,
+    ) {
+        let item = match result {
+            Ok(task_result) => DagStreamItem::TaskCompleted(task_result.clone()),
+            Err(_) if dag_lock.read().await.get_task(task_id).is_some_and(|t| t.status == TaskStatus::Cancelled) => {
+                return;
             }
+            Err(e) => DagStreamItem::TaskFailed { task_id, error: e.to_string() },
         };
+        if let Err(mpsc::error::TrySendError::Full(_)) = progress.try_send(item) {
+            counter!("apex_dag_stream_dropped_total").increment(1);
+            tracing::debug!(task_id = %task_id, "DAG stream consumer is behind, dropping task update");
+        }
+    }
 
-        let elapsed = execution_start.elapsed();
+    async fn run_dag(
+        &self,
+        dag_id: Uuid,
+        progress: Option<&mpsc::Sender<DagStreamItem>>,
+    ) -> Result<DagExecutionResult> {
+        let dag_lock = self.active_dags.get(&dag_id)
+            .ok_or_else(|| ApexError::not_found("DAG", dag_id.to_string()))?
+            .clone();
+
+        let span = tracing::info_span!("execute_dag", dag_id = %dag_id);
+        let _guard = span.enter();
+
+        let start_time = std::time::Instant::now();
+        let mut total_tokens = 0u64;
+        let mut total_cost = 0.0f64;
+        let mut tasks_completed = 0usize;
+        let mut tasks_failed = 0usize;
+        let mut tasks_cancelled = 0usize;
+
+        let (failure_policy, max_failed_tasks, weight) = {
+            let dag = dag_lock.read().await;
+            let policy = dag.failure_policy().unwrap_or(self.config.failure_policy);
+            let limit = self.config.max_failed_tasks.map(|t| t.limit(dag.stats().total));
+            (policy, limit, dag.weight())
+        };
+        let dag_budget = self.dag_budgets.get(&dag_id).map(|budget| budget.clone());
+        let mut aborted = false;
+        let mut cancelled = false;
+        let mut paused = self.pause_flag(dag_id);
+        let (_executing, finished) = watch::channel(());
+        self.executing_dags.insert(dag_id, finished);
+
+        // Tasks run as soon as they are ready rather than in waves: the loop
+        // dispatches what is ready, then sleeps until a running task finishes
+        let task_done = Arc::new(Notify::new());
+        let ctx = Arc::new(TaskContext {
+            dag_id,
+            dag_lock: dag_lock.clone(),
+            dag_budget,
+            task_done: task_done.clone(),
+            db: self.db.clone(),
+            runner: self.runner.clone(),
+            model_router: self.model_router.clone(),
+            agents: self.agents.clone(),
+            agent_released: self.agent_released.clone(),
+            cnp: self.cnp.clone(),
+            circuit_breakers: self.circuit_breakers.clone(),
+            agent_circuit_breakers: self.agent_circuit_breakers.clone(),
+            events: self.events.clone(),
+            default_limits: self.config.default_limits.clone(),
+            max_task_limits: self.config.max_task_limits.clone(),
+            retry_delay: Duration::from_millis(self.config.retry_delay_ms),
+            context_limit: self.config.context_limit.clone(),
+        });
+        let mut in_flight: Vec<(TaskId, JoinHandle<Result<TaskExecutionResult>>)> = Vec::new();
+
+        loop {
+            // Settle finished tasks. A paused, cancelled or aborted DAG waits
+            // for everything in flight, so that it halts holding no workers
+            let halting = aborted || *paused.borrow() || self.cancelled_dags.contains(&dag_id);
+            let settled: Vec<_> = {
+                let dag = dag_lock.read().await;
+                let (settled, running) = std::mem::take(&mut in_flight)
+                    .into_iter()
+                    .partition(|(task_id, handle)| {
+                        halting
+                            || handle.is_finished()
+                            || dag.get_task(*task_id).is_some_and(|t| t.status.is_terminal())
+                    });
+                in_flight = running;
+                settled
+            };
+            let (task_ids, handles): (Vec<_>, Vec<_>) = settled.into_iter().unzip();
+            let results = futures::future::join_all(handles).await;
+
+            for (task_id, result) in task_ids.into_iter().zip(results) {
+                let (error, task_error) = match result {
+                    Ok(Ok(task_result)) => {
+                        total_tokens += task_result.tokens_used;
+                        total_cost += task_result.cost;
+                        tasks_completed += 1;
+                        continue;
+                    }
+                    Ok(Err(e)) => {
+                        tracing::error!(task_id = %task_id, error = %e, "Task execution failed");
+                        (e.to_string(), TaskError::from(&e))
+                    }
+                    Err(e) => {
+                        tracing::error!(task_id = %task_id, error = %e, "Task join error");
+                        (e.to_string(), TaskError::new(crate::error::ErrorCode::InternalError, e.to_string()))
+                    }
+                };
+
+                // Withdrawn by `cancel_dag` before a worker ran it
+                if dag_lock.read().await.get_task(task_id).is_some_and(|t| t.status == TaskStatus::Cancelled) {
+                    tasks_cancelled += 1;
+                    let _ = self.events.send(ExecutionEvent::TaskCancelled { dag_id, task_id });
+                    continue;
+                }
+                tasks_failed += 1;
+
+                let cancelled = dag_lock.write().await
+                    .fail_task(task_id, task_error, failure_policy)?;
+                tasks_cancelled += cancelled.len();
+
+                let _ = self.events.send(ExecutionEvent::TaskFailed {
+                    dag_id,
+                    task_id,
+                    error,
+                    will_retry: false,
+                });
+                for cancelled_id in cancelled {
+                    let _ = self.events.send(ExecutionEvent::TaskCancelled {
+                        dag_id,
+                        task_id: cancelled_id,
+                    });
+                }
+            }
+
+            // Tasks still pending once the DAG was aborted never run
+            if aborted {
+                let cancelled = dag_lock.write().await.cancel_remaining();
+                tasks_cancelled += cancelled.len();
+                for cancelled_id in cancelled {
+                    let _ = self.events.send(ExecutionEvent::TaskCancelled {
+                        dag_id,
+                        task_id: cancelled_id,
+                    });
+                }
+                break;
+            }
+
+            let over_threshold = max_failed_tasks.map(|limit| tasks_failed > limit).unwrap_or(false);
+            let out_of_budget = match &ctx.dag_budget {
+                Some(budget) => budget.is_exhausted(),
+                None => false,
+            };
+            if over_threshold || out_of_budget {
+                if over_threshold {
+                    tracing::warn!(
+                        dag_id = %dag_id,
+                        tasks_failed = tasks_failed,
+                        max_failed_tasks = ?max_failed_tasks,
+                        "Failure threshold exceeded, aborting DAG"
+                    );
+                } else {
+                    tracing::warn!(
+                        dag_id = %dag_id,
+                        total_tokens = total_tokens,
+                        total_cost = total_cost,
+                        "DAG budget exhausted, aborting DAG"
+                    );
+                }
+                // Let the tasks in flight finish before cancelling the rest
+                aborted = true;
+                continue;
+            }
+
+            // A paused DAG waits here, with nothing in flight and no worker
+            // permits held
+            if *paused.borrow() && in_flight.is_empty() {
+                tracing::info!(dag_id = %dag_id, "DAG paused, waiting for resume");
+                let _ = paused.wait_for(|paused| !*paused).await;
+            }
+
+            // Likewise a cancelled DAG stops here
+            if self.cancelled_dags.contains(&dag_id) && in_flight.is_empty() {
+                tasks_cancelled = dag_lock.read().await.stats().cancelled;
+                cancelled = true;
+                break;
+            }
+
+            // Get ready tasks, after expanding and joining map tasks and
+            // skipping branches whose conditions weren't met
+            let mut expanded: Vec<Task> = Vec::new();
+            let ready_tasks = {
+                let mut dag = dag_lock.write().await;
+                loop {
+                    let updates = dag.advance_map_nodes(failure_policy);
+                    let skipped = dag.skip_unsatisfied();
+                    if updates.is_empty() && skipped.is_empty() {
+                        break;
+                    }
+
+                    let mut cancelled = skipped;
+                    for update in updates {
+                        match update {
+                            MapUpdate::Expanded { map_id, instances } => {
+                                tracing::debug!(dag_id = %dag_id, task_id = %map_id, instances = instances.len(), "Expanded map task");
+                                for id in instances {
+                                    // Repeated items share an instance under dedup
+                                    if !expanded.iter().any(|task| task.id == id) {
+                                        expanded.extend(dag.get_task(id).cloned());
+                                    }
+                                }
+                            }
+                            MapUpdate::Joined { map_id } => {
+                                let _ = self.events.send(ExecutionEvent::TaskCompleted {
+                                    dag_id,
+                                    task_id: map_id,
+                                    tokens: 0,
+                                    cost: 0.0,
+                                    duration_ms: 0,
+                                });
+                            }
+                            MapUpdate::Failed { map_id, error, cancelled: dependents } => {
+                                tasks_failed += 1;
+                                cancelled.extend(dependents);
+                                let _ = self.events.send(ExecutionEvent::TaskFailed {
+                                    dag_id,
+                                    task_id: map_id,
+                                    error,
+                                    will_retry: false,
+                                });
+                            }
+                        }
+                    }
+                    tasks_cancelled += cancelled.len();
+                    for cancelled_id in cancelled {
+                        let _ = self.events.send(ExecutionEvent::TaskCancelled {
+                            dag_id,
+                            task_id: cancelled_id,
+                        });
+                    }
+                }
+                if dag.is_complete() && in_flight.is_empty() {
+                    break;
+                }
+                dag.get_ready_tasks()
+            };
+
+            // Map instances get their rows before they run, so their status
+            // updates have something to land on
+            if !expanded.is_empty() {
+                if let Err(e) = self.dag_store.insert_tasks(dag_id, &expanded).await {
+                    tracing::warn!(dag_id = %dag_id, tasks = expanded.len(), error = %e, "Failed to persist map instances");
+                }
+            }
+
+            // Dispatched tasks stay pending until they claim an agent
+            let ready_tasks: Vec<_> = ready_tasks
+                .into_iter()
+                .filter(|task_id| !in_flight.iter().any(|(running, _)| running == task_id))
+                .collect();
+            if ready_tasks.is_empty() && in_flight.is_empty() {
+                // Nothing is running, so the remaining tasks are blocked
+                // behind failures
+                break;
+            }
+
+            for task_id in ready_tasks {
+                // Once paused, leave the remaining ready tasks for after
+                // resume; once cancelled, for `cancel_dag` to cancel
+                if *paused.borrow() || self.cancelled_dags.contains(&dag_id) {
+                    break;
+                }
+
+                // Under fair scheduling, wait for this DAG's turn before taking a worker
+                let fair_permit = match &self.fair_scheduler {
+                    Some(scheduler) => Some(scheduler.acquire(dag_id, weight).await),
+                    None => None,
+                };
+                let permit = self.worker_semaphore.clone().acquire_owned().await?;
+
+                let ctx = ctx.clone();
+                let progress = progress.cloned();
+
+                let handle = tokio::spawn(async move {
+                    let result = Self::execute_task(&ctx, task_id).await;
+
+                    drop(permit); // Release semaphore permit
+                    drop(fair_permit);
+                    if let Some(progress) = &progress {
+                        Self::report_task(progress, &ctx.dag_lock, task_id, &result).await;
+                    }
+                    // Completions signal from `execute_task`; failures from here
+                    if result.is_err() {
+                        ctx.task_done.notify_one();
+                    }
+                    result
+                }.in_current_span());
+
+                in_flight.push((task_id, handle));
+            }
+
+            // Wait for a task to finish, re-checking now and then in case one
+            // is stuck without signalling
+            if !in_flight.is_empty() {
+                let _ = tokio::time::timeout(READY_POLL_FALLBACK, task_done.notified()).await;
+            }
+        }
+
+        let elapsed = start_time.elapsed();
+        let dag_name = dag_lock.read().await.name().to_string();
+
+        // Clean up
+        self.active_dags.remove(&dag_id);
+        self.dag_budgets.remove(&dag_id);
+        self.paused_dags.remove(&dag_id);
+        self.cancelled_dags.remove(&dag_id);
+        self.executing_dags.remove(&dag_id);
+
+        let result = DagExecutionResult {
+            dag_id,
+            status: if cancelled {
+                DagExecutionStatus::Cancelled
+            } else if aborted {
+                DagExecutionStatus::Failed
+            } else if tasks_failed == 0 {
+                DagExecutionStatus::Completed
+            } else {
+                DagExecutionStatus::PartialFailure
+            },
+            tasks_completed,
+            tasks_failed,
+            tasks_cancelled,
+            max_failed_tasks,
+            total_tokens,
+            total_cost,
+            duration_ms: elapsed.as_millis() as u64,
+        };
+
+        DagMetrics::record_execution(
+            &dag_name,
+            result.status.as_str(),
+            tasks_completed as u64,
+            tasks_failed as u64,
+            total_cost,
+            elapsed.as_secs_f64(),
+        );
+
+        tracing::info!(
+            dag_id = %dag_id,
+            tasks_completed = tasks_completed,
+            tasks_failed = tasks_failed,
+            tasks_cancelled = tasks_cancelled,
+            total_tokens = total_tokens,
+            total_cost = total_cost,
+            duration_ms = result.duration_ms,
+            "DAG execution completed"
+        );
+
+        Ok(result)
+    }
+
+    /// Execute a single task by handing it to the task runner and waiting for the result.
+    async fn execute_task(ctx: &TaskContext, task_id: TaskId) -> Result<TaskExecutionResult> {
+        let TaskContext {
+            dag_id,
+            dag_lock,
+            dag_budget,
+            task_done,
+            db,
+            runner,
+            model_router,
+            agents,
+            agent_released,
+            cnp,
+            circuit_breakers,
+            agent_circuit_breakers,
+            events,
+            default_limits,
+            max_task_limits,
+            retry_delay,
+            context_limit,
+        } = ctx;
+        let (dag_id, retry_delay, db) = (*dag_id, *retry_delay, db.clone());
+        let span = tracing::info_span!(
+            "execute_task",
+            task_id = %task_id,
+            worker_duration_ms = tracing::field::Empty,
+            dispatch_overhead_ms = tracing::field::Empty,
+        );
+        let _guard = span.enter();
+
+        // Get task details, with input bindings filled from the outputs of
+        // completed predecessors
+        let (task, mut input) = {
+            let dag = dag_lock.read().await;
+            let task = dag.get_task(task_id)
+                .ok_or_else(|| ApexError::task_not_found(task_id.0))?
+                .clone();
+            (task, dag.resolve_input(task_id)?)
+        };
+
+        // Select the least-loaded, best-reputation agent, or the winner of a
+        // bidding round, among those whose circuit isn't open, waiting up to
+        // the task's own time limit (or what is left of its DAG's) if all are
+        // busy; the slot is held until this task finishes
+        let candidates: Vec<Arc<Agent>> = agents
+            .iter()
+            .filter(|entry| agent_circuit_breakers.can_execute(&entry.key().0.to_string()))
+            .map(|entry| entry.value().clone())
+            .collect();
+        if candidates.is_empty() && !agents.is_empty() {
+            return Err(ApexError::new(
+                crate::error::ErrorCode::AllAgentsBusy,
+                format!("The circuit breakers of all {} agents are open", agents.len()),
+            ));
+        }
+        // A task can ask for more or less than the default, up to the ceiling
+        let task_limits = match &task.limits {
+            Some(limits) => limits.capped_at(max_task_limits),
+            None => default_limits.clone(),
+        };
+        let claim_limits = match &dag_budget {
+            Some(budget) => budget.time_bounded(&task_limits),
+            None => task_limits.clone(),
+        };
+        let claim_timeout = Duration::from_secs(claim_limits.time_limit_seconds);
+        let agent = match &cnp {
+            Some(cnp) => {
+                let announcement = TaskAnnouncement {
+                    task_id: task_id.to_string(),
+                    description: task.name.clone(),
+                    requirements: Vec::new(),
+                    deadline_secs: task_limits.time_limit_seconds,
+                    min_bid_count: 1,
+                    metadata: serde_json::json!({ "dag_id": dag_id }),
+                };
+                let prompt = input.prompt();
+                let (agent, award) = cnp.claim_by_bidding(&announcement, &candidates, agent_released, claim_timeout, |agent| {
+                    model_router
+                        .estimate_dispatch(&agent.model, &prompt, ESTIMATED_OUTPUT_TOKENS)
+                        .map(|estimate| estimate.cost)
+                        .unwrap_or(0.0)
+                }).await?;
+
+                // Keep the scoring with the task, for comparing strategies
+                let award = serde_json::to_value(&award)?;
+                if let Some(t) = dag_lock.write().await.get_task_mut(task_id) {
+                    t.metadata.insert(CNP_AWARD_KEY.to_string(), award.clone());
+                }
+                let db = db.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = db.set_task_metadata(task_id, CNP_AWARD_KEY, &award).await {
+                        tracing::warn!(task_id = %task_id, error = %e, "Failed to persist task award");
+                    }
+                });
+                agent
+            }
+            None => claim_agent(&candidates, agent_released, claim_timeout).await?,
+        };
+
+        // Contract for this task, reserving its share of what is left of the
+        // DAG's budget until the task finishes
+        let mut contract = match &dag_budget {
+            Some(budget) => budget.reserve(agent.id.0, task_id.0, &task_limits).await?,
+            None => TaskContract::standalone(AgentContract::new(agent.id.0, task_id.0, task_limits)),
+        };
+        // Child contracts can expire before their own time limit runs out
+        let limits = contract.limits.with_time_budget_deadline(contract.expires_at);
+
+        // Select model via router
+        let mut model = if let Some(router) = Some(&model_router) {
+            router.select_model_with_slo(&input.instruction, input.latency_slo())
+        } else {
+            "gpt-4o-mini".to_string()
+        };
+
+        // Enforce the context size limit before estimating or dispatching
+        let (context, context_truncation) = context_limit.apply(&input.context, &model)?;
+        input.context = context;
+        if let Some(truncation) = &context_truncation {
+            tracing::warn!(
+                task_id = %task_id,
+                strategy = ?truncation.strategy,
+                original_bytes = truncation.original_bytes,
+                truncated_bytes = truncation.truncated_bytes,
+                "Task context truncated to fit the context limit"
+            );
+        }
+
+        // Estimate the prompt against the model's context and the task budget,
+        // downgrading to a cheaper model or refusing before anything is spent
+        let prompt = input.prompt();
+        let estimate = match model_router.estimate_dispatch(&model, &prompt, ESTIMATED_OUTPUT_TOKENS) {
+            Some(estimate) if !estimate.within(limits.token_limit, limits.cost_limit) => {
+                let downgraded = model_router.cheapest_within_budget(
+                    &prompt,
+                    ESTIMATED_OUTPUT_TOKENS,
+                    limits.token_limit,
+                    limits.cost_limit,
+                );
+                let Some(downgraded) = downgraded else {
+                    return Err(if estimate.total_tokens() > limits.token_limit {
+                        ApexError::token_limit_exceeded(estimate.total_tokens(), limits.token_limit)
+                    } else {
+                        ApexError::cost_limit_exceeded(estimate.cost, limits.cost_limit)
+                    });
+                };
+                tracing::info!(
+                    task_id = %task_id,
+                    from = %model,
+                    to = %downgraded.model,
+                    estimated_cost = estimate.cost,
+                    "Downgrading model to fit task budget"
+                );
+                model = downgraded.model.clone();
+                Some(downgraded)
+            }
+            estimate => estimate,
+        };
+
+        // Fail fast while the chosen model's circuit is open
+        if !circuit_breakers.can_execute(&model) {
+            return Err(ApexError::new(
+                crate::error::ErrorCode::LlmUnavailable,
+                format!("Circuit breaker for model '{}' is open", model),
+            ));
+        }
+        let agent_key = agent.id.0.to_string();
+        // Failures the worker reports come from the model or its provider;
+        // only attempts that got no result at all count against the agent
+        let record_failure = |model: &str, agent_side: bool| {
+            if let Some(reason) = circuit_breakers.record_failure(model) {
+                let _ = events.send(ExecutionEvent::CircuitOpened { model: model.to_string(), reason });
+            }
+            if !agent_side {
+                return;
+            }
+            if let Some(reason) = agent_circuit_breakers.record_failure(&agent_key) {
+                tracing::warn!(agent_id = %agent.id.0, reason = ?reason, "Agent circuit opened; passing over it until it recovers");
+            }
+        };
+
+        // Mark task as running, unless its DAG was cancelled meanwhile
+        {
+            let mut dag = dag_lock.write().await;
+            if let Some(t) = dag.get_task_mut(task_id) {
+                if t.status == TaskStatus::Cancelled {
+                    return Err(ApexError::task_cancelled(task_id.0));
+                }
+                t.start(agent.id.0);
+            }
+        }
+
+        // Build the task payload for the worker
+        let payload = RedisTaskPayload {
+            task_id: task_id.0.to_string(),
+            dag_id: dag_id.to_string(),
+            input: serde_json::to_value(&input)?,
+            contract: RedisContractPayload {
+                token_limit: limits.token_limit,
+                cost_limit: limits.cost_limit,
+                api_call_limit: limits.api_call_limit,
+                time_limit_seconds: limits.time_limit_seconds,
+            },
+            trace_context: Some(RedisTraceContext::for_span(&span, task.trace_id.clone(), task.span_id.clone())),
+            context_truncation,
+            model: Some(model.clone()),
+        };
+
+        tracing::info!(
+            task_id = %task_id,
+            model = %model,
+            estimated_input_tokens = estimate.as_ref().map(|e| e.input_tokens),
+            estimated_cost = estimate.as_ref().map(|e| e.cost),
+            "Dispatching task"
+        );
+        // Prompts can carry user data; only log them with secrets redacted
+        let redactor = SensitiveFieldRedactor::global();
+        tracing::debug!(
+            task_id = %task_id,
+            input = %redactor.redact_json(&payload.input),
+            "Task input"
+        );
+
+        // Stored only once nothing but the dispatch itself can fail, so every
+        // stored contract gets an outcome
+        let contract_inserted = {
+            let db = db.clone();
+            let contract = contract.clone();
+            tokio::spawn(async move {
+                if let Err(e) = db.insert_contract(&contract).await {
+                    tracing::warn!(contract_id = %contract.id, error = %e, "Failed to persist contract");
+                }
+            })
+        };
+
+        // Execute the task via the runner. Worker failures and result
+        // timeouts are retried with exponential backoff while the task has
+        // retries left; Redis connection errors are retried separately and
+        // neither use up retries nor count against the model's circuit. All
+        // of it, retries included, has to fit in the contract's time budget
+        let mut connection_retries = 0;
+        let dispatched_at = std::time::Instant::now();
+        let mut execution_start = dispatched_at;
+        let time_budget = Duration::from_secs(limits.time_limit_seconds);
+        let execution = async {
+            loop {
+                // Each attempt is bounded by, and tells the worker, what is
+                // left of the time budget
+                execution_start = std::time::Instant::now();
+                let remaining = time_budget.saturating_sub(dispatched_at.elapsed());
+                let mut attempt = payload.clone();
+                attempt.contract.time_limit_seconds = remaining.as_secs().max(1);
+                let outcome = match tokio::time::timeout(remaining, runner.run(attempt)).await {
+                    Ok(outcome) => outcome,
+                    Err(_) => Err(ApexError::new(crate::error::ErrorCode::AgentTimeout, "Task attempt ran out of time")),
+                };
+                // What the worker spent counts against the contract, whether
+                // or not the attempt succeeded
+                if let Ok(result) = &outcome {
+                    contract.charge(result.tokens_used, result.cost_dollars);
+                }
+                let error = match &outcome {
+                    Ok(result) if result.status == "failed" => result
+                        .error
+                        .clone()
+                        .unwrap_or_else(|| "Agent worker reported failure".to_string()),
+                    Ok(_) => break outcome,
+                    // Timeout: no result received within the configured window
+                    Err(e) if e.code() == crate::error::ErrorCode::AgentTimeout => e.to_string(),
+                    Err(e) if matches!(e.code(), crate::error::ErrorCode::CacheConnectionFailed | crate::error::ErrorCode::CacheError)
+                        && connection_retries < MAX_CONNECTION_RETRIES =>
+                    {
+                        let delay = backoff(retry_delay, connection_retries);
+                        connection_retries += 1;
+                        tracing::warn!(
+                            task_id = %task_id,
+                            error = %e,
+                            attempt = connection_retries,
+                            delay_ms = delay.as_millis() as u64,
+                            "Redis unavailable, retrying task dispatch"
+                        );
+                        withdraw_attempt(runner.as_ref(), &payload.task_id).await;
+                        tokio::time::sleep(delay).await;
+                        continue;
+                    }
+                    Err(_) => break outcome,
+                };
+                record_failure(&model, outcome.is_err());
+
+                // Retry unless the task is out of retries or time, or its
+                // model's or agent's circuit just opened
+                let retry_count = {
+                    let mut dag = dag_lock.write().await;
+                    match dag.get_task_mut(task_id) {
+                        Some(t) if t.should_retry()
+                            && dispatched_at.elapsed() < time_budget
+                            && circuit_breakers.can_execute(&model)
+                            && agent_circuit_breakers.can_execute(&agent_key) =>
+                        {
+                            t.retry_count += 1;
+                            t.status = TaskStatus::Ready;
+                            t.started_at = None;
+                            t.retry_count
+                        }
+                        _ => break outcome,
+                    }
+                };
+                if let Ok(result) = &outcome {
+                    model_router.record_latency(&model, execution_start.elapsed().as_secs_f64());
+                    trace::record_worker_span(&span, result, execution_start.elapsed());
+                }
+                let _ = events.send(ExecutionEvent::TaskFailed {
+                    dag_id,
+                    task_id,
+                    error: error.clone(),
+                    will_retry: true,
+                });
+                let persist = db.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = persist.requeue_task(task_id).await {
+                        tracing::warn!(task_id = %task_id, error = %e, "Failed to persist task retry");
+                    }
+                });
+                let delay = backoff(retry_delay, retry_count - 1);
+                tracing::warn!(
+                    task_id = %task_id,
+                    error = %error,
+                    retry_count,
+                    max_retries = task.max_retries,
+                    delay_ms = delay.as_millis() as u64,
+                    "Task failed, retrying"
+                );
+                // A timed-out attempt may still be queued; take it back before publishing again
+                if outcome.is_err() {
+                    withdraw_attempt(runner.as_ref(), &payload.task_id).await;
+                }
+                tokio::time::sleep(delay.min(time_budget.saturating_sub(dispatched_at.elapsed()))).await;
+
+                // Back to running, unless its DAG was cancelled meanwhile
+                let mut dag = dag_lock.write().await;
+                if let Some(t) = dag.get_task_mut(task_id) {
+                    if t.status == TaskStatus::Cancelled {
+                        return Err(ApexError::task_cancelled(task_id.0));
+                    }
+                    t.start(agent.id.0);
+                }
+            }
+        };
+        let outcome = match tokio::time::timeout(time_budget, execution).await {
+            Ok(Ok(result)) => Ok(result),
+            Ok(Err(e)) if dispatched_at.elapsed() < time_budget => Err(e),
+            _ => {
+                let elapsed = dispatched_at.elapsed().as_secs();
+                contract.status = ContractStatus::Exceeded;
+                ApexEvent::ContractExceeded {
+                    contract_id: contract.id.to_string(),
+                    limit_type: "time".to_string(),
+                    used: elapsed as f64,
+                    limit: limits.time_limit_seconds as f64,
+                }
+                .log();
+                persist_contract_outcome(db.clone(), contract_inserted, &contract);
+                // Keep a worker from picking up a task nobody waits for
+                if let Err(e) = runner.withdraw(&payload.task_id).await {
+                    tracing::warn!(task_id = %task_id, error = %e, "Failed to withdraw timed out task");
+                }
+                let error = TaskError::new(
+                    crate::error::ErrorCode::TimeLimitExceeded,
+                    format!("Contract time budget of {}s exceeded", limits.time_limit_seconds),
+                )
+                .with_agent(agent.id.0)
+                .with_model(&model);
+                {
+                    let mut dag = dag_lock.write().await;
+                    if let Some(t) = dag.get_task_mut(task_id) {
+                        t.fail(error.clone());
+                    }
+                }
+                tokio::spawn(async move {
+                    if let Err(e) = db.fail_task(task_id, &error).await {
+                        tracing::warn!(task_id = %task_id, error = %e, "Failed to persist task failure");
+                    }
+                });
+                return Err(ApexError::time_limit_exceeded(elapsed, limits.time_limit_seconds));
+            }
+        };
+        // Out of retries, or a failure that isn't retried
+        let redis_result = match outcome {
+            Ok(result) => result,
+            Err(e) => {
+                contract.cancel();
+                persist_contract_outcome(db.clone(), contract_inserted, &contract);
+                return Err(e);
+            }
+        };
+
+        let elapsed = execution_start.elapsed();
+        model_router.record_latency(&model, elapsed.as_secs_f64());
+        trace::record_worker_span(&span, &redis_result, elapsed);
+
+        // Withdrawn from the queue when its DAG was cancelled
+        if redis_result.status == RedisTaskResult::CANCELLED {
+            contract.cancel();
+            persist_contract_outcome(db.clone(), contract_inserted, &contract);
+            let mut dag = dag_lock.write().await;
+            if let Some(t) = dag.get_task_mut(task_id) {
+                t.status = TaskStatus::Cancelled;
+                t.completed_at = Some(chrono::Utc::now());
+            }
+            return Err(ApexError::task_cancelled(task_id.0));
+        }
 
         // Check if the worker reported a failure
         if redis_result.status == "failed" {
-            circuit_breaker.record_failure();
+            contract.cancel();
+            persist_contract_outcome(db.clone(), contract_inserted, &contract);
             let error_msg = redis_result
                 .error
                 .unwrap_or_else(|| "Agent worker reported failure".to_string());
+            let error = TaskError::new(crate::error::ErrorCode::AgentExecutionFailed, &error_msg)
+                .with_agent(agent.id.0)
+                .with_model(&model);
             // Update task as failed
             {
                 let mut dag = dag_lock.write().await;
                 if let Some(t) = dag.get_task_mut(task_id) {
-                    t.fail(&error_msg);
+                    t.fail(error.clone());
                 }
             }
+            tokio::spawn(async move {
+                if let Err(e) = db.fail_task(task_id, &error).await {
+                    tracing::warn!(task_id = %task_id, error = %e, "Failed to persist task failure");
+                }
+            });
             return Err(ApexError::agent_execution_failed(error_msg));
         }
 
@@ -497,15 +1748,37 @@ impl SwarmOrchestrator {
         let tokens_used = redis_result.tokens_used;
         let cost = redis_result.cost_dollars;
 
+        tracing::debug!(
+            task_id = %task_id,
+            output = %redactor.redact("output", &output.result),
+            data = %redactor.redact_json(&output.data),
+            reasoning = output.reasoning.as_deref().map(|r| redactor.redact("reasoning", r)),
+            "Task output"
+        );
+
         // Update task as completed
         {
             let mut dag = dag_lock.write().await;
             if let Some(t) = dag.get_task_mut(task_id) {
-                t.complete(output, tokens_used, cost);
+                t.complete(output.clone(), tokens_used, cost);
             }
         }
+        task_done.notify_one();
+
+        // Persist the result with the agent that produced it, off the hot path
+        if contract.status == ContractStatus::Active {
+            contract.complete();
+        }
+        persist_contract_outcome(db.clone(), contract_inserted, &contract);
+        let agent_id = agent.id.0;
+        tokio::spawn(async move {
+            if let Err(e) = db.complete_task(task_id, &output, tokens_used, cost, Some(agent_id)).await {
+                tracing::warn!(task_id = %task_id, error = %e, "Failed to persist task completion");
+            }
+        });
 
-        circuit_breaker.record_success();
+        circuit_breakers.record_success(&model);
+        agent_circuit_breakers.record_success(&agent_key);
 
         tracing::info!(
             task_id = %task_id,
@@ -514,6 +1787,7 @@ impl SwarmOrchestrator {
             tokens = tokens_used,
             cost = cost,
             duration_ms = elapsed.as_millis(),
+            worker_duration_ms = redis_result.duration_ms,
             "Task completed"
         );
 
@@ -531,10 +1805,13 @@ impl SwarmOrchestrator {
     pub fn stats(&self) -> OrchestratorStats {
         OrchestratorStats {
             active_dags: self.active_dags.len(),
+            max_dags: self.config.max_concurrent_dags,
             registered_agents: self.agents.len(),
-            active_contracts: self.contracts.len(),
+            busy_agents: self.agents.iter().filter(|a| a.current_load() > 0).count(),
+            active_contracts: self.agents.iter().map(|a| a.current_load() as usize).sum(),
             available_workers: self.worker_semaphore.available_permits(),
             max_workers: self.config.max_concurrent_agents,
+            circuits: self.circuit_breakers.all_metrics(),
         }
     }
 }
@@ -546,6 +1823,9 @@ pub struct DagExecutionResult {
     pub status: DagExecutionStatus,
     pub tasks_completed: usize,
     pub tasks_failed: usize,
+    pub tasks_cancelled: usize,
+    /// Resolved failure threshold the DAG ran under, if any
+    pub max_failed_tasks: Option<usize>,
     pub total_tokens: u64,
     pub total_cost: f64,
     pub duration_ms: u64,
@@ -560,6 +1840,17 @@ pub enum DagExecutionStatus {
     Cancelled,
 }
 
+impl DagExecutionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DagExecutionStatus::Completed => "completed",
+            DagExecutionStatus::PartialFailure => "partial_failure",
+            DagExecutionStatus::Failed => "failed",
+            DagExecutionStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
 /// Result of task execution.
 #[derive(Debug, Clone)]
 pub struct TaskExecutionResult {
@@ -571,17 +1862,1346 @@ pub struct TaskExecutionResult {
     pub duration_ms: u64,
 }
 
+/// What [`SwarmOrchestrator::execute_dag_stream`] yields.
+#[derive(Debug)]
+pub enum DagStreamItem {
+    /// A task finished successfully
+    TaskCompleted(TaskExecutionResult),
+    /// A task failed
+    TaskFailed { task_id: TaskId, error: String },
+    /// The DAG finished (or couldn't run); always the last item
+    Finished(Result<DagExecutionResult>),
+}
+
+/// A stuck task put back on the worker queue.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequeuedTask {
+    pub task_id: Uuid,
+    pub dag_id: Uuid,
+    /// Retries used, including this one
+    pub retry_count: u32,
+    /// How long the task had been running
+    pub stuck_secs: u64,
+}
+
 /// Orchestrator statistics.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct OrchestratorStats {
     pub active_dags: usize,
+    /// `max_concurrent_dags` (0 = unlimited)
+    pub max_dags: usize,
     pub registered_agents: usize,
+    /// Agents with at least one task in flight
+    pub busy_agents: usize,
+    /// Contracts of the tasks in flight, one per task
     pub active_contracts: usize,
     pub available_workers: usize,
     pub max_workers: usize,
+    /// Circuit of every model tasks have been routed to; `agent_id` is the
+    /// model name
+    pub circuits: Vec<AgentCircuitMetrics>,
+}
+
+impl OrchestratorStats {
+    /// Workers in use, as a fraction of `max_workers` (0.0 - 1.0).
+    pub fn worker_utilization(&self) -> f64 {
+        if self.max_workers == 0 {
+            return 0.0;
+        }
+        self.max_workers.saturating_sub(self.available_workers) as f64 / self.max_workers as f64
+    }
+}
+
+/// What the tasks of one DAG execution share: the DAG's own state and the
+/// orchestrator's handles and settings.
+struct TaskContext {
+    dag_id: Uuid,
+    dag_lock: Arc<RwLock<TaskDAG>>,
+    dag_budget: Option<Arc<DagBudget>>,
+    /// Notified when a task of the DAG finishes
+    task_done: Arc<Notify>,
+    db: Arc<Database>,
+    runner: Arc<dyn TaskRunner>,
+    model_router: Arc<ModelRouter>,
+    agents: Arc<DashMap<AgentId, Arc<Agent>>>,
+    agent_released: Arc<Notify>,
+    cnp: Option<Arc<CnpManager>>,
+    circuit_breakers: Arc<AgentCircuitBreakerRegistry>,
+    agent_circuit_breakers: Arc<AgentCircuitBreakerRegistry>,
+    events: broadcast::Sender<ExecutionEvent>,
+    default_limits: ResourceLimits,
+    max_task_limits: ResourceLimits,
+    retry_delay: Duration,
+    context_limit: ContextLimit,
+}
+
+/// Take an attempt that timed out or hit a Redis error back from the queue
+/// before it is published again, so two workers don't run the same task.
+async fn withdraw_attempt(runner: &dyn TaskRunner, task_id: &str) {
+    match runner.withdraw(task_id).await {
+        Ok(true) => tracing::debug!(task_id = %task_id, "Withdrew queued attempt before retrying"),
+        Ok(false) => tracing::debug!(task_id = %task_id, "No queued attempt to withdraw; a worker may still finish it"),
+        Err(e) => tracing::warn!(task_id = %task_id, error = %e, "Failed to withdraw task before retrying"),
+    }
+}
+
+/// Record `contract`'s final usage and status once its insert has landed.
+fn persist_contract_outcome(db: Arc<Database>, inserted: JoinHandle<()>, contract: &AgentContract) {
+    let (contract_id, usage, status) = (contract.id, contract.usage.clone(), contract.status.clone());
+    tokio::spawn(async move {
+        let _ = inserted.await;
+        if let Err(e) = db.update_contract_usage(contract_id, &usage).await {
+            tracing::warn!(contract_id = %contract_id, error = %e, "Failed to persist contract usage");
+        }
+        if let Err(e) = db.update_contract_status(contract_id, &status).await {
+            tracing::warn!(contract_id = %contract_id, error = %e, "Failed to persist contract status");
+        }
+    });
+}
+
+/// Delay before retry `attempt` (0-based): `base` doubled each attempt.
+fn backoff(base: Duration, attempt: u32) -> Duration {
+    base.saturating_mul(2u32.saturating_pow(attempt))
 }
 
 #[cfg(test)]
 mod tests {
-    // Tests would go here
+    use super::*;
+
+    #[test]
+    fn test_worker_utilization() {
+        let stats = OrchestratorStats {
+            active_dags: 1,
+            max_dags: 100,
+            registered_agents: 2,
+            busy_agents: 1,
+            active_contracts: 0,
+            available_workers: 75,
+            max_workers: 100,
+            circuits: vec![],
+        };
+        assert!((stats.worker_utilization() - 0.25).abs() < 1e-9);
+        assert_eq!(OrchestratorStats { max_workers: 0, available_workers: 0, ..stats }.worker_utilization(), 0.0);
+    }
+
+    #[test]
+    fn test_failure_threshold_limit() {
+        assert_eq!(FailureThreshold::Count(3).limit(100), 3);
+        assert_eq!(FailureThreshold::Fraction(0.25).limit(10), 2);
+        assert_eq!(FailureThreshold::Fraction(0.0).limit(10), 0);
+        assert_eq!(FailureThreshold::Fraction(1.5).limit(10), 10);
+    }
+
+    #[test]
+    fn test_failure_threshold_deserialize() {
+        let count: FailureThreshold = serde_json::from_str("2").unwrap();
+        assert_eq!(count, FailureThreshold::Count(2));
+        let fraction: FailureThreshold = serde_json::from_str("0.1").unwrap();
+        assert_eq!(fraction, FailureThreshold::Fraction(0.1));
+    }
+
+    /// Keeps the ids and owners of stored DAGs, or fails every insert.
+    #[derive(Default)]
+    struct MemoryDagStore {
+        stored: std::sync::Mutex<Vec<(Uuid, Option<Uuid>)>>,
+        added_tasks: std::sync::Mutex<Vec<(Uuid, String)>>,
+        unavailable: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl DagStore for MemoryDagStore {
+        async fn insert_dag(&self, dag: &TaskDAG, organization_id: Option<Uuid>) -> Result<()> {
+            if self.unavailable {
+                return Err(ApexError::new(crate::error::ErrorCode::DatabaseConnectionFailed, "database down"));
+            }
+            self.stored.lock().unwrap().push((dag.id(), organization_id));
+            Ok(())
+        }
+
+        async fn insert_tasks(&self, dag_id: Uuid, tasks: &[Task]) -> Result<()> {
+            let mut added = self.added_tasks.lock().unwrap();
+            added.extend(tasks.iter().map(|task| (dag_id, task.name.clone())));
+            Ok(())
+        }
+    }
+
+    async fn orchestrator_with(runner: Arc<dyn TaskRunner>) -> SwarmOrchestrator {
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let redis_client = redis::Client::open("redis://127.0.0.1/").unwrap();
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig { retry_delay_ms: 1, ..Default::default() },
+            db,
+            redis_client,
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_runner(runner)
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+        orchestrator.register_agent(Agent::new("worker", "gpt-4o-mini"));
+        orchestrator
+    }
+
+    fn chain(names: &[&str]) -> TaskDAG {
+        let mut dag = TaskDAG::new("chain");
+        let mut previous = None;
+        for name in names {
+            let input = crate::dag::TaskInput {
+                instruction: format!("do {}", name),
+                ..Default::default()
+            };
+            let id = dag.add_task(crate::dag::Task::new(*name, input)).unwrap();
+            if let Some(previous) = previous {
+                dag.add_dependency(previous, id).unwrap();
+            }
+            previous = Some(id);
+        }
+        dag
+    }
+
+    #[tokio::test]
+    async fn test_scratchpad_shared_per_dag() {
+        let orchestrator = orchestrator_with(Arc::new(MockTaskRunner::echo()))
+            .await
+            .with_scratchpad_store(Arc::new(MemoryScratchpadStore::new()));
+        let dag_id = Uuid::new_v4();
+
+        let (a, b) = (orchestrator.scratchpad(dag_id), orchestrator.scratchpad(dag_id));
+        assert_ne!(a.node_id(), b.node_id());
+        let (sa, sb) = (a.set("sources"), b.set("sources"));
+        let (ra, rb) = tokio::join!(sa.add("arxiv"), sb.add("wiki"));
+        ra.unwrap();
+        rb.unwrap();
+        assert_eq!(b.set("sources").members().await.unwrap().len(), 2);
+
+        let other = orchestrator.scratchpad(Uuid::new_v4());
+        assert!(other.set("sources").members().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_execute_dag_with_mock_runner() {
+        let runner = Arc::new(MockTaskRunner::new(|_| Ok(RedisTaskResult::completed("ok", 50, 0.002))));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+
+        let dag_id = orchestrator.submit_dag(chain(&["a", "b"]), None, None, None).await.unwrap().dag_id();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+
+        assert_eq!(result.status, DagExecutionStatus::Completed);
+        assert_eq!(result.tasks_completed, 2);
+        assert_eq!(result.total_tokens, 100);
+
+        let instructions: Vec<_> = runner
+            .calls()
+            .iter()
+            .map(|p| p.input["instruction"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(instructions, vec!["do a", "do b"]);
+    }
+
+    /// Keeps finished spans for inspection.
+    #[derive(Debug, Clone, Default)]
+    struct CollectSpans(Arc<std::sync::Mutex<Vec<opentelemetry_sdk::export::trace::SpanData>>>);
+
+    impl opentelemetry_sdk::export::trace::SpanExporter for CollectSpans {
+        fn export(
+            &mut self,
+            batch: Vec<opentelemetry_sdk::export::trace::SpanData>,
+        ) -> futures::future::BoxFuture<'static, opentelemetry_sdk::export::trace::ExportResult> {
+            self.0.lock().unwrap().extend(batch);
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_dag_stream_reports_each_task() {
+        use futures::StreamExt;
+
+        let runner = Arc::new(MockTaskRunner::new(|payload| {
+            if payload.input["instruction"] == "do b" {
+                Ok(RedisTaskResult::failed("boom"))
+            } else {
+                Ok(RedisTaskResult::completed("ok", 10, 0.0))
+            }
+        }));
+        let orchestrator = Arc::new(orchestrator_with(runner).await);
+        let mut dag = chain(&["a", "b"]);
+        dag.add_task(crate::dag::Task::new("c", crate::dag::TaskInput {
+            instruction: "do c".to_string(),
+            ..Default::default()
+        })).unwrap();
+        let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+
+        let items: Vec<_> = orchestrator.execute_dag_stream(dag_id).collect().await;
+        assert_eq!(items.len(), 4);
+        let completed = items.iter().filter(|i| matches!(i, DagStreamItem::TaskCompleted(_))).count();
+        assert_eq!(completed, 2);
+        let failed: Vec<_> = items
+            .iter()
+            .filter_map(|i| match i {
+                DagStreamItem::TaskFailed { error, .. } => Some(error),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].contains("boom"));
+        let DagStreamItem::Finished(Ok(result)) = &items[3] else {
+            panic!("last item should be the DAG result: {:?}", items[3]);
+        };
+        assert_eq!((result.tasks_completed, result.tasks_failed), (2, 1));
+    }
+
+    #[tokio::test]
+    async fn test_slow_stream_consumer_does_not_stall_execution() {
+        use futures::StreamExt;
+
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig { stream_buffer: 1, ..Default::default() },
+            db,
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_runner(Arc::new(MockTaskRunner::echo()))
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+        orchestrator.register_agent(Agent::new("worker", "gpt-4o-mini"));
+        let orchestrator = Arc::new(orchestrator);
+        let dag_id = orchestrator.submit_dag(chain(&["a", "b", "c"]), None, None, None).await.unwrap().dag_id();
+
+        // Nobody reads until the DAG is done; updates past the buffer are dropped
+        let stream = orchestrator.execute_dag_stream(dag_id);
+        while orchestrator.stats().active_dags > 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let items: Vec<_> = stream.collect().await;
+        assert_eq!(items.len(), 2);
+        assert!(matches!(&items[0], DagStreamItem::TaskCompleted(t) if t.tokens_used == 0));
+        assert!(matches!(&items[1], DagStreamItem::Finished(Ok(result)) if result.tasks_completed == 3));
+    }
+
+    #[tokio::test]
+    async fn test_trace_continues_through_worker() {
+        use opentelemetry::trace::{Span as _, Tracer as _, TracerProvider as _};
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let spans = CollectSpans::default();
+        let provider = opentelemetry_sdk::trace::TracerProvider::builder()
+            .with_simple_exporter(spans.clone())
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("orchestrator")));
+        let _default = tracing::subscriber::set_default(subscriber);
+
+        // A worker that joins the trace it is handed and reports its span
+        let worker = provider.tracer("worker");
+        let runner = Arc::new(MockTaskRunner::new(move |payload| {
+            let parent = payload.trace_context.as_ref().and_then(|t| t.parent_context()).unwrap();
+            let mut span = worker.start_with_context("worker_task", &parent);
+            let sc = span.span_context().clone();
+            span.end();
+            let mut result = RedisTaskResult::completed("ok", 1, 0.0);
+            result.duration_ms = Some(0);
+            result.trace_id = Some(sc.trace_id().to_string());
+            result.span_id = Some(sc.span_id().to_string());
+            Ok(result)
+        }));
+        let orchestrator = orchestrator_with(runner).await;
+        let dag_id = orchestrator.submit_dag(chain(&["a"]), None, None, None).await.unwrap().dag_id();
+        orchestrator.execute_dag(dag_id).await.unwrap();
+        for result in provider.force_flush() {
+            result.unwrap();
+        }
+
+        let spans = spans.0.lock().unwrap();
+        let named = |name: &str| spans.iter().find(|s| s.name == name).unwrap_or_else(|| panic!("no span {}", name));
+        let (task, worker) = (named("execute_task"), named("worker_task"));
+        assert_eq!(worker.span_context.trace_id(), task.span_context.trace_id());
+        assert_eq!(worker.parent_span_id, task.span_context.span_id());
+        assert_eq!(task.parent_span_id, named("execute_dag").span_context.span_id());
+
+        // Back on the orchestrator: linked to the worker, with its timing
+        assert!(task.links.iter().any(|link| link.span_context.span_id() == worker.span_context.span_id()));
+        assert!(task.attributes.iter().any(|kv| kv.key.as_str() == "worker_duration_ms"));
+    }
+
+    #[tokio::test]
+    async fn test_input_bindings_resolved_at_dispatch() {
+        let runner = Arc::new(MockTaskRunner::new(|payload| {
+            let mut result = RedisTaskResult::completed("ok", 10, 0.0);
+            result.data = Some(serde_json::json!({ "summary": format!("notes on {}", payload.input["instruction"]) }));
+            Ok(result)
+        }));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+
+        let mut dag = chain(&["research"]);
+        let research = dag.get_ready_tasks()[0];
+        let input = crate::dag::TaskInput {
+            instruction: "Write up {{research.summary}}".to_string(),
+            ..Default::default()
+        };
+        let write = crate::dag::Task::new("write", input).with_input_binding(
+            "research.summary",
+            crate::dag::InputBinding::new("research", "/data/summary"),
+        );
+        let write = dag.add_task(write).unwrap();
+        dag.add_dependency(research, write).unwrap();
+
+        let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+        assert_eq!(result.status, DagExecutionStatus::Completed);
+
+        let calls = runner.calls();
+        assert_eq!(calls[1].input["instruction"], "Write up notes on \"do research\"");
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_dag() {
+        // Pause the DAG from inside its first task, so the pause lands while
+        // a task is in flight
+        let cell: Arc<std::sync::OnceLock<std::sync::Weak<SwarmOrchestrator>>> = Default::default();
+        let runner = Arc::new(MockTaskRunner::new({
+            let cell = cell.clone();
+            move |payload| {
+                if payload.input["instruction"] == "do a" {
+                    let orchestrator = cell.get().and_then(std::sync::Weak::upgrade).unwrap();
+                    orchestrator.pause_dag(payload.dag_id.parse().unwrap()).unwrap();
+                }
+                Ok(RedisTaskResult::completed("ok", 10, 0.0))
+            }
+        }));
+        let orchestrator = Arc::new(orchestrator_with(runner.clone()).await);
+        cell.set(Arc::downgrade(&orchestrator)).unwrap();
+        let mut events = orchestrator.subscribe();
+
+        let dag_id = orchestrator.submit_dag(chain(&["a", "b", "c"]), None, None, None).await.unwrap().dag_id();
+        let execution = tokio::spawn({
+            let orchestrator = orchestrator.clone();
+            async move { orchestrator.execute_dag(dag_id).await }
+        });
+
+        while !matches!(events.recv().await.unwrap(), ExecutionEvent::DagPaused { .. }) {}
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // The in-flight task finished; nothing else was dispatched and no
+        // worker is held
+        assert!(orchestrator.is_dag_paused(dag_id));
+        assert_eq!(runner.calls().len(), 1);
+        let (_, progress) = orchestrator.active_dag_progress(dag_id).await.unwrap();
+        assert_eq!((progress.stats.completed, progress.stats.running), (1, 0));
+        let stats = orchestrator.stats();
+        assert_eq!(stats.available_workers, stats.max_workers);
+
+        orchestrator.resume_dag(dag_id).unwrap();
+        let result = execution.await.unwrap().unwrap();
+        assert_eq!(result.status, DagExecutionStatus::Completed);
+        assert_eq!(result.tasks_completed, 3);
+        let instructions: Vec<_> = runner.calls().iter().map(|p| p.input["instruction"].clone()).collect();
+        assert_eq!(instructions, ["do a", "do b", "do c"]);
+
+        // Finished DAGs can't be paused
+        assert!(orchestrator.pause_dag(dag_id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failing_model_only_blocks_its_own_tasks() {
+        let router = ModelRouter::new();
+        let flaky = router.select_model("do flaky");
+        let steady_instruction = "Analyze and debug the code";
+        let steady = router.select_model(steady_instruction);
+        assert_ne!(flaky, steady);
+
+        let flaky_model = flaky.clone();
+        let runner = Arc::new(MockTaskRunner::new(move |payload| {
+            if payload.model.as_deref() == Some(flaky_model.as_str()) {
+                Ok(RedisTaskResult::failed("provider error"))
+            } else {
+                Ok(RedisTaskResult::completed("ok", 10, 0.0))
+            }
+        }));
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig { circuit_breaker_threshold: 2, ..Default::default() },
+            db,
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_runner(runner.clone())
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+        orchestrator.register_agent(Agent::new("worker", "gpt-4o-mini"));
+        let mut events = orchestrator.subscribe();
+
+        let run = |instruction: &str| {
+            let mut dag = TaskDAG::new("single");
+            let input = crate::dag::TaskInput { instruction: instruction.to_string(), ..Default::default() };
+            dag.add_task(crate::dag::Task::new("only", input)).unwrap();
+            let orchestrator = &orchestrator;
+            async move {
+                let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+                orchestrator.execute_dag(dag_id).await.unwrap().status
+            }
+        };
+
+        // Two failures open the flaky model's circuit; the third task fails
+        // without reaching the runner
+        for _ in 0..3 {
+            assert_eq!(run("do flaky").await, DagExecutionStatus::PartialFailure);
+        }
+        assert_eq!(runner.calls().len(), 2);
+
+        // Tasks routed to another model still run
+        assert_eq!(run(steady_instruction).await, DagExecutionStatus::Completed);
+        assert_eq!(runner.calls().len(), 3);
+
+        let mut opened = vec![];
+        while let Ok(event) = events.try_recv() {
+            if let ExecutionEvent::CircuitOpened { model, reason } = event {
+                opened.push((model, reason));
+            }
+        }
+        assert_eq!(opened, vec![(flaky.clone(), AgentCircuitOpenReason::ConsecutiveFailures)]);
+
+        let circuits = orchestrator.stats().circuits;
+        let state = |model: &str| circuits.iter().find(|c| c.agent_id == model).map(|c| c.state);
+        assert_eq!(state(&flaky), Some(CircuitState::Open));
+        assert_eq!(state(&steady), Some(CircuitState::Closed));
+    }
+
+    #[tokio::test]
+    async fn test_open_agent_circuit_is_passed_over() {
+        // The bad and good tasks go to different models, so only the agent's
+        // circuit stands between the good task and the agent that failed
+        let good = "Analyze and debug the code";
+        let router = ModelRouter::new();
+        assert_ne!(router.select_model("do bad"), router.select_model(good));
+        let runner = Arc::new(MockTaskRunner::new(|payload| {
+            if payload.input["instruction"] == "do bad" {
+                Err(ApexError::new(crate::error::ErrorCode::AgentTimeout, "worker never answered"))
+            } else {
+                Ok(RedisTaskResult::completed("ok", 10, 0.0))
+            }
+        }));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let worker = *orchestrator.agents.iter().next().unwrap().key();
+        let standby = orchestrator.register_agent(Agent::new("standby", "gpt-4o-mini"));
+        let run = |instruction: &str| {
+            let mut dag = TaskDAG::new("single");
+            let input = crate::dag::TaskInput { instruction: instruction.to_string(), ..Default::default() };
+            dag.add_task(crate::dag::Task::new("only", input)).unwrap();
+            let orchestrator = &orchestrator;
+            async move {
+                let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+                orchestrator.execute_dag(dag_id).await.unwrap().status
+            }
+        };
+        assert!(orchestrator.agent_circuit_metrics(worker).is_none());
+
+        // Attempts the worker never answered, retries included, count
+        // against the agent that ran the task: four, then a fifth opens its
+        // circuit
+        assert_eq!(run("do bad").await, DagExecutionStatus::PartialFailure);
+        assert_eq!(run("do bad").await, DagExecutionStatus::PartialFailure);
+        let failures = |agent| orchestrator.agent_circuit_metrics(agent).map_or(0, |m| m.total_failures);
+        let (failed_on, other) = if failures(worker) > 0 { (worker, standby) } else { (standby, worker) };
+        let metrics = orchestrator.agent_circuit_metrics(failed_on).unwrap();
+        assert_eq!((metrics.total_failures, failures(other)), (5, 0));
+        assert_eq!(metrics.open_reason, Some(AgentCircuitOpenReason::ConsecutiveFailures));
+
+        // Its circuit is open, so the next task goes to the other agent
+        assert_eq!(run(good).await, DagExecutionStatus::Completed);
+        assert_eq!(orchestrator.agent_circuit_metrics(other).unwrap().total_successes, 1);
+        assert_eq!(orchestrator.agent_circuit_metrics(failed_on).unwrap().state, CircuitState::Open);
+
+        // With every agent's circuit open there is no one to run it
+        for _ in 0..5 {
+            orchestrator.agent_circuit_breakers().record_failure(&other.0.to_string());
+        }
+        assert_eq!(run(good).await, DagExecutionStatus::PartialFailure);
+        assert_eq!(runner.calls().len(), 6);
+    }
+
+    #[test]
+    fn test_idempotency_key_validation() {
+        let org = Uuid::new_v4();
+        let key = IdempotencyKey::new("order-42/retry", &TenantScope::Organization(org.to_string().into())).unwrap();
+        assert_eq!(key.as_str(), "order-42/retry");
+        assert_eq!(key.namespace(), org.to_string());
+        assert_eq!(key.organization_id(), Some(org));
+
+        // A tenant without an organization row keeps its own namespace but
+        // references no organization
+        let key = IdempotencyKey::new("order-42/retry", &TenantScope::Organization("acme".into())).unwrap();
+        assert_eq!(key.namespace(), "acme");
+        assert_eq!(key.organization_id(), None);
+        let key = IdempotencyKey::new("order-42/retry", &TenantScope::All).unwrap();
+        assert_eq!(key.namespace(), "");
+        assert_eq!(key.organization_id(), None);
+
+        assert!(IdempotencyKey::new("", &TenantScope::All).is_err());
+        assert!(IdempotencyKey::new("has space", &TenantScope::All).is_err());
+        assert!(IdempotencyKey::new("k".repeat(IdempotencyKey::MAX_LEN + 1), &TenantScope::All).is_err());
+
+        let id = Uuid::new_v4();
+        assert_eq!(DagSubmission::Existing(id).dag_id(), id);
+        assert!(DagSubmission::Existing(id).is_existing());
+        assert!(!DagSubmission::Created(id).is_existing());
+    }
+
+    #[tokio::test]
+    async fn test_submit_dag_rejected_at_capacity() {
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig { max_concurrent_dags: 2, ..Default::default() },
+            db,
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_runner(Arc::new(MockTaskRunner::echo()))
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+        orchestrator.register_agent(Agent::new("worker", "gpt-4o-mini"));
+
+        let first = orchestrator.submit_dag(chain(&["a"]), None, None, None).await.unwrap().dag_id();
+        orchestrator.submit_dag(chain(&["b"]), None, None, None).await.unwrap();
+
+        let err = orchestrator.submit_dag(chain(&["c"]), None, None, None).await.unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::TooManyActiveDags);
+        assert!(err.is_retryable());
+        assert_eq!(err.details().retry_after_secs, Some(5));
+        let stats = orchestrator.stats();
+        assert_eq!((stats.active_dags, stats.max_dags), (2, 2));
+
+        // A finished DAG frees its slot
+        orchestrator.execute_dag(first).await.unwrap();
+        orchestrator.submit_dag(chain(&["c"]), None, None, None).await.unwrap();
+    }
+
+    /// Keeps every dispatched task queued until it is withdrawn.
+    #[derive(Default)]
+    struct QueuedRunner {
+        queued: std::sync::Mutex<std::collections::HashMap<String, tokio::sync::oneshot::Sender<RedisTaskResult>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TaskRunner for QueuedRunner {
+        async fn run(&self, payload: RedisTaskPayload) -> Result<RedisTaskResult> {
+            let (tx, rx) = tokio::sync::oneshot::channel();
+            self.queued.lock().unwrap().insert(payload.task_id, tx);
+            Ok(rx.await.unwrap_or_else(|_| RedisTaskResult::failed("runner dropped")))
+        }
+
+        async fn withdraw(&self, task_id: &str) -> Result<bool> {
+            let queued = self.queued.lock().unwrap().remove(task_id);
+            Ok(queued.is_some_and(|tx| tx.send(RedisTaskResult::cancelled()).is_ok()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cancel_dag_withdraws_queued_tasks() {
+        let runner = Arc::new(QueuedRunner::default());
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig::default(),
+            db,
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_runner(runner.clone())
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+        orchestrator.register_agent(Agent::new("worker", "gpt-4o-mini"));
+        let orchestrator = Arc::new(orchestrator);
+
+        let dag_id = orchestrator.submit_dag(chain(&["a", "b", "c"]), None, None, None).await.unwrap().dag_id();
+        let execution = tokio::spawn({
+            let orchestrator = orchestrator.clone();
+            async move { orchestrator.execute_dag(dag_id).await }
+        });
+        while runner.queued.lock().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // "a" is withdrawn from the queue, "b" and "c" never start
+        assert_eq!(orchestrator.cancel_dag(dag_id).await.unwrap(), 3);
+        let result = execution.await.unwrap().unwrap();
+        assert_eq!(result.status, DagExecutionStatus::Cancelled);
+        assert_eq!((result.tasks_completed, result.tasks_failed, result.tasks_cancelled), (0, 0, 3));
+
+        assert_eq!(orchestrator.stats().active_dags, 0);
+        let err = orchestrator.cancel_dag(dag_id).await.unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::RecordNotFound);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_unexecuted_dag_drops_it() {
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig::default(),
+            db,
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_runner(Arc::new(QueuedRunner::default()))
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+
+        let dag_id = orchestrator.submit_dag(chain(&["a", "b"]), None, None, None).await.unwrap().dag_id();
+        orchestrator.pause_dag(dag_id).unwrap();
+        assert_eq!(orchestrator.cancel_dag(dag_id).await.unwrap(), 2);
+
+        // Nothing was executing it, so cancelling is what retires it
+        assert_eq!(orchestrator.stats().active_dags, 0);
+        assert!(!orchestrator.is_dag_paused(dag_id));
+        assert!(orchestrator.execute_dag(dag_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ready_task_dispatches_while_sibling_runs() {
+        let runner = Arc::new(QueuedRunner::default());
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig::default(),
+            db,
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_runner(runner.clone())
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+        orchestrator.register_agent(Agent::new("worker", "gpt-4o-mini"));
+        let orchestrator = Arc::new(orchestrator);
+
+        let mut dag = TaskDAG::new("uneven");
+        let [a, b, slow] = ["a", "b", "slow"].map(|name| {
+            let input = crate::dag::TaskInput { instruction: format!("do {}", name), ..Default::default() };
+            dag.add_task(crate::dag::Task::new(name, input)).unwrap()
+        });
+        dag.add_dependency(a, b).unwrap();
+        let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+        let execution = tokio::spawn({
+            let orchestrator = orchestrator.clone();
+            async move { orchestrator.execute_dag(dag_id).await }
+        });
+        let queued = |task_id: TaskId| runner.queued.lock().unwrap().contains_key(&task_id.0.to_string());
+        let release = |task_id: TaskId| {
+            let tx = runner.queued.lock().unwrap().remove(&task_id.0.to_string()).unwrap();
+            tx.send(RedisTaskResult::completed("ok", 10, 0.0)).unwrap();
+        };
+        while !(queued(a) && queued(slow)) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // "b" starts as soon as "a" finishes, without waiting for "slow" or
+        // the fallback re-check
+        release(a);
+        tokio::time::timeout(READY_POLL_FALLBACK / 2, async {
+            while !queued(b) {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(queued(slow));
+
+        release(b);
+        release(slow);
+        let result = execution.await.unwrap().unwrap();
+        assert_eq!(result.status, DagExecutionStatus::Completed);
+        assert_eq!((result.tasks_completed, result.total_tokens), (3, 30));
+    }
+
+    #[tokio::test]
+    async fn test_cnp_awards_task_and_records_scores() {
+        let runner = Arc::new(QueuedRunner::default());
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig { enable_cnp: true, ..Default::default() },
+            db,
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_runner(runner.clone())
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+        orchestrator.register_agent(Agent::new("pricey", "claude-opus-4"));
+        let cheap = orchestrator.register_agent(Agent::new("cheap", "gpt-4o-mini"));
+        let orchestrator = Arc::new(orchestrator);
+
+        let dag_id = orchestrator.submit_dag(chain(&["a"]), None, None, None).await.unwrap().dag_id();
+        let execution = tokio::spawn({
+            let orchestrator = orchestrator.clone();
+            async move { orchestrator.execute_dag(dag_id).await }
+        });
+        while runner.queued.lock().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let dag_lock = orchestrator.active_dags.get(&dag_id).unwrap().clone();
+        let task = dag_lock.read().await.nodes().unwrap()[0].task.clone();
+        assert_eq!(task.agent_id, Some(cheap.0));
+        let award: AwardDecision = serde_json::from_value(task.metadata[CNP_AWARD_KEY].clone()).unwrap();
+        assert_eq!(award.winning_bid.bid.agent_id, cheap.0.to_string());
+        assert_eq!(award.total_bids, 2);
+        assert!(award.winning_bid.breakdown.cost_score > award.runner_up.unwrap().breakdown.cost_score);
+
+        let tx = runner.queued.lock().unwrap().remove(&task.id.0.to_string()).unwrap();
+        tx.send(RedisTaskResult::completed("ok", 10, 0.0)).unwrap();
+        assert_eq!(execution.await.unwrap().unwrap().status, DagExecutionStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn test_requeue_stuck_task() {
+        let runner = Arc::new(MockTaskRunner::echo());
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let dag = chain(&["a", "b"]);
+        let (a, b) = (dag.topological_order().unwrap()[0], dag.topological_order().unwrap()[1]);
+        let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+        let dag_lock = orchestrator.active_dags.get(&dag_id).unwrap().clone();
+        let stuck_after = Duration::from_secs(60);
+
+        let start = |minutes_ago: i64| {
+            let dag_lock = dag_lock.clone();
+            async move {
+                let mut dag = dag_lock.write().await;
+                let task = dag.get_task_mut(a).unwrap();
+                task.status = TaskStatus::Running;
+                task.started_at = Some(chrono::Utc::now() - chrono::Duration::minutes(minutes_ago));
+            }
+        };
+
+        // Not running, or not running for long enough
+        let err = orchestrator.requeue_task(b, stuck_after).await.unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::InvalidStateTransition);
+        start(0).await;
+        assert!(orchestrator.requeue_task(a, stuck_after).await.is_err());
+        assert!(orchestrator.requeue_stuck_tasks(stuck_after).await.is_empty());
+        assert!(runner.requeued().is_empty());
+
+        start(10).await;
+        let requeued = orchestrator.requeue_stuck_tasks(stuck_after).await;
+        assert_eq!(requeued.len(), 1);
+        assert_eq!((requeued[0].task_id, requeued[0].dag_id, requeued[0].retry_count), (a.0, dag_id, 1));
+        assert!(requeued[0].stuck_secs >= 600);
+        assert_eq!(runner.requeued(), vec![a.0.to_string()]);
+        {
+            let dag = dag_lock.read().await;
+            let task = dag.get_task(a).unwrap();
+            assert_eq!((task.status.clone(), task.retry_count, task.started_at), (TaskStatus::Ready, 1, None));
+        }
+
+        // Two requeues racing on one stuck task push its payload once
+        start(10).await;
+        let (first, second) = tokio::join!(
+            orchestrator.requeue_task(a, stuck_after),
+            orchestrator.requeue_task(a, stuck_after),
+        );
+        assert!(first.is_ok() != second.is_ok());
+        assert_eq!(runner.requeued().len(), 2);
+        assert_eq!(dag_lock.read().await.get_task(a).unwrap().retry_count, 2);
+
+        // Out of retries
+        dag_lock.write().await.get_task_mut(a).unwrap().retry_count = 3;
+        start(10).await;
+        assert!(orchestrator.requeue_task(a, stuck_after).await.is_err());
+        assert_eq!(runner.requeued().len(), 2);
+
+        let err = orchestrator.requeue_task(TaskId::new(), stuck_after).await.unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::TaskNotFound);
+    }
+
+    /// Answers requeues with `published`, noting whether the DAG was locked.
+    struct LockCheckingRunner {
+        dag: std::sync::OnceLock<Arc<RwLock<TaskDAG>>>,
+        published: bool,
+        dag_locked: std::sync::Mutex<Vec<bool>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TaskRunner for LockCheckingRunner {
+        async fn run(&self, _payload: RedisTaskPayload) -> Result<RedisTaskResult> {
+            Ok(RedisTaskResult::completed("ok", 0, 0.0))
+        }
+
+        async fn requeue(&self, _task_id: &str) -> Result<bool> {
+            let locked = self.dag.get().unwrap().try_write().is_err();
+            self.dag_locked.lock().unwrap().push(locked);
+            Ok(self.published)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_requeue_publishes_outside_the_dag_lock() {
+        for published in [true, false] {
+            let runner = Arc::new(LockCheckingRunner {
+                dag: Default::default(),
+                published,
+                dag_locked: Default::default(),
+            });
+            let orchestrator = orchestrator_with(runner.clone()).await;
+            let dag = chain(&["a"]);
+            let a = dag.topological_order().unwrap()[0];
+            let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+            let dag_lock = orchestrator.active_dags.get(&dag_id).unwrap().clone();
+            runner.dag.set(dag_lock.clone()).unwrap();
+            {
+                let mut dag = dag_lock.write().await;
+                let task = dag.get_task_mut(a).unwrap();
+                task.status = TaskStatus::Running;
+                task.started_at = Some(chrono::Utc::now() - chrono::Duration::minutes(10));
+            }
+
+            let result = orchestrator.requeue_task(a, Duration::from_secs(60)).await;
+            assert_eq!(*runner.dag_locked.lock().unwrap(), vec![false]);
+            assert_eq!(result.is_ok(), published);
+            assert!(orchestrator.requeueing_tasks.is_empty());
+            let expected = if published { (TaskStatus::Ready, 1) } else { (TaskStatus::Running, 0) };
+            let dag = dag_lock.read().await;
+            let task = dag.get_task(a).unwrap();
+            assert_eq!((task.status.clone(), task.retry_count), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_submit_dag_persists_before_accepting() {
+        let store = Arc::new(MemoryDagStore::default());
+        let orchestrator = orchestrator_with(Arc::new(MockTaskRunner::echo())).await.with_dag_store(store.clone());
+        let org = Uuid::new_v4();
+        let dag_id = orchestrator.submit_dag(chain(&["a", "b"]), Some(org), None, None).await.unwrap().dag_id();
+        assert_eq!(*store.stored.lock().unwrap(), vec![(dag_id, Some(org))]);
+
+        // A DAG that can't be stored isn't accepted either
+        let orchestrator = orchestrator.with_dag_store(Arc::new(MemoryDagStore { unavailable: true, ..Default::default() }));
+        let err = orchestrator.submit_dag(chain(&["c"]), None, None, None).await.unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::DatabaseConnectionFailed);
+        assert_eq!(orchestrator.stats().active_dags, 1);
+    }
+
+    #[tokio::test]
+    async fn test_map_instances_are_persisted() {
+        let runner = MockTaskRunner::new(|payload| {
+            let instruction = payload.input["instruction"].as_str().unwrap_or_default();
+            let mut result = RedisTaskResult::completed(instruction, 0, 0.0);
+            if instruction == "list" {
+                result.data = Some(serde_json::json!({ "urls": ["a", "b"] }));
+            }
+            Ok(result)
+        });
+        let store = Arc::new(MemoryDagStore::default());
+        let orchestrator = orchestrator_with(Arc::new(runner)).await.with_dag_store(store.clone());
+
+        let mut dag = TaskDAG::new("fan-out");
+        let list = dag.add_task(crate::dag::Task::new("list", crate::dag::TaskInput {
+            instruction: "list".into(),
+            ..Default::default()
+        })).unwrap();
+        let read = crate::dag::Task::new("read", crate::dag::TaskInput { instruction: "read".into(), ..Default::default() });
+        dag.add_map_task(read, list, crate::dag::MapSpec::new("$.data.urls")).unwrap();
+        let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+        assert_eq!(result.status, DagExecutionStatus::Completed);
+
+        let added = store.added_tasks.lock().unwrap().clone();
+        assert_eq!(added, vec![(dag_id, "read[0]".to_string()), (dag_id, "read[1]".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn test_submit_dag_rejects_oversized_dag() {
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let limits = DagLimits { max_tasks: 10, max_depth: 3, max_fan_out: 10 };
+        let orchestrator = SwarmOrchestrator::new(
+            OrchestratorConfig { dag_limits: limits, ..Default::default() },
+            db,
+            redis::Client::open("redis://127.0.0.1/").unwrap(),
+            Arc::new(Tracer::new("apex-test")),
+        )
+        .await
+        .unwrap()
+        .with_dag_store(Arc::new(MemoryDagStore::default()));
+
+        let err = orchestrator.submit_dag(chain(&["a", "b", "c", "d"]), None, None, None).await.unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::DagValidationFailed);
+        assert!(err.to_string().contains("depth is 4"), "{}", err);
+        assert_eq!(orchestrator.stats().active_dags, 0);
+
+        orchestrator.submit_dag(chain(&["a", "b", "c"]), None, None, None).await.unwrap();
+        assert_eq!(orchestrator.stats().active_dags, 1);
+    }
+
+    /// Takes a few milliseconds per task and records which DAG each came from.
+    struct SlowRunner {
+        dags: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TaskRunner for SlowRunner {
+        async fn run(&self, payload: RedisTaskPayload) -> Result<RedisTaskResult> {
+            self.dags.lock().unwrap().push(payload.dag_id.clone());
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok(RedisTaskResult::completed("ok", 1, 0.0))
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_fair_scheduling_tiny_dag_progresses() {
+        let runner = Arc::new(SlowRunner { dags: Default::default() });
+        let db = Arc::new(Database::connect_lazy("postgres://localhost/apex_test").unwrap());
+        let orchestrator = Arc::new(
+            SwarmOrchestrator::new(
+                OrchestratorConfig { max_concurrent_agents: 2, fair_scheduling: true, ..Default::default() },
+                db,
+                redis::Client::open("redis://127.0.0.1/").unwrap(),
+                Arc::new(Tracer::new("apex-test")),
+            )
+            .await
+            .unwrap()
+            .with_runner(runner.clone())
+            .with_dag_store(Arc::new(MemoryDagStore::default())),
+        );
+        orchestrator.register_agent(Agent::new("worker", "gpt-4o-mini").with_max_load(2));
+
+        // 40 independent tasks, all ready at once
+        let mut huge = TaskDAG::new("huge");
+        for i in 0..40 {
+            let input = crate::dag::TaskInput { instruction: format!("do {}", i), ..Default::default() };
+            huge.add_task(crate::dag::Task::new(format!("t{}", i), input)).unwrap();
+        }
+        let huge_id = orchestrator.submit_dag(huge, None, None, None).await.unwrap().dag_id();
+        let tiny_id = orchestrator.submit_dag(chain(&["only"]), None, None, None).await.unwrap().dag_id();
+
+        let huge_run = tokio::spawn({
+            let orchestrator = orchestrator.clone();
+            async move { orchestrator.execute_dag(huge_id).await }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let tiny = orchestrator.execute_dag(tiny_id).await.unwrap();
+        assert_eq!(tiny.status, DagExecutionStatus::Completed);
+
+        // The tiny DAG finished while most of the huge one was still queued
+        let dispatched = runner.dags.lock().unwrap().clone();
+        let tiny_at = dispatched.iter().position(|d| *d == tiny_id.to_string()).unwrap();
+        assert!(tiny_at < 10, "tiny DAG dispatched at position {}", tiny_at);
+        assert_eq!(huge_run.await.unwrap().unwrap().tasks_completed, 40);
+    }
+
+    #[tokio::test]
+    async fn test_worker_failure_cancels_dependents() {
+        let runner = Arc::new(MockTaskRunner::new(|payload| {
+            Ok(if payload.input["instruction"] == "do a" {
+                RedisTaskResult::failed("model refused")
+            } else {
+                RedisTaskResult::completed("ok", 10, 0.0)
+            })
+        }));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+
+        let dag_id = orchestrator.submit_dag(chain(&["a", "b", "c"]), None, None, None).await.unwrap().dag_id();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+
+        assert_eq!(result.status, DagExecutionStatus::PartialFailure);
+        assert_eq!(result.tasks_failed, 1);
+        assert_eq!(result.tasks_cancelled, 2);
+        // "a" ran once plus its three retries; "b" and "c" never ran
+        assert_eq!(runner.calls().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_failed_task_retries_with_backoff() {
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let runner = Arc::new(MockTaskRunner::new({
+            let attempts = attempts.clone();
+            move |_| {
+                Ok(match attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                    0 => RedisTaskResult::failed("provider hiccup"),
+                    1 => return Err(ApexError::new(crate::error::ErrorCode::AgentTimeout, "no result")),
+                    _ => RedisTaskResult::completed("ok", 10, 0.0),
+                })
+            }
+        }));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let mut events = orchestrator.subscribe();
+
+        let dag_id = orchestrator.submit_dag(chain(&["a"]), None, None, None).await.unwrap().dag_id();
+        let started = std::time::Instant::now();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+
+        assert_eq!(result.status, DagExecutionStatus::Completed);
+        assert_eq!(runner.calls().len(), 3);
+        // 1ms then 2ms of backoff
+        assert!(started.elapsed() >= Duration::from_millis(3));
+        let mut retried = 0;
+        while let Ok(event) = events.try_recv() {
+            if let ExecutionEvent::TaskFailed { will_retry, .. } = event {
+                assert!(will_retry);
+                retried += 1;
+            }
+        }
+        assert_eq!(retried, 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_withdraw_and_charge_failed_attempts() {
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let runner = Arc::new(MockTaskRunner::new({
+            let attempts = attempts.clone();
+            move |_| {
+                Ok(match attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                    0 => return Err(ApexError::new(crate::error::ErrorCode::AgentTimeout, "no result")),
+                    1 => RedisTaskResult { tokens_used: 4_000, ..RedisTaskResult::failed("provider hiccup") },
+                    _ => RedisTaskResult::completed("ok", 1_000, 0.0),
+                })
+            }
+        }));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let budget = ResourceLimits { token_limit: 10_000, ..ResourceLimits::medium() };
+        let dag = chain(&["a", "b"]);
+        let a = dag.nodes().unwrap()[0].task.id;
+
+        let dag_id = orchestrator.submit_dag(dag, None, Some(budget), None).await.unwrap().dag_id();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+        assert_eq!(result.status, DagExecutionStatus::Completed);
+
+        // Only the timed-out attempt is taken back before publishing again
+        assert_eq!(runner.withdrawn(), vec![a.to_string()]);
+        // "b" gets what is left after both of "a"'s attempts that spent anything
+        let token_limits: Vec<_> = runner.calls().iter().map(|p| p.contract.token_limit).collect();
+        assert_eq!(token_limits, vec![10_000, 10_000, 10_000, 5_000]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_attempts_charged_once() {
+        let runner = Arc::new(MockTaskRunner::new(|_| {
+            Ok(RedisTaskResult { tokens_used: 1_000, ..RedisTaskResult::failed("provider hiccup") })
+        }));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let budget = ResourceLimits { token_limit: 10_000, ..ResourceLimits::medium() };
+
+        let dag_id = orchestrator.submit_dag(chain(&["a"]), None, Some(budget), None).await.unwrap().dag_id();
+        let budget = orchestrator.dag_budgets.get(&dag_id).unwrap().clone();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+        assert_eq!(result.tasks_failed, 1);
+
+        // One attempt plus three retries, each charged exactly once
+        assert_eq!(runner.calls().len(), 4);
+        let (limits, remaining) = budget.snapshot();
+        assert_eq!(limits.token_limit - remaining.token_limit, 4_000);
+    }
+
+    #[tokio::test]
+    async fn test_connection_errors_do_not_use_up_retries() {
+        let attempts = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let runner = Arc::new(MockTaskRunner::new({
+            let attempts = attempts.clone();
+            move |_| match attempts.fetch_add(1, std::sync::atomic::Ordering::SeqCst) {
+                0..=2 => Err(ApexError::new(crate::error::ErrorCode::CacheConnectionFailed, "connection refused")),
+                _ => Ok(RedisTaskResult::completed("ok", 10, 0.0)),
+            }
+        }));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let mut dag = chain(&["a"]);
+        let a = dag.nodes().unwrap()[0].task.id;
+        dag.get_task_mut(a).unwrap().max_retries = 0;
+
+        let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+        assert_eq!(result.status, DagExecutionStatus::Completed);
+        assert_eq!(runner.calls().len(), 4);
+
+        // Past the limit the error is the task's
+        let runner = Arc::new(MockTaskRunner::new(|_| {
+            Err(ApexError::new(crate::error::ErrorCode::CacheConnectionFailed, "connection refused"))
+        }));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let dag_id = orchestrator.submit_dag(chain(&["a"]), None, None, None).await.unwrap().dag_id();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+        assert_eq!(result.tasks_failed, 1);
+        assert_eq!(runner.calls().len(), 1 + MAX_CONNECTION_RETRIES as usize);
+    }
+
+    #[test]
+    fn test_backoff_doubles() {
+        let base = Duration::from_millis(100);
+        let delays: Vec<_> = (0..4).map(|attempt| backoff(base, attempt).as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800]);
+        assert_eq!(backoff(Duration::MAX, 1), Duration::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_dag_budget_bounds_tasks_and_aborts() {
+        let runner = Arc::new(MockTaskRunner::new(|_| Ok(RedisTaskResult::completed("ok", 6_000, 0.01))));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let budget = ResourceLimits { token_limit: 10_000, ..ResourceLimits::medium() };
+
+        let dag_id = orchestrator.submit_dag(chain(&["a", "b", "c"]), None, Some(budget), None).await.unwrap().dag_id();
+        let (limits, remaining) = orchestrator.dag_budget(dag_id).await.unwrap();
+        assert_eq!((limits.token_limit, remaining.token_limit), (10_000, 10_000));
+
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+        assert_eq!(result.status, DagExecutionStatus::Failed);
+        assert_eq!((result.tasks_completed, result.tasks_cancelled), (2, 1));
+        assert!(orchestrator.dag_budget(dag_id).await.is_none());
+
+        // The second task only got what the first left over
+        let token_limits: Vec<_> = runner.calls().iter().map(|p| p.contract.token_limit).collect();
+        assert_eq!(token_limits, vec![10_000, 4_000]);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_tasks_share_dag_budget() {
+        let runner = Arc::new(MockTaskRunner::new(|_| Ok(RedisTaskResult::completed("ok", 3_000, 0.01))));
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let mut dag = TaskDAG::new("fan-out");
+        for name in ["a", "b", "c"] {
+            let input = crate::dag::TaskInput { instruction: format!("do {}", name), ..Default::default() };
+            dag.add_task(crate::dag::Task::new(name, input)).unwrap();
+        }
+        let budget = ResourceLimits { token_limit: 10_000, ..ResourceLimits::medium() };
+
+        let dag_id = orchestrator.submit_dag(dag, None, Some(budget), None).await.unwrap().dag_id();
+        orchestrator.execute_dag(dag_id).await.unwrap();
+
+        // However the tasks interleave, they are never promised more than the budget
+        let token_limits: Vec<_> = runner.calls().iter().map(|p| p.contract.token_limit).collect();
+        assert_eq!(token_limits.len(), 3);
+        assert_eq!(token_limits[0], 10_000);
+        assert!(token_limits.iter().skip(1).all(|&limit| limit <= 7_000));
+
+        // A budget that can't be spent against is refused
+        let zero = ResourceLimits { token_limit: 0, ..ResourceLimits::medium() };
+        let err = orchestrator.submit_dag(chain(&["a"]), None, Some(zero), None).await.unwrap_err();
+        assert_eq!(err.code(), crate::error::ErrorCode::ValidationError);
+    }
+
+    #[tokio::test]
+    async fn test_task_limits_override_default() {
+        let runner = Arc::new(MockTaskRunner::echo());
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let mut dag = TaskDAG::new("mixed");
+        for (name, token_limit) in [("summarize", 2_000), ("analyze", 50_000), ("everything", 1_000_000)] {
+            let input = crate::dag::TaskInput { instruction: format!("do {}", name), ..Default::default() };
+            let limits = ResourceLimits { token_limit, ..ResourceLimits::medium() };
+            dag.add_task(crate::dag::Task::new(name, input).with_limits(limits)).unwrap();
+        }
+        dag.add_task(crate::dag::Task::new("plain", crate::dag::TaskInput {
+            instruction: "do plain".into(),
+            ..Default::default()
+        })).unwrap();
+
+        let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+        orchestrator.execute_dag(dag_id).await.unwrap();
+
+        let mut token_limits: Vec<_> = runner
+            .calls()
+            .iter()
+            .map(|p| (p.input["instruction"].as_str().unwrap().to_string(), p.contract.token_limit))
+            .collect();
+        token_limits.sort();
+        let config = OrchestratorConfig::default();
+        assert_eq!(config.default_limits.token_limit, 20_000);
+        // Raising the limit works up to the ceiling, past it gets the ceiling
+        assert_eq!(token_limits, vec![
+            ("do analyze".to_string(), 50_000),
+            ("do everything".to_string(), config.max_task_limits.token_limit),
+            ("do plain".to_string(), config.default_limits.token_limit),
+            ("do summarize".to_string(), 2_000),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn test_task_fails_when_time_budget_runs_out() {
+        let runner = Arc::new(QueuedRunner::default());
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        let mut dag = TaskDAG::new("slow");
+        let input = crate::dag::TaskInput { instruction: "never answers".into(), ..Default::default() };
+        let limits = ResourceLimits { time_limit_seconds: 1, ..ResourceLimits::simple() };
+        dag.add_task(crate::dag::Task::new("slow", input).with_limits(limits)).unwrap();
+        let mut events = orchestrator.subscribe();
+
+        let dag_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+        let started = std::time::Instant::now();
+        let result = tokio::time::timeout(Duration::from_secs(10), orchestrator.execute_dag(dag_id))
+            .await
+            .expect("time budget not enforced")
+            .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(result.tasks_failed, 1);
+        loop {
+            if let ExecutionEvent::TaskFailed { error, will_retry, .. } = events.recv().await.unwrap() {
+                assert!(error.contains("Time limit exceeded"), "{}", error);
+                assert!(!will_retry);
+                break;
+            }
+        }
+        // Withdrawn, so no worker picks it up later
+        assert!(runner.queued.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_busy_agent_wait_bounded_by_task_limit() {
+        let runner = Arc::new(QueuedRunner::default());
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        orchestrator.agents.clear();
+        orchestrator.register_agent(Agent::new("solo", "gpt-4o-mini").with_max_load(1));
+        let orchestrator = Arc::new(orchestrator);
+
+        // Occupy the only agent
+        let long_id = orchestrator.submit_dag(chain(&["long"]), None, None, None).await.unwrap().dag_id();
+        let long_run = tokio::spawn({
+            let orchestrator = orchestrator.clone();
+            async move { orchestrator.execute_dag(long_id).await }
+        });
+        while runner.queued.lock().unwrap().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        // Gives up after its own 1s limit, not the 300s default
+        let mut dag = TaskDAG::new("short");
+        let input = crate::dag::TaskInput { instruction: "do short".into(), ..Default::default() };
+        let limits = ResourceLimits { time_limit_seconds: 1, ..ResourceLimits::simple() };
+        dag.add_task(crate::dag::Task::new("short", input).with_limits(limits)).unwrap();
+        let mut events = orchestrator.subscribe();
+        let short_id = orchestrator.submit_dag(dag, None, None, None).await.unwrap().dag_id();
+        let short = tokio::time::timeout(Duration::from_secs(10), orchestrator.execute_dag(short_id))
+            .await
+            .expect("busy wait not bounded by the task's limit")
+            .unwrap();
+        assert_eq!(short.tasks_failed, 1);
+        loop {
+            if let ExecutionEvent::TaskFailed { error, .. } = events.recv().await.unwrap() {
+                assert!(error.contains("stayed busy for 1s"), "{}", error);
+                break;
+            }
+        }
+
+        let tx = runner.queued.lock().unwrap().drain().map(|(_, tx)| tx).next().unwrap();
+        tx.send(RedisTaskResult::completed("ok", 10, 0.0)).unwrap();
+        assert_eq!(long_run.await.unwrap().unwrap().tasks_completed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_no_agents_fails_fast() {
+        let runner = Arc::new(MockTaskRunner::echo());
+        let orchestrator = orchestrator_with(runner.clone()).await;
+        orchestrator.agents.clear();
+        let mut events = orchestrator.subscribe();
+
+        let dag_id = orchestrator.submit_dag(chain(&["a"]), None, None, None).await.unwrap().dag_id();
+        let result = orchestrator.execute_dag(dag_id).await.unwrap();
+
+        assert_eq!(result.tasks_failed, 1);
+        assert!(runner.calls().is_empty());
+        loop {
+            if let ExecutionEvent::TaskFailed { error, .. } = events.recv().await.unwrap() {
+                assert!(error.contains("No agents are registered"), "{}", error);
+                break;
+            }
+        }
+    }
 }
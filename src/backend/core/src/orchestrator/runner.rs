@@ -0,0 +1,518 @@
+//! Task runners: how a dispatched task reaches a worker and its result comes back.
+//!
+//! The orchestrator decides *what* runs (agent, model, limits, context) and
+//! hands the resulting [`RedisTaskPayload`] to a [`TaskRunner`]. The default
+//! [`RedisTaskRunner`] publishes it to the Python worker queue; the
+//! [`MockTaskRunner`] answers in-process, so orchestration policy can be
+//! exercised without Redis or workers.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use metrics::counter;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+use super::{RedisTaskPayload, RedisTaskResult};
+use crate::error::{ApexError, ErrorCode, Result};
+
+/// Queue the Python workers consume task payloads from.
+pub const PENDING_QUEUE: &str = "apex:tasks:pending";
+
+/// List a worker pushes the result of task `task_id` to.
+fn result_key(task_id: &str) -> String {
+    format!("apex:tasks:result:{}", task_id)
+}
+
+/// Copy of the payload of in-flight task `task_id`, kept for requeueing.
+fn payload_key(task_id: &str) -> String {
+    format!("apex:tasks:payload:{}", task_id)
+}
+
+/// Push a task's payload back onto the pending queue unless its result has
+/// arrived (0) or its payload is gone (-1). A copy still waiting in the queue
+/// is removed first, so the payload is never queued twice.
+const REQUEUE_SCRIPT: &str = r#"
+if redis.call('LLEN', KEYS[1]) > 0 then
+    return 0
+end
+local payload = redis.call('GET', KEYS[2])
+if not payload then
+    return -1
+end
+redis.call('LREM', KEYS[3], 0, payload)
+redis.call('RPUSH', KEYS[3], payload)
+return 1
+"#;
+
+/// Most times result polling reconnects after losing Redis, per task.
+const RESULT_POLL_MAX_RECONNECTS: u32 = 5;
+
+/// First wait before reconnecting; doubles per attempt up to the max.
+const RESULT_POLL_BACKOFF_BASE: Duration = Duration::from_millis(200);
+const RESULT_POLL_BACKOFF_MAX: Duration = Duration::from_secs(5);
+
+/// Which [`TaskRunner`] the server uses, set with `orchestrator.runner`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RunnerKind {
+    /// Python workers behind the Redis queue
+    #[default]
+    Redis,
+    /// Direct provider calls from this process ([`InProcessTaskRunner`](super::InProcessTaskRunner))
+    InProcess,
+}
+
+/// Runs a task payload to completion and returns the worker's result.
+///
+/// A worker-reported failure is a successful `run` returning a result with
+/// `status: "failed"`; `Err` is reserved for dispatch problems (connection
+/// errors, timeouts). A timeout must use [`ErrorCode::AgentTimeout`] so the
+/// orchestrator counts it against the circuit breaker.
+#[async_trait]
+pub trait TaskRunner: Send + Sync {
+    async fn run(&self, payload: RedisTaskPayload) -> Result<RedisTaskResult>;
+
+    /// Hand the payload of in-flight task `task_id` to the workers again,
+    /// for when the worker running it died. The pending `run` picks up the
+    /// new worker's result. Returns `false` if a result is already waiting,
+    /// so there is nothing to rescue.
+    async fn requeue(&self, task_id: &str) -> Result<bool> {
+        Err(ApexError::new(
+            ErrorCode::InvalidStateTransition,
+            format!("Task {} can't be requeued: this runner has no worker queue", task_id),
+        ))
+    }
+
+    /// Take in-flight task `task_id` back before any worker picks it up,
+    /// for when its DAG is cancelled. The pending `run` then returns a
+    /// [`cancelled`](RedisTaskResult::cancelled) result. Returns `false` if
+    /// the task already reached a worker (or never waits in a queue).
+    async fn withdraw(&self, _task_id: &str) -> Result<bool> {
+        Ok(false)
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Redis
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Publishes payloads to [`PENDING_QUEUE`] and blocks on the per-task result list.
+pub struct RedisTaskRunner {
+    client: redis::Client,
+    result_timeout_secs: u64,
+}
+
+impl RedisTaskRunner {
+    pub fn new(client: redis::Client, result_timeout_secs: u64) -> Self {
+        Self { client, result_timeout_secs }
+    }
+
+    async fn connection(&self, purpose: &str) -> Result<redis::aio::MultiplexedConnection> {
+        self.client.get_multiplexed_async_connection().await.map_err(|e| {
+            ApexError::with_internal(
+                ErrorCode::CacheConnectionFailed,
+                format!("Failed to connect to Redis for {}", purpose),
+                e.to_string(),
+            )
+        })
+    }
+}
+
+#[async_trait]
+impl TaskRunner for RedisTaskRunner {
+    async fn run(&self, payload: RedisTaskPayload) -> Result<RedisTaskResult> {
+        let payload_json = serde_json::to_string(&payload)?;
+
+        // Publish task to the pending queue, keeping a copy for requeueing
+        // until the result is due. A retry clears whatever an earlier
+        // attempt left on the result list, such as a withdrawal marker
+        {
+            let _redis_span = tracing::info_span!("redis_publish_task", task_id = %payload.task_id);
+            let _redis_guard = _redis_span.enter();
+
+            let mut conn = self.connection("task publishing").await?;
+            redis::pipe()
+                .atomic()
+                .cmd("DEL")
+                .arg(result_key(&payload.task_id))
+                .ignore()
+                .cmd("SET")
+                .arg(payload_key(&payload.task_id))
+                .arg(&payload_json)
+                .arg("EX")
+                .arg(self.result_timeout_secs.max(1))
+                .ignore()
+                .cmd("RPUSH")
+                .arg(PENDING_QUEUE)
+                .arg(&payload_json)
+                .ignore()
+                .query_async::<_, ()>(&mut conn)
+                .await
+                .map_err(|e| ApexError::with_internal(
+                    ErrorCode::CacheError,
+                    "Failed to publish task to Redis queue",
+                    e.to_string(),
+                ))?;
+        }
+
+        // Wait for the result on the per-task result queue
+        let result_key = result_key(&payload.task_id);
+        let _redis_span = tracing::info_span!("redis_await_result", task_id = %payload.task_id, result_key = %result_key);
+        let _redis_guard = _redis_span.enter();
+
+        match self.await_result(&result_key).await? {
+            Some(value) => serde_json::from_str::<RedisTaskResult>(&value).map_err(|e| {
+                ApexError::with_internal(
+                    ErrorCode::DeserializationError,
+                    "Failed to deserialize task result from Redis",
+                    e.to_string(),
+                )
+            }),
+            // Timeout: no result received within the configured window
+            None => Err(ApexError::with_internal(
+                ErrorCode::AgentTimeout,
+                "Task execution timed out waiting for agent result",
+                format!("No result on {} within {}s", result_key, self.result_timeout_secs),
+            )),
+        }
+    }
+
+    async fn requeue(&self, task_id: &str) -> Result<bool> {
+        let redis_error = |e: redis::RedisError| {
+            ApexError::with_internal(ErrorCode::CacheError, "Failed to requeue task in Redis", e.to_string())
+        };
+        let mut conn = self.connection("task requeue").await?;
+        // One script, so two requeues racing can't both push the payload:
+        // removing any copy still pending before pushing makes it idempotent
+        let requeued: i64 = redis::Script::new(REQUEUE_SCRIPT)
+            .key(result_key(task_id))
+            .key(payload_key(task_id))
+            .key(PENDING_QUEUE)
+            .invoke_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        match requeued {
+            1 => Ok(true),
+            0 => Ok(false),
+            _ => Err(ApexError::new(
+                ErrorCode::InvalidStateTransition,
+                format!("Task {} can't be requeued: nothing is waiting for its result any more", task_id),
+            )),
+        }
+    }
+
+    async fn withdraw(&self, task_id: &str) -> Result<bool> {
+        let redis_error = |e: redis::RedisError| {
+            ApexError::with_internal(ErrorCode::CacheError, "Failed to withdraw task from Redis", e.to_string())
+        };
+        let mut conn = self.connection("task withdrawal").await?;
+        let payload: Option<String> = redis::cmd("GET")
+            .arg(payload_key(task_id))
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        let Some(payload) = payload else {
+            return Ok(false);
+        };
+
+        // LREM is atomic with the workers' pops: once it removes the
+        // payload, no worker can have it
+        let removed: i64 = redis::cmd("LREM")
+            .arg(PENDING_QUEUE)
+            .arg(1)
+            .arg(&payload)
+            .query_async(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        if removed == 0 {
+            return Ok(false);
+        }
+
+        let cancelled = serde_json::to_string(&RedisTaskResult::cancelled())?;
+        redis::pipe()
+            .atomic()
+            .cmd("DEL")
+            .arg(payload_key(task_id))
+            .ignore()
+            .cmd("RPUSH")
+            .arg(result_key(task_id))
+            .arg(cancelled)
+            .ignore()
+            .cmd("EXPIRE")
+            .arg(result_key(task_id))
+            .arg(self.result_timeout_secs.max(1))
+            .ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await
+            .map_err(redis_error)?;
+        Ok(true)
+    }
+}
+
+impl RedisTaskRunner {
+    /// BLPOP `result_key` until a value arrives or the result timeout runs
+    /// out (`None`).
+    ///
+    /// Losing the connection (a Redis restart, say) isn't a failure: the
+    /// result stays queued, so polling reconnects after a jittered backoff,
+    /// up to [`RESULT_POLL_MAX_RECONNECTS`] times and never past the timeout.
+    /// If the timeout runs out while still reconnecting, the connection error
+    /// is returned rather than `None`.
+    async fn await_result(&self, result_key: &str) -> Result<Option<String>> {
+        let deadline = Instant::now() + Duration::from_secs(self.result_timeout_secs);
+        let mut reconnects = 0;
+        let mut lost: Option<redis::RedisError> = None;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return match lost {
+                    Some(error) => Err(result_poll_error(&error)),
+                    None => Ok(None),
+                };
+            }
+
+            let polled = match self.client.get_multiplexed_async_connection().await {
+                Ok(mut conn) => {
+                    // BLPOP blocks until a result is available or the timeout expires
+                    redis::cmd("BLPOP")
+                        .arg(result_key)
+                        .arg(remaining.as_secs().max(1))
+                        .query_async::<_, Option<(String, String)>>(&mut conn)
+                        .await
+                }
+                Err(e) => Err(e),
+            };
+
+            let error = match polled {
+                Ok(value) => return Ok(value.map(|(_key, value)| value)),
+                Err(e) => e,
+            };
+            if !is_connection_lost(&error) || reconnects >= RESULT_POLL_MAX_RECONNECTS {
+                return Err(result_poll_error(&error));
+            }
+
+            reconnects += 1;
+            counter!("apex_redis_result_poll_reconnects_total").increment(1);
+            let wait = reconnect_backoff(reconnects, remaining);
+            tracing::warn!(
+                result_key = %result_key,
+                attempt = reconnects,
+                wait_ms = wait.as_millis() as u64,
+                error = %error,
+                "Lost Redis while waiting for a task result, reconnecting"
+            );
+            tokio::time::sleep(wait).await;
+            lost = Some(error);
+        }
+    }
+}
+
+fn result_poll_error(error: &redis::RedisError) -> ApexError {
+    ApexError::with_internal(ErrorCode::CacheError, "Failed to read task result from Redis", error.to_string())
+}
+
+/// Whether `error` means the connection went away, as opposed to Redis
+/// rejecting the command.
+fn is_connection_lost(error: &redis::RedisError) -> bool {
+    error.is_io_error() || error.is_connection_dropped() || error.is_connection_refusal()
+}
+
+/// Wait before reconnect `attempt` (1-based): exponential from
+/// [`RESULT_POLL_BACKOFF_BASE`], with full jitter so pollers that lost Redis
+/// together don't reconnect together, and never more than `remaining`.
+fn reconnect_backoff(attempt: u32, remaining: Duration) -> Duration {
+    let ceiling = RESULT_POLL_BACKOFF_BASE
+        .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+        .min(RESULT_POLL_BACKOFF_MAX);
+    ceiling.mul_f64(rand::random::<f64>()).min(remaining)
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// In-process
+// ═══════════════════════════════════════════════════════════════════════════════
+
+type Handler = Box<dyn Fn(&RedisTaskPayload) -> Result<RedisTaskResult> + Send + Sync>;
+
+/// Answers every payload in-process with a caller-supplied handler.
+///
+/// Records the payloads it receives, so tests can assert on what the
+/// orchestrator dispatched.
+pub struct MockTaskRunner {
+    handler: Handler,
+    calls: Mutex<Vec<RedisTaskPayload>>,
+    requeued: Mutex<Vec<String>>,
+    withdrawn: Mutex<Vec<String>>,
+}
+
+impl MockTaskRunner {
+    pub fn new(
+        handler: impl Fn(&RedisTaskPayload) -> Result<RedisTaskResult> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            handler: Box::new(handler),
+            calls: Mutex::new(Vec::new()),
+            requeued: Mutex::new(Vec::new()),
+            withdrawn: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Completes every task, echoing its instruction as the output.
+    pub fn echo() -> Self {
+        Self::new(|payload| {
+            let instruction = payload.input["instruction"].as_str().unwrap_or_default();
+            Ok(RedisTaskResult::completed(instruction, 0, 0.0))
+        })
+    }
+
+    /// Payloads received so far, in dispatch order.
+    pub fn calls(&self) -> Vec<RedisTaskPayload> {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Ids of the tasks requeued so far.
+    pub fn requeued(&self) -> Vec<String> {
+        self.requeued.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Ids of the tasks withdrawn so far.
+    pub fn withdrawn(&self) -> Vec<String> {
+        self.withdrawn.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+}
+
+#[async_trait]
+impl TaskRunner for MockTaskRunner {
+    async fn run(&self, payload: RedisTaskPayload) -> Result<RedisTaskResult> {
+        let result = (self.handler)(&payload);
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).push(payload);
+        result
+    }
+
+    async fn requeue(&self, task_id: &str) -> Result<bool> {
+        self.requeued.lock().unwrap_or_else(|e| e.into_inner()).push(task_id.to_string());
+        Ok(true)
+    }
+
+    async fn withdraw(&self, task_id: &str) -> Result<bool> {
+        self.withdrawn.lock().unwrap_or_else(|e| e.into_inner()).push(task_id.to_string());
+        Ok(false)
+    }
+}
+
+impl RedisTaskResult {
+    /// Status of the result a withdrawn task's `run` returns.
+    pub const CANCELLED: &'static str = "cancelled";
+
+    /// A successful result with `output` and the given usage.
+    pub fn completed(output: impl Into<String>, tokens_used: u64, cost_dollars: f64) -> Self {
+        Self {
+            output: output.into(),
+            tokens_used,
+            cost_dollars,
+            status: "completed".to_string(),
+            data: None,
+            reasoning: None,
+            error: None,
+            duration_ms: None,
+            trace_id: None,
+            span_id: None,
+        }
+    }
+
+    /// A worker-reported failure.
+    pub fn failed(error: impl Into<String>) -> Self {
+        Self {
+            output: String::new(),
+            tokens_used: 0,
+            cost_dollars: 0.0,
+            status: "failed".to_string(),
+            data: None,
+            reasoning: None,
+            error: Some(error.into()),
+            duration_ms: None,
+            trace_id: None,
+            span_id: None,
+        }
+    }
+
+    /// The task was withdrawn before a worker ran it.
+    pub fn cancelled() -> Self {
+        Self {
+            status: Self::CANCELLED.to_string(),
+            ..Self::failed("Task cancelled before a worker picked it up")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::RedisContractPayload;
+
+    fn payload(instruction: &str) -> RedisTaskPayload {
+        RedisTaskPayload {
+            task_id: "t-1".to_string(),
+            dag_id: "d-1".to_string(),
+            input: serde_json::json!({ "instruction": instruction }),
+            contract: RedisContractPayload {
+                token_limit: 100,
+                cost_limit: 1.0,
+                api_call_limit: 10,
+                time_limit_seconds: 60,
+            },
+            trace_context: None,
+            context_truncation: None,
+            model: None,
+        }
+    }
+
+    #[test]
+    fn test_reconnect_backoff_is_bounded() {
+        let plenty = Duration::from_secs(60);
+        for attempt in 1..=RESULT_POLL_MAX_RECONNECTS + 20 {
+            let wait = reconnect_backoff(attempt, plenty);
+            let ceiling = RESULT_POLL_BACKOFF_BASE * 2u32.pow(attempt.min(16) - 1);
+            assert!(wait <= ceiling.min(RESULT_POLL_BACKOFF_MAX), "attempt {}: {:?}", attempt, wait);
+        }
+        // Never past the task's remaining time
+        let remaining = Duration::from_millis(10);
+        assert!(reconnect_backoff(5, remaining) <= remaining);
+    }
+
+    #[test]
+    fn test_connection_errors_are_retried() {
+        let io = |kind| redis::RedisError::from(std::io::Error::new(kind, "redis went away"));
+        assert!(is_connection_lost(&io(std::io::ErrorKind::ConnectionReset)));
+        assert!(is_connection_lost(&io(std::io::ErrorKind::ConnectionRefused)));
+        assert!(is_connection_lost(&io(std::io::ErrorKind::BrokenPipe)));
+
+        let rejected = redis::RedisError::from((redis::ErrorKind::TypeError, "WRONGTYPE"));
+        assert!(!is_connection_lost(&rejected));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_redis_gives_up_within_timeout() {
+        // Nothing listens on port 1; every attempt is refused
+        let runner = RedisTaskRunner::new(redis::Client::open("redis://127.0.0.1:1/").unwrap(), 1);
+        let started = std::time::Instant::now();
+        // Whether the reconnect budget or the timeout runs out first, a lost
+        // connection is reported as such, never as a timeout
+        let err = runner.await_result("apex:tasks:result:t-1").await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::CacheError);
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+
+    #[tokio::test]
+    async fn test_mock_runner_echoes_and_records_calls() {
+        let runner = MockTaskRunner::echo();
+        let result = runner.run(payload("say hi")).await.unwrap();
+        assert_eq!(result.status, "completed");
+        assert_eq!(result.output, "say hi");
+
+        let calls = runner.calls();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].task_id, "t-1");
+    }
+}
@@ -0,0 +1,377 @@
+//! Shared, conflict-free scratchpads for agents working on the same DAG.
+//!
+//! Each DAG gets one [`MergeableState`]: named LWW registers, OR-sets and
+//! G-counters. A [`Scratchpad`] handle turns every write into a small CRDT
+//! delta and merges it into the stored state, so workers writing at the same
+//! time never clobber each other: concurrent set additions all survive, a
+//! counter sums every worker's increments, and a register keeps the latest
+//! write. [`RedisScratchpadStore`] keeps the state where the Python workers
+//! can read it too; [`MemoryScratchpadStore`] keeps it in-process.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::error::{ApexError, ErrorCode, Result};
+use crate::events::{LWWRegister, MergeableState, ORSet};
+
+/// How long an untouched scratchpad is kept in Redis.
+pub const SCRATCHPAD_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Optimistic merge attempts before giving up on a heavily contended scratchpad.
+const MAX_MERGE_ATTEMPTS: usize = 16;
+
+/// Redis key holding a DAG's scratchpad as JSON.
+pub fn scratchpad_key(dag_id: Uuid) -> String {
+    format!("apex:scratchpad:{}", dag_id)
+}
+
+/// Where scratchpad state lives.
+///
+/// `merge` must be atomic with respect to other merges of the same DAG: the
+/// stored state afterwards is the stored state before, merged with `delta`.
+#[async_trait]
+pub trait ScratchpadStore: Send + Sync {
+    /// Current state of a DAG's scratchpad; empty if nothing has been written.
+    async fn load(&self, dag_id: Uuid) -> Result<MergeableState>;
+
+    /// Merge `delta` into a DAG's scratchpad and return the merged state.
+    async fn merge(&self, dag_id: Uuid, delta: &MergeableState) -> Result<MergeableState>;
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Redis
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Stores each scratchpad as JSON under [`scratchpad_key`].
+///
+/// Merges run in a `WATCH`/`MULTI` loop: a write that raced another is simply
+/// merged again on top of it, which CRDT merges make safe to repeat.
+pub struct RedisScratchpadStore {
+    client: redis::Client,
+}
+
+impl RedisScratchpadStore {
+    pub fn new(client: redis::Client) -> Self {
+        Self { client }
+    }
+
+    fn cache_error(message: &str, e: impl ToString) -> ApexError {
+        ApexError::with_internal(ErrorCode::CacheError, message.to_string(), e.to_string())
+    }
+}
+
+fn decode(json: Option<String>) -> Result<MergeableState> {
+    match json {
+        Some(json) => serde_json::from_str(&json).map_err(|e| {
+            ApexError::with_internal(
+                ErrorCode::DeserializationError,
+                "Failed to deserialize scratchpad from Redis",
+                e.to_string(),
+            )
+        }),
+        None => Ok(MergeableState::new()),
+    }
+}
+
+#[async_trait]
+impl ScratchpadStore for RedisScratchpadStore {
+    async fn load(&self, dag_id: Uuid) -> Result<MergeableState> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .map_err(|e| Self::cache_error("Failed to connect to Redis for scratchpad", e))?;
+        let json: Option<String> = redis::cmd("GET")
+            .arg(scratchpad_key(dag_id))
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| Self::cache_error("Failed to read scratchpad from Redis", e))?;
+        decode(json)
+    }
+
+    async fn merge(&self, dag_id: Uuid, delta: &MergeableState) -> Result<MergeableState> {
+        let key = scratchpad_key(dag_id);
+        // WATCH is per connection, so this can't share the multiplexed one
+        let mut conn = self
+            .client
+            .get_async_connection()
+            .await
+            .map_err(|e| Self::cache_error("Failed to connect to Redis for scratchpad", e))?;
+
+        for _ in 0..MAX_MERGE_ATTEMPTS {
+            redis::cmd("WATCH")
+                .arg(&key)
+                .query_async::<_, ()>(&mut conn)
+                .await
+                .map_err(|e| Self::cache_error("Failed to watch scratchpad in Redis", e))?;
+            let json: Option<String> = redis::cmd("GET")
+                .arg(&key)
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| Self::cache_error("Failed to read scratchpad from Redis", e))?;
+
+            let mut state = decode(json)?;
+            state.merge(delta);
+            let merged = serde_json::to_string(&state)?;
+
+            // EXEC replies nil if the key changed since WATCH
+            let committed: Option<()> = redis::pipe()
+                .atomic()
+                .set_ex(&key, merged, SCRATCHPAD_TTL_SECS)
+                .ignore()
+                .query_async(&mut conn)
+                .await
+                .map_err(|e| Self::cache_error("Failed to write scratchpad to Redis", e))?;
+            if committed.is_some() {
+                return Ok(state);
+            }
+        }
+
+        Err(ApexError::with_internal(
+            ErrorCode::CacheError,
+            "Scratchpad is too contended to update",
+            format!("{} changed during {} merge attempts", key, MAX_MERGE_ATTEMPTS),
+        ))
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// In-process
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Keeps scratchpads in memory, for single-process deployments and tests.
+#[derive(Default)]
+pub struct MemoryScratchpadStore {
+    states: Mutex<HashMap<Uuid, MergeableState>>,
+}
+
+impl MemoryScratchpadStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ScratchpadStore for MemoryScratchpadStore {
+    async fn load(&self, dag_id: Uuid) -> Result<MergeableState> {
+        let states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(states.get(&dag_id).cloned().unwrap_or_default())
+    }
+
+    async fn merge(&self, dag_id: Uuid, delta: &MergeableState) -> Result<MergeableState> {
+        let mut states = self.states.lock().unwrap_or_else(|e| e.into_inner());
+        let state = states.entry(dag_id).or_default();
+        state.merge(delta);
+        Ok(state.clone())
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Handles
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// One writer's handle on a DAG's scratchpad.
+///
+/// Every handle has its own CRDT node id, so give each worker its own handle
+/// (from [`SwarmOrchestrator::scratchpad`](super::SwarmOrchestrator::scratchpad))
+/// rather than sharing one between concurrent writers.
+#[derive(Clone)]
+pub struct Scratchpad {
+    dag_id: Uuid,
+    node_id: Uuid,
+    store: Arc<dyn ScratchpadStore>,
+}
+
+impl Scratchpad {
+    pub fn new(dag_id: Uuid, store: Arc<dyn ScratchpadStore>) -> Self {
+        Self {
+            dag_id,
+            node_id: Uuid::new_v4(),
+            store,
+        }
+    }
+
+    pub fn dag_id(&self) -> Uuid {
+        self.dag_id
+    }
+
+    /// This handle's CRDT node id.
+    pub fn node_id(&self) -> Uuid {
+        self.node_id
+    }
+
+    /// The whole scratchpad as currently stored.
+    pub async fn snapshot(&self) -> Result<MergeableState> {
+        self.store.load(self.dag_id).await
+    }
+
+    /// A named last-writer-wins register.
+    pub fn register(&self, name: impl Into<String>) -> Register<'_> {
+        Register { pad: self, name: name.into() }
+    }
+
+    /// A named observed-remove set of strings.
+    pub fn set(&self, name: impl Into<String>) -> Set<'_> {
+        Set { pad: self, name: name.into() }
+    }
+
+    /// A named grow-only counter.
+    pub fn counter(&self, name: impl Into<String>) -> Counter<'_> {
+        Counter { pad: self, name: name.into() }
+    }
+}
+
+/// A named LWW register in a [`Scratchpad`].
+pub struct Register<'a> {
+    pad: &'a Scratchpad,
+    name: String,
+}
+
+impl Register<'_> {
+    /// Current value, if the register has been written.
+    pub async fn get(&self) -> Result<Option<serde_json::Value>> {
+        let state = self.pad.snapshot().await?;
+        Ok(state.get_register(&self.name).cloned())
+    }
+
+    /// Write `value`, timestamped now. A later write from any handle wins.
+    pub async fn set(&self, value: serde_json::Value) -> Result<()> {
+        let mut delta = MergeableState::new();
+        delta
+            .registers
+            .insert(self.name.clone(), LWWRegister::new(value, self.pad.node_id));
+        self.pad.store.merge(self.pad.dag_id, &delta).await?;
+        Ok(())
+    }
+}
+
+/// A named OR-set in a [`Scratchpad`].
+pub struct Set<'a> {
+    pad: &'a Scratchpad,
+    name: String,
+}
+
+impl Set<'_> {
+    /// Live members, in no particular order.
+    pub async fn members(&self) -> Result<Vec<String>> {
+        let state = self.pad.snapshot().await?;
+        Ok(state.get_set(&self.name).into_iter().cloned().collect())
+    }
+
+    pub async fn contains(&self, element: &str) -> Result<bool> {
+        let state = self.pad.snapshot().await?;
+        Ok(state.sets.get(&self.name).is_some_and(|set| set.contains(&element.to_string())))
+    }
+
+    /// Add `element` under a fresh tag.
+    pub async fn add(&self, element: impl Into<String>) -> Result<()> {
+        let mut delta = MergeableState::new();
+        delta.add_to_set(self.name.clone(), element.into());
+        self.pad.store.merge(self.pad.dag_id, &delta).await?;
+        Ok(())
+    }
+
+    /// Remove `element` as currently observed. A concurrent add of the same
+    /// element that this remove didn't see survives it.
+    ///
+    /// Returns `false` if the element wasn't present.
+    pub async fn remove(&self, element: &str) -> Result<bool> {
+        let state = self.pad.snapshot().await?;
+        let mut observed: ORSet<String> = state.sets.get(&self.name).cloned().unwrap_or_default();
+        if !observed.remove(&element.to_string()) {
+            return Ok(false);
+        }
+
+        let mut delta = MergeableState::new();
+        delta.sets.insert(self.name.clone(), observed);
+        self.pad.store.merge(self.pad.dag_id, &delta).await?;
+        Ok(true)
+    }
+}
+
+/// A named G-counter in a [`Scratchpad`].
+pub struct Counter<'a> {
+    pad: &'a Scratchpad,
+    name: String,
+}
+
+impl Counter<'_> {
+    /// Sum of every handle's increments.
+    pub async fn value(&self) -> Result<i64> {
+        let state = self.pad.snapshot().await?;
+        Ok(state.get_counter(&self.name))
+    }
+
+    /// Add `amount` to this handle's share of the counter.
+    pub async fn increment(&self, amount: i64) -> Result<()> {
+        let state = self.pad.snapshot().await?;
+        let mut delta = MergeableState::new();
+        // Counters merge by per-node maximum, so carry this node's running total
+        if let Some(counter) = state.counters.get(&self.name) {
+            delta.counters.insert(self.name.clone(), counter.clone());
+        }
+        delta.increment_counter(self.name.clone(), self.pad.node_id, amount);
+        self.pad.store.merge(self.pad.dag_id, &delta).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pads(n: usize) -> Vec<Scratchpad> {
+        let store: Arc<dyn ScratchpadStore> = Arc::new(MemoryScratchpadStore::new());
+        let dag_id = Uuid::new_v4();
+        (0..n).map(|_| Scratchpad::new(dag_id, store.clone())).collect()
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_set_additions_merge() {
+        let pads = pads(3);
+        let writes = pads.iter().enumerate().map(|(i, pad)| async move {
+            let findings = pad.set("findings");
+            findings.add(format!("finding-{}", i)).await.unwrap();
+            findings.add("shared").await.unwrap();
+        });
+        futures::future::join_all(writes).await;
+
+        let mut members = pads[0].set("findings").members().await.unwrap();
+        members.sort();
+        assert_eq!(members, vec!["finding-0", "finding-1", "finding-2", "shared"]);
+
+        // Removing what one worker saw doesn't undo another's later add
+        assert!(pads[0].set("findings").remove("shared").await.unwrap());
+        pads[1].set("findings").add("shared").await.unwrap();
+        assert!(pads[2].set("findings").contains("shared").await.unwrap());
+        assert!(!pads[2].set("findings").remove("missing").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_counters_and_registers() {
+        let pads = pads(2);
+        pads[0].counter("pages").increment(2).await.unwrap();
+        pads[1].counter("pages").increment(3).await.unwrap();
+        pads[0].counter("pages").increment(1).await.unwrap();
+        assert_eq!(pads[1].counter("pages").value().await.unwrap(), 6);
+
+        assert_eq!(pads[0].register("summary").get().await.unwrap(), None);
+        pads[0].register("summary").set(serde_json::json!("draft")).await.unwrap();
+        pads[1].register("summary").set(serde_json::json!("final")).await.unwrap();
+        assert_eq!(
+            pads[0].register("summary").get().await.unwrap(),
+            Some(serde_json::json!("final"))
+        );
+    }
+
+    #[test]
+    fn test_decode_missing_scratchpad_is_empty() {
+        let state = decode(None).unwrap();
+        assert!(state.sets.is_empty() && state.registers.is_empty());
+        assert!(decode(Some("not json".to_string())).is_err());
+        assert_eq!(scratchpad_key(Uuid::nil()), "apex:scratchpad:00000000-0000-0000-0000-000000000000");
+    }
+}
@@ -0,0 +1,120 @@
+//! Trace propagation across the Redis boundary.
+//!
+//! The payload of a dispatched task carries the `execute_task` span as a W3C
+//! `traceparent`, so the worker starts its task span as a child and one trace
+//! runs orchestrator → worker. The result comes back with the worker's own
+//! trace and span ids and its timing: `execute_task` records the timing
+//! (`worker_duration_ms`, and `dispatch_overhead_ms` for queueing and
+//! transit) and links to the worker's span, which keeps the two connected
+//! even when a worker started a trace of its own.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+use opentelemetry::Context;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use super::{RedisTaskResult, RedisTraceContext};
+use crate::telemetry::TraceContext;
+
+impl RedisTraceContext {
+    /// Context of `span` for a worker to continue. Without an exported span
+    /// (no OpenTelemetry layer installed) the given ids are passed through.
+    pub fn for_span(span: &tracing::Span, trace_id: Option<String>, span_id: Option<String>) -> Self {
+        let cx = span.context();
+        let sc = cx.span().span_context().clone();
+        if !sc.is_valid() {
+            return Self { trace_id, span_id, traceparent: None };
+        }
+        Self {
+            trace_id: Some(sc.trace_id().to_string()),
+            span_id: Some(sc.span_id().to_string()),
+            traceparent: Some(format!(
+                "00-{}-{}-{:02x}",
+                sc.trace_id(),
+                sc.span_id(),
+                sc.trace_flags().to_u8()
+            )),
+        }
+    }
+
+    /// The remote parent a worker's task span belongs under, if the
+    /// `traceparent` is valid.
+    pub fn parent_context(&self) -> Option<Context> {
+        let traceparent = self.traceparent.clone()?;
+        let headers = HashMap::from([("traceparent".to_string(), traceparent)]);
+        let cx = TraceContext::extract_from_headers(&headers);
+        cx.span().span_context().is_valid().then_some(cx)
+    }
+}
+
+impl RedisTaskResult {
+    /// The span the worker ran the task in, if it reported valid ids.
+    pub fn worker_span_context(&self) -> Option<SpanContext> {
+        let trace_id = TraceId::from_hex(self.trace_id.as_deref()?).ok()?;
+        let span_id = SpanId::from_hex(self.span_id.as_deref()?).ok()?;
+        let sc = SpanContext::new(trace_id, span_id, TraceFlags::SAMPLED, true, TraceState::default());
+        sc.is_valid().then_some(sc)
+    }
+}
+
+/// Record the worker's side of a task on its `execute_task` span: the
+/// worker-reported duration, the rest of `elapsed` as dispatch overhead, and
+/// a link to the worker's span.
+pub(crate) fn record_worker_span(span: &tracing::Span, result: &RedisTaskResult, elapsed: Duration) {
+    if let Some(worker_ms) = result.duration_ms {
+        let elapsed_ms = elapsed.as_millis() as u64;
+        span.record("worker_duration_ms", worker_ms);
+        span.record("dispatch_overhead_ms", elapsed_ms.saturating_sub(worker_ms));
+    }
+    if let Some(worker_span) = result.worker_span_context() {
+        span.add_link(worker_span);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::RedisTaskResult;
+
+    #[test]
+    fn test_unexported_span_passes_ids_through() {
+        let span = tracing::info_span!("execute_task");
+        let cx = RedisTraceContext::for_span(&span, Some("t".to_string()), None);
+        assert_eq!(cx.trace_id.as_deref(), Some("t"));
+        assert!(cx.traceparent.is_none());
+        assert!(cx.parent_context().is_none());
+    }
+
+    #[test]
+    fn test_parent_context_from_traceparent() {
+        let cx = RedisTraceContext {
+            trace_id: None,
+            span_id: None,
+            traceparent: Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string()),
+        };
+        let parent = cx.parent_context().unwrap();
+        let sc = parent.span().span_context().clone();
+        assert_eq!(sc.trace_id().to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(sc.span_id().to_string(), "00f067aa0ba902b7");
+        assert!(sc.is_remote());
+
+        let garbled = RedisTraceContext { traceparent: Some("00-zz-01".to_string()), ..cx };
+        assert!(garbled.parent_context().is_none());
+    }
+
+    #[test]
+    fn test_worker_span_context_needs_valid_ids() {
+        let mut result = RedisTaskResult::completed("ok", 0, 0.0);
+        assert!(result.worker_span_context().is_none());
+
+        result.trace_id = Some("4bf92f3577b34da6a3ce929d0e0e4736".to_string());
+        result.span_id = Some("not-hex".to_string());
+        assert!(result.worker_span_context().is_none());
+
+        result.span_id = Some("00f067aa0ba902b7".to_string());
+        let sc = result.worker_span_context().unwrap();
+        assert_eq!(sc.span_id().to_string(), "00f067aa0ba902b7");
+    }
+}
@@ -309,6 +309,20 @@ impl Cursor {
         self.metadata.get(key).map(|s| s.as_str())
     }
 
+    /// Create a cursor for a `(created_at, id)` keyset position.
+    pub fn keyset(created_at: chrono::DateTime<chrono::Utc>, id: uuid::Uuid) -> Self {
+        let mut cursor = Self::with_value("created_at", CursorValue::from_timestamp(created_at));
+        cursor.add_value("id", CursorValue::from_uuid(id));
+        cursor
+    }
+
+    /// Read back the `(created_at, id)` position of a keyset cursor.
+    pub fn keyset_position(&self) -> Option<(chrono::DateTime<chrono::Utc>, uuid::Uuid)> {
+        let created_at = self.get_value("created_at")?.as_timestamp()?;
+        let id = self.get_value("id")?.as_uuid()?;
+        Some((created_at, id))
+    }
+
     /// Check if the cursor is empty (no values).
     pub fn is_empty(&self) -> bool {
         self.values.is_empty()
@@ -812,4 +826,16 @@ mod tests {
         assert_eq!(decoded.get_metadata("shard"), Some("shard-1"));
         assert_eq!(decoded.get_metadata("version"), Some("2"));
     }
+
+    #[test]
+    fn test_keyset_cursor_roundtrip() {
+        let created_at = chrono::Utc::now();
+        let id = uuid::Uuid::new_v4();
+
+        let encoded = Cursor::keyset(created_at, id).encode().unwrap();
+        let decoded = Cursor::decode(&encoded).unwrap();
+        assert_eq!(decoded.keyset_position(), Some((created_at, id)));
+
+        assert_eq!(Cursor::with_value("id", id).keyset_position(), None);
+    }
 }
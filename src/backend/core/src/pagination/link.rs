@@ -0,0 +1,194 @@
+//! `Link` response headers for paginated lists (RFC 8288, formerly 5988).
+//!
+//! The body of a paginated response already carries its cursors; many HTTP
+//! clients instead follow `Link: <...>; rel="next"`. [`PageLinks`] builds
+//! those links from the request URI, replacing only the pagination
+//! parameters so filters and the page size carry over:
+//!
+//! ```rust,ignore
+//! async fn list(OriginalUri(uri): OriginalUri, ...) -> impl IntoResponse {
+//!     let (items, cursor_info) = ...;
+//!     (PageLinks::for_cursors(&uri, &cursor_info, "after"), Json(items))
+//! }
+//! ```
+//!
+//! Links are origin-relative (`/api/v1/agents?after=...`), which clients
+//! resolve against the request URL, so they stay correct behind proxies.
+//! Nested routers see a stripped URI; use `OriginalUri`.
+
+use axum::http::{header, HeaderValue, Uri};
+use axum::response::{IntoResponseParts, ResponseParts};
+
+use super::response::{CursorInfo, PageInfo};
+
+/// The `Link` header of one page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PageLinks {
+    links: Vec<(&'static str, String)>,
+}
+
+impl PageLinks {
+    /// No links.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `first`, `prev`, `next` and `last` for offset pagination with a
+    /// `page` query parameter.
+    pub fn for_pages(uri: &Uri, info: &PageInfo) -> Self {
+        let page = |n: u64| page_url(uri, &[("page", Some(&n.to_string()))]);
+        let mut links = Self::new().with_link("first", page(1));
+        if info.has_previous_page {
+            links = links.with_link("prev", page(info.current_page.saturating_sub(1).max(1)));
+        }
+        if info.has_next_page {
+            links = links.with_link("next", page(info.current_page + 1));
+        }
+        links.with_link("last", page(info.total_pages.max(1)))
+    }
+
+    /// `first` and `next` for forward-only cursor pagination, where `param`
+    /// carries the end cursor of the previous page.
+    ///
+    /// Keyset lists can't jump backwards or to the end, so there is no
+    /// `prev` or `last`.
+    pub fn for_cursors(uri: &Uri, info: &CursorInfo, param: &str) -> Self {
+        let mut links = Self::new().with_link("first", page_url(uri, &[(param, None)]));
+        if let (true, Some(cursor)) = (info.has_next_page, info.end_cursor.as_deref()) {
+            links = links.with_link("next", page_url(uri, &[(param, Some(cursor))]));
+        }
+        links
+    }
+
+    /// Add a link with relation `rel`.
+    pub fn with_link(mut self, rel: &'static str, url: impl Into<String>) -> Self {
+        self.links.push((rel, url.into()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    /// The header value, or `None` without links.
+    pub fn to_header_value(&self) -> Option<HeaderValue> {
+        if self.links.is_empty() {
+            return None;
+        }
+        let value = self
+            .links
+            .iter()
+            .map(|(rel, url)| format!("<{}>; rel=\"{}\"", url, rel))
+            .collect::<Vec<_>>()
+            .join(", ");
+        HeaderValue::from_str(&value).ok()
+    }
+}
+
+impl IntoResponseParts for PageLinks {
+    type Error = std::convert::Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        if let Some(value) = self.to_header_value() {
+            res.headers_mut().insert(header::LINK, value);
+        }
+        Ok(res)
+    }
+}
+
+/// The path and query of `uri` with each named parameter replaced by its
+/// value, or removed when the value is `None`. Other parameters keep their
+/// order and encoding.
+pub fn page_url(uri: &Uri, params: &[(&str, Option<&str>)]) -> String {
+    let replaced = |pair: &&str| {
+        let key = pair.split('=').next().unwrap_or_default();
+        params.iter().any(|(name, _)| *name == key)
+    };
+    let mut query: Vec<String> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty() && !replaced(pair))
+        .map(String::from)
+        .collect();
+    query.extend(
+        params
+            .iter()
+            .filter_map(|(name, value)| value.map(|v| format!("{}={}", name, encode_query_value(v)))),
+    );
+
+    if query.is_empty() {
+        uri.path().to_string()
+    } else {
+        format!("{}?{}", uri.path(), query.join("&"))
+    }
+}
+
+/// Percent-encode everything but RFC 3986 unreserved characters.
+fn encode_query_value(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(s: &str) -> Uri {
+        s.parse().unwrap()
+    }
+
+    fn header(links: &PageLinks) -> String {
+        links.to_header_value().unwrap().to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_page_url_replaces_only_named_params() {
+        let uri = uri("/api/v1/agents?status=idle&after=old&limit=5");
+        assert_eq!(
+            page_url(&uri, &[("after", Some("abc"))]),
+            "/api/v1/agents?status=idle&limit=5&after=abc"
+        );
+        assert_eq!(page_url(&uri, &[("after", None)]), "/api/v1/agents?status=idle&limit=5");
+        assert_eq!(page_url(&Uri::from_static("/tasks?after=x"), &[("after", None)]), "/tasks");
+        assert_eq!(page_url(&Uri::from_static("/tasks"), &[("q", Some("a b&c"))]), "/tasks?q=a%20b%26c");
+    }
+
+    #[test]
+    fn test_offset_links() {
+        let links = PageLinks::for_pages(&uri("/items?page=2&per_page=10"), &PageInfo::new(2, 10, 45));
+        assert_eq!(
+            header(&links),
+            "</items?per_page=10&page=1>; rel=\"first\", \
+             </items?per_page=10&page=1>; rel=\"prev\", \
+             </items?per_page=10&page=3>; rel=\"next\", \
+             </items?per_page=10&page=5>; rel=\"last\""
+        );
+
+        // A single empty page still has first and last
+        let links = PageLinks::for_pages(&uri("/items"), &PageInfo::new(1, 10, 0));
+        assert_eq!(header(&links), "</items?page=1>; rel=\"first\", </items?page=1>; rel=\"last\"");
+    }
+
+    #[test]
+    fn test_cursor_links() {
+        let info = CursorInfo::new(Some("s".into()), Some("e".into()), true, true);
+        let links = PageLinks::for_cursors(&uri("/agents?limit=5&after=s"), &info, "after");
+        assert_eq!(
+            header(&links),
+            "</agents?limit=5>; rel=\"first\", </agents?limit=5&after=e>; rel=\"next\""
+        );
+
+        // Last page: nowhere to go next
+        let links = PageLinks::for_cursors(&uri("/agents"), &info.with_has_next(false), "after");
+        assert_eq!(header(&links), "</agents>; rel=\"first\"");
+        assert_eq!(PageLinks::new().to_header_value(), None);
+    }
+}
@@ -5,6 +5,7 @@
 //! - Offset-based pagination for traditional page-based navigation
 //! - Unified response types for consistent API responses
 //! - Query parameter parsing for HTTP request handling
+//! - `Link` response headers pointing at neighbouring pages
 //!
 //! # Usage
 //!
@@ -27,6 +28,7 @@
 //! ```
 
 mod cursor;
+mod link;
 mod offset;
 mod query;
 mod response;
@@ -34,6 +36,7 @@ mod response;
 pub use cursor::{
     Cursor, CursorBuilder, CursorPagination, CursorValue, SortDirection, SortField,
 };
+pub use link::{page_url, PageLinks};
 pub use offset::{OffsetPagination, OffsetPaginationBuilder, PageMetadata};
 pub use query::{PaginationMode, PaginationQuery, PaginationQueryBuilder};
 pub use response::{CursorInfo, PageInfo, PaginatedResponse, PaginationInfo};
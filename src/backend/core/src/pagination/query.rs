@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use super::cursor::{Cursor, CursorBuilder, CursorPagination, SortDirection, SortField};
 use super::offset::OffsetPagination;
 use crate::error::{ApexError, ErrorCode};
+use crate::validation::{Validate, ValidationErrorKind, ValidationErrors, ValidationResult};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Pagination Mode
@@ -272,6 +273,84 @@ impl PaginationQuery {
         Ok(())
     }
 
+    /// Field-level validation for [`ValidatedQuery`](crate::validation::ValidatedQuery).
+    ///
+    /// Unlike [`validate`](Self::validate), page sizes out of range are
+    /// rejected instead of clamped. When `sortable` is given, every sort
+    /// field must be one of those names.
+    pub fn validate_fields(&self, sortable: Option<&[&str]>) -> ValidationResult<()> {
+        let mut errors = ValidationErrors::new();
+        let max = super::MAX_PAGE_SIZE;
+
+        let sizes = [
+            ("limit", self.limit),
+            ("per_page", self.per_page),
+            ("first", self.first),
+            ("last", self.last),
+        ];
+        for (field, size) in sizes {
+            match size {
+                Some(size) if size == 0 || size > max => errors.add_error(
+                    field,
+                    ValidationErrorKind::Range {
+                        min: "1".to_string(),
+                        max: max.to_string(),
+                        actual: size.to_string(),
+                    },
+                ),
+                _ => {}
+            }
+        }
+        if self.page == Some(0) {
+            errors.add_error(
+                "page",
+                ValidationErrorKind::MinValue { min: "1".to_string(), actual: "0".to_string() },
+            );
+        }
+
+        if self.after.is_some() && self.before.is_some() {
+            errors.add_with_message(
+                "before",
+                ValidationErrorKind::Custom { code: "conflicting_cursors".to_string() },
+                "Cannot specify both 'after' and 'before' cursors",
+            );
+        }
+        if self.first.is_some() && self.last.is_some() {
+            errors.add_with_message(
+                "last",
+                ValidationErrorKind::Custom { code: "conflicting_sizes".to_string() },
+                "Cannot specify both 'first' and 'last'",
+            );
+        }
+        for (field, cursor) in [("after", &self.after), ("before", &self.before)] {
+            if cursor.as_deref().is_some_and(|c| Cursor::decode(c).is_err()) {
+                errors.add_with_message(
+                    field,
+                    ValidationErrorKind::Custom { code: "invalid_cursor".to_string() },
+                    "Must be a cursor returned by a previous page",
+                );
+            }
+        }
+
+        if let Some(sortable) = sortable {
+            let field = if self.sort.is_some() { "sort" } else { "sort_by" };
+            if self.parse_sort_fields().iter().any(|f| !sortable.contains(&f.name.as_str())) {
+                errors.add_error(
+                    field,
+                    ValidationErrorKind::NotInSet {
+                        allowed: sortable.iter().map(|s| s.to_string()).collect(),
+                    },
+                );
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
     /// Check if this is a forward pagination request.
     pub fn is_forward(&self) -> bool {
         self.before.is_none() && self.last.is_none()
@@ -382,6 +461,12 @@ impl PaginationQueryBuilder {
     }
 }
 
+impl Validate for PaginationQuery {
+    fn validate(&self) -> ValidationResult<()> {
+        self.validate_fields(None)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Axum Integration
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -581,4 +666,38 @@ mod tests {
         let fields = query.parse_sort_fields();
         assert_eq!(fields.len(), 2);
     }
+
+    #[test]
+    fn test_validate_fields_rejects_instead_of_clamping() {
+        let query = PaginationQuery::builder().limit(500).build();
+        assert_eq!(query.effective_limit(), super::super::MAX_PAGE_SIZE);
+        let errors = query.validate_fields(None).unwrap_err();
+        assert!(errors.has_errors("limit"));
+
+        let query = PaginationQuery { page: Some(0), first: Some(0), ..Default::default() };
+        let errors = query.validate_fields(None).unwrap_err();
+        assert!(errors.has_errors("page"));
+        assert!(errors.has_errors("first"));
+
+        let query = PaginationQuery::builder().after("not-a-cursor").build();
+        assert!(query.validate_fields(None).unwrap_err().has_errors("after"));
+
+        assert!(PaginationQuery::builder().limit(100).build().validate_fields(None).is_ok());
+    }
+
+    #[test]
+    fn test_validate_fields_checks_sort_fields() {
+        let sortable: &[&str] = &["created_at"];
+        let query = PaginationQuery { sort_by: Some("created_at".into()), ..Default::default() };
+        assert!(query.validate_fields(Some(sortable)).is_ok());
+
+        let query = PaginationQuery { sort_by: Some("password".into()), ..Default::default() };
+        assert!(query.validate_fields(Some(sortable)).unwrap_err().has_errors("sort_by"));
+
+        let query = PaginationQuery { sort: Some("created_at:desc,name".into()), ..Default::default() };
+        assert!(query.validate_fields(Some(sortable)).unwrap_err().has_errors("sort"));
+
+        // Any field is accepted when the caller doesn't restrict them
+        assert!(query.validate_fields(None).is_ok());
+    }
 }
@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::validation::{JsonSchema, ValidationResult};
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Capability
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -122,6 +124,10 @@ pub struct PluginManifest {
     #[serde(default)]
     pub entry_point: Option<String>,
 
+    /// JSON Schema that `PluginInput.parameters` must conform to.
+    #[serde(default)]
+    pub input_schema: Option<serde_json::Value>,
+
     /// Arbitrary metadata.
     #[serde(default)]
     pub metadata: HashMap<String, serde_json::Value>,
@@ -204,8 +210,25 @@ impl PluginManifest {
             ));
         }
 
+        if let Some(schema) = &self.input_schema {
+            JsonSchema::new(schema).map_err(|e| {
+                ManifestError::ValidationError(format!("input_schema is not a valid JSON Schema: {}", e))
+            })?;
+        }
+
         Ok(())
     }
+
+    /// Check plugin `parameters` against the declared `input_schema`.
+    ///
+    /// Violations are keyed `parameters/<json-pointer>`. Manifests without a
+    /// schema accept anything.
+    pub fn validate_parameters(&self, parameters: &serde_json::Value) -> ValidationResult<()> {
+        match &self.input_schema {
+            Some(schema) => crate::validation::validate_json_schema("parameters", parameters, schema),
+            None => Ok(()),
+        }
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -277,6 +300,7 @@ capabilities = ["tool_execution"]
             permissions: vec![],
             min_apex_version: None,
             entry_point: None,
+            input_schema: None,
             metadata: HashMap::new(),
         };
         assert!(manifest.validate().is_err());
@@ -296,6 +320,7 @@ capabilities = ["tool_execution"]
             permissions: vec![],
             min_apex_version: None,
             entry_point: None,
+            input_schema: None,
             metadata: HashMap::new(),
         };
         assert!(manifest.validate().is_err());
@@ -315,8 +340,35 @@ capabilities = ["tool_execution"]
             permissions: vec![],
             min_apex_version: None,
             entry_point: None,
+            input_schema: None,
             metadata: HashMap::new(),
         };
         assert!(manifest.validate().is_ok());
     }
+
+    #[test]
+    fn test_input_schema_validates_parameters() {
+        let json = r#"{
+            "name": "fetch-url",
+            "version": "1.0.0",
+            "input_schema": {
+                "type": "object",
+                "properties": { "url": { "type": "string" } },
+                "required": ["url"]
+            }
+        }"#;
+        let manifest = PluginManifest::from_json(json).unwrap();
+        assert!(manifest.validate().is_ok());
+
+        assert!(manifest.validate_parameters(&serde_json::json!({ "url": "https://example.com" })).is_ok());
+        let errors = manifest.validate_parameters(&serde_json::json!({ "url": 42 })).unwrap_err();
+        assert!(errors.has_errors("parameters/url"));
+    }
+
+    #[test]
+    fn test_validate_rejects_invalid_input_schema() {
+        let json = r#"{ "name": "bad-schema", "version": "1.0.0", "input_schema": { "type": 5 } }"#;
+        let manifest = PluginManifest::from_json(json).unwrap();
+        assert!(manifest.validate().is_err());
+    }
 }
@@ -0,0 +1,322 @@
+//! Remote plugin marketplace.
+//!
+//! Plugins are published as `.tar.gz` archives listed in a JSON index:
+//!
+//! ```json
+//! { "plugins": [
+//!     { "name": "web-search", "version": "1.2.0", "description": "...",
+//!       "archive_url": "https://plugins.example.com/web-search-1.2.0.tar.gz",
+//!       "sha256": "9f86d0..." }
+//! ] }
+//! ```
+//!
+//! Every download goes through a [`SandboxContext`] built from
+//! [`MarketplaceConfig::network_policy`], and nothing is fetched at all unless
+//! [`MarketplaceConfig::allow_remote_install`] is set, so air-gapped
+//! deployments keep the default and stay offline.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::registry::RegistryError;
+use super::sandbox::{SandboxContext, SandboxPolicy, SandboxViolation};
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Configuration
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Remote install and search settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketplaceConfig {
+    /// Allow fetching plugins over the network. Off by default.
+    #[serde(default)]
+    pub allow_remote_install: bool,
+
+    /// URL of the JSON index used by `search`
+    #[serde(default)]
+    pub index_url: Option<String>,
+
+    /// Sandbox applied to marketplace downloads. Must grant `Network`;
+    /// `allowed_hosts` restricts where plugins may come from and
+    /// `max_memory_bytes` caps the archive size.
+    #[serde(default)]
+    pub network_policy: SandboxPolicy,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Index
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// A plugin listed in the marketplace index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Where the `.tar.gz` archive can be downloaded
+    pub archive_url: String,
+    /// Hex-encoded SHA-256 of the archive
+    pub sha256: String,
+}
+
+impl IndexEntry {
+    /// Case-insensitive match on name, description and tags.
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        self.name.to_lowercase().contains(&query)
+            || self.description.to_lowercase().contains(&query)
+            || self.tags.iter().any(|t| t.to_lowercase().contains(&query))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct MarketplaceIndex {
+    #[serde(default)]
+    pub plugins: Vec<IndexEntry>,
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Download
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// HTTP client for marketplace downloads. Redirects aren't followed, since
+/// only the first hop is checked against `allowed_hosts`.
+pub(crate) fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .expect("failed to build the marketplace HTTP client")
+}
+
+impl MarketplaceConfig {
+    /// Fetch `url`, with the request and body size checked against the sandbox.
+    ///
+    /// `client` must not follow redirects (see [`http_client`]); a redirect
+    /// is refused.
+    pub(crate) async fn fetch(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+    ) -> Result<Vec<u8>, RegistryError> {
+        if !self.allow_remote_install {
+            return Err(RegistryError::RemoteInstallDisabled);
+        }
+
+        let parsed = reqwest::Url::parse(url)
+            .map_err(|e| RegistryError::Remote(format!("Invalid URL '{}': {}", url, e)))?;
+        let host = parsed
+            .host_str()
+            .ok_or_else(|| RegistryError::Remote(format!("URL has no host: {}", url)))?;
+
+        let mut sandbox = SandboxContext::new(self.network_policy.clone());
+        sandbox.request_network(host)?;
+
+        let mut response = client
+            .get(parsed.clone())
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| RegistryError::Remote(format!("GET {} failed: {}", url, e)))?;
+        if response.status().is_redirection() {
+            return Err(RegistryError::Remote(format!(
+                "GET {} redirected ({}); redirects are not followed",
+                url,
+                response.status()
+            )));
+        }
+
+        // A declared size over the cap is refused before reading anything;
+        // either way reading stops as soon as the body passes it
+        let limit = self.network_policy.max_memory_bytes;
+        if let Some(length) = response.content_length() {
+            if limit > 0 && length > limit {
+                return Err(SandboxViolation::MemoryLimitExceeded { requested: length, limit, current: 0 }.into());
+            }
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| RegistryError::Remote(format!("GET {} failed: {}", url, e)))?
+        {
+            sandbox.request_memory(chunk.len() as u64)?;
+            body.extend_from_slice(&chunk);
+        }
+
+        sandbox.record_network_bytes(body.len() as u64);
+        Ok(body)
+    }
+}
+
+/// Compare the archive's SHA-256 with the published digest.
+pub(crate) fn verify_checksum(bytes: &[u8], expected: &str) -> Result<(), RegistryError> {
+    let actual = hex::encode(Sha256::digest(bytes));
+    if !actual.eq_ignore_ascii_case(expected.trim()) {
+        return Err(RegistryError::ChecksumMismatch {
+            expected: expected.trim().to_lowercase(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// Most bytes a plugin archive may unpack to.
+const MAX_EXTRACTED_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Most entries (files, directories, links) a plugin archive may hold.
+const MAX_ARCHIVE_ENTRIES: usize = 10_000;
+
+/// Unpack a `.tar.gz` archive into `dest` and return the directory holding
+/// the manifest: `dest` itself or its single top-level directory.
+///
+/// Entries that would escape `dest` (absolute paths, `..`) are skipped by
+/// the tar unpacker. Unpacking stops with an error once the archive passes
+/// [`MAX_EXTRACTED_BYTES`] or [`MAX_ARCHIVE_ENTRIES`], so a small download
+/// can't fill the disk.
+pub(crate) fn extract_archive(bytes: &[u8], dest: &Path) -> Result<PathBuf, RegistryError> {
+    extract_archive_within(bytes, dest, MAX_EXTRACTED_BYTES, MAX_ARCHIVE_ENTRIES)
+}
+
+fn extract_archive_within(
+    bytes: &[u8],
+    dest: &Path,
+    max_bytes: u64,
+    max_entries: usize,
+) -> Result<PathBuf, RegistryError> {
+    let extract_error = |e: std::io::Error| RegistryError::Remote(format!("Failed to extract plugin archive: {}", e));
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+    let (mut unpacked_bytes, mut unpacked_entries) = (0u64, 0usize);
+    for entry in archive.entries().map_err(extract_error)? {
+        let mut entry = entry.map_err(extract_error)?;
+        // The size is checked before anything of the entry is written
+        unpacked_entries += 1;
+        unpacked_bytes = unpacked_bytes.saturating_add(entry.size());
+        if unpacked_entries > max_entries {
+            return Err(RegistryError::Remote(format!(
+                "Plugin archive has more than {} entries",
+                max_entries
+            )));
+        }
+        if unpacked_bytes > max_bytes {
+            return Err(RegistryError::Remote(format!(
+                "Plugin archive unpacks to more than {} bytes",
+                max_bytes
+            )));
+        }
+        entry.unpack_in(dest).map_err(extract_error)?;
+    }
+
+    if dest.join("plugin.toml").exists() || dest.join("plugin.json").exists() {
+        return Ok(dest.to_path_buf());
+    }
+
+    let dirs: Vec<PathBuf> = std::fs::read_dir(dest)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    match dirs.as_slice() {
+        [single] => Ok(single.clone()),
+        _ => Err(RegistryError::Remote(
+            "Plugin archive must contain a manifest at its root or in a single top-level directory"
+                .to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, description: &str, tags: &[&str]) -> IndexEntry {
+        IndexEntry {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            description: description.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            archive_url: format!("https://plugins.example.com/{}.tar.gz", name),
+            sha256: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_index_entry_matches() {
+        let e = entry("web-search", "Search the web", &["http"]);
+        assert!(e.matches("WEB"));
+        assert!(e.matches("search the"));
+        assert!(e.matches("http"));
+        assert!(!e.matches("database"));
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        // sha256("test")
+        let digest = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+        assert!(verify_checksum(b"test", digest).is_ok());
+        assert!(verify_checksum(b"test", &digest.to_uppercase()).is_ok());
+        assert!(matches!(
+            verify_checksum(b"tampered", digest),
+            Err(RegistryError::ChecksumMismatch { .. })
+        ));
+    }
+
+    /// A `.tar.gz` holding a manifest and `files` of `size` bytes each.
+    fn archive(files: usize, size: usize) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast()));
+        let mut append = |path: &str, data: &[u8]| {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, data).unwrap();
+        };
+        append("plugin.toml", b"name = \"bomb\"\n");
+        for i in 0..files {
+            append(&format!("data/{}.bin", i), &vec![0u8; size]);
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_extract_archive_stops_at_its_budget() {
+        let within = tempfile::tempdir().unwrap();
+        assert_eq!(extract_archive_within(&archive(2, 1024), within.path(), 4096, 10).unwrap(), within.path());
+
+        // Compresses to a fraction of what it unpacks to
+        let bomb = archive(1, 64 * 1024);
+        assert!(bomb.len() < 4096);
+        let dest = tempfile::tempdir().unwrap();
+        let err = extract_archive_within(&bomb, dest.path(), 4096, 10).unwrap_err();
+        assert!(err.to_string().contains("more than 4096 bytes"), "{}", err);
+        assert!(!dest.path().join("data/0.bin").exists());
+
+        let dest = tempfile::tempdir().unwrap();
+        let err = extract_archive_within(&archive(20, 1), dest.path(), 4096, 10).unwrap_err();
+        assert!(err.to_string().contains("more than 10 entries"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_requires_flag_and_network_permission() {
+        let client = http_client();
+
+        let disabled = MarketplaceConfig::default();
+        assert!(matches!(
+            disabled.fetch(&client, "https://plugins.example.com/x.tar.gz").await,
+            Err(RegistryError::RemoteInstallDisabled)
+        ));
+
+        let no_network = MarketplaceConfig {
+            allow_remote_install: true,
+            ..MarketplaceConfig::default()
+        };
+        assert!(matches!(
+            no_network.fetch(&client, "https://plugins.example.com/x.tar.gz").await,
+            Err(RegistryError::Sandbox(_))
+        ));
+    }
+}
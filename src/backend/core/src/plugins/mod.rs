@@ -8,6 +8,8 @@
 //!   parsed from `plugin.toml` or `plugin.json` inside each plugin directory.
 //! - **Registry**: Discovery, installation, enabling/disabling, and uninstallation
 //!   of plugins with full lifecycle state management.
+//! - **Marketplace**: Optional remote index search and checksum-verified
+//!   installs from downloaded archives.
 //! - **Sandbox**: Permission and resource enforcement layer that constrains plugin
 //!   execution (network access, filesystem, memory, execution time).
 //! - **Plugin trait**: The interface every plugin must implement to participate in
@@ -25,6 +27,7 @@
 //! ```
 
 pub mod manifest;
+pub mod marketplace;
 pub mod registry;
 pub mod sandbox;
 
@@ -213,8 +216,9 @@ impl PluginLoader {
 // ═══════════════════════════════════════════════════════════════════════════════
 
 pub use manifest::{PluginCapability, PluginDependency, PluginManifest, PluginPermission};
-pub use registry::{PluginRegistry, PluginState, RegisteredPlugin};
-pub use sandbox::{SandboxContext, SandboxPolicy, SandboxViolation};
+pub use marketplace::{IndexEntry, MarketplaceConfig};
+pub use registry::{PluginExecution, PluginRegistry, PluginState, RegisteredPlugin};
+pub use sandbox::{PluginResourceReport, SandboxContext, SandboxPolicy, SandboxViolation};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Tests
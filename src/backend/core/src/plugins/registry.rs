@@ -2,7 +2,12 @@
 //!
 //! The [`PluginRegistry`] scans plugin directories, loads manifests,
 //! validates them, and manages the lifecycle (install, enable, disable,
-//! uninstall) of every registered plugin.
+//! uninstall) of every registered plugin. Calls made through
+//! [`PluginRegistry::execute`] are checked against the manifest's
+//! `input_schema` first and report the resources they consumed.
+//!
+//! With a [`MarketplaceConfig`] attached, plugins can also be searched for
+//! and installed from a remote index.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -14,7 +19,23 @@ use tokio::sync::RwLock;
 use tracing::{info, warn};
 
 use super::manifest::{ManifestError, PluginManifest};
-use super::sandbox::SandboxPolicy;
+use super::marketplace::{self, IndexEntry, MarketplaceConfig, MarketplaceIndex};
+use super::sandbox::{PluginResourceReport, SandboxContext, SandboxPolicy, SandboxViolation};
+use super::{Plugin, PluginError, PluginInput, PluginOutput};
+use crate::observability::metrics::record_tool_latency;
+use crate::validation::JsonSchema;
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Plugin Execution
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Result of [`PluginRegistry::execute`]: the plugin's output plus what it
+/// consumed inside the sandbox, for billing against the task's contract.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginExecution {
+    pub output: PluginOutput,
+    pub resources: PluginResourceReport,
+}
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Plugin State
@@ -84,6 +105,18 @@ pub enum RegistryError {
 
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
+
+    #[error("Remote plugin installation is disabled")]
+    RemoteInstallDisabled,
+
+    #[error("Marketplace error: {0}")]
+    Remote(String),
+
+    #[error("Checksum mismatch: expected {expected}, got {actual}")]
+    ChecksumMismatch { expected: String, actual: String },
+
+    #[error("Sandbox violation: {0}")]
+    Sandbox(#[from] SandboxViolation),
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -96,6 +129,8 @@ pub enum RegistryError {
 #[derive(Debug, Clone)]
 pub struct PluginRegistry {
     inner: Arc<RwLock<RegistryInner>>,
+    marketplace: Arc<MarketplaceConfig>,
+    http: reqwest::Client,
 }
 
 #[derive(Debug)]
@@ -104,6 +139,8 @@ struct RegistryInner {
     plugins: HashMap<String, RegisteredPlugin>,
     /// Base directory where plugins are stored.
     plugins_dir: PathBuf,
+    /// Compiled `input_schema` per plugin, filled on first use.
+    schemas: HashMap<String, Arc<JsonSchema>>,
 }
 
 impl PluginRegistry {
@@ -113,10 +150,19 @@ impl PluginRegistry {
             inner: Arc::new(RwLock::new(RegistryInner {
                 plugins: HashMap::new(),
                 plugins_dir: plugins_dir.into(),
+                schemas: HashMap::new(),
             })),
+            marketplace: Arc::new(MarketplaceConfig::default()),
+            http: marketplace::http_client(),
         }
     }
 
+    /// Enable remote search and installs with the given settings.
+    pub fn with_marketplace(mut self, config: MarketplaceConfig) -> Self {
+        self.marketplace = Arc::new(config);
+        self
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Discovery
     // ─────────────────────────────────────────────────────────────────────────
@@ -309,10 +355,108 @@ impl PluginRegistry {
         }
 
         let removed = inner.plugins.remove(name).unwrap();
+        inner.schemas.remove(name);
         info!(plugin = name, "Plugin uninstalled");
         Ok(removed)
     }
 
+    // ─────────────────────────────────────────────────────────────────────────
+    // Marketplace
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Search the configured marketplace index.
+    pub async fn search(&self, query: &str) -> Result<Vec<IndexEntry>, RegistryError> {
+        let index_url = self
+            .marketplace
+            .index_url
+            .as_deref()
+            .ok_or_else(|| RegistryError::Remote("No marketplace index URL configured".into()))?;
+
+        let body = self.marketplace.fetch(&self.http, index_url).await?;
+        let index: MarketplaceIndex = serde_json::from_slice(&body)
+            .map_err(|e| RegistryError::Remote(format!("Invalid marketplace index: {}", e)))?;
+
+        Ok(index.plugins.into_iter().filter(|e| e.matches(query)).collect())
+    }
+
+    /// Download a plugin archive, verify its SHA-256, unpack it into the
+    /// plugins directory and install it.
+    ///
+    /// Nothing is left behind on failure: the staging directory is always
+    /// removed, and a plugin that fails to install is unregistered and its
+    /// directory deleted.
+    pub async fn install_from_url(
+        &self,
+        url: &str,
+        sha256: &str,
+    ) -> Result<RegisteredPlugin, RegistryError> {
+        let archive = self.marketplace.fetch(&self.http, url).await?;
+        marketplace::verify_checksum(&archive, sha256)?;
+
+        let plugins_dir = self.inner.read().await.plugins_dir.clone();
+        let staging = plugins_dir.join(format!(".staging-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&staging).await?;
+
+        let registered = self.register_archive(&archive, &staging, &plugins_dir).await;
+        let _ = tokio::fs::remove_dir_all(&staging).await;
+        let name = registered?;
+
+        match self.install(&name).await {
+            Ok(plugin) => {
+                info!(plugin = %name, url, "Plugin installed from marketplace");
+                Ok(plugin)
+            }
+            Err(e) => {
+                let removed = {
+                    let mut inner = self.inner.write().await;
+                    inner.schemas.remove(&name);
+                    inner.plugins.remove(&name)
+                };
+                if let Some(plugin) = removed {
+                    let _ = tokio::fs::remove_dir_all(&plugin.path).await;
+                }
+                warn!(plugin = %name, error = %e, "Rolled back marketplace install");
+                Err(e)
+            }
+        }
+    }
+
+    /// Unpack `archive` into `staging`, move the plugin into place and
+    /// register it as Discovered. Returns the plugin name.
+    async fn register_archive(
+        &self,
+        archive: &[u8],
+        staging: &Path,
+        plugins_dir: &Path,
+    ) -> Result<String, RegistryError> {
+        let root = marketplace::extract_archive(archive, staging)?;
+        let manifest = PluginManifest::load_from_dir(&root)?;
+        manifest.validate()?;
+
+        let name = manifest.name.clone();
+        let target = plugins_dir.join(&name);
+
+        let mut inner = self.inner.write().await;
+        if inner.plugins.contains_key(&name) || target.exists() {
+            return Err(RegistryError::AlreadyRegistered(name));
+        }
+        tokio::fs::rename(&root, &target).await?;
+
+        let now = Utc::now();
+        inner.plugins.insert(
+            name.clone(),
+            RegisteredPlugin {
+                manifest,
+                state: PluginState::Discovered,
+                path: target,
+                sandbox_policy: SandboxPolicy::default(),
+                discovered_at: now,
+                updated_at: now,
+            },
+        );
+        Ok(name)
+    }
+
     /// Update the sandbox policy for a plugin.
     pub async fn set_sandbox_policy(
         &self,
@@ -328,6 +472,62 @@ impl PluginRegistry {
         plugin.updated_at = Utc::now();
         Ok(())
     }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Execution
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Check `input.parameters` against the plugin's declared `input_schema`.
+    pub async fn validate_input(&self, name: &str, input: &PluginInput) -> Result<(), PluginError> {
+        let Some(schema) = self.input_schema(name).await? else {
+            return Ok(());
+        };
+
+        schema.validate_field("parameters", &input.parameters).map_err(|errors| {
+            PluginError::ExecutionFailed(format!("Invalid input for plugin '{}': {}", name, errors))
+        })
+    }
+
+    /// Validate `input`, run the plugin and report the resources it used.
+    pub async fn execute(
+        &self,
+        plugin: &dyn Plugin,
+        input: PluginInput,
+        sandbox: &mut SandboxContext,
+    ) -> Result<PluginExecution, PluginError> {
+        self.validate_input(plugin.name(), &input).await?;
+
+        let started = std::time::Instant::now();
+        let output = plugin.execute(input, sandbox).await;
+        record_tool_latency(plugin.name(), started.elapsed().as_secs_f64());
+
+        Ok(PluginExecution {
+            output: output?,
+            resources: sandbox.report(),
+        })
+    }
+
+    /// Compiled input schema for `name`, compiling and caching it on first use.
+    async fn input_schema(&self, name: &str) -> Result<Option<Arc<JsonSchema>>, PluginError> {
+        if let Some(schema) = self.inner.read().await.schemas.get(name) {
+            return Ok(Some(schema.clone()));
+        }
+
+        let mut inner = self.inner.write().await;
+        let plugin = inner
+            .plugins
+            .get(name)
+            .ok_or_else(|| RegistryError::PluginNotFound(name.to_string()))?;
+        let Some(raw) = &plugin.manifest.input_schema else {
+            return Ok(None);
+        };
+
+        let schema = Arc::new(JsonSchema::new(raw).map_err(|e| {
+            ManifestError::ValidationError(format!("input_schema is not a valid JSON Schema: {}", e))
+        })?);
+        inner.schemas.insert(name.to_string(), schema.clone());
+        Ok(Some(schema))
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -337,6 +537,7 @@ impl PluginRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sha2::Digest;
     use tempfile::TempDir;
     use std::fs;
 
@@ -407,6 +608,206 @@ capabilities = ["tool_execution"]
         assert!(registry.enable("test-plugin").await.is_err());
     }
 
+    #[derive(Debug)]
+    struct EchoPlugin;
+
+    #[async_trait::async_trait]
+    impl Plugin for EchoPlugin {
+        fn name(&self) -> &str {
+            "echo-plugin"
+        }
+
+        fn version(&self) -> &str {
+            "1.0.0"
+        }
+
+        fn description(&self) -> &str {
+            "Echoes its parameters"
+        }
+
+        async fn execute(
+            &self,
+            input: PluginInput,
+            _sandbox: &mut SandboxContext,
+        ) -> Result<PluginOutput, PluginError> {
+            Ok(PluginOutput::ok(input.parameters))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_execute_validates_input_schema() {
+        let tmp = TempDir::new().unwrap();
+        let plugin_dir = tmp.path().join("echo-plugin");
+        fs::create_dir_all(&plugin_dir).unwrap();
+        fs::write(
+            plugin_dir.join("plugin.json"),
+            r#"{
+                "name": "echo-plugin",
+                "version": "1.0.0",
+                "input_schema": {
+                    "type": "object",
+                    "properties": { "text": { "type": "string" } },
+                    "required": ["text"]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let registry = PluginRegistry::new(tmp.path());
+        registry.discover().await.unwrap();
+        let mut sandbox = SandboxContext::new(SandboxPolicy::default());
+
+        let ok = PluginInput { action: "echo".into(), parameters: serde_json::json!({ "text": "hi" }) };
+        let execution = registry.execute(&EchoPlugin, ok, &mut sandbox).await.unwrap();
+        assert_eq!(execution.output.data["text"], "hi");
+        assert_eq!(execution.resources.network_requests, 0);
+
+        let bad = PluginInput { action: "echo".into(), parameters: serde_json::json!({ "text": 7 }) };
+        match registry.execute(&EchoPlugin, bad, &mut sandbox).await {
+            Err(PluginError::ExecutionFailed(msg)) => assert!(msg.contains("parameters/text"), "{}", msg),
+            other => panic!("expected ExecutionFailed, got {:?}", other),
+        }
+        assert_eq!(registry.inner.read().await.schemas.len(), 1);
+    }
+
+    /// `.tar.gz` holding `<name>/plugin.json`.
+    fn plugin_archive(name: &str, dependencies: &str) -> Vec<u8> {
+        let manifest = format!(
+            r#"{{ "name": "{}", "version": "1.0.0", "dependencies": [{}] }}"#,
+            name, dependencies
+        );
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+
+        let encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        builder
+            .append_data(&mut header, format!("{}/plugin.json", name), manifest.as_bytes())
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    /// Serve `archives` at `/<file>` and an index at `/index.json` on a local port.
+    async fn serve(archives: Vec<(&'static str, Vec<u8>)>, index: serde_json::Value) -> String {
+        use axum::routing::get;
+
+        let mut app = axum::Router::new().route("/index.json", get(move || async move { axum::Json(index) }));
+        for (file, bytes) in archives {
+            app = app.route(&format!("/{}", file), get(move || async move { bytes }));
+        }
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    fn marketplace(index_url: Option<String>) -> MarketplaceConfig {
+        let mut network_policy = SandboxPolicy::default();
+        network_policy.granted_permissions.insert(crate::plugins::PluginPermission::Network);
+        network_policy.allowed_hosts = vec!["127.0.0.1".to_string()];
+        MarketplaceConfig {
+            allow_remote_install: true,
+            index_url,
+            network_policy,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_install_from_url() {
+        let good = plugin_archive("remote-plugin", "");
+        let good_sha = hex::encode(sha2::Sha256::digest(&good));
+        let base = serve(vec![("remote.tar.gz", good)], serde_json::json!({ "plugins": [] })).await;
+
+        let tmp = TempDir::new().unwrap();
+        let registry = PluginRegistry::new(tmp.path()).with_marketplace(marketplace(None));
+
+        let bad_sha = "0".repeat(64);
+        let err = registry
+            .install_from_url(&format!("{}/remote.tar.gz", base), &bad_sha)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, RegistryError::ChecksumMismatch { .. }));
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 0);
+
+        let plugin = registry
+            .install_from_url(&format!("{}/remote.tar.gz", base), &good_sha)
+            .await
+            .unwrap();
+        assert_eq!(plugin.state, PluginState::Installed);
+        assert!(tmp.path().join("remote-plugin/plugin.json").exists());
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_install_from_url_refuses_redirects_and_oversized_archives() {
+        use axum::{response::Redirect, routing::get};
+
+        let archive = plugin_archive("big-plugin", "");
+        let sha = hex::encode(sha2::Sha256::digest(&archive));
+        let base = serve(vec![("big.tar.gz", archive.clone())], serde_json::json!({ "plugins": [] })).await;
+
+        // Bounces to a host outside `allowed_hosts`
+        let app = axum::Router::new().route("/bounce.tar.gz", get(|| async { Redirect::temporary("http://localhost:1/x.tar.gz") }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let tmp = TempDir::new().unwrap();
+        let mut config = marketplace(None);
+        config.network_policy.max_memory_bytes = archive.len() as u64 - 1;
+        let registry = PluginRegistry::new(tmp.path()).with_marketplace(config);
+
+        let err = registry
+            .install_from_url(&format!("http://{}/bounce.tar.gz", addr), &sha)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, RegistryError::Remote(msg) if msg.contains("redirects are not followed")), "{}", err);
+
+        let err = registry.install_from_url(&format!("{}/big.tar.gz", base), &sha).await.unwrap_err();
+        assert!(matches!(err, RegistryError::Sandbox(SandboxViolation::MemoryLimitExceeded { .. })), "{}", err);
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_install_from_url_rolls_back_on_missing_dependency() {
+        let archive = plugin_archive("needs-dep", r#"{ "name": "missing", "version_req": "^1" }"#);
+        let sha = hex::encode(sha2::Sha256::digest(&archive));
+        let base = serve(vec![("dep.tar.gz", archive)], serde_json::json!({ "plugins": [] })).await;
+
+        let tmp = TempDir::new().unwrap();
+        let registry = PluginRegistry::new(tmp.path()).with_marketplace(marketplace(None));
+
+        let err = registry.install_from_url(&format!("{}/dep.tar.gz", base), &sha).await.unwrap_err();
+        assert!(matches!(err, RegistryError::DependencyNotSatisfied { .. }));
+        assert!(registry.get("needs-dep").await.is_err());
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_search_marketplace_index() {
+        let index = serde_json::json!({ "plugins": [
+            { "name": "web-search", "version": "1.0.0", "description": "Search the web",
+              "archive_url": "http://127.0.0.1/web-search.tar.gz", "sha256": "00" },
+            { "name": "sql-runner", "version": "0.3.0", "tags": ["database"],
+              "archive_url": "http://127.0.0.1/sql-runner.tar.gz", "sha256": "00" }
+        ] });
+        let base = serve(vec![], index).await;
+
+        let tmp = TempDir::new().unwrap();
+        let registry = PluginRegistry::new(tmp.path())
+            .with_marketplace(marketplace(Some(format!("{}/index.json", base))));
+
+        let hits = registry.search("database").await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].name, "sql-runner");
+        assert_eq!(registry.search("").await.unwrap().len(), 2);
+
+        let offline = PluginRegistry::new(tmp.path());
+        assert!(matches!(offline.search("web").await, Err(RegistryError::Remote(_))));
+    }
+
     #[tokio::test]
     async fn test_plugin_not_found() {
         let tmp = TempDir::new().unwrap();
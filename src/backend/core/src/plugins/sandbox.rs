@@ -4,11 +4,12 @@
 //! by enforcing resource limits, permission checks, and isolation boundaries.
 
 use std::collections::HashSet;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
 
 use super::manifest::PluginPermission;
+use crate::contracts::AgentContract;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Sandbox Policy
@@ -65,8 +66,11 @@ impl Default for SandboxPolicy {
 #[derive(Debug)]
 pub struct SandboxContext {
     policy: SandboxPolicy,
+    started_at: Instant,
     network_requests_made: u32,
+    network_bytes: u64,
     memory_allocated: u64,
+    allocations: u32,
 }
 
 impl SandboxContext {
@@ -74,8 +78,11 @@ impl SandboxContext {
     pub fn new(policy: SandboxPolicy) -> Self {
         Self {
             policy,
+            started_at: Instant::now(),
             network_requests_made: 0,
+            network_bytes: 0,
             memory_allocated: 0,
+            allocations: 0,
         }
     }
 
@@ -120,9 +127,15 @@ impl SandboxContext {
             });
         }
         self.memory_allocated = new_total;
+        self.allocations += 1;
         Ok(())
     }
 
+    /// Record bytes transferred by a permitted network request.
+    pub fn record_network_bytes(&mut self, bytes: u64) {
+        self.network_bytes = self.network_bytes.saturating_add(bytes);
+    }
+
     /// Check whether a file read is permitted.
     pub fn check_file_read(&self, path: &str) -> Result<(), SandboxViolation> {
         if !self.has_permission(&PluginPermission::FileRead) {
@@ -168,6 +181,71 @@ impl SandboxContext {
     pub fn policy(&self) -> &SandboxPolicy {
         &self.policy
     }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // Resource accounting
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Wall-clock time since the sandbox was created.
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Memory granted through [`request_memory`](Self::request_memory), in bytes.
+    pub fn memory_used(&self) -> u64 {
+        self.memory_allocated
+    }
+
+    /// Network requests granted so far.
+    pub fn network_requests(&self) -> u32 {
+        self.network_requests_made
+    }
+
+    /// Bytes reported through [`record_network_bytes`](Self::record_network_bytes).
+    pub fn network_bytes(&self) -> u64 {
+        self.network_bytes
+    }
+
+    /// Number of granted memory requests.
+    pub fn allocations(&self) -> u32 {
+        self.allocations
+    }
+
+    /// Snapshot of everything consumed so far.
+    pub fn report(&self) -> PluginResourceReport {
+        PluginResourceReport {
+            elapsed_ms: self.elapsed().as_millis() as u64,
+            memory_used_bytes: self.memory_allocated,
+            network_requests: self.network_requests_made,
+            network_bytes: self.network_bytes,
+            allocations: self.allocations,
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Resource Report
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Resources a plugin consumed during one execution.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginResourceReport {
+    pub elapsed_ms: u64,
+    pub memory_used_bytes: u64,
+    pub network_requests: u32,
+    pub network_bytes: u64,
+    pub allocations: u32,
+}
+
+impl PluginResourceReport {
+    /// Bill this execution against a task contract: one API call for the
+    /// plugin invocation plus one per outbound network request.
+    pub fn charge(&self, contract: &mut AgentContract) -> crate::error::Result<()> {
+        for _ in 0..=self.network_requests {
+            contract.record_api_call()?;
+        }
+        Ok(())
+    }
 }
 
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -207,6 +285,8 @@ pub enum SandboxViolation {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::contracts::ResourceLimits;
+    use uuid::Uuid;
 
     fn policy_with_network() -> SandboxPolicy {
         let mut perms = HashSet::new();
@@ -266,6 +346,30 @@ mod tests {
         assert!(ctx.check_file_read("/etc/passwd").is_err());
     }
 
+    #[test]
+    fn test_resource_report() {
+        let mut ctx = SandboxContext::new(policy_with_network());
+        ctx.request_network("api.example.com").unwrap();
+        ctx.record_network_bytes(2048);
+        ctx.request_memory(100).unwrap();
+        ctx.request_memory(50).unwrap();
+
+        let report = ctx.report();
+        assert_eq!(report.network_requests, 1);
+        assert_eq!(report.network_bytes, 2048);
+        assert_eq!(report.memory_used_bytes, 150);
+        assert_eq!(report.allocations, 2);
+
+        let limits = ResourceLimits {
+            api_call_limit: 2,
+            ..ResourceLimits::medium()
+        };
+        let mut contract = AgentContract::new(Uuid::new_v4(), Uuid::new_v4(), limits);
+        report.charge(&mut contract).unwrap();
+        assert_eq!(contract.usage.api_calls_used, 2);
+        assert!(report.charge(&mut contract).is_err());
+    }
+
     #[test]
     fn test_file_write_without_permission() {
         let ctx = SandboxContext::new(SandboxPolicy::default());
@@ -17,9 +17,9 @@ use std::task::{Context, Poll};
 use tower::{Layer, Service};
 use tracing::warn;
 
-use super::models::{OrganizationId, Permission, UserId};
+use super::models::{OrganizationId, Permission, TenantScope, UserId};
 use super::policy::PolicyEngine;
-use crate::middleware::auth::AuthContext;
+use crate::middleware::auth::{AuthContext, AuthError};
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // RBAC Context (extracted in handlers)
@@ -66,6 +66,54 @@ where
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// Tenant Scope (extracted in handlers)
+// ═══════════════════════════════════════════════════════════════════════════════
+
+impl TenantScope {
+    /// Scope for an authenticated caller: admins see every organization,
+    /// everyone else only their own. A non-admin without an organization
+    /// has nothing they may see and is refused.
+    pub fn for_auth(ctx: &AuthContext) -> Result<Self, AuthError> {
+        if !ctx.is_authenticated() {
+            return Err(AuthError::MissingCredentials);
+        }
+        if ctx.has_role("admin") {
+            return Ok(Self::All);
+        }
+        ctx.org_id
+            .as_deref()
+            .map(|org| Self::Organization(OrganizationId::new(org)))
+            .ok_or(AuthError::InsufficientPermissions)
+    }
+}
+
+/// Axum extractor for `TenantScope`.
+///
+/// Prefers the organization resolved by the RBAC middleware, falling back to
+/// the `org_id` on the `AuthContext`. Requests that carry no `AuthContext`
+/// never passed the auth layer and are refused.
+#[axum::async_trait]
+impl<S> FromRequestParts<S> for TenantScope
+where
+    S: Send + Sync,
+{
+    type Rejection = AuthError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let auth = parts.extensions.get::<AuthContext>();
+        let is_admin = auth.is_some_and(|ctx| ctx.is_authenticated() && ctx.has_role("admin"));
+
+        if let Some(rbac) = parts.extensions.get::<RbacContext>() {
+            if !is_admin {
+                return Ok(TenantScope::Organization(rbac.organization_id.clone()));
+            }
+        }
+
+        TenantScope::for_auth(auth.ok_or(AuthError::MissingCredentials)?)
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Tower Layer
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -162,6 +210,22 @@ where
                 }
             };
 
+            // Scoped credentials (API keys) can never exceed their scopes.
+            if !auth_ctx.has_scope(&permission) {
+                warn!(
+                    user_id = %auth_ctx.user_id,
+                    api_key_id = ?auth_ctx.api_key_id,
+                    permission = %permission,
+                    "API key scope denied"
+                );
+                return Ok(forbidden_response(&format!(
+                    "API key scope does not grant: {}",
+                    permission
+                )));
+            }
+
+            let user_id = UserId::new(&auth_ctx.user_id);
+
             // Determine organization from auth context.
             let org_id = match &auth_ctx.org_id {
                 Some(id) => OrganizationId::new(id),
@@ -172,12 +236,12 @@ where
                 }
             };
 
-            let user_id = UserId::new(&auth_ctx.user_id);
-
-            // Evaluate policy.
-            let decision = engine.check(&user_id, &permission, &org_id);
-
-            if decision.is_denied() {
+            // Evaluate policy for the roles in the token and those bound to
+            // the user in the organization.
+            if engine
+                .check_with_roles(&user_id, &auth_ctx.roles, &permission, &org_id)
+                .is_denied()
+            {
                 warn!(
                     user_id = %user_id,
                     permission = %permission,
@@ -252,6 +316,9 @@ mod tests {
             token_id: None,
             expires_at: None,
             request_id: "test-req".to_string(),
+            api_key_id: None,
+            scopes: None,
+            key_grace_until: None,
         }
     }
 
@@ -267,6 +334,72 @@ mod tests {
         assert_eq!(ctx.organization_id.as_str(), "org1");
     }
 
+    #[test]
+    fn test_tenant_scope_for_auth() {
+        let org = "7c9e6679-7425-40de-944b-e07fc1f90ae7";
+        let member = make_auth_context("alice", Some(org));
+        let scope = TenantScope::for_auth(&member).unwrap();
+        assert_eq!(scope, TenantScope::Organization(OrganizationId::new(org)));
+        assert_eq!(scope.organization_filter(), Some(org.parse().unwrap()));
+
+        let mut admin = make_auth_context("root", Some(org));
+        admin.roles = vec!["admin".to_string()];
+        let scope = TenantScope::for_auth(&admin).unwrap();
+        assert!(scope.is_all());
+        assert_eq!(scope.organization_filter(), None);
+
+        // A malformed org id must narrow, never widen, the scope
+        let scope = TenantScope::for_auth(&make_auth_context("bob", Some("acme"))).unwrap();
+        assert_eq!(scope.organization_filter(), Some(uuid::Uuid::nil()));
+
+        assert!(matches!(
+            TenantScope::for_auth(&make_auth_context("carol", None)),
+            Err(AuthError::InsufficientPermissions)
+        ));
+        assert!(matches!(
+            TenantScope::for_auth(&AuthContext::anonymous("req".to_string())),
+            Err(AuthError::MissingCredentials)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_tenant_scope_extractor() {
+        async fn extract(ctx: Option<AuthContext>, rbac: Option<RbacContext>) -> Result<TenantScope, AuthError> {
+            let mut req = Request::new(Body::empty());
+            if let Some(ctx) = ctx {
+                req.extensions_mut().insert(ctx);
+            }
+            if let Some(rbac) = rbac {
+                req.extensions_mut().insert(rbac);
+            }
+            let (mut parts, _) = req.into_parts();
+            TenantScope::from_request_parts(&mut parts, &()).await
+        }
+
+        // Auth layer not installed: refused rather than unscoped
+        assert!(matches!(extract(None, None).await, Err(AuthError::MissingCredentials)));
+
+        // RBAC's resolved organization wins over the token's
+        let rbac = RbacContext {
+            user_id: UserId::new("alice"),
+            organization_id: OrganizationId::new("org-rbac"),
+            checked_permission: None,
+        };
+        let ctx = make_auth_context("alice", Some("org-token"));
+        assert_eq!(
+            extract(Some(ctx.clone()), Some(rbac.clone())).await.unwrap(),
+            TenantScope::Organization(OrganizationId::new("org-rbac"))
+        );
+        assert_eq!(
+            extract(Some(ctx), None).await.unwrap(),
+            TenantScope::Organization(OrganizationId::new("org-token"))
+        );
+
+        let mut admin = make_auth_context("root", None);
+        admin.roles = vec!["admin".to_string()];
+        assert!(extract(Some(admin), Some(rbac)).await.unwrap().is_all());
+    }
+
     #[test]
     fn test_require_permission_layer_parse() {
         let engine = Arc::new(PolicyEngine::new());
@@ -274,4 +407,50 @@ mod tests {
         assert_eq!(layer.permission.resource, "swarm");
         assert_eq!(layer.permission.action, "create");
     }
+
+    async fn call_with(layer: RequirePermissionLayer, ctx: AuthContext) -> StatusCode {
+        use tower::ServiceExt;
+
+        let svc = layer.layer(tower::service_fn(|_req: Request<Body>| async {
+            Ok::<_, std::convert::Infallible>(StatusCode::OK.into_response())
+        }));
+        let mut req = Request::new(Body::empty());
+        req.extensions_mut().insert(ctx);
+        svc.oneshot(req).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn test_api_key_scope_enforced() {
+        let engine = setup_engine_with_user("svc-reporter", "admin", "org1");
+        let mut ctx = make_auth_context("svc-reporter", Some("org1"));
+        ctx.auth_method = AuthMethod::ApiKey;
+        ctx.api_key_id = Some("key-1".to_string());
+        ctx.scopes = Some(vec![Permission::new("task", "read")]);
+
+        let allowed = RequirePermissionLayer::new(engine.clone(), "task:read");
+        assert_eq!(call_with(allowed, ctx.clone()).await, StatusCode::OK);
+
+        let denied = RequirePermissionLayer::new(engine.clone(), "task:delete");
+        assert_eq!(call_with(denied, ctx.clone()).await, StatusCode::FORBIDDEN);
+
+        // A key's scopes alone authorize nothing: it needs an organization
+        // in which its owner holds the permission.
+        ctx.org_id = None;
+        let layer = RequirePermissionLayer::new(engine.clone(), "task:read");
+        assert_eq!(call_with(layer, ctx.clone()).await, StatusCode::FORBIDDEN);
+
+        ctx.org_id = Some("org2".to_string());
+        let layer = RequirePermissionLayer::new(engine, "task:read");
+        assert_eq!(call_with(layer, ctx).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_api_key_scope_does_not_bypass_role() {
+        let engine = setup_engine_with_user("bob", "viewer", "org1");
+        let mut ctx = make_auth_context("bob", Some("org1"));
+        ctx.scopes = Some(vec![Permission::new("*", "*")]);
+
+        let layer = RequirePermissionLayer::new(engine, "swarm:create");
+        assert_eq!(call_with(layer, ctx).await, StatusCode::FORBIDDEN);
+    }
 }
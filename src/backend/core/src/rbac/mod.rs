@@ -39,7 +39,7 @@ pub mod roles;
 pub use models::{
     Permission, Role, RoleId, RoleBinding, UserId, OrganizationId,
     Organization, OrganizationMember, OrganizationStatus, MemberRole,
-    ResourceScope,
+    ResourceScope, TenantScope,
 };
 pub use policy::{PolicyEngine, PolicyDecision, PolicyError};
 pub use middleware::{
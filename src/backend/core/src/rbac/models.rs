@@ -409,6 +409,49 @@ impl ResourceScope {
     }
 }
 
+/// Which organizations' rows a request may read or modify.
+///
+/// Handlers take this as an extractor and pass it to every list/get query
+/// on a tenant-owned table (tasks, DAGs, agents, contracts). A row owned by
+/// another organization is indistinguishable from one that doesn't exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantScope {
+    /// Every organization: platform admins, or a server running without auth
+    All,
+    /// Only rows owned by this organization
+    Organization(OrganizationId),
+}
+
+impl TenantScope {
+    pub fn is_all(&self) -> bool {
+        matches!(self, Self::All)
+    }
+
+    /// Value to bind for an `($n::uuid IS NULL OR organization_id = $n)` filter.
+    ///
+    /// `None` matches every row. An organization id that isn't a UUID maps to
+    /// the nil UUID, which owns nothing, so a malformed id can never widen the
+    /// scope.
+    pub fn organization_filter(&self) -> Option<Uuid> {
+        match self {
+            Self::All => None,
+            Self::Organization(org) => Some(Uuid::parse_str(org.as_str()).unwrap_or(Uuid::nil())),
+        }
+    }
+
+    /// Organization to record as the owner of a row created in this scope.
+    ///
+    /// `None` for unscoped callers, leaving the row visible to admins only.
+    /// An organization id that isn't a UUID can't reference `organizations`,
+    /// so it gets `None` as well rather than the nil UUID.
+    pub fn owner(&self) -> Option<Uuid> {
+        match self {
+            Self::All => None,
+            Self::Organization(org) => Uuid::parse_str(org.as_str()).ok(),
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Tests
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -82,6 +82,13 @@ impl PolicyEngine {
         }
     }
 
+    /// A policy engine with the predefined roles loaded.
+    pub fn with_predefined_roles() -> Self {
+        let engine = Self::new();
+        engine.load_roles(super::roles::PredefinedRole::all_defaults());
+        engine
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Role management
     // ─────────────────────────────────────────────────────────────────────────
@@ -225,6 +232,27 @@ impl PolicyEngine {
         ))
     }
 
+    /// Like [`check`](Self::check), also counting `token_roles`, the roles
+    /// the caller's credential carries, alongside those bound to them in
+    /// `organization_id`. Roles the engine doesn't know grant nothing.
+    pub fn check_with_roles(
+        &self,
+        user_id: &UserId,
+        token_roles: &[String],
+        permission: &Permission,
+        organization_id: &OrganizationId,
+    ) -> PolicyDecision {
+        let granted_by_token = token_roles.iter().any(|role_id| {
+            self.roles
+                .get(&RoleId::new(role_id.as_str()))
+                .is_some_and(|role| role.has_permission(permission))
+        });
+        if granted_by_token {
+            return PolicyDecision::Allow;
+        }
+        self.check(user_id, permission, organization_id)
+    }
+
     /// Convenience: returns `Ok(())` if allowed, `Err(PolicyError)` if denied.
     pub fn enforce(
         &self,
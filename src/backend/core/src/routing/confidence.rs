@@ -0,0 +1,264 @@
+//! Confidence evaluation for cascade acceptance.
+//!
+//! The cascade accepts a tier's response when its confidence clears the
+//! tier's threshold, and escalates otherwise. How confidence is judged is
+//! pluggable through [`ConfidenceEvaluator`]: [`HeuristicEvaluator`] is free
+//! and instant, [`LlmJudgeEvaluator`] asks an economy model to grade the
+//! answer and reports what that cost.
+
+use std::future::Future;
+
+use async_trait::async_trait;
+
+use super::ModelResponse;
+use crate::error::Result;
+
+/// A confidence score plus whatever it cost to produce.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Evaluation {
+    /// Confidence in the response, 0.0 - 1.0
+    pub confidence: f64,
+    /// Tokens spent judging the response
+    pub tokens: u64,
+    /// Dollars spent judging the response
+    pub cost: f64,
+}
+
+/// Scores how likely a response is to be an acceptable answer to a task.
+///
+/// Implement [`score`](Self::score) for evaluators that are free to run.
+/// Evaluators that call a model should also override
+/// [`evaluate`](Self::evaluate) so the cascade can account for their cost.
+#[async_trait]
+pub trait ConfidenceEvaluator: Send + Sync {
+    /// Confidence in `response` as an answer to `task`, 0.0 - 1.0.
+    async fn score(&self, task: &str, response: &str) -> f64;
+
+    /// Score `response` and report the cost of doing so.
+    async fn evaluate(&self, task: &str, response: &str) -> Evaluation {
+        Evaluation {
+            confidence: self.score(task, response).await,
+            ..Evaluation::default()
+        }
+    }
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Heuristic
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Phrases that suggest the model could not or would not answer.
+const HEDGING_PHRASES: &[&str] = &[
+    "i'm not sure",
+    "i am not sure",
+    "i don't know",
+    "i do not know",
+    "i cannot",
+    "i can't",
+    "unable to",
+    "as an ai",
+    "i apologize",
+];
+
+/// Cheap, model-free checks on length, format and wording.
+///
+/// Starts from full confidence and deducts for an empty or very short
+/// response, hedging or refusal phrases, a response that shares no words
+/// with the task, and a requested format (JSON, list) that isn't met.
+#[derive(Debug, Clone)]
+pub struct HeuristicEvaluator {
+    /// Responses shorter than this many words are penalized
+    pub min_words: usize,
+}
+
+impl Default for HeuristicEvaluator {
+    fn default() -> Self {
+        Self { min_words: 5 }
+    }
+}
+
+#[async_trait]
+impl ConfidenceEvaluator for HeuristicEvaluator {
+    async fn score(&self, task: &str, response: &str) -> f64 {
+        let response = response.trim();
+        if response.is_empty() {
+            return 0.0;
+        }
+
+        let task_lower = task.to_lowercase();
+        let response_lower = response.to_lowercase();
+        let mut score: f64 = 1.0;
+
+        // Length
+        if response.split_whitespace().count() < self.min_words {
+            score -= 0.3;
+        }
+
+        // Hedging and refusals
+        if HEDGING_PHRASES.iter().any(|p| response_lower.contains(p)) {
+            score -= 0.4;
+        }
+
+        // Keyword overlap with the task
+        let keywords: Vec<&str> = task_lower
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| w.len() > 4)
+            .collect();
+        if !keywords.is_empty() && !keywords.iter().any(|k| response_lower.contains(k)) {
+            score -= 0.2;
+        }
+
+        // Requested format
+        if task_lower.contains("json") && serde_json::from_str::<serde_json::Value>(response).is_err() {
+            score -= 0.4;
+        }
+        if task_lower.contains("list") {
+            let is_list = response.lines().filter(|l| is_list_item(l)).count() >= 2;
+            if !is_list {
+                score -= 0.2;
+            }
+        }
+
+        score.clamp(0.0, 1.0)
+    }
+}
+
+fn is_list_item(line: &str) -> bool {
+    let line = line.trim_start();
+    line.starts_with("- ")
+        || line.starts_with("* ")
+        || line
+            .split_once(['.', ')'])
+            .is_some_and(|(n, _)| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// LLM judge
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Asks a (cheap) model to grade the response from 0 to 10.
+///
+/// `call` sends a prompt to a model, taking `(model, prompt)`. A judge call
+/// that fails or returns no number scores 0.0, so the cascade escalates
+/// rather than accepting an unjudged answer.
+pub struct LlmJudgeEvaluator<F> {
+    model: String,
+    call: F,
+}
+
+impl<F, Fut> LlmJudgeEvaluator<F>
+where
+    F: Fn(String, String) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<ModelResponse>> + Send,
+{
+    /// Judge with `gpt-4o-mini`.
+    pub fn new(call: F) -> Self {
+        Self {
+            model: "gpt-4o-mini".to_string(),
+            call,
+        }
+    }
+
+    /// Judge with a different model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    fn prompt(task: &str, response: &str) -> String {
+        format!(
+            "Rate how well the response answers the task, from 0 (wrong or missing) \
+             to 10 (complete and correct). Reply with the number only.\n\n\
+             Task:\n{}\n\nResponse:\n{}",
+            task, response
+        )
+    }
+}
+
+/// First number in a judge reply, scaled from 0-10 to 0.0-1.0.
+fn parse_grade(reply: &str) -> Option<f64> {
+    reply
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .find_map(|s| s.trim_matches('.').parse::<f64>().ok())
+        .map(|grade| (grade / 10.0).clamp(0.0, 1.0))
+}
+
+#[async_trait]
+impl<F, Fut> ConfidenceEvaluator for LlmJudgeEvaluator<F>
+where
+    F: Fn(String, String) -> Fut + Send + Sync,
+    Fut: Future<Output = Result<ModelResponse>> + Send,
+{
+    async fn score(&self, task: &str, response: &str) -> f64 {
+        self.evaluate(task, response).await.confidence
+    }
+
+    async fn evaluate(&self, task: &str, response: &str) -> Evaluation {
+        match (self.call)(self.model.clone(), Self::prompt(task, response)).await {
+            Ok(reply) => Evaluation {
+                confidence: parse_grade(&reply.text).unwrap_or(0.0),
+                tokens: reply.tokens,
+                cost: reply.cost,
+            },
+            Err(e) => {
+                tracing::warn!(model = %self.model, error = %e, "Confidence judge call failed");
+                Evaluation::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{ApexError, ErrorCode};
+
+    #[tokio::test]
+    async fn test_heuristic_scoring() {
+        let eval = HeuristicEvaluator::default();
+        let task = "Explain how photosynthesis converts sunlight";
+
+        let good = eval
+            .score(task, "Photosynthesis captures sunlight in chlorophyll and converts it to sugar.")
+            .await;
+        assert_eq!(good, 1.0);
+
+        assert_eq!(eval.score(task, "   ").await, 0.0);
+        assert!(eval.score(task, "I'm not sure, sorry.").await < 0.5);
+
+        let json_task = "Return the result as JSON";
+        assert!(eval.score(json_task, r#"{"result": "the answer is 42"}"#).await > 0.5);
+        assert!(eval.score(json_task, "The result is 42, nothing more to add").await < good);
+
+        let list_task = "List three primary colors";
+        assert_eq!(eval.score(list_task, "- red primary\n- blue\n- yellow").await, 1.0);
+        assert!(eval.score(list_task, "red, blue and yellow are the primary colors").await < 1.0);
+    }
+
+    #[test]
+    fn test_parse_grade() {
+        assert_eq!(parse_grade("8"), Some(0.8));
+        assert_eq!(parse_grade("Score: 7.5/10"), Some(0.75));
+        assert_eq!(parse_grade("15"), Some(1.0));
+        assert_eq!(parse_grade("no idea"), None);
+    }
+
+    #[tokio::test]
+    async fn test_llm_judge_reports_cost() {
+        let judge = LlmJudgeEvaluator::new(|model: String, prompt: String| async move {
+            assert_eq!(model, "claude-3.5-haiku");
+            assert!(prompt.contains("Task:\nAdd 2 and 2"));
+            Ok(ModelResponse { text: "9".to_string(), tokens: 120, cost: 0.0002 })
+        })
+        .with_model("claude-3.5-haiku");
+
+        let eval = judge.evaluate("Add 2 and 2", "4").await;
+        assert_eq!(eval, Evaluation { confidence: 0.9, tokens: 120, cost: 0.0002 });
+        assert_eq!(judge.score("Add 2 and 2", "4").await, 0.9);
+
+        let failing = LlmJudgeEvaluator::new(|_: String, _: String| async {
+            Err(ApexError::new(ErrorCode::LlmUnavailable, "down"))
+        });
+        assert_eq!(failing.evaluate("Add 2 and 2", "4").await, Evaluation::default());
+    }
+}
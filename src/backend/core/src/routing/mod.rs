@@ -3,8 +3,25 @@
 //! Implements a cascade strategy where cheaper models are tried first,
 //! escalating to more expensive models only when needed.
 
+pub mod confidence;
+pub mod tokens;
+
+pub use confidence::{ConfidenceEvaluator, Evaluation, HeuristicEvaluator, LlmJudgeEvaluator};
+pub use tokens::estimate_input_tokens;
+
+use std::collections::VecDeque;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 
+use crate::error::Result;
+
+/// Number of recent completions kept per model for latency percentiles.
+const LATENCY_WINDOW: usize = 200;
+
 /// Model tier in the cascade.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum ModelTier {
@@ -135,6 +152,9 @@ pub struct ModelRouter {
 
     /// Routing configuration
     config: RoutingConfig,
+
+    /// Rolling window of observed response latencies (seconds) per model
+    latencies: DashMap<String, VecDeque<f64>>,
 }
 
 impl ModelRouter {
@@ -151,6 +171,7 @@ impl ModelRouter {
         Self {
             models,
             config: RoutingConfig::default(),
+            latencies: DashMap::new(),
         }
     }
 
@@ -165,6 +186,15 @@ impl ModelRouter {
     ///
     /// Uses heuristics based on task complexity to choose initial model.
     pub fn select_model(&self, task_description: &str) -> String {
+        self.select_model_with_slo(task_description, None)
+    }
+
+    /// Select a model, preferring ones whose observed p95 latency meets `latency_slo`.
+    ///
+    /// Within the target tier the cheapest model meeting the SLO wins; if none
+    /// does, the fastest one is used. Models without latency history are
+    /// ignored, and with no history at all selection is purely cost-based.
+    pub fn select_model_with_slo(&self, task_description: &str, latency_slo: Option<Duration>) -> String {
         if !self.config.enable_cascade {
             // Default to standard tier if cascade disabled
             return self.models.iter()
@@ -183,9 +213,63 @@ impl ModelRouter {
             ModelTier::Premium
         };
 
+        if let Some(slo) = latency_slo {
+            if let Some(model) = self.get_model_for_slo(&target_tier, slo) {
+                return model;
+            }
+        }
+
         self.get_cheapest_model_for_tier(&target_tier)
     }
 
+    /// Pick a model in `tier` by observed latency, if any have history.
+    fn get_model_for_slo(&self, tier: &ModelTier, slo: Duration) -> Option<String> {
+        let slo = slo.as_secs_f64();
+        let observed: Vec<(&ModelConfig, f64)> = self.models.iter()
+            .filter(|m| &m.tier == tier)
+            .filter_map(|m| self.latency_stats(&m.name).map(|s| (m, s.p95)))
+            .collect();
+
+        let meeting_slo = observed.iter()
+            .filter(|(_, p95)| *p95 <= slo)
+            .min_by(|(a, _), (b, _)| {
+                let cost_a = a.cost_per_1k_input + a.cost_per_1k_output;
+                let cost_b = b.cost_per_1k_input + b.cost_per_1k_output;
+                cost_a.total_cmp(&cost_b)
+            });
+
+        meeting_slo
+            .or_else(|| observed.iter().min_by(|(_, a), (_, b)| a.total_cmp(b)))
+            .map(|(m, _)| m.name.clone())
+    }
+
+    /// Record an observed response latency for a model.
+    pub fn record_latency(&self, model: &str, secs: f64) {
+        let mut window = self.latencies.entry(model.to_string()).or_default();
+        if window.len() == LATENCY_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(secs);
+    }
+
+    /// Latency percentiles over the recent window, if the model has history.
+    pub fn latency_stats(&self, model: &str) -> Option<LatencyStats> {
+        let window = self.latencies.get(model)?;
+        if window.is_empty() {
+            return None;
+        }
+
+        let mut sorted: Vec<f64> = window.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+
+        Some(LatencyStats {
+            p50: percentile(0.50),
+            p95: percentile(0.95),
+            samples: sorted.len(),
+        })
+    }
+
     /// Get the cheapest model for a given tier.
     fn get_cheapest_model_for_tier(&self, tier: &ModelTier) -> String {
         self.models.iter()
@@ -267,11 +351,142 @@ impl ModelRouter {
         }
     }
 
+    /// Run the cascade for `task`: start at the tier [`select_model`](Self::select_model)
+    /// picks, score each response with `evaluator`, and escalate to the next
+    /// tier while [`should_escalate`](Self::should_escalate) says so, up to
+    /// `max_escalations` times.
+    ///
+    /// `call` sends the task to the named model. Usage from both the model
+    /// calls and the evaluator is accumulated in the result.
+    pub async fn run_cascade<F, Fut>(
+        &self,
+        task: &str,
+        evaluator: Arc<dyn ConfidenceEvaluator>,
+        call: F,
+    ) -> Result<CascadeResult>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<ModelResponse>>,
+    {
+        let (call, evaluator) = (&call, &evaluator);
+        self.cascade(task, |model| async move {
+            let response = call(model).await?;
+            let evaluation = evaluator.evaluate(task, &response.text).await;
+            Ok((response, evaluation))
+        })
+        .await
+    }
+
+    /// Run the cascade for `task` like [`run_cascade`](Self::run_cascade),
+    /// for models that rate their own answers.
+    ///
+    /// `call` sends the task to the named model and returns its
+    /// `(response, confidence, tokens, cost)`.
+    pub async fn execute_cascade<F, Fut>(&self, task: &str, call: F) -> Result<CascadeResult>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<(String, f64, u64, f64)>>,
+    {
+        let call = &call;
+        self.cascade(task, |model| async move {
+            let (text, confidence, tokens, cost) = call(model).await?;
+            Ok((ModelResponse { text, tokens, cost }, Evaluation { confidence, ..Evaluation::default() }))
+        })
+        .await
+    }
+
+    /// The cascade loop: `attempt` calls the named model and judges its reply.
+    async fn cascade<F, Fut>(&self, task: &str, attempt: F) -> Result<CascadeResult>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<(ModelResponse, Evaluation)>>,
+    {
+        let mut model = self.select_model(task);
+        let mut escalations = 0;
+        let mut total_cost = 0.0;
+        let mut total_tokens = 0;
+        let mut evaluation_cost = 0.0;
+        let mut evaluation_tokens = 0;
+
+        loop {
+            let (response, evaluation) = attempt(model.clone()).await?;
+
+            total_cost += response.cost + evaluation.cost;
+            total_tokens += response.tokens + evaluation.tokens;
+            evaluation_cost += evaluation.cost;
+            evaluation_tokens += evaluation.tokens;
+
+            let tier = self.get_model(&model).map(|m| m.tier.clone()).unwrap_or(ModelTier::Premium);
+            let next_tier = self.escalate_tier(&tier)
+                .filter(|_| self.config.enable_cascade && escalations < self.config.max_escalations)
+                .filter(|_| self.should_escalate(evaluation.confidence, &tier));
+
+            match next_tier {
+                Some(next) => {
+                    tracing::debug!(
+                        from = %model,
+                        confidence = evaluation.confidence,
+                        "Escalating cascade to {:?} tier",
+                        next
+                    );
+                    model = self.get_cheapest_model_for_tier(&next);
+                    escalations += 1;
+                }
+                None => {
+                    return Ok(CascadeResult {
+                        model,
+                        escalations,
+                        total_cost,
+                        total_tokens,
+                        response: response.text,
+                        confidence: evaluation.confidence,
+                        evaluation_cost,
+                        evaluation_tokens,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Models available for routing.
+    pub fn models(&self) -> &[ModelConfig] {
+        &self.models
+    }
+
     /// Get model by name.
     pub fn get_model(&self, name: &str) -> Option<&ModelConfig> {
         self.models.iter().find(|m| m.name == name)
     }
 
+    /// Estimate the tokens and cost of sending `prompt` to a model, reserving
+    /// `output_tokens` for the response.
+    pub fn estimate_dispatch(&self, model_name: &str, prompt: &str, output_tokens: u32) -> Option<TokenEstimate> {
+        let model = self.get_model(model_name)?;
+        let input_tokens = estimate_input_tokens(prompt, model_name);
+
+        Some(TokenEstimate {
+            model: model.name.clone(),
+            input_tokens,
+            output_tokens: output_tokens as u64,
+            cost: self.estimate_cost(model_name, input_tokens.min(u32::MAX as u64) as u32, output_tokens),
+            fits_context: input_tokens + output_tokens as u64 <= model.max_tokens as u64,
+        })
+    }
+
+    /// Cheapest model whose estimate for `prompt` stays within the given budget.
+    pub fn cheapest_within_budget(
+        &self,
+        prompt: &str,
+        output_tokens: u32,
+        token_limit: u64,
+        cost_limit: f64,
+    ) -> Option<TokenEstimate> {
+        self.models.iter()
+            .filter_map(|m| self.estimate_dispatch(&m.name, prompt, output_tokens))
+            .filter(|e| e.within(token_limit, cost_limit))
+            .min_by(|a, b| a.cost.partial_cmp(&b.cost).unwrap_or(std::cmp::Ordering::Equal))
+    }
+
     /// Calculate estimated cost for a task.
     pub fn estimate_cost(&self, model_name: &str, input_tokens: u32, output_tokens: u32) -> f64 {
         self.get_model(model_name)
@@ -289,6 +504,45 @@ impl Default for ModelRouter {
     }
 }
 
+/// Observed response latency percentiles for a model, in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatencyStats {
+    pub p50: f64,
+    pub p95: f64,
+    pub samples: usize,
+}
+
+/// Pre-dispatch token and cost estimate for a prompt on one model.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenEstimate {
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost: f64,
+    /// Whether input plus reserved output fits the model's context window
+    pub fits_context: bool,
+}
+
+impl TokenEstimate {
+    /// Total tokens the call is expected to consume.
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens
+    }
+
+    /// Check the estimate against a token and cost budget.
+    pub fn within(&self, token_limit: u64, cost_limit: f64) -> bool {
+        self.fits_context && self.total_tokens() <= token_limit && self.cost <= cost_limit
+    }
+}
+
+/// A model's reply to one call, with its usage.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelResponse {
+    pub text: String,
+    pub tokens: u64,
+    pub cost: f64,
+}
+
 /// Result of a cascade routing attempt.
 #[derive(Debug, Clone)]
 pub struct CascadeResult {
@@ -298,10 +552,10 @@ pub struct CascadeResult {
     /// Number of escalations performed
     pub escalations: u32,
 
-    /// Total cost across all attempts
+    /// Total cost across all attempts, including evaluation
     pub total_cost: f64,
 
-    /// Total tokens used
+    /// Total tokens used, including evaluation
     pub total_tokens: u64,
 
     /// Final response
@@ -309,6 +563,12 @@ pub struct CascadeResult {
 
     /// Final confidence score
     pub confidence: f64,
+
+    /// Portion of `total_cost` spent by the confidence evaluator
+    pub evaluation_cost: f64,
+
+    /// Portion of `total_tokens` spent by the confidence evaluator
+    pub evaluation_tokens: u64,
 }
 
 #[cfg(test)]
@@ -348,6 +608,72 @@ mod tests {
         assert!(config.tier >= ModelTier::Standard);
     }
 
+    #[test]
+    fn test_dispatch_estimate_and_downgrade() {
+        let router = ModelRouter::new();
+        let prompt = "Analyze this report. ".repeat(200);
+
+        let opus = router.estimate_dispatch("claude-opus-4", &prompt, 1000).unwrap();
+        assert!(opus.input_tokens > 0);
+        assert!(opus.fits_context);
+        assert!(router.estimate_dispatch("unknown-model", &prompt, 1000).is_none());
+
+        // Too expensive on opus, but an economy model fits the budget
+        let budget = opus.cost / 10.0;
+        assert!(!opus.within(100_000, budget));
+        let cheaper = router.cheapest_within_budget(&prompt, 1000, 100_000, budget).unwrap();
+        assert_eq!(router.get_model(&cheaper.model).unwrap().tier, ModelTier::Economy);
+
+        // Nothing fits a token limit smaller than the prompt
+        assert!(router.cheapest_within_budget(&prompt, 1000, 10, f64::MAX).is_none());
+    }
+
+    #[test]
+    fn test_latency_slo_selection() {
+        let router = ModelRouter::new();
+        let task = "Format this text";
+        let slo = Some(Duration::from_secs(2));
+
+        // No history: cost-based
+        assert_eq!(router.select_model_with_slo(task, slo), "gpt-4o-mini");
+
+        for _ in 0..20 {
+            router.record_latency("gpt-4o-mini", 5.0);
+            router.record_latency("claude-3.5-haiku", 1.0);
+        }
+        let stats = router.latency_stats("claude-3.5-haiku").unwrap();
+        assert_eq!((stats.p50, stats.p95, stats.samples), (1.0, 1.0, 20));
+
+        assert_eq!(router.select_model_with_slo(task, slo), "claude-3.5-haiku");
+        // Without a hint latency is ignored
+        assert_eq!(router.select_model(task), "gpt-4o-mini");
+        // Nobody meets a tight SLO: take the fastest
+        assert_eq!(router.select_model_with_slo(task, Some(Duration::from_millis(100))), "claude-3.5-haiku");
+    }
+
+    #[test]
+    fn test_latency_window_is_bounded() {
+        let router = ModelRouter::new();
+        for i in 0..LATENCY_WINDOW + 50 {
+            router.record_latency("gpt-4o", i as f64);
+        }
+        let stats = router.latency_stats("gpt-4o").unwrap();
+        assert_eq!(stats.samples, LATENCY_WINDOW);
+        assert!(stats.p50 >= 50.0);
+    }
+
+    #[test]
+    fn test_nan_latency_does_not_panic() {
+        let router = ModelRouter::new();
+        router.record_latency("gpt-4o-mini", f64::NAN);
+        router.record_latency("gpt-4o-mini", 1.0);
+        router.record_latency("claude-3.5-haiku", f64::NAN);
+
+        assert_eq!(router.latency_stats("gpt-4o-mini").unwrap().samples, 2);
+        let model = router.select_model_with_slo("Format this text", Some(Duration::from_secs(2)));
+        assert_eq!(router.get_model(&model).unwrap().tier, ModelTier::Economy);
+    }
+
     #[test]
     fn test_escalation() {
         let router = ModelRouter::new();
@@ -359,4 +685,93 @@ mod tests {
         assert_eq!(router.escalate_tier(&ModelTier::Economy), Some(ModelTier::Standard));
         assert_eq!(router.escalate_tier(&ModelTier::Premium), None);
     }
+
+    /// Scores each model's reply from a fixed table.
+    struct FixedScores(Vec<(&'static str, f64)>);
+
+    #[async_trait::async_trait]
+    impl ConfidenceEvaluator for FixedScores {
+        async fn score(&self, _task: &str, response: &str) -> f64 {
+            self.0.iter().find(|(m, _)| *m == response).map(|(_, s)| *s).unwrap_or(0.0)
+        }
+
+        async fn evaluate(&self, task: &str, response: &str) -> Evaluation {
+            Evaluation { confidence: self.score(task, response).await, tokens: 10, cost: 0.001 }
+        }
+    }
+
+    async fn echo_model(model: String) -> Result<ModelResponse> {
+        Ok(ModelResponse { text: model, tokens: 100, cost: 0.01 })
+    }
+
+    #[tokio::test]
+    async fn test_cascade_escalates_until_confident() {
+        let router = ModelRouter::new();
+        let evaluator = Arc::new(FixedScores(vec![("gpt-4o-mini", 0.5), ("claude-3.5-sonnet", 0.9)]));
+
+        let result = router.run_cascade("Format this text", evaluator, echo_model).await.unwrap();
+        assert_eq!(result.model, "claude-3.5-sonnet");
+        assert_eq!(result.response, "claude-3.5-sonnet");
+        assert_eq!(result.escalations, 1);
+        assert_eq!(result.confidence, 0.9);
+        assert_eq!(result.total_tokens, 220);
+        assert_eq!(result.evaluation_tokens, 20);
+        assert!((result.total_cost - 0.022).abs() < 1e-12);
+        assert!((result.evaluation_cost - 0.002).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn test_cascade_respects_max_escalations() {
+        let router = ModelRouter::with_config(RoutingConfig { max_escalations: 1, ..Default::default() });
+        let evaluator: Arc<dyn ConfidenceEvaluator> = Arc::new(FixedScores(vec![]));
+
+        let result = router.run_cascade("Format this text", evaluator.clone(), echo_model).await.unwrap();
+        assert_eq!(result.escalations, 1);
+        assert_eq!(router.get_model(&result.model).unwrap().tier, ModelTier::Standard);
+
+        // A confident first answer is accepted as is
+        let result = router
+            .run_cascade("Format this text", Arc::new(HeuristicEvaluator::default()), |_model: String| async {
+                Ok(ModelResponse { text: "Formatted text, ready to use as requested.".to_string(), tokens: 5, cost: 0.0 })
+            })
+            .await
+            .unwrap();
+        assert_eq!(result.escalations, 0);
+        assert_eq!(result.model, "gpt-4o-mini");
+    }
+
+    #[tokio::test]
+    async fn test_execute_cascade_uses_self_reported_confidence() {
+        let router = ModelRouter::new();
+        let calls = std::sync::Mutex::new(Vec::new());
+
+        let result = router
+            .execute_cascade("Format this text", |model: String| {
+                calls.lock().unwrap().push(model.clone());
+                async move {
+                    let confidence = if model == "gpt-4o-mini" { 0.3 } else { 0.95 };
+                    Ok((format!("answer from {}", model), confidence, 100, 0.01))
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(*calls.lock().unwrap(), vec!["gpt-4o-mini", "claude-3.5-sonnet"]);
+        assert_eq!(result.model, "claude-3.5-sonnet");
+        assert_eq!(result.response, "answer from claude-3.5-sonnet");
+        assert_eq!(result.escalations, 1);
+        assert_eq!(result.confidence, 0.95);
+        assert_eq!(result.total_tokens, 200);
+        assert!((result.total_cost - 0.02).abs() < 1e-12);
+        assert_eq!((result.evaluation_tokens, result.evaluation_cost), (0, 0.0));
+
+        // Never confident: stops after max_escalations
+        let router = ModelRouter::with_config(RoutingConfig { max_escalations: 1, ..Default::default() });
+        let result = router
+            .execute_cascade("Format this text", |model: String| async move { Ok((model, 0.0, 10, 0.001)) })
+            .await
+            .unwrap();
+        assert_eq!(result.escalations, 1);
+        assert_eq!(result.total_tokens, 20);
+    }
 }
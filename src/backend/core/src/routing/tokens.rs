@@ -0,0 +1,50 @@
+//! Prompt token estimation for pre-dispatch budget checks.
+//!
+//! Uses the tiktoken encodings. Models without a known OpenAI encoding
+//! (e.g. Claude) fall back to `cl100k_base`, which is close enough for
+//! budgeting but not exact.
+
+use std::sync::OnceLock;
+
+use dashmap::DashMap;
+use tiktoken_rs::{cl100k_base_singleton, bpe_for_model, CoreBPE};
+
+/// Resolved tokenizer per model name.
+static TOKENIZERS: OnceLock<DashMap<String, &'static CoreBPE>> = OnceLock::new();
+
+fn tokenizer_for(model: &str) -> &'static CoreBPE {
+    let cache = TOKENIZERS.get_or_init(DashMap::new);
+    if let Some(bpe) = cache.get(model) {
+        return *bpe;
+    }
+
+    let bpe = bpe_for_model(model).unwrap_or_else(|_| cl100k_base_singleton());
+    cache.insert(model.to_string(), bpe);
+    bpe
+}
+
+/// Estimate how many input tokens `text` costs on `model`.
+pub fn estimate_input_tokens(text: &str, model: &str) -> u64 {
+    tokenizer_for(model).encode_with_special_tokens(text).len() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_input_tokens() {
+        assert_eq!(estimate_input_tokens("", "gpt-4o"), 0);
+
+        let short = estimate_input_tokens("Summarize this document.", "gpt-4o-mini");
+        let long = estimate_input_tokens(&"Summarize this document. ".repeat(50), "gpt-4o-mini");
+        assert!(short > 0 && short < 10);
+        assert!(long > short * 40);
+    }
+
+    #[test]
+    fn test_unknown_model_falls_back() {
+        assert!(estimate_input_tokens("hello world", "claude-3.5-haiku") > 0);
+        assert!(TOKENIZERS.get().unwrap().contains_key("claude-3.5-haiku"));
+    }
+}
@@ -161,6 +161,21 @@ impl Default for RedactionConfig {
     }
 }
 
+impl RedactionConfig {
+    /// Also redact fields with these names, on top of the configured patterns.
+    pub fn with_field_names(mut self, field_names: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let field_names: Vec<String> = field_names.into_iter().map(Into::into).collect();
+        if !field_names.is_empty() {
+            self.patterns.push(RedactionPattern {
+                name: "configured_fields".to_string(),
+                field_names,
+                value_pattern: None,
+            });
+        }
+        self
+    }
+}
+
 /// A pattern for identifying sensitive data to redact.
 #[derive(Debug, Clone, Deserialize)]
 pub struct RedactionPattern {
@@ -258,6 +273,34 @@ impl SensitiveFieldRedactor {
         self.redact_value(value)
     }
 
+    /// Redact a JSON payload: values under sensitive keys are replaced whole,
+    /// and every other string is checked against the value patterns.
+    pub fn redact_json(&self, value: &serde_json::Value) -> serde_json::Value {
+        use serde_json::Value;
+
+        if !self.enabled {
+            return value.clone();
+        }
+
+        match value {
+            Value::String(s) => Value::String(self.redact_value(s)),
+            Value::Array(items) => Value::Array(items.iter().map(|v| self.redact_json(v)).collect()),
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, v)| {
+                        let v = if self.should_redact_field(key) {
+                            Value::String(self.replacement.clone())
+                        } else {
+                            self.redact_json(v)
+                        };
+                        (key.clone(), v)
+                    })
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
     /// Get the global redactor instance.
     pub fn global() -> &'static SensitiveFieldRedactor {
         REDACTOR.get_or_init(|| SensitiveFieldRedactor::new(&RedactionConfig::default()))
@@ -340,6 +383,13 @@ fn default_redaction_patterns() -> Vec<RedactionPattern> {
     ]
 }
 
+/// Install the global redactor used by [`SensitiveFieldRedactor::global`].
+///
+/// Only the first call takes effect; call it before anything is logged.
+pub fn init_redaction(config: &RedactionConfig) {
+    let _ = REDACTOR.set(SensitiveFieldRedactor::new(config));
+}
+
 /// Initialize the logging subsystem.
 ///
 /// This function sets up the tracing subscriber with the appropriate format
@@ -354,8 +404,7 @@ fn default_redaction_patterns() -> Vec<RedactionPattern> {
 ///
 /// Returns an error if the subscriber cannot be initialized.
 pub fn init_logging(config: &LoggingConfig, environment: &str) -> anyhow::Result<()> {
-    // Initialize the global redactor
-    let _ = REDACTOR.set(SensitiveFieldRedactor::new(&config.redaction));
+    init_redaction(&config.redaction);
 
     // Build the environment filter
     let mut filter = EnvFilter::try_new(&config.level)?;
@@ -603,6 +652,25 @@ mod tests {
         assert_eq!(redactor.redact_value(normal), normal);
     }
 
+    #[test]
+    fn test_redact_json_payload() {
+        let config = RedactionConfig::default().with_field_names(["email"]);
+        let redactor = SensitiveFieldRedactor::new(&config);
+
+        let payload = serde_json::json!({
+            "instruction": "Summarize, using key sk-abcdefghijklmnopqrstuvwx",
+            "context": {"Email": "jane@example.com", "notes": ["SSN 123-45-6789", 42]},
+            "parameters": {"api_key": "anything"}
+        });
+        let redacted = redactor.redact_json(&payload);
+
+        assert_eq!(redacted["instruction"], "Summarize, using key [REDACTED]");
+        assert_eq!(redacted["context"]["Email"], "[REDACTED]");
+        assert_eq!(redacted["context"]["notes"][0], "SSN [REDACTED]");
+        assert_eq!(redacted["context"]["notes"][1], 42);
+        assert_eq!(redacted["parameters"]["api_key"], "[REDACTED]");
+    }
+
     #[test]
     fn test_logging_config_defaults() {
         let config = LoggingConfig::default();
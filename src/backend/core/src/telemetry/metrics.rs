@@ -6,6 +6,7 @@
 //! - Active connections gauge for connection pool monitoring
 //! - Error counters by type/code for observability
 //! - Custom business metrics (tokens, costs, etc.)
+//! - A cardinality guard for labels fed from dynamic values
 //!
 //! # Example
 //!
@@ -21,14 +22,19 @@
 
 use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
+use parking_lot::RwLock;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::OnceLock;
 use std::time::{Duration, Instant};
 
 /// Global metrics registry.
 static METRICS_REGISTRY: OnceLock<MetricsRegistry> = OnceLock::new();
 
+/// Global label cardinality guard.
+static LABEL_GUARD: OnceLock<LabelGuard> = OnceLock::new();
+
 /// Metrics configuration.
 #[derive(Debug, Clone, Deserialize)]
 pub struct MetricsConfig {
@@ -51,6 +57,11 @@ pub struct MetricsConfig {
     /// Whether to enable default process metrics
     #[serde(default = "default_enable_process_metrics")]
     pub enable_process_metrics: bool,
+
+    /// Distinct values a dynamic label may take per metric before further
+    /// values are recorded as `other`
+    #[serde(default = "default_max_label_values")]
+    pub max_label_values: usize,
 }
 
 impl Default for MetricsConfig {
@@ -61,6 +72,7 @@ impl Default for MetricsConfig {
             duration_buckets: default_duration_buckets(),
             global_labels: HashMap::new(),
             enable_process_metrics: default_enable_process_metrics(),
+            max_label_values: default_max_label_values(),
         }
     }
 }
@@ -84,6 +96,94 @@ fn default_enable_process_metrics() -> bool {
     true
 }
 
+fn default_max_label_values() -> usize {
+    DEFAULT_MAX_LABEL_VALUES
+}
+
+// ═══════════════════════════════════════════════════════════════════════════════
+// Cardinality Guard
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Distinct values per metric label when the configuration doesn't say.
+pub const DEFAULT_MAX_LABEL_VALUES: usize = 100;
+
+/// Label value recorded once a metric has used up its distinct values.
+pub const OVERFLOW_LABEL_VALUE: &str = "other";
+
+/// Caps the distinct values each metric records for a dynamic label.
+///
+/// Every Prometheus label value creates a new time series, so a label fed
+/// from tool names, model names or paths can grow without bound. The first
+/// `max_values` values seen for a metric are kept; later ones are recorded
+/// as [`OVERFLOW_LABEL_VALUE`].
+pub struct LabelGuard {
+    max_values: AtomicUsize,
+    seen: RwLock<HashMap<&'static str, HashSet<String>>>,
+}
+
+impl LabelGuard {
+    /// Create a guard allowing `max_values` distinct values per metric.
+    pub fn new(max_values: usize) -> Self {
+        Self {
+            max_values: AtomicUsize::new(max_values),
+            seen: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// The guard used by the metric helpers in this module.
+    pub fn global() -> &'static LabelGuard {
+        LABEL_GUARD.get_or_init(|| LabelGuard::new(DEFAULT_MAX_LABEL_VALUES))
+    }
+
+    /// Change the cap. Values already admitted stay admitted.
+    pub fn set_max_values(&self, max_values: usize) {
+        self.max_values.store(max_values, Ordering::Relaxed);
+    }
+
+    /// `value` if `metric` may record it, otherwise [`OVERFLOW_LABEL_VALUE`].
+    pub fn bound(&self, metric: &'static str, value: &str) -> String {
+        if self.seen.read().get(metric).is_some_and(|values| values.contains(value)) {
+            return value.to_string();
+        }
+
+        let max_values = self.max_values.load(Ordering::Relaxed);
+        let mut seen = self.seen.write();
+        let values = seen.entry(metric).or_default();
+        if values.contains(value) || values.len() < max_values {
+            values.insert(value.to_string());
+            value.to_string()
+        } else {
+            OVERFLOW_LABEL_VALUE.to_string()
+        }
+    }
+}
+
+/// Bound `value` for `metric` with the global [`LabelGuard`].
+pub fn bounded_label(metric: &'static str, value: &str) -> String {
+    LabelGuard::global().bound(metric, value)
+}
+
+/// Replace path segments that identify a resource with `:id`, so
+/// `/api/v1/tasks/4f1c…/status` and `/api/v1/tasks/9a2b…/status` share a
+/// label. UUIDs, numbers and long hex strings count as ids.
+pub fn normalize_path(path: &str) -> String {
+    fn is_id(segment: &str) -> bool {
+        uuid::Uuid::parse_str(segment).is_ok()
+            || (!segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()))
+            || (segment.len() >= 16 && segment.bytes().all(|b| b.is_ascii_hexdigit()))
+    }
+
+    path.split('/')
+        .map(|segment| if is_id(segment) { ":id" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Path label for HTTP metrics: normalized, then bounded.
+pub fn path_label(path: &str) -> String {
+    bounded_label("http_requests", &normalize_path(path))
+}
+
 /// Central metrics registry for managing all metrics.
 pub struct MetricsRegistry {
     prometheus_handle: Option<PrometheusHandle>,
@@ -159,6 +259,8 @@ pub fn init_metrics(config: &MetricsConfig, service_name: &str) -> anyhow::Resul
     // Set custom buckets for histograms
     builder = builder.set_buckets(&config.duration_buckets)?;
 
+    LabelGuard::global().set_max_values(config.max_label_values);
+
     // Install the recorder and get the handle
     let handle = builder.install_recorder()?;
 
@@ -222,6 +324,20 @@ fn register_metric_descriptions() {
         "Task execution duration in seconds"
     );
 
+    // DAG metrics
+    describe_histogram!(
+        "apex_dag_duration_seconds",
+        "End-to-end DAG execution duration in seconds"
+    );
+    describe_counter!(
+        "apex_dag_tasks_total",
+        "Total number of tasks executed as part of a DAG"
+    );
+    describe_histogram!(
+        "apex_dag_cost_dollars",
+        "Total cost of a DAG execution in dollars"
+    );
+
     // Agent metrics
     describe_gauge!("apex_active_agents", "Number of currently active agents");
     describe_counter!("apex_agent_spawns_total", "Total number of agents spawned");
@@ -271,6 +387,15 @@ fn register_metric_descriptions() {
         "apex_contract_violations_total",
         "Total contract violations"
     );
+
+    // Cache metrics
+    describe_counter!("apex_cache_hits_total", "Cache hits by key type");
+    describe_counter!("apex_cache_misses_total", "Cache misses by key type");
+    describe_counter!(
+        "apex_cache_evictions_total",
+        "Cache entries evicted for capacity, by key type"
+    );
+    describe_gauge!("apex_cache_entries", "Entries held in memory caches, by key type");
 }
 
 /// Request duration histogram for HTTP requests.
@@ -285,11 +410,12 @@ impl RequestDurationHistogram {
         status_code: u16,
         duration_seconds: f64,
     ) {
+        let path = path_label(path);
         histogram!(
             "http_request_duration_seconds",
             "protocol" => protocol.to_string(),
             "method" => method.to_string(),
-            "path" => path.to_string(),
+            "path" => path.clone(),
             "status_code" => status_code.to_string(),
         )
         .record(duration_seconds);
@@ -298,7 +424,7 @@ impl RequestDurationHistogram {
             "http_requests_total",
             "protocol" => protocol.to_string(),
             "method" => method.to_string(),
-            "path" => path.to_string(),
+            "path" => path.clone(),
             "status_code" => status_code.to_string(),
         )
         .increment(1);
@@ -309,7 +435,7 @@ impl RequestDurationHistogram {
                 "http_request_errors_total",
                 "protocol" => protocol.to_string(),
                 "method" => method.to_string(),
-                "path" => path.to_string(),
+                "path" => path,
                 "status_code" => status_code.to_string(),
             )
             .increment(1);
@@ -433,21 +559,22 @@ pub struct TokenUsageMetrics;
 impl TokenUsageMetrics {
     /// Record token usage for a task.
     pub fn record(model: &str, input_tokens: u64, output_tokens: u64) {
+        let model = bounded_label("apex_tokens_total", model);
         counter!(
             "apex_tokens_total",
-            "model" => model.to_string(),
+            "model" => model.clone(),
         )
         .increment(input_tokens + output_tokens);
 
         counter!(
             "apex_tokens_input_total",
-            "model" => model.to_string(),
+            "model" => model.clone(),
         )
         .increment(input_tokens);
 
         counter!(
             "apex_tokens_output_total",
-            "model" => model.to_string(),
+            "model" => model.clone(),
         )
         .increment(output_tokens);
     }
@@ -462,7 +589,7 @@ impl CostMetrics {
         let microdollars = (cost_dollars * 1_000_000.0) as u64;
         counter!(
             "apex_cost_total_microdollars",
-            "model" => model.to_string(),
+            "model" => bounded_label("apex_cost_total_microdollars", model),
         )
         .increment(microdollars);
     }
@@ -471,12 +598,83 @@ impl CostMetrics {
     pub fn record_microdollars(model: &str, microdollars: u64) {
         counter!(
             "apex_cost_total_microdollars",
-            "model" => model.to_string(),
+            "model" => bounded_label("apex_cost_total_microdollars", model),
         )
         .increment(microdollars);
     }
 }
 
+/// DAG-level metrics recorded once per execution.
+///
+/// Labeled by DAG name rather than id so label cardinality stays bounded by
+/// the number of distinct pipelines, not the number of runs.
+pub struct DagMetrics;
+
+impl DagMetrics {
+    /// Record the outcome of a finished DAG execution.
+    pub fn record_execution(
+        dag_name: &str,
+        status: &str,
+        tasks_completed: u64,
+        tasks_failed: u64,
+        cost_dollars: f64,
+        duration_seconds: f64,
+    ) {
+        let dag_name = bounded_label("apex_dag", dag_name);
+        histogram!(
+            "apex_dag_duration_seconds",
+            "dag_name" => dag_name.clone(),
+            "status" => status.to_string(),
+        )
+        .record(duration_seconds);
+
+        counter!(
+            "apex_dag_tasks_total",
+            "dag_name" => dag_name.clone(),
+            "outcome" => "completed",
+        )
+        .increment(tasks_completed);
+
+        counter!(
+            "apex_dag_tasks_total",
+            "dag_name" => dag_name.clone(),
+            "outcome" => "failed",
+        )
+        .increment(tasks_failed);
+
+        histogram!(
+            "apex_dag_cost_dollars",
+            "dag_name" => dag_name.clone(),
+        )
+        .record(cost_dollars);
+    }
+}
+
+/// Cache effectiveness metrics, labeled by cache key type (`task`, `agent`, ...).
+pub struct CacheMetrics;
+
+impl CacheMetrics {
+    /// Record a cache hit.
+    pub fn record_hit(key_type: &str) {
+        counter!("apex_cache_hits_total", "key_type" => key_type.to_string()).increment(1);
+    }
+
+    /// Record a cache miss.
+    pub fn record_miss(key_type: &str) {
+        counter!("apex_cache_misses_total", "key_type" => key_type.to_string()).increment(1);
+    }
+
+    /// Record entries evicted to make room.
+    pub fn record_evictions(key_type: &str, count: u64) {
+        counter!("apex_cache_evictions_total", "key_type" => key_type.to_string()).increment(count);
+    }
+
+    /// Adjust the number of cached entries by `delta`.
+    pub fn adjust_entries(key_type: &str, delta: f64) {
+        gauge!("apex_cache_entries", "key_type" => key_type.to_string()).increment(delta);
+    }
+}
+
 /// Comprehensive business metrics collection.
 pub struct BusinessMetrics;
 
@@ -496,7 +694,7 @@ impl BusinessMetrics {
         counter!("apex_tasks_completed").increment(1);
 
         // Duration histogram
-        histogram!("apex_task_duration_seconds", "model" => model.to_string())
+        histogram!("apex_task_duration_seconds", "model" => bounded_label("apex_task_duration_seconds", model))
             .record(duration_seconds);
 
         // Token metrics
@@ -532,7 +730,7 @@ impl BusinessMetrics {
 
     /// Record an agent spawn.
     pub fn record_agent_spawned(agent_id: &str, model: &str) {
-        counter!("apex_agent_spawns_total", "model" => model.to_string()).increment(1);
+        counter!("apex_agent_spawns_total", "model" => bounded_label("apex_agent_spawns_total", model)).increment(1);
         gauge!("apex_active_agents").increment(1.0);
 
         tracing::debug!(
@@ -564,16 +762,17 @@ impl BusinessMetrics {
 
     /// Record a tool call.
     pub fn record_tool_call(tool_name: &str, duration_seconds: f64, success: bool) {
+        let tool = bounded_label("apex_tool", tool_name);
         counter!(
             "apex_tool_calls_total",
-            "tool" => tool_name.to_string(),
+            "tool" => tool.clone(),
             "success" => success.to_string(),
         )
         .increment(1);
 
         histogram!(
             "apex_tool_latency_seconds",
-            "tool" => tool_name.to_string(),
+            "tool" => tool,
         )
         .record(duration_seconds);
     }
@@ -606,7 +805,7 @@ impl BusinessMetrics {
     pub fn record_contract_violation(contract_id: &str, limit_type: &str) {
         counter!(
             "apex_contract_violations_total",
-            "contract_id" => contract_id.to_string(),
+            "contract_id" => bounded_label("apex_contract_violations_total", contract_id),
             "limit_type" => limit_type.to_string(),
         )
         .increment(1);
@@ -675,6 +874,36 @@ impl OperationTimer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+    /// Metrics recorded while `f` runs, as `(name, sorted labels, value)`.
+    fn capture(f: impl FnOnce()) -> Vec<(String, Vec<(String, String)>, DebugValue)> {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        metrics::with_local_recorder(&recorder, f);
+        snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| {
+                let (_, key) = key.into_parts();
+                let mut labels: Vec<_> = key.labels().map(|l| (l.key().to_string(), l.value().to_string())).collect();
+                labels.sort();
+                (key.name().to_string(), labels, value)
+            })
+            .collect()
+    }
+
+    /// The value recorded for `name` with exactly `labels`.
+    fn value_of<'a>(recorded: &'a [(String, Vec<(String, String)>, DebugValue)], name: &str, labels: &[(&str, &str)]) -> &'a DebugValue {
+        let mut labels: Vec<_> = labels.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        labels.sort();
+        recorded
+            .iter()
+            .find(|(n, l, _)| n == name && *l == labels)
+            .map(|(_, _, v)| v)
+            .unwrap_or_else(|| panic!("{} {:?} not recorded", name, labels))
+    }
 
     #[test]
     fn test_metrics_config_defaults() {
@@ -701,6 +930,67 @@ mod tests {
         assert!(duration.as_millis() >= 10);
     }
 
+    #[test]
+    fn test_dag_metrics_record_execution() {
+        // Recording without an installed recorder must be a no-op, not a panic.
+        DagMetrics::record_execution("nightly-report", "completed", 5, 1, 0.42, 12.5);
+
+        let recorded = capture(|| DagMetrics::record_execution("nightly-report", "completed", 5, 1, 0.42, 12.5));
+        let dag = ("dag_name", "nightly-report");
+        assert_eq!(
+            value_of(&recorded, "apex_dag_duration_seconds", &[dag, ("status", "completed")]),
+            &DebugValue::Histogram(vec![12.5.into()])
+        );
+        assert_eq!(value_of(&recorded, "apex_dag_tasks_total", &[dag, ("outcome", "completed")]), &DebugValue::Counter(5));
+        assert_eq!(value_of(&recorded, "apex_dag_tasks_total", &[dag, ("outcome", "failed")]), &DebugValue::Counter(1));
+        assert_eq!(value_of(&recorded, "apex_dag_cost_dollars", &[dag]), &DebugValue::Histogram(vec![0.42.into()]));
+    }
+
+    #[test]
+    fn test_cache_metrics() {
+        let record = || {
+            CacheMetrics::record_hit("task");
+            CacheMetrics::record_miss("task");
+            CacheMetrics::record_evictions("agent", 3);
+            CacheMetrics::adjust_entries("agent", 5.0);
+            CacheMetrics::adjust_entries("agent", -3.0);
+        };
+        // Without a recorder this is a no-op, not a panic
+        record();
+
+        let recorded = capture(record);
+        assert_eq!(value_of(&recorded, "apex_cache_hits_total", &[("key_type", "task")]), &DebugValue::Counter(1));
+        assert_eq!(value_of(&recorded, "apex_cache_misses_total", &[("key_type", "task")]), &DebugValue::Counter(1));
+        assert_eq!(value_of(&recorded, "apex_cache_evictions_total", &[("key_type", "agent")]), &DebugValue::Counter(3));
+        assert_eq!(value_of(&recorded, "apex_cache_entries", &[("key_type", "agent")]), &DebugValue::Gauge(2.0.into()));
+    }
+
+    #[test]
+    fn test_label_guard_caps_distinct_values() {
+        let guard = LabelGuard::new(2);
+        assert_eq!(guard.bound("tool_calls", "search"), "search");
+        assert_eq!(guard.bound("tool_calls", "fetch"), "fetch");
+        assert_eq!(guard.bound("tool_calls", "shell"), OVERFLOW_LABEL_VALUE);
+        // Admitted values keep their label; other metrics have their own budget
+        assert_eq!(guard.bound("tool_calls", "search"), "search");
+        assert_eq!(guard.bound("tokens", "shell"), "shell");
+
+        guard.set_max_values(3);
+        assert_eq!(guard.bound("tool_calls", "shell"), "shell");
+    }
+
+    #[test]
+    fn test_normalize_path() {
+        assert_eq!(
+            normalize_path("/api/v1/tasks/6f1c2d3e-1111-4222-8333-444455556666/status"),
+            "/api/v1/tasks/:id/status"
+        );
+        assert_eq!(normalize_path("/api/v1/agents/42"), "/api/v1/agents/:id");
+        assert_eq!(normalize_path("/blobs/0123456789abcdef0123"), "/blobs/:id");
+        assert_eq!(normalize_path("/api/v2/dags"), "/api/v2/dags");
+        assert_eq!(normalize_path("/"), "/");
+    }
+
     #[test]
     fn test_circuit_breaker_state() {
         assert_eq!(CircuitBreakerState::Closed, CircuitBreakerState::Closed);
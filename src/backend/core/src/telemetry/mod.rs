@@ -20,7 +20,7 @@ pub mod metrics;
 pub mod tracing;
 
 pub use logging::{
-    init_logging, LogFormat, LoggingConfig, RedactionConfig, RedactionPattern,
+    init_logging, init_redaction, LogFormat, LoggingConfig, RedactionConfig, RedactionPattern,
     SensitiveFieldRedactor,
 };
 pub use metrics::{
@@ -28,7 +28,7 @@ pub use metrics::{
     // Metric types
     ActiveConnectionsGauge, ErrorCounter, RequestDurationHistogram,
     // Business metrics
-    BusinessMetrics, TokenUsageMetrics, CostMetrics,
+    BusinessMetrics, TokenUsageMetrics, CostMetrics, DagMetrics, CacheMetrics,
 };
 pub use tracing::{
     init_tracing, shutdown_tracing, TracingConfig, SpanBuilder, TraceContext,
@@ -48,6 +48,8 @@ pub enum ValidationErrorKind {
     MaxItems { max: usize, actual: usize },
     /// Array/collection contains duplicate items.
     DuplicateItems,
+    /// JSON value does not conform to a schema; `keyword` is the failing schema keyword.
+    Schema { keyword: String },
     /// Nested validation failed.
     Nested,
     /// Custom validation failed.
@@ -90,6 +92,7 @@ impl fmt::Display for ValidationErrorKind {
                 write!(f, "must have at most {} items (got {})", max, actual)
             }
             Self::DuplicateItems => write!(f, "must not contain duplicate items"),
+            Self::Schema { keyword } => write!(f, "does not match schema ({})", keyword),
             Self::Nested => write!(f, "nested validation failed"),
             Self::Custom { code } => write!(f, "validation failed: {}", code),
         }
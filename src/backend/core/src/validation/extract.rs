@@ -0,0 +1,102 @@
+//! Axum extractors that validate their input before the handler runs.
+
+use axum::extract::{FromRequestParts, Query};
+use axum::http::request::Parts;
+use serde::de::DeserializeOwned;
+
+use super::error::{ValidationErrorKind, ValidationErrors};
+use super::validator::Validate;
+use crate::error::ApexError;
+
+/// Query string extractor that runs [`Validate`] over the deserialized
+/// struct.
+///
+/// A query string that doesn't deserialize (a negative or non-numeric
+/// `limit`, say) or fails validation is rejected with 422 and the messages
+/// per field under `errors`, so handlers only ever see parameters that are
+/// in range.
+///
+/// ```rust,ignore
+/// pub async fn list_agents(ValidatedQuery(query): ValidatedQuery<KeysetQuery>) -> impl IntoResponse {
+///     // query.effective_limit() is already within bounds
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ValidatedQuery<T>(pub T);
+
+#[axum::async_trait]
+impl<T, S> FromRequestParts<S> for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate + Send,
+    S: Send + Sync,
+{
+    type Rejection = ApexError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(value) = Query::<T>::from_request_parts(parts, state)
+            .await
+            .map_err(|rejection| {
+                let mut errors = ValidationErrors::new();
+                errors.add_with_message(
+                    "query",
+                    ValidationErrorKind::Custom { code: "invalid_query".to_string() },
+                    rejection.body_text(),
+                );
+                ApexError::from(errors)
+            })?;
+
+        value.validate()?;
+        Ok(ValidatedQuery(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCode;
+    use crate::validation::ValidationResult;
+    use axum::http::{Request, StatusCode};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct ListQuery {
+        limit: Option<u32>,
+    }
+
+    impl Validate for ListQuery {
+        fn validate(&self) -> ValidationResult<()> {
+            let mut errors = ValidationErrors::new();
+            if self.limit == Some(0) {
+                errors.add_error(
+                    "limit",
+                    ValidationErrorKind::MinValue { min: "1".to_string(), actual: "0".to_string() },
+                );
+            }
+            if errors.is_empty() { Ok(()) } else { Err(errors) }
+        }
+    }
+
+    async fn extract(uri: &str) -> Result<ValidatedQuery<ListQuery>, ApexError> {
+        let (mut parts, _) = Request::builder().uri(uri).body(()).unwrap().into_parts();
+        ValidatedQuery::<ListQuery>::from_request_parts(&mut parts, &()).await
+    }
+
+    #[tokio::test]
+    async fn test_valid_query_passes_through() {
+        let ValidatedQuery(query) = extract("/items?limit=10").await.unwrap();
+        assert_eq!(query.limit, Some(10));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_query_is_unprocessable() {
+        let err = extract("/items?limit=0").await.unwrap_err();
+        assert_eq!(err.code(), ErrorCode::ValidationError);
+        assert_eq!(err.http_status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(err.to_string().contains("limit"));
+
+        // Doesn't deserialize at all
+        let err = extract("/items?limit=-5").await.unwrap_err();
+        assert_eq!(err.http_status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(err.to_string().contains("query"));
+    }
+}
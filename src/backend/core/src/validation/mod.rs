@@ -10,6 +10,7 @@
 //!   - Custom regex patterns
 //!   - Collection constraints (min/max items, unique items)
 //!   - Set membership validation
+//!   - JSON Schema conformance for `serde_json::Value` fields
 //!
 //! - **Validators**: Traits and builders for sync and async validation
 //!   - `Validate` trait for synchronous validation
@@ -26,6 +27,9 @@
 //!
 //! - **Macros**: Helper macros for ergonomic validation code
 //!
+//! - **Extractors**: `ValidatedQuery<T>` validates query strings and answers
+//!   422 with the field errors before the handler runs
+//!
 //! # Quick Start
 //!
 //! ```rust,ignore
@@ -189,6 +193,7 @@
 //! ```
 
 pub mod error;
+pub mod extract;
 pub mod macros;
 pub mod rules;
 pub mod validator;
@@ -209,6 +214,7 @@ pub use rules::{
     Alphanumeric,
     Email,
     ExactLength,
+    JsonSchema,
     LengthRange,
     Max,
     MaxItems,
@@ -230,6 +236,7 @@ pub use rules::{
     ValidationRule,
     // Convenience functions
     validate_email,
+    validate_json_schema,
     validate_length,
     validate_pattern,
     validate_range,
@@ -249,6 +256,9 @@ pub use validator::{
 // Macros helper
 pub use macros::ValidationBuilder;
 
+// Extractors
+pub use extract::ValidatedQuery;
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Prelude
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -306,6 +316,15 @@ impl From<ValidationErrors> for ApexError {
     }
 }
 
+/// `422 Unprocessable Entity` with the messages for each field path under
+/// `errors`.
+impl axum::response::IntoResponse for ValidationErrors {
+    fn into_response(self) -> axum::response::Response {
+        let body = crate::api::ApiResponse::<()>::validation_failed(self.to_message_map());
+        (axum::http::StatusCode::UNPROCESSABLE_ENTITY, axum::Json(body)).into_response()
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Tests
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -7,6 +7,7 @@
 //! - Format validation (email, URL, UUID)
 //! - Custom regex pattern matching
 //! - Collection size constraints
+//! - JSON Schema conformance
 
 use crate::validation::error::{FieldError, ValidationErrorKind, ValidationErrors, ValidationResult};
 use regex::Regex;
@@ -736,6 +737,60 @@ impl<T: PartialEq + ToString> ValidationRule<Option<T>> for OneOf<T> {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// JSON Schema Rule
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Rule that validates a JSON value against a JSON Schema.
+///
+/// As a [`ValidationRule`] it reports the first violation only; use
+/// [`JsonSchema::validate_field`] to get every violation keyed by
+/// `field` + JSON pointer (e.g. `parameters/items/0`).
+#[derive(Debug)]
+pub struct JsonSchema {
+    validator: jsonschema::Validator,
+}
+
+impl JsonSchema {
+    /// Compile a schema. Fails if the schema itself is invalid.
+    pub fn new(schema: &serde_json::Value) -> Result<Self, String> {
+        jsonschema::validator_for(schema)
+            .map(|validator| Self { validator })
+            .map_err(|e| e.to_string())
+    }
+
+    /// Validate `value`, reporting each violation at its JSON pointer under `field`.
+    pub fn validate_field(&self, field: &str, value: &serde_json::Value) -> ValidationResult<()> {
+        let mut errors = ValidationErrors::new();
+        for error in self.validator.iter_errors(value) {
+            let path = format!("{}{}", field, error.instance_path);
+            errors.add(path, schema_error(&error));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn schema_error(error: &jsonschema::ValidationError<'_>) -> FieldError {
+    let schema_path = error.schema_path.to_string();
+    let keyword = schema_path.rsplit('/').next().unwrap_or_default().to_string();
+    FieldError::with_message(ValidationErrorKind::Schema { keyword }, error.to_string())
+}
+
+impl ValidationRule<serde_json::Value> for JsonSchema {
+    fn validate(&self, value: &serde_json::Value) -> Option<FieldError> {
+        self.validator.iter_errors(value).next().map(|e| schema_error(&e))
+    }
+
+    fn description(&self) -> String {
+        "conforms to JSON schema".to_string()
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Convenience Functions
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -832,6 +887,24 @@ pub fn validate_pattern(field: &str, value: &str, pattern: &Regex, description:
     }
 }
 
+/// Validate a JSON value against a JSON Schema.
+///
+/// An invalid schema is reported as a `schema` error on `field` itself.
+pub fn validate_json_schema(field: &str, value: &serde_json::Value, schema: &serde_json::Value) -> ValidationResult<()> {
+    match JsonSchema::new(schema) {
+        Ok(rule) => rule.validate_field(field, value),
+        Err(e) => {
+            let mut errors = ValidationErrors::new();
+            errors.add_with_message(
+                field,
+                ValidationErrorKind::Schema { keyword: "$schema".to_string() },
+                format!("invalid schema: {}", e),
+            );
+            Err(errors)
+        }
+    }
+}
+
 /// Validate numeric range.
 pub fn validate_range<T: PartialOrd + ToString>(
     field: &str,
@@ -882,6 +955,42 @@ mod tests {
         assert!(rule.validate(&"   ".to_string()).is_some());
     }
 
+    #[test]
+    fn test_json_schema_reports_pointer_paths() {
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "retries": { "type": "integer", "minimum": 0 },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["url"]
+        });
+        let rule = JsonSchema::new(&schema).unwrap();
+
+        assert!(rule.validate_field("parameters", &serde_json::json!({ "url": "x", "retries": 2 })).is_ok());
+
+        let errors = rule
+            .validate_field("parameters", &serde_json::json!({ "retries": -1, "tags": ["a", 3] }))
+            .unwrap_err();
+        assert!(errors.has_errors("parameters"));
+        assert!(errors.has_errors("parameters/retries"));
+        assert!(errors.has_errors("parameters/tags/1"));
+        assert_eq!(
+            errors.get("parameters/retries").unwrap()[0].kind,
+            ValidationErrorKind::Schema { keyword: "minimum".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_json_schema_rule() {
+        let rule = JsonSchema::new(&serde_json::json!({ "type": "string" })).unwrap();
+        assert!(rule.validate(&serde_json::json!("ok")).is_none());
+        assert!(rule.validate(&serde_json::json!(1)).is_some());
+
+        assert!(JsonSchema::new(&serde_json::json!({ "type": 12 })).is_err());
+        assert!(validate_json_schema("input", &serde_json::json!(1), &serde_json::json!({ "type": 12 })).is_err());
+    }
+
     #[test]
     fn test_min_length() {
         let rule = MinLength(3);
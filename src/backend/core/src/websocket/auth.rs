@@ -351,6 +351,7 @@ pub fn can_subscribe(claims: &Claims, room_type: &super::room::RoomType) -> bool
         RoomType::Metrics => claims.has_permission(permissions::METRICS_READ),
         RoomType::Approval => claims.has_permission(permissions::APPROVALS_MANAGE),
         RoomType::Error => claims.has_permission(permissions::ERRORS_READ),
+        RoomType::Organization => claims.org_id.is_some() || claims.has_permission(permissions::ADMIN),
         RoomType::Custom => claims.has_permission(permissions::ADMIN),
     }
 }
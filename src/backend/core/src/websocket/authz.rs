@@ -0,0 +1,163 @@
+//! Authorization of room subscriptions.
+//!
+//! Task, agent and DAG rooms carry one organization's updates, so joining
+//! one is checked against the connection's tenant before the membership is
+//! added. An organization's own room is open to members of that
+//! organization. The "all" rooms span every organization and are limited to
+//! connections that may see every tenant.
+
+use async_trait::async_trait;
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::auth::{permissions, Claims};
+use super::room::RoomId;
+use crate::dag::TaskId;
+use crate::db::Database;
+use crate::rbac::{OrganizationId, TenantScope};
+
+/// Decides whether a connection may join a room.
+#[async_trait]
+pub trait RoomAuthorizer: Send + Sync {
+    /// `Ok` if a connection holding `claims` may join `room`, otherwise the
+    /// reason it was refused.
+    async fn authorize(&self, claims: Option<&Claims>, room: &RoomId) -> Result<(), String>;
+}
+
+/// Tenant scope of a connection: admins see every organization, anyone else
+/// only the one in their token. `None` when there is nothing the connection
+/// may see.
+pub fn connection_scope(claims: Option<&Claims>) -> Option<TenantScope> {
+    let claims = claims?;
+    if claims.has_permission(permissions::ADMIN) {
+        return Some(TenantScope::All);
+    }
+    claims
+        .org_id
+        .as_deref()
+        .map(|org| TenantScope::Organization(OrganizationId::new(org)))
+}
+
+/// Checks tenant-owned rooms against the rows the connection's organization
+/// owns in the database.
+pub struct TenantRoomAuthorizer {
+    db: Arc<Database>,
+}
+
+impl TenantRoomAuthorizer {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait]
+impl RoomAuthorizer for TenantRoomAuthorizer {
+    async fn authorize(&self, claims: Option<&Claims>, room: &RoomId) -> Result<(), String> {
+        let (kind, id) = match room {
+            RoomId::Task(id) => ("task", id),
+            RoomId::Agent(id) => ("agent", id),
+            RoomId::Dag(id) => ("DAG", id),
+            RoomId::AllTasks | RoomId::AllAgents | RoomId::AllDags => {
+                return match connection_scope(claims) {
+                    Some(TenantScope::All) => Ok(()),
+                    _ => Err(format!("Subscribing to {} requires admin access", room.as_str())),
+                };
+            }
+            RoomId::Organization(org) => {
+                return match connection_scope(claims) {
+                    Some(TenantScope::All) => Ok(()),
+                    Some(TenantScope::Organization(own)) if own.as_str() == org => Ok(()),
+                    Some(_) => Err(format!("Not authorized to view organization {}", org)),
+                    None => Err("Authentication required".to_string()),
+                };
+            }
+            _ => return Ok(()),
+        };
+
+        let scope = connection_scope(claims).ok_or_else(|| "Authentication required".to_string())?;
+        if scope.is_all() {
+            return Ok(());
+        }
+
+        // A row owned by another organization is refused the same way as one
+        // that doesn't exist
+        let denied = || format!("Not authorized to view {} {}", kind, id);
+        let id = Uuid::parse_str(id).map_err(|_| denied())?;
+        let owned = match room {
+            RoomId::Task(_) => self.db.get_task(TaskId(id), &scope).await.map(|row| row.is_some()),
+            RoomId::Agent(_) => self.db.get_agent(id, &scope).await.map(|row| row.is_some()),
+            _ => self.db.get_dag(id, &scope).await.map(|row| row.is_some()),
+        };
+        match owned {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(denied()),
+            Err(e) => {
+                warn!(room = %room.as_str(), error = %e, "Could not check room ownership");
+                Err(denied())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::websocket::WebSocketAuth;
+
+    fn claims(permissions: &[&str], org_id: Option<&str>) -> Claims {
+        let auth = WebSocketAuth::new("test-secret".to_string(), 3600);
+        let token = auth
+            .generate_token(
+                "user-1",
+                permissions.iter().map(|p| p.to_string()).collect(),
+                org_id.map(String::from),
+            )
+            .unwrap();
+        auth.validate_token(&token.token).unwrap()
+    }
+
+    #[test]
+    fn test_connection_scope() {
+        assert_eq!(connection_scope(None), None);
+        assert_eq!(connection_scope(Some(&claims(&[], None))), None);
+        assert_eq!(
+            connection_scope(Some(&claims(&[permissions::TASKS_READ], Some("org-a")))),
+            Some(TenantScope::Organization(OrganizationId::new("org-a")))
+        );
+        assert_eq!(
+            connection_scope(Some(&claims(&[permissions::ADMIN], Some("org-a")))),
+            Some(TenantScope::All)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenant_rooms_need_ownership() {
+        // Never reachable: any ownership lookup fails and is refused
+        let db = Arc::new(Database::connect_lazy("postgres://apex@127.0.0.1:1/apex").unwrap());
+        let authorizer = TenantRoomAuthorizer::new(db);
+        let task = RoomId::Task(Uuid::new_v4().to_string());
+        let member = claims(&[permissions::TASKS_READ], Some("7c9e6679-7425-40de-944b-e07fc1f90ae7"));
+        let admin = claims(&[permissions::ADMIN], None);
+
+        assert!(authorizer.authorize(None, &task).await.is_err());
+        assert!(authorizer.authorize(Some(&member), &task).await.is_err());
+        assert!(authorizer.authorize(Some(&member), &RoomId::Task("not-a-uuid".into())).await.is_err());
+        assert!(authorizer.authorize(Some(&member), &RoomId::AllDags).await.is_err());
+        assert!(authorizer.authorize(Some(&admin), &task).await.is_ok());
+        assert!(authorizer.authorize(Some(&admin), &RoomId::AllDags).await.is_ok());
+
+        // A tenant joins its own organization's room, whatever form its id
+        // takes, and no other
+        let own = RoomId::Organization("7c9e6679-7425-40de-944b-e07fc1f90ae7".into());
+        assert!(authorizer.authorize(Some(&member), &own).await.is_ok());
+        let named = claims(&[permissions::TASKS_READ], Some("acme"));
+        assert!(authorizer.authorize(Some(&named), &RoomId::Organization("acme".into())).await.is_ok());
+        assert!(authorizer.authorize(Some(&named), &own).await.is_err());
+        assert!(authorizer.authorize(None, &own).await.is_err());
+        assert!(authorizer.authorize(Some(&admin), &own).await.is_ok());
+
+        // Rooms that aren't tenant-owned are left open
+        assert!(authorizer.authorize(Some(&member), &RoomId::Metrics).await.is_ok());
+    }
+}
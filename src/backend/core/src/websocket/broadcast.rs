@@ -5,6 +5,7 @@
 //! - Priority queues for important messages
 //! - Back-pressure handling
 //! - Statistics and monitoring
+//! - Fan-out to other API replicas through a [`BroadcastTransport`]
 
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -12,11 +13,14 @@ use std::sync::Arc;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
 use tokio::sync::{broadcast, RwLock};
-use tracing::debug;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+use uuid::Uuid;
 
 use super::handler::ConnectionId;
 use super::message::ServerMessage;
 use super::room::RoomId;
+use super::transport::{BroadcastTransport, InProcessTransport, RemoteBroadcast};
 
 /// Priority levels for broadcast messages.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -94,6 +98,9 @@ pub struct RoomSubscriber {
     pub receiver: broadcast::Receiver<BroadcastMessage>,
 }
 
+/// Room recorded on messages sent with [`Broadcaster::broadcast_global`].
+pub const GLOBAL_ROOM: &str = "global";
+
 /// The main broadcaster for efficient message distribution.
 pub struct Broadcaster {
     /// Per-room broadcast channels
@@ -109,6 +116,10 @@ pub struct Broadcaster {
     total_delivered: AtomicU64,
     total_failed: AtomicU64,
     broadcasts_by_priority: RwLock<HashMap<BroadcastPriority, u64>>,
+    /// Tells this broadcaster's messages apart on the transport
+    instance_id: Uuid,
+    /// Carries room broadcasts to other replicas
+    transport: Arc<dyn BroadcastTransport>,
 }
 
 impl Broadcaster {
@@ -125,7 +136,41 @@ impl Broadcaster {
             total_delivered: AtomicU64::new(0),
             total_failed: AtomicU64::new(0),
             broadcasts_by_priority: RwLock::new(HashMap::new()),
+            instance_id: Uuid::new_v4(),
+            transport: Arc::new(InProcessTransport),
+        }
+    }
+
+    /// Also publish untargeted room broadcasts on `transport`, for the
+    /// other replicas. Run [`Self::spawn_relay`] to receive theirs.
+    pub fn with_transport(mut self, transport: Arc<dyn BroadcastTransport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Deliver broadcasts other replicas publish on the transport to this
+    /// broadcaster's subscribers, until the transport stops delivering.
+    pub fn spawn_relay(self: &Arc<Self>) -> JoinHandle<()> {
+        let broadcaster = self.clone();
+        let mut remote = self.transport.subscribe();
+        tokio::spawn(async move {
+            while let Some(received) = remote.recv().await {
+                broadcaster.receive_remote(received).await;
+            }
+        })
+    }
+
+    /// Deliver a broadcast from the transport locally, unless this
+    /// broadcaster published it. Returns whether it was delivered.
+    pub async fn receive_remote(&self, received: RemoteBroadcast) -> bool {
+        if received.origin == self.instance_id {
+            return false;
         }
+        let mut broadcast_msg = BroadcastMessage::new(received.room_id, received.message);
+        broadcast_msg.id = self.next_message_id();
+        broadcast_msg.priority = self.determine_priority(&broadcast_msg.message);
+        self.deliver(broadcast_msg).await;
+        true
     }
 
     /// Get the next message ID.
@@ -181,6 +226,32 @@ impl Broadcaster {
         self.send_broadcast(broadcast_msg).await;
     }
 
+    /// Broadcast a message to a room of this replica only, e.g. for
+    /// snapshots of its own state.
+    pub async fn broadcast_local(&self, room_id: &RoomId, message: ServerMessage) {
+        let mut broadcast_msg = BroadcastMessage::new(room_id.clone(), message);
+        broadcast_msg.id = self.next_message_id();
+        broadcast_msg.priority = self.determine_priority(&broadcast_msg.message);
+
+        self.deliver(broadcast_msg).await;
+    }
+
+    /// Broadcast a message on the global channel only, which every
+    /// connection listens on, e.g. for server-wide notices.
+    pub async fn broadcast_global(&self, message: ServerMessage) {
+        let mut broadcast_msg = BroadcastMessage::new(RoomId::Custom(GLOBAL_ROOM.to_string()), message);
+        broadcast_msg.id = self.next_message_id();
+        broadcast_msg.priority = self.determine_priority(&broadcast_msg.message);
+        let priority = broadcast_msg.priority;
+
+        let delivered = self.global_channel.send(broadcast_msg).unwrap_or(0);
+        self.total_broadcasts.fetch_add(1, Ordering::Relaxed);
+        self.total_delivered.fetch_add(delivered as u64, Ordering::Relaxed);
+
+        let mut stats = self.broadcasts_by_priority.write().await;
+        *stats.entry(priority).or_insert(0) += 1;
+    }
+
     /// Broadcast a message with specific priority.
     pub async fn broadcast_with_priority(
         &self,
@@ -209,8 +280,26 @@ impl Broadcaster {
         self.send_broadcast(broadcast_msg).await;
     }
 
-    /// Send a broadcast message.
+    /// Send a broadcast message here and, unless it has specific targets,
+    /// to the other replicas.
     async fn send_broadcast(&self, msg: BroadcastMessage) {
+        let remote = msg.targets.is_none().then(|| RemoteBroadcast {
+            origin: self.instance_id,
+            room_id: msg.room_id.clone(),
+            message: msg.message.clone(),
+        });
+        self.deliver(msg).await;
+
+        if let Some(remote) = remote {
+            if let Err(e) = self.transport.publish(&remote).await {
+                self.total_failed.fetch_add(1, Ordering::Relaxed);
+                warn!(room = %remote.room_id.as_str(), error = %e, "Failed to publish broadcast to other replicas");
+            }
+        }
+    }
+
+    /// Deliver a broadcast message to this replica's subscribers.
+    async fn deliver(&self, msg: BroadcastMessage) {
         let room_id = msg.room_id.clone();
         let priority = msg.priority;
 
@@ -252,7 +341,9 @@ impl Broadcaster {
             ServerMessage::Error(_) => BroadcastPriority::High,
             ServerMessage::ApprovalRequired(_) => BroadcastPriority::High,
             ServerMessage::ApprovalResult { .. } => BroadcastPriority::High,
+            ServerMessage::QuotaWarning(_) => BroadcastPriority::High,
             ServerMessage::Closing { .. } => BroadcastPriority::Critical,
+            ServerMessage::ServerShutdown { .. } => BroadcastPriority::Critical,
             ServerMessage::Heartbeat { .. } => BroadcastPriority::Low,
             ServerMessage::Metrics(_) => BroadcastPriority::Low,
             _ => BroadcastPriority::Normal,
@@ -442,6 +533,81 @@ mod tests {
             .await;
     }
 
+    #[tokio::test]
+    async fn test_broadcast_global_skips_rooms() {
+        let broadcaster = Broadcaster::new(100);
+        let mut global = broadcaster.subscribe_global();
+        let mut room = broadcaster.subscribe_to_room(RoomId::Metrics).await;
+
+        broadcaster
+            .broadcast_global(ServerMessage::ServerShutdown { reconnect_after_ms: 1000 })
+            .await;
+
+        let received = global.try_recv().unwrap();
+        assert_eq!(received.priority, BroadcastPriority::Critical);
+        assert!(matches!(received.message, ServerMessage::ServerShutdown { reconnect_after_ms: 1000 }));
+        assert!(room.receiver.try_recv().is_err());
+        assert_eq!(broadcaster.get_stats().total_delivered, 1);
+    }
+
+    /// Stand-in for a shared pub/sub server: every subscriber sees every
+    /// publish, JSON-encoded like on Redis.
+    struct Bus(broadcast::Sender<String>);
+
+    #[async_trait::async_trait]
+    impl BroadcastTransport for Bus {
+        async fn publish(&self, broadcast: &RemoteBroadcast) -> crate::error::Result<()> {
+            let _ = self.0.send(serde_json::to_string(broadcast)?);
+            Ok(())
+        }
+
+        fn subscribe(&self) -> tokio::sync::mpsc::Receiver<RemoteBroadcast> {
+            let (tx, rx) = tokio::sync::mpsc::channel(16);
+            let mut published = self.0.subscribe();
+            tokio::spawn(async move {
+                while let Ok(json) = published.recv().await {
+                    if tx.send(serde_json::from_str(&json).unwrap()).await.is_err() {
+                        break;
+                    }
+                }
+            });
+            rx
+        }
+    }
+
+    #[tokio::test]
+    async fn test_room_broadcasts_reach_other_replicas() {
+        let bus: Arc<dyn BroadcastTransport> = Arc::new(Bus(broadcast::channel(16).0));
+        let replica = || Arc::new(Broadcaster::new(100).with_transport(bus.clone()));
+        let (a, b) = (replica(), replica());
+        a.spawn_relay();
+        b.spawn_relay();
+
+        let room_id = RoomId::Task("t-1".to_string());
+        let mut on_a = a.subscribe_to_room(room_id.clone()).await;
+        let mut on_b = b.subscribe_to_room(room_id.clone()).await;
+        let mut global_b = b.subscribe_global();
+
+        a.broadcast_global(ServerMessage::ServerShutdown { reconnect_after_ms: 0 }).await;
+        a.broadcast_local(&room_id, ServerMessage::Heartbeat { timestamp: 1 }).await;
+        a.broadcast_to_room(&room_id, ServerMessage::Heartbeat { timestamp: 2 }).await;
+
+        let received = tokio::time::timeout(std::time::Duration::from_secs(1), on_b.receiver.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(received.message, ServerMessage::Heartbeat { timestamp: 2 }));
+        // Global and local-only broadcasts stay on their replica
+        assert!(matches!(global_b.try_recv().unwrap().message, ServerMessage::Heartbeat { timestamp: 2 }));
+        assert!(global_b.try_recv().is_err());
+
+        // The sender delivers its own broadcasts once, not again off the bus
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        assert!(matches!(on_a.receiver.try_recv().unwrap().message, ServerMessage::Heartbeat { timestamp: 1 }));
+        assert!(matches!(on_a.receiver.try_recv().unwrap().message, ServerMessage::Heartbeat { timestamp: 2 }));
+        assert!(on_a.receiver.try_recv().is_err());
+    }
+
     #[tokio::test]
     async fn test_cleanup_empty_channels() {
         let broadcaster = Broadcaster::new(100);
@@ -2,25 +2,32 @@
 
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
-        Query, State,
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        ConnectInfo, Query, State,
     },
-    response::IntoResponse,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
 };
 use chrono::{DateTime, Utc};
+use futures::stream::SplitSink;
 use futures::{SinkExt, StreamExt};
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::net::IpAddr;
+use std::error::Error as _;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
-use tokio::time::{interval, Duration, Instant};
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::{interval, interval_at, Duration, Instant};
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
-use super::auth::{AuthError, Claims};
-use super::message::{ClientMessage, ServerMessage};
+use super::auth::{permissions, AuthError, Claims};
+use super::reconnect::{ConnectLimiter, ReconnectGuard};
+use super::broadcast::BroadcastMessage;
+use super::message::{ClientMessage, PresenceChange, ServerMessage, SubscriptionTarget};
 use super::room::RoomId;
 use super::session::{self, WebSocketSession};
 use super::{WebSocketConfig, WebSocketState};
@@ -71,6 +78,8 @@ pub struct WebSocketConnection {
     pub connected_at: DateTime<Utc>,
     pub last_activity: Instant,
     pub last_ping: Option<Instant>,
+    /// Last time the client answered a ping (starts at connect time)
+    pub last_pong: Instant,
     pub ip_address: Option<IpAddr>,
     pub user_agent: Option<String>,
     pub messages_sent: u64,
@@ -91,6 +100,7 @@ impl WebSocketConnection {
             connected_at: Utc::now(),
             last_activity: Instant::now(),
             last_ping: None,
+            last_pong: Instant::now(),
             ip_address: None,
             user_agent: None,
             messages_sent: 0,
@@ -134,6 +144,10 @@ pub struct WebSocketHandler {
     connections: RwLock<HashMap<ConnectionId, WebSocketConnection>>,
     /// Connection count by IP for rate limiting
     connections_by_ip: RwLock<HashMap<IpAddr, usize>>,
+    /// Backoff enforced on sessions that reconnect too quickly
+    reconnects: ReconnectGuard,
+    /// Rate of other connections per client IP
+    connects: ConnectLimiter,
     /// Configuration
     config: WebSocketConfig,
     /// Statistics
@@ -148,6 +162,14 @@ impl WebSocketHandler {
         Self {
             connections: RwLock::new(HashMap::new()),
             connections_by_ip: RwLock::new(HashMap::new()),
+            reconnects: ReconnectGuard::new(
+                Duration::from_millis(config.reconnection_backoff_ms),
+                config.max_reconnection_attempts,
+            ),
+            connects: ConnectLimiter::new(
+                config.max_connections_per_ip as u32,
+                Duration::from_millis(config.reconnection_backoff_ms),
+            ),
             config,
             total_connections: AtomicU64::new(0),
             total_disconnections: AtomicU64::new(0),
@@ -156,12 +178,49 @@ impl WebSocketHandler {
         }
     }
 
+    /// Whether `ip` already holds `max_connections_per_ip` connections (0 = unlimited).
+    pub async fn is_ip_at_limit(&self, ip: IpAddr) -> bool {
+        self.config.max_connections_per_ip > 0
+            && self.connections_by_ip.read().await.get(&ip).copied().unwrap_or(0)
+                >= self.config.max_connections_per_ip
+    }
+
+    /// Record a connection attempt, returning how long the client must back
+    /// off if it came back too soon.
+    ///
+    /// A `session_id` the server issued is held to its exponential backoff.
+    /// Any other attempt, with no session id or one the client made up,
+    /// takes from its IP's bucket of `max_connections_per_ip` connections,
+    /// refilled one per `reconnection_backoff_ms`. An attempt with neither
+    /// can't be attributed and is let through.
+    pub fn check_reconnect(&self, session_id: Option<&str>, ip: Option<IpAddr>) -> Result<(), Duration> {
+        match (session_id, ip) {
+            (Some(session_id), _) if self.reconnects.is_tracked(session_id) => self.reconnects.check(session_id),
+            (_, Some(ip)) => self.connects.check(ip),
+            (_, None) => Ok(()),
+        }
+    }
+
+    /// Retry hint for closes caused by overload or rate limiting.
+    pub fn retry_after(&self) -> Duration {
+        Duration::from_millis(self.config.reconnection_backoff_ms)
+    }
+
+    /// Number of active connections from `ip`.
+    pub async fn connections_for_ip(&self, ip: IpAddr) -> usize {
+        self.connections_by_ip.read().await.get(&ip).copied().unwrap_or(0)
+    }
+
     /// Register a new connection.
+    ///
+    /// The connection is counted against `ip` until it is unregistered.
     pub async fn register_connection(
         &self,
-        conn: WebSocketConnection,
+        mut conn: WebSocketConnection,
         ip: Option<IpAddr>,
     ) -> Result<(), &'static str> {
+        conn.ip_address = ip;
+
         // Check IP rate limit
         if let Some(ip) = ip {
             let mut by_ip = self.connections_by_ip.write().await;
@@ -176,6 +235,7 @@ impl WebSocketHandler {
         }
 
         let conn_id = conn.id;
+        self.reconnects.track(&conn.session_id);
         self.connections.write().await.insert(conn_id, conn);
         self.total_connections.fetch_add(1, Ordering::Relaxed);
 
@@ -219,6 +279,7 @@ impl WebSocketHandler {
                 connected_at: c.connected_at,
                 last_activity: c.last_activity,
                 last_ping: c.last_ping,
+                last_pong: c.last_pong,
                 ip_address: c.ip_address,
                 user_agent: c.user_agent.clone(),
                 messages_sent: c.messages_sent,
@@ -260,6 +321,45 @@ impl WebSocketHandler {
         }
     }
 
+    /// Whether a connection authenticated with the admin permission.
+    pub async fn is_admin(&self, conn_id: ConnectionId) -> bool {
+        self.connections
+            .read()
+            .await
+            .get(&conn_id)
+            .and_then(|c| c.claims.as_ref())
+            .is_some_and(|c| c.has_permission(permissions::ADMIN))
+    }
+
+    /// Tell the other members of a room that `changed` joined or left.
+    ///
+    /// The changed connection's ID is only revealed to admin connections.
+    pub async fn send_presence_change(
+        &self,
+        room_id: &RoomId,
+        changed: ConnectionId,
+        change: PresenceChange,
+        count: usize,
+    ) {
+        let connections = self.connections.read().await;
+
+        for conn in connections.values() {
+            if conn.id == changed || !conn.subscriptions.contains(room_id) {
+                continue;
+            }
+            let is_admin = conn.claims.as_ref().is_some_and(|c| c.has_permission(permissions::ADMIN));
+            let message = ServerMessage::PresenceChanged {
+                room: room_id.clone(),
+                count,
+                change,
+                connection_id: is_admin.then(|| changed.to_string()),
+            };
+            if conn.sender.try_send(message).is_ok() {
+                self.messages_sent.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
     /// Update connection state after authentication.
     pub async fn authenticate_connection(
         &self,
@@ -328,24 +428,194 @@ impl WebSocketHandler {
         }
     }
 
-    /// Check for stale connections and remove them.
-    pub async fn cleanup_stale_connections(&self) {
+    /// Record that a ping was sent to a connection.
+    pub async fn record_ping(&self, conn_id: ConnectionId) {
+        if let Some(conn) = self.connections.write().await.get_mut(&conn_id) {
+            conn.last_ping = Some(Instant::now());
+        }
+    }
+
+    /// Record that a connection answered a ping.
+    pub async fn record_pong(&self, conn_id: ConnectionId) {
+        if let Some(conn) = self.connections.write().await.get_mut(&conn_id) {
+            conn.last_pong = Instant::now();
+            conn.update_activity();
+        }
+    }
+
+    /// Whether a connection has gone `connection_timeout_secs` without a pong.
+    ///
+    /// Unknown connections count as expired so their loops wind down.
+    pub async fn is_heartbeat_expired(&self, conn_id: ConnectionId) -> bool {
+        let timeout = Duration::from_secs(self.config.connection_timeout_secs);
+        self.connections
+            .read()
+            .await
+            .get(&conn_id)
+            .map(|c| c.last_pong.elapsed() > timeout)
+            .unwrap_or(true)
+    }
+
+    /// Remove connections that missed pongs past the timeout.
+    ///
+    /// Returns the evicted IDs so callers can drop their room memberships.
+    pub async fn cleanup_stale_connections(&self) -> Vec<ConnectionId> {
         let timeout = Duration::from_secs(self.config.connection_timeout_secs);
         let mut to_remove = Vec::new();
 
         {
             let connections = self.connections.read().await;
             for (id, conn) in connections.iter() {
-                if conn.last_activity.elapsed() > timeout {
+                if conn.last_pong.elapsed() > timeout {
+                    let _ = conn.sender.try_send(ServerMessage::Closing {
+                        reason: "Heartbeat timeout".to_string(),
+                        code: close_code::AWAY,
+                        retry_after_ms: None,
+                    });
                     to_remove.push(*id);
                 }
             }
         }
+        self.reconnects.prune();
+        self.connects.prune();
 
-        for conn_id in to_remove {
+        for conn_id in &to_remove {
             warn!(connection_id = %conn_id, "Removing stale connection");
-            self.unregister_connection(conn_id).await;
+            self.unregister_connection(*conn_id).await;
         }
+
+        to_remove
+    }
+}
+
+/// Forward queued messages to the socket and send a protocol ping every `ping_every`.
+pub fn spawn_outgoing(
+    mut ws_sender: SplitSink<WebSocket, Message>,
+    mut rx: mpsc::Receiver<ServerMessage>,
+    handler: Arc<WebSocketHandler>,
+    conn_id: ConnectionId,
+    ping_every: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ping_timer = interval_at(Instant::now() + ping_every, ping_every);
+        loop {
+            tokio::select! {
+                msg = rx.recv() => {
+                    let Some(msg) = msg else { break };
+                    if let Some(json) = encode_outgoing(&msg, handler.config.max_message_size) {
+                        if ws_sender.send(Message::Text(json)).await.is_err() {
+                            break;
+                        }
+                    }
+                    let close = match &msg {
+                        ServerMessage::ServerShutdown { .. } => Some((close_code::AWAY, "Server shutting down".to_string())),
+                        ServerMessage::Closing { reason, code, retry_after_ms } => {
+                            Some((*code, close_reason(reason, *retry_after_ms)))
+                        }
+                        _ => None,
+                    };
+                    if let Some((code, reason)) = close {
+                        let _ = ws_sender
+                            .send(Message::Close(Some(CloseFrame { code, reason: reason.into() })))
+                            .await;
+                        break;
+                    }
+                }
+                _ = ping_timer.tick() => {
+                    if ws_sender.send(Message::Ping(Vec::new())).await.is_err() {
+                        break;
+                    }
+                    handler.record_ping(conn_id).await;
+                }
+            }
+        }
+    })
+}
+
+/// `msg` as JSON, or `None` if it can't be serialized or is longer than
+/// `max_message_size` (0 = unlimited); an oversized message is logged and
+/// dropped rather than sent to clients that would reject it.
+pub fn encode_outgoing(msg: &ServerMessage, max_message_size: usize) -> Option<String> {
+    let json = serde_json::to_string(msg).ok()?;
+    if max_message_size > 0 && json.len() > max_message_size {
+        warn!(size = json.len(), limit = max_message_size, "Dropping outgoing WebSocket message over the size limit");
+        return None;
+    }
+    Some(json)
+}
+
+/// Cap incoming messages and frames at `max_message_size` (0 = unlimited),
+/// so an oversized one fails to read instead of being buffered.
+pub fn limit_message_size(ws: WebSocketUpgrade, max_message_size: usize) -> WebSocketUpgrade {
+    if max_message_size == 0 {
+        return ws;
+    }
+    ws.max_message_size(max_message_size).max_frame_size(max_message_size)
+}
+
+/// The close notice for a connection whose read failed, if the failure was
+/// an incoming message over the size limit: close code 1009 (message too
+/// big) naming the limit.
+pub fn oversized_message_close(error: &axum::Error, max_message_size: usize) -> Option<ServerMessage> {
+    use tokio_tungstenite::tungstenite::Error as WsError;
+
+    let too_big = matches!(error.source()?.downcast_ref::<WsError>()?, WsError::Capacity(_));
+    too_big.then(|| ServerMessage::Closing {
+        reason: format!("Message exceeds the {} byte limit", max_message_size),
+        code: close_code::SIZE,
+        retry_after_ms: None,
+    })
+}
+
+/// Close frame reason, with the retry hint appended for clients that only
+/// see the frame.
+pub fn close_reason(reason: &str, retry_after_ms: Option<u64>) -> String {
+    match retry_after_ms {
+        Some(ms) => format!("{}; retry_after_ms={}", reason, ms),
+        None => reason.to_string(),
+    }
+}
+
+/// Refuse a connection before it is registered, telling the client how long
+/// to wait before trying again.
+pub async fn reject_with_retry(socket: &mut WebSocket, reason: &str, retry_after: Duration) {
+    let retry_after_ms = Some(retry_after.as_millis() as u64);
+    let closing = ServerMessage::Closing {
+        reason: reason.to_string(),
+        code: close_code::AGAIN,
+        retry_after_ms,
+    };
+    if let Ok(json) = serde_json::to_string(&closing) {
+        let _ = socket.send(Message::Text(json)).await;
+    }
+    let _ = socket
+        .send(Message::Close(Some(CloseFrame {
+            code: close_code::AGAIN,
+            reason: close_reason(reason, retry_after_ms).into(),
+        })))
+        .await;
+}
+
+/// How long a connection waits for its outgoing task to flush the shutdown
+/// notice and close frame before aborting it.
+pub const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The shutdown notice carried by a global broadcast, if that's what it is.
+///
+/// A lagged receiver is treated as no notice; a closed global channel means
+/// the broadcaster is gone, which only happens on shutdown.
+pub fn shutdown_notice(
+    received: Result<BroadcastMessage, broadcast::error::RecvError>,
+) -> Option<ServerMessage> {
+    match received {
+        Ok(broadcast_msg) => match broadcast_msg.message {
+            msg @ ServerMessage::ServerShutdown { .. } => Some(msg),
+            _ => None,
+        },
+        Err(broadcast::error::RecvError::Lagged(_)) => None,
+        Err(broadcast::error::RecvError::Closed) => Some(ServerMessage::ServerShutdown {
+            reconnect_after_ms: WebSocketConfig::default().reconnection_backoff_ms,
+        }),
     }
 }
 
@@ -356,25 +626,75 @@ pub struct WsQueryParams {
     pub session_id: Option<String>,
 }
 
+/// A 429 for an upgrade from an IP that already holds
+/// `max_connections_per_ip` connections, before the handshake completes.
+pub async fn check_ip_limit(handler: &WebSocketHandler, ip: Option<IpAddr>) -> Option<Response> {
+    let ip = ip?;
+    if !handler.is_ip_at_limit(ip).await {
+        return None;
+    }
+    warn!(ip = %ip, limit = handler.config.max_connections_per_ip, "WebSocket upgrade rejected: per-IP connection limit reached");
+    let retry_after = handler.retry_after();
+    Some((
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after.as_secs().max(1).to_string())],
+        Json(serde_json::json!({
+            "success": false,
+            "error": "Too many WebSocket connections from this IP",
+            "error_code": "WS_CONNECTION_LIMIT",
+            "retry_after_ms": retry_after.as_millis() as u64,
+        })),
+    )
+        .into_response())
+}
+
 /// Handle WebSocket upgrade request.
+///
+/// Upgrades from an IP that already holds `max_connections_per_ip` connections
+/// are rejected with 429 before the handshake completes. The IP is the peer
+/// address, or the `X-Forwarded-For` client when the peer is a trusted proxy.
 pub async fn ws_upgrade_handler(
     ws: WebSocketUpgrade,
     Query(params): Query<WsQueryParams>,
     State(state): State<Arc<WebSocketState>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_websocket(socket, params, state))
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+) -> Response {
+    let ip = state.config.trusted_proxies.client_ip(&headers, connect_info.map(|ConnectInfo(addr)| addr));
+    if let Some(rejection) = check_ip_limit(&state.handler, ip).await {
+        return rejection;
+    }
+
+    let ws = limit_message_size(ws, state.config.max_message_size);
+    ws.on_upgrade(move |socket| handle_websocket(socket, params, state, ip))
 }
 
 /// Handle an individual WebSocket connection.
 async fn handle_websocket(
-    socket: WebSocket,
+    mut socket: WebSocket,
     params: WsQueryParams,
     state: Arc<WebSocketState>,
+    ip: Option<IpAddr>,
 ) {
+    // Re-check the per-IP limit at registration; concurrent upgrades can race past the pre-check.
+    if let Some(ip) = ip {
+        if state.handler.is_ip_at_limit(ip).await {
+            reject_with_retry(&mut socket, "Too many connections from this IP", state.handler.retry_after()).await;
+            return;
+        }
+    }
+
+    // Refuse clients that reconnect faster than their backoff allows
+    if let Err(retry_after) = state.handler.check_reconnect(params.session_id.as_deref(), ip) {
+        warn!(session_id = ?params.session_id, ip = ?ip, retry_after_ms = retry_after.as_millis() as u64, "Reconnect refused: too soon");
+        reject_with_retry(&mut socket, "Reconnecting too fast", retry_after).await;
+        return;
+    }
+
     let (mut ws_sender, mut ws_receiver) = socket.split();
 
     // Create channel for outgoing messages
-    let (tx, mut rx) = mpsc::channel::<ServerMessage>(100);
+    let (tx, rx) = mpsc::channel::<ServerMessage>(100);
 
     // Create connection
     let mut connection = WebSocketConnection::new(tx.clone());
@@ -407,11 +727,8 @@ async fn handle_websocket(
                         }
                     }
 
-                    // Restore subscriptions
-                    let room_ids = session::strings_to_room_ids(&session.subscribed_rooms);
-                    for room_id in &room_ids {
-                        connection.add_subscription(room_id.clone());
-                    }
+                    // Subscriptions are restored once the connection is
+                    // registered, after each room is re-authorized
 
                     recovered_session = Some(session);
                 }
@@ -445,8 +762,14 @@ async fn handle_websocket(
     }
 
     // Register connection
-    if let Err(e) = state.handler.register_connection(connection, None).await {
+    if let Err(e) = state.handler.register_connection(connection, ip).await {
         error!(error = %e, "Failed to register connection");
+        let _ = ws_sender
+            .send(Message::Close(Some(CloseFrame {
+                code: close_code::AGAIN,
+                reason: e.into(),
+            })))
+            .await;
         return;
     }
 
@@ -455,6 +778,8 @@ async fn handle_websocket(
         connection_id: conn_id.to_string(),
         server_time: Utc::now(),
         session_id: session_id.clone(),
+        reconnect_backoff_ms: state.config.reconnection_backoff_ms,
+        max_reconnect_attempts: state.config.max_reconnection_attempts,
     };
 
     if let Ok(json) = serde_json::to_string(&connected_msg) {
@@ -466,7 +791,7 @@ async fn handle_websocket(
 
     // If we recovered a session, re-join rooms and replay missed messages
     if let Some(ref session) = recovered_session {
-        let room_ids = session::strings_to_room_ids(&session.subscribed_rooms);
+        let room_ids = state.authorized_rooms(conn_id, session::strings_to_room_ids(&session.subscribed_rooms)).await;
 
         // Re-join rooms in the room manager
         {
@@ -525,23 +850,27 @@ async fn handle_websocket(
     let heartbeat_interval = Duration::from_secs(state.config.heartbeat_interval_secs);
     let mut heartbeat_timer = interval(heartbeat_interval);
 
-    // Message forwarding task (outgoing)
-    let forward_handle = {
-        let mut ws_sender = ws_sender;
-        tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                if let Ok(json) = serde_json::to_string(&msg) {
-                    if ws_sender.send(Message::Text(json)).await.is_err() {
-                        break;
-                    }
-                }
-            }
-        })
-    };
+    // Message forwarding task (outgoing), also sends protocol pings
+    let mut forward_handle = spawn_outgoing(ws_sender, rx, state.handler.clone(), conn_id, heartbeat_interval);
+
+    // Server-wide notices such as shutdown
+    let mut global_rx = state.broadcaster.subscribe_global();
+    // Set once a close notice is queued, so the outgoing task gets to flush it
+    let mut closing = false;
 
     // Main message loop
     loop {
         tokio::select! {
+            // Server shutdown: forward the notice, the outgoing task closes the socket
+            received = global_rx.recv() => {
+                if let Some(notice) = shutdown_notice(received) {
+                    info!(connection_id = %conn_id, "Server shutting down, closing connection");
+                    let _ = tx.send(notice).await;
+                    closing = true;
+                    break;
+                }
+            }
+
             // Incoming message from client
             msg = ws_receiver.next() => {
                 match msg {
@@ -557,12 +886,21 @@ async fn handle_websocket(
                         };
                         let _ = tx.send(pong_msg).await;
                     }
+                    Some(Ok(Message::Pong(_))) => {
+                        state.handler.record_pong(conn_id).await;
+                    }
                     Some(Ok(Message::Close(_))) => {
                         info!(connection_id = %conn_id, "Client requested close");
                         break;
                     }
                     Some(Err(e)) => {
-                        error!(connection_id = %conn_id, error = %e, "WebSocket error");
+                        if let Some(notice) = oversized_message_close(&e, state.config.max_message_size) {
+                            warn!(connection_id = %conn_id, error = %e, "Incoming message over the size limit, closing");
+                            let _ = tx.try_send(notice);
+                            closing = true;
+                        } else {
+                            error!(connection_id = %conn_id, error = %e, "WebSocket error");
+                        }
                         break;
                     }
                     None => {
@@ -575,6 +913,16 @@ async fn handle_websocket(
 
             // Heartbeat timer
             _ = heartbeat_timer.tick() => {
+                if state.handler.is_heartbeat_expired(conn_id).await {
+                    warn!(connection_id = %conn_id, "No pong within timeout, disconnecting");
+                    let _ = tx.try_send(ServerMessage::Closing {
+                        reason: "Heartbeat timeout".to_string(),
+                        code: close_code::AWAY,
+                        retry_after_ms: None,
+                    });
+                    closing = true;
+                    break;
+                }
                 let heartbeat = ServerMessage::Heartbeat {
                     timestamp: Utc::now().timestamp_millis(),
                 };
@@ -616,12 +964,14 @@ async fn handle_websocket(
         }
     }
 
+    if closing {
+        let _ = tokio::time::timeout(SHUTDOWN_FLUSH_TIMEOUT, &mut forward_handle).await;
+    }
     forward_handle.abort();
     state.handler.unregister_connection(conn_id).await;
 
     // Remove from all rooms
-    let mut room_manager = state.room_manager.write().await;
-    room_manager.remove_connection_from_all(conn_id);
+    state.leave_all_rooms(conn_id).await;
 
     info!(connection_id = %conn_id, "WebSocket connection closed");
 }
@@ -686,11 +1036,17 @@ async fn handle_client_message(
         ClientMessage::Subscribe { target } => {
             let room_id: RoomId = (&target).into();
 
+            if let Err(reason) = state.authorize_subscription(conn_id, &room_id).await {
+                debug!(connection_id = %conn_id, room = %room_id.as_str(), %reason, "Subscription denied");
+                let _ = tx.send(ServerMessage::SubscribeDenied { target, reason }).await;
+                return;
+            }
+
             // Add to room
-            {
+            let joined = {
                 let mut room_manager = state.room_manager.write().await;
-                room_manager.join_room(conn_id, room_id.clone());
-            }
+                room_manager.join_room(conn_id, room_id.clone())
+            };
 
             // Add subscription to connection
             if let Err(e) = state.handler.add_subscription(conn_id, room_id.clone()).await {
@@ -698,6 +1054,10 @@ async fn handle_client_message(
                 return;
             }
 
+            if joined {
+                state.notify_presence_change(&room_id, conn_id, PresenceChange::Joined).await;
+            }
+
             // Fetch current state from session store for the subscribed room
             let current_state = if let Some(ref sm) = state.session_manager {
                 match sm.get_missed_messages(&room_id.as_str(), 0).await {
@@ -725,10 +1085,10 @@ async fn handle_client_message(
             let room_id: RoomId = (&target).into();
 
             // Remove from room
-            {
+            let left = {
                 let mut room_manager = state.room_manager.write().await;
-                room_manager.leave_room(conn_id, &room_id);
-            }
+                room_manager.leave_room(conn_id, &room_id)
+            };
 
             // Remove subscription from connection
             if let Err(e) = state.handler.remove_subscription(conn_id, &room_id).await {
@@ -736,6 +1096,10 @@ async fn handle_client_message(
                 return;
             }
 
+            if left {
+                state.notify_presence_change(&room_id, conn_id, PresenceChange::Left).await;
+            }
+
             let response = ServerMessage::Unsubscribed { target };
             let _ = tx.send(response).await;
 
@@ -743,6 +1107,7 @@ async fn handle_client_message(
         }
 
         ClientMessage::Ping { timestamp } => {
+            state.handler.record_pong(conn_id).await;
             let response = ServerMessage::Pong {
                 client_timestamp: timestamp,
                 server_timestamp: Utc::now().timestamp_millis(),
@@ -819,7 +1184,7 @@ async fn handle_client_message(
                             .or(session.last_seen_event_id)
                             .unwrap_or(0);
 
-                        let room_ids = session::strings_to_room_ids(&session.subscribed_rooms);
+                        let room_ids = state.authorized_rooms(conn_id, session::strings_to_room_ids(&session.subscribed_rooms)).await;
                         let mut all_missed = Vec::new();
 
                         for room_id in &room_ids {
@@ -899,7 +1264,7 @@ async fn handle_client_message(
                             }
                         }
 
-                        let room_ids = session::strings_to_room_ids(&session.subscribed_rooms);
+                        let room_ids = state.authorized_rooms(conn_id, session::strings_to_room_ids(&session.subscribed_rooms)).await;
                         let mut total_missed: usize = 0;
 
                         for room_id in &room_ids {
@@ -960,6 +1325,18 @@ async fn handle_client_message(
                 debug!("Session manager not configured, cannot restore session");
             }
         }
+
+        ClientMessage::QueryPresence { room } => {
+            // Only connections that may join a room may see who is in it
+            if let Err(reason) = state.authorize_subscription(conn_id, &room).await {
+                debug!(connection_id = %conn_id, room = %room.as_str(), %reason, "Presence query denied");
+                if let Some(target) = SubscriptionTarget::for_room(&room) {
+                    let _ = tx.send(ServerMessage::SubscribeDenied { target, reason }).await;
+                }
+                return;
+            }
+            let _ = tx.send(state.presence(&room, conn_id).await).await;
+        }
     }
 }
 
@@ -967,6 +1344,45 @@ async fn handle_client_message(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_oversized_outgoing_message_dropped() {
+        let heartbeat = ServerMessage::Heartbeat { timestamp: 0 };
+        let size = serde_json::to_string(&heartbeat).unwrap().len();
+        assert!(encode_outgoing(&heartbeat, size).is_some());
+        assert!(encode_outgoing(&heartbeat, 0).is_some());
+        assert!(encode_outgoing(&heartbeat, size - 1).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_oversized_incoming_message_closes_connection() {
+        use tokio_tungstenite::tungstenite::{protocol::frame::coding::CloseCode, Message as WsMessage};
+
+        let state = Arc::new(WebSocketState::new(WebSocketConfig {
+            max_message_size: 1024,
+            ..Default::default()
+        }));
+        let app = axum::Router::new()
+            .route("/ws", axum::routing::get(ws_upgrade_handler))
+            .with_state(state);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await });
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://{}/ws", addr)).await.unwrap();
+        client.send(WsMessage::Text("x".repeat(2048))).await.unwrap();
+
+        let mut close = None;
+        while let Some(Ok(msg)) = client.next().await {
+            if let WsMessage::Close(frame) = msg {
+                close = frame;
+                break;
+            }
+        }
+        let close = close.expect("server sent a close frame");
+        assert_eq!(close.code, CloseCode::Size);
+        assert_eq!(close.reason, "Message exceeds the 1024 byte limit");
+    }
+
     #[test]
     fn test_connection_id() {
         let id1 = ConnectionId::new();
@@ -974,6 +1390,202 @@ mod tests {
         assert_ne!(id1, id2);
     }
 
+    #[test]
+    fn test_shutdown_notice() {
+        let notice = |message| Ok(BroadcastMessage::new(RoomId::Metrics, message));
+
+        assert!(matches!(
+            shutdown_notice(notice(ServerMessage::ServerShutdown { reconnect_after_ms: 10 })),
+            Some(ServerMessage::ServerShutdown { reconnect_after_ms: 10 })
+        ));
+        assert!(shutdown_notice(notice(ServerMessage::Heartbeat { timestamp: 0 })).is_none());
+        assert!(shutdown_notice(Err(broadcast::error::RecvError::Lagged(3))).is_none());
+        assert!(shutdown_notice(Err(broadcast::error::RecvError::Closed)).is_some());
+    }
+
+    fn connection() -> WebSocketConnection {
+        let (tx, _rx) = mpsc::channel(1);
+        WebSocketConnection::new(tx)
+    }
+
+    #[tokio::test]
+    async fn test_missed_pong_evicts_connection() {
+        let handler = WebSocketHandler::new(WebSocketConfig {
+            connection_timeout_secs: 90,
+            ..Default::default()
+        });
+        let mut stale = connection();
+        stale.last_pong = Instant::now() - Duration::from_secs(91);
+        let stale_id = stale.id;
+        let live = connection();
+        let live_id = live.id;
+        handler.register_connection(stale, None).await.unwrap();
+        handler.register_connection(live, None).await.unwrap();
+
+        assert!(handler.is_heartbeat_expired(stale_id).await);
+        assert!(!handler.is_heartbeat_expired(live_id).await);
+
+        assert_eq!(handler.cleanup_stale_connections().await, vec![stale_id]);
+        let stats = handler.get_stats().await;
+        assert_eq!(stats.active_connections, 1);
+        assert_eq!(stats.total_disconnections, 1);
+        assert!(handler.is_heartbeat_expired(stale_id).await);
+    }
+
+    #[tokio::test]
+    async fn test_pong_resets_heartbeat_deadline() {
+        let handler = WebSocketHandler::new(WebSocketConfig::default());
+        let mut conn = connection();
+        conn.last_pong = Instant::now() - Duration::from_secs(120);
+        let id = conn.id;
+        handler.register_connection(conn, None).await.unwrap();
+
+        assert!(handler.is_heartbeat_expired(id).await);
+        handler.record_pong(id).await;
+        assert!(!handler.is_heartbeat_expired(id).await);
+    }
+
+    #[tokio::test]
+    async fn test_per_ip_connection_limit() {
+        let handler = WebSocketHandler::new(WebSocketConfig {
+            max_connections_per_ip: 2,
+            ..Default::default()
+        });
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        let other: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let first = connection();
+        let first_id = first.id;
+        handler.register_connection(first, Some(ip)).await.unwrap();
+        handler.register_connection(connection(), Some(ip)).await.unwrap();
+
+        assert!(handler.is_ip_at_limit(ip).await);
+        assert!(handler.register_connection(connection(), Some(ip)).await.is_err());
+        assert!(handler.register_connection(connection(), Some(other)).await.is_ok());
+
+        // Disconnecting frees a slot
+        handler.unregister_connection(first_id).await;
+        assert_eq!(handler.connections_for_ip(ip).await, 1);
+        assert!(handler.register_connection(connection(), Some(ip)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_zero_connection_limit_is_unlimited() {
+        let handler = WebSocketHandler::new(WebSocketConfig {
+            max_connections_per_ip: 0,
+            ..Default::default()
+        });
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+
+        for _ in 0..50 {
+            handler.register_connection(connection(), Some(ip)).await.unwrap();
+        }
+        assert!(!handler.is_ip_at_limit(ip).await);
+    }
+
+    #[tokio::test]
+    async fn test_ip_limit_rejects_upgrade() {
+        let handler = WebSocketHandler::new(WebSocketConfig {
+            max_connections_per_ip: 1,
+            ..Default::default()
+        });
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+        handler.register_connection(connection(), Some(ip)).await.unwrap();
+
+        let rejection = check_ip_limit(&handler, Some(ip)).await.unwrap();
+        assert_eq!(rejection.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(rejection.headers().contains_key(header::RETRY_AFTER));
+        assert!(check_ip_limit(&handler, Some("10.0.0.2".parse().unwrap())).await.is_none());
+        assert!(check_ip_limit(&handler, None).await.is_none());
+    }
+
+    #[test]
+    fn test_reconnect_backoff_follows_config() {
+        let handler = WebSocketHandler::new(WebSocketConfig {
+            reconnection_backoff_ms: 500,
+            max_reconnection_attempts: 2,
+            ..Default::default()
+        });
+
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+        handler.reconnects.track("s-1");
+        assert_eq!(handler.check_reconnect(Some("s-1"), Some(ip)), Err(Duration::from_millis(1000)));
+        assert_eq!(handler.check_reconnect(Some("s-1"), None), Err(Duration::from_millis(2000)));
+        assert_eq!(handler.check_reconnect(Some("s-1"), Some(ip)), Err(Duration::from_millis(2000)));
+        assert_eq!(handler.retry_after(), Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_untracked_connects_share_a_per_ip_burst() {
+        let handler = WebSocketHandler::new(WebSocketConfig {
+            max_connections_per_ip: 3,
+            reconnection_backoff_ms: 60_000,
+            ..Default::default()
+        });
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+
+        // A second tab, or a second user behind the same NAT, gets in
+        assert!(handler.check_reconnect(None, Some(ip)).is_ok());
+        assert!(handler.check_reconnect(None, Some(ip)).is_ok());
+        // Made-up session ids draw from the same bucket
+        assert!(handler.check_reconnect(Some("made-up"), Some(ip)).is_ok());
+        assert!(handler.check_reconnect(Some("made-up-too"), Some(ip)).is_err());
+        assert!(handler.check_reconnect(None, Some("203.0.113.8".parse().unwrap())).is_ok());
+        // Nothing to attribute the attempt to
+        assert!(handler.check_reconnect(None, None).is_ok());
+
+        // The session a registered connection was issued is held to its backoff
+        let conn = connection();
+        let session_id = conn.session_id.clone();
+        handler.register_connection(conn, Some(ip)).await.unwrap();
+        assert_eq!(handler.check_reconnect(Some(&session_id), Some(ip)), Err(Duration::from_secs(120)));
+    }
+
+    /// Refuses every room.
+    struct DenyAll;
+
+    #[async_trait::async_trait]
+    impl super::super::RoomAuthorizer for DenyAll {
+        async fn authorize(&self, _claims: Option<&Claims>, room: &RoomId) -> Result<(), String> {
+            Err(format!("Not authorized to view {}", room.as_str()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_presence_query_requires_room_authorization() {
+        let state = Arc::new(WebSocketState::with_defaults().with_room_authorizer(Arc::new(DenyAll)));
+        let (tx, mut rx) = mpsc::channel(4);
+        let conn = WebSocketConnection::new(tx.clone());
+        let id = conn.id;
+        state.handler.register_connection(conn, None).await.unwrap();
+        let room = RoomId::Dag("d-1".to_string());
+        state.room_manager.write().await.join_room(ConnectionId::new(), room.clone());
+
+        let query = serde_json::json!({ "type": "query_presence", "room": room }).to_string();
+        handle_client_message(&query, id, &state, &tx).await;
+        match rx.recv().await.unwrap() {
+            ServerMessage::SubscribeDenied { target, reason } => {
+                assert_eq!(target, SubscriptionTarget::Dag { id: "d-1".to_string() });
+                assert_eq!(reason, "Not authorized to view dag:d-1");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_closing_carries_retry_hint() {
+        let closing = ServerMessage::Closing {
+            reason: "Client too slow".to_string(),
+            code: 1008,
+            retry_after_ms: Some(1000),
+        };
+        let json = serde_json::to_value(&closing).unwrap();
+        assert_eq!(json["type"], "closing");
+        assert_eq!(json["retry_after_ms"], 1000);
+        assert_eq!(close_reason("Client too slow", Some(1000)), "Client too slow; retry_after_ms=1000");
+        assert_eq!(close_reason("Heartbeat timeout", None), "Heartbeat timeout");
+    }
+
     #[tokio::test]
     async fn test_handler_creation() {
         let config = WebSocketConfig::default();
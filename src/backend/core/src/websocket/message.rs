@@ -6,6 +6,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::room::RoomId;
+use crate::contracts::QuotaResource;
 
 // ═══════════════════════════════════════════════════════════════════════════════
 // Client Messages (Client -> Server)
@@ -55,6 +56,11 @@ pub enum ClientMessage {
         session_id: String,
         last_event_id: Option<i64>,
     },
+
+    /// Ask how many connections are in a room
+    QueryPresence {
+        room: RoomId,
+    },
 }
 
 /// Target resource for subscriptions.
@@ -90,6 +96,9 @@ pub enum SubscriptionTarget {
 
     /// Subscribe to error notifications
     Errors,
+
+    /// Subscribe to notices for an organization (its own, unless admin)
+    Organization { id: String },
 }
 
 fn default_metrics_interval() -> u64 {
@@ -108,10 +117,31 @@ impl From<&SubscriptionTarget> for RoomId {
             SubscriptionTarget::Metrics { .. } => RoomId::Metrics,
             SubscriptionTarget::Approvals => RoomId::Approvals,
             SubscriptionTarget::Errors => RoomId::Errors,
+            SubscriptionTarget::Organization { id } => RoomId::Organization(id.clone()),
         }
     }
 }
 
+impl SubscriptionTarget {
+    /// The target whose subscription joins `room`; `None` for custom rooms,
+    /// which clients can't subscribe to.
+    pub fn for_room(room: &RoomId) -> Option<Self> {
+        Some(match room {
+            RoomId::Task(id) => SubscriptionTarget::Task { id: id.clone() },
+            RoomId::AllTasks => SubscriptionTarget::AllTasks,
+            RoomId::Agent(id) => SubscriptionTarget::Agent { id: id.clone() },
+            RoomId::AllAgents => SubscriptionTarget::AllAgents,
+            RoomId::Dag(id) => SubscriptionTarget::Dag { id: id.clone() },
+            RoomId::AllDags => SubscriptionTarget::AllDags,
+            RoomId::Metrics => SubscriptionTarget::Metrics { interval_secs: default_metrics_interval() },
+            RoomId::Approvals => SubscriptionTarget::Approvals,
+            RoomId::Errors => SubscriptionTarget::Errors,
+            RoomId::Organization(id) => SubscriptionTarget::Organization { id: id.clone() },
+            RoomId::Custom(_) => return None,
+        })
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Server Messages (Server -> Client)
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -125,6 +155,12 @@ pub enum ServerMessage {
         connection_id: String,
         server_time: DateTime<Utc>,
         session_id: String,
+        /// Base delay for the client's exponential reconnect backoff
+        #[serde(default)]
+        reconnect_backoff_ms: u64,
+        /// Doublings of the base delay before the backoff stops growing
+        #[serde(default)]
+        max_reconnect_attempts: u32,
     },
 
     /// Authentication result
@@ -146,6 +182,12 @@ pub enum ServerMessage {
         current_state: Option<serde_json::Value>,
     },
 
+    /// Subscription refused: the connection may not view the target
+    SubscribeDenied {
+        target: SubscriptionTarget,
+        reason: String,
+    },
+
     /// Unsubscription confirmed
     Unsubscribed {
         target: SubscriptionTarget,
@@ -172,6 +214,9 @@ pub enum ServerMessage {
     /// Approval required notification
     ApprovalRequired(ApprovalRequest),
 
+    /// An organization is close to a monthly quota
+    QuotaWarning(QuotaWarning),
+
     /// Approval result notification
     ApprovalResult {
         request_id: String,
@@ -205,13 +250,50 @@ pub enum ServerMessage {
         timestamp: i64,
     },
 
-    /// Connection will close
+    /// Connection will close; a close frame with the same code follows
     Closing {
         reason: String,
         code: u16,
+        /// Set when the close is due to overload or rate limiting: wait at
+        /// least this long before reconnecting
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        retry_after_ms: Option<u64>,
+    },
+
+    /// The server is shutting down; a close frame follows. Reconnect after
+    /// the delay, backing off if the server isn't back yet.
+    ServerShutdown {
+        reconnect_after_ms: u64,
+    },
+
+    /// Answer to a presence query
+    Presence {
+        room: RoomId,
+        count: usize,
+        /// Only included for admin connections
+        #[serde(skip_serializing_if = "Option::is_none")]
+        connection_ids: Option<Vec<String>>,
+    },
+
+    /// A connection joined or left a room the client is in
+    PresenceChanged {
+        room: RoomId,
+        count: usize,
+        change: PresenceChange,
+        /// Only included for admin connections
+        #[serde(skip_serializing_if = "Option::is_none")]
+        connection_id: Option<String>,
     },
 }
 
+/// Direction of a presence change.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceChange {
+    Joined,
+    Left,
+}
+
 impl ServerMessage {
     /// Get a unique message ID for tracking.
     pub fn message_type(&self) -> &'static str {
@@ -220,6 +302,7 @@ impl ServerMessage {
             Self::Authenticated { .. } => "authenticated",
             Self::AuthenticationFailed { .. } => "authentication_failed",
             Self::Subscribed { .. } => "subscribed",
+            Self::SubscribeDenied { .. } => "subscribe_denied",
             Self::Unsubscribed { .. } => "unsubscribed",
             Self::Pong { .. } => "pong",
             Self::TaskUpdate(_) => "task_update",
@@ -227,6 +310,7 @@ impl ServerMessage {
             Self::DagUpdate(_) => "dag_update",
             Self::Metrics(_) => "metrics",
             Self::ApprovalRequired(_) => "approval_required",
+            Self::QuotaWarning(_) => "quota_warning",
             Self::ApprovalResult { .. } => "approval_result",
             Self::Error(_) => "error",
             Self::Reconnected { .. } => "reconnected",
@@ -234,6 +318,9 @@ impl ServerMessage {
             Self::MissedUpdates { .. } => "missed_updates",
             Self::Heartbeat { .. } => "heartbeat",
             Self::Closing { .. } => "closing",
+            Self::ServerShutdown { .. } => "server_shutdown",
+            Self::Presence { .. } => "presence",
+            Self::PresenceChanged { .. } => "presence_changed",
         }
     }
 
@@ -330,6 +417,7 @@ pub struct DagUpdate {
 pub enum DagStatusUpdate {
     Pending,
     Running,
+    Paused,
     Completed,
     Failed,
     PartiallyCompleted,
@@ -439,6 +527,20 @@ pub struct ApprovalResponse {
     pub modified_params: Option<serde_json::Value>,
 }
 
+/// Soft-limit warning for an organization's monthly quota.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaWarning {
+    pub organization_id: String,
+    pub resource: QuotaResource,
+    pub used: f64,
+    pub limit: f64,
+    /// `used / limit`
+    pub utilization: f64,
+    /// When the quota resets
+    pub period_end: DateTime<Utc>,
+    pub timestamp: DateTime<Utc>,
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // Error Types
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -8,13 +8,38 @@
 //! - WebSocket authentication
 //! - Efficient broadcasting to many clients
 //! - Graceful disconnection and reconnection support
+//! - Broadcasts across API replicas ([`BroadcastTransport`])
+//!
+//! ## Reconnection contract
+//!
+//! The server tells clients how to back off rather than leaving it to each
+//! client library:
+//!
+//! - `connected` carries `reconnect_backoff_ms` and `max_reconnect_attempts`.
+//!   After an unexpected disconnect, wait
+//!   `reconnect_backoff_ms * 2^attempt` (plus jitter) before reconnecting,
+//!   where `attempt` stops growing at `max_reconnect_attempts`. Pass the
+//!   previous `session_id` to restore subscriptions.
+//! - `closing` with `retry_after_ms` (overload, rate limiting) and
+//!   `server_shutdown` with `reconnect_after_ms` set the minimum wait before
+//!   the next attempt. Close frames repeat the hint in their reason as
+//!   `retry_after_ms=<n>` for clients that only see the frame.
+//! - Reconnects sooner than their current backoff are refused with close
+//!   code 1013 and a doubled `retry_after_ms` ([`ReconnectGuard`]), tracked
+//!   per server-issued `session_id`. Other connections are rate limited per
+//!   client IP ([`ConnectLimiter`]) after a burst of `max_connections_per_ip`,
+//!   also with 1013; per-IP limits answer upgrades with 429 and a
+//!   `Retry-After` header.
 
 mod handler;
 mod message;
 mod room;
 mod broadcast;
 mod auth;
+mod authz;
+mod reconnect;
 mod session;
+mod transport;
 
 pub use handler::{
     WebSocketHandler,
@@ -26,10 +51,16 @@ pub use handler::{
 pub use message::{
     ClientMessage,
     ServerMessage,
+    PresenceChange,
     SubscriptionTarget,
     MetricsSnapshot,
+    AgentMetrics,
+    TaskMetrics,
+    ResourceMetrics,
+    SystemMetrics,
     ApprovalRequest,
     ApprovalResponse,
+    QuotaWarning,
     TaskUpdate,
     AgentUpdate,
     DagUpdate,
@@ -37,10 +68,23 @@ pub use message::{
 pub use room::{Room, RoomId, RoomManager, RoomType};
 pub use broadcast::{Broadcaster, BroadcastMessage, BroadcastStats};
 pub use auth::{WebSocketAuth, AuthToken, AuthError, Claims};
+pub use authz::{connection_scope, RoomAuthorizer, TenantRoomAuthorizer};
+pub use reconnect::{ConnectLimiter, ReconnectGuard};
 pub use session::{SessionManager, WebSocketSession};
+pub use transport::{
+    BroadcastTransport,
+    BroadcastTransportKind,
+    InProcessTransport,
+    RedisTransport,
+    RemoteBroadcast,
+    REDIS_CHANNEL,
+};
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::middleware::TrustedProxies;
 
 /// Configuration for WebSocket connections.
 #[derive(Debug, Clone)]
@@ -49,20 +93,28 @@ pub struct WebSocketConfig {
     pub heartbeat_interval_secs: u64,
     /// Connection timeout after missed heartbeats
     pub connection_timeout_secs: u64,
-    /// Maximum message size in bytes
+    /// Largest message in bytes either way (0 = unlimited); a bigger
+    /// incoming one closes the connection with 1009, a bigger outgoing one
+    /// is dropped
     pub max_message_size: usize,
     /// Maximum connections per IP (0 = unlimited)
     pub max_connections_per_ip: usize,
     /// Enable compression
     pub enable_compression: bool,
-    /// Maximum reconnection attempts
+    /// Doublings of the reconnect backoff before it stops growing
     pub max_reconnection_attempts: u32,
-    /// Reconnection backoff multiplier
+    /// Base reconnect backoff; also the retry hint sent on overload closes
     pub reconnection_backoff_ms: u64,
+    /// Interval between metrics snapshots pushed to the metrics room
+    pub metrics_interval_secs: u64,
     /// JWT secret for authentication
     pub jwt_secret: String,
     /// Token expiration in seconds
     pub token_expiration_secs: u64,
+    /// How long shutdown waits for connections to close after the notice
+    pub shutdown_grace_secs: u64,
+    /// Proxies whose `X-Forwarded-For` names the client for the per-IP limit
+    pub trusted_proxies: TrustedProxies,
 }
 
 impl Default for WebSocketConfig {
@@ -75,12 +127,18 @@ impl Default for WebSocketConfig {
             enable_compression: true,
             max_reconnection_attempts: 5,
             reconnection_backoff_ms: 1000,
+            metrics_interval_secs: 5,
             jwt_secret: "change-me-in-production".to_string(),
             token_expiration_secs: 3600,
+            shutdown_grace_secs: 5,
+            trusted_proxies: TrustedProxies::default(),
         }
     }
 }
 
+/// Messages buffered per broadcast channel.
+const BROADCAST_CAPACITY: usize = 1024;
+
 /// Shared state for the WebSocket system.
 pub struct WebSocketState {
     /// WebSocket handler for connection management
@@ -93,6 +151,8 @@ pub struct WebSocketState {
     pub auth: Arc<WebSocketAuth>,
     /// Session manager for persistence and recovery
     pub session_manager: Option<Arc<SessionManager>>,
+    /// Checks room subscriptions; without one every room may be joined
+    pub room_authorizer: Option<Arc<dyn RoomAuthorizer>>,
     /// Configuration
     pub config: WebSocketConfig,
 }
@@ -104,7 +164,7 @@ impl WebSocketState {
             config.jwt_secret.clone(),
             config.token_expiration_secs,
         ));
-        let broadcaster = Arc::new(Broadcaster::new(1024)); // 1024 message buffer
+        let broadcaster = Arc::new(Broadcaster::new(BROADCAST_CAPACITY));
         let room_manager = Arc::new(RwLock::new(RoomManager::new()));
         let handler = Arc::new(WebSocketHandler::new(config.clone()));
 
@@ -114,6 +174,7 @@ impl WebSocketState {
             broadcaster,
             auth,
             session_manager: None,
+            room_authorizer: None,
             config,
         }
     }
@@ -129,6 +190,42 @@ impl WebSocketState {
         self
     }
 
+    /// Share room broadcasts with other replicas over `transport`. Call
+    /// [`Broadcaster::spawn_relay`] on the result's broadcaster to receive them.
+    pub fn with_broadcast_transport(mut self, transport: Arc<dyn BroadcastTransport>) -> Self {
+        self.broadcaster = Arc::new(Broadcaster::new(BROADCAST_CAPACITY).with_transport(transport));
+        self
+    }
+
+    /// Check every subscription with `authorizer` before joining the room.
+    pub fn with_room_authorizer(mut self, authorizer: Arc<dyn RoomAuthorizer>) -> Self {
+        self.room_authorizer = Some(authorizer);
+        self
+    }
+
+    /// Whether `conn_id` may join `room`, judged on the claims the
+    /// connection authenticated with.
+    pub async fn authorize_subscription(&self, conn_id: ConnectionId, room: &RoomId) -> Result<(), String> {
+        let Some(authorizer) = &self.room_authorizer else {
+            return Ok(());
+        };
+        let claims = self.handler.get_connection(conn_id).await.and_then(|c| c.claims);
+        authorizer.authorize(claims.as_ref(), room).await
+    }
+
+    /// The subset of `rooms` that `conn_id` may join, for restoring a
+    /// session's subscriptions.
+    pub async fn authorized_rooms(&self, conn_id: ConnectionId, rooms: Vec<RoomId>) -> Vec<RoomId> {
+        let mut allowed = Vec::with_capacity(rooms.len());
+        for room in rooms {
+            match self.authorize_subscription(conn_id, &room).await {
+                Ok(()) => allowed.push(room),
+                Err(reason) => warn!(connection_id = %conn_id, room = %room.as_str(), %reason, "Dropping restored subscription"),
+            }
+        }
+        allowed
+    }
+
     /// Broadcast a task update to all subscribed clients.
     pub async fn broadcast_task_update(&self, update: TaskUpdate) {
         let room_id = RoomId::Task(update.task_id.clone());
@@ -150,11 +247,13 @@ impl WebSocketState {
         self.broadcaster.broadcast_to_room(&room_id, message).await;
     }
 
-    /// Broadcast metrics to all subscribed clients.
+    /// Broadcast metrics to all subscribed clients of this replica; every
+    /// replica sends its own.
     pub async fn broadcast_metrics(&self, metrics: MetricsSnapshot) {
         let room_id = RoomId::Metrics;
         let message = ServerMessage::Metrics(metrics);
-        self.broadcaster.broadcast_to_room(&room_id, message).await;
+        self.handler.send_to_room(&room_id, message.clone()).await;
+        self.broadcaster.broadcast_local(&room_id, message).await;
     }
 
     /// Send an approval request to relevant clients.
@@ -164,6 +263,119 @@ impl WebSocketState {
         self.broadcaster.broadcast_to_room(&room_id, message).await;
     }
 
+    /// Tell every connection the server is shutting down and wait up to
+    /// `shutdown_grace_secs` for them to close.
+    ///
+    /// Each connection forwards a [`ServerMessage::ServerShutdown`] asking
+    /// the client to reconnect after `reconnection_backoff_ms`, then sends a
+    /// close frame. Returns how many connections were still open when the
+    /// wait ended.
+    pub async fn shutdown(&self) -> usize {
+        let open = self.handler.get_stats().await.active_connections;
+        info!(connections = open, "Closing WebSocket connections for shutdown");
+        self.broadcaster
+            .broadcast_global(ServerMessage::ServerShutdown {
+                reconnect_after_ms: self.config.reconnection_backoff_ms,
+            })
+            .await;
+
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(self.config.shutdown_grace_secs);
+        loop {
+            let remaining = self.handler.get_stats().await.active_connections;
+            if remaining == 0 || tokio::time::Instant::now() >= deadline {
+                return remaining;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+    }
+
+    /// Tell the approvals channel how `approver` decided a request.
+    pub async fn send_approval_decided(
+        &self,
+        request_id: String,
+        approved: bool,
+        approver: String,
+        comment: Option<String>,
+    ) {
+        let room_id = RoomId::Approvals;
+        let message = ServerMessage::ApprovalResult {
+            request_id,
+            approved,
+            approver: Some(approver),
+            comment,
+        };
+        self.broadcaster.broadcast_to_room(&room_id, message).await;
+    }
+
+    /// Tell the approvals channel that a request expired and was denied.
+    pub async fn send_approval_expired(&self, request_id: String) {
+        let room_id = RoomId::Approvals;
+        let message = ServerMessage::ApprovalResult {
+            request_id,
+            approved: false,
+            approver: Some("system".to_string()),
+            comment: Some("Approval expired".to_string()),
+        };
+        self.broadcaster.broadcast_to_room(&room_id, message).await;
+    }
+
+    /// Warn an organization's room that it is close to a quota.
+    pub async fn send_quota_warning(&self, warning: QuotaWarning) {
+        let room_id = RoomId::Organization(warning.organization_id.clone());
+        let message = ServerMessage::QuotaWarning(warning);
+        self.broadcaster.broadcast_to_room(&room_id, message).await;
+    }
+
+    /// Build a presence answer for `room`. Connection IDs are only listed for admin requesters.
+    pub async fn presence(&self, room: &RoomId, requester: ConnectionId) -> ServerMessage {
+        let members = self.room_manager.read().await.get_room_members(room);
+        let connection_ids = if self.handler.is_admin(requester).await {
+            Some(members.iter().map(|id| id.to_string()).collect())
+        } else {
+            None
+        };
+
+        ServerMessage::Presence {
+            room: room.clone(),
+            count: members.len(),
+            connection_ids,
+        }
+    }
+
+    /// Broadcast a join/leave delta to the remaining members of `room`.
+    pub async fn notify_presence_change(&self, room: &RoomId, changed: ConnectionId, change: PresenceChange) {
+        let count = self
+            .room_manager
+            .read()
+            .await
+            .get_room(room)
+            .map_or(0, |r| r.member_count());
+        self.handler.send_presence_change(room, changed, change, count).await;
+    }
+
+    /// Remove a connection from every room, notifying the remaining members.
+    pub async fn leave_all_rooms(&self, conn_id: ConnectionId) {
+        let rooms = {
+            let mut room_manager = self.room_manager.write().await;
+            let rooms = room_manager.get_connection_rooms(conn_id);
+            room_manager.remove_connection_from_all(conn_id);
+            rooms
+        };
+
+        for room in &rooms {
+            self.notify_presence_change(room, conn_id, PresenceChange::Left).await;
+        }
+    }
+
+    /// Evict connections that missed heartbeats and drop their room memberships.
+    pub async fn evict_stale_connections(&self) -> usize {
+        let evicted = self.handler.cleanup_stale_connections().await;
+        for conn_id in &evicted {
+            self.leave_all_rooms(*conn_id).await;
+        }
+        evicted.len()
+    }
+
     /// Get connection statistics.
     pub async fn get_stats(&self) -> WebSocketStats {
         let handler_stats = self.handler.get_stats().await;
@@ -215,6 +427,200 @@ mod tests {
         assert_eq!(config.max_message_size, 1024 * 1024);
     }
 
+    async fn register(state: &WebSocketState, admin: bool) -> (ConnectionId, tokio::sync::mpsc::Receiver<ServerMessage>) {
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let conn = WebSocketConnection::new(tx);
+        let id = conn.id;
+        state.handler.register_connection(conn, None).await.unwrap();
+        if admin {
+            let token = state.auth.generate_token("root", vec![auth::permissions::ADMIN.to_string()], None).unwrap();
+            let claims = state.auth.validate_token(&token.token).unwrap();
+            state.handler.authenticate_connection(id, claims).await.unwrap();
+        }
+        (id, rx)
+    }
+
+    async fn join(state: &WebSocketState, id: ConnectionId, room: &RoomId) {
+        state.room_manager.write().await.join_room(id, room.clone());
+        state.handler.add_subscription(id, room.clone()).await.unwrap();
+        state.notify_presence_change(room, id, PresenceChange::Joined).await;
+    }
+
+    /// Admits admins only.
+    struct AdminOnly;
+
+    #[async_trait::async_trait]
+    impl RoomAuthorizer for AdminOnly {
+        async fn authorize(&self, claims: Option<&Claims>, _room: &RoomId) -> Result<(), String> {
+            match connection_scope(claims) {
+                Some(crate::rbac::TenantScope::All) => Ok(()),
+                _ => Err("admins only".to_string()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription_authorized_by_connection_claims() {
+        let state = WebSocketState::with_defaults().with_room_authorizer(Arc::new(AdminOnly));
+        let room = RoomId::Task("t-1".to_string());
+        let (viewer, _rx1) = register(&state, false).await;
+        let (admin, _rx2) = register(&state, true).await;
+
+        assert_eq!(state.authorize_subscription(viewer, &room).await, Err("admins only".to_string()));
+        assert!(state.authorize_subscription(admin, &room).await.is_ok());
+        assert_eq!(state.authorized_rooms(viewer, vec![room.clone()]).await, vec![]);
+        assert_eq!(state.authorized_rooms(admin, vec![room.clone()]).await, vec![room]);
+    }
+
+    #[tokio::test]
+    async fn test_presence_count_and_admin_gated_ids() {
+        let state = WebSocketState::with_defaults();
+        let room = RoomId::Task("t-1".to_string());
+        let (viewer, _rx1) = register(&state, false).await;
+        let (admin, _rx2) = register(&state, true).await;
+        join(&state, viewer, &room).await;
+        join(&state, admin, &room).await;
+
+        match state.presence(&room, viewer).await {
+            ServerMessage::Presence { count, connection_ids, .. } => {
+                assert_eq!(count, 2);
+                assert!(connection_ids.is_none());
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        match state.presence(&room, admin).await {
+            ServerMessage::Presence { connection_ids: Some(ids), .. } => assert_eq!(ids.len(), 2),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_presence_deltas_reach_existing_members() {
+        let state = WebSocketState::with_defaults();
+        let room = RoomId::Dag("d-1".to_string());
+        let (first, mut first_rx) = register(&state, false).await;
+        let (second, _second_rx) = register(&state, false).await;
+        join(&state, first, &room).await;
+        join(&state, second, &room).await;
+
+        match first_rx.try_recv().unwrap() {
+            ServerMessage::PresenceChanged { count, change, connection_id, .. } => {
+                assert_eq!((count, change, connection_id), (2, PresenceChange::Joined, None));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        state.leave_all_rooms(second).await;
+        match first_rx.try_recv().unwrap() {
+            ServerMessage::PresenceChanged { count, change, .. } => {
+                assert_eq!((count, change), (1, PresenceChange::Left));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_eviction_clears_room_membership() {
+        let state = WebSocketState::new(WebSocketConfig {
+            connection_timeout_secs: 0,
+            ..Default::default()
+        });
+        let room = RoomId::Dag("d-2".to_string());
+        let (id, _rx) = register(&state, false).await;
+        join(&state, id, &room).await;
+        assert_eq!(state.get_stats().await.active_rooms, 1);
+
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        assert_eq!(state.evict_stale_connections().await, 1);
+
+        let stats = state.get_stats().await;
+        assert_eq!(stats.active_connections, 0);
+        assert_eq!(stats.total_disconnections, 1);
+        assert_eq!(stats.active_rooms, 0);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_reach_subscribed_connections() {
+        let state = WebSocketState::with_defaults();
+        let (subscribed, mut subscribed_rx) = register(&state, false).await;
+        let (_other, mut other_rx) = register(&state, false).await;
+        join(&state, subscribed, &RoomId::Metrics).await;
+        while subscribed_rx.try_recv().is_ok() {}
+
+        let snapshot: MetricsSnapshot = serde_json::from_value(serde_json::json!({
+            "timestamp": chrono::Utc::now(),
+            "agents": { "total": 3, "active": 1, "idle": 2, "errored": 0, "avg_success_rate": 1.0, "total_tasks_completed": 0 },
+            "tasks": { "queued": 0, "running": 1, "completed_last_hour": 0, "failed_last_hour": 0, "avg_duration_ms": 0.0, "p99_duration_ms": 0.0 },
+            "resources": { "total_tokens_used": 0, "total_cost_dollars": 0.0, "tokens_last_hour": 0, "cost_last_hour": 0.0, "budget_remaining": null },
+            "system": { "cpu_usage_percent": 0.0, "memory_usage_percent": 0.0, "active_connections": 2, "db_pool_available": 0, "cache_hit_rate": 0.0, "uptime_seconds": 0 },
+        })).unwrap();
+        state.broadcast_metrics(snapshot).await;
+
+        assert!(matches!(subscribed_rx.try_recv(), Ok(ServerMessage::Metrics(m)) if m.agents.total == 3));
+        assert!(other_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_quota_warning_reaches_only_its_organization() {
+        let state = WebSocketState::with_defaults();
+        let mut own = state.broadcaster.subscribe_to_room(RoomId::Organization("org-a".to_string())).await;
+        let mut other = state.broadcaster.subscribe_to_room(RoomId::Organization("org-b".to_string())).await;
+        let mut approvals = state.broadcaster.subscribe_to_room(RoomId::Approvals).await;
+
+        let now = chrono::Utc::now();
+        state.send_quota_warning(QuotaWarning {
+            organization_id: "org-a".to_string(),
+            resource: crate::contracts::QuotaResource::Tasks,
+            used: 8.0,
+            limit: 10.0,
+            utilization: 0.8,
+            period_end: now,
+            timestamp: now,
+        }).await;
+
+        let broadcast = own.receiver.try_recv().unwrap();
+        assert!(matches!(broadcast.message, ServerMessage::QuotaWarning(w) if w.organization_id == "org-a"));
+        assert!(other.receiver.try_recv().is_err());
+        assert!(approvals.receiver.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_notifies_and_waits_for_connections() {
+        let state = Arc::new(WebSocketState::new(WebSocketConfig {
+            reconnection_backoff_ms: 2500,
+            shutdown_grace_secs: 5,
+            ..Default::default()
+        }));
+        let (conn, _rx) = register(&state, false).await;
+        let mut global = state.broadcaster.subscribe_global();
+
+        // Stand-in for a connection loop: close on the notice
+        let closer = {
+            let state = state.clone();
+            tokio::spawn(async move {
+                let notice = handler::shutdown_notice(global.recv().await);
+                state.handler.unregister_connection(conn).await;
+                notice
+            })
+        };
+
+        assert_eq!(state.shutdown().await, 0);
+        assert!(matches!(
+            closer.await.unwrap(),
+            Some(ServerMessage::ServerShutdown { reconnect_after_ms: 2500 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_gives_up_after_grace_period() {
+        let state = WebSocketState::new(WebSocketConfig {
+            shutdown_grace_secs: 0,
+            ..Default::default()
+        });
+        let _conn = register(&state, false).await;
+        assert_eq!(state.shutdown().await, 1);
+    }
+
     #[tokio::test]
     async fn test_websocket_state_creation() {
         let state = WebSocketState::with_defaults();
@@ -0,0 +1,237 @@
+//! Server-side guard against reconnection storms.
+//!
+//! A client that reconnects with its previous `session_id` is expected to
+//! back off exponentially (see the contract in the [module docs](super)).
+//! [`ReconnectGuard`] enforces that: a session that comes back sooner than
+//! its current backoff is refused, and each refusal doubles the delay it has
+//! to wait, up to `reconnection_backoff_ms * 2^max_reconnection_attempts`.
+//! A session that waits out its delay is admitted and starts over at the
+//! base delay.
+//!
+//! Only sessions the server issued are held to that backoff, since a client
+//! can make up any `session_id`. Everything else is limited per client IP by
+//! [`ConnectLimiter`], which admits a burst (a second tab, several users
+//! behind one NAT) and then one connection per base delay.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Tracks recent reconnects per session and how long each must back off.
+pub struct ReconnectGuard {
+    base: Duration,
+    max_exponent: u32,
+    sessions: Mutex<HashMap<String, ReconnectState>>,
+}
+
+struct ReconnectState {
+    last_attempt: Instant,
+    /// Refusals since the session was last admitted
+    strikes: u32,
+}
+
+impl ReconnectGuard {
+    pub fn new(base: Duration, max_exponent: u32) -> Self {
+        Self {
+            base,
+            max_exponent,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Start tracking a session the server issued, as of now. A session
+    /// already tracked keeps its state.
+    pub fn track(&self, session_id: &str) {
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(session_id.to_string())
+            .or_insert(ReconnectState { last_attempt: Instant::now(), strikes: 0 });
+    }
+
+    /// Whether `session_id` is tracked.
+    pub fn is_tracked(&self, session_id: &str) -> bool {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner()).contains_key(session_id)
+    }
+
+    /// Record a reconnect for `session_id`.
+    ///
+    /// Returns how long the client must wait before trying again when the
+    /// reconnect comes too soon after the previous one.
+    pub fn check(&self, session_id: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut sessions = self.sessions.lock().unwrap_or_else(|e| e.into_inner());
+
+        let Some(state) = sessions.get_mut(session_id) else {
+            sessions.insert(
+                session_id.to_string(),
+                ReconnectState { last_attempt: now, strikes: 0 },
+            );
+            return Ok(());
+        };
+
+        if now.duration_since(state.last_attempt) < self.delay(state.strikes) {
+            state.strikes = (state.strikes + 1).min(self.max_exponent);
+            state.last_attempt = now;
+            return Err(self.delay(state.strikes));
+        }
+
+        state.strikes = 0;
+        state.last_attempt = now;
+        Ok(())
+    }
+
+    /// Forget sessions whose longest possible backoff has passed.
+    pub fn prune(&self) {
+        let horizon = self.delay(self.max_exponent);
+        self.sessions
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, state| state.last_attempt.elapsed() < horizon);
+    }
+
+    /// Sessions currently tracked.
+    pub fn tracked(&self) -> usize {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Required gap between reconnects after `strikes` refusals.
+    fn delay(&self, strikes: u32) -> Duration {
+        self.base.saturating_mul(1 << strikes.min(self.max_exponent).min(16))
+    }
+}
+
+/// Per-IP token bucket for connections that don't resume a tracked session.
+pub struct ConnectLimiter {
+    /// Connections admitted at once (0 = unlimited)
+    burst: u32,
+    /// Time to earn back one connection
+    refill: Duration,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl ConnectLimiter {
+    pub fn new(burst: u32, refill: Duration) -> Self {
+        Self {
+            burst,
+            refill,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a connection from `ip`'s bucket, or return how long until one
+    /// is available.
+    pub fn check(&self, ip: IpAddr) -> Result<(), Duration> {
+        if self.burst == 0 || self.refill.is_zero() {
+            return Ok(());
+        }
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = buckets
+            .entry(ip)
+            .or_insert(Bucket { tokens: self.burst as f64, updated: now });
+
+        let earned = now.duration_since(bucket.updated).as_secs_f64() / self.refill.as_secs_f64();
+        bucket.tokens = (bucket.tokens + earned).min(self.burst as f64);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(self.refill.mul_f64(1.0 - bucket.tokens))
+    }
+
+    /// Forget IPs whose bucket has filled up again.
+    pub fn prune(&self) {
+        let full = self.refill.saturating_mul(self.burst);
+        self.buckets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, bucket| bucket.updated.elapsed() < full);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rapid_reconnects_back_off_exponentially() {
+        let guard = ReconnectGuard::new(Duration::from_millis(100), 3);
+
+        assert!(guard.check("s-1").is_ok());
+        assert_eq!(guard.check("s-1"), Err(Duration::from_millis(200)));
+        assert_eq!(guard.check("s-1"), Err(Duration::from_millis(400)));
+        assert_eq!(guard.check("s-1"), Err(Duration::from_millis(800)));
+        // Capped at base * 2^max
+        assert_eq!(guard.check("s-1"), Err(Duration::from_millis(800)));
+
+        // Other sessions are unaffected
+        assert!(guard.check("s-2").is_ok());
+    }
+
+    #[test]
+    fn test_waiting_out_the_delay_resets_backoff() {
+        let guard = ReconnectGuard::new(Duration::from_millis(20), 5);
+
+        assert!(guard.check("s-1").is_ok());
+        let wait = guard.check("s-1").unwrap_err();
+        std::thread::sleep(wait);
+        assert!(guard.check("s-1").is_ok());
+        // Back at the base delay
+        assert_eq!(guard.check("s-1"), Err(Duration::from_millis(40)));
+    }
+
+    #[test]
+    fn test_prune_drops_quiet_sessions() {
+        let guard = ReconnectGuard::new(Duration::from_millis(5), 1);
+        guard.check("s-1").unwrap();
+        assert_eq!(guard.tracked(), 1);
+
+        std::thread::sleep(Duration::from_millis(15));
+        guard.prune();
+        assert_eq!(guard.tracked(), 0);
+    }
+
+    #[test]
+    fn test_track_keeps_existing_state() {
+        let guard = ReconnectGuard::new(Duration::from_millis(100), 3);
+        assert!(!guard.is_tracked("s-1"));
+        guard.track("s-1");
+        assert!(guard.is_tracked("s-1"));
+        assert_eq!(guard.check("s-1"), Err(Duration::from_millis(200)));
+
+        guard.track("s-1");
+        assert_eq!(guard.check("s-1"), Err(Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn test_connect_limiter_admits_a_burst_then_refills() {
+        let limiter = ConnectLimiter::new(3, Duration::from_millis(50));
+        let ip: IpAddr = "203.0.113.7".parse().unwrap();
+
+        for _ in 0..3 {
+            assert!(limiter.check(ip).is_ok());
+        }
+        let wait = limiter.check(ip).unwrap_err();
+        assert!(wait > Duration::ZERO && wait <= Duration::from_millis(50));
+        // Other IPs have their own bucket
+        assert!(limiter.check("203.0.113.8".parse().unwrap()).is_ok());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(limiter.check(ip).is_ok());
+        assert!(limiter.check(ip).is_err());
+
+        // 0 = unlimited
+        let unlimited = ConnectLimiter::new(0, Duration::from_millis(50));
+        for _ in 0..100 {
+            assert!(unlimited.check(ip).is_ok());
+        }
+    }
+}
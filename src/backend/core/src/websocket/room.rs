@@ -30,6 +30,8 @@ pub enum RoomId {
     Approvals,
     /// Room for error notifications
     Errors,
+    /// Room for notices addressed to one organization, such as quota warnings
+    Organization(String),
     /// Custom room with arbitrary name
     Custom(String),
 }
@@ -47,6 +49,7 @@ impl RoomId {
             RoomId::Metrics => "metrics".to_string(),
             RoomId::Approvals => "approvals".to_string(),
             RoomId::Errors => "errors".to_string(),
+            RoomId::Organization(id) => format!("org:{}", id),
             RoomId::Custom(name) => format!("custom:{}", name),
         }
     }
@@ -60,6 +63,7 @@ impl RoomId {
             RoomId::Metrics => RoomType::Metrics,
             RoomId::Approvals => RoomType::Approval,
             RoomId::Errors => RoomType::Error,
+            RoomId::Organization(_) => RoomType::Organization,
             RoomId::Custom(_) => RoomType::Custom,
         }
     }
@@ -75,6 +79,7 @@ pub enum RoomType {
     Metrics,
     Approval,
     Error,
+    Organization,
     Custom,
 }
 
@@ -290,6 +290,8 @@ pub fn strings_to_room_ids(strings: &[String]) -> Vec<RoomId> {
                 RoomId::Approvals
             } else if s == "errors" {
                 RoomId::Errors
+            } else if let Some(id) = s.strip_prefix("org:") {
+                RoomId::Organization(id.to_string())
             } else if let Some(name) = s.strip_prefix("custom:") {
                 RoomId::Custom(name.to_string())
             } else {
@@ -315,6 +317,7 @@ mod tests {
             RoomId::Metrics,
             RoomId::Approvals,
             RoomId::Errors,
+            RoomId::Organization("org-1".to_string()),
             RoomId::Custom("my-room".to_string()),
         ];
 
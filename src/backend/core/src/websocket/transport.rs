@@ -0,0 +1,173 @@
+//! Carrying broadcasts between API replicas.
+//!
+//! A [`Broadcaster`](super::Broadcaster) delivers to subscribers in its own
+//! process. Behind a load balancer the client watching a task may be
+//! connected to a different replica than the one producing its updates, so
+//! room broadcasts are also handed to a [`BroadcastTransport`], which brings
+//! them to the broadcaster of every replica:
+//!
+//! - [`InProcessTransport`] (default): a single replica, nothing leaves the
+//!   process.
+//! - [`RedisTransport`]: Redis pub/sub on [`REDIS_CHANNEL`].
+//!
+//! Only untargeted room broadcasts travel. Global notices (shutdown) and
+//! targeted sends concern this replica's own connections, and metrics
+//! snapshots describe the replica that took them.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{mpsc, OnceCell};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::message::ServerMessage;
+use super::room::RoomId;
+use crate::error::Result;
+
+/// Pub/sub channel [`RedisTransport`] publishes on.
+pub const REDIS_CHANNEL: &str = "apex:ws:broadcast";
+
+/// Received broadcasts waiting for the local broadcaster.
+const RELAY_BUFFER: usize = 1024;
+
+/// Which [`BroadcastTransport`] the server uses, set with `server.broadcast`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BroadcastTransportKind {
+    /// This process only ([`InProcessTransport`])
+    #[default]
+    InProcess,
+    /// Every replica, over Redis pub/sub ([`RedisTransport`])
+    Redis,
+}
+
+/// A room broadcast on its way between replicas.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteBroadcast {
+    /// Broadcaster that sent it, so it can skip its own
+    pub origin: Uuid,
+    pub room_id: RoomId,
+    pub message: ServerMessage,
+}
+
+/// Delivers room broadcasts to the broadcasters of all replicas.
+#[async_trait]
+pub trait BroadcastTransport: Send + Sync {
+    /// Send `broadcast` to every subscribed replica.
+    async fn publish(&self, broadcast: &RemoteBroadcast) -> Result<()>;
+
+    /// Broadcasts published by any replica, possibly including this one.
+    /// The channel closes when the transport stops delivering.
+    fn subscribe(&self) -> mpsc::Receiver<RemoteBroadcast>;
+}
+
+/// No other replicas: publishing is a no-op and nothing is ever received.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InProcessTransport;
+
+#[async_trait]
+impl BroadcastTransport for InProcessTransport {
+    async fn publish(&self, _broadcast: &RemoteBroadcast) -> Result<()> {
+        Ok(())
+    }
+
+    fn subscribe(&self) -> mpsc::Receiver<RemoteBroadcast> {
+        mpsc::channel(1).1
+    }
+}
+
+/// Redis pub/sub between replicas sharing one Redis.
+///
+/// Delivery is at most once: a replica that is disconnected from Redis
+/// misses what is published meanwhile. The subscriber reconnects with a
+/// doubling backoff up to 30 seconds.
+pub struct RedisTransport {
+    client: redis::Client,
+    channel: String,
+    publisher: OnceCell<redis::aio::ConnectionManager>,
+}
+
+impl RedisTransport {
+    pub fn new(client: redis::Client) -> Self {
+        Self {
+            client,
+            channel: REDIS_CHANNEL.to_string(),
+            publisher: OnceCell::new(),
+        }
+    }
+
+    /// Publish and subscribe on `channel` instead of [`REDIS_CHANNEL`], e.g.
+    /// to keep two deployments on one Redis apart.
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = channel.into();
+        self
+    }
+
+    /// Forward messages on the channel to `tx` until `tx` closes or the
+    /// subscription drops.
+    async fn relay(client: &redis::Client, channel: &str, tx: &mpsc::Sender<RemoteBroadcast>) -> redis::RedisResult<()> {
+        let mut pubsub = client.get_async_connection().await?.into_pubsub();
+        pubsub.subscribe(channel).await?;
+        let mut messages = pubsub.into_on_message();
+        while let Some(msg) = messages.next().await {
+            let payload: String = match msg.get_payload() {
+                Ok(payload) => payload,
+                Err(e) => {
+                    warn!(error = %e, "Ignoring non-text broadcast payload");
+                    continue;
+                }
+            };
+            match serde_json::from_str::<RemoteBroadcast>(&payload) {
+                Ok(broadcast) => {
+                    if tx.send(broadcast).await.is_err() {
+                        return Ok(());
+                    }
+                }
+                Err(e) => warn!(error = %e, "Ignoring malformed broadcast"),
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BroadcastTransport for RedisTransport {
+    async fn publish(&self, broadcast: &RemoteBroadcast) -> Result<()> {
+        let mut conn = self
+            .publisher
+            .get_or_try_init(|| redis::aio::ConnectionManager::new(self.client.clone()))
+            .await?
+            .clone();
+        let payload = serde_json::to_string(broadcast)?;
+        redis::cmd("PUBLISH")
+            .arg(&self.channel)
+            .arg(payload)
+            .query_async::<_, i64>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    fn subscribe(&self) -> mpsc::Receiver<RemoteBroadcast> {
+        let (tx, rx) = mpsc::channel(RELAY_BUFFER);
+        let client = self.client.clone();
+        let channel = self.channel.clone();
+        tokio::spawn(async move {
+            let mut backoff = Duration::from_secs(1);
+            while !tx.is_closed() {
+                match Self::relay(&client, &channel, &tx).await {
+                    Ok(()) => {
+                        warn!(channel = %channel, "Broadcast subscription ended, resubscribing");
+                        backoff = Duration::from_secs(1);
+                    }
+                    Err(e) => warn!(channel = %channel, error = %e, "Broadcast subscription failed, retrying"),
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(Duration::from_secs(30));
+            }
+        });
+        rx
+    }
+}
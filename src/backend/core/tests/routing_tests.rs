@@ -519,6 +519,8 @@ fn test_cascade_result_structure() {
         total_tokens: 5000,
         response: "Test response".to_string(),
         confidence: 0.85,
+        evaluation_cost: 0.0,
+        evaluation_tokens: 0,
     };
 
     assert_eq!(result.model, "gpt-4o");
@@ -538,6 +540,8 @@ fn test_cascade_result_clone() {
         total_tokens: 10000,
         response: "Cloned response".to_string(),
         confidence: 0.90,
+        evaluation_cost: 0.0,
+        evaluation_tokens: 0,
     };
 
     let cloned = result.clone();
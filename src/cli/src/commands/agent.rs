@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 use uuid::Uuid;
 
-use crate::client::ApiClient;
+use apex_client::ApexClient;
 use crate::output::{self, OutputFormat};
 
 #[derive(Subcommand)]
@@ -28,6 +28,10 @@ pub enum AgentCommands {
     Inspect {
         /// Agent ID
         agent_id: Uuid,
+
+        /// Include a 7-day performance summary
+        #[arg(long)]
+        history: bool,
     },
 
     /// Stop (remove) an agent
@@ -53,6 +57,12 @@ pub enum AgentCommands {
 
 // ── API response types ──────────────────────────────────────────────────────
 
+/// One page of `GET /api/v1/agents`.
+#[derive(Debug, Deserialize)]
+struct AgentPage {
+    items: Vec<AgentInfo>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct AgentInfo {
     id: Uuid,
@@ -133,6 +143,30 @@ struct AgentStats {
     reputation_score: f64,
 }
 
+/// `GET /api/v1/agents/:id/history`; snapshots are left as raw JSON.
+#[derive(Debug, Deserialize, Serialize)]
+struct AgentHistory {
+    #[serde(default)]
+    snapshots: Vec<serde_json::Value>,
+    summary: HistorySummary,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct HistorySummary {
+    #[serde(default)]
+    tasks_completed: u64,
+    #[serde(default)]
+    tasks_failed: u64,
+    #[serde(default)]
+    success_rate: Option<f64>,
+    #[serde(default)]
+    avg_duration_ms: Option<f64>,
+    #[serde(default)]
+    tokens_used: u64,
+    #[serde(default)]
+    cost_dollars: f64,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 struct RemoveResponse {
     id: Uuid,
@@ -141,7 +175,7 @@ struct RemoveResponse {
 
 // ── Execution ───────────────────────────────────────────────────────────────
 
-pub async fn execute(cmd: AgentCommands, client: &ApiClient, format: OutputFormat) -> Result<()> {
+pub async fn execute(cmd: AgentCommands, client: &ApexClient, format: OutputFormat) -> Result<()> {
     match cmd {
         AgentCommands::List { swarm, limit } => {
             let path = match &swarm {
@@ -149,9 +183,10 @@ pub async fn execute(cmd: AgentCommands, client: &ApiClient, format: OutputForma
                 None => format!("/api/v1/agents?limit={}", limit),
             };
 
-            let agents: Vec<AgentInfo> = client.get(&path).await?;
+            let page: AgentPage = client.get(&path).await?;
 
-            let rows: Vec<AgentRow> = agents
+            let rows: Vec<AgentRow> = page
+                .items
                 .into_iter()
                 .map(|a| AgentRow {
                     id: a.id.to_string()[..8].to_string(),
@@ -167,9 +202,14 @@ pub async fn execute(cmd: AgentCommands, client: &ApiClient, format: OutputForma
             output::print_list(&rows, format);
         }
 
-        AgentCommands::Inspect { agent_id } => {
+        AgentCommands::Inspect { agent_id, history } => {
             let agent: AgentDetail =
                 client.get(&format!("/api/v1/agents/{}", agent_id)).await?;
+            let history: Option<AgentHistory> = if history {
+                Some(client.get(&format!("/api/v1/agents/{}/history", agent_id)).await?)
+            } else {
+                None
+            };
 
             match format {
                 OutputFormat::Table => {
@@ -195,8 +235,35 @@ pub async fn execute(cmd: AgentCommands, client: &ApiClient, format: OutputForma
                     if let Some(active) = &agent.last_active_at {
                         output::print_detail("Last Active", active);
                     }
+                    if let Some(history) = &history {
+                        let s = &history.summary;
+                        output::print_header("Last 7 Days");
+                        output::print_detail("Snapshots", &history.snapshots.len().to_string());
+                        output::print_detail("Completed", &s.tasks_completed.to_string());
+                        output::print_detail("Failed", &s.tasks_failed.to_string());
+                        output::print_detail(
+                            "Success Rate",
+                            &s.success_rate
+                                .map(|r| format!("{:.1}%", r * 100.0))
+                                .unwrap_or_else(|| "-".to_string()),
+                        );
+                        output::print_detail(
+                            "Avg Duration",
+                            &s.avg_duration_ms
+                                .map(|ms| format!("{:.1}ms", ms))
+                                .unwrap_or_else(|| "-".to_string()),
+                        );
+                        output::print_detail("Tokens", &s.tokens_used.to_string());
+                        output::print_detail("Cost", &format!("${:.4}", s.cost_dollars));
+                    }
                 }
-                _ => output::print_item(&agent, format),
+                _ => match history {
+                    Some(history) => output::print_item(
+                        &serde_json::json!({ "agent": agent, "history": history }),
+                        format,
+                    ),
+                    None => output::print_item(&agent, format),
+                },
             }
         }
 
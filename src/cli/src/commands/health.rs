@@ -5,7 +5,7 @@
 use anyhow::Result;
 use clap::Args;
 
-use crate::client::ApiClient;
+use apex_client::ApexClient;
 use crate::output::{self, OutputFormat};
 
 #[derive(Args)]
@@ -15,7 +15,7 @@ pub struct HealthArgs {
     detailed: bool,
 }
 
-pub async fn execute(args: HealthArgs, client: &ApiClient, format: OutputFormat) -> Result<()> {
+pub async fn execute(args: HealthArgs, client: &ApexClient, format: OutputFormat) -> Result<()> {
     let health: serde_json::Value = client.get_raw("/health").await?;
 
     match format {
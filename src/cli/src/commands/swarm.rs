@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 use uuid::Uuid;
 
-use crate::client::ApiClient;
+use apex_client::ApexClient;
 use crate::output::{self, OutputFormat};
 
 #[derive(Subcommand)]
@@ -108,7 +108,7 @@ struct DeleteResponse {
 
 // ── Execution ───────────────────────────────────────────────────────────────
 
-pub async fn execute(cmd: SwarmCommands, client: &ApiClient, format: OutputFormat) -> Result<()> {
+pub async fn execute(cmd: SwarmCommands, client: &ApexClient, format: OutputFormat) -> Result<()> {
     match cmd {
         SwarmCommands::Create {
             name,
@@ -8,7 +8,7 @@ use serde::{Deserialize, Serialize};
 use tabled::Tabled;
 use uuid::Uuid;
 
-use crate::client::ApiClient;
+use apex_client::ApexClient;
 use crate::output::{self, OutputFormat};
 
 #[derive(Subcommand)]
@@ -149,7 +149,7 @@ struct CancelResponse {
 
 // ── Execution ───────────────────────────────────────────────────────────────
 
-pub async fn execute(cmd: TaskCommands, client: &ApiClient, format: OutputFormat) -> Result<()> {
+pub async fn execute(cmd: TaskCommands, client: &ApexClient, format: OutputFormat) -> Result<()> {
     match cmd {
         TaskCommands::Submit {
             dag,
@@ -2,12 +2,13 @@
 //!
 //! Provides commands for swarm, agent, task, health, and configuration management.
 
-mod client;
 mod commands;
 mod output;
 
 use anyhow::Result;
-use clap::{Parser, Subcommand};
+use apex_client::ApexClient;
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 
 use commands::{agent, config, health, swarm, task};
 use output::OutputFormat;
@@ -59,12 +60,29 @@ enum Commands {
     /// Configuration management
     #[command(subcommand)]
     Config(config::ConfigCommands),
+
+    /// Print a shell completion script to stdout
+    #[command(hide = true)]
+    Completions {
+        /// Target shell
+        shell: Shell,
+    },
+}
+
+/// Write the completion script for `shell` to `out`.
+fn write_completions(shell: Shell, out: &mut dyn std::io::Write) {
+    clap_complete::generate(shell, &mut Cli::command(), "apex", out);
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
 
+    if let Commands::Completions { shell } = cli.command {
+        write_completions(shell, &mut std::io::stdout());
+        return Ok(());
+    }
+
     if cli.no_color {
         colored::control::set_override(false);
     }
@@ -75,7 +93,7 @@ async fn main() -> Result<()> {
         .or_else(|| config::load_api_url())
         .unwrap_or_else(|| "http://localhost:8080".to_string());
 
-    let client = client::ApiClient::new(&api_url)?;
+    let client = ApexClient::new(&api_url)?;
     let format = cli.output;
 
     let result = match cli.command {
@@ -84,6 +102,7 @@ async fn main() -> Result<()> {
         Commands::Task(cmd) => task::execute(cmd, &client, format).await,
         Commands::Health(args) => health::execute(args, &client, format).await,
         Commands::Config(cmd) => config::execute(cmd, format).await,
+        Commands::Completions { .. } => unreachable!("handled before the client is built"),
     };
 
     if let Err(e) = result {
@@ -93,3 +112,18 @@ async fn main() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions_for_each_shell() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            write_completions(shell, &mut script);
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains("apex"), "{:?} completions missing command name", shell);
+        }
+    }
+}
@@ -0,0 +1,448 @@
+//! HTTP client for the Apex REST API.
+
+use std::time::Duration;
+
+use apex_core::api::dto::{
+    AgentSummary, CreateDagRequest, CreateTaskRequest, DagDetail, DagExecutionResponse, DagResponse,
+    Page, TaskResponse, IDEMPOTENCY_KEY_HEADER,
+};
+use apex_core::api::v1::routes::paths;
+use apex_core::api::ApiResponse;
+use apex_core::websocket::SubscriptionTarget;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::error::ClientError;
+use crate::subscription::Subscription;
+
+const DEFAULT_BASE_URL: &str = "http://localhost:8080";
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Builder for [`ApexClient`].
+#[derive(Debug, Clone)]
+pub struct ApexClientBuilder {
+    base_url: String,
+    auth_token: Option<String>,
+    timeout: Duration,
+    connect_timeout: Option<Duration>,
+}
+
+impl Default for ApexClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: DEFAULT_BASE_URL.to_string(),
+            auth_token: None,
+            timeout: DEFAULT_TIMEOUT,
+            connect_timeout: None,
+        }
+    }
+}
+
+impl ApexClientBuilder {
+    /// Server address, e.g. `https://apex.example.com`. Defaults to `http://localhost:8080`.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Token sent as `Authorization: Bearer <token>` and on WebSocket connect.
+    pub fn auth_token(mut self, token: impl Into<String>) -> Self {
+        self.auth_token = Some(token.into());
+        self
+    }
+
+    /// Total time allowed per request. Defaults to 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Time allowed to establish a connection, HTTP or WebSocket.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> Result<ApexClient, ClientError> {
+        let base_url = self.base_url.trim_end_matches('/').to_string();
+        let parsed = reqwest::Url::parse(&base_url)
+            .map_err(|e| ClientError::Config(format!("Invalid base URL '{}': {}", base_url, e)))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(ClientError::Config(format!(
+                "Base URL must be http or https, got '{}'",
+                base_url
+            )));
+        }
+
+        let mut http = Client::builder().timeout(self.timeout);
+        if let Some(connect_timeout) = self.connect_timeout {
+            http = http.connect_timeout(connect_timeout);
+        }
+        let http = http
+            .build()
+            .map_err(|e| ClientError::Config(format!("Failed to create HTTP client: {}", e)))?;
+
+        Ok(ApexClient {
+            http,
+            base_url,
+            auth_token: self.auth_token,
+            connect_timeout: self.connect_timeout.unwrap_or(self.timeout),
+        })
+    }
+}
+
+/// Client for the Apex API.
+///
+/// Cheap to clone; clones share the connection pool.
+#[derive(Debug, Clone)]
+pub struct ApexClient {
+    http: Client,
+    base_url: String,
+    auth_token: Option<String>,
+    connect_timeout: Duration,
+}
+
+impl ApexClient {
+    pub fn builder() -> ApexClientBuilder {
+        ApexClientBuilder::default()
+    }
+
+    /// Client for `base_url` with default settings and no auth token.
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Self::builder().base_url(base_url).build()
+    }
+
+    /// Return the configured base URL.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // Tasks
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    pub async fn create_task(&self, request: &CreateTaskRequest) -> Result<TaskResponse, ClientError> {
+        self.post(paths::TASKS, request).await
+    }
+
+    pub async fn get_task(&self, id: Uuid) -> Result<TaskResponse, ClientError> {
+        self.get(&with_id(paths::TASK, id)).await
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // DAGs
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    pub async fn create_dag(&self, request: &CreateDagRequest) -> Result<DagResponse, ClientError> {
+        self.post(paths::DAGS, request).await
+    }
+
+    /// Like [`create_dag`](Self::create_dag), but safe to retry: every call
+    /// with the same `key` returns the DAG created by the first one, with
+    /// `existing` set on the repeats.
+    pub async fn create_dag_idempotent(
+        &self,
+        request: &CreateDagRequest,
+        key: &str,
+    ) -> Result<DagResponse, ClientError> {
+        let url = self.url(paths::DAGS);
+        let builder = self.request(Method::POST, &url).header(IDEMPOTENCY_KEY_HEADER, key).json(request);
+        let resp = self.send("POST", &url, builder).await?;
+        unwrap_response(&url, resp).await
+    }
+
+    pub async fn get_dag(&self, id: Uuid) -> Result<DagDetail, ClientError> {
+        self.get(&with_id(paths::DAG, id)).await
+    }
+
+    /// Run a DAG to completion. The request lasts as long as the DAG does, so
+    /// long-running DAGs need a matching [`ApexClientBuilder::timeout`].
+    pub async fn execute_dag(&self, id: Uuid) -> Result<DagExecutionResponse, ClientError> {
+        self.post(&with_id(paths::DAG_EXECUTE, id), &serde_json::json!({})).await
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // Agents
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// One page of agents, newest first. Pass the previous page's
+    /// `page_info.end_cursor` as `after` to continue.
+    pub async fn list_agents(
+        &self,
+        after: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Page<AgentSummary>, ClientError> {
+        let mut query = Vec::new();
+        if let Some(after) = after {
+            query.push(("after", after.to_string()));
+        }
+        if let Some(limit) = limit {
+            query.push(("limit", limit.to_string()));
+        }
+        let url = self.url(paths::AGENTS);
+        let resp = self.send("GET", &url, self.request(Method::GET, &url).query(&query)).await?;
+        unwrap_response(&url, resp).await
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // WebSocket
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Open a `/ws` connection and subscribe to `targets`.
+    pub async fn subscribe(&self, targets: Vec<SubscriptionTarget>) -> Result<Subscription, ClientError> {
+        let mut url = reqwest::Url::parse(&self.url("/ws"))
+            .map_err(|e| ClientError::Config(format!("Invalid WebSocket URL: {}", e)))?;
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        url.set_scheme(scheme)
+            .map_err(|_| ClientError::Config("Invalid WebSocket URL".to_string()))?;
+        if let Some(ref token) = self.auth_token {
+            url.query_pairs_mut().append_pair("token", token);
+        }
+
+        Subscription::connect(url.as_str(), targets, self.connect_timeout).await
+    }
+
+    // ═══════════════════════════════════════════════════════════════════════════
+    // Raw requests
+    // ═══════════════════════════════════════════════════════════════════════════
+
+    /// Perform a GET request and deserialize the response data.
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        let url = self.url(path);
+        let resp = self.send("GET", &url, self.request(Method::GET, &url)).await?;
+        unwrap_response(&url, resp).await
+    }
+
+    /// Perform a POST request with a JSON body and deserialize the response data.
+    pub async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> Result<T, ClientError> {
+        let url = self.url(path);
+        let resp = self.send("POST", &url, self.request(Method::POST, &url).json(body)).await?;
+        unwrap_response(&url, resp).await
+    }
+
+    /// Perform a DELETE request and deserialize the response data.
+    pub async fn delete<T: DeserializeOwned>(&self, path: &str) -> Result<T, ClientError> {
+        let url = self.url(path);
+        let resp = self.send("DELETE", &url, self.request(Method::DELETE, &url)).await?;
+        unwrap_response(&url, resp).await
+    }
+
+    /// Perform a GET request and return the full JSON body, for endpoints
+    /// like `/health` that don't use the `ApiResponse` envelope.
+    pub async fn get_raw(&self, path: &str) -> Result<serde_json::Value, ClientError> {
+        let url = self.url(path);
+        let resp = self.send("GET", &url, self.request(Method::GET, &url)).await?;
+        resp.json()
+            .await
+            .map_err(|source| ClientError::Decode { url, source })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let builder = self.http.request(method, url);
+        match self.auth_token {
+            Some(ref token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+
+    /// Send `request` and turn non-2xx statuses into [`ClientError::Status`].
+    async fn send(
+        &self,
+        method: &'static str,
+        url: &str,
+        request: RequestBuilder,
+    ) -> Result<Response, ClientError> {
+        let resp = request.send().await.map_err(|source| ClientError::Http {
+            method,
+            url: url.to_string(),
+            source,
+        })?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return Err(ClientError::Status { status, body });
+        }
+        Ok(resp)
+    }
+}
+
+/// Decode an `ApiResponse<T>` envelope into its data.
+async fn unwrap_response<T: DeserializeOwned>(url: &str, resp: Response) -> Result<T, ClientError> {
+    let api_resp: ApiResponse<T> = resp.json().await.map_err(|source| ClientError::Decode {
+        url: url.to_string(),
+        source,
+    })?;
+
+    if api_resp.success {
+        api_resp.data.ok_or_else(|| ClientError::Api {
+            message: "API returned success but no data".to_string(),
+            code: None,
+        })
+    } else {
+        Err(ClientError::Api {
+            message: api_resp.error.unwrap_or_else(|| "Unknown error".into()),
+            code: api_resp.error_code,
+        })
+    }
+}
+
+fn with_id(path: &str, id: Uuid) -> String {
+    path.replace(":id", &id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Path, Query};
+    use axum::routing::{get, post};
+    use axum::{Json, Router};
+    use std::collections::HashMap;
+
+    async fn serve(router: Router) -> ApexClient {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        ApexClient::builder()
+            .base_url(format!("http://{}/", addr))
+            .auth_token("secret")
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_builder_rejects_bad_base_url() {
+        assert!(matches!(
+            ApexClient::builder().base_url("not a url").build(),
+            Err(ClientError::Config(_))
+        ));
+        assert!(matches!(
+            ApexClient::builder().base_url("ftp://apex.example.com").build(),
+            Err(ClientError::Config(_))
+        ));
+
+        let client = ApexClient::builder().base_url("https://apex.example.com/").build().unwrap();
+        assert_eq!(client.base_url(), "https://apex.example.com");
+    }
+
+    #[tokio::test]
+    async fn test_typed_calls_round_trip_server_dtos() {
+        let router = Router::new()
+            .route(
+                "/api/v1/tasks",
+                post(|headers: axum::http::HeaderMap, Json(req): Json<CreateTaskRequest>| async move {
+                    assert_eq!(headers["authorization"], "Bearer secret");
+                    Json(ApiResponse::success(TaskResponse {
+                        id: Uuid::nil(),
+                        name: req.name,
+                        status: "pending".to_string(),
+                        tokens_used: 0,
+                        cost_dollars: 0.0,
+                        created_at: "2024-01-01T00:00:00+00:00".to_string(),
+                        error: None,
+                        error_details: None,
+                    }))
+                }),
+            )
+            .route(
+                "/api/v1/dags/:id/execute",
+                post(|Path(id): Path<Uuid>| async move {
+                    Json(ApiResponse::success(DagExecutionResponse {
+                        dag_id: id,
+                        status: "Completed".to_string(),
+                        tasks_completed: 2,
+                        tasks_failed: 0,
+                        total_tokens: 120,
+                        total_cost: 0.01,
+                        duration_ms: 5,
+                    }))
+                }),
+            )
+            .route(
+                "/api/v1/agents",
+                get(|Query(query): Query<HashMap<String, String>>| async move {
+                    assert_eq!(query.get("limit").map(String::as_str), Some("1"));
+                    Json(ApiResponse::success(Page::<AgentSummary> {
+                        items: vec![],
+                        page_info: apex_core::pagination::CursorInfo::new(None, None, false, false),
+                    }))
+                }),
+            );
+        let client = serve(router).await;
+
+        let task = client
+            .create_task(&CreateTaskRequest {
+                name: "summarize".to_string(),
+                instruction: "Summarize".to_string(),
+                context: None,
+                priority: Some(5),
+                limits: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(task.name, "summarize");
+
+        let dag_id = Uuid::new_v4();
+        let result = client.execute_dag(dag_id).await.unwrap();
+        assert_eq!(result.dag_id, dag_id);
+        assert_eq!(result.tasks_completed, 2);
+
+        let page = client.list_agents(None, Some(1)).await.unwrap();
+        assert!(page.items.is_empty());
+        assert!(!page.page_info.has_next_page);
+    }
+
+    #[tokio::test]
+    async fn test_create_dag_idempotent_sends_key() {
+        let router = Router::new().route(
+            "/api/v1/dags",
+            post(|headers: axum::http::HeaderMap, Json(req): Json<CreateDagRequest>| async move {
+                Json(ApiResponse::success(DagResponse {
+                    id: Uuid::nil(),
+                    name: req.name,
+                    task_count: req.tasks.len(),
+                    status: "created".to_string(),
+                    existing: headers[IDEMPOTENCY_KEY_HEADER] == "retry-1",
+                }))
+            }),
+        );
+        let client = serve(router).await;
+
+        let spec: CreateDagRequest = serde_json::from_value(serde_json::json!({
+            "name": "nightly",
+            "tasks": [],
+        }))
+        .unwrap();
+        let dag = client.create_dag_idempotent(&spec, "retry-1").await.unwrap();
+        assert_eq!(dag.name, "nightly");
+        assert!(dag.existing);
+    }
+
+    #[tokio::test]
+    async fn test_error_envelope_and_status() {
+        let router = Router::new().route(
+            "/api/v1/dags/:id",
+            get(|| async {
+                Json(ApiResponse::<DagDetail>::error_with_code("DAG not found", "DAG_NOT_FOUND"))
+            }),
+        );
+        let client = serve(router).await;
+
+        let err = client.get_dag(Uuid::new_v4()).await.unwrap_err();
+        assert_eq!(err.code(), Some("DAG_NOT_FOUND"));
+        assert_eq!(err.to_string(), "API error: DAG not found");
+
+        let err = client.get_task(Uuid::new_v4()).await.unwrap_err();
+        assert!(matches!(err, ClientError::Status { status, .. } if status == 404));
+    }
+}
@@ -0,0 +1,55 @@
+//! Client errors.
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error("Invalid client configuration: {0}")]
+    Config(String),
+
+    #[error("{method} {url} failed: {source}")]
+    Http {
+        method: &'static str,
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// Non-2xx status from the server
+    #[error("API error ({status}): {body}")]
+    Status { status: reqwest::StatusCode, body: String },
+
+    /// The server answered `success: false`
+    #[error("API error: {message}")]
+    Api { message: String, code: Option<String> },
+
+    #[error("Failed to parse response from {url}: {source}")]
+    Decode {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    /// Boxed, as it is many times the size of the other variants
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[source] Box<tokio_tungstenite::tungstenite::Error>),
+
+    #[error("Invalid WebSocket message: {0}")]
+    Message(#[from] serde_json::Error),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for ClientError {
+    fn from(error: tokio_tungstenite::tungstenite::Error) -> Self {
+        ClientError::WebSocket(Box::new(error))
+    }
+}
+
+impl ClientError {
+    /// The server's error code (e.g. `TASK_NOT_FOUND`), if it sent one.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ClientError::Api { code, .. } => code.as_deref(),
+            _ => None,
+        }
+    }
+}
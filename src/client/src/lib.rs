@@ -0,0 +1,49 @@
+//! Typed Rust client for the Apex API.
+//!
+//! Requests and responses use the same structs as the server (from
+//! `apex_core::api::dto`, built with the `client` feature), so a field renamed
+//! on one side fails to compile on the other instead of failing at runtime.
+//!
+//! ```no_run
+//! use apex_client::{ApexClient, CreateTaskRequest, SubscriptionTarget};
+//! use futures::StreamExt;
+//! use std::time::Duration;
+//!
+//! # async fn run() -> Result<(), apex_client::ClientError> {
+//! let client = ApexClient::builder()
+//!     .base_url("https://apex.example.com")
+//!     .auth_token("apex_...")
+//!     .timeout(Duration::from_secs(10))
+//!     .build()?;
+//!
+//! let task = client
+//!     .create_task(&CreateTaskRequest {
+//!         name: "summarize".into(),
+//!         instruction: "Summarize the release notes".into(),
+//!         context: None,
+//!         priority: None,
+//!         limits: None,
+//!     })
+//!     .await?;
+//!
+//! let mut updates = client.subscribe(vec![SubscriptionTarget::Task { id: task.id.to_string() }]).await?;
+//! while let Some(message) = updates.next().await {
+//!     println!("{:?}", message?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod client;
+mod error;
+mod subscription;
+
+pub use client::{ApexClient, ApexClientBuilder};
+pub use error::ClientError;
+pub use subscription::Subscription;
+
+pub use apex_core::api::dto::*;
+pub use apex_core::api::ApiResponse;
+pub use apex_core::dag::{DagSpec, DependencySpec, TaskSpec};
+pub use apex_core::pagination::CursorInfo;
+pub use apex_core::websocket::{ClientMessage, ServerMessage, SubscriptionTarget};
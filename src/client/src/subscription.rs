@@ -0,0 +1,145 @@
+//! WebSocket subscriptions.
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use apex_core::websocket::{ClientMessage, ServerMessage, SubscriptionTarget};
+use futures::{SinkExt, Stream};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use crate::error::ClientError;
+
+/// A live `/ws` connection, yielding server messages as a [`Stream`].
+///
+/// Protocol-level ping/pong frames are handled for you; the stream ends when
+/// the server closes the connection.
+pub struct Subscription {
+    socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+}
+
+impl Subscription {
+    pub(crate) async fn connect(
+        url: &str,
+        targets: Vec<SubscriptionTarget>,
+        connect_timeout: Duration,
+    ) -> Result<Self, ClientError> {
+        let (socket, _) = tokio::time::timeout(connect_timeout, tokio_tungstenite::connect_async(url))
+            .await
+            .map_err(|_| {
+                tungstenite::Error::Io(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "timed out connecting to WebSocket",
+                ))
+            })??;
+
+        let mut subscription = Self { socket };
+        for target in targets {
+            subscription.subscribe(target).await?;
+        }
+        Ok(subscription)
+    }
+
+    /// Start receiving updates for another resource.
+    pub async fn subscribe(&mut self, target: SubscriptionTarget) -> Result<(), ClientError> {
+        self.send(&ClientMessage::Subscribe { target }).await
+    }
+
+    /// Stop receiving updates for a resource.
+    pub async fn unsubscribe(&mut self, target: SubscriptionTarget) -> Result<(), ClientError> {
+        self.send(&ClientMessage::Unsubscribe { target }).await
+    }
+
+    /// Send any client message, e.g. an approval response.
+    pub async fn send(&mut self, message: &ClientMessage) -> Result<(), ClientError> {
+        let text = serde_json::to_string(message)?;
+        self.socket.send(Message::Text(text)).await?;
+        Ok(())
+    }
+
+    /// Close the connection cleanly.
+    pub async fn close(mut self) -> Result<(), ClientError> {
+        self.socket.close(None).await?;
+        Ok(())
+    }
+}
+
+impl Stream for Subscription {
+    type Item = Result<ServerMessage, ClientError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            let frame = match Pin::new(&mut self.socket).poll_next(cx) {
+                Poll::Ready(Some(Ok(frame))) => frame,
+                Poll::Ready(Some(Err(tungstenite::Error::ConnectionClosed))) | Poll::Ready(None) => {
+                    return Poll::Ready(None)
+                }
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let parsed = match frame {
+                Message::Text(text) => serde_json::from_str(&text),
+                Message::Binary(bytes) => serde_json::from_slice(&bytes),
+                Message::Close(_) => return Poll::Ready(None),
+                Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => continue,
+            };
+            return Poll::Ready(Some(parsed.map_err(ClientError::from)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ApexClient;
+    use axum::extract::ws::{Message as WsMessage, WebSocketUpgrade};
+    use axum::extract::Query;
+    use axum::routing::get;
+    use axum::Router;
+    use futures::StreamExt;
+    use std::collections::HashMap;
+
+    /// Answers every `subscribe` with `subscribed`, after checking the token.
+    async fn ws(ws: WebSocketUpgrade, Query(query): Query<HashMap<String, String>>) -> axum::response::Response {
+        assert_eq!(query.get("token").map(String::as_str), Some("secret"));
+        ws.on_upgrade(|mut socket| async move {
+            while let Some(Ok(WsMessage::Text(text))) = socket.recv().await {
+                if let Ok(ClientMessage::Subscribe { target }) = serde_json::from_str(&text) {
+                    let reply = ServerMessage::Subscribed { target, current_state: None };
+                    let _ = socket.send(WsMessage::Ping(vec![])).await;
+                    let _ = socket.send(WsMessage::Text(serde_json::to_string(&reply).unwrap())).await;
+                }
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_yields_server_messages() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/ws", get(ws))).await.unwrap()
+        });
+
+        let client = ApexClient::builder()
+            .base_url(format!("http://{}", addr))
+            .auth_token("secret")
+            .build()
+            .unwrap();
+        let mut updates = client
+            .subscribe(vec![SubscriptionTarget::Dag { id: "dag-1".to_string() }])
+            .await
+            .unwrap();
+
+        match updates.next().await {
+            Some(Ok(ServerMessage::Subscribed { target: SubscriptionTarget::Dag { id }, .. })) => {
+                assert_eq!(id, "dag-1")
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+        updates.close().await.unwrap();
+    }
+}